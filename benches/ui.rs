@@ -0,0 +1,7 @@
+// Drawing timings; see src/bench.rs.
+
+fn main() {
+    for result in sharad_ratatui::bench::run(sharad_ratatui::bench::DEFAULT_ITERATIONS) {
+        println!("{}", result);
+    }
+}
@@ -1,22 +1,34 @@
+use crate::archivist;
+use crate::assistant;
+use crate::backend::{AgentModels, AssistantBackend, OpenAIBackend, RunState};
 use crate::character::{
-    CharacterSheet, CharacterSheetBuilder, CharacterSheetUpdate, Contact, Item, MatrixAttributes,
-    Quality, Race, Skills, UpdateOperation,
+    generate_npc, AttributeKey, CharacterSheet, CharacterSheetBuilder, CharacterSheetUpdate,
+    Contact, Item, NpcRole, Quality, Race, Skills, UpdateOperation,
 };
-use crate::dice::{perform_dice_roll, DiceRollRequest, DiceRollResponse};
+use crate::damage::{self, resist_damage, DamageResistanceRequest};
+use crate::dice::{
+    perform_dice_roll, perform_npc_test, perform_opposed_test, DiceRollRequest, DiceRollResponse,
+    NpcTestRequest, OpposedTestRequest, ShownRoll,
+};
+use crate::dice_expr::DiceExpr;
 use crate::error::{AIError, AppError, GameError, ShadowrunError};
+use crate::events::GameEvent;
 use crate::game_state::GameState;
-use crate::image::generate_and_save_image;
+use crate::image::{generate_and_save_image, generate_scene_image, GeneratedImage};
 use crate::message;
-use crate::message::{Message, MessageType};
-use async_openai::types::{RequiredAction, RunToolCallObject};
-use async_openai::{
-    config::OpenAIConfig,
-    types::{
-        CreateMessageRequestArgs, CreateRunRequestArgs, CreateThreadRequestArgs, MessageContent,
-        MessageRole, RunObject, RunStatus, SubmitToolOutputsRunRequest, ToolsOutputs,
-    },
-    Client,
+use crate::message::Message;
+use crate::metrics;
+use crate::plugins;
+use crate::review::ReviewQueue;
+use crate::status::{RunActivity, Shared};
+use crate::tool_args::{
+    self, AugmentationsArgs, BasicAttributesArgs, ContactsArgs, InventoryArgs,
+    MatrixAttributesArgs, QualitiesArgs, RollDiceArgs, SceneImageArgs, SheetFieldsArgs,
+    SkillRatings, SkillUpdates, SkillsArgs,
 };
+use crate::webhook::{self, WebhookEvent};
+use async_openai::types::{RunToolCallObject, ToolsOutputs};
+use async_openai::{config::OpenAIConfig, Client};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
@@ -32,20 +44,70 @@ pub struct GameConversationState {
     pub character_sheet: Option<CharacterSheet>, // Optional character sheet for the active session.
 }
 
+// Messages fetched per page of thread history.
+pub const HISTORY_PAGE_SIZE: usize = 50;
+
+pub use crate::backend::MessagePage;
+
+// What repair_conversation had to make again for a save.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Repaired {
+    pub assistant: bool,
+    pub thread: bool,
+}
+
+impl Repaired {
+    pub fn any(&self) -> bool {
+        self.assistant || self.thread
+    }
+}
+
+// Times an answer that breaks the response schema is sent back for repair.
+pub const MAX_REPAIR_ATTEMPTS: usize = 2;
+
+fn repair_request(problem: &str) -> String {
+    format!(
+        "Your last answer did not follow the response schema: {}. Send the same answer again as \
+         valid json following the schema, with nothing else.",
+        problem
+    )
+}
+
+pub type Checkpoint = Arc<dyn Fn(&GameState) + Send + Sync>;
+
 // Structure representing the game's AI component.
 
 pub struct GameAI {
     pub client: Client<OpenAIConfig>,
+    pub backend: Arc<dyn AssistantBackend>,
     pub conversation_state: Arc<Mutex<Option<GameConversationState>>>,
     pub debug_callback: Arc<dyn Fn(String) + Send + Sync>,
+    pub activity: Shared<RunActivity>,
+    pub review: Shared<ReviewQueue>,
+    // Portraits and scenes finished since the game last filed them.
+    pub images: Shared<Vec<GeneratedImage>>,
+    // Dice rolled since the game last showed them.
+    pub rolls: Shared<Vec<ShownRoll>>,
+    // The models the OpenAI backend runs each agent on.
+    pub models: Shared<AgentModels>,
+    // Saves the game when a run starts, so it is known on the next start if the game closes
+    // before the run ends.
+    pub checkpoint: Option<Checkpoint>,
 }
 
 impl Clone for GameAI {
     fn clone(&self) -> Self {
         GameAI {
             client: self.client.clone(),
+            backend: Arc::clone(&self.backend),
             conversation_state: Arc::clone(&self.conversation_state),
             debug_callback: Arc::clone(&self.debug_callback),
+            activity: self.activity.clone(),
+            review: self.review.clone(),
+            images: self.images.clone(),
+            rolls: self.rolls.clone(),
+            models: self.models.clone(),
+            checkpoint: self.checkpoint.clone(),
         }
     }
 }
@@ -59,12 +121,33 @@ impl GameAI {
     ) -> Result<Self, AppError> {
         let openai_config = OpenAIConfig::new().with_api_key(api_key);
         let client = Client::with_config(openai_config);
+        let models = Shared::new(AgentModels::default());
 
         Ok(Self {
-            client,
+            client: client.clone(),
+            models: models.clone(),
+            ..Self::with_backend(Arc::new(OpenAIBackend::new(client, models)), debug_callback)
+        })
+    }
+
+    // Play turns against another backend, like the mock one in tests. Images and speech still go
+    // to OpenAI, without a key.
+    pub fn with_backend(
+        backend: Arc<dyn AssistantBackend>,
+        debug_callback: impl Fn(String) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            client: Client::new(),
+            backend,
             conversation_state: Arc::new(Mutex::new(None)),
             debug_callback: Arc::new(debug_callback),
-        })
+            activity: Shared::new(RunActivity::Idle),
+            review: Shared::new(ReviewQueue::default()),
+            images: Shared::new(Vec::new()),
+            rolls: Shared::new(Vec::new()),
+            models: Shared::new(AgentModels::default()),
+            checkpoint: None,
+        }
     }
 
     // Method to add debug messages through the provided callback.
@@ -77,32 +160,22 @@ impl GameAI {
         &self,
         assistant_id: &str,
         initial_game_state: GameConversationState,
-    ) -> Result<(), AIError> {
-        let thread = self
-            .client
-            .threads()
-            .create(CreateThreadRequestArgs::default().build()?)
-            .await?;
+    ) -> Result<(), ShadowrunError> {
+        let thread_id = self.backend.create_thread().await?;
 
         let mut state = self.conversation_state.lock().await;
         *state = Some(GameConversationState {
             assistant_id: assistant_id.to_string(),
-            thread_id: thread.id.clone(),
+            thread_id: thread_id.clone(),
             ..initial_game_state
         });
 
-        let initial_message = CreateMessageRequestArgs::default()
-            .role(MessageRole::User)
-            .content("Start the game by assisting the player to create a character. Answer in valid json")
-            .build()?;
-
-        self.client
-            .threads()
-            .messages(&thread.id)
-            .create(initial_message)
-            .await?;
-
-        Ok(())
+        self.backend
+            .add_message(
+                &thread_id,
+                "Start the game by assisting the player to create a character. Answer in valid json",
+            )
+            .await
     }
 
     pub async fn load_conversation(&mut self, state: GameConversationState) {
@@ -117,25 +190,63 @@ impl GameAI {
         formatted_message: &str,
         game_state: &mut GameState,
     ) -> Result<message::GameMessage, ShadowrunError> {
-        let (thread_id, assistant_id) = self
+        self.activity.set(RunActivity::Submitting);
+        let result = self.run_message(formatted_message, game_state).await;
+        self.activity.set(RunActivity::Idle);
+        result
+    }
+
+    // Add a note to the thread without starting a run, for something that happened between turns.
+    pub async fn tell(&self, note: &str, game_state: &mut GameState) -> Result<(), ShadowrunError> {
+        let (thread_id, _) = self
             .get_conversation_ids()
             .await
             .map_err(ShadowrunError::from)?;
+        self.add_message_to_thread(&thread_id, note).await?;
+        game_state.record(GameEvent::Message {
+            sender: message::MessageType::System,
+            text: note.to_string(),
+        })?;
+        Ok(())
+    }
 
-        self.add_message_to_thread(&thread_id, formatted_message)
+    async fn run_message(
+        &mut self,
+        formatted_message: &str,
+        game_state: &mut GameState,
+    ) -> Result<message::GameMessage, ShadowrunError> {
+        let (thread_id, assistant_id) = self
+            .get_conversation_ids()
             .await
             .map_err(ShadowrunError::from)?;
 
-        let run = self
-            .create_run(&thread_id, &assistant_id)
+        // Nothing of the turn happened yet, so the player's action can wait for the connection.
+        self.add_message_to_thread(&thread_id, formatted_message)
             .await
-            .map_err(ShadowrunError::from)?;
+            .map_err(|e| match e {
+                ShadowrunError::Network(e) => ShadowrunError::Offline(e),
+                e => e,
+            })?;
+        game_state.record(GameEvent::Message {
+            sender: message::MessageType::User,
+            text: formatted_message.to_string(),
+        })?;
 
-        self.wait_for_run_completion(&thread_id, &run.id, game_state)
-            .await
-            .map_err(ShadowrunError::from)?;
+        let run_id = self
+            .create_run(&thread_id, &assistant_id, game_state)
+            .await?;
+
+        self.wait_for_run_completion(&thread_id, &run_id, game_state)
+            .await?;
 
         let response = self.get_latest_message(&thread_id).await?;
+        game_state.record(GameEvent::Message {
+            sender: message::MessageType::Game,
+            text: response.clone(),
+        })?;
+        let response = self
+            .repair_answer(&thread_id, &assistant_id, response, game_state)
+            .await?;
 
         self.add_debug_message(format!("\nAI Response: {:#?}\n", response));
 
@@ -144,14 +255,207 @@ impl GameAI {
             .map_err(ShadowrunError::from)
     }
 
+    // Structured outputs make a bad answer rare, but an old assistant or a cut-off reply can
+    // still break the schema. Ask for a corrected answer before giving up on the turn; the last
+    // answer is returned either way.
+    async fn repair_answer(
+        &mut self,
+        thread_id: &str,
+        assistant_id: &str,
+        mut response: String,
+        game_state: &mut GameState,
+    ) -> Result<String, ShadowrunError> {
+        for _ in 0..MAX_REPAIR_ATTEMPTS {
+            let Err(problem) = message::parse_game_message(&response) else {
+                break;
+            };
+            self.add_debug_message(format!(
+                "Answer broke the schema ({}), asking again",
+                problem
+            ));
+            let repair = repair_request(&problem);
+            self.add_message_to_thread(thread_id, &repair).await?;
+            game_state.record(GameEvent::Message {
+                sender: message::MessageType::System,
+                text: repair,
+            })?;
+            let run_id = self.create_run(thread_id, assistant_id, game_state).await?;
+            self.wait_for_run_completion(thread_id, &run_id, game_state)
+                .await?;
+            response = self.get_latest_message(thread_id).await?;
+            game_state.record(GameEvent::Message {
+                sender: message::MessageType::Game,
+                text: response.clone(),
+            })?;
+        }
+        Ok(response)
+    }
+
+    // Give a save whose assistant or thread OpenAI no longer has new ones, so it still loads. A new
+    // thread opens with a summary of the story the save kept.
+    pub async fn repair_conversation(
+        &self,
+        game_state: &mut GameState,
+    ) -> Result<Repaired, ShadowrunError> {
+        let repaired = Repaired {
+            assistant: !self
+                .backend
+                .assistant_exists(&game_state.assistant_id)
+                .await?,
+            thread: !self.backend.thread_exists(&game_state.thread_id).await?,
+        };
+        if !repaired.any() {
+            return Ok(repaired);
+        }
+        self.add_debug_message(format!("Repairing the conversation: {:?}", repaired));
+        let assistant_id = if repaired.assistant {
+            self.backend
+                .create_assistant(&game_state.save_name, game_state.ruleset)
+                .await?
+        } else {
+            game_state.assistant_id.clone()
+        };
+        let thread_id = if repaired.thread {
+            let (thread_id, archive) =
+                archivist::rebuild(self.backend.as_ref(), game_state).await?;
+            // A run on the lost thread can't be picked up.
+            if game_state.pending_run.is_some() {
+                game_state.record(GameEvent::Run { run_id: None })?;
+            }
+            game_state.record(GameEvent::Archive { archive })?;
+            thread_id
+        } else {
+            game_state.thread_id.clone()
+        };
+        game_state.record(GameEvent::Thread {
+            assistant_id,
+            thread_id,
+        })?;
+        if repaired.assistant {
+            game_state.record(GameEvent::Instructions {
+                version: assistant::instructions_version(game_state.ruleset),
+            })?;
+        }
+        Ok(repaired)
+    }
+
+    // Whether the save's assistant was given an older prompt than this release's.
+    pub fn instructions_outdated(game_state: &GameState) -> bool {
+        game_state.instructions_version != Some(assistant::instructions_version(game_state.ruleset))
+    }
+
+    // Give the save's assistant the prompt of this release: the same assistant updated, or, with
+    // `recreate`, a new one made in its place, for an assistant the update can't fix.
+    pub async fn upgrade_assistant(
+        &self,
+        game_state: &mut GameState,
+        recreate: bool,
+    ) -> Result<(), ShadowrunError> {
+        if recreate {
+            let assistant_id = self
+                .backend
+                .create_assistant(&game_state.save_name, game_state.ruleset)
+                .await?;
+            let replaced = game_state.assistant_id.clone();
+            game_state.record(GameEvent::Thread {
+                assistant_id: assistant_id.clone(),
+                thread_id: game_state.thread_id.clone(),
+            })?;
+            if let Some(state) = self.conversation_state.lock().await.as_mut() {
+                state.assistant_id = assistant_id;
+            }
+            self.backend.delete_assistant(&replaced).await;
+        } else {
+            self.backend
+                .update_assistant(&game_state.assistant_id, game_state.ruleset)
+                .await?;
+        }
+        game_state.record(GameEvent::Instructions {
+            version: assistant::instructions_version(game_state.ruleset),
+        })?;
+        Ok(())
+    }
+
+    // Pick up the run the game was closed during, if any: the answer of one that finished, or
+    // that finishes now, is returned as if the turn had just ended. One waiting for tool outputs
+    // is cancelled, as its calls may have changed the game already.
+    pub async fn resume_run(
+        &mut self,
+        game_state: &mut GameState,
+    ) -> Result<Option<message::GameMessage>, ShadowrunError> {
+        let Some(run_id) = game_state.pending_run.clone() else {
+            return Ok(None);
+        };
+        let thread_id = game_state.thread_id.clone();
+        self.add_debug_message(format!("Resuming run {}", run_id));
+        let answered = match self.backend.run_state(&thread_id, &run_id).await {
+            Ok(RunState::Completed) => true,
+            Ok(RunState::InProgress) => self
+                .wait_for_run_completion(&thread_id, &run_id, game_state)
+                .await
+                .is_ok(),
+            Ok(RunState::RequiresAction(_)) => {
+                let _ = self.cancel_run(&thread_id, &run_id).await;
+                false
+            }
+            Ok(RunState::Failed(_)) | Err(_) => false,
+        };
+        game_state.record(GameEvent::Run { run_id: None })?;
+        if !answered {
+            return Ok(None);
+        }
+
+        let response = self.get_latest_message(&thread_id).await?;
+        // The answer may have been saved before the game closed.
+        let saved = game_state
+            .events
+            .iter()
+            .rev()
+            .find_map(|recorded| match &recorded.event {
+                GameEvent::Message {
+                    sender: message::MessageType::Game,
+                    text,
+                } => Some(text),
+                _ => None,
+            });
+        if saved == Some(&response) {
+            return Ok(None);
+        }
+        game_state.record(GameEvent::Message {
+            sender: message::MessageType::Game,
+            text: response.clone(),
+        })?;
+        // The answer the run left may break the schema as much as a fresh one.
+        let assistant_id = game_state.assistant_id.clone();
+        let response = self
+            .repair_answer(&thread_id, &assistant_id, response, game_state)
+            .await?;
+        self.update_game_state(game_state, &response)
+            .await
+            .map(Some)
+            .map_err(ShadowrunError::from)
+    }
+
     async fn wait_for_run_completion(
         &mut self,
         thread_id: &str,
         run_id: &str,
         game_state: &mut GameState,
+    ) -> Result<(), ShadowrunError> {
+        let result = self.poll_run(thread_id, run_id, game_state).await;
+        game_state.record(GameEvent::Run { run_id: None })?;
+        result
+    }
+
+    async fn poll_run(
+        &mut self,
+        thread_id: &str,
+        run_id: &str,
+        game_state: &mut GameState,
     ) -> Result<(), ShadowrunError> {
         let timeout_duration = Duration::from_secs(60);
         let start_time = Instant::now();
+        self.activity.set(RunActivity::Thinking);
 
         loop {
             if start_time.elapsed() > timeout_duration {
@@ -159,33 +463,29 @@ impl GameAI {
                 return Err(AppError::Timeout.into());
             }
 
-            let run = self
-                .client
-                .threads()
-                .runs(thread_id)
-                .retrieve(run_id)
-                .await?;
+            let run = self.backend.run_state(thread_id, run_id).await?;
 
-            self.add_debug_message(format!("Run status: {:#?}", run.status));
-            match run.status {
-                RunStatus::Completed => {
+            self.add_debug_message(format!("Run status: {:#?}", run));
+            match run {
+                RunState::Completed => {
                     self.add_debug_message("Run completed".to_string());
+                    self.activity.set(RunActivity::Composing);
                     return Ok(());
                 }
-                RunStatus::RequiresAction => {
+                RunState::RequiresAction(tool_calls) => {
                     self.add_debug_message("Run requires action".to_string());
-                    self.handle_required_action(thread_id, run_id, &run, game_state)
+                    self.handle_tool_outputs(thread_id, run_id, &tool_calls, game_state)
                         .await?;
                 }
-                RunStatus::Failed | RunStatus::Cancelled | RunStatus::Expired => {
+                RunState::Failed(status) => {
                     self.add_debug_message("Run failed, cancelled, or expired".to_string());
                     let _ = self.cancel_run(thread_id, run_id).await;
                     return Err(ShadowrunError::Game(format!(
-                        "Run failed with status: {:#?}",
-                        run.status
+                        "Run failed with status: {}",
+                        status
                     )));
                 }
-                _ => {
+                RunState::InProgress => {
                     self.add_debug_message("Run is in progress".to_string());
                     tokio::time::sleep(Duration::from_secs(1)).await
                 }
@@ -193,15 +493,15 @@ impl GameAI {
         }
     }
 
-    async fn update_game_state(
+    pub async fn update_game_state(
         &self,
         game_state: &mut GameState,
         response: &str,
     ) -> Result<message::GameMessage, AppError> {
         self.add_debug_message(format!("Response: {:#?}", response));
-        let game_message: message::GameMessage = serde_json::from_str(response).map_err(|e| {
+        let game_message = message::parse_game_message(response).map_err(|e| {
             ShadowrunError::Game(format!(
-                "Failed to parse GameMessage: {:#?}\n Message: {:#?}",
+                "Failed to parse GameMessage: {}\n Message: {:#?}",
                 e, response
             ))
         })?;
@@ -226,111 +526,166 @@ impl GameAI {
             "Update Character sheet: Character sheet: {:#?}",
             new_sheet
         ));
-        // Update the main character sheet
-        game_state.main_character_sheet = Some(new_sheet.clone());
-
-        // Update or add the character in the characters vector
-        if let Some(existing_character) = game_state
-            .characters
-            .iter_mut()
-            .find(|c| c.name == new_sheet.name)
-        {
-            *existing_character = new_sheet;
-        } else {
-            game_state.characters.push(new_sheet);
-        }
+        // Update the main character sheet, and add or replace the character by name
+        game_state.record(GameEvent::MainCharacter {
+            sheet: Some(new_sheet.clone()),
+        })?;
+        game_state.record(GameEvent::Character { sheet: new_sheet })?;
 
         Ok(())
     }
 
     pub async fn cancel_run(&self, thread_id: &str, run_id: &str) -> Result<(), AppError> {
-        self.client
-            .threads()
-            .runs(thread_id)
-            .cancel(run_id)
-            .await
-            .map_err(|e| ShadowrunError::OpenAI(e.to_string()))
-            .map_err(AppError::Shadowrun)?;
+        self.backend.cancel_run(thread_id, run_id).await?;
         self.add_debug_message(format!("Run {} cancelled", run_id));
         Ok(())
     }
 
-    async fn handle_required_action(
+    async fn handle_tool_outputs(
         &mut self,
         thread_id: &str,
         run_id: &str,
-        run: &RunObject,
+        tool_calls: &[RunToolCallObject],
         game_state: &mut GameState,
     ) -> Result<(), ShadowrunError> {
-        self.add_debug_message(format!(
-            "Handling required action: {:#?}",
-            run.required_action
-        ));
-
-        if let Some(required_action) = &run.required_action {
-            match required_action.r#type.as_str() {
-                "submit_tool_outputs" => {
-                    self.handle_tool_outputs(thread_id, run_id, required_action, game_state)
-                        .await
-                }
-                _ => Err(ShadowrunError::Game(format!(
-                    "Unknown required action type: {}",
-                    required_action.r#type
-                ))),
-            }
-        } else {
-            Err(ShadowrunError::Game("No required action found".to_string()))
-        }
+        let tool_outputs = self.run_tool_calls(tool_calls, game_state).await;
+        self.activity.set(RunActivity::Thinking);
+        self.submit_tool_outputs(thread_id, run_id, tool_outputs)
+            .await
     }
 
-    async fn handle_tool_outputs(
+    // The outputs of a run's tool calls, in the order they were asked for. The built-in tools change
+    // the game one after the other, so calls about the same character apply in order; a plugin
+    // only reads it, so plugins called one after the other run at the same time, and each sees
+    // the changes of the calls before it.
+    pub async fn run_tool_calls(
         &mut self,
-        thread_id: &str,
-        run_id: &str,
-        required_action: &RequiredAction,
+        tool_calls: &[RunToolCallObject],
         game_state: &mut GameState,
-    ) -> Result<(), ShadowrunError> {
-        let mut tool_outputs = Vec::new();
-
-        for tool_call in &required_action.submit_tool_outputs.tool_calls {
-            self.add_debug_message(format!("Handling tool call: {:#?}", tool_call));
+    ) -> Vec<ToolsOutputs> {
+        let mut outputs = Vec::new();
+        let mut rest = tool_calls;
+        while let Some(tool_call) = rest.first() {
+            let plugin_calls = rest
+                .iter()
+                .take_while(|call| plugins::find(&call.function.name).is_some())
+                .count();
+            let batch = &rest[..plugin_calls.max(1)];
+            rest = &rest[batch.len()..];
+            self.activity.set(RunActivity::ToolCall(
+                batch
+                    .iter()
+                    .map(|call| call.function.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ));
 
-            let output = match tool_call.function.name.as_str() {
-                "create_character_sheet" => {
-                    self.handle_create_character_sheet(tool_call, game_state)
-                        .await?
-                }
-                "perform_dice_roll" => self.handle_perform_dice_roll(tool_call, game_state)?,
-                "generate_character_image" => self.handle_generate_character_image(tool_call)?,
-                "update_basic_attributes" => {
-                    self.handle_update_basic_attributes(tool_call, game_state)?
-                }
-                "update_skills" => self.handle_update_skills(tool_call, game_state)?,
-                "update_inventory" => self.handle_update_inventory(tool_call, game_state)?,
-                "update_qualities" => self.handle_update_qualities(tool_call, game_state)?,
-                "update_matrix_attributes" => {
-                    self.handle_update_matrix_attributes(tool_call, game_state)?
-                }
-                "update_contacts" => self.handle_update_contacts(tool_call, game_state)?,
-                "update_augmentations" => {
-                    self.handle_update_augmentations(tool_call, game_state)?
-                }
-                _ => {
-                    return Err(ShadowrunError::Game(format!(
-                        "Unknown function: {}",
-                        tool_call.function.name
-                    )))
-                }
+            let results = if plugin_calls == 0 {
+                self.add_debug_message(format!("Handling tool call: {:#?}", tool_call));
+                vec![self.handle_tool_call(tool_call, game_state).await]
+            } else {
+                self.add_debug_message(format!("Handling plugin calls: {:#?}", batch));
+                let game_state = &*game_state;
+                futures::future::join_all(
+                    batch.iter().map(|call| self.call_plugin(call, game_state)),
+                )
+                .await
             };
 
-            tool_outputs.push(ToolsOutputs {
-                tool_call_id: Some(tool_call.id.clone()),
-                output: Some(output),
-            });
+            // A bad call goes back to the assistant to fix, instead of leaving the run waiting.
+            for (call, result) in batch.iter().zip(results) {
+                let output = result.unwrap_or_else(|e| {
+                    self.add_debug_message(format!("Tool call failed: {}", e));
+                    format!("Error: {}", e)
+                });
+                outputs.push(ToolsOutputs {
+                    tool_call_id: Some(call.id.clone()),
+                    output: Some(output),
+                });
+            }
         }
+        outputs
+    }
 
-        self.submit_tool_outputs(thread_id, run_id, tool_outputs)
-            .await
+    async fn call_plugin(
+        &self,
+        tool_call: &RunToolCallObject,
+        game_state: &GameState,
+    ) -> Result<String, ShadowrunError> {
+        let name = tool_call.function.name.as_str();
+        let plugin = plugins::find(name)
+            .ok_or_else(|| ShadowrunError::Game(format!("Unknown function: {}", name)))?;
+        self.add_debug_message(format!("Calling the plugin tool {}", name));
+        let output = plugin
+            .call(&tool_call.function.arguments, game_state)
+            .await?;
+        metrics::record(&format!("tool/{}", name));
+        Ok(output)
+    }
+
+    // Run one tool call against the game state and return the output for the assistant.
+    pub async fn handle_tool_call(
+        &mut self,
+        tool_call: &RunToolCallObject,
+        game_state: &mut GameState,
+    ) -> Result<String, ShadowrunError> {
+        let held_before = self.review.with(|review| review.pending.len());
+        let output = match tool_call.function.name.as_str() {
+            "create_character_sheet" => {
+                self.handle_create_character_sheet(tool_call, game_state)
+                    .await?
+            }
+            "perform_dice_roll" => self.handle_perform_dice_roll(tool_call, game_state)?,
+            "roll_dice" => self.handle_roll_dice(tool_call, game_state)?,
+            "resist_damage" => self.handle_resist_damage(tool_call, game_state)?,
+            "opposed_test" => self.handle_opposed_test(tool_call, game_state)?,
+            "npc_test" => self.handle_npc_test(tool_call, game_state)?,
+            "generate_character_image" => self.handle_generate_character_image(tool_call)?,
+            "generate_scene_image" => self.handle_generate_scene_image(tool_call)?,
+            "generate_npc" => self.handle_generate_npc(tool_call, game_state)?,
+            "update_basic_attributes" => {
+                self.handle_update_basic_attributes(tool_call, game_state)?
+            }
+            "update_skills" => self.handle_update_skills(tool_call, game_state)?,
+            "update_inventory" => self.handle_update_inventory(tool_call, game_state)?,
+            "update_qualities" => self.handle_update_qualities(tool_call, game_state)?,
+            "update_matrix_attributes" => {
+                self.handle_update_matrix_attributes(tool_call, game_state)?
+            }
+            "update_contacts" => self.handle_update_contacts(tool_call, game_state)?,
+            "update_augmentations" => self.handle_update_augmentations(tool_call, game_state)?,
+            "update_sheet_fields" => self.handle_update_sheet_fields(tool_call, game_state)?,
+            _ => return self.call_plugin(tool_call, game_state).await,
+        };
+        metrics::record(&format!("tool/{}", tool_call.function.name));
+        // Don't let the assistant narrate a change the player may still turn down.
+        if self.review.with(|review| review.pending.len()) > held_before {
+            return Ok(
+                "The change was sent to the player for approval and is not on the sheet yet."
+                    .to_string(),
+            );
+        }
+        Ok(output)
+    }
+
+    // Apply an update, or hold it for the player when updates need their approval.
+    fn apply_character_update(
+        &self,
+        game_state: &mut GameState,
+        character: &CharacterSheet,
+        update: CharacterSheetUpdate,
+    ) -> Result<(), ShadowrunError> {
+        self.review.with(|review| {
+            if review.enabled {
+                review.hold(character, update, game_state.ruleset, &game_state.save_name)
+            } else {
+                game_state.record(GameEvent::SheetUpdate {
+                    character: character.name.clone(),
+                    update,
+                })
+            }
+        })?;
+        Ok(())
     }
 
     async fn handle_create_character_sheet(
@@ -348,9 +703,13 @@ impl GameAI {
         };
 
         if character_sheet.main {
-            game_state.main_character_sheet = Some(character_sheet.clone());
+            game_state.record(GameEvent::MainCharacter {
+                sheet: Some(character_sheet.clone()),
+            })?;
         }
-        game_state.characters.push(character_sheet.clone());
+        game_state.record(GameEvent::Character {
+            sheet: character_sheet.clone(),
+        })?;
 
         if let Some(state) = &mut *self.conversation_state.lock().await {
             state.character_sheet = Some(character_sheet.clone());
@@ -360,15 +719,101 @@ impl GameAI {
         Ok(serde_json::to_string(&character_sheet)?)
     }
 
+    // A roll from a dice expression; a malformed one goes back to the assistant with the reason.
+    fn handle_roll_dice(
+        &mut self,
+        tool_call: &RunToolCallObject,
+        game_state: &mut GameState,
+    ) -> Result<String, ShadowrunError> {
+        let args: RollDiceArgs = tool_args::parse("roll_dice", &tool_call.function.arguments)?;
+        let expr: DiceExpr = args.expression.parse().map_err(|e| {
+            ShadowrunError::Game(format!(
+                "Invalid dice expression '{}': {}",
+                args.expression, e
+            ))
+        })?;
+        let title = match args.reason.filter(|reason| !reason.trim().is_empty()) {
+            Some(reason) => format!("{}: {}", reason, expr),
+            None => expr.to_string(),
+        };
+        let roll = expr.roll(title);
+        self.add_debug_message(format!("Dice expression roll: {:#?}", roll));
+        game_state.record(GameEvent::Roll { roll: roll.clone() })?;
+        let output = serde_json::to_string(&roll)?;
+        self.rolls.with(|rolls| rolls.push(roll));
+        Ok(output)
+    }
+
+    // An NPC's test from their sheet; an NPC without one goes back to the assistant with the reason.
+    fn handle_npc_test(
+        &mut self,
+        tool_call: &RunToolCallObject,
+        game_state: &mut GameState,
+    ) -> Result<String, ShadowrunError> {
+        let args: NpcTestRequest = tool_args::parse("npc_test", &tool_call.function.arguments)?;
+        let (response, roll) = perform_npc_test(&args, game_state).map_err(ShadowrunError::Game)?;
+        self.add_debug_message(format!("NPC test: {:#?}", response));
+        game_state.record(GameEvent::Roll { roll: roll.clone() })?;
+        self.rolls.with(|rolls| rolls.push(roll));
+        Ok(serde_json::to_string(&response)?)
+    }
+
+    // Both sides of an opposed test, rolled by the game so neither is made up.
+    fn handle_opposed_test(
+        &mut self,
+        tool_call: &RunToolCallObject,
+        game_state: &mut GameState,
+    ) -> Result<String, ShadowrunError> {
+        let args: OpposedTestRequest =
+            tool_args::parse("opposed_test", &tool_call.function.arguments)?;
+        let (response, rolls) =
+            perform_opposed_test(&args, game_state).map_err(ShadowrunError::Game)?;
+        self.add_debug_message(format!("Opposed test: {:#?}", response));
+        for roll in rolls {
+            game_state.record(GameEvent::Roll { roll: roll.clone() })?;
+            self.rolls.with(|rolls| rolls.push(roll));
+        }
+        Ok(serde_json::to_string(&response)?)
+    }
+
+    // A soak roll; the damage left after the hits goes back to the assistant.
+    fn handle_resist_damage(
+        &mut self,
+        tool_call: &RunToolCallObject,
+        game_state: &mut GameState,
+    ) -> Result<String, ShadowrunError> {
+        let args: DamageResistanceRequest =
+            tool_args::parse("resist_damage", &tool_call.function.arguments)?;
+        let (roll, response) = resist_damage(&args, game_state).map_err(ShadowrunError::Game)?;
+        self.add_debug_message(format!("Damage resistance: {:#?}", response));
+        game_state.record(GameEvent::Roll { roll: roll.clone() })?;
+        damage::track(game_state, &args, &response)?;
+        self.rolls.with(|rolls| rolls.push(roll));
+        Ok(serde_json::to_string(&response)?)
+    }
+
     fn handle_perform_dice_roll(
         &mut self,
         tool_call: &RunToolCallObject,
         game_state: &mut GameState,
     ) -> Result<String, ShadowrunError> {
         let args: DiceRollRequest = serde_json::from_str(&tool_call.function.arguments)?;
+        let summary = args.describe();
         let response = match perform_dice_roll(args, game_state) {
             Ok(response) => {
                 self.add_debug_message(format!("Dice roll: {:#?}", response));
+                let shown = ShownRoll {
+                    title: summary.clone(),
+                    dice_results: response.dice_results.clone(),
+                    hits: response.hits,
+                    glitch: response.glitch,
+                    critical_glitch: response.critical_glitch,
+                    total: None,
+                };
+                game_state.record(GameEvent::Roll {
+                    roll: shown.clone(),
+                })?;
+                self.rolls.with(|rolls| rolls.push(shown));
                 response
             }
             Err(e) => {
@@ -384,7 +829,9 @@ impl GameAI {
             }
         };
 
-        Ok(serde_json::to_string(&response)?)
+        let output = serde_json::to_string(&response)?;
+        webhook::post(WebhookEvent::DiceRoll { summary, response });
+        Ok(output)
     }
 
     fn handle_generate_character_image(
@@ -392,264 +839,221 @@ impl GameAI {
         tool_call: &RunToolCallObject,
     ) -> Result<String, ShadowrunError> {
         let args: Value = serde_json::from_str(&tool_call.function.arguments)?;
+        let name = args["name"].as_str().unwrap_or_default().to_string();
+        let images = self.images.clone();
         tokio::spawn(async move {
-            let _ = generate_and_save_image(&args["image_generation_prompt"].to_string()).await;
+            if let Ok(path) =
+                generate_and_save_image(&args["image_generation_prompt"].to_string()).await
+            {
+                if !name.is_empty() {
+                    images.with(|images| {
+                        images.push(GeneratedImage::Portrait {
+                            name,
+                            path: path.clone(),
+                        })
+                    });
+                }
+                webhook::post(WebhookEvent::Image(path));
+            }
         });
 
         Ok("Generating image...".to_string())
     }
 
-    fn handle_update_basic_attributes(
+    fn handle_generate_scene_image(
+        &mut self,
+        tool_call: &RunToolCallObject,
+    ) -> Result<String, ShadowrunError> {
+        let args: SceneImageArgs =
+            tool_args::parse("generate_scene_image", &tool_call.function.arguments)?;
+        let images = self.images.clone();
+        tokio::spawn(async move {
+            if let Ok(scene) =
+                generate_scene_image(&args.title, &args.image_generation_prompt).await
+            {
+                webhook::post(WebhookEvent::Image(scene.path.clone()));
+                images.with(|images| images.push(GeneratedImage::Scene(scene)));
+            }
+        });
+
+        Ok("Generating scene illustration...".to_string())
+    }
+
+    fn handle_generate_npc(
         &mut self,
         tool_call: &RunToolCallObject,
         game_state: &mut GameState,
     ) -> Result<String, ShadowrunError> {
-        let args: serde_json::Value = serde_json::from_str(&tool_call.function.arguments)?;
-        let character_name = args["character_name"]
-            .as_str()
-            .ok_or_else(|| ShadowrunError::Game("Missing character_name".to_string()))?;
-        let updates = &args["updates"];
+        let args: Value = serde_json::from_str(&tool_call.function.arguments)?;
+        let role: NpcRole = serde_json::from_value(args["role"].clone())?;
+        let mut npc = generate_npc(role, &mut rand::thread_rng());
+        if let Some(name) = args["name"].as_str().filter(|name| !name.trim().is_empty()) {
+            npc.name = name.to_string();
+        }
+        self.add_debug_message(format!("Generated NPC: {:#?}", npc));
 
+        game_state.record(GameEvent::Character { sheet: npc.clone() })?;
+        Ok(serde_json::to_string(&npc)?)
+    }
+
+    // Apply the updates built from the named character, and keep the main sheet in step with it.
+    fn update_character(
+        &self,
+        game_state: &mut GameState,
+        character_name: &str,
+        updates: impl FnOnce(&CharacterSheet) -> Result<Vec<CharacterSheetUpdate>, ShadowrunError>,
+    ) -> Result<(), ShadowrunError> {
         let character = game_state
             .characters
-            .iter_mut()
+            .iter()
             .find(|c| c.name == character_name)
+            .cloned()
             .ok_or_else(|| GameError::CharacterNotFound(character_name.to_string()))?;
 
-        for (attr, value) in updates.as_object().unwrap() {
-            let update = CharacterSheetUpdate::UpdateAttribute {
-                attribute: attr.to_string(),
-                operation: UpdateOperation::Modify(self.parse_value(attr, value)?),
-            };
-            character.apply_update(update)?;
+        // The main sheet is kept in step by the event.
+        for update in updates(&character)? {
+            self.apply_character_update(game_state, &character, update)?;
         }
+        Ok(())
+    }
 
-        if game_state
-            .main_character_sheet
-            .as_ref()
-            .map(|cs| cs.name == character_name)
-            .unwrap_or(false)
+    // Free-form entries. Naming a character without a sheet creates one.
+    fn handle_update_sheet_fields(
+        &mut self,
+        tool_call: &RunToolCallObject,
+        game_state: &mut GameState,
+    ) -> Result<String, ShadowrunError> {
+        let args: SheetFieldsArgs =
+            tool_args::parse("update_sheet_fields", &tool_call.function.arguments)?;
+        if !game_state
+            .characters
+            .iter()
+            .any(|c| c.name == args.character_name)
         {
-            game_state.main_character_sheet = Some(character.clone());
+            let sheet = CharacterSheetBuilder::new(
+                args.character_name.clone(),
+                Race::Human,
+                String::new(),
+                String::new(),
+                args.main_character,
+            )
+            .build();
+            if args.main_character {
+                game_state.record(GameEvent::MainCharacter {
+                    sheet: Some(sheet.clone()),
+                })?;
+            }
+            game_state.record(GameEvent::Character { sheet })?;
         }
 
+        self.update_character(game_state, &args.character_name, |_| {
+            Ok(vec![CharacterSheetUpdate::SetFields {
+                fields: args.fields,
+            }])
+        })?;
+
         Ok(format!(
-            "Updated basic attributes for character: {}",
-            character_name
+            "Updated the sheet entries of character: {}",
+            args.character_name
         ))
     }
 
-    fn handle_update_skills(
+    fn handle_update_basic_attributes(
         &mut self,
         tool_call: &RunToolCallObject,
         game_state: &mut GameState,
     ) -> Result<String, ShadowrunError> {
-        let args: serde_json::Value = serde_json::from_str(&tool_call.function.arguments)?;
-        let character_name = args["character_name"]
-            .as_str()
-            .ok_or_else(|| ShadowrunError::Game("Missing character_name".to_string()))?;
-        let updates = &args["updates"]["skills"];
-
-        let character = game_state
-            .characters
-            .iter_mut()
-            .find(|c| c.name == character_name)
-            .ok_or_else(|| GameError::CharacterNotFound(character_name.to_string()))?;
-
-        let update_category = |category: &str,
-                               skill_map: &mut HashMap<String, u8>|
-         -> Result<(), AppError> {
-            if let Some(category_skills) = updates.get(category).and_then(|s| s.as_array()) {
-                for skill in category_skills {
-                    let name = skill["name"].as_str().ok_or_else(|| {
-                        ShadowrunError::Game(format!("Missing skill name in {} category", category))
-                    })?;
-                    let rating = skill["rating"].as_u64().ok_or_else(|| {
-                        ShadowrunError::Game(format!(
-                            "Invalid skill rating in {} category",
-                            category
-                        ))
-                    })? as u8;
-                    skill_map.insert(name.to_string(), rating);
-                }
-            }
-            Ok(())
-        };
-
-        // Update regular skills
-        let mut updated_skills = character.skills.clone();
-        update_category("combat", &mut updated_skills.combat)?;
-        update_category("physical", &mut updated_skills.physical)?;
-        update_category("social", &mut updated_skills.social)?;
-        update_category("technical", &mut updated_skills.technical)?;
-
-        let skills_update = CharacterSheetUpdate::UpdateAttribute {
-            attribute: "skills".to_string(),
-            operation: UpdateOperation::Modify(crate::character::Value::Skills(updated_skills)),
-        };
-        character.apply_update(skills_update)?;
-
-        // Update knowledge skills
-        if let Some(knowledge_skills) = updates.get("knowledge") {
-            let mut updated_knowledge_skills = character.knowledge_skills.clone();
-            if let Some(knowledge_array) = knowledge_skills.as_array() {
-                for skill in knowledge_array {
-                    let name = skill["name"].as_str().ok_or_else(|| {
-                        ShadowrunError::Game("Missing knowledge skill name".to_string())
-                    })?;
-                    let rating = skill["rating"].as_u64().ok_or_else(|| {
-                        ShadowrunError::Game("Invalid knowledge skill rating".to_string())
-                    })? as u8;
-                    updated_knowledge_skills.insert(name.to_string(), rating);
-                }
-            }
-
-            let knowledge_update = CharacterSheetUpdate::UpdateAttribute {
-                attribute: "knowledge_skills".to_string(),
-                operation: UpdateOperation::Modify(crate::character::Value::HashMapStringU8(
-                    updated_knowledge_skills,
-                )),
-            };
-            character.apply_update(knowledge_update)?;
-        }
+        let args: BasicAttributesArgs =
+            tool_args::parse("update_basic_attributes", &tool_call.function.arguments)?;
 
-        if game_state
-            .main_character_sheet
-            .as_ref()
-            .map(|cs| cs.name == character_name)
-            .unwrap_or(false)
-        {
-            game_state.main_character_sheet = Some(character.clone());
-        }
+        self.update_character(game_state, &args.character_name, |_| {
+            args.updates
+                .iter()
+                .map(|(attr, value)| {
+                    Ok(CharacterSheetUpdate::UpdateAttribute {
+                        attribute: *attr,
+                        operation: UpdateOperation::Modify(self.parse_value(*attr, value)?),
+                    })
+                })
+                .collect()
+        })?;
 
-        Ok(format!("Updated skills for character: {}", character_name))
+        Ok(format!(
+            "Updated basic attributes for character: {}",
+            args.character_name
+        ))
     }
 
-    fn handle_update_inventory(
+    fn handle_update_skills(
         &mut self,
         tool_call: &RunToolCallObject,
         game_state: &mut GameState,
     ) -> Result<String, ShadowrunError> {
-        // Implementation similar to original code, but extracted into this function
-        let args: serde_json::Value = serde_json::from_str(&tool_call.function.arguments)?;
-        let character_name = args["character_name"]
-            .as_str()
-            .ok_or_else(|| ShadowrunError::Game("Missing character_name".to_string()))?;
-        let operation = args["operation"]
-            .as_str()
-            .ok_or_else(|| ShadowrunError::Game("Missing operation".to_string()))?;
-        let items = args.get("items").or_else(|| args.get("item"));
-
-        let character = game_state
-            .characters
-            .iter_mut()
-            .find(|c| c.name == character_name)
-            .ok_or_else(|| GameError::CharacterNotFound(character_name.to_string()))?;
-
-        let mut new_items: HashMap<String, Item> = HashMap::new();
-
-        match operation {
-            "Remove" => {
-                // For removal, we only need the item names
-                if let Some(items) = items {
-                    let item_names: Vec<String> = if items.is_array() {
-                        serde_json::from_value(items.clone())?
-                    } else if items.is_object() && items.get("name").is_some() {
-                        vec![items["name"].as_str().unwrap().to_string()]
-                    } else if items.is_object() && items.get("name").is_none() {
-                        items.as_object().unwrap().keys().cloned().collect()
-                    } else {
-                        return Err(ShadowrunError::Game(
-                            "Invalid items format for removal".to_string(),
-                        ));
-                    };
-                    for name in item_names {
-                        new_items.insert(
-                            name.clone(),
-                            Item {
-                                name: name.clone(),
-                                quantity: 1,
-                                description: String::new(),
-                            },
-                        );
-                    }
-                }
+        let args: SkillsArgs = tool_args::parse("update_skills", &tool_call.function.arguments)?;
+        let SkillUpdates {
+            skills,
+            knowledge_skills,
+        } = args.updates;
+
+        self.update_character(game_state, &args.character_name, |character| {
+            let mut updated_skills = character.skills.clone();
+            for (ratings, skill_map) in [
+                (skills.combat, &mut updated_skills.combat),
+                (skills.physical, &mut updated_skills.physical),
+                (skills.social, &mut updated_skills.social),
+                (skills.technical, &mut updated_skills.technical),
+            ] {
+                skill_map.extend(ratings.map(SkillRatings::into_map).unwrap_or_default());
             }
-            "Add" | "Modify" => {
-                if let Some(items) = items {
-                    if items.is_object() {
-                        if items.get("name").is_some() {
-                            // Single item
-                            let item: Item = serde_json::from_value(items.clone())?;
-                            new_items.insert(item.name.clone(), item);
-                        } else {
-                            // Multiple items
-                            for (key, value) in items.as_object().unwrap() {
-                                let item = if value.is_object() {
-                                    Item {
-                                        name: key.clone(),
-                                        quantity: value["quantity"].as_u64().unwrap_or(1) as u32,
-                                        description: value["description"]
-                                            .as_str()
-                                            .unwrap_or("")
-                                            .to_string(),
-                                    }
-                                } else {
-                                    return Err(ShadowrunError::Game(format!(
-                                        "Invalid item format for {}",
-                                        key
-                                    )));
-                                };
-                                new_items.insert(key.clone(), item);
-                            }
-                        }
-                    } else {
-                        return Err(ShadowrunError::Game(
-                            "Invalid items format for add/modify".to_string(),
-                        ));
-                    }
-                } else {
-                    return Err(ShadowrunError::Game(
-                        "Missing items or item for add/modify".to_string(),
-                    ));
+            let mut updates = vec![CharacterSheetUpdate::UpdateAttribute {
+                attribute: AttributeKey::Skills,
+                operation: UpdateOperation::Modify(crate::character::Value::Skills(updated_skills)),
+            }];
+
+            let knowledge: Vec<SkillRatings> = knowledge_skills
+                .into_iter()
+                .chain(skills.knowledge)
+                .collect();
+            if !knowledge.is_empty() {
+                let mut updated_knowledge_skills = character.knowledge_skills.clone();
+                for ratings in knowledge {
+                    updated_knowledge_skills.extend(ratings.into_map());
                 }
+                updates.push(CharacterSheetUpdate::UpdateAttribute {
+                    attribute: AttributeKey::KnowledgeSkills,
+                    operation: UpdateOperation::Modify(crate::character::Value::HashMapStringU8(
+                        updated_knowledge_skills,
+                    )),
+                });
             }
-            _ => {
-                return Err(ShadowrunError::Game(
-                    "Invalid inventory operation".to_string(),
-                ))
-            }
-        };
+            Ok(updates)
+        })?;
 
-        let update = CharacterSheetUpdate::UpdateAttribute {
-            attribute: "inventory".to_string(),
-            operation: match operation {
-                "Add" => {
-                    UpdateOperation::Add(crate::character::Value::HashMapStringItem(new_items))
-                }
-                "Remove" => {
-                    UpdateOperation::Remove(crate::character::Value::HashMapStringItem(new_items))
-                }
-                "Modify" => {
-                    UpdateOperation::Modify(crate::character::Value::HashMapStringItem(new_items))
-                }
-                _ => unreachable!(),
-            },
-        };
-        character.apply_update(update)?;
+        Ok(format!(
+            "Updated skills for character: {}",
+            args.character_name
+        ))
+    }
 
-        if game_state
-            .main_character_sheet
-            .as_ref()
-            .map(|cs| cs.name == character_name)
-            .unwrap_or(false)
-        {
-            game_state.main_character_sheet = Some(character.clone());
-        }
+    fn handle_update_inventory(
+        &mut self,
+        tool_call: &RunToolCallObject,
+        game_state: &mut GameState,
+    ) -> Result<String, ShadowrunError> {
+        let args: InventoryArgs =
+            tool_args::parse("update_inventory", &tool_call.function.arguments)?;
+        let items = crate::character::Value::HashMapStringItem(args.item.into_items());
+
+        self.update_character(game_state, &args.character_name, |_| {
+            Ok(vec![CharacterSheetUpdate::UpdateAttribute {
+                attribute: AttributeKey::Inventory,
+                operation: args.operation.with(items),
+            }])
+        })?;
 
         Ok(format!(
             "Updated inventory for character: {}",
-            character_name
+            args.character_name
         ))
     }
 
@@ -658,50 +1062,20 @@ impl GameAI {
         tool_call: &RunToolCallObject,
         game_state: &mut GameState,
     ) -> Result<String, ShadowrunError> {
-        let args: serde_json::Value = serde_json::from_str(&tool_call.function.arguments)?;
-        let character_name = args["character_name"]
-            .as_str()
-            .ok_or_else(|| ShadowrunError::Game("Missing character_name".to_string()))?;
-        let operation = args["operation"]
-            .as_str()
-            .ok_or_else(|| ShadowrunError::Game("Missing operation".to_string()))?;
-        let qualities = &args["qualities"];
-
-        let character = game_state
-            .characters
-            .iter_mut()
-            .find(|c| c.name == character_name)
-            .ok_or_else(|| GameError::CharacterNotFound(character_name.to_string()))?;
-
-        let new_qualities: Vec<Quality> = serde_json::from_value(qualities.clone())?;
-
-        let update = CharacterSheetUpdate::UpdateAttribute {
-            attribute: "qualities".to_string(),
-            operation: match operation {
-                "Add" => UpdateOperation::Add(crate::character::Value::VecQuality(new_qualities)),
-                "Remove" => {
-                    UpdateOperation::Remove(crate::character::Value::VecQuality(new_qualities))
-                }
-                _ => {
-                    return Err(ShadowrunError::Game(
-                        "Invalid qualities operation".to_string(),
-                    ))
-                }
-            },
-        };
-        character.apply_update(update)?;
+        let args: QualitiesArgs =
+            tool_args::parse("update_qualities", &tool_call.function.arguments)?;
+        let operation = args.operation.without_modify("update_qualities")?;
+
+        self.update_character(game_state, &args.character_name, |_| {
+            Ok(vec![CharacterSheetUpdate::UpdateAttribute {
+                attribute: AttributeKey::Qualities,
+                operation: operation.with(crate::character::Value::VecQuality(args.qualities)),
+            }])
+        })?;
 
-        if game_state
-            .main_character_sheet
-            .as_ref()
-            .map(|cs| cs.name == character_name)
-            .unwrap_or(false)
-        {
-            game_state.main_character_sheet = Some(character.clone());
-        }
         Ok(format!(
             "Updated qualities for character: {}",
-            character_name
+            args.character_name
         ))
     }
 
@@ -710,40 +1084,21 @@ impl GameAI {
         tool_call: &RunToolCallObject,
         game_state: &mut GameState,
     ) -> Result<String, ShadowrunError> {
-        let args: serde_json::Value = serde_json::from_str(&tool_call.function.arguments)?;
-        let character_name = args["character_name"]
-            .as_str()
-            .ok_or_else(|| ShadowrunError::Game("Missing character_name".to_string()))?;
-        let matrix_attributes = &args["matrix_attributes"];
-
-        let character = game_state
-            .characters
-            .iter_mut()
-            .find(|c| c.name == character_name)
-            .ok_or_else(|| GameError::CharacterNotFound(character_name.to_string()))?;
-
-        let new_matrix_attributes: MatrixAttributes =
-            serde_json::from_value(matrix_attributes.clone())?;
-
-        let update = CharacterSheetUpdate::UpdateAttribute {
-            attribute: "matrix_attributes".to_string(),
-            operation: UpdateOperation::Modify(crate::character::Value::OptionMatrixAttributes(
-                Some(new_matrix_attributes),
-            )),
-        };
-        character.apply_update(update)?;
+        let args: MatrixAttributesArgs =
+            tool_args::parse("update_matrix_attributes", &tool_call.function.arguments)?;
+
+        self.update_character(game_state, &args.character_name, |_| {
+            Ok(vec![CharacterSheetUpdate::UpdateAttribute {
+                attribute: AttributeKey::MatrixAttributes,
+                operation: UpdateOperation::Modify(
+                    crate::character::Value::OptionMatrixAttributes(Some(args.matrix_attributes)),
+                ),
+            }])
+        })?;
 
-        if game_state
-            .main_character_sheet
-            .as_ref()
-            .map(|cs| cs.name == character_name)
-            .unwrap_or(false)
-        {
-            game_state.main_character_sheet = Some(character.clone());
-        }
         Ok(format!(
             "Updated matrix attributes for character: {}",
-            character_name
+            args.character_name
         ))
     }
 
@@ -752,62 +1107,26 @@ impl GameAI {
         tool_call: &RunToolCallObject,
         game_state: &mut GameState,
     ) -> Result<String, ShadowrunError> {
-        let args: serde_json::Value = serde_json::from_str(&tool_call.function.arguments)?;
-        let character_name = args["character_name"]
-            .as_str()
-            .ok_or_else(|| ShadowrunError::Game("Missing character_name".to_string()))?;
-        let operation = args["operation"]
-            .as_str()
-            .ok_or_else(|| ShadowrunError::Game("Missing operation".to_string()))?;
-        let contacts = &args["contacts"];
-
-        let character = game_state
-            .characters
-            .iter_mut()
-            .find(|c| c.name == character_name)
-            .ok_or_else(|| GameError::CharacterNotFound(character_name.to_string()))?;
-
-        // Deserialize into Vec<Contact>
-        let new_contacts_vec: Vec<Contact> = serde_json::from_value(contacts.clone())?;
-
-        // Convert Vec<Contact> into HashMap<String, Contact>
-        let new_contacts: HashMap<String, Contact> = new_contacts_vec
+        let args: ContactsArgs =
+            tool_args::parse("update_contacts", &tool_call.function.arguments)?;
+        let contacts: HashMap<String, Contact> = args
+            .contacts
             .into_iter()
             .map(|contact| (contact.name.clone(), contact))
             .collect();
 
-        let update = CharacterSheetUpdate::UpdateAttribute {
-            attribute: "contacts".to_string(),
-            operation: match operation {
-                "Add" => UpdateOperation::Add(crate::character::Value::HashMapStringContact(
-                    new_contacts,
-                )),
-                "Remove" => UpdateOperation::Remove(crate::character::Value::HashMapStringContact(
-                    new_contacts,
-                )),
-                "Modify" => UpdateOperation::Modify(crate::character::Value::HashMapStringContact(
-                    new_contacts,
-                )),
-                _ => {
-                    return Err(ShadowrunError::Game(
-                        "Invalid contacts operation".to_string(),
-                    ))
-                }
-            },
-        };
-        character.apply_update(update)?;
+        self.update_character(game_state, &args.character_name, |_| {
+            Ok(vec![CharacterSheetUpdate::UpdateAttribute {
+                attribute: AttributeKey::Contacts,
+                operation: args
+                    .operation
+                    .with(crate::character::Value::HashMapStringContact(contacts)),
+            }])
+        })?;
 
-        if game_state
-            .main_character_sheet
-            .as_ref()
-            .map(|cs| cs.name == character_name)
-            .unwrap_or(false)
-        {
-            game_state.main_character_sheet = Some(character.clone());
-        }
         Ok(format!(
             "Updated contacts for character: {}",
-            character_name
+            args.character_name
         ))
     }
 
@@ -816,74 +1135,42 @@ impl GameAI {
         tool_call: &RunToolCallObject,
         game_state: &mut GameState,
     ) -> Result<String, ShadowrunError> {
-        let args: serde_json::Value = serde_json::from_str(&tool_call.function.arguments)?;
-        let character_name = args["character_name"]
-            .as_str()
-            .ok_or_else(|| ShadowrunError::Game("Missing character_name".to_string()))?;
-        let operation = args["operation"]
-            .as_str()
-            .ok_or_else(|| ShadowrunError::Game("Missing operation".to_string()))?;
-        let augmentation_type = args["augmentation_type"]
-            .as_str()
-            .ok_or_else(|| ShadowrunError::Game("Missing augmentation_type".to_string()))?;
-        let augmentations = &args["augmentations"];
-
-        let character = game_state
-            .characters
-            .iter_mut()
-            .find(|c| c.name == character_name)
-            .ok_or_else(|| GameError::CharacterNotFound(character_name.to_string()))?;
-
-        let new_augmentations: Vec<String> = serde_json::from_value(augmentations.clone())?;
-
-        let update = CharacterSheetUpdate::UpdateAttribute {
-            attribute: augmentation_type.to_string(),
-            operation: match operation {
-                "Add" => {
-                    UpdateOperation::Add(crate::character::Value::VecString(new_augmentations))
-                }
-                "Remove" => {
-                    UpdateOperation::Remove(crate::character::Value::VecString(new_augmentations))
-                }
-                _ => {
-                    return Err(ShadowrunError::Game(
-                        "Invalid augmentations operation".to_string(),
-                    ))
-                }
-            },
-        };
-        character.apply_update(update)?;
-
-        if game_state
-            .main_character_sheet
-            .as_ref()
-            .map(|cs| cs.name == character_name)
-            .unwrap_or(false)
-        {
-            game_state.main_character_sheet = Some(character.clone());
-        }
+        let args: AugmentationsArgs =
+            tool_args::parse("update_augmentations", &tool_call.function.arguments)?;
+        let operation = args.operation.without_modify("update_augmentations")?;
+        let attribute = args.augmentation_type.attribute();
+
+        self.update_character(game_state, &args.character_name, |_| {
+            Ok(vec![CharacterSheetUpdate::UpdateAttribute {
+                attribute,
+                operation: operation.with(crate::character::Value::VecString(args.augmentations)),
+            }])
+        })?;
 
         Ok(format!(
-            "{} updated for character '{}'. Operation: {}",
-            augmentation_type, character_name, operation
+            "{} updated for character '{}'. Operation: {:?}",
+            attribute, args.character_name, operation
         ))
     }
 
     // Helper method to parse values based on attribute type
     fn parse_value(
         &self,
-        attribute: &str,
+        attribute: AttributeKey,
         value: &Value,
     ) -> Result<crate::character::Value, ShadowrunError> {
         self.add_debug_message(format!("Parsing value for attribute: {:#?}", attribute));
         match attribute {
-            "name" | "gender" | "backstory" | "lifestyle" => Ok(crate::character::Value::String(
+            AttributeKey::Name
+            | AttributeKey::Gender
+            | AttributeKey::Backstory
+            | AttributeKey::Lifestyle => Ok(crate::character::Value::String(
                 value
                     .as_str()
                     .ok_or_else(|| ShadowrunError::Game("Invalid string value".to_string()))?
                     .to_string(),
             )),
-            "race" => Ok(crate::character::Value::Race(
+            AttributeKey::Race => Ok(crate::character::Value::Race(
                 match value
                     .as_str()
                     .ok_or_else(|| ShadowrunError::Game("Invalid race value".to_string()))?
@@ -896,50 +1183,59 @@ impl GameAI {
                     _ => return Err(ShadowrunError::Game("Invalid race".to_string())),
                 },
             )),
-            "body" | "agility" | "reaction" | "strength" | "willpower" | "logic" | "intuition"
-            | "charisma" | "edge" => Ok(crate::character::Value::U8(
+            AttributeKey::Body
+            | AttributeKey::Agility
+            | AttributeKey::Reaction
+            | AttributeKey::Strength
+            | AttributeKey::Willpower
+            | AttributeKey::Logic
+            | AttributeKey::Intuition
+            | AttributeKey::Charisma
+            | AttributeKey::Edge => Ok(crate::character::Value::U8(
                 value
                     .as_u64()
                     .ok_or_else(|| ShadowrunError::Game("Invalid u8 value".to_string()))?
                     as u8,
             )),
-            "magic" | "resonance" => Ok(crate::character::Value::OptionU8(
+            AttributeKey::Magic | AttributeKey::Resonance => Ok(crate::character::Value::OptionU8(
                 value.as_u64().map(|v| v as u8),
             )),
-            "nuyen" => {
+            AttributeKey::Nuyen => {
                 Ok(crate::character::Value::U32(value.as_u64().ok_or_else(|| {
                     ShadowrunError::Game("Invalid u32 value for nuyen".to_string())
                 })? as u32))
             }
-            "skills" => Ok(crate::character::Value::Skills(
-                serde_json::from_value(value.clone())
-                    .map_err(|e| ShadowrunError::Serialization(e.to_string()))?,
-            )),
-            "knowledge_skills" => Ok(crate::character::Value::HashMapStringU8(
+            AttributeKey::Skills => Ok(crate::character::Value::Skills(
                 serde_json::from_value(value.clone())
                     .map_err(|e| ShadowrunError::Serialization(e.to_string()))?,
             )),
-            "contacts" => Ok(crate::character::Value::HashMapStringContact(
+            AttributeKey::KnowledgeSkills => Ok(crate::character::Value::HashMapStringU8(
                 serde_json::from_value(value.clone())
                     .map_err(|e| ShadowrunError::Serialization(e.to_string()))?,
             )),
-            "qualities" => Ok(crate::character::Value::VecQuality(
+            AttributeKey::Contacts => Ok(crate::character::Value::HashMapStringContact(
                 serde_json::from_value(value.clone())
                     .map_err(|e| ShadowrunError::Serialization(e.to_string()))?,
             )),
-            "cyberware" | "bioware" => Ok(crate::character::Value::VecString(
+            AttributeKey::Qualities => Ok(crate::character::Value::VecQuality(
                 serde_json::from_value(value.clone())
                     .map_err(|e| ShadowrunError::Serialization(e.to_string()))?,
             )),
-            "inventory" => Ok(crate::character::Value::HashMapStringItem(
+            AttributeKey::Cyberware | AttributeKey::Bioware => {
+                Ok(crate::character::Value::VecString(
+                    serde_json::from_value(value.clone())
+                        .map_err(|e| ShadowrunError::Serialization(e.to_string()))?,
+                ))
+            }
+            AttributeKey::Inventory => Ok(crate::character::Value::HashMapStringItem(
                 serde_json::from_value(value.clone())
                     .map_err(|e| ShadowrunError::Serialization(e.to_string()))?,
             )),
-            "matrix_attributes" => Ok(crate::character::Value::OptionMatrixAttributes(
+            AttributeKey::MatrixAttributes => Ok(crate::character::Value::OptionMatrixAttributes(
                 serde_json::from_value(value.clone())
                     .map_err(|e| ShadowrunError::Serialization(e.to_string()))?,
             )),
-            _ => Err(ShadowrunError::Game(format!(
+            AttributeKey::Main => Err(ShadowrunError::Game(format!(
                 "Unsupported attribute: {}",
                 attribute
             ))),
@@ -947,60 +1243,46 @@ impl GameAI {
     }
 
     // Asynchronous method to fetch all messages from a thread, ordered and formatted appropriately.
-    pub async fn fetch_all_messages(&self, thread_id: &str) -> Result<Vec<Message>, AIError> {
-        let mut all_messages = Vec::new();
-        let mut before: Option<String> = None;
-        loop {
-            let mut params = vec![("order", "desc"), ("limit", "100")];
-            if let Some(before_id) = &before {
-                params.push(("before", before_id));
-            }
-            let messages = self
-                .client
-                .threads()
-                .messages(thread_id)
-                .list(&params)
-                .await?;
-
-            for message in messages.data.into_iter().rev() {
-                if let Some(MessageContent::Text(text_content)) = message.content.first() {
-                    let message_type = match message.role {
-                        MessageRole::User => MessageType::User,
-                        MessageRole::Assistant => MessageType::Game,
-                    };
-                    all_messages.push(Message::new(message_type, text_content.text.value.clone()));
-                }
-            }
+    // One page of thread history, newest first from `older_than` (or from the end of the thread).
+    pub async fn fetch_message_page(
+        &self,
+        thread_id: &str,
+        older_than: Option<&str>,
+    ) -> Result<MessagePage, ShadowrunError> {
+        self.backend
+            .list_messages(thread_id, HISTORY_PAGE_SIZE, older_than)
+            .await
+    }
 
-            if messages.has_more {
-                before = messages.first_id;
-            } else {
-                break;
-            }
+    pub async fn fetch_all_messages(
+        &self,
+        thread_id: &str,
+    ) -> Result<Vec<Message>, ShadowrunError> {
+        let mut page = self.fetch_message_page(thread_id, None).await?;
+        let mut all_messages = page.messages;
+        while let Some(cursor) = page.older_than {
+            page = self.fetch_message_page(thread_id, Some(&cursor)).await?;
+            page.messages.append(&mut all_messages);
+            all_messages = std::mem::take(&mut page.messages);
         }
         Ok(all_messages)
     }
 
     // Asynchronous method to retrieve the latest message from a conversation thread.
-    async fn get_latest_message(&self, thread_id: &str) -> Result<String, AIError> {
+    async fn get_latest_message(&self, thread_id: &str) -> Result<String, ShadowrunError> {
         self.add_debug_message(format!(
             "Retrieving latest message from thread: {:#?}",
             thread_id
         ));
-        let messages = self
-            .client
-            .threads()
-            .messages(thread_id)
-            .list(&[("limit", "1")])
-            .await?;
+        let page = self.backend.list_messages(thread_id, 1, None).await?;
 
-        if let Some(latest_message) = messages.data.first() {
-            if let Some(MessageContent::Text(text_content)) = latest_message.content.first() {
-                self.add_debug_message(format!("Latest message: {:#?}", latest_message,));
-                return Ok(text_content.text.value.clone());
+        match page.messages.last() {
+            Some(latest_message) => {
+                self.add_debug_message(format!("Latest message: {:#?}", latest_message.content));
+                Ok(latest_message.content.clone())
             }
+            None => Err(AIError::NoMessageFound.into()),
         }
-        Err(AIError::NoMessageFound)
     }
 
     // Helper methods
@@ -1023,33 +1305,37 @@ impl GameAI {
             "Adding message to thread: {:#?} - {:#?}",
             thread_id, message
         ));
-        let message_request = CreateMessageRequestArgs::default()
-            .role(MessageRole::User)
-            .content(message)
-            .build()?;
-        self.client
-            .threads()
-            .messages(thread_id)
-            .create(message_request)
-            .await?;
-        Ok(())
+        self.backend.add_message(thread_id, message).await
     }
 
     async fn create_run(
         &self,
         thread_id: &str,
         assistant_id: &str,
-    ) -> Result<RunObject, ShadowrunError> {
+        game_state: &mut GameState,
+    ) -> Result<String, ShadowrunError> {
+        // Summarize what slipped out of the run window first; failing only costs some context.
+        let mut archive = game_state.archive.clone();
+        match archivist::bring_up_to_date(self.backend.as_ref(), thread_id, &mut archive).await {
+            Ok(true) => self.add_debug_message("The archivist updated the summary".to_string()),
+            Ok(false) => {}
+            Err(e) => self.add_debug_message(format!("The archivist failed: {}", e)),
+        }
+        if archive != game_state.archive {
+            game_state.record(GameEvent::Archive { archive })?;
+        }
         self.add_debug_message(format!("Creating run for thread: {:#?}", thread_id));
-        let run_request = CreateRunRequestArgs::default()
-            .assistant_id(assistant_id)
-            .build()?;
-        Ok(self
-            .client
-            .threads()
-            .runs(thread_id)
-            .create(run_request)
-            .await?)
+        let run_id = self
+            .backend
+            .create_run(thread_id, assistant_id, &game_state.archive.context())
+            .await?;
+        game_state.record(GameEvent::Run {
+            run_id: Some(run_id.clone()),
+        })?;
+        if let Some(checkpoint) = &self.checkpoint {
+            checkpoint(game_state);
+        }
+        Ok(run_id)
     }
 
     // Asynchronous method to submit output from a tool during a run.
@@ -1060,18 +1346,9 @@ impl GameAI {
         tool_outputs: Vec<ToolsOutputs>,
     ) -> Result<(), ShadowrunError> {
         self.add_debug_message(format!("Submitting tool outputs: {:#?}", tool_outputs));
-        let submit_request = SubmitToolOutputsRunRequest {
-            tool_outputs,
-            stream: None,
-        };
-
-        self.client
-            .threads()
-            .runs(thread_id)
-            .submit_tool_outputs(run_id, submit_request)
-            .await?;
-
-        Ok(())
+        self.backend
+            .submit_tool_outputs(thread_id, run_id, tool_outputs)
+            .await
     }
 
     // Asynchronous method to create a character based on provided arguments, handling attributes and skills.
@@ -1214,14 +1491,7 @@ impl GameAI {
                         let quantity = item.get("quantity")?.as_u64()? as u32;
                         let description = item.get("description")?.as_str()?.to_string();
 
-                        Some((
-                            name.to_string(),
-                            Item {
-                                name: name.to_string(),
-                                quantity,
-                                description,
-                            },
-                        ))
+                        Some((name.to_string(), Item::new(name, quantity, &description)))
                     })
                     .collect::<HashMap<String, Item>>()
             })
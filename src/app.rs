@@ -1,31 +1,83 @@
-use crate::ai::{GameAI, GameConversationState};
+use crate::ai::{GameAI, GameConversationState, MessagePage};
 use crate::ai_response::{create_user_message, UserMessage};
 use crate::app_state::AppState;
-use crate::assistant::{create_assistant, delete_assistant, get_assistant_id};
-use crate::audio::{self, play_audio};
-use crate::character::CharacterSheet;
-use crate::cleanup::cleanup;
-use crate::error::{send_global_error, AppError, ErrorMessage, ShadowrunError};
-use crate::game_state::GameState;
-use crate::image;
+use crate::archetype::{Archetype, ARCHETYPES};
+use crate::archivist;
+use crate::assistant::{self, get_assistant_id};
+use crate::audio::{self, SpeechSettings};
+use crate::branding::BrandingCache;
+use crate::campaign_stats::{self, CampaignStats};
+use crate::character::{generate_npc, CharacterSheet, CharacterSheetUpdate, NpcRole, NPC_ROLES};
+use crate::cleanup::{self, cleanup};
+use crate::cli::CliArgs;
+use crate::clipboard::Clipboard;
+use crate::combat::Combat;
+use crate::commands::{describe_roll, fork_name, parse_input, CommandRegistry, ParsedInput};
+use crate::dice::ShownRoll;
+use crate::emergency::{self, EmergencySave};
+use crate::error::{AppError, ErrorMessage, ShadowrunError};
+use crate::events::GameEvent;
+use crate::export;
+use crate::game_state::{GameState, PlayStats};
+use crate::i18n::LANGUAGES;
+use crate::image::{self, GeneratedImage, SceneImage};
+use crate::journal::{self, SaveError};
+use crate::log_viewer::{LogViewer, LogViewerAction};
+use crate::logging;
+use crate::macros::{MacroEditor, MacroEditorAction, Macros};
 use crate::message::{self, AIMessage, GameMessage, Message, MessageType};
-use crate::save::SaveManager;
-use crate::settings::Settings;
-use crate::settings_state::SettingsState;
+use crate::metrics;
+use crate::mirror::{self, MirrorTarget};
+use crate::paths;
+use crate::pool_hint::{self, PoolHint};
+use crate::review::{self, UpdateReview};
+use crate::roll_popup::{RollPopup, RollPopupAction};
+use crate::ruleset::{RulesetKind, RULESETS};
+use crate::save::{self, SaveManager, SaveSort};
+use crate::selection::{self as text_selection, Point, Selection};
+use crate::settings::{
+    settings_path, InputStyle, Settings, SpectatorMode, SpeechBackend, SubmitKey, TerminalCopy,
+    UpdateMode, BACKUP_COUNTS, CHAT_MODELS, FRAME_RATES, IDLE_FRAME_RATE, LOG_LEVELS,
+    NARRATOR_VOICES, SPEECH_MODELS, SPEECH_SPEEDS, THEMES, TICK_RATES, TRANSCRIPTION_MODELS,
+};
+use crate::settings_state::{
+    SettingsState, ACCESSIBILITY, API_KEY, ARCHIVIST_MODEL, AUDIO_INPUT, AUDIO_OUTPUT, BACKUPS,
+    CONFIRM_UPDATES, DATA_DIR, DEBUG_MODE, DICE_ANIMATION, ELEVENLABS_KEY, ENTER_NEW_LINE,
+    FRAME_RATE, INPUT_DEVICE, INPUT_STYLE, LANGUAGE, LOGS, LOG_LEVEL, MACROS, MENU_ART,
+    MESSAGE_TIMES, MIRROR, MOUSE_SELECTION, NARRATOR_MODEL, NARRATOR_VOICE, OUTPUT_DEVICE,
+    PLAYER_VOICE, RULESET, SAVE_COMPRESSION, SETTINGS_ITEMS, SPECTATOR, SPECTATOR_SHEET,
+    SPEECH_BACKEND, SPEECH_MODEL, SPEECH_SPEED, SUBMIT_KEY, TERMINAL_COPY, THEME, TICK_RATE,
+    UPDATES, USAGE_METRICS, VOICE_LANGUAGE, VOICE_MODEL, WEBHOOK,
+};
+use crate::sheet_diff::sheet_changes;
+use crate::shop::{self, Catalog, Shop, ShopAction};
+use crate::spectator::SpectatorServer;
+use crate::status::{Narration, RunActivity, Shared};
+use crate::tasks::{Jump, TaskKind, TaskStatus, Tasks, Toast};
+use crate::thumbnail::Thumbnails;
+use crate::transcript::{self, TranscriptCache};
+use crate::ui::dice::DiceAnimation;
+use crate::ui::game::HighlightedSection;
+use crate::ui::theme;
 use crate::ui::utils::Spinner;
-use crate::ui::{game, game::HighlightedSection};
+use crate::update;
+use crate::webhook::{self, WebhookEvent};
+use crate::wizard::{CharacterWizard, WizardAction};
 
 use chrono::Local;
-use copypasta::{ClipboardContext, ClipboardProvider};
-use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
 use futures::stream::{FuturesOrdered, StreamExt};
+use rand::Rng;
+use ratatui::layout::{Position, Rect};
 use ratatui::widgets::ListState;
-use ratatui::{layout::Alignment, text::Line};
 use std::borrow::BorrowMut;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::fs::OpenOptions;
 use std::io::Write;
-use std::rc::Rc;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -35,14 +87,117 @@ use tui_input::backend::crossterm::EventHandler;
 use tui_input::Input;
 use tui_input::InputRequest;
 
+#[allow(clippy::large_enum_variant)]
 pub enum AppCommand {
     LoadGame(String),
-    StartNewGame(String),
+    StartNewGame(String, Option<CharacterSeed>),
+    // Branch the current game into a new save with this name.
+    ForkGame(String),
+    // Branch a save from the load menu into the second one, without loading either.
+    DuplicateSave(String, String),
+    // Give the current game's assistant the latest prompt, or a new assistant with it if true.
+    UpgradeAssistant(bool),
+    // Give a save another name, and its assistant too if true.
+    RenameSave(String, String, bool),
     ProcessMessage(String),
     AIResponse(Result<GameMessage, AppError>),
     ApiKeyValidationResult(bool),
     TranscriptionResult(String, TranscriptionTarget),
     TranscriptionError(String),
+    SystemMessage(String),
+    UpdateNotice(String),
+    // A page of older thread history, for the thread it was requested for.
+    OlderMessagesLoaded(String, Result<MessagePage, String>),
+    // The assistant can be reached again after the connection dropped.
+    Reconnected,
+}
+
+// How often to check whether the connection is back while offline.
+pub const RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+
+// A save that failed its integrity check, and the ways it can still be loaded.
+pub struct SaveRecovery {
+    pub save_name: String,
+    pub reason: String,
+    pub readable: bool,
+    pub has_backup: bool,
+}
+
+// The entries of the main menu, in order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MainMenuItem {
+    // Load the save played last, skipping the load menu.
+    Continue(String),
+    NewGame,
+    LoadGame,
+    CreateImage,
+    Settings,
+}
+
+// The backups of one save, to pick one to restore.
+pub struct BackupMenu {
+    pub save_name: String,
+    pub backups: Vec<PathBuf>,
+    pub selected: usize,
+}
+
+// The new name being typed for a save in the load menu.
+pub struct SaveRename {
+    pub save_name: String,
+    pub input: Input,
+    // Whether the assistant on the OpenAI dashboard takes the new name too.
+    pub rename_assistant: bool,
+    pub error: Option<String>,
+}
+
+// A game played this run: its totals when it was first opened, and as last saved.
+struct SessionPlay {
+    started: PlayStats,
+    latest: PlayStats,
+}
+
+// The part of a loaded thread's history that has not been fetched yet.
+pub struct OlderHistory {
+    pub thread_id: String,
+    pub older_than: String,
+    pub loading: bool,
+}
+
+// Starting point for the character of a new game, instead of a blank AI-led creation.
+pub enum CharacterSeed {
+    Archetype(&'static Archetype),
+    // A character finished offline in the creation wizard.
+    Complete(Box<CharacterSheet>),
+}
+
+impl CharacterSeed {
+    pub fn character_sheet(&self) -> CharacterSheet {
+        match self {
+            CharacterSeed::Archetype(archetype) => archetype.builder().build(),
+            CharacterSeed::Complete(sheet) => sheet.as_ref().clone(),
+        }
+    }
+
+    // Extra instructions for the assistant's first turn.
+    pub fn instructions(&self, sheet: &CharacterSheet) -> String {
+        let sheet_json = serde_json::to_string(sheet).unwrap_or_default();
+        match self {
+            CharacterSeed::Archetype(archetype) => format!(
+                " The player chose the {} archetype as a starting template ({}). \
+                Its starting character sheet is: {}. \
+                Help the player personalise it (name, gender, backstory and any tweaks), \
+                then call create_character_sheet with the final character.",
+                archetype.name, archetype.description, sheet_json
+            ),
+            CharacterSeed::Complete(_) => format!(
+                " The player already built their character with the priority system: {}. \
+                Do not recreate it or change its attributes and skills. \
+                Only ask for a backstory if it is missing, \
+                call create_character_sheet with the character, then start the first scene.",
+                sheet_json
+            ),
+        }
+    }
 }
 
 pub enum TranscriptionTarget {
@@ -75,7 +230,17 @@ pub struct App {
     // Menu states
     pub main_menu_state: ListState,
     pub load_game_menu_state: ListState,
+    pub archetype_menu_state: ListState,
+    pub character_wizard: Option<CharacterWizard>,
+    pub log_viewer: Option<LogViewer>,
+    // The game's totals while the stats screen is open.
+    pub campaign_stats: Option<CampaignStats>,
+    pub shop: Option<Shop>,
+    pub macros: Macros,
+    pub macro_editor: Option<MacroEditor>,
+    logs_return_state: AppState,
     pub settings_state: SettingsState,
+    pub commands: CommandRegistry,
 
     // Game state and AI interaction
     pub ai_client: Option<GameAI>,
@@ -85,15 +250,25 @@ pub struct App {
     // User inputs and interaction handling
     pub user_input: Input,
     pub api_key_input: Input,
+    pub webhook_url_input: Input,
+    pub elevenlabs_key_input: Input,
+    pub data_dir_input: Input,
     pub image_prompt: Input,
     pub is_recording: Arc<AtomicBool>,
+    // Where the recording in progress is written, until it is transcribed.
+    pub recording_path: Option<PathBuf>,
 
     // Game content management
     pub game_content: RefCell<Vec<message::Message>>,
     pub visible_messages: usize,
     pub game_content_scroll: usize,
-    pub cached_game_content: Option<Rc<Vec<(Line<'static>, Alignment)>>>,
-    pub cached_content_len: usize,
+    pub transcript: TranscriptCache,
+    // Where the transcript was last drawn, for the mouse, and what is selected in it.
+    pub transcript_area: Option<Rect>,
+    pub selection: Option<Selection>,
+    pub older_history: Option<OlderHistory>,
+    // Set by --mirror; overrides the Mirror Output setting.
+    pub mirror_override: Option<MirrorTarget>,
     pub visible_lines: usize,
     pub total_lines: usize,
     pub message_line_counts: Vec<usize>,
@@ -101,12 +276,15 @@ pub struct App {
 
     // Debugging and logging
     pub debug_info: RefCell<String>,
+    // Pictures drawn in the terminal, e.g. the portrait of the save previewed in the load menu.
+    pub thumbnails: RefCell<Thumbnails>,
+    pub branding: RefCell<BrandingCache>,
 
     // Settings and configurations
     pub settings: Settings,
 
     // Clipboard handling
-    clipboard: ClipboardContext,
+    pub clipboard: Clipboard,
 
     // Asynchronous message handling
     ai_sender: mpsc::UnboundedSender<AIMessage>,
@@ -116,10 +294,69 @@ pub struct App {
     pub backspace_counter: bool,
     pub spinner: Spinner,
     pub spinner_active: bool,
+    // When the player started waiting on the spinner.
+    pub spinner_started: Instant,
+    pub narration: Shared<Narration>,
+    // The narrated line the transcript last scrolled to.
+    pub followed_narration: Option<(u64, usize)>,
+    // The roll in the dice popup, and the ones waiting for it.
+    pub dice_animation: Option<DiceAnimation>,
+    pub queued_rolls: VecDeque<ShownRoll>,
+    pub update_notice: Option<String>,
+    pub pending_recovery: Option<EmergencySave>,
+    pub save_recovery: Option<SaveRecovery>,
+    pub backup_menu: Option<BackupMenu>,
+    pub save_rename: Option<SaveRename>,
+    // The load menu's filter, being typed while `filtering_saves`, and its order.
+    pub save_filter: Input,
+    pub filtering_saves: bool,
+    pub save_sort: SaveSort,
+    recovered_transcript: Option<Vec<Message>>,
+    last_snapshot: Option<Instant>,
+    // Bumped whenever game_content changes, so the emergency copy only copies it when it did.
+    transcript_revision: Cell<u64>,
+    // Since play time was last added to the game on screen, or None out of a game.
+    play_clock: Option<Instant>,
+    // The games played this run, by save name.
+    session_play: BTreeMap<String, SessionPlay>,
     pub last_spinner_update: Instant,
 
     // Last known data
     pub last_known_character_sheet: Option<CharacterSheet>,
+    // The rules of the game on screen, which decide how its sheets are drawn.
+    pub last_known_ruleset: RulesetKind,
+    // The character whose sheet is shown, or None for the player's own.
+    pub viewed_character: Option<String>,
+    // The names of the game's characters and the portrait of the sheet on screen, as last drawn.
+    pub last_known_characters: Vec<String>,
+    pub last_known_portrait: Option<PathBuf>,
+    // The scene gallery beside the transcript, and the scenes it lists as last drawn.
+    pub show_scenes: bool,
+    pub last_known_scenes: Vec<SceneImage>,
+    // The combat tracker as last drawn, while a fight is on.
+    pub last_known_combat: Option<Combat>,
+    // The main sheet as the current turn started, and what the last turn changed on it
+    turn_start_sheet: Option<CharacterSheet>,
+    pub sheet_changes: Vec<String>,
+    pub update_review: Option<UpdateReview>,
+    // A roll from the sheet, waiting for the player to confirm its modifier.
+    pub roll_popup: Option<RollPopup>,
+    // Pictures, narration and transcriptions running in the background.
+    pub tasks: Tasks,
+    // The row selected in the tasks panel, while it is open.
+    pub tasks_panel: Option<usize>,
+    // Notices of the tasks that finished while the player was away from the game.
+    pub toasts: Vec<Toast>,
+    // While the connection is down, the actions typed are kept to be sent, in order, once it is
+    // back; the one being sent is kept until it reaches the assistant.
+    pub offline: bool,
+    pub offline_queue: VecDeque<String>,
+    sending_action: Option<String>,
+    // The last action the Game Master failed to answer, sent again by /retry.
+    failed_action: Option<String>,
+
+    // Read-only stream for people following the session in a browser
+    pub spectator: Option<SpectatorServer>,
 }
 
 impl App {
@@ -131,9 +368,9 @@ impl App {
         let mut main_menu_state = ListState::default();
         main_menu_state.select(Some(0));
 
-        let settings =
-            Settings::load_settings_from_file("./data/settings.json").unwrap_or_default();
+        let settings = Settings::load_settings_from_file(&settings_path()).unwrap_or_default();
         let settings_state = SettingsState::from_settings(&settings);
+        let clipboard = Clipboard::new(settings.terminal_copy);
 
         let mut load_game_menu_state = ListState::default();
         load_game_menu_state.select(Some(0));
@@ -144,7 +381,7 @@ impl App {
             false
         };
 
-        let app = Self {
+        let mut app = Self {
             should_quit: false,
             state: AppState::MainMenu,
             highlighted_section: HighlightedSection::None,
@@ -155,43 +392,108 @@ impl App {
             settings,
             user_input: Input::default(),
             api_key_input: Input::default(),
+            webhook_url_input: Input::default(),
+            elevenlabs_key_input: Input::default(),
+            data_dir_input: Input::default(),
             save_name_input: Input::default(),
             image_prompt: Input::default(),
             input_mode: InputMode::Normal,
             settings_state,
+            commands: CommandRegistry::with_builtins(),
             save_manager: SaveManager::new(),
             load_game_menu_state,
+            archetype_menu_state: ListState::default(),
+            character_wizard: None,
+            log_viewer: None,
+            campaign_stats: None,
+            shop: None,
+            macros: Macros::load(),
+            macro_editor: None,
+            logs_return_state: AppState::MainMenu,
             openai_api_key_valid,
             error_messages: Vec::new(),
             game_content: RefCell::new(Vec::new()),
             game_content_scroll: 0,
-            cached_game_content: None,
-            cached_content_len: 0,
+            transcript: TranscriptCache::new(),
+            transcript_area: None,
+            selection: None,
+            older_history: None,
+            mirror_override: None,
             debug_info: RefCell::new(String::new()),
+            thumbnails: RefCell::new(Thumbnails::default()),
+            branding: RefCell::new(BrandingCache::default()),
             visible_messages: 0,
             total_lines: 0,
             visible_lines: 0,
             message_line_counts: Vec::new(),
-            clipboard: ClipboardContext::new().expect("Failed to initialize clipboard"),
+            clipboard,
             ai_sender,
             current_game_response: None,
             last_user_message: None,
             backspace_counter: false,
             spinner: Spinner::new(),
             spinner_active: false,
+            spinner_started: Instant::now(),
+            narration: Shared::new(Narration::Idle),
+            followed_narration: None,
+            dice_animation: None,
+            queued_rolls: VecDeque::new(),
+            update_notice: None,
+            pending_recovery: emergency::find(),
+            save_recovery: None,
+            backup_menu: None,
+            save_rename: None,
+            save_filter: Input::default(),
+            filtering_saves: false,
+            save_sort: SaveSort::default(),
+            recovered_transcript: None,
+            last_snapshot: None,
+            transcript_revision: Cell::new(0),
+            play_clock: None,
+            session_play: BTreeMap::new(),
             last_spinner_update: Instant::now(),
             current_save_name: Arc::new(RwLock::new(String::new())),
             last_known_character_sheet: None,
+            last_known_ruleset: RulesetKind::default(),
+            viewed_character: None,
+            last_known_characters: Vec::new(),
+            last_known_portrait: None,
+            show_scenes: false,
+            last_known_scenes: Vec::new(),
+            last_known_combat: None,
+            turn_start_sheet: None,
+            sheet_changes: Vec::new(),
+            update_review: None,
+            roll_popup: None,
+            tasks: Tasks::new(),
+            tasks_panel: None,
+            toasts: Vec::new(),
+            offline: false,
+            offline_queue: VecDeque::new(),
+            sending_action: None,
+            failed_action: None,
             is_recording: Arc::new(AtomicBool::new(false)),
+            recording_path: None,
+            spectator: None,
         };
+        app.sync_spectator();
+        app.sync_webhook();
+        theme::set(app.settings.theme);
+        journal::set_compression(app.settings.compress_saves);
+        journal::set_backup_count(app.settings.backup_count);
+        app.sync_metrics();
 
         (app, command_receiver)
     }
 
-    pub fn update_cached_content(&mut self, max_width: usize) {
-        let parsed_content = game::parse_game_content(self, max_width);
-        self.cached_game_content = Some(Rc::new(parsed_content));
-        self.cached_content_len = self.game_content.borrow().len();
+    // Bring the parsed transcript up to date for a width, and recount its lines.
+    pub fn refresh_transcript(&mut self, max_width: usize) {
+        self.transcript.update(
+            &self.game_content.borrow(),
+            max_width,
+            self.settings.accessibility_mode,
+        );
+        self.total_lines = self.transcript.len();
     }
 
     pub async fn initialize_ai_client(&mut self) -> Result<(), AppError> {
@@ -207,41 +509,135 @@ impl App {
             let _ = ai_sender.send(message::AIMessage::Debug(message));
         };
 
-        self.ai_client = Some(GameAI::new(api_key, debug_callback).await?);
+        let mut ai_client = GameAI::new(api_key, debug_callback).await?;
+        ai_client.checkpoint = Some(Arc::new(|game_state: &GameState| {
+            let save = SaveManager {
+                available_saves: Vec::new(),
+                current_save: Some(game_state.clone()),
+                metadata: HashMap::new(),
+            };
+            if let Err(e) = save.save() {
+                log::warn!("Failed to save the game as the run started: {}", e);
+            }
+        }));
+        self.ai_client = Some(ai_client);
+        self.sync_review();
+        self.sync_models();
 
         Ok(())
     }
 
+    // Apply the command line: check for updates, and jump straight into a game if one was given.
+    pub fn launch(&mut self, cli: &CliArgs) {
+        if !cli.no_update {
+            update::spawn_check(self.settings.update_mode, self.command_sender.clone());
+        }
+
+        let command = match (&cli.load, &cli.new) {
+            (Some(save_name), _) => AppCommand::LoadGame(save_name.clone()),
+            (None, Some(save_name)) => AppCommand::StartNewGame(save_name.clone(), None),
+            (None, None) => return,
+        };
+        if let Err(e) = self.command_sender.send(command) {
+            self.add_message(Message::new(
+                MessageType::System,
+                format!("Failed to send startup command: {:#?}", e),
+            ));
+            return;
+        }
+        self.state = AppState::InGame;
+    }
+
     pub fn process_message(&mut self, message: String) {
+        if self.offline {
+            self.offline_queue.push_back(message);
+            self.add_message(Message::new(
+                MessageType::System,
+                format!(
+                    "Offline: your action is queued ({} waiting) and will be sent when the \
+                     connection is back.",
+                    self.offline_queue.len()
+                ),
+            ));
+            return;
+        }
+        self.sending_action = Some(message.clone());
         let user_message = create_user_message(&self.settings.language, &message);
         let formatted_message = serde_json::to_string(&user_message).unwrap();
 
         self.start_spinner();
+        // The AI edits the sheet while it runs, so remember it as it was before.
+        self.turn_start_sheet = self
+            .current_game
+            .as_ref()
+            .and_then(|game| game.try_lock().ok()?.main_character_sheet.clone())
+            .or_else(|| {
+                self.last_known_character_sheet
+                    .clone()
+                    .filter(|_| self.viewed_character.is_none())
+            });
 
         let ai_client = self.ai_client.clone();
         let current_game = self.current_game.clone();
         let sender = self.command_sender.clone();
 
-        tokio::spawn(async move {
+        let turn = tokio::spawn(async move {
             if let (Some(mut ai), Some(game_state)) = (ai_client, current_game) {
                 let mut game_state = game_state.lock().await;
-                let result = ai
-                    .send_message(&formatted_message, &mut game_state)
+                ai.send_message(&formatted_message, &mut game_state)
                     .await
-                    .map_err(AppError::Shadowrun);
-                let _ = sender.send(AppCommand::AIResponse(result));
+                    .map_err(AppError::Shadowrun)
             } else {
-                let _ = sender.send(AppCommand::AIResponse(Err(AppError::NoCurrentGame)));
+                Err(AppError::NoCurrentGame)
             }
         });
+        // A turn that panics, e.g. in a tool, still answers, so the spinner stops.
+        tokio::spawn(async move {
+            let result = turn.await.unwrap_or_else(|e| {
+                Err(AppError::Shadowrun(ShadowrunError::Unknown(format!(
+                    "the turn stopped unexpectedly: {}",
+                    e
+                ))))
+            });
+            let _ = sender.send(AppCommand::AIResponse(result));
+        });
+    }
+
+    // Send the action the Game Master last failed to answer once more.
+    pub fn retry_failed_action(&mut self) {
+        let text = match self.failed_action.take() {
+            Some(action) => match self.command_sender.send(AppCommand::ProcessMessage(action)) {
+                Ok(()) => "Sending your action again...".to_string(),
+                Err(e) => format!("Failed to send the action again: {}", e),
+            },
+            None => "There is no failed action to send again.".to_string(),
+        };
+        self.add_message(Message::new(MessageType::System, text));
     }
 
     pub async fn handle_ai_response(&mut self, result: Result<GameMessage, AppError>) {
         self.stop_spinner();
+        self.open_update_review();
         self.add_debug_message(format!("Spinner: {:#?}", self.spinner_active));
 
+        let sent = self.sending_action.take();
+        if let Err(AppError::Shadowrun(ShadowrunError::Offline(e))) = &result {
+            metrics::record_error("Offline");
+            self.add_debug_message(format!("Offline: {}", e));
+            if let Some(action) = sent {
+                self.offline_queue.push_front(action);
+            }
+            self.go_offline();
+            return;
+        }
+        // The next queued action waits for this one's answer, so the story stays in order.
+        if let Some(action) = self.offline_queue.pop_front() {
+            let _ = self.command_sender.send(AppCommand::ProcessMessage(action));
+        }
+
         match result {
             Ok(game_message) => {
+                self.failed_action = None;
                 self.add_debug_message(format!(
                     "Received game message from AI: {:#?}",
                     game_message
@@ -249,20 +645,29 @@ impl App {
 
                 let game_message_json = serde_json::to_string(&game_message).unwrap();
                 self.add_debug_message(format!("Game message: {:#?}", game_message_json.clone()));
-                self.add_message(Message::new(MessageType::Game, game_message_json.clone()));
+                let message = Message::new(MessageType::Game, game_message_json.clone());
+                let spoken_message = transcript::fingerprint(&message);
+                self.add_message(message);
+                webhook::post(WebhookEvent::GameMessage(Box::new(game_message.clone())));
 
                 if self.settings.audio_output_enabled {
+                    metrics::record("voice_output");
                     self.add_debug_message(format!(
                         "generating audio from {:#?}",
                         game_message.fluff.clone()
                     ));
                     if let Some(ai_client) = self.ai_client.clone() {
                         let mut game_message_clone = game_message.clone();
-                        let save_name = match self.save_manager.current_save.clone() {
-                            Some(game_state) => game_state.save_name,
-                            None => "unknown".to_string(),
-                        };
-                        tokio::spawn(async move {
+                        let narration = self.narration.clone();
+                        let speech = SpeechSettings::from_settings(&self.settings);
+                        let output_device = self.settings.output_device.clone();
+                        let jump = self.settings.last_save.clone().map(Jump::Game);
+                        let action = sent.clone();
+                        narration.set(Narration::Generating);
+                        let lines = game_message.fluff.dialogue.len();
+                        let description =
+                            format!("{} line{}", lines, if lines == 1 { "" } else { "s" });
+                        self.tasks.spawn(TaskKind::Speech, description, async move {
                             game_message_clone
                                 .fluff
                                 .speakers
@@ -271,54 +676,58 @@ impl App {
 
                             let mut audio_futures = FuturesOrdered::new();
 
-                            for (index, fluff_line) in
-                                game_message_clone.fluff.dialogue.iter_mut().enumerate()
-                            {
-                                let voice = game_message_clone
-                                    .fluff
-                                    .speakers
-                                    .iter()
-                                    .find(|s| s.index == fluff_line.speaker_index)
-                                    .and_then(|s| s.voice.clone())
-                                    .expect("Voice not found for speaker");
-
+                            // Generate the sentences in parallel, keeping track of the line
+                            for clip in audio::narration_clips(
+                                &game_message_clone.fluff,
+                                action.as_deref(),
+                                &speech,
+                            ) {
                                 let ai_client = ai_client.clone();
-                                let text = fluff_line.text.clone();
-                                let save_name = save_name.clone();
-
-                                // Generate the audio in parallel, keeping track of the index
+                                let speech = speech.clone();
                                 audio_futures.push_back(async move {
-                                    let result = audio::generate_audio(
-                                        &ai_client.client,
-                                        &save_name,
-                                        &text,
-                                        voice,
-                                    )
-                                    .await;
-                                    (result, index)
+                                    let result =
+                                        audio::generate_audio(&ai_client.client, &clip, &speech)
+                                            .await;
+                                    (result, clip.line)
                                 });
                             }
 
-                            // Process the results in order
+                            // Play each clip as soon as it and those before it are ready, following
+                            // along in the transcript, while the rest are still generating. If the
+                            // task is cancelled, the player stops after the clips it already has.
+                            let (clips, playlist) = std::sync::mpsc::channel();
+                            let player = tokio::task::spawn_blocking(move || {
+                                audio::play_narration(playlist, output_device.as_deref(), |line| {
+                                    narration.set(Narration::Playing {
+                                        message: spoken_message,
+                                        line,
+                                    })
+                                });
+                                narration.set(Narration::Idle);
+                            });
+                            let mut failed = 0;
                             while let Some((result, index)) = audio_futures.next().await {
-                                if let Ok(path) = result {
-                                    game_message_clone.fluff.dialogue[index].audio = Some(path);
+                                match result {
+                                    Ok(path) => {
+                                        let _ = clips.send((index, path));
+                                    }
+                                    Err(e) => {
+                                        log::warn!("Failed to generate narration: {}", e);
+                                        failed += 1;
+                                    }
                                 }
                             }
-
-                            // Play audio sequentially
-                            for file in game_message_clone.fluff.dialogue.iter() {
-                                if let Some(audio_path) = &file.audio {
-                                    let _status = play_audio(audio_path.clone());
-                                }
+                            drop(clips);
+                            let _ = player.await;
+                            match failed {
+                                0 => Ok(jump),
+                                _ => Err(format!("{} clip(s) could not be generated", failed)),
                             }
                         });
                     }
                 }
 
                 // Update the UI
-                self.cached_game_content = None; // Force recalculation of cached content
-                self.cached_content_len = 0;
                 self.scroll_to_bottom();
 
                 if let Some(character_sheet) = game_message.character_sheet {
@@ -326,6 +735,7 @@ impl App {
                     self.update_character_sheet(character_sheet).await;
                 }
                 self.add_debug_message("Updated character sheet".to_string());
+                self.report_sheet_changes().await;
 
                 if let Err(e) = self.save_current_game().await {
                     self.add_debug_message(format!("Failed to save game: {:#?}", e));
@@ -337,55 +747,147 @@ impl App {
                 self.add_debug_message("saved game".to_string());
             }
             Err(e) => {
+                metrics::record_error("AI response");
                 self.add_debug_message(format!("Error: {:#?}", e));
-                self.add_message(Message::new(
-                    MessageType::System,
-                    format!("AI Error: {:#?}", e),
-                ));
+                log::warn!("The turn failed: {}", e);
+                let mut text = e.turn_failure();
+                self.failed_action = sent.filter(|_| e.retryable());
+                if self.failed_action.is_some() {
+                    text.push_str(" Type /retry to send your action again.");
+                }
+                self.add_message(Message::new(MessageType::System, text));
             }
         }
     }
 
     fn handle_paste(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        if let Ok(contents) = self.clipboard.get_contents() {
-            match self.state {
-                AppState::InGame => {
-                    for c in contents.chars() {
-                        self.user_input.handle(InputRequest::InsertChar(c));
-                    }
-                }
-                AppState::InputSaveName => {
-                    for c in contents.chars() {
-                        self.save_name_input.handle(InputRequest::InsertChar(c));
-                    }
-                }
-                AppState::InputApiKey => {
-                    for c in contents.chars() {
-                        self.api_key_input.handle(InputRequest::InsertChar(c));
-                    }
+        self.warn_about_clipboard();
+        if let Some(contents) = self.clipboard.get() {
+            self.paste(&contents);
+        }
+        Ok(())
+    }
+
+    pub fn copy(&mut self, text: &str) {
+        self.clipboard.set(text);
+        self.warn_about_clipboard();
+    }
+
+    fn warn_about_clipboard(&mut self) {
+        if let Some(warning) = self.clipboard.take_warning() {
+            self.add_message(Message::new(MessageType::System, warning.to_string()));
+        }
+    }
+
+    // Put pasted text, from the clipboard or pasted into the terminal, into the input being
+    // typed in. Only the player's message keeps its line breaks; the other inputs take one line.
+    pub fn paste(&mut self, text: &str) {
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        let line = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let input = match self.state {
+            // Outside the input, keys are commands, so what is pasted there is dropped.
+            AppState::InGame if self.input_mode == InputMode::Editing => {
+                let text = text.trim_end_matches('\n');
+                for c in text.chars() {
+                    self.user_input.handle(InputRequest::InsertChar(c));
                 }
-                _ => {} // Other states don't have editable inputs
+                return;
             }
+            AppState::CreateImage if self.input_mode == InputMode::Editing => {
+                &mut self.image_prompt
+            }
+            AppState::InputSaveName => &mut self.save_name_input,
+            AppState::InputApiKey => &mut self.api_key_input,
+            AppState::InputWebhookUrl => &mut self.webhook_url_input,
+            AppState::InputElevenLabsKey => &mut self.elevenlabs_key_input,
+            AppState::InputDataDir => &mut self.data_dir_input,
+            AppState::LoadMenu => match self.save_rename.as_mut() {
+                Some(rename) => &mut rename.input,
+                None if self.filtering_saves => &mut self.save_filter,
+                None => return,
+            },
+            AppState::CharacterWizard => match self.character_wizard.as_mut() {
+                Some(wizard) => &mut wizard.name,
+                None => return,
+            },
+            AppState::Macros => match self
+                .macro_editor
+                .as_mut()
+                .and_then(MacroEditor::active_input)
+            {
+                Some(input) => input,
+                None => return,
+            },
+            AppState::Logs => match self.log_viewer.as_mut() {
+                Some(viewer) => &mut viewer.search,
+                None => return,
+            },
+            _ => return, // Other states don't have editable inputs
+        };
+        for c in line.chars() {
+            input.handle(InputRequest::InsertChar(c));
+        }
+        if self.state == AppState::LoadMenu && self.filtering_saves {
+            self.load_game_menu_state.select(Some(0));
         }
-        Ok(())
     }
 
     pub fn handle_input(&mut self, key: KeyEvent) {
+        if key.code == KeyCode::F(2) && self.followed_toast().is_some() {
+            self.follow_toast();
+            return;
+        }
+        if self.simple_input() && self.input_mode != InputMode::Recording {
+            if key.code == KeyCode::Char('r') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                self.start_recording();
+                return;
+            }
+            self.input_mode = self.simple_input_mode(key);
+        }
+        if key.code == KeyCode::F(12)
+            && self.input_mode == InputMode::Normal
+            && self.state != AppState::Logs
+        {
+            self.open_logs();
+            return;
+        }
         match self.input_mode {
             InputMode::Normal => match self.state {
                 AppState::MainMenu => self.handle_main_menu_input(key),
                 AppState::InGame => self.handle_in_game_input(key),
-                AppState::LoadMenu => self.handle_load_game_input(key),
+                AppState::LoadMenu => {
+                    self.handle_load_game_input(key);
+                    self.describe_selected_save();
+                }
                 AppState::CreateImage => self.handle_create_image_input(key),
                 AppState::SettingsMenu => self.handle_settings_input(key),
                 AppState::InputApiKey => self.handle_api_key_input(key),
+                AppState::InputWebhookUrl => self.handle_webhook_url_input(key),
+                AppState::InputElevenLabsKey => self.handle_elevenlabs_key_input(key),
+                AppState::InputDataDir => self.handle_data_dir_input(key),
                 AppState::InputSaveName => self.handle_save_name_input(key),
+                AppState::ChooseArchetype => self.handle_archetype_input(key),
+                AppState::CharacterWizard => self.handle_character_wizard_input(key),
+                AppState::Logs => self.handle_logs_input(key),
+                AppState::Macros => self.handle_macros_input(key),
+                AppState::CampaignStats => self.handle_campaign_stats_input(key),
+                AppState::Shop => self.handle_shop_input(key),
             },
             InputMode::Editing => match self.state {
                 AppState::InGame => self.handle_in_game_editing(key),
                 AppState::InputSaveName => self.handle_save_name_editing(key),
                 AppState::InputApiKey => self.handle_api_key_editing(key),
                 AppState::CreateImage => self.handle_create_image_editing(key),
+                // These type in Normal mode too; Enter and Esc are gone to it by now.
+                AppState::InputWebhookUrl => self.handle_webhook_url_input(key),
+                AppState::InputElevenLabsKey => self.handle_elevenlabs_key_input(key),
+                AppState::InputDataDir => self.handle_data_dir_input(key),
+                AppState::Macros => self.handle_macros_input(key),
                 _ => {} // Other states don't have editing mode
             },
             InputMode::Recording => {
@@ -399,11 +901,161 @@ impl App {
                 }
             }
         }
+        self.settle_input_mode();
+    }
+
+    // The mouse in the game: the wheel scrolls the transcript, and a drag over it selects text,
+    // which is copied when the button is let go.
+    pub fn handle_mouse(&mut self, event: MouseEvent) {
+        if self.state != AppState::InGame {
+            return;
+        }
+        match event.kind {
+            MouseEventKind::ScrollUp => (0..3).for_each(|_| self.scroll_up()),
+            MouseEventKind::ScrollDown => (0..3).for_each(|_| self.scroll_down()),
+            MouseEventKind::Down(MouseButton::Left) => {
+                self.selection = self
+                    .transcript_point(event.column, event.row, false)
+                    .map(Selection::new);
+            }
+            MouseEventKind::Drag(MouseButton::Left) if self.selection.is_some() => {
+                let head = self.transcript_point(event.column, event.row, true);
+                if let (Some(selection), Some(head)) = (self.selection.as_mut(), head) {
+                    selection.head = head;
+                }
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                if let Some(text) = self.selected_text() {
+                    self.copy(&text);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // The transcript cell under the mouse, or None outside the transcript. While dragging, a cell
+    // past its top or bottom scrolls it, and is taken as the nearest line.
+    fn transcript_point(&mut self, column: u16, row: u16, dragging: bool) -> Option<Point> {
+        let area = self.transcript_area?;
+        if self.transcript.is_empty() || (!dragging && !area.contains(Position::new(column, row))) {
+            return None;
+        }
+        if dragging && row < area.y {
+            self.scroll_up();
+        } else if dragging && row >= area.bottom() {
+            self.scroll_down();
+        }
+        let row = row.clamp(area.y, area.bottom().saturating_sub(1)) - area.y;
+        let column = column.clamp(area.x, area.right().saturating_sub(1)) - area.x;
+        Some(Point {
+            line: (self.game_content_scroll + row as usize).min(self.transcript.len() - 1),
+            column,
+        })
+    }
+
+    // The text of the selection, or None without one or for a click that selected nothing.
+    pub fn selected_text(&self) -> Option<String> {
+        let selection = self.selection.filter(|selection| !selection.is_empty())?;
+        let area = self.transcript_area?;
+        let (start, end) = selection.ordered();
+        let lines = self.transcript.lines(start.line, end.line - start.line + 1);
+        let text = text_selection::selected_text(&selection, &lines, start.line, area.width);
+        (!text.trim().is_empty()).then_some(text)
+    }
+
+    // Whether the text inputs are always typing (the Simple input mode) rather than modal.
+    pub fn simple_input(&self) -> bool {
+        self.settings.input_style == InputStyle::Simple
+    }
+
+    // Whether this screen's text input takes the keys in the Simple input mode: the ones with an
+    // input, unless a popup is open over it.
+    fn has_simple_input(&self) -> bool {
+        match self.state {
+            AppState::InGame => {
+                self.update_review.is_none()
+                    && self.roll_popup.is_none()
+                    && self.tasks_panel.is_none()
+            }
+            AppState::InputSaveName
+            | AppState::CreateImage
+            | AppState::InputApiKey
+            | AppState::InputWebhookUrl
+            | AppState::InputElevenLabsKey
+            | AppState::InputDataDir => true,
+            // The macro form, not the list, whose letters are commands.
+            AppState::Macros => self
+                .macro_editor
+                .as_ref()
+                .is_some_and(|editor| editor.editing.is_some()),
+            _ => false,
+        }
+    }
+
+    // In the Simple input mode the input takes every key but those a typed text can't use, which
+    // go to the screen's Normal mode keys: Enter sends (in the game, unless another submit key is
+    // set or Enter starts new lines), Esc leaves, and F12, the arrows and Tab (with no test to add) do what they do there.
+    fn simple_input_mode(&self, key: KeyEvent) -> InputMode {
+        if !self.has_simple_input() {
+            return InputMode::Normal;
+        }
+        let in_game = self.state == AppState::InGame;
+        // Ctrl+Enter sends from the input, as in the Vim mode.
+        let modified = key
+            .modifiers
+            .intersects(KeyModifiers::SHIFT | KeyModifiers::ALT | KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc | KeyCode::F(12) => InputMode::Normal,
+            KeyCode::Enter if !in_game || (!modified && self.enter_sends()) => InputMode::Normal,
+            KeyCode::Up | KeyCode::Down | KeyCode::PageUp | KeyCode::PageDown if in_game => {
+                InputMode::Normal
+            }
+            KeyCode::Tab if in_game && self.pool_hint().is_none() => InputMode::Normal,
+            KeyCode::Left | KeyCode::Right
+                if in_game && self.highlighted_section != HighlightedSection::None =>
+            {
+                InputMode::Normal
+            }
+            _ => InputMode::Editing,
+        }
+    }
+
+    // Whether `key` is the submit key, which sends the message from Normal mode.
+    fn is_submit_key(&self, key: KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match self.settings.submit_key {
+            SubmitKey::Enter => key.code == KeyCode::Enter,
+            SubmitKey::CtrlS => ctrl && key.code == KeyCode::Char('s'),
+            SubmitKey::CtrlEnter => ctrl && key.code == KeyCode::Enter,
+        }
+    }
+
+    // Whether `key` sends the message right from the input: Ctrl+Enter always, and the submit
+    // key when it isn't Enter.
+    fn sends_from_input(&self, key: KeyEvent) -> bool {
+        (key.code == KeyCode::Enter && key.modifiers.contains(KeyModifiers::CONTROL))
+            || (self.settings.submit_key != SubmitKey::Enter && self.is_submit_key(key))
+    }
+
+    // Whether a plain Enter sends in the Simple input mode, rather than starting a new line or
+    // only expanding macros.
+    pub fn enter_sends(&self) -> bool {
+        self.settings.submit_key == SubmitKey::Enter && !self.settings.enter_new_line
+    }
+
+    // Back to typing after a key handled in Normal mode, in the Simple input mode.
+    fn settle_input_mode(&mut self) {
+        if self.simple_input() && self.input_mode == InputMode::Normal && self.has_simple_input() {
+            self.input_mode = InputMode::Editing;
+        }
     }
 
     pub fn start_recording(&mut self) {
+        metrics::record("voice_input");
         self.is_recording.store(true, Ordering::SeqCst);
-        audio::start_recording(&self.is_recording);
+        let path = paths::new_recording_file();
+        self.recording_path = Some(path.clone());
+        audio::start_recording(&self.is_recording, path, self.settings.input_device.clone());
         self.input_mode = InputMode::Recording;
     }
 
@@ -414,8 +1066,12 @@ impl App {
         std::thread::sleep(Duration::from_millis(100));
 
         self.input_mode = InputMode::Normal;
+        let Some(recording_path) = self.recording_path.take() else {
+            return;
+        };
 
         if self.ai_client.is_none() {
+            let _ = std::fs::remove_file(&recording_path);
             self.add_message(Message::new(
                 MessageType::System,
                 "AI client not initialized. Cannot transcribe audio.".to_string(),
@@ -427,34 +1083,47 @@ impl App {
         let ai_client = self.ai_client.clone();
         let state = self.state.clone();
         let sender = self.command_sender.clone();
-
-        tokio::spawn(async move {
-            if let Some(ai_client) = ai_client {
-                match audio::transcribe_audio(&ai_client.client).await {
-                    Ok(transcription) => {
-                        let command = match state {
-                            AppState::InGame => AppCommand::TranscriptionResult(
-                                transcription,
-                                TranscriptionTarget::UserInput,
-                            ),
-                            AppState::InputSaveName => AppCommand::TranscriptionResult(
-                                transcription,
-                                TranscriptionTarget::SaveNameInput,
-                            ),
-                            AppState::CreateImage => AppCommand::TranscriptionResult(
-                                transcription,
-                                TranscriptionTarget::ImagePrompt,
-                            ),
-                            _ => return,
-                        };
-                        let _ = sender.send(command);
-                    }
-                    Err(e) => {
-                        let _ = sender.send(AppCommand::TranscriptionError(format!("{}", e)));
+        let model = self.settings.transcription_model.clone();
+        let language = self.settings.transcription_language_code();
+
+        self.tasks
+            .spawn(TaskKind::Transcription, "Voice input", async move {
+                if let Some(ai_client) = ai_client {
+                    let transcription = audio::transcribe_audio(
+                        &ai_client.client,
+                        &recording_path,
+                        &model,
+                        language,
+                    )
+                    .await;
+                    let _ = std::fs::remove_file(&recording_path);
+                    match transcription {
+                        Ok(transcription) => {
+                            let command = match state {
+                                AppState::InGame => AppCommand::TranscriptionResult(
+                                    transcription,
+                                    TranscriptionTarget::UserInput,
+                                ),
+                                AppState::InputSaveName => AppCommand::TranscriptionResult(
+                                    transcription,
+                                    TranscriptionTarget::SaveNameInput,
+                                ),
+                                AppState::CreateImage => AppCommand::TranscriptionResult(
+                                    transcription,
+                                    TranscriptionTarget::ImagePrompt,
+                                ),
+                                _ => return Ok(None),
+                            };
+                            let _ = sender.send(command);
+                        }
+                        Err(e) => {
+                            let _ = sender.send(AppCommand::TranscriptionError(format!("{}", e)));
+                            return Err(e.to_string());
+                        }
                     }
                 }
-            }
-        });
+                Ok(None)
+            });
     }
 
     pub async fn update_save_name(&self, new_name: String) {
@@ -471,13 +1140,9 @@ impl App {
             KeyCode::Esc => {
                 self.input_mode = InputMode::Normal;
             }
-            KeyCode::Char('v') => {
-                if key.modifiers.contains(KeyModifiers::CONTROL) {
-                    if let Err(e) = self.handle_paste() {
-                        self.add_debug_message(format!("Failed to paste: {:#?}", e));
-                    }
-                } else {
-                    self.save_name_input.handle_event(&Event::Key(key));
+            KeyCode::Char('v') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if let Err(e) = self.handle_paste() {
+                    self.add_debug_message(format!("Failed to paste: {:#?}", e));
                 }
             }
             _ => {
@@ -499,21 +1164,9 @@ impl App {
                     self.state = AppState::MainMenu;
                     self.save_name_input.reset();
                 }
-                KeyCode::Enter => {
-                    if !self.save_name_input.value().is_empty() {
-                        self.game_content.borrow_mut().clear();
-                        self.current_game = None;
-                        if let Err(e) = self.command_sender.send(AppCommand::StartNewGame(
-                            self.save_name_input.value().to_string(),
-                        )) {
-                            self.add_message(Message::new(
-                                MessageType::System,
-                                format!("Failed to send start new game command: {:#?}", e),
-                            ));
-                        }
-                        self.save_name_input.reset();
-                        self.state = AppState::InGame;
-                    }
+                KeyCode::Enter if !self.save_name_input.value().is_empty() => {
+                    self.archetype_menu_state.select(Some(0));
+                    self.state = AppState::ChooseArchetype;
                 }
                 _ => {}
             },
@@ -521,13 +1174,9 @@ impl App {
                 KeyCode::Esc => {
                     self.input_mode = InputMode::Normal;
                 }
-                KeyCode::Char('v') => {
-                    if key.modifiers.contains(KeyModifiers::CONTROL) {
-                        if let Err(e) = self.handle_paste() {
-                            self.add_debug_message(format!("Failed to paste: {:#?}", e));
-                        }
-                    } else {
-                        self.save_name_input.handle_event(&Event::Key(key));
+                KeyCode::Char('v') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    if let Err(e) = self.handle_paste() {
+                        self.add_debug_message(format!("Failed to paste: {:#?}", e));
                     }
                 }
                 _ => {
@@ -541,104 +1190,497 @@ impl App {
         }
     }
 
-    fn handle_api_key_editing(&mut self, key: KeyEvent) {
+    fn handle_archetype_input(&mut self, key: KeyEvent) {
+        // Entry 0 is the blank AI-led creation, entry 1 the offline wizard, the archetypes follow.
+        let entries = ARCHETYPES.len() + 2;
+        let selected = self.archetype_menu_state.selected().unwrap_or(0);
         match key.code {
-            KeyCode::Enter => {
-                // Handle API key submission
-                self.input_mode = InputMode::Normal;
-            }
+            KeyCode::Up => self
+                .archetype_menu_state
+                .select(Some((selected + entries - 1) % entries)),
+            KeyCode::Down => self
+                .archetype_menu_state
+                .select(Some((selected + 1) % entries)),
             KeyCode::Esc => {
-                self.input_mode = InputMode::Normal;
+                self.state = AppState::InputSaveName;
             }
-            KeyCode::Char('v') => {
-                if key.modifiers.contains(KeyModifiers::CONTROL) {
-                    if let Err(e) = self.handle_paste() {
-                        self.add_debug_message(format!("Failed to paste: {:#?}", e));
-                    }
-                } else {
-                    self.api_key_input.handle_event(&Event::Key(key));
-                }
+            KeyCode::Enter if selected == 1 => {
+                self.character_wizard = Some(CharacterWizard::new());
+                self.state = AppState::CharacterWizard;
             }
-            _ => {
-                self.api_key_input.handle_event(&Event::Key(key));
+            KeyCode::Enter => {
+                let seed = selected
+                    .checked_sub(2)
+                    .map(|index| CharacterSeed::Archetype(&ARCHETYPES[index]));
+                self.start_seeded_game(seed);
             }
+            _ => {}
         }
     }
 
-    fn handle_api_key_input(&mut self, key: KeyEvent) {
-        match key.code {
-            KeyCode::Enter => {
-                if !self.api_key_input.value().is_empty() {
-                    let api_key = self.api_key_input.value().to_string();
-                    self.settings.openai_api_key = Some(api_key.clone());
-
-                    let sender = self.command_sender.clone();
-                    tokio::spawn(async move {
-                        let is_valid = Settings::validate_api_key(&api_key).await;
-                        let _ = sender.send(AppCommand::ApiKeyValidationResult(is_valid));
-                    });
-
-                    self.state = AppState::SettingsMenu;
-                }
-            }
-            KeyCode::Esc => {
-                self.state = AppState::SettingsMenu;
+    fn handle_character_wizard_input(&mut self, key: KeyEvent) {
+        if key.code == KeyCode::Char('v') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            if let Err(e) = self.handle_paste() {
+                self.add_debug_message(format!("Failed to paste: {:#?}", e));
             }
-            KeyCode::Char('v') => {
-                if key.modifiers.contains(KeyModifiers::CONTROL) {
-                    if let Err(e) = self.handle_paste() {
-                        self.add_debug_message(format!("Failed to paste: {:#?}", e));
-                    }
-                } else {
-                    self.api_key_input.handle_event(&Event::Key(key));
-                }
+            return;
+        }
+        let Some(wizard) = self.character_wizard.as_mut() else {
+            self.state = AppState::ChooseArchetype;
+            return;
+        };
+        match wizard.handle_key(key) {
+            WizardAction::None => {}
+            WizardAction::Cancel => {
+                self.character_wizard = None;
+                self.state = AppState::ChooseArchetype;
             }
-            _ => {
-                self.api_key_input.handle_event(&Event::Key(key));
+            WizardAction::Finish(sheet) => {
+                self.character_wizard = None;
+                self.start_seeded_game(Some(CharacterSeed::Complete(sheet)));
             }
         }
     }
 
-    fn handle_in_game_editing(&mut self, key: KeyEvent) {
-        match key.code {
-            KeyCode::Enter => {
-                self.input_mode = InputMode::Normal;
-            }
-            KeyCode::Esc => {
-                self.input_mode = InputMode::Normal;
-            }
-            KeyCode::Char('v') => {
-                if key.modifiers.contains(KeyModifiers::CONTROL) {
-                    if let Err(e) = self.handle_paste() {
-                        self.add_debug_message(format!("Failed to paste: {:#?}", e));
-                    }
-                } else {
-                    self.user_input.handle_event(&Event::Key(key));
+    // Show the statistics of the game being played.
+    pub fn open_campaign_stats(&mut self) -> Result<(), String> {
+        let game = self.current_game.clone().ok_or("There is no game yet.")?;
+        let game_state = game
+            .try_lock()
+            .map_err(|_| "The game is busy, try again in a moment.")?;
+        self.campaign_stats = Some(campaign_stats::compute(&game_state));
+        self.state = AppState::CampaignStats;
+        Ok(())
+    }
+
+    fn handle_campaign_stats_input(&mut self, key: KeyEvent) {
+        if matches!(key.code, KeyCode::Esc | KeyCode::Char('q')) {
+            self.campaign_stats = None;
+            self.state = AppState::InGame;
+        }
+    }
+
+    // Open the gear shop for the player's character.
+    pub fn open_shop(&mut self) -> Result<(), String> {
+        let game = self.current_game.clone().ok_or("There is no game yet.")?;
+        let game_state = game
+            .try_lock()
+            .map_err(|_| "The game is busy, try again in a moment.")?;
+        if !game_state.ruleset.rules().shadowrun_sheet() {
+            return Err("The shop sells Shadowrun gear, so it is closed in freeform games.".into());
+        }
+        if game_state.main_character_sheet.is_none() {
+            return Err("Create your character before going shopping.".into());
+        }
+        self.shop = Some(Shop::new(Catalog::load()?));
+        self.state = AppState::Shop;
+        Ok(())
+    }
+
+    fn handle_shop_input(&mut self, key: KeyEvent) {
+        let Some(open) = self.shop.as_mut() else {
+            self.state = AppState::InGame;
+            return;
+        };
+        match open.handle_key(key) {
+            ShopAction::None => {}
+            ShopAction::Buy(index) => {
+                let message = self.buy(index).unwrap_or_else(|e| e);
+                if let Some(open) = self.shop.as_mut() {
+                    open.message = Some(message);
                 }
             }
-            _ => {
-                // Let tui_input handle all other key events
-                self.user_input.handle_event(&Event::Key(key));
+            ShopAction::Close => {
+                self.shop = None;
+                self.state = AppState::InGame;
             }
         }
     }
 
-    fn handle_in_game_input(&mut self, key: KeyEvent) {
-        match self.input_mode {
-            InputMode::Normal => match key.code {
-                KeyCode::Char('e') => {
-                    self.input_mode = InputMode::Editing;
-                }
-                KeyCode::Char('r') => {
-                    self.start_recording();
-                }
-                KeyCode::Esc if (self.highlighted_section != HighlightedSection::None) => {
-                    self.highlighted_section = HighlightedSection::None;
-                }
+    // Buy the shop's item at `index` for the main character, and tell the assistant.
+    fn buy(&mut self, index: usize) -> Result<String, String> {
+        let Some(open) = self.shop.as_ref() else {
+            return Err("The shop is closed.".to_string());
+        };
+        let item = open.catalog.items[index].clone();
+        let quantity = open.quantity;
+        let game = self.current_game.clone().ok_or("There is no game yet.")?;
+        let mut game_state = game
+            .try_lock()
+            .map_err(|_| "The game is busy, try again in a moment.")?;
+        let sheet = game_state
+            .main_character_sheet
+            .as_ref()
+            .ok_or("There is no character to buy for.")?;
+        let character = sheet.name.clone();
+        let updates = shop::purchase(&item, quantity, sheet).map_err(|e| e.to_string())?;
+        for update in updates {
+            game_state.record(GameEvent::SheetUpdate {
+                character: character.clone(),
+                update,
+            })?;
+        }
+        let nuyen = game_state
+            .main_character_sheet
+            .as_ref()
+            .map_or(0, |sheet| sheet.nuyen);
+        let bought = format!(
+            "Bought {} x {} for {}¥, {}¥ left.",
+            quantity,
+            item.name,
+            item.cost as u64 * quantity as u64,
+            nuyen
+        );
+        metrics::record("shop/purchase");
+
+        if let (Some(spectator), Some(sheet)) = (&self.spectator, &game_state.main_character_sheet)
+        {
+            spectator.publish_sheet(sheet);
+        }
+        self.save_manager.current_save = Some(game_state.clone());
+        let save_manager = self.save_manager.clone();
+        drop(game_state);
+        if let Err(e) = save_manager.save() {
+            self.add_message(Message::new(
+                MessageType::System,
+                format!("Failed to save game: {}", e),
+            ));
+        }
+        self.add_message(Message::new(MessageType::System, bought.clone()));
+
+        let note = shop::purchase_note(&character, &item, quantity);
+        let ai_client = self.ai_client.clone();
+        let sender = self.command_sender.clone();
+        tokio::spawn(async move {
+            let Some(ai) = ai_client else {
+                return;
+            };
+            if let Err(e) = ai.tell(&note, &mut *game.lock().await).await {
+                let _ = sender.send(AppCommand::SystemMessage(format!(
+                    "Could not tell the game master about the purchase: {}",
+                    e
+                )));
+            }
+        });
+        Ok(bought)
+    }
+
+    fn open_logs(&mut self) {
+        self.logs_return_state = self.state.clone();
+        self.log_viewer = Some(LogViewer::new());
+        self.state = AppState::Logs;
+    }
+
+    fn handle_logs_input(&mut self, key: KeyEvent) {
+        if key.code == KeyCode::Char('v') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            if let Err(e) = self.handle_paste() {
+                self.add_debug_message(format!("Failed to paste: {:#?}", e));
+            }
+            return;
+        }
+        let Some(viewer) = self.log_viewer.as_mut() else {
+            self.state = self.logs_return_state.clone();
+            return;
+        };
+        if let LogViewerAction::Close = viewer.handle_key(key) {
+            self.log_viewer = None;
+            self.state = self.logs_return_state.clone();
+        }
+    }
+
+    fn handle_macros_input(&mut self, key: KeyEvent) {
+        if key.code == KeyCode::Char('v') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            if let Err(e) = self.handle_paste() {
+                self.add_debug_message(format!("Failed to paste: {:#?}", e));
+            }
+            return;
+        }
+        let Some(editor) = self.macro_editor.as_mut() else {
+            self.state = AppState::SettingsMenu;
+            return;
+        };
+        if let MacroEditorAction::Close = editor.handle_key(key) {
+            if let Some(editor) = self.macro_editor.take() {
+                self.macros = editor.macros;
+            }
+            self.state = AppState::SettingsMenu;
+        }
+    }
+
+    // Swap "!name" macros in the player input for their text, so the player sees what is sent.
+    fn expand_macros(&mut self) -> bool {
+        let expanded = self.macros.expand(self.user_input.value());
+        if expanded == self.user_input.value() {
+            return false;
+        }
+        metrics::record("macro");
+        self.user_input = Input::new(expanded);
+        true
+    }
+
+    // The likely test for what the player is typing, with their own character's pool.
+    pub fn pool_hint(&self) -> Option<PoolHint> {
+        if !self.last_known_ruleset.rules().shadowrun_sheet() {
+            return None;
+        }
+        let main = self
+            .current_game
+            .as_ref()
+            .and_then(|game| game.try_lock().ok()?.main_character_sheet.clone());
+        let sheet = match (&main, &self.viewed_character) {
+            (Some(sheet), _) => sheet,
+            // The sheet last drawn is the player's unless another character is viewed.
+            (None, None) => self.last_known_character_sheet.as_ref()?,
+            (None, Some(_)) => return None,
+        };
+        pool_hint::suggest(self.user_input.value(), sheet)
+    }
+
+    fn insert_pool_hint(&mut self) {
+        if let Some(hint) = self.pool_hint() {
+            let text = format!("{} {}", self.user_input.value().trim_end(), hint.tag());
+            self.user_input = Input::new(text);
+        }
+    }
+
+    fn start_seeded_game(&mut self, seed: Option<CharacterSeed>) {
+        self.game_content.borrow_mut().clear();
+        self.transcript_changed();
+        self.current_game = None;
+        if let Err(e) = self.command_sender.send(AppCommand::StartNewGame(
+            self.save_name_input.value().to_string(),
+            seed,
+        )) {
+            self.add_message(Message::new(
+                MessageType::System,
+                format!("Failed to send start new game command: {:#?}", e),
+            ));
+        }
+        self.save_name_input.reset();
+        self.state = AppState::InGame;
+    }
+
+    fn handle_api_key_editing(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => {
+                // Handle API key submission
+                self.input_mode = InputMode::Normal;
+            }
+            KeyCode::Esc => {
+                self.input_mode = InputMode::Normal;
+            }
+            KeyCode::Char('v') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if let Err(e) = self.handle_paste() {
+                    self.add_debug_message(format!("Failed to paste: {:#?}", e));
+                }
+            }
+            _ => {
+                self.api_key_input.handle_event(&Event::Key(key));
+            }
+        }
+    }
+
+    fn handle_api_key_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => {
+                if !self.api_key_input.value().is_empty() {
+                    let api_key = self.api_key_input.value().to_string();
+                    self.settings.openai_api_key = Some(api_key.clone());
+
+                    let sender = self.command_sender.clone();
+                    tokio::spawn(async move {
+                        let is_valid = Settings::validate_api_key(&api_key).await;
+                        let _ = sender.send(AppCommand::ApiKeyValidationResult(is_valid));
+                    });
+
+                    self.state = AppState::SettingsMenu;
+                }
+            }
+            KeyCode::Esc => {
+                self.state = AppState::SettingsMenu;
+            }
+            KeyCode::Char('v') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if let Err(e) = self.handle_paste() {
+                    self.add_debug_message(format!("Failed to paste: {:#?}", e));
+                }
+            }
+            _ => {
+                self.api_key_input.handle_event(&Event::Key(key));
+            }
+        }
+    }
+
+    fn handle_webhook_url_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => {
+                let url = self.webhook_url_input.value().trim().to_string();
+                // An empty URL clears the webhook.
+                self.settings.discord_webhook_url = if url.is_empty() { None } else { Some(url) };
+                self.settings_state.selected_options[WEBHOOK] =
+                    if self.settings.discord_webhook_url.is_some() {
+                        1
+                    } else {
+                        0
+                    };
+                self.apply_settings();
+                self.state = AppState::SettingsMenu;
+            }
+            KeyCode::Esc => {
+                self.state = AppState::SettingsMenu;
+            }
+            KeyCode::Char('v') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if let Err(e) = self.handle_paste() {
+                    self.add_debug_message(format!("Failed to paste: {:#?}", e));
+                }
+            }
+            _ => {
+                self.webhook_url_input.handle_event(&Event::Key(key));
+            }
+        }
+    }
+
+    fn handle_elevenlabs_key_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => {
+                let api_key = self.elevenlabs_key_input.value().trim().to_string();
+                // An empty key clears it.
+                self.settings.elevenlabs_api_key = (!api_key.is_empty()).then_some(api_key);
+                self.apply_settings();
+                self.state = AppState::SettingsMenu;
+            }
+            KeyCode::Esc => {
+                self.state = AppState::SettingsMenu;
+            }
+            KeyCode::Char('v') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if let Err(e) = self.handle_paste() {
+                    self.add_debug_message(format!("Failed to paste: {:#?}", e));
+                }
+            }
+            _ => {
+                self.elevenlabs_key_input.handle_event(&Event::Key(key));
+            }
+        }
+    }
+
+    fn handle_data_dir_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => {
+                let dir = self.data_dir_input.value().trim().to_string();
+                // An empty folder goes back to the platform's.
+                self.settings.data_dir = (!dir.is_empty()).then(|| PathBuf::from(dir));
+                self.apply_settings();
+                // The data directory is picked once at startup, so the change waits for a restart.
+                if paths::data_dir_changed(self.settings.data_dir.as_deref()) {
+                    self.toasts.push(Toast {
+                        text: "Restart Sharad to use the new data folder".to_string(),
+                        jump: None,
+                        shown: Instant::now(),
+                    });
+                }
+                self.state = AppState::SettingsMenu;
+            }
+            KeyCode::Esc => {
+                self.state = AppState::SettingsMenu;
+            }
+            KeyCode::Char('v') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if let Err(e) = self.handle_paste() {
+                    self.add_debug_message(format!("Failed to paste: {:#?}", e));
+                }
+            }
+            _ => {
+                self.data_dir_input.handle_event(&Event::Key(key));
+            }
+        }
+    }
+
+    fn handle_in_game_editing(&mut self, key: KeyEvent) {
+        match key.code {
+            // Ctrl+Enter (or Ctrl+S when it is the submit key) sends without leaving the input and
+            // Shift+Enter starts a new line, as does Enter with Enter While Typing. Most terminals
+            // only tell them from Enter with the kitty keyboard protocol; Alt+Enter is a new line
+            // everywhere.
+            _ if self.sends_from_input(key) => {
+                // Expanded macros are shown first, as with Enter.
+                if !self.user_input.value().is_empty() && !self.expand_macros() {
+                    self.submit_user_input();
+                }
+            }
+            KeyCode::Enter
+                if self.settings.enter_new_line
+                    || key
+                        .modifiers
+                        .intersects(KeyModifiers::SHIFT | KeyModifiers::ALT) =>
+            {
+                self.user_input.handle(InputRequest::InsertChar('\n'));
+            }
+            KeyCode::Enter => {
+                self.expand_macros();
+                self.input_mode = InputMode::Normal;
+            }
+            KeyCode::Esc => {
+                self.input_mode = InputMode::Normal;
+            }
+            KeyCode::Tab => self.insert_pool_hint(),
+            KeyCode::Char('v') => {
+                if key.modifiers.contains(KeyModifiers::CONTROL) {
+                    if let Err(e) = self.handle_paste() {
+                        self.add_debug_message(format!("Failed to paste: {:#?}", e));
+                    }
+                } else {
+                    self.user_input.handle_event(&Event::Key(key));
+                }
+            }
+            _ => {
+                // Let tui_input handle all other key events
+                self.user_input.handle_event(&Event::Key(key));
+            }
+        }
+    }
+
+    fn handle_in_game_input(&mut self, key: KeyEvent) {
+        if self.update_review.is_some() {
+            self.handle_update_review_input(key);
+            return;
+        }
+        if self.roll_popup.is_some() {
+            self.handle_roll_popup_input(key);
+            return;
+        }
+        if self.tasks_panel.is_some() {
+            self.handle_tasks_panel_input(key);
+            return;
+        }
+        match self.input_mode {
+            InputMode::Normal => match key.code {
+                KeyCode::Char('e') => {
+                    self.input_mode = InputMode::Editing;
+                }
+                KeyCode::Char('r') => {
+                    self.start_recording();
+                }
+                KeyCode::Esc if (self.highlighted_section != HighlightedSection::None) => {
+                    self.highlighted_section = HighlightedSection::None;
+                }
                 KeyCode::Esc => {
+                    // Save the time played, unless the AI is busy with the game.
+                    if let Some(game) = self.current_game.clone() {
+                        if let Ok(mut game_state) = game.try_lock() {
+                            self.clock_play_time(&mut game_state);
+                            let mut save_manager = self.save_manager.clone();
+                            save_manager.current_save = Some(game_state.clone());
+                            if let Err(e) = save_manager.save() {
+                                self.add_debug_message(format!("Failed to save game: {:#?}", e));
+                            }
+                        }
+                    }
+                    self.play_clock = None;
+                    self.selection = None;
                     self.game_content.borrow_mut().clear();
+                    self.transcript_changed();
                     self.current_game = None;
                     self.last_known_character_sheet = None;
+                    self.viewed_character = None;
+                    self.sheet_changes.clear();
                     self.user_input.reset();
                     self.state = AppState::MainMenu;
                     self.save_manager.available_saves = SaveManager::scan_save_files();
@@ -647,10 +1689,17 @@ impl App {
                         "Game paused. Returned to main menu.".to_string(),
                     ))
                 }
-                KeyCode::Enter => {
-                    if !self.user_input.value().is_empty() {
-                        self.submit_user_input();
-                    }
+                KeyCode::Enter
+                    if matches!(self.highlighted_section, HighlightedSection::Skill(_)) =>
+                {
+                    self.open_roll_popup()
+                }
+                // Expanded macros are shown first; the next press sends them.
+                _ if self.is_submit_key(key)
+                    && !self.user_input.value().is_empty()
+                    && !self.expand_macros() =>
+                {
+                    self.submit_user_input();
                 }
                 KeyCode::PageUp => {
                     for _ in 0..self.visible_lines {
@@ -666,45 +1715,45 @@ impl App {
                 KeyCode::Down => self.scroll_down(),
 
                 KeyCode::Tab => self.cycle_highlighted_section(),
+                KeyCode::Left | KeyCode::Right
+                    if matches!(
+                        self.highlighted_section,
+                        HighlightedSection::InventoryItem(_)
+                    ) =>
+                {
+                    self.select_inventory_item(key.code == KeyCode::Right)
+                }
+                KeyCode::Left | KeyCode::Right
+                    if matches!(self.highlighted_section, HighlightedSection::Skill(_)) =>
+                {
+                    self.select_skill(key.code == KeyCode::Right)
+                }
+                KeyCode::Char('s')
+                    if matches!(
+                        self.highlighted_section,
+                        HighlightedSection::InventoryItem(_)
+                    ) =>
+                {
+                    self.cycle_item_state()
+                }
+                KeyCode::Char('t') => self.open_tasks_panel(),
+                KeyCode::Char('x') => self.export_character_sheet(),
+                KeyCode::Char('n') => self.cycle_viewed_character(),
+                KeyCode::Char('p') => self.open_portrait(),
+                KeyCode::Char('g') => self.show_scenes = !self.show_scenes,
+                KeyCode::Char('v') => self.open_latest_scene(),
 
                 KeyCode::Home => {
                     self.game_content_scroll = 0;
+                    self.load_older_messages();
                 }
                 KeyCode::End => {
                     self.game_content_scroll = self.total_lines.saturating_sub(self.visible_lines);
                 }
                 _ => {}
             },
-            InputMode::Editing => match key.code {
-                KeyCode::Esc => {
-                    self.input_mode = InputMode::Normal;
-                }
-                KeyCode::Enter => {
-                    self.input_mode = InputMode::Normal;
-                }
-                KeyCode::Char('v') => {
-                    if key.modifiers.contains(KeyModifiers::CONTROL) {
-                        if let Err(e) = self.handle_paste() {
-                            self.add_debug_message(format!("Failed to paste: {:#?}", e));
-                        }
-                    } else {
-                        self.user_input.handle_event(&Event::Key(key));
-                    }
-                }
-                _ => {
-                    self.user_input.handle_event(&Event::Key(key));
-                }
-            },
-            InputMode::Recording => {
-                match key.code {
-                    KeyCode::Esc => {
-                        self.stop_recording();
-                    }
-                    _ => {
-                        // Ignore other keys during recording
-                    }
-                }
-            }
+            // handle_input sends typing and recording to their own handlers.
+            InputMode::Editing | InputMode::Recording => {}
         }
     }
 
@@ -723,87 +1772,42 @@ impl App {
                 "API Key Validated, Thank you.".to_string(),
             ));
         }
-        if let Err(e) = self.settings.save_to_file("./data/settings.json") {
+        if let Err(e) = self.settings.save_to_file(&settings_path()) {
             self.add_debug_message(format!("Failed to save settings: {:#?}", e));
         }
     }
 
     fn handle_settings_input(&mut self, key: KeyEvent) {
+        let setting_count = SETTINGS_ITEMS.len();
         match key.code {
             KeyCode::Up => {
                 self.settings_state.selected_setting =
-                    (self.settings_state.selected_setting + 4) % 5; // Wrap around 5 settings
+                    (self.settings_state.selected_setting + setting_count - 1) % setting_count;
             }
             KeyCode::Down => {
                 self.settings_state.selected_setting =
-                    (self.settings_state.selected_setting + 1) % 5; // Wrap around 5 settings
+                    (self.settings_state.selected_setting + 1) % setting_count;
             }
             KeyCode::Left => {
                 let current_setting = self.settings_state.selected_setting;
-                if current_setting == 0 {
-                    // Language setting
-                    let current_option = self.settings_state.selected_options[current_setting];
-                    self.settings_state.selected_options[current_setting] =
-                        (current_option + 2) % 3;
-                } else if current_setting != 1 {
-                    // Not API Key setting
-                    self.settings_state.selected_options[current_setting] =
-                        1 - self.settings_state.selected_options[current_setting];
-                }
-                self.apply_settings();
+                self.cycle_setting(current_setting, false);
             }
             KeyCode::Right => {
                 let current_setting = self.settings_state.selected_setting;
-                if current_setting == 0 {
-                    // Language setting
-                    let current_option = self.settings_state.selected_options[current_setting];
-                    self.settings_state.selected_options[current_setting] =
-                        (current_option + 1) % 3;
-                } else if current_setting != 1 {
-                    // Not API Key setting
-                    self.settings_state.selected_options[current_setting] =
-                        1 - self.settings_state.selected_options[current_setting];
-                }
-                self.apply_settings();
+                self.cycle_setting(current_setting, true);
             }
             KeyCode::Enter => {
-                let current_setting = self.settings_state.selected_setting;
-                if current_setting == 1 {
-                    // API Key setting
-                    self.state = AppState::InputApiKey;
-                } else {
-                    let current_option = self.settings_state.selected_options[current_setting];
-                    let new_option = match current_setting {
-                        0 => (current_option + 1) % 3, // Language (3 options)
-                        2..=4 => 1 - current_option,   // Toggle settings (2 options)
-                        _ => current_option,
-                    };
-                    self.settings_state.selected_options[current_setting] = new_option;
-                    self.apply_settings();
-                }
+                self.activate_setting(self.settings_state.selected_setting);
             }
             KeyCode::Esc => {
                 self.state = AppState::MainMenu;
             }
             KeyCode::Char(c) => {
                 if let Some(digit) = c.to_digit(10) {
-                    if digit <= 5 {
-                        self.settings_state.selected_setting = (digit - 1) as usize;
-                        let current_setting = self.settings_state.selected_setting;
-                        if current_setting == 1 {
-                            // API Key setting
-                            self.state = AppState::InputApiKey;
-                        } else {
-                            let current_option =
-                                self.settings_state.selected_options[current_setting];
-                            let new_option = match current_setting {
-                                0 => (current_option + 1) % 3, // Language (3 options)
-                                2..=5 => 1 - current_option,   // Toggle settings (2 options)
-                                _ => current_option,
-                            };
-                            self.settings_state.selected_options[current_setting] = new_option;
-                        }
-                        self.apply_settings();
+                    let digit = digit as usize;
+                    if (1..=setting_count).contains(&digit) {
+                        self.settings_state.selected_setting = digit - 1;
+                        self.activate_setting(digit - 1);
                     }
                 }
             }
@@ -811,28 +1815,88 @@ impl App {
         }
     }
 
+    // Enter on a row: open its input screen if it has one, otherwise step to the next option.
+    fn activate_setting(&mut self, setting: usize) {
+        if setting == API_KEY {
+            self.state = AppState::InputApiKey;
+        } else if setting == WEBHOOK {
+            self.webhook_url_input = Input::new(
+                self.settings
+                    .discord_webhook_url
+                    .clone()
+                    .unwrap_or_default(),
+            );
+            self.state = AppState::InputWebhookUrl;
+        } else if setting == ELEVENLABS_KEY {
+            self.elevenlabs_key_input =
+                Input::new(self.settings.elevenlabs_api_key.clone().unwrap_or_default());
+            self.state = AppState::InputElevenLabsKey;
+        } else if setting == DATA_DIR {
+            self.data_dir_input = Input::new(
+                self.settings
+                    .data_dir
+                    .as_ref()
+                    .map(|dir| dir.display().to_string())
+                    .unwrap_or_default(),
+            );
+            self.state = AppState::InputDataDir;
+        } else if setting == LOGS {
+            self.open_logs();
+        } else if setting == MACROS {
+            self.macro_editor = Some(MacroEditor::new(self.macros.clone()));
+            self.state = AppState::Macros;
+        } else {
+            self.cycle_setting(setting, true);
+        }
+    }
+
+    fn cycle_setting(&mut self, setting: usize, forward: bool) {
+        // Device rows step through the devices present now rather than fixed options.
+        if setting == INPUT_DEVICE {
+            self.settings.input_device = audio::next_device(
+                &audio::input_device_names(),
+                self.settings.input_device.as_deref(),
+                forward,
+            );
+        } else if setting == OUTPUT_DEVICE {
+            self.settings.output_device = audio::next_device(
+                &audio::output_device_names(),
+                self.settings.output_device.as_deref(),
+                forward,
+            );
+        } else {
+            self.settings_state.cycle_option(setting, forward);
+        }
+        self.apply_settings();
+    }
+
     fn handle_load_game_input(&mut self, key: KeyEvent) {
-        match key.code {
-            KeyCode::Enter => {
-                if let Some(selected) = self.load_game_menu_state.selected() {
-                    if selected < self.save_manager.available_saves.len() {
-                        if let Err(e) = self.command_sender.send(AppCommand::LoadGame(
-                            self.save_manager.available_saves[selected].clone(),
-                        )) {
-                            self.add_message(Message::new(
-                                MessageType::System,
-                                format!("Failed to send load game command: {:#?}", e),
-                            ));
-                        } else {
-                            // Add a message to indicate that the game is being loaded
-                            self.add_message(Message::new(
-                                MessageType::System,
-                                "Loading game...".to_string(),
-                            ));
-                        }
-                    }
+        if self.backup_menu.is_some() {
+            self.handle_backup_menu_input(key);
+            return;
+        }
+        if self.save_recovery.is_some() {
+            self.handle_save_recovery_input(key);
+            return;
+        }
+        if self.save_rename.is_some() {
+            self.handle_save_rename_input(key);
+            return;
+        }
+        if self.filtering_saves {
+            self.handle_save_filter_input(key);
+            return;
+        }
+        match key.code {
+            KeyCode::Enter => {
+                if let Some(save_name) = self.selected_save() {
+                    self.send_load_game(save_name);
                 }
             }
+            KeyCode::Esc if !self.save_filter.value().is_empty() => {
+                self.save_filter.reset();
+                self.load_game_menu_state.select(Some(0));
+            }
             KeyCode::Esc => {
                 self.state = AppState::MainMenu;
             }
@@ -846,7 +1910,7 @@ impl App {
             }
             KeyCode::Backspace => {
                 if self.backspace_counter {
-                    if !self.save_manager.available_saves.is_empty() {
+                    if self.selected_save().is_some() {
                         let _ = self.delete_selected_save();
                     }
                     self.backspace_counter = false;
@@ -854,63 +1918,136 @@ impl App {
                     self.backspace_counter = true;
                 }
             }
+            KeyCode::Char('/') => {
+                self.backspace_counter = false;
+                self.filtering_saves = true;
+            }
+            KeyCode::Char('s') => {
+                let selected = self.selected_save();
+                self.save_sort = self.save_sort.next();
+                self.select_listed_save(selected.as_deref());
+            }
+            KeyCode::Char('r') => {
+                if let Some(save_name) = self.selected_save() {
+                    self.open_backup_menu(save_name);
+                }
+            }
+            KeyCode::Char('p') => self.open_save_thumbnail(),
+            KeyCode::Char('n') => {
+                if let Some(save_name) = self.selected_save() {
+                    self.save_rename = Some(SaveRename {
+                        input: Input::new(save_name.clone()),
+                        save_name,
+                        rename_assistant: true,
+                        error: None,
+                    });
+                }
+            }
+            KeyCode::Char('d') => {
+                if let Some(save_name) = self.selected_save() {
+                    let copy_name = fork_name(&save_name, "", &self.save_manager.available_saves)
+                        .unwrap_or_else(|_| format!("{} fork", save_name));
+                    self.start_spinner();
+                    let text = match self
+                        .command_sender
+                        .send(AppCommand::DuplicateSave(save_name, copy_name.clone()))
+                    {
+                        Ok(()) => format!("Duplicating the save into '{}'...", copy_name),
+                        Err(e) => {
+                            self.stop_spinner();
+                            format!("Failed to send duplicate command: {:#?}", e)
+                        }
+                    };
+                    self.add_message(Message::new(MessageType::System, text));
+                }
+            }
 
             KeyCode::Char(c) => {
-                if let Some(digit) = c.to_digit(10) {
-                    let selected = ((digit as usize).saturating_sub(1))
-                        % self.save_manager.available_saves.len();
+                let listed = self.listed_saves();
+                if let Some(digit) = c.to_digit(10).filter(|_| !listed.is_empty()) {
+                    let selected = ((digit as usize).saturating_sub(1)) % listed.len();
                     self.load_game_menu_state.select(Some(selected));
-                    let save_name = self.save_manager.available_saves[selected].clone();
-                    if let Err(e) = self.command_sender.send(AppCommand::LoadGame(save_name)) {
-                        self.add_message(Message::new(
-                            MessageType::System,
-                            format!("Failed to send load game command: {:#?}", e),
-                        ));
-                    } else {
-                        self.add_message(Message::new(
-                            MessageType::System,
-                            "Loading game...".to_string(),
-                        ));
-                    }
+                    self.send_load_game(listed[selected].clone());
                 }
             }
             _ => {}
         }
     }
 
-    fn handle_main_menu_input(&mut self, key: KeyEvent) {
+    // Typing after '/': the list narrows to the saves that match as the filter is typed.
+    fn handle_save_filter_input(&mut self, key: KeyEvent) {
         match key.code {
-            KeyCode::Enter => {
-                match self.main_menu_state.selected() {
-                    Some(0) => {
-                        // Start New Game
-                        self.state = AppState::InputSaveName;
-                        self.save_name_input.reset(); // Clear any previous input
-                    }
-                    Some(1) => {
-                        // Load Game
-                        self.state = AppState::LoadMenu;
-                        self.save_manager.available_saves = SaveManager::scan_save_files();
-                        self.load_game_menu_state.select(Some(0));
-                    }
-                    Some(2) => {
-                        if self.openai_api_key_valid {
-                            self.state = AppState::CreateImage;
-                        } else {
-                            self.state = AppState::InputApiKey;
-                        }
-                    }
-                    Some(3) => {
-                        self.state = AppState::SettingsMenu;
-                    }
-                    _ => {}
-                }
+            KeyCode::Enter | KeyCode::Down => self.filtering_saves = false,
+            KeyCode::Esc => {
+                self.filtering_saves = false;
+                self.save_filter.reset();
             }
+            _ => {
+                self.save_filter.handle_event(&Event::Key(key));
+            }
+        }
+        self.load_game_menu_state.select(Some(0));
+    }
+
+    fn send_load_game(&mut self, save_name: String) {
+        if let Err(e) = self.command_sender.send(AppCommand::LoadGame(save_name)) {
+            self.add_message(Message::new(
+                MessageType::System,
+                format!("Failed to send load game command: {:#?}", e),
+            ));
+        } else {
+            // Add a message to indicate that the game is being loaded
+            self.add_message(Message::new(
+                MessageType::System,
+                "Loading game...".to_string(),
+            ));
+        }
+    }
+
+    // The saves in the load menu, as filtered and sorted there.
+    pub fn listed_saves(&self) -> Vec<String> {
+        self.save_manager
+            .listed(self.save_filter.value(), self.save_sort)
+    }
+
+    pub fn selected_save(&self) -> Option<String> {
+        let selected = self.load_game_menu_state.selected()?;
+        self.listed_saves().into_iter().nth(selected)
+    }
+
+    // Put the load menu's selection on `save_name`, or on the first save if it isn't listed.
+    fn select_listed_save(&mut self, save_name: Option<&str>) {
+        let selected = save_name
+            .and_then(|save_name| {
+                self.listed_saves()
+                    .iter()
+                    .position(|listed| listed == save_name)
+            })
+            .unwrap_or(0);
+        self.load_game_menu_state.select(Some(selected));
+        self.describe_selected_save();
+    }
+
+    // What the load menu shows of the selected save, loading it if it was written without it.
+    fn describe_selected_save(&mut self) {
+        if let Some(save_name) = self.selected_save() {
+            self.save_manager.describe(&save_name);
+        }
+    }
+
+    fn handle_main_menu_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => self.select_main_menu_option(),
             KeyCode::Up => self.navigate_main_menu(-1),
             KeyCode::Down => self.navigate_main_menu(1),
-            KeyCode::Char(c) if ('1'..='4').contains(&c) => self.select_main_menu_by_char(c),
+            KeyCode::Char(c) if ('1'..='9').contains(&c) => self.select_main_menu_by_char(c),
+            KeyCode::Char('r') if self.pending_recovery.is_some() => self.recover_emergency_save(),
+            KeyCode::Char('d') if self.pending_recovery.is_some() => self.discard_emergency_save(),
             KeyCode::Char('q') => {
                 cleanup();
+                if let Some(summary) = self.session_summary() {
+                    println!("{}", summary);
+                }
                 std::process::exit(0);
             }
             _ => {}
@@ -929,99 +2066,546 @@ impl App {
                 KeyCode::Esc => self.state = AppState::MainMenu,
                 KeyCode::Enter => {
                     let prompt = self.image_prompt.value().to_owned();
+                    metrics::record("image");
+
+                    self.tasks
+                        .spawn(TaskKind::Image, prompt.clone(), async move {
+                            let path = image::generate_and_save_image(&prompt)
+                                .await
+                                .map_err(|e| e.to_string())?;
+                            webhook::post(WebhookEvent::Image(path.clone()));
+                            Ok(Some(Jump::Image(path)))
+                        });
+                    self.add_message(Message::new(
+                        MessageType::System,
+                        "Generating image...".to_string(),
+                    ));
+                    self.image_prompt.reset();
+                    self.state = AppState::MainMenu;
+                }
+                _ => {}
+            },
+            InputMode::Editing => match key.code {
+                KeyCode::Esc => {
+                    self.input_mode = InputMode::Normal;
+                }
+                KeyCode::Char('v') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    if let Err(e) = self.handle_paste() {
+                        self.add_debug_message(format!("Failed to paste: {:#?}", e));
+                    }
+                }
+                _ => {
+                    self.image_prompt.handle_event(&Event::Key(key));
+                }
+            },
+            InputMode::Recording if key.code == KeyCode::Esc => {
+                self.stop_recording();
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_create_image_editing(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => {
+                // Handle save name submission
+                self.input_mode = InputMode::Normal;
+            }
+            KeyCode::Esc => {
+                self.input_mode = InputMode::Normal;
+            }
+            KeyCode::Char('v') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if let Err(e) = self.handle_paste() {
+                    self.add_debug_message(format!("Failed to paste: {:#?}", e));
+                }
+            }
+            _ => {
+                self.image_prompt.handle_event(&Event::Key(key));
+            }
+        }
+    }
+
+    fn cycle_highlighted_section(&mut self) {
+        // This is a basic implementation. You might want to adjust this based on your layout.
+        self.highlighted_section = match self.highlighted_section {
+            HighlightedSection::None => HighlightedSection::Backstory,
+            HighlightedSection::Backstory => {
+                let sheet = self.last_known_character_sheet.as_ref();
+                if let Some((skill, _)) = sheet.and_then(|sheet| {
+                    sheet
+                        .sorted_active_skills()
+                        .first()
+                        .map(|(skill, rating)| ((*skill).clone(), *rating))
+                }) {
+                    HighlightedSection::Skill(skill)
+                } else if let Some((name, _)) =
+                    sheet.and_then(|sheet| sheet.sorted_inventory().first().copied())
+                {
+                    HighlightedSection::InventoryItem(name.clone())
+                } else {
+                    HighlightedSection::None
+                }
+            }
+            HighlightedSection::Skill(_) => {
+                if let Some((name, _)) = self
+                    .last_known_character_sheet
+                    .as_ref()
+                    .and_then(|sheet| sheet.sorted_inventory().first().copied())
+                {
+                    HighlightedSection::InventoryItem(name.clone())
+                } else {
+                    HighlightedSection::None
+                }
+            }
+            HighlightedSection::InventoryItem(_) => {
+                if let Some((name, _)) = self
+                    .last_known_character_sheet
+                    .as_ref()
+                    .and_then(|sheet| sheet.contacts.iter().next())
+                {
+                    HighlightedSection::Contact(name.clone())
+                } else {
+                    HighlightedSection::None
+                }
+            }
+            HighlightedSection::Contact(_) => HighlightedSection::None,
+        };
+    }
+
+    // Pick the next or previous skill in the skill details.
+    fn select_skill(&mut self, forward: bool) {
+        let (Some(sheet), HighlightedSection::Skill(selected)) =
+            (&self.last_known_character_sheet, &self.highlighted_section)
+        else {
+            return;
+        };
+        let names: Vec<&String> = sheet
+            .sorted_active_skills()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        if names.is_empty() {
+            return;
+        }
+        let current = names.iter().position(|name| *name == selected).unwrap_or(0);
+        let next = if forward {
+            (current + 1) % names.len()
+        } else {
+            (current + names.len() - 1) % names.len()
+        };
+        self.highlighted_section = HighlightedSection::Skill(names[next].clone());
+    }
+
+    fn open_roll_popup(&mut self) {
+        let (Some(sheet), HighlightedSection::Skill(skill)) =
+            (&self.last_known_character_sheet, &self.highlighted_section)
+        else {
+            return;
+        };
+        if !self.last_known_ruleset.rules().shadowrun_sheet() {
+            return;
+        }
+        self.roll_popup = RollPopup::for_skill(sheet, skill);
+    }
+
+    // Tell about the tasks that ended while the player was elsewhere, e.g. in the main menu,
+    // where the picture or the narration would otherwise finish unnoticed.
+    fn announce_finished_tasks(&mut self) {
+        self.toasts.retain(|toast| !toast.expired());
+        let finished = self.tasks.take_finished();
+        if self.state == AppState::InGame {
+            return;
+        }
+        for task in finished {
+            let text = match &task.status {
+                TaskStatus::Failed(e) => format!("{} failed: {}", task.kind.label(), e),
+                // The transcript is already in the input it was spoken for.
+                _ if task.kind == TaskKind::Transcription => continue,
+                _ => format!("{} done: {}", task.kind.label(), task.description),
+            };
+            self.toasts.push(Toast {
+                text,
+                jump: task.jump,
+                shown: Instant::now(),
+            });
+        }
+    }
+
+    // The latest notice F2 can follow.
+    pub fn followed_toast(&self) -> Option<usize> {
+        self.toasts
+            .iter()
+            .rposition(|toast| toast.jump.is_some() && !toast.expired())
+    }
+
+    // Open the picture of the latest notice, or go back to the game its narration was for.
+    fn follow_toast(&mut self) {
+        let Some(index) = self.followed_toast() else {
+            return;
+        };
+        match self.toasts.remove(index).jump {
+            Some(Jump::Image(path)) => {
+                if let Err(e) = image::open_image(&path) {
+                    self.add_message(Message::new(
+                        MessageType::System,
+                        format!("Failed to open '{}': {}", path.display(), e),
+                    ));
+                }
+            }
+            Some(Jump::Game(save_name)) => {
+                if self.current_game.is_some()
+                    && self.settings.last_save.as_ref() == Some(&save_name)
+                {
+                    self.state = AppState::InGame;
+                    self.input_mode = InputMode::Normal;
+                } else {
+                    self.send_load_game(save_name);
+                }
+            }
+            None => {}
+        }
+    }
+
+    pub fn open_tasks_panel(&mut self) {
+        // The latest task first.
+        self.tasks_panel = Some(self.tasks.list().len().saturating_sub(1));
+    }
+
+    fn handle_tasks_panel_input(&mut self, key: KeyEvent) {
+        let Some(selected) = self.tasks_panel else {
+            return;
+        };
+        let tasks = self.tasks.list();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('t') => self.tasks_panel = None,
+            KeyCode::Up => self.tasks_panel = Some(selected.saturating_sub(1)),
+            KeyCode::Down => {
+                self.tasks_panel = Some((selected + 1).min(tasks.len().saturating_sub(1)))
+            }
+            KeyCode::Char('c') | KeyCode::Delete => {
+                if let Some(task) = tasks.get(selected) {
+                    if self.tasks.cancel(task.id) {
+                        self.add_message(Message::new(
+                            MessageType::System,
+                            format!("Cancelled: {} {}", task.kind.label(), task.description),
+                        ));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_roll_popup_input(&mut self, key: KeyEvent) {
+        let Some(popup) = self.roll_popup.as_mut() else {
+            return;
+        };
+        match popup.handle_key(key) {
+            RollPopupAction::None => {}
+            RollPopupAction::Close => self.roll_popup = None,
+            RollPopupAction::Roll => {
+                let roll = popup.roll();
+                self.roll_popup = None;
+                self.post_sheet_roll(roll);
+            }
+        }
+    }
+
+    // Show a roll from the sheet, keep it with the game's rolls, and tell the Game Master.
+    fn post_sheet_roll(&mut self, roll: ShownRoll) {
+        let text = describe_roll(&roll);
+        metrics::record("sheet_roll");
+        self.add_message(Message::new(MessageType::System, text.clone()));
+        self.show_roll(roll.clone());
+        let Some(game) = self.current_game.clone() else {
+            return;
+        };
+        let note = format!(
+            "The player rolled from the character sheet: {}. Take the result into account in \
+             your next answer.",
+            text
+        );
+        let ai_client = self.ai_client.clone();
+        let sender = self.command_sender.clone();
+        tokio::spawn(async move {
+            let mut game_state = game.lock().await;
+            if let Err(e) = game_state.record(GameEvent::Roll { roll }) {
+                let _ = sender.send(AppCommand::SystemMessage(e));
+            }
+            let Some(ai) = ai_client else {
+                return;
+            };
+            if let Err(e) = ai.tell(&note, &mut game_state).await {
+                let _ = sender.send(AppCommand::SystemMessage(format!(
+                    "Could not tell the game master about the roll: {}",
+                    e
+                )));
+            }
+        });
+    }
+
+    // Pick the next or previous item in the inventory details.
+    fn select_inventory_item(&mut self, forward: bool) {
+        let (Some(sheet), HighlightedSection::InventoryItem(selected)) =
+            (&self.last_known_character_sheet, &self.highlighted_section)
+        else {
+            return;
+        };
+        let names: Vec<&String> = sheet
+            .sorted_inventory()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        if names.is_empty() {
+            return;
+        }
+        let current = names.iter().position(|name| *name == selected).unwrap_or(0);
+        let next = if forward {
+            (current + 1) % names.len()
+        } else {
+            (current + names.len() - 1) % names.len()
+        };
+        self.highlighted_section = HighlightedSection::InventoryItem(names[next].clone());
+    }
+
+    // Equip, carry or stash the item picked in the inventory details, in that order.
+    fn cycle_item_state(&mut self) {
+        let HighlightedSection::InventoryItem(item) = self.highlighted_section.clone() else {
+            return;
+        };
+        let Some(character) = self
+            .last_known_character_sheet
+            .as_ref()
+            .map(|sheet| sheet.name.clone())
+        else {
+            return;
+        };
+        let Some(game) = self.current_game.clone() else {
+            return;
+        };
+        let Ok(mut game_state) = game.try_lock() else {
+            self.add_error(ShadowrunError::Game(
+                "The game is busy, try again in a moment".to_string(),
+            ));
+            return;
+        };
+        let Some(state) = game_state
+            .characters
+            .iter()
+            .find(|sheet| sheet.name == character)
+            .and_then(|sheet| sheet.inventory.get(&item))
+            .map(|item| item.state.next())
+        else {
+            return;
+        };
+        if let Err(e) = game_state.record(GameEvent::SheetUpdate {
+            character,
+            update: CharacterSheetUpdate::SetItemState { item, state },
+        }) {
+            drop(game_state);
+            self.add_error(ShadowrunError::Game(e));
+            return;
+        }
+        if let (Some(spectator), Some(sheet)) = (&self.spectator, &game_state.main_character_sheet)
+        {
+            spectator.publish_sheet(sheet);
+        }
+        self.save_manager.current_save = Some(game_state.clone());
+        let save_manager = self.save_manager.clone();
+        drop(game_state);
+        if let Err(e) = save_manager.save() {
+            self.add_debug_message(format!("Failed to save game: {:#?}", e));
+        }
+    }
+
+    // Show the next character's sheet, in the order they joined the game.
+    pub fn cycle_viewed_character(&mut self) {
+        let names = &self.last_known_characters;
+        if names.len() < 2 {
+            return;
+        }
+        let current = self
+            .last_known_character_sheet
+            .as_ref()
+            .and_then(|sheet| names.iter().position(|name| *name == sheet.name));
+        let next = current.map_or(0, |index| (index + 1) % names.len());
+        self.viewed_character = Some(names[next].clone());
+        self.highlighted_section = HighlightedSection::None;
+    }
+
+    fn open_portrait(&mut self) {
+        let name = self
+            .last_known_character_sheet
+            .as_ref()
+            .map_or_else(String::new, |sheet| sheet.name.clone());
+        let text = match &self.last_known_portrait {
+            Some(path) => match image::open_image(path) {
+                Ok(()) => return,
+                Err(e) => format!("Failed to open the portrait of {}: {}", name, e),
+            },
+            None => format!("No portrait of {} yet.", name),
+        };
+        self.add_message(Message::new(MessageType::System, text));
+    }
+
+    // The image of the save selected in the load menu, in the system viewer.
+    fn open_save_thumbnail(&mut self) {
+        let Some(save_name) = self.selected_save() else {
+            return;
+        };
+        let text = match self
+            .save_manager
+            .metadata
+            .get(&save_name)
+            .and_then(|metadata| metadata.thumbnail.as_ref())
+        {
+            Some(path) => match image::open_image(path) {
+                Ok(()) => return,
+                Err(e) => format!("Failed to open the image of '{}': {}", save_name, e),
+            },
+            None => format!("'{}' has no image yet.", save_name),
+        };
+        self.add_message(Message::new(MessageType::System, text));
+    }
+
+    fn open_latest_scene(&mut self) {
+        let text = match self.last_known_scenes.last() {
+            Some(scene) => match image::open_image(&scene.path) {
+                Ok(()) => return,
+                Err(e) => format!("Failed to open '{}': {}", scene.title, e),
+            },
+            None => "No scene has been illustrated yet.".to_string(),
+        };
+        self.add_message(Message::new(MessageType::System, text));
+    }
 
-                    tokio::spawn(async move {
-                        let _ = image::generate_and_save_image(&prompt).await;
-                    });
+    // File the images finished in the background in the save, once the game state is free:
+    // portraits under their characters, scenes in the gallery, which opens to show them.
+    fn collect_images(&mut self) {
+        let (Some(ai), Some(game)) = (&self.ai_client, self.current_game.clone()) else {
+            return;
+        };
+        let Ok(mut game_state) = game.try_lock() else {
+            return;
+        };
+        let images = ai.images.with(std::mem::take);
+        if images.is_empty() {
+            return;
+        }
+        for image in images {
+            match image {
+                GeneratedImage::Portrait { name, path } => {
+                    if let Err(e) = game_state.record(GameEvent::Portrait { name, path }) {
+                        self.add_debug_message(format!("Failed to file a portrait: {}", e));
+                    }
+                }
+                GeneratedImage::Scene(scene) => {
                     self.add_message(Message::new(
                         MessageType::System,
-                        "Generating image...".to_string(),
+                        format!("Scene illustrated: {} (v to view)", scene.title),
                     ));
-                    self.image_prompt.reset();
-                    self.state = AppState::MainMenu;
-                }
-                _ => {}
-            },
-            InputMode::Editing => match key.code {
-                KeyCode::Esc => {
-                    self.input_mode = InputMode::Normal;
-                }
-                KeyCode::Char('v') => {
-                    if key.modifiers.contains(KeyModifiers::CONTROL) {
-                        if let Err(e) = self.handle_paste() {
-                            self.add_debug_message(format!("Failed to paste: {:#?}", e));
-                        }
-                    } else {
-                        self.image_prompt.handle_event(&Event::Key(key));
+                    self.show_scenes = true;
+                    if let Err(e) = game_state.record(GameEvent::Scene { scene }) {
+                        self.add_debug_message(format!("Failed to file a scene: {}", e));
                     }
                 }
-                _ => {
-                    self.image_prompt.handle_event(&Event::Key(key));
-                }
-            },
-            InputMode::Recording if key.code == KeyCode::Esc => {
-                self.stop_recording();
             }
-            _ => {}
+        }
+        self.last_known_scenes = game_state.scenes.clone();
+        let mut save_manager = self.save_manager.clone();
+        save_manager.current_save = Some(game_state.clone());
+        if let Err(e) = save_manager.save() {
+            self.add_debug_message(format!("Failed to save images: {}", e));
         }
     }
 
-    fn handle_create_image_editing(&mut self, key: KeyEvent) {
-        match key.code {
-            KeyCode::Enter => {
-                // Handle save name submission
-                self.input_mode = InputMode::Normal;
-            }
-            KeyCode::Esc => {
-                self.input_mode = InputMode::Normal;
-            }
-            KeyCode::Char('v') => {
-                if key.modifiers.contains(KeyModifiers::CONTROL) {
-                    if let Err(e) = self.handle_paste() {
-                        self.add_debug_message(format!("Failed to paste: {:#?}", e));
-                    }
-                } else {
-                    self.image_prompt.handle_event(&Event::Key(key));
-                }
-            }
-            _ => {
-                self.image_prompt.handle_event(&Event::Key(key));
+    // Write the character sheet to HTML right away, then try for a PDF in the background.
+    fn export_character_sheet(&mut self) {
+        let Some(sheet) = self.last_known_character_sheet.clone() else {
+            self.add_message(Message::new(
+                MessageType::System,
+                "No character sheet to export yet.".to_string(),
+            ));
+            return;
+        };
+        let save_name = match &self.save_manager.current_save {
+            Some(game_state) => game_state.save_name.clone(),
+            None => "unknown".to_string(),
+        };
+
+        metrics::record("export_sheet");
+        match export::export_character_sheet_html(&sheet, &save_name) {
+            Ok(path) => {
+                self.add_message(Message::new(
+                    MessageType::System,
+                    format!("Character sheet exported to {}", path.display()),
+                ));
+                let sender = self.command_sender.clone();
+                tokio::spawn(async move {
+                    let message = match export::export_pdf(&path).await {
+                        Ok(pdf_path) => format!("PDF exported to {}", pdf_path.display()),
+                        Err(e) => format!("PDF export skipped: {}", e),
+                    };
+                    let _ = sender.send(AppCommand::SystemMessage(message));
+                });
             }
+            Err(e) => self.add_message(Message::new(
+                MessageType::System,
+                format!("Failed to export character sheet: {}", e),
+            )),
         }
+        self.scroll_to_bottom();
     }
 
-    fn cycle_highlighted_section(&mut self) {
-        // This is a basic implementation. You might want to adjust this based on your layout.
-        self.highlighted_section = match self.highlighted_section {
-            HighlightedSection::None => HighlightedSection::Backstory,
-            HighlightedSection::Backstory => {
-                if let Some((name, _)) = self
-                    .last_known_character_sheet
-                    .as_ref()
-                    .and_then(|sheet| sheet.inventory.iter().next())
-                {
-                    HighlightedSection::InventoryItem(name.clone())
-                } else {
-                    HighlightedSection::None
-                }
-            }
-            HighlightedSection::InventoryItem(_) => {
-                if let Some((name, _)) = self
-                    .last_known_character_sheet
-                    .as_ref()
-                    .and_then(|sheet| sheet.contacts.iter().next())
-                {
-                    HighlightedSection::Contact(name.clone())
-                } else {
-                    HighlightedSection::None
-                }
+    // The sheet as Markdown tables, for pasting into a chat: written to the exports folder, or
+    // copied to the clipboard with `copy`.
+    pub fn export_character_sheet_markdown(&mut self, copy: bool) {
+        let Some(sheet) = self.last_known_character_sheet.clone() else {
+            self.add_message(Message::new(
+                MessageType::System,
+                "No character sheet to export yet.".to_string(),
+            ));
+            return;
+        };
+        metrics::record("export_sheet");
+        let message = if copy {
+            self.copy(&export::character_sheet_markdown(&sheet));
+            "Character sheet copied as Markdown.".to_string()
+        } else {
+            let save_name = match &self.save_manager.current_save {
+                Some(game_state) => game_state.save_name.clone(),
+                None => "unknown".to_string(),
+            };
+            match export::export_character_sheet_markdown(&sheet, &save_name) {
+                Ok(path) => format!("Character sheet exported to {}", path.display()),
+                Err(e) => format!("Failed to export character sheet: {}", e),
             }
-            HighlightedSection::Contact(_) => HighlightedSection::None,
         };
+        self.add_message(Message::new(MessageType::System, message));
+        self.scroll_to_bottom();
     }
 
     fn submit_user_input(&mut self) {
-        let input = self.user_input.value().trim().to_string();
+        let raw_input = self.user_input.value().to_string();
+        let input = match parse_input(&raw_input) {
+            ParsedInput::Command { name, args } => {
+                match self.commands.find(name, self.settings.debug_mode) {
+                    Some(command) => {
+                        metrics::record(&format!("command/{}", command.name));
+                        (command.run)(self, args)
+                    }
+                    None => self.add_message(Message::new(
+                        MessageType::System,
+                        format!("Unknown command /{}. Type /help for the list.", name),
+                    )),
+                }
+                self.user_input = Input::default();
+                self.scroll_to_bottom();
+                return;
+            }
+            ParsedInput::Text(text) => text.to_string(),
+        };
+
         self.start_spinner();
 
         if input.is_empty() {
@@ -1029,6 +2613,7 @@ impl App {
         }
 
         self.add_message(Message::new(MessageType::User, input.clone()));
+        metrics::record("turn");
 
         // Send a command to process the message
         if let Err(e) = self.command_sender.send(AppCommand::ProcessMessage(input)) {
@@ -1043,41 +2628,234 @@ impl App {
         self.scroll_to_bottom();
     }
 
+    // Debug command for GMs: "/npc [role]" rolls up an NPC locally and adds it to the game.
+    pub fn generate_debug_npc(&mut self, role: &str) {
+        let role = if role.is_empty() {
+            NPC_ROLES[rand::thread_rng().gen_range(0..NPC_ROLES.len())]
+        } else {
+            match NpcRole::parse(role) {
+                Some(role) => role,
+                None => {
+                    let roles: Vec<&str> = NPC_ROLES.iter().map(|role| role.name()).collect();
+                    self.add_message(Message::new(
+                        MessageType::System,
+                        format!("Unknown NPC role. Try one of: {}", roles.join(", ")),
+                    ));
+                    return;
+                }
+            }
+        };
+
+        let npc = generate_npc(role, &mut rand::thread_rng());
+        let mut skills: Vec<String> = npc
+            .get_all_active_skills()
+            .iter()
+            .map(|(skill, rating)| format!("{} {}", skill, rating))
+            .collect();
+        skills.sort();
+        let summary = format!(
+            "NPC {} ({} {}): BOD {} AGI {} REA {} STR {} WIL {} LOG {} INT {} CHA {} EDG {} MAG {} | Skills: {}",
+            npc.name,
+            npc.race,
+            role.name(),
+            npc.body,
+            npc.agility,
+            npc.reaction,
+            npc.strength,
+            npc.willpower,
+            npc.logic,
+            npc.intuition,
+            npc.charisma,
+            npc.edge,
+            npc.magic.unwrap_or(0),
+            skills.join(", ")
+        );
+
+        // The AI may be holding the game state mid-run; the NPC is still shown either way.
+        let added = match &self.current_game {
+            Some(game_state) => match game_state.try_lock() {
+                Ok(mut game_state) => game_state
+                    .record(GameEvent::Character { sheet: npc })
+                    .is_ok(),
+                Err(_) => false,
+            },
+            None => false,
+        };
+        self.add_message(Message::new(
+            MessageType::System,
+            if added {
+                summary
+            } else {
+                format!("{} (not added to the game)", summary)
+            },
+        ));
+    }
+
     pub fn apply_settings(&mut self) {
         // Apply changes from settings_state to settings
-        self.settings.language = match self.settings_state.selected_options[0] {
-            0 => "English".to_string(),
-            1 => "Français".to_string(),
-            2 => "日本語".to_string(),
-            _ => self.settings.language.clone(),
+        if let Some((language, _)) = LANGUAGES.get(self.settings_state.selected_options[LANGUAGE]) {
+            self.settings.language = language.to_string();
+        }
+        self.settings.audio_output_enabled =
+            self.settings_state.selected_options[AUDIO_OUTPUT] == 0;
+        self.settings.audio_input_enabled = self.settings_state.selected_options[AUDIO_INPUT] == 0;
+        self.settings.debug_mode = self.settings_state.selected_options[DEBUG_MODE] == 1;
+        self.settings.spectator_mode = match self.settings_state.selected_options[SPECTATOR] {
+            1 => SpectatorMode::Local,
+            2 => SpectatorMode::Lan,
+            _ => SpectatorMode::Off,
+        };
+        self.settings.spectator_share_sheet =
+            self.settings_state.selected_options[SPECTATOR_SHEET] == 1;
+        self.settings.discord_webhook_enabled = self.settings_state.selected_options[WEBHOOK] == 1;
+        self.settings.update_mode = match self.settings_state.selected_options[UPDATES] {
+            0 => UpdateMode::Off,
+            2 => UpdateMode::Auto,
+            _ => UpdateMode::CheckOnly,
+        };
+        let log_level = LOG_LEVELS[self.settings_state.selected_options[LOG_LEVEL]];
+        if log_level != self.settings.log_level {
+            self.settings.log_level = log_level;
+            logging::set_level(log_level.filter());
+        }
+        self.settings.accessibility_mode = self.settings_state.selected_options[ACCESSIBILITY] == 1;
+        self.settings.theme = THEMES[self.settings_state.selected_options[THEME]];
+        theme::set(self.settings.theme);
+        self.settings.mirror_transcript = self.settings_state.selected_options[MIRROR] == 1;
+        self.settings.compress_saves = self.settings_state.selected_options[SAVE_COMPRESSION] == 1;
+        journal::set_compression(self.settings.compress_saves);
+        self.settings.backup_count = BACKUP_COUNTS[self.settings_state.selected_options[BACKUPS]];
+        journal::set_backup_count(self.settings.backup_count);
+        self.settings.confirm_updates = self.settings_state.selected_options[CONFIRM_UPDATES] == 1;
+        self.sync_review();
+        self.settings.usage_metrics = self.settings_state.selected_options[USAGE_METRICS] == 1;
+        self.sync_metrics();
+        self.settings.message_times = self.settings_state.selected_options[MESSAGE_TIMES] == 1;
+        self.settings.dice_animation = self.settings_state.selected_options[DICE_ANIMATION] == 0;
+        self.settings.menu_art = self.settings_state.selected_options[MENU_ART] == 0;
+        let mouse_selection = self.settings_state.selected_options[MOUSE_SELECTION] == 0;
+        if mouse_selection != self.settings.mouse_selection {
+            self.settings.mouse_selection = mouse_selection;
+            cleanup::capture_mouse(mouse_selection);
+        }
+        self.settings.terminal_copy = match self.settings_state.selected_options[TERMINAL_COPY] {
+            1 => TerminalCopy::Always,
+            2 => TerminalCopy::Off,
+            _ => TerminalCopy::Auto,
+        };
+        self.clipboard.terminal_copy = self.settings.terminal_copy;
+        self.settings.frame_rate = FRAME_RATES[self.settings_state.selected_options[FRAME_RATE]];
+        self.settings.tick_rate = TICK_RATES[self.settings_state.selected_options[TICK_RATE]];
+        self.settings.ruleset = RULESETS[self.settings_state.selected_options[RULESET]];
+        self.settings.narrator_model =
+            CHAT_MODELS[self.settings_state.selected_options[NARRATOR_MODEL]].to_string();
+        self.settings.archivist_model =
+            CHAT_MODELS[self.settings_state.selected_options[ARCHIVIST_MODEL]].to_string();
+        self.sync_models();
+        self.settings.transcription_model =
+            TRANSCRIPTION_MODELS[self.settings_state.selected_options[VOICE_MODEL]].to_string();
+        self.settings.transcription_language = SETTINGS_ITEMS[VOICE_LANGUAGE].1
+            [self.settings_state.selected_options[VOICE_LANGUAGE]]
+            .to_string();
+        self.settings.speech_model =
+            SPEECH_MODELS[self.settings_state.selected_options[SPEECH_MODEL]].to_string();
+        self.settings.speech_speed =
+            SPEECH_SPEEDS[self.settings_state.selected_options[SPEECH_SPEED]];
+        self.settings.narrator_voice = self.settings_state.selected_options[NARRATOR_VOICE]
+            .checked_sub(1)
+            .map(|index| NARRATOR_VOICES[index].clone());
+        self.settings.speech_backend = match self.settings_state.selected_options[SPEECH_BACKEND] {
+            0 => SpeechBackend::OpenAI,
+            _ => SpeechBackend::ElevenLabs,
+        };
+        self.settings.player_voice = self.settings_state.selected_options[PLAYER_VOICE]
+            .checked_sub(1)
+            .map(|index| NARRATOR_VOICES[index].clone());
+        self.settings.input_style = match self.settings_state.selected_options[INPUT_STYLE] {
+            0 => InputStyle::Vim,
+            _ => InputStyle::Simple,
+        };
+        self.settings.submit_key = match self.settings_state.selected_options[SUBMIT_KEY] {
+            0 => SubmitKey::Enter,
+            1 => SubmitKey::CtrlS,
+            _ => SubmitKey::CtrlEnter,
         };
-        self.settings.audio_output_enabled = self.settings_state.selected_options[2] == 0;
-        self.settings.audio_input_enabled = self.settings_state.selected_options[3] == 0;
-        self.settings.debug_mode = self.settings_state.selected_options[4] == 1;
+        self.settings.enter_new_line = self.settings_state.selected_options[ENTER_NEW_LINE] == 1;
+        self.sync_spectator();
+        self.sync_webhook();
+        self.sync_mirror();
+        // The transcript is coloured by the theme, and laid out differently in accessibility mode.
+        self.transcript.invalidate();
 
         // Save settings to file
-        if let Err(e) = self.settings.save_to_file("./data/settings.json") {
+        if let Err(e) = self.settings.save_to_file(&settings_path()) {
             eprintln!("Failed to save settings: {:#?}", e);
         }
     }
 
+    // The save whose branding the menus show: the one offered to continue, or the one selected
+    // in the load menu.
+    pub fn branded_save(&self) -> Option<String> {
+        match self.state {
+            AppState::LoadMenu => self.selected_save(),
+            _ => match self.main_menu_items().into_iter().next() {
+                Some(MainMenuItem::Continue(save_name)) => Some(save_name),
+                _ => None,
+            },
+        }
+    }
+
+    // The main menu, opening with the last game played while its save is still there.
+    pub fn main_menu_items(&self) -> Vec<MainMenuItem> {
+        let last_save = self
+            .settings
+            .last_save
+            .clone()
+            .filter(|save_name| self.save_manager.available_saves.contains(save_name));
+        last_save
+            .map(MainMenuItem::Continue)
+            .into_iter()
+            .chain([
+                MainMenuItem::NewGame,
+                MainMenuItem::LoadGame,
+                MainMenuItem::CreateImage,
+                MainMenuItem::Settings,
+            ])
+            .collect()
+    }
+
     fn navigate_main_menu(&mut self, direction: isize) {
+        let len = self.main_menu_items().len() as isize;
         let i = self.main_menu_state.selected().unwrap_or(0) as isize;
-        let new_i = (i + direction).rem_euclid(4) as usize;
+        let new_i = (i + direction).rem_euclid(len) as usize;
         self.main_menu_state.select(Some(new_i));
     }
 
     fn select_main_menu_option(&mut self) {
-        match self.main_menu_state.selected() {
-            Some(0) => {
+        let Some(item) = self
+            .main_menu_state
+            .selected()
+            .and_then(|selected| self.main_menu_items().into_iter().nth(selected))
+        else {
+            return;
+        };
+        match item {
+            MainMenuItem::Continue(save_name) => self.send_load_game(save_name),
+            MainMenuItem::NewGame => {
+                self.save_name_input.reset(); // Clear any previous input
                 self.state = if self.openai_api_key_valid {
                     AppState::InputSaveName
                 } else {
                     AppState::InputApiKey
                 }
             }
-            Some(1) => self.state = AppState::LoadMenu,
-            Some(2) => {
+            MainMenuItem::LoadGame => {
+                self.state = AppState::LoadMenu;
+                self.save_manager.refresh();
+                self.load_game_menu_state.select(Some(0));
+                self.describe_selected_save();
+            }
+            MainMenuItem::CreateImage => {
                 self.state = {
                     if self.openai_api_key_valid {
                         AppState::CreateImage
@@ -1086,19 +2864,26 @@ impl App {
                     }
                 }
             }
-
-            Some(3) => self.state = AppState::SettingsMenu,
-            _ => {}
+            MainMenuItem::Settings => self.state = AppState::SettingsMenu,
         }
     }
 
     fn select_main_menu_by_char(&mut self, c: char) {
-        let index = (c as usize - 1) % 4;
+        let Some(index) = c
+            .to_digit(10)
+            .map(|digit| digit as usize - 1)
+            .filter(|index| *index < self.main_menu_items().len())
+        else {
+            return;
+        };
         self.main_menu_state.select(Some(index));
         self.select_main_menu_option();
     }
 
     pub fn start_spinner(&mut self) {
+        if !self.spinner_active {
+            self.spinner_started = Instant::now();
+        }
         self.spinner_active = true;
         self.last_spinner_update = Instant::now();
     }
@@ -1117,7 +2902,74 @@ impl App {
     pub fn scroll_up(&mut self) {
         if self.game_content_scroll > 0 {
             self.game_content_scroll -= 1;
+        } else {
+            self.load_older_messages();
+        }
+    }
+
+    // Fetch the page of history before what is shown, when the player reaches the top.
+    pub fn load_older_messages(&mut self) {
+        let (Some(history), Some(ai_client)) = (self.older_history.as_mut(), &self.ai_client)
+        else {
+            return;
+        };
+        if history.loading {
+            return;
+        }
+        history.loading = true;
+        let ai_client = ai_client.clone();
+        let thread_id = history.thread_id.clone();
+        let cursor = history.older_than.clone();
+        let sender = self.command_sender.clone();
+        tokio::spawn(async move {
+            let page = ai_client
+                .fetch_message_page(&thread_id, Some(&cursor))
+                .await
+                .map_err(|e| e.to_string());
+            let _ = sender.send(AppCommand::OlderMessagesLoaded(thread_id, page));
+        });
+    }
+
+    // Put a fetched page above the transcript, keeping the same lines on screen.
+    pub fn prepend_older_messages(&mut self, thread_id: String, page: Result<MessagePage, String>) {
+        // The player may have left or switched games while the page was loading.
+        let Some(history) = self
+            .older_history
+            .as_mut()
+            .filter(|history| history.thread_id == thread_id)
+        else {
+            return;
+        };
+        let page = match page {
+            Ok(page) => page,
+            Err(e) => {
+                history.loading = false;
+                self.add_error(ShadowrunError::OpenAI(format!(
+                    "Failed to load older messages: {}",
+                    e
+                )));
+                return;
+            }
+        };
+        match page.older_than {
+            Some(cursor) => {
+                history.older_than = cursor;
+                history.loading = false;
+            }
+            None => self.older_history = None,
         }
+
+        let lines_before = self.total_lines;
+        {
+            let mut content = self.game_content.borrow_mut();
+            let newer = std::mem::replace(&mut *content, page.messages);
+            content.extend(newer);
+        }
+        self.transcript_changed();
+        self.transcript.invalidate();
+        self.refresh_transcript(self.transcript.width().max(1));
+        self.game_content_scroll += self.total_lines.saturating_sub(lines_before);
+        self.reset_spectator();
     }
 
     pub fn scroll_down(&mut self) {
@@ -1127,25 +2979,11 @@ impl App {
     }
 
     pub fn scroll_to_bottom(&mut self) {
-        // Recalculate total lines
-        self.total_lines = self.calculate_total_lines();
+        // Count lines the way the transcript draws them, at the width it was last drawn with.
+        self.refresh_transcript(self.transcript.width().max(1));
 
         // Update the scroll position
         self.game_content_scroll = self.total_lines.saturating_sub(self.visible_lines);
-
-        // Force UI update
-        self.cached_game_content = None;
-    }
-
-    fn calculate_total_lines(&self) -> usize {
-        self.game_content
-            .borrow()
-            .iter()
-            .map(|message| {
-                let wrapped_lines = textwrap::wrap(&message.content, self.visible_lines);
-                wrapped_lines.len()
-            })
-            .sum()
     }
 
     pub fn update_scroll(&mut self) {
@@ -1154,6 +2992,7 @@ impl App {
     }
 
     pub fn add_debug_message(&self, message: String) {
+        log::debug!("{}", message);
         self.debug_info.borrow_mut().push_str(&message);
         self.debug_info.borrow_mut().push('\n');
 
@@ -1164,39 +3003,259 @@ impl App {
         if let Ok(mut file) = OpenOptions::new()
             .create(true)
             .append(true)
-            .open("sharad_debug.log")
+            .open(paths::debug_log_file())
         {
             let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
             let _ = writeln!(file, "[{}] {}", timestamp, &message);
         }
     }
 
+    // A damaged save opens the recovery dialog; anything else is reported as before.
+    pub fn handle_load_error(&mut self, error: Box<dyn std::error::Error>) {
+        match error.downcast_ref::<SaveError>() {
+            Some(SaveError::Corrupt {
+                save_name,
+                reason,
+                readable,
+            }) => {
+                self.save_recovery = Some(SaveRecovery {
+                    save_name: save_name.clone(),
+                    reason: reason.clone(),
+                    readable: *readable,
+                    has_backup: !journal::backups(&paths::save_dir(), save_name).is_empty(),
+                });
+                self.state = AppState::LoadMenu;
+            }
+            _ => self.add_message(Message::new(
+                MessageType::System,
+                format!("Failed to load game: {:#?}", error),
+            )),
+        }
+    }
+
+    fn handle_save_recovery_input(&mut self, key: KeyEvent) {
+        let Some(recovery) = self.save_recovery.as_ref() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Char('b') if recovery.has_backup => {
+                let save_name = recovery.save_name.clone();
+                self.save_recovery = None;
+                self.open_backup_menu(save_name);
+            }
+            KeyCode::Char('l') if recovery.readable => {
+                let save_name = recovery.save_name.clone();
+                self.save_recovery = None;
+                let result = journal::accept(&paths::save_dir(), &save_name);
+                self.reload_after_recovery(save_name, result);
+            }
+            KeyCode::Esc => self.save_recovery = None,
+            _ => {}
+        }
+    }
+
+    fn open_backup_menu(&mut self, save_name: String) {
+        let backups = journal::backups(&paths::save_dir(), &save_name);
+        self.backup_menu = Some(BackupMenu {
+            save_name,
+            backups,
+            selected: 0,
+        });
+    }
+
+    fn handle_backup_menu_input(&mut self, key: KeyEvent) {
+        let Some(menu) = self.backup_menu.as_mut() else {
+            return;
+        };
+        let len = menu.backups.len().max(1);
+        match key.code {
+            KeyCode::Up => menu.selected = (menu.selected + len - 1) % len,
+            KeyCode::Down => menu.selected = (menu.selected + 1) % len,
+            KeyCode::Enter => {
+                let Some(backup) = menu.backups.get(menu.selected).cloned() else {
+                    return;
+                };
+                let save_name = menu.save_name.clone();
+                self.backup_menu = None;
+                let result = journal::restore_backup(&paths::save_dir(), &save_name, &backup);
+                self.reload_after_recovery(save_name, result);
+            }
+            KeyCode::Esc => self.backup_menu = None,
+            _ => {}
+        }
+    }
+
+    fn handle_save_rename_input(&mut self, key: KeyEvent) {
+        let Some(rename) = self.save_rename.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.save_rename = None,
+            KeyCode::Tab => rename.rename_assistant = !rename.rename_assistant,
+            KeyCode::Enter => {
+                let others: Vec<String> = self
+                    .save_manager
+                    .available_saves
+                    .iter()
+                    .filter(|save| **save != rename.save_name)
+                    .cloned()
+                    .collect();
+                match save::check_save_name(rename.input.value(), &others) {
+                    Ok(new_name) if new_name == rename.save_name => self.save_rename = None,
+                    Ok(new_name) => {
+                        let command = AppCommand::RenameSave(
+                            rename.save_name.clone(),
+                            new_name,
+                            rename.rename_assistant,
+                        );
+                        self.save_rename = None;
+                        if let Err(e) = self.command_sender.send(command) {
+                            self.add_debug_message(format!(
+                                "Failed to send rename command: {:#?}",
+                                e
+                            ));
+                        }
+                    }
+                    Err(error) => rename.error = Some(error),
+                }
+            }
+            _ => {
+                rename.input.handle_event(&Event::Key(key));
+                rename.error = None;
+            }
+        }
+    }
+
+    fn reload_after_recovery(&mut self, save_name: String, result: std::io::Result<()>) {
+        match result {
+            Ok(()) => {
+                if let Err(e) = self.command_sender.send(AppCommand::LoadGame(save_name)) {
+                    self.add_debug_message(format!("Failed to send load command: {:#?}", e));
+                }
+            }
+            Err(e) => self.add_error(ShadowrunError::IO(format!(
+                "Failed to recover '{}': {}",
+                save_name, e
+            ))),
+        }
+    }
+
     pub fn add_error(&mut self, error: ShadowrunError) {
+        metrics::record_error(error.category());
         self.error_messages.push(ErrorMessage::new(error));
     }
 
-    pub fn clean_old_errors(&mut self, max_age: Duration) {
-        self.error_messages
-            .retain(|msg| msg.timestamp.elapsed() < max_age);
+    pub fn clean_old_errors(&mut self, max_age: Duration) {
+        self.error_messages
+            .retain(|msg| msg.timestamp.elapsed() < max_age);
+    }
+
+    pub fn update_debug_info(&mut self) {
+        if !self.settings.debug_mode {
+            return;
+        }
+        self.debug_info = format!(
+            "Scroll: {}/{}, Visible Lines: {}, Total Lines: {}, Messages: {}",
+            self.game_content_scroll,
+            self.total_lines.saturating_sub(self.visible_lines),
+            self.visible_lines,
+            self.total_lines,
+            self.game_content.borrow().len()
+        )
+        .into();
+    }
+
+    pub fn add_message(&self, message: message::Message) {
+        if let Some(spectator) = &self.spectator {
+            spectator.publish_message(&message);
+        }
+        mirror::publish(&message);
+        self.game_content.borrow_mut().push(message);
+        self.transcript_changed();
+    }
+
+    // Note that game_content changed, after changing it other than with add_message.
+    pub fn transcript_changed(&self) {
+        self.transcript_revision
+            .set(self.transcript_revision.get() + 1);
+    }
+
+    // Start, restart or stop the spectator stream to match the current settings.
+    pub fn sync_spectator(&mut self) {
+        let host = match self.settings.spectator_mode {
+            SpectatorMode::Off => {
+                self.spectator = None;
+                return;
+            }
+            SpectatorMode::Local => [127, 0, 0, 1],
+            SpectatorMode::Lan => [0, 0, 0, 0],
+        };
+        let address = std::net::SocketAddr::from((host, self.settings.spectator_port));
+
+        if let Some(spectator) = &self.spectator {
+            if spectator.address == address
+                && spectator.share_sheet() == self.settings.spectator_share_sheet
+            {
+                return;
+            }
+        }
+        // Drop the old server first so its port is free again.
+        self.spectator = None;
+
+        match SpectatorServer::start(address, self.settings.spectator_share_sheet) {
+            Ok(spectator) => {
+                self.add_debug_message(format!(
+                    "Spectator stream available at http://{}",
+                    spectator.address
+                ));
+                self.spectator = Some(spectator);
+                self.reset_spectator();
+                metrics::record("spectator");
+            }
+            Err(e) => {
+                self.add_error(ShadowrunError::Network(format!(
+                    "Failed to start spectator stream on {}: {}",
+                    address, e
+                )));
+            }
+        }
+    }
+
+    // Turn the Discord webhook on or off to match the current settings.
+    pub fn sync_webhook(&self) {
+        let url = if self.settings.discord_webhook_enabled {
+            self.settings.discord_webhook_url.clone()
+        } else {
+            None
+        };
+        webhook::configure(url);
     }
 
-    pub fn update_debug_info(&mut self) {
-        if !self.settings.debug_mode {
-            return;
+    // Start or stop the transcript mirror: --mirror wins over the Mirror Output setting.
+    pub fn sync_mirror(&mut self) {
+        let target = self.mirror_override.clone().or_else(|| {
+            self.settings
+                .mirror_transcript
+                .then(|| MirrorTarget::File(mirror::default_file()))
+        });
+        if let Err(e) = mirror::configure(target) {
+            self.add_error(ShadowrunError::IO(format!(
+                "Failed to open the transcript mirror: {}",
+                e
+            )));
         }
-        self.debug_info = format!(
-            "Scroll: {}/{}, Visible Lines: {}, Total Lines: {}, Messages: {}",
-            self.game_content_scroll,
-            self.total_lines.saturating_sub(self.visible_lines),
-            self.visible_lines,
-            self.total_lines,
-            self.game_content.borrow().len()
-        )
-        .into();
     }
 
-    pub fn add_message(&self, message: message::Message) {
-        self.game_content.borrow_mut().push(message);
+    // Send the whole transcript to spectators again, e.g. after a load or a new game.
+    pub fn reset_spectator(&self) {
+        if let Some(spectator) = &self.spectator {
+            let sheet = self
+                .save_manager
+                .current_save
+                .as_ref()
+                .and_then(|game_state| game_state.main_character_sheet.as_ref());
+            spectator.reset(&self.game_content.borrow(), sheet);
+        }
     }
 
     pub async fn send_message(&mut self, message: String) -> Result<(), AppError> {
@@ -1211,7 +3270,7 @@ impl App {
                 let mut game_state = game_state.lock().await;
                 ai.send_message(&formatted_message, &mut game_state)
                     .await
-                    .map_err(|e| AppError::Shadowrun(ShadowrunError::from(e)))
+                    .map_err(AppError::Shadowrun)
             } else if self.ai_client.is_none() {
                 Err(AppError::AIClientNotInitialized)
             } else {
@@ -1250,33 +3309,179 @@ impl App {
         }
     }
 
+    // Whether something on screen moves on its own, so frames are drawn at the full rate.
+    pub fn animating(&self) -> bool {
+        let ai_busy = self
+            .ai_client
+            .as_ref()
+            .is_some_and(|ai| ai.activity.get() != RunActivity::Idle);
+        self.spinner_active
+            || ai_busy
+            || self.input_mode == InputMode::Recording
+            || self.dice_animation.is_some()
+            || !self.queued_rolls.is_empty()
+            || self.narration.get() != Narration::Idle
+            || self.settings.debug_mode
+            // For the time the tasks have been running.
+            || self.tasks_panel.is_some()
+    }
+
+    // How often the interface handles events and can draw a frame.
+    pub fn frame_interval(&self) -> Duration {
+        Duration::from_secs(1) / self.settings.frame_rate.max(1)
+    }
+
+    // How often a frame is drawn when nothing happened: every frame while something moves, a few
+    // times a second otherwise, which saves the battery of laptops while the player reads.
+    pub fn redraw_interval(&self) -> Duration {
+        if self.animating() {
+            self.frame_interval()
+        } else {
+            Duration::from_secs(1) / IDLE_FRAME_RATE.min(self.settings.frame_rate).max(1)
+        }
+    }
+
+    pub fn tick_interval(&self) -> Duration {
+        Duration::from_secs(1) / self.settings.tick_rate.max(1)
+    }
+
     pub fn on_tick(&mut self) {
         if self.settings.debug_mode {
             self.update_debug_info();
         }
+        // A game loaded or started in the background opens on its input.
+        self.settle_input_mode();
+        self.collect_images();
+        self.announce_finished_tasks();
+        self.advance_dice_rolls();
+        self.snapshot_for_emergency();
+    }
+
+    // Queue a roll for the dice popup, with the bell for a glitch while sound is on.
+    pub fn show_roll(&mut self, roll: ShownRoll) {
+        if roll.glitch && self.settings.audio_output_enabled {
+            audio::ring_bell();
+        }
+        self.queued_rolls.push_back(roll);
+    }
+
+    // Pick up the assistant's rolls and show the queued ones one after the other.
+    fn advance_dice_rolls(&mut self) {
+        let rolls = self
+            .ai_client
+            .as_ref()
+            .map(|ai| ai.rolls.with(std::mem::take))
+            .unwrap_or_default();
+        for roll in rolls {
+            // The assistant's rolls only reach the transcript through its story, so say when one
+            // glitched.
+            if let Some(notice) = roll.glitch_notice() {
+                self.add_message(Message::new(MessageType::System, notice));
+            }
+            self.show_roll(roll);
+        }
+        if self
+            .dice_animation
+            .as_ref()
+            .is_some_and(|animation| animation.finished(animation.elapsed()))
+        {
+            self.dice_animation = None;
+        }
+        if self.dice_animation.is_none() {
+            self.dice_animation = self
+                .queued_rolls
+                .pop_front()
+                .map(|roll| DiceAnimation::new(roll, self.settings.dice_animation));
+        }
+    }
+
+    // Keep the emergency save copy about a second behind the game being played. The game is only
+    // copied again once a save was written, and the transcript once it changed.
+    fn snapshot_for_emergency(&mut self) {
+        let Some(game) = self
+            .current_game
+            .as_ref()
+            .filter(|_| self.state == AppState::InGame)
+        else {
+            if self.last_snapshot.take().is_some() {
+                emergency::untrack();
+            }
+            return;
+        };
+        if self
+            .last_snapshot
+            .is_some_and(|last| last.elapsed() < Duration::from_secs(1))
+        {
+            return;
+        }
+        if let Ok(game_state) = game.try_lock() {
+            emergency::track(
+                &game_state,
+                journal::revision(),
+                &self.game_content.borrow(),
+                self.transcript_revision.get(),
+            );
+            self.last_snapshot = Some(Instant::now());
+        }
+    }
+
+    // Put the emergency save back in place of the regular one and load it.
+    fn recover_emergency_save(&mut self) {
+        let Some(recovery) = self.pending_recovery.take() else {
+            return;
+        };
+        let save_name = recovery.game_state.save_name.clone();
+        let save_manager = SaveManager {
+            available_saves: Vec::new(),
+            current_save: Some(recovery.game_state),
+            metadata: HashMap::new(),
+        };
+        if let Err(e) = save_manager.save_snapshot() {
+            self.add_error(ShadowrunError::IO(format!(
+                "Failed to recover '{}': {}",
+                save_name, e
+            )));
+            return;
+        }
+        let _ = emergency::discard(&save_name);
+        self.recovered_transcript = Some(recovery.transcript);
+        if let Err(e) = self.command_sender.send(AppCommand::LoadGame(save_name)) {
+            self.add_debug_message(format!("Failed to send load command: {:#?}", e));
+        }
+    }
+
+    fn discard_emergency_save(&mut self) {
+        if let Some(recovery) = self.pending_recovery.take() {
+            if let Err(e) = emergency::discard(&recovery.game_state.save_name) {
+                self.add_debug_message(format!("Failed to discard emergency save: {:#?}", e));
+            }
+        }
     }
 
     pub async fn start_new_game(
         &mut self,
         save_name: String,
+        seed: Option<CharacterSeed>,
     ) -> Result<(), Box<dyn std::error::Error>> {
+        metrics::record(if seed.is_some() {
+            "new_game/archetype"
+        } else {
+            "new_game"
+        });
         // Initialize AI client if not already initialized
         if self.ai_client.is_none() {
             self.initialize_ai_client().await?;
         }
 
-        let client = self.ai_client.clone().unwrap().client;
-        let assistant = match create_assistant(&client, &save_name).await {
-            Ok(assistant) => assistant,
-            Err(err) => {
-                println!("{}", err);
-                return Err(err);
-            }
-        };
-        let assistant_id = &assistant.id;
+        let backend = self.ai_client.clone().unwrap().backend;
+        let assistant_id = &backend
+            .create_assistant(&save_name, self.settings.ruleset)
+            .await?;
         // let assistant_id = "asst_oavbUQD3KMkNKgyYRj42tKsM"; //Original
         // let assistant_id = "asst_4kaphuqlAkwnsbBrf482Z6dR"; //copy
 
+        let seed_sheet = seed.as_ref().map(|seed| seed.character_sheet());
+
         if let Some(ai) = &self.ai_client {
             // Start a new conversation
             ai.start_new_conversation(
@@ -1284,7 +3489,7 @@ impl App {
                 GameConversationState {
                     assistant_id: assistant_id.to_string(),
                     thread_id: String::new(),
-                    character_sheet: None,
+                    character_sheet: seed_sheet.clone(),
                 },
             )
             .await?;
@@ -1300,18 +3505,30 @@ impl App {
                 .clone();
 
             // Create a new game state
-            let new_game_state = Arc::new(Mutex::new(GameState {
+            let mut game_state = GameState::new(&save_name);
+            game_state.record(GameEvent::Thread {
                 assistant_id: assistant_id.to_string(),
                 thread_id,
-                main_character_sheet: None,
-                characters: Vec::new(),
-                save_name: save_name.clone(),
-            }));
+            })?;
+            game_state.record(GameEvent::Ruleset {
+                ruleset: self.settings.ruleset,
+            })?;
+            game_state.record(GameEvent::Instructions {
+                version: assistant::instructions_version(self.settings.ruleset),
+            })?;
+            if let Some(sheet) = seed_sheet.clone() {
+                game_state.record(GameEvent::MainCharacter {
+                    sheet: Some(sheet.clone()),
+                })?;
+                game_state.record(GameEvent::Character { sheet })?;
+            }
 
-            self.current_game = Some(new_game_state);
+            self.start_play_clock(&game_state);
+            self.current_game = Some(Arc::new(Mutex::new(game_state)));
 
             // Save the game
             self.save_current_game().await?;
+            self.reset_spectator();
 
             self.state = AppState::InGame;
             self.add_message(message::Message::new(
@@ -1323,10 +3540,16 @@ impl App {
             self.start_spinner();
 
             // Send initial message to start the game
-            self.process_message(format!(
+            let mut start_message = format!(
                 "Start the game. Respond with the fluff in the following language: {}",
                 self.settings.language
-            ));
+            );
+            start_message.push(' ');
+            start_message.push_str(self.settings.ruleset.rules().briefing());
+            if let (Some(seed), Some(sheet)) = (&seed, &seed_sheet) {
+                start_message.push_str(&seed.instructions(sheet));
+            }
+            self.process_message(start_message);
 
             Ok(())
         } else {
@@ -1345,19 +3568,267 @@ impl App {
                     ));
                 } else {
                     self.add_debug_message("Character sheet updated successfully".to_string());
+                    if let (Some(spectator), Some(sheet)) =
+                        (&self.spectator, &game_state.main_character_sheet)
+                    {
+                        spectator.publish_sheet(sheet);
+                    }
+                }
+            }
+        }
+    }
+
+    // Count feature use only while Usage Metrics is on.
+    pub fn sync_metrics(&self) {
+        metrics::configure(self.settings.usage_metrics.then(metrics::default_file));
+    }
+
+    // Whether the AI's sheet updates wait for the player, from the Confirm Updates setting.
+    // Queue the actions until the assistant can be reached again, checking every few seconds.
+    fn go_offline(&mut self) {
+        let queued = self.offline_queue.len();
+        self.add_message(Message::new(
+            MessageType::System,
+            format!(
+                "Connection lost: your action is queued ({} waiting) and will be sent when the \
+                 connection is back.",
+                queued
+            ),
+        ));
+        if self.offline {
+            return;
+        }
+        self.offline = true;
+        let Some(ai) = self.ai_client.clone() else {
+            return;
+        };
+        let sender = self.command_sender.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(RECONNECT_INTERVAL).await;
+                if ai.backend.ping().await.is_ok() {
+                    let _ = sender.send(AppCommand::Reconnected);
+                    return;
+                }
+            }
+        });
+    }
+
+    // Send the queued actions, one turn at a time.
+    pub fn handle_reconnected(&mut self) {
+        if !self.offline {
+            return;
+        }
+        self.offline = false;
+        let Some(action) = self.offline_queue.pop_front() else {
+            return;
+        };
+        self.add_message(Message::new(
+            MessageType::System,
+            format!(
+                "Back online: sending {} queued action(s).",
+                self.offline_queue.len() + 1
+            ),
+        ));
+        self.process_message(action);
+    }
+
+    // Point the agents at the models picked in the settings.
+    fn sync_models(&self) {
+        if let Some(ai) = &self.ai_client {
+            ai.models.set(self.settings.agent_models());
+        }
+    }
+
+    fn sync_review(&self) {
+        if let Some(ai) = &self.ai_client {
+            let enabled = self.settings.confirm_updates;
+            ai.review.with(|review| review.enabled = enabled);
+        }
+    }
+
+    // Show the updates the AI asked for this turn, if any were held back.
+    fn open_update_review(&mut self) {
+        let Some(ai) = &self.ai_client else {
+            return;
+        };
+        let pending = ai.review.with(|review| std::mem::take(&mut review.pending));
+        if pending.is_empty() {
+            return;
+        }
+        match self.update_review.as_mut() {
+            Some(open) => open.pending.extend(pending),
+            None => {
+                self.update_review = Some(UpdateReview {
+                    pending,
+                    selected: 0,
+                })
+            }
+        }
+    }
+
+    fn handle_update_review_input(&mut self, key: KeyEvent) {
+        let Some(open) = self.update_review.as_mut() else {
+            return;
+        };
+        let len = open.pending.len().max(1);
+        let (accept, all) = match key.code {
+            KeyCode::Up => {
+                open.selected = (open.selected + len - 1) % len;
+                return;
+            }
+            KeyCode::Down => {
+                open.selected = (open.selected + 1) % len;
+                return;
+            }
+            KeyCode::Char('y') => (true, false),
+            KeyCode::Char('n') => (false, false),
+            KeyCode::Char('Y') => (true, true),
+            KeyCode::Char('N') => (false, true),
+            _ => return,
+        };
+        let Some(game) = self.current_game.clone() else {
+            self.update_review = None;
+            return;
+        };
+        // The run that asked for these is over, so the game is free unless another one started.
+        let Ok(mut game_state) = game.try_lock() else {
+            self.add_error(ShadowrunError::Game(
+                "The game is busy, try again in a moment".to_string(),
+            ));
+            return;
+        };
+        let decided: Vec<_> = if all {
+            open.pending.drain(..).collect()
+        } else {
+            vec![open.pending.remove(open.selected)]
+        };
+        open.selected = open.selected.min(open.pending.len().saturating_sub(1));
+        if open.pending.is_empty() {
+            self.update_review = None;
+        }
+
+        let mut messages = Vec::new();
+        for pending in &decided {
+            let changes = pending.changes.join(", ");
+            messages.push(if !accept {
+                format!("Rejected for {}: {}", pending.character_name, changes)
+            } else {
+                match review::apply(&mut game_state, pending) {
+                    Ok(()) => format!("Accepted for {}: {}", pending.character_name, changes),
+                    Err(e) => format!(
+                        "Could not apply the update for {}: {}",
+                        pending.character_name, e
+                    ),
+                }
+            });
+        }
+        if accept {
+            if let (Some(spectator), Some(sheet)) =
+                (&self.spectator, &game_state.main_character_sheet)
+            {
+                spectator.publish_sheet(sheet);
+            }
+            let mut save_manager = self.save_manager.clone();
+            save_manager.current_save = Some(game_state.clone());
+            self.save_manager.current_save = Some(game_state.clone());
+            if let Err(e) = save_manager.save() {
+                messages.push(format!("Failed to save game: {}", e));
+            }
+        }
+        drop(game_state);
+        for message in messages {
+            self.add_message(Message::new(MessageType::System, message));
+        }
+    }
+
+    // Say what the turn changed on the main sheet, so edits by the AI don't go unnoticed.
+    async fn report_sheet_changes(&mut self) {
+        let Some(before) = self.turn_start_sheet.take() else {
+            return;
+        };
+        let Some(game_state) = &self.current_game else {
+            return;
+        };
+        let changes = match &game_state.lock().await.main_character_sheet {
+            Some(after) if after.name == before.name => sheet_changes(&before, after),
+            _ => Vec::new(),
+        };
+        if !changes.is_empty() {
+            self.add_message(Message::new(
+                MessageType::System,
+                format!("Sheet changes: {}", changes.join(", ")),
+            ));
+        }
+        self.sheet_changes = changes;
+    }
+
+    // Start counting play time for the game just opened.
+    fn start_play_clock(&mut self, game_state: &GameState) {
+        self.play_clock = Some(Instant::now());
+        self.session_play
+            .entry(game_state.save_name.clone())
+            .or_insert(SessionPlay {
+                started: game_state.stats,
+                latest: game_state.stats,
+            });
+        self.remember_last_save(Some(game_state.save_name.clone()));
+    }
+
+    // Keep the save the main menu offers to continue in the settings, so it lasts between runs.
+    fn remember_last_save(&mut self, save_name: Option<String>) {
+        if self.settings.last_save == save_name {
+            return;
+        }
+        self.settings.last_save = save_name;
+        if let Err(e) = self.settings.save_to_file(&settings_path()) {
+            self.add_debug_message(format!("Failed to save settings: {:#?}", e));
+        }
+    }
+
+    // Add the time played since the last call to the game.
+    fn clock_play_time(&mut self, game_state: &mut GameState) {
+        if let Some(clock) = self.play_clock.as_mut() {
+            let seconds = clock.elapsed().as_secs();
+            if seconds > 0 {
+                *clock += Duration::from_secs(seconds);
+                if let Err(e) = game_state.record(GameEvent::Played { seconds }) {
+                    log::warn!("Could not add the play time: {}", e);
                 }
             }
         }
+        if let Some(session) = self.session_play.get_mut(&game_state.save_name) {
+            session.latest = game_state.stats;
+        }
+    }
+
+    // What was played this run, game by game, e.g. for when the player quits.
+    pub fn session_summary(&self) -> Option<String> {
+        let lines: Vec<String> = self
+            .session_play
+            .iter()
+            .filter(|(_, session)| session.latest != session.started)
+            .map(|(save_name, session)| {
+                format!(
+                    "{}: {} this session, {} in all",
+                    save_name,
+                    session.latest.since(session.started).describe(),
+                    session.latest.describe()
+                )
+            })
+            .collect();
+        (!lines.is_empty()).then(|| lines.join("\n"))
     }
 
     pub async fn save_current_game(&mut self) -> Result<(), AppError> {
         let game_state = match &self.current_game {
-            Some(arc_mutex) => arc_mutex,
+            Some(arc_mutex) => Arc::clone(arc_mutex),
             None => return Err(AppError::NoCurrentGame),
         };
+        self.clock_play_time(&mut *game_state.lock().await);
 
         // Clone the Arc to get a new reference
-        let game_state_clone = Arc::clone(game_state);
+        let game_state_clone = Arc::clone(&game_state);
 
         // Clone the save_name to own the data
         let mut save_manager_clone = self.save_manager.clone();
@@ -1379,20 +3850,27 @@ impl App {
     }
 
     fn delete_selected_save(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        if let Some(selected) = self.load_game_menu_state.selected() {
-            let save_name = self.save_manager.available_saves[selected].clone();
+        if let (Some(selected), Some(save_name)) =
+            (self.load_game_menu_state.selected(), self.selected_save())
+        {
             let ai_client = self.ai_client.clone().ok_or("AI client not found")?;
             let save_2 = save_name.clone();
             let assistant_id = get_assistant_id(&save_name)?;
             tokio::spawn(async move {
-                delete_assistant(&ai_client.client, &assistant_id).await;
+                ai_client.backend.delete_assistant(&assistant_id).await;
             });
-            self.save_manager.available_saves.remove(selected);
+            self.save_manager
+                .available_saves
+                .retain(|save| *save != save_name);
             self.save_manager.clone().delete_save(&save_2)?;
+            if self.settings.last_save.as_ref() == Some(&save_name) {
+                self.remember_last_save(None);
+            }
 
             // Update the selected state to ensure it remains within bounds
-            let new_selected = if selected >= self.save_manager.available_saves.len() {
-                self.save_manager.available_saves.len().saturating_sub(1)
+            let listed = self.listed_saves().len();
+            let new_selected = if selected >= listed {
+                listed.saturating_sub(1)
             } else {
                 selected
             };
@@ -1404,7 +3882,7 @@ impl App {
     }
 
     fn navigate_load_game_menu(&mut self, direction: isize) {
-        let len = self.save_manager.available_saves.len();
+        let len = self.listed_saves().len();
         if len == 0 {
             return;
         }
@@ -1417,8 +3895,166 @@ impl App {
         self.load_game_menu_state.select(Some(next));
     }
 
+    // Copy the current game into a new save on a fresh thread, seeded with a summary of the story,
+    // and switch to it. The original save and its thread are left untouched.
+    pub async fn fork_game(&mut self, fork_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        metrics::record("fork");
+        let game = self.current_game.clone().ok_or(AppError::NoCurrentGame)?;
+        let game_state = game.lock().await.clone();
+        let original = game_state.save_name.clone();
+        self.branch(game_state, fork_name).await?;
+
+        self.load_game(fork_name).await?;
+        self.add_message(Message::new(
+            MessageType::System,
+            format!(
+                "Forked '{}' into '{}'. Load '{}' to go back to the original timeline.",
+                original, fork_name, original
+            ),
+        ));
+        Ok(())
+    }
+
+    // Fork a save that isn't loaded into a new one, e.g. to try a risky plan on the copy. The
+    // load menu stays open with the copy selected.
+    pub async fn duplicate_save(
+        &mut self,
+        save_name: &str,
+        copy_name: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        metrics::record("duplicate_save");
+        let (_, game_state) = journal::Journal::load(&paths::save_dir(), save_name)?;
+        self.branch(game_state, copy_name).await?;
+        self.save_manager.refresh();
+        self.select_listed_save(Some(copy_name));
+        self.add_message(Message::new(
+            MessageType::System,
+            format!(
+                "Duplicated '{}' into '{}', on a thread of its own.",
+                save_name, copy_name
+            ),
+        ));
+        Ok(())
+    }
+
+    // Write a copy of `game_state` as `fork_name`, with its own assistant and a new thread seeded
+    // with a summary of the story.
+    async fn branch(
+        &mut self,
+        game_state: GameState,
+        fork_name: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if self.ai_client.is_none() {
+            self.initialize_ai_client().await?;
+        }
+        let backend = self
+            .ai_client
+            .clone()
+            .ok_or("AI client not initialized")?
+            .backend;
+
+        // Each save deletes its own assistant, so the branch gets one too.
+        let assistant_id = backend
+            .create_assistant(fork_name, game_state.ruleset)
+            .await?;
+        let (thread_id, archive) =
+            archivist::fork(backend.as_ref(), &game_state.thread_id, &game_state.archive).await?;
+        // The branch keeps the history of the original, up to where it branched off.
+        let mut branch = GameState {
+            save_name: fork_name.to_string(),
+            ..game_state
+        };
+        branch.record(GameEvent::Thread {
+            assistant_id,
+            thread_id,
+        })?;
+        branch.record(GameEvent::Archive { archive })?;
+        branch.record(GameEvent::Instructions {
+            version: assistant::instructions_version(branch.ruleset),
+        })?;
+        let mut save_manager = self.save_manager.clone();
+        save_manager.current_save = Some(branch);
+        save_manager.save()?;
+        Ok(())
+    }
+
+    pub async fn upgrade_assistant(
+        &mut self,
+        recreate: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        metrics::record("upgrade_assistant");
+        let game = self.current_game.clone().ok_or(AppError::NoCurrentGame)?;
+        let ai_client = self.ai_client.as_ref().ok_or("AI client not initialized")?;
+        ai_client
+            .upgrade_assistant(&mut *game.lock().await, recreate)
+            .await?;
+        self.save_current_game().await?;
+        self.add_message(Message::new(
+            MessageType::System,
+            if recreate {
+                "A new assistant with the latest instructions now runs this campaign."
+            } else {
+                "The campaign's assistant now has the latest instructions."
+            }
+            .to_string(),
+        ));
+        Ok(())
+    }
+
+    // Rename a save on disk and, if asked, its assistant; the save stays selected in the menu.
+    pub async fn rename_save(
+        &mut self,
+        from: &str,
+        to: &str,
+        rename_assistant: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        metrics::record("rename_save");
+        let game_state = self.save_manager.rename_save(from, to)?;
+        if self.settings.last_save.as_deref() == Some(from) {
+            self.remember_last_save(Some(to.to_string()));
+        }
+        if let Some(game) = self.current_game.clone() {
+            let mut game = game.lock().await;
+            if game.save_name == from {
+                game.save_name = to.to_string();
+                self.save_manager.current_save = Some(game.clone());
+                drop(game);
+                self.update_save_name(to.to_string()).await;
+            }
+        }
+        self.select_listed_save(Some(to));
+        self.add_message(Message::new(
+            MessageType::System,
+            format!("'{}' is now called '{}'.", from, to),
+        ));
+        if rename_assistant {
+            if self.ai_client.is_none() {
+                self.initialize_ai_client().await?;
+            }
+            let ai_client = self.ai_client.as_ref().ok_or("AI client not initialized")?;
+            if let Err(e) = ai_client
+                .backend
+                .rename_assistant(&game_state.assistant_id, to)
+                .await
+            {
+                self.add_error(e);
+            }
+        }
+        Ok(())
+    }
+
     pub async fn load_game(&mut self, save_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        metrics::record("load_game");
         self.save_manager = self.save_manager.clone().load_from_file(save_name)?;
+        self.sheet_changes.clear();
+        self.update_review = None;
+        self.viewed_character = None;
+        self.last_known_combat = None;
+        self.last_known_scenes = self
+            .save_manager
+            .current_save
+            .as_ref()
+            .map_or_else(Vec::new, |game_state| game_state.scenes.clone());
 
         let mut game_state = self
             .save_manager
@@ -1433,37 +4069,135 @@ impl App {
             self.initialize_ai_client().await?;
         }
 
+        // Clone the Arc to get a new reference to the AI client
+        let ai_client = self.ai_client.as_mut().unwrap().borrow_mut();
+
+        // An old save's assistant or thread may be gone from OpenAI; new ones are made first.
+        let repaired = ai_client.repair_conversation(&mut game_state).await?;
         let conversation_state = GameConversationState {
             assistant_id: game_state.assistant_id.clone(),
             thread_id: game_state.thread_id.clone(),
             character_sheet: game_state.main_character_sheet.clone(),
         };
 
-        // Clone the Arc to get a new reference to the AI client
-        let ai_client = self.ai_client.as_mut().unwrap().borrow_mut();
-
         // Use the cloned Arc to call load_conversation
         ai_client.load_conversation(conversation_state).await;
 
-        // Fetch all messages from the thread
-        let all_messages = ai_client.fetch_all_messages(&game_state.thread_id).await?;
+        // Pick up the run the game was closed during, before the thread is read, so its answer
+        // is in the history.
+        let outdated = GameAI::instructions_outdated(&game_state);
+        let resumed = if game_state.pending_run.is_some() {
+            Some(ai_client.resume_run(&mut game_state).await)
+        } else {
+            None
+        };
+
+        // Fetch the latest page of the thread; older ones load when the player scrolls up. A new
+        // thread only holds the summary, so the history is the one kept in the save.
+        let page = if repaired.thread {
+            MessagePage {
+                messages: game_state
+                    .events
+                    .iter()
+                    .filter_map(|recorded| match &recorded.event {
+                        GameEvent::Message { sender, text } => {
+                            Some(Message::new(sender.clone(), text.clone()))
+                        }
+                        _ => None,
+                    })
+                    .collect(),
+                older_than: None,
+            }
+        } else {
+            ai_client
+                .fetch_message_page(&game_state.thread_id, None)
+                .await?
+        };
+        self.older_history = page.older_than.map(|older_than| OlderHistory {
+            thread_id: game_state.thread_id.clone(),
+            older_than,
+            loading: false,
+        });
 
-        // Load message history
-        *self.game_content.borrow_mut() = all_messages;
+        // Load message history, or what was on screen when an emergency save was written, which
+        // may include entries that never made it to the thread.
+        *self.game_content.borrow_mut() = match self.recovered_transcript.take() {
+            Some(transcript) if transcript.len() > page.messages.len() => {
+                // Its start can't be matched against the thread, so don't page in front of it.
+                self.older_history = None;
+                transcript
+            }
+            _ => page.messages,
+        };
+        self.transcript_changed();
+        self.transcript.invalidate();
+        self.reset_spectator();
 
         // Add a system message indicating the game was loaded
         self.add_message(message::Message::new(
             message::MessageType::System,
             format!("Game '{}' loaded successfully!", game_state.save_name),
         ));
+        if repaired.any() {
+            let lost = match (repaired.assistant, repaired.thread) {
+                (true, true) => "assistant and conversation",
+                (true, false) => "assistant",
+                _ => "conversation",
+            };
+            self.add_message(message::Message::new(
+                message::MessageType::System,
+                format!(
+                    "This save's {} no longer existed on OpenAI, so a new one was made.{}",
+                    lost,
+                    if repaired.thread {
+                        " The Game Master picks the story up from a summary of it."
+                    } else {
+                        ""
+                    }
+                ),
+            ));
+        }
+        if outdated {
+            self.add_message(message::Message::new(
+                message::MessageType::System,
+                "The Game Master's instructions changed since this campaign's assistant was made. \
+                 Type /upgrade to give it the new ones, or /upgrade new to replace it with a new \
+                 assistant."
+                    .to_string(),
+            ));
+        }
+        match &resumed {
+            Some(Ok(Some(_))) => self.add_message(message::Message::new(
+                message::MessageType::System,
+                "The answer to your last action came in while the game was closed.".to_string(),
+            )),
+            Some(Ok(None)) => self.add_message(message::Message::new(
+                message::MessageType::System,
+                "Your last action was cut short when the game closed. Send it again if it has \
+                 no answer above."
+                    .to_string(),
+            )),
+            Some(Err(e)) => self.add_message(message::Message::new(
+                message::MessageType::System,
+                format!(
+                    "Could not pick up the run the game was closed during: {}",
+                    e
+                ),
+            )),
+            None => {}
+        }
 
         // Store the game state
+        self.start_play_clock(&game_state);
         self.current_game = Some(Arc::new(Mutex::new(game_state)));
+        if resumed.is_some() || repaired.any() {
+            if let Err(e) = self.save_current_game().await {
+                self.add_debug_message(format!("Failed to save the loaded game: {:#?}", e));
+            }
+        }
 
         self.state = AppState::InGame;
 
-        // Calculate total lines after loading the game content
-        self.total_lines = self.calculate_total_lines();
         // Scroll to the bottom after updating the scroll
         self.scroll_to_bottom();
 
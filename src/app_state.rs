@@ -8,5 +8,14 @@ pub enum AppState {
     CreateImage,
     SettingsMenu,
     InputApiKey,
+    InputWebhookUrl,
+    InputElevenLabsKey,
+    InputDataDir,
     InputSaveName,
+    ChooseArchetype,
+    CharacterWizard,
+    Logs,
+    Macros,
+    CampaignStats,
+    Shop,
 }
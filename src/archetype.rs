@@ -0,0 +1,418 @@
+// archetype.rs
+// Pre-built character templates the player can start from before the AI-led creation.
+
+use crate::character::{CharacterSheetBuilder, Contact, Item, ItemState, Quality, Race, Skills};
+use std::collections::HashMap;
+
+pub struct Archetype {
+    pub name: &'static str,
+    pub description: &'static str,
+    template: fn() -> CharacterSheetBuilder,
+}
+
+impl Archetype {
+    // Starting builder for this archetype. Attributes are base values, before race modifiers.
+    pub fn builder(&self) -> CharacterSheetBuilder {
+        (self.template)()
+    }
+}
+
+pub const ARCHETYPES: &[Archetype] = &[
+    Archetype {
+        name: "Street Samurai",
+        description:
+            "Chromed-up muscle for hire. Hits hard, shoots straight, never misses a payday.",
+        template: street_samurai,
+    },
+    Archetype {
+        name: "Decker",
+        description: "Matrix specialist who cracks hosts, spoofs devices and steals paydata.",
+        template: decker,
+    },
+    Archetype {
+        name: "Mage",
+        description:
+            "Hermetic spellslinger, equally at home in a firefight and on the astral plane.",
+        template: mage,
+    },
+    Archetype {
+        name: "Face",
+        description:
+            "Smooth talker who negotiates the contracts and gets the team through the door.",
+        template: face,
+    },
+    Archetype {
+        name: "Rigger",
+        description:
+            "Jumps into vehicles and drone swarms; the getaway starts before the job does.",
+        template: rigger,
+    },
+    Archetype {
+        name: "Adept",
+        description: "Channels magic through their own body for impossible speed and precision.",
+        template: adept,
+    },
+];
+
+fn ratings(entries: &[(&str, u8)]) -> HashMap<String, u8> {
+    entries
+        .iter()
+        .map(|(name, rating)| (name.to_string(), *rating))
+        .collect()
+}
+
+fn skills(
+    combat: &[(&str, u8)],
+    physical: &[(&str, u8)],
+    social: &[(&str, u8)],
+    technical: &[(&str, u8)],
+) -> Skills {
+    Skills {
+        combat: ratings(combat),
+        physical: ratings(physical),
+        social: ratings(social),
+        technical: ratings(technical),
+    }
+}
+
+fn qualities(entries: &[(&str, bool)]) -> Vec<Quality> {
+    entries
+        .iter()
+        .map(|(name, positive)| Quality {
+            name: name.to_string(),
+            positive: *positive,
+        })
+        .collect()
+}
+
+fn inventory(entries: &[(&str, u32, &str)]) -> HashMap<String, Item> {
+    entries
+        .iter()
+        .map(|(name, quantity, description)| {
+            let mut item = Item::new(name, *quantity, description);
+            // Runners start out wearing their armor.
+            if item.armor_rating() > 0 {
+                item.state = ItemState::Equipped;
+            }
+            (name.to_string(), item)
+        })
+        .collect()
+}
+
+fn contacts(entries: &[(&str, &str, u8, u8)]) -> HashMap<String, Contact> {
+    entries
+        .iter()
+        .map(|(name, description, loyalty, connection)| {
+            (
+                name.to_string(),
+                Contact {
+                    name: name.to_string(),
+                    description: description.to_string(),
+                    loyalty: *loyalty,
+                    connection: *connection,
+                },
+            )
+        })
+        .collect()
+}
+
+fn base(name: &str, race: Race, backstory: &str) -> CharacterSheetBuilder {
+    CharacterSheetBuilder::new(
+        name.to_string(),
+        race,
+        "Unspecified".to_string(),
+        backstory.to_string(),
+        true,
+    )
+}
+
+fn street_samurai() -> CharacterSheetBuilder {
+    base(
+        "Street Samurai",
+        Race::Ork,
+        "A former gang enforcer who traded the colors for chrome and contracts.",
+    )
+    .body(4)
+    .agility(6)
+    .reaction(5)
+    .strength(4)
+    .willpower(3)
+    .logic(2)
+    .intuition(4)
+    .charisma(2)
+    .edge(2)
+    .skills(skills(
+        &[
+            ("Automatics", 5),
+            ("Blades", 5),
+            ("Pistols", 4),
+            ("Unarmed Combat", 3),
+        ],
+        &[("Running", 2), ("Sneaking", 3), ("Perception", 3)],
+        &[("Intimidation", 3)],
+        &[("First Aid", 2)],
+    ))
+    .knowledge_skills(ratings(&[
+        ("Gang Territories", 3),
+        ("Security Procedures", 3),
+    ]))
+    .qualities(qualities(&[
+        ("Ambidextrous", true),
+        ("Code of Honor", false),
+    ]))
+    .nuyen(5000)
+    .inventory(inventory(&[
+        ("Ares Predator V", 1, "Heavy pistol, the runner's classic."),
+        ("Katana", 1, "Monofilament-edged blade."),
+        ("Armor Jacket", 1, "Armor 12, looks like a leather jacket."),
+        ("Medkit", 1, "Rating 3 medkit."),
+    ]))
+    .contacts(contacts(&[(
+        "Fixer",
+        "Finds you work and takes a cut.",
+        2,
+        4,
+    )]))
+}
+
+fn decker() -> CharacterSheetBuilder {
+    base(
+        "Decker",
+        Race::Human,
+        "Burned out of a corp IT department after finding something they were not supposed to see.",
+    )
+    .body(2)
+    .agility(3)
+    .reaction(3)
+    .strength(2)
+    .willpower(4)
+    .logic(6)
+    .intuition(5)
+    .charisma(2)
+    .edge(3)
+    .skills(skills(
+        &[("Pistols", 2)],
+        &[("Sneaking", 2)],
+        &[("Con", 2)],
+        &[
+            ("Hacking", 6),
+            ("Cybercombat", 5),
+            ("Electronic Warfare", 4),
+            ("Computer", 5),
+            ("Software", 4),
+        ],
+    ))
+    .knowledge_skills(ratings(&[
+        ("Matrix Security", 4),
+        ("Corporate Politics", 2),
+    ]))
+    .qualities(qualities(&[("Codeslinger", true), ("SINner", false)]))
+    .nuyen(3000)
+    .inventory(inventory(&[
+        (
+            "Cyberdeck",
+            1,
+            "Microdeck Summit, well-loved and overclocked.",
+        ),
+        ("Commlink", 1, "Hermes Ikon."),
+        (
+            "Hold-out Pistol",
+            1,
+            "Small enough to pass a lazy pat-down.",
+        ),
+    ]))
+    .contacts(contacts(&[(
+        "Data Broker",
+        "Buys and sells paydata, no questions asked.",
+        2,
+        3,
+    )]))
+}
+
+fn mage() -> CharacterSheetBuilder {
+    base(
+        "Mage",
+        Race::Elf,
+        "Dropped out of a university thaumaturgy program when the tuition came due.",
+    )
+    .body(2)
+    .agility(3)
+    .reaction(3)
+    .strength(2)
+    .willpower(5)
+    .logic(4)
+    .intuition(4)
+    .charisma(3)
+    .edge(2)
+    .magic(6)
+    .skills(skills(
+        &[("Pistols", 2)],
+        &[("Perception", 3)],
+        &[("Etiquette", 2)],
+        &[
+            ("Spellcasting", 6),
+            ("Counterspelling", 4),
+            ("Summoning", 4),
+            ("Assensing", 3),
+        ],
+    ))
+    .knowledge_skills(ratings(&[("Magic Theory", 5), ("Talismongering", 2)]))
+    .qualities(qualities(&[("Magician", true), ("Spirit Bane", false)]))
+    .nuyen(4000)
+    .inventory(inventory(&[
+        ("Power Focus", 1, "Rating 2 power focus, an engraved ring."),
+        ("Reagents", 10, "Drams of reagents for rituals and spirits."),
+        ("Lined Coat", 1, "Armor 9."),
+    ]))
+    .contacts(contacts(&[(
+        "Talismonger",
+        "Sells foci, reagents and rumors from the astral.",
+        3,
+        2,
+    )]))
+}
+
+fn face() -> CharacterSheetBuilder {
+    base(
+        "Face",
+        Race::Elf,
+        "Grew up hustling in the clubs of Downtown and never stopped smiling.",
+    )
+    .body(3)
+    .agility(3)
+    .reaction(3)
+    .strength(2)
+    .willpower(4)
+    .logic(3)
+    .intuition(4)
+    .charisma(5)
+    .edge(4)
+    .skills(skills(
+        &[("Pistols", 3)],
+        &[("Perception", 3)],
+        &[
+            ("Con", 6),
+            ("Negotiation", 6),
+            ("Etiquette", 5),
+            ("Leadership", 4),
+            ("Intimidation", 3),
+        ],
+        &[],
+    ))
+    .knowledge_skills(ratings(&[("Corporate Politics", 4), ("Fashion", 3)]))
+    .qualities(qualities(&[
+        ("First Impression", true),
+        ("Distinctive Style", false),
+    ]))
+    .nuyen(8000)
+    .inventory(inventory(&[
+        (
+            "Actioneer Business Clothes",
+            1,
+            "Armor 8, and it looks good.",
+        ),
+        ("Fake SIN", 1, "Rating 4 fake SIN with a matching license."),
+        (
+            "Colt America L36",
+            1,
+            "Light pistol that fits under a tailored jacket.",
+        ),
+    ]))
+    .contacts(contacts(&[
+        (
+            "Mr. Johnson",
+            "Corp middle manager who hires deniable assets.",
+            1,
+            5,
+        ),
+        (
+            "Bartender",
+            "Knows everyone who drinks at the Big Rhino.",
+            3,
+            2,
+        ),
+    ]))
+}
+
+fn rigger() -> CharacterSheetBuilder {
+    base(
+        "Rigger",
+        Race::Dwarf,
+        "A smuggler's kid who could drive before they could walk.",
+    )
+    .body(3)
+    .agility(3)
+    .reaction(5)
+    .strength(2)
+    .willpower(3)
+    .logic(5)
+    .intuition(4)
+    .charisma(2)
+    .edge(3)
+    .skills(skills(
+        &[("Gunnery", 5), ("Pistols", 2)],
+        &[("Perception", 3)],
+        &[("Negotiation", 2)],
+        &[
+            ("Pilot Ground Craft", 6),
+            ("Pilot Aircraft", 4),
+            ("Automotive Mechanic", 4),
+            ("Electronic Warfare", 3),
+        ],
+    ))
+    .knowledge_skills(ratings(&[("Smuggling Routes", 4), ("Vehicle Models", 3)]))
+    .qualities(qualities(&[("Gearhead", true), ("Bad Luck", false)]))
+    .nuyen(6000)
+    .inventory(inventory(&[
+        ("Rigger Command Console", 1, "Scratch-built RCC."),
+        ("Ford Americar", 1, "Boring, reliable, secretly armored."),
+        ("MCT Fly-Spy", 2, "Tiny surveillance drones."),
+    ]))
+    .contacts(contacts(&[(
+        "Mechanic",
+        "Runs a chop shop in the Barrens.",
+        3,
+        2,
+    )]))
+}
+
+fn adept() -> CharacterSheetBuilder {
+    base(
+        "Adept",
+        Race::Human,
+        "Trained in a dojo that does not officially exist, then left to find a purpose.",
+    )
+    .body(4)
+    .agility(5)
+    .reaction(5)
+    .strength(4)
+    .willpower(4)
+    .logic(2)
+    .intuition(4)
+    .charisma(2)
+    .edge(3)
+    .magic(5)
+    .skills(skills(
+        &[
+            ("Unarmed Combat", 6),
+            ("Blades", 4),
+            ("Throwing Weapons", 3),
+        ],
+        &[("Gymnastics", 5), ("Sneaking", 4), ("Perception", 4)],
+        &[],
+        &[],
+    ))
+    .knowledge_skills(ratings(&[("Martial Arts Styles", 4), ("Philosophy", 2)]))
+    .qualities(qualities(&[("Adept", true), ("Code of Honor", false)]))
+    .nuyen(3000)
+    .inventory(inventory(&[
+        ("Weapon Focus", 1, "Rating 2 weapon focus, a pair of sai."),
+        ("Armor Vest", 1, "Armor 9."),
+    ]))
+    .contacts(contacts(&[(
+        "Sensei",
+        "Old teacher who still answers the phone.",
+        4,
+        1,
+    )]))
+}
@@ -0,0 +1,223 @@
+// archivist.rs
+// Keeps long campaigns affordable. Runs only read the latest messages of the thread, and the
+// archivist folds the ones that drop out of that window into a running summary sent along with
+// every run.
+//
+// The thread itself is never pruned, so a save still loads its whole transcript.
+
+use crate::backend::AssistantBackend;
+use crate::error::ShadowrunError;
+use crate::events::GameEvent;
+use crate::game_state::GameState;
+use crate::message::Message;
+use crate::mirror;
+use serde::{Deserialize, Serialize};
+
+// Messages a run reads word for word.
+pub const RECENT_MESSAGES: usize = 40;
+// Messages that leave that window before the summary is brought up to date.
+pub const ARCHIVE_EVERY: usize = 20;
+// The most messages the thread API returns at once.
+const PAGE_LIMIT: usize = 100;
+
+pub const INSTRUCTIONS: &str = "You are the archivist of a Shadowrun campaign. Merge the summary \
+so far with the new transcript into one summary in the campaign's language, at most 400 words. \
+Keep names, places, open jobs, debts, injuries, promises and what each contact knows. Drop dice \
+rolls and descriptions. Answer with the summary only.";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Archive {
+    pub summary: String,
+    // Messages at the end of the thread the summary does not cover yet, or None for a save the
+    // archivist has not seen.
+    pub uncovered: Option<usize>,
+}
+
+// What a run is given of the story: the latest messages and, before them, the summary.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunContext {
+    pub recent_messages: Option<usize>,
+    pub summary: Option<String>,
+}
+
+impl Archive {
+    pub fn context(&self) -> RunContext {
+        RunContext {
+            recent_messages: Some(RECENT_MESSAGES),
+            summary: (!self.summary.is_empty()).then(|| {
+                format!(
+                    "Summary of the earlier story, older than the messages you can see:\n{}",
+                    self.summary
+                )
+            }),
+        }
+    }
+
+    // Count messages just added to the thread.
+    pub fn added(&mut self, count: usize) {
+        if let Some(uncovered) = &mut self.uncovered {
+            *uncovered += count;
+        }
+    }
+
+    fn due(&self) -> bool {
+        self.uncovered
+            .is_none_or(|uncovered| uncovered >= RECENT_MESSAGES + ARCHIVE_EVERY)
+    }
+}
+
+// The last `count` messages of the thread, or all of them, oldest first.
+async fn last_messages(
+    backend: &dyn AssistantBackend,
+    thread_id: &str,
+    count: Option<usize>,
+) -> Result<Vec<Message>, ShadowrunError> {
+    let mut messages = Vec::new();
+    let mut older_than = None;
+    loop {
+        let wanted = count.map_or(PAGE_LIMIT, |count| count - messages.len());
+        if wanted == 0 {
+            break;
+        }
+        let mut page = backend
+            .list_messages(thread_id, wanted.min(PAGE_LIMIT), older_than.as_deref())
+            .await?;
+        page.messages.append(&mut messages);
+        messages = page.messages;
+        match page.older_than {
+            Some(cursor) => older_than = Some(cursor),
+            None => break,
+        }
+    }
+    Ok(messages)
+}
+
+// What the archivist is asked to merge.
+pub fn request(summary: &str, messages: &[Message]) -> String {
+    let transcript: Vec<String> = messages.iter().map(mirror::plain_text).collect();
+    format!(
+        "Summary so far:\n{}\n\nNew transcript:\n{}",
+        if summary.is_empty() {
+            "(none)"
+        } else {
+            summary
+        },
+        transcript.join("\n\n")
+    )
+}
+
+// Fold the messages that left the run window into the summary, when enough have. Returns whether
+// the summary changed.
+pub async fn bring_up_to_date(
+    backend: &dyn AssistantBackend,
+    thread_id: &str,
+    archive: &mut Archive,
+) -> Result<bool, ShadowrunError> {
+    if !archive.due() {
+        return Ok(false);
+    }
+    // A save from before the archivist is read whole, once.
+    let uncovered = last_messages(backend, thread_id, archive.uncovered).await?;
+    let leaving = uncovered.len().saturating_sub(RECENT_MESSAGES);
+    if leaving == 0 {
+        archive.uncovered = Some(uncovered.len());
+        return Ok(false);
+    }
+    archive.summary = backend
+        .summarize(&request(&archive.summary, &uncovered[..leaving]))
+        .await?;
+    archive.uncovered = Some(uncovered.len() - leaving);
+    Ok(true)
+}
+
+// Start a new thread that picks the story up where `thread_id` stands, for a branch of the game.
+// The original thread is left as it is. Returns the new thread and its archive.
+pub async fn fork(
+    backend: &dyn AssistantBackend,
+    thread_id: &str,
+    archive: &Archive,
+) -> Result<(String, Archive), ShadowrunError> {
+    let uncovered = last_messages(backend, thread_id, archive.uncovered).await?;
+    let summary = backend
+        .summarize(&request(&archive.summary, &uncovered))
+        .await?;
+    seed(
+        backend,
+        "This game branches off another timeline.",
+        &summary,
+    )
+    .await
+}
+
+// The messages of the save's current thread its summary does not cover, as the save recorded
+// them, oldest first.
+fn saved_messages(game_state: &GameState) -> Vec<Message> {
+    let thread_start = game_state
+        .events
+        .iter()
+        .rposition(|recorded| matches!(recorded.event, GameEvent::Thread { .. }))
+        .map_or(0, |index| index + 1);
+    let messages: Vec<Message> = game_state.events[thread_start..]
+        .iter()
+        .filter_map(|recorded| match &recorded.event {
+            GameEvent::Message { sender, text } => Some(Message::new(sender.clone(), text.clone())),
+            _ => None,
+        })
+        .collect();
+    let covered = game_state
+        .archive
+        .uncovered
+        .map_or(0, |uncovered| messages.len().saturating_sub(uncovered));
+    messages[covered..].to_vec()
+}
+
+// Start a new thread for a save whose thread OpenAI no longer has, from the summary and the
+// messages kept in the save. Returns the new thread and its archive.
+pub async fn rebuild(
+    backend: &dyn AssistantBackend,
+    game_state: &GameState,
+) -> Result<(String, Archive), ShadowrunError> {
+    let messages = saved_messages(game_state);
+    let summary = if messages.is_empty() {
+        game_state.archive.summary.clone()
+    } else {
+        backend
+            .summarize(&request(&game_state.archive.summary, &messages))
+            .await?
+    };
+    seed(
+        backend,
+        "This game's earlier conversation was lost and picks up again here.",
+        if summary.is_empty() {
+            "(none kept)"
+        } else {
+            &summary
+        },
+    )
+    .await
+}
+
+// A new thread that opens with the story so far.
+async fn seed(
+    backend: &dyn AssistantBackend,
+    intro: &str,
+    summary: &str,
+) -> Result<(String, Archive), ShadowrunError> {
+    let thread_id = backend.create_thread().await?;
+    backend
+        .add_message(
+            &thread_id,
+            &format!(
+                "{} The story so far:\n{}\n\nContinue from the last moment described, in the same \
+                 language. Answer in valid json",
+                intro, summary
+            ),
+        )
+        .await?;
+    // The seed message holds the whole summary, so the archive starts empty.
+    let archive = Archive {
+        summary: String::new(),
+        uncovered: Some(1),
+    };
+    Ok((thread_id, archive))
+}
@@ -2,22 +2,24 @@ use async_openai::types::{ResponseFormat, ResponseFormatJsonSchema};
 use include_dir::{include_dir, Dir, DirEntry};
 use serde_json::Value;
 use std::error::Error;
-use std::fs::File;
-use std::io::Read;
 
-use crate::save::SAVE_DIR;
+use crate::journal::{self, Journal};
+use crate::paths;
+use crate::plugins;
+use crate::ruleset::RulesetKind;
 use async_openai::{
     config::OpenAIConfig,
     types::{
         AssistantObject, AssistantTools, AssistantsApiResponseFormatOption,
-        CreateAssistantRequestArgs, FunctionObject,
+        CreateAssistantRequestArgs, FunctionObject, ModifyAssistantRequestArgs,
     },
     Client,
 };
 
 static ASSETS_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/assets");
+pub const ASSISTANT_MODEL: &str = "gpt-4o-mini";
 
-fn load_function_objects() -> Result<Vec<FunctionObject>, Box<dyn Error>> {
+fn builtin_function_objects() -> Result<Vec<FunctionObject>, Box<dyn Error>> {
     let folder_dir = ASSETS_DIR
         .get_dir("assistant_functions")
         .expect("Failed to get assistant_functions directory");
@@ -64,14 +66,47 @@ fn load_function_objects() -> Result<Vec<FunctionObject>, Box<dyn Error>> {
     Ok(function_objects)
 }
 
-fn define_schema() -> Result<ResponseFormat, Box<dyn Error>> {
+// The tools the game answers itself, which plugins cannot take the names of.
+pub fn builtin_tool_names() -> Vec<String> {
+    builtin_function_objects()
+        .map(|functions| {
+            functions
+                .into_iter()
+                .map(|function| function.name)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// The built-in tools of the ruleset, then those of the registered plugins.
+fn load_function_objects(ruleset: RulesetKind) -> Result<Vec<FunctionObject>, Box<dyn Error>> {
+    let mut function_objects = builtin_function_objects()?;
+    function_objects.retain(|function| ruleset.rules().offers_tool(&function.name));
+    function_objects.extend(
+        plugins::definitions()
+            .into_iter()
+            .map(|definition| FunctionObject {
+                name: definition.name,
+                description: Some(definition.description),
+                parameters: Some(definition.parameters),
+                strict: Some(definition.strict),
+            }),
+    );
+    Ok(function_objects)
+}
+
+// The GameMessage schema every answer must follow, as sent to OpenAI.
+pub fn response_schema() -> Result<Value, serde_json::Error> {
     let schema_file = ASSETS_DIR
         .get_file("assistant_instructions/schema.json")
         .expect("Failed to get assistant schema file")
         .contents_utf8()
         .expect("Failed to read assistant schema file");
+    serde_json::from_str(schema_file)
+}
 
-    let json_schema: Value = serde_json::from_str(schema_file)?;
+pub fn define_schema() -> Result<ResponseFormat, Box<dyn Error>> {
+    let json_schema = response_schema()?;
     let name = json_schema["name"].as_str().unwrap();
     let schema = json_schema["schema"].clone(); // This extracts the parameters part
     let strict = json_schema["strict"].as_bool().unwrap_or(true); // Defaults to true if not found
@@ -86,18 +121,40 @@ fn define_schema() -> Result<ResponseFormat, Box<dyn Error>> {
     Ok(response_format)
 }
 
+fn instructions(ruleset: RulesetKind) -> &'static str {
+    ASSETS_DIR
+        .get_file(ruleset.rules().instructions_file())
+        .expect("Failed to get assistant instructions file")
+        .contents_utf8()
+        .expect("Failed to read assistant instructions file")
+}
+
+// A checksum of what this release gives a new assistant for `ruleset`: the instructions, the
+// built-in tools and the answer schema. A save keeps the one its assistant got, so the game can
+// tell when a release changed the prompt.
+pub fn instructions_version(ruleset: RulesetKind) -> u32 {
+    let mut prompt = instructions(ruleset).to_string();
+    for function in builtin_function_objects().unwrap_or_default() {
+        if ruleset.rules().offers_tool(&function.name) {
+            prompt.push_str(&serde_json::to_string(&function).unwrap_or_default());
+        }
+    }
+    prompt.push_str(
+        &response_schema()
+            .map(|schema| schema.to_string())
+            .unwrap_or_default(),
+    );
+    journal::checksum(prompt.as_bytes())
+}
+
 // Function to create the assistant with multiple function objects
 pub async fn create_assistant(
     client: &Client<OpenAIConfig>,
     name: &str,
+    ruleset: RulesetKind,
 ) -> Result<AssistantObject, Box<dyn Error>> {
     // Load all FunctionObjects from the specified folder
-    let function_objects = load_function_objects()?;
-    let instructions = ASSETS_DIR
-        .get_file("assistant_instructions/instructions.json")
-        .expect("Failed to get assistant instructions file")
-        .contents_utf8()
-        .expect("Failed to read assistant instructions file");
+    let function_objects = load_function_objects(ruleset)?;
 
     // Convert FunctionObjects to AssistantTools using the Into trait
     let assistant_tools = function_objects
@@ -113,8 +170,8 @@ pub async fn create_assistant(
     let create_assistant_request = CreateAssistantRequestArgs::default()
         .name(name)
         .temperature(0.7)
-        .instructions(instructions)
-        .model("gpt-4o-mini")
+        .instructions(instructions(ruleset))
+        .model(ASSISTANT_MODEL)
         .response_format(AssistantsApiResponseFormatOption::Format(response_format))
         .tools(assistant_tools) // Pass the vector of AssistantTools
         .build()?;
@@ -124,26 +181,47 @@ pub async fn create_assistant(
     Ok(assistant)
 }
 
+// Give an existing assistant the instructions, tools and schema of this release. It keeps its id,
+// so the save and its thread go on with it.
+pub async fn update_assistant(
+    client: &Client<OpenAIConfig>,
+    assistant_id: &str,
+    ruleset: RulesetKind,
+) -> Result<AssistantObject, Box<dyn Error>> {
+    let assistant_tools = load_function_objects(ruleset)?
+        .into_iter()
+        .map(Into::into)
+        .collect::<Vec<AssistantTools>>();
+    let update_assistant_request = ModifyAssistantRequestArgs::default()
+        .instructions(instructions(ruleset))
+        .response_format(AssistantsApiResponseFormatOption::Format(define_schema()?))
+        .tools(assistant_tools)
+        .build()?;
+    let assistant = client
+        .assistants()
+        .update(assistant_id, update_assistant_request)
+        .await?;
+    Ok(assistant)
+}
+
+// Show the assistant under the save's new name on the OpenAI dashboard.
+pub async fn rename_assistant(
+    client: &Client<OpenAIConfig>,
+    assistant_id: &str,
+    name: &str,
+) -> Result<AssistantObject, Box<dyn Error>> {
+    let rename_request = ModifyAssistantRequestArgs::default().name(name).build()?;
+    let assistant = client
+        .assistants()
+        .update(assistant_id, rename_request)
+        .await?;
+    Ok(assistant)
+}
+
 pub fn get_assistant_id(save_name: &str) -> Result<String, Box<dyn Error>> {
-    let file_path = format!("{}/{}.json", SAVE_DIR, save_name);
-    let mut file = File::open(file_path)?;
-
-    // Read the file content into a string
-    let mut content = String::new();
-    file.read_to_string(&mut content)?;
-
-    // Parse the JSON string into a serde_json::Value
-    let json: Value = serde_json::from_str(&content)?;
-
-    // Extract the "assistant_id" field
-    if let Some(assistant_id) = json["assistant_id"].as_str() {
-        Ok(assistant_id.to_string())
-    } else {
-        Err(Box::new(std::io::Error::new(
-            std::io::ErrorKind::InvalidData,
-            "Couldn't find assistant_id in the file",
-        )))
-    }
+    // Replay the journal too, in case the assistant changed since the last snapshot.
+    let (_, game_state) = Journal::load(&paths::save_dir(), save_name)?;
+    Ok(game_state.assistant_id)
 }
 
 pub async fn delete_assistant(client: &Client<OpenAIConfig>, assistant_id: &str) {
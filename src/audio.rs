@@ -1,67 +1,323 @@
+use crate::elevenlabs;
 use crate::error::{AIError, AudioError};
+use crate::message::{Fluff, Speaker};
+use crate::narration_cache;
+use crate::paths;
+use crate::settings::{Settings, SpeechBackend};
 use async_openai::{
     config::OpenAIConfig,
     types::{CreateSpeechRequestArgs, CreateTranscriptionRequestArgs, SpeechModel, Voice},
     Audio,
 };
-use chrono::Local;
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{FromSample, Sample};
-use rodio::{Decoder, OutputStream, Sink};
-use std::io::{BufReader, BufWriter};
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, StreamError};
+use std::collections::HashMap;
+use std::io::{BufReader, BufWriter, Write};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
-use std::{fs::File, path::PathBuf};
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+};
 use std::{
     fs::{self},
-    path::Path,
     thread,
     time::Duration,
 };
-use uuid::Uuid;
 
+// How the narration is spoken, from the settings when a turn's audio starts generating.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpeechSettings {
+    pub model: SpeechModel,
+    pub speed: f32,
+    pub narrator_voice: Option<Voice>,
+    pub player_voice: Option<Voice>,
+    pub backend: SpeechBackend,
+    pub elevenlabs_api_key: Option<String>,
+    pub elevenlabs_voices: HashMap<String, String>,
+}
+
+// The speaker name the player's action is read under, e.g. for its own ElevenLabs voice.
+pub const PLAYER_SPEAKER: &str = "Player";
+
+// The clip index of the player's action, read back before the lines of the narration.
+pub const PLAYER_LINE: usize = usize::MAX;
+
+impl SpeechSettings {
+    pub fn from_settings(settings: &Settings) -> Self {
+        SpeechSettings {
+            model: match settings.speech_model.as_str() {
+                "tts-1" => SpeechModel::Tts1,
+                "tts-1-hd" => SpeechModel::Tts1Hd,
+                other => SpeechModel::Other(other.to_string()),
+            },
+            speed: settings.speech_speed,
+            narrator_voice: settings.narrator_voice.clone(),
+            player_voice: settings.player_voice.clone(),
+            backend: settings.speech_backend,
+            elevenlabs_api_key: settings.elevenlabs_api_key.clone(),
+            elevenlabs_voices: settings.elevenlabs_voices.clone(),
+        }
+    }
+
+    // The ElevenLabs voice that reads `speaker`, or None when OpenAI reads the narration.
+    pub fn elevenlabs_voice(&self, speaker: &str, voice: &Voice) -> Option<String> {
+        (self.backend == SpeechBackend::ElevenLabs)
+            .then(|| elevenlabs::voice_id(&self.elevenlabs_voices, speaker, voice))
+    }
+
+    // The voice of a speaker: picked from their name and gender, except for a chosen Narrator voice.
+    pub fn voice_for(&self, speaker: &Speaker) -> Voice {
+        match &self.narrator_voice {
+            Some(voice) if speaker.name == "Narrator" => voice.clone(),
+            _ => speaker.voice.clone().unwrap_or_default(),
+        }
+    }
+
+    // The narration cache's name for `text` spoken in `voice`.
+    pub fn cache_key(&self, voice: &Voice, text: &str) -> String {
+        narration_cache::key(&api_name(voice), &api_name(&self.model), self.speed, text)
+    }
+}
+
+// How the API spells a voice or model, e.g. "onyx" or "tts-1-hd".
+fn api_name(value: &(impl serde::Serialize + std::fmt::Debug)) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => format!("{:?}", value),
+    }
+}
+
+// The audio of one clip, from the narration cache when it was generated before, spoken by the
+// backend of the settings.
 pub async fn generate_audio(
     client: &async_openai::Client<OpenAIConfig>,
-    save_name: &str,
-    text: &str,
-    voice: Voice,
+    clip: &Clip,
+    speech: &SpeechSettings,
 ) -> Result<PathBuf, AIError> {
-    let audio = Audio::new(client);
+    let cache_dir = paths::narration_dir();
+    let elevenlabs_voice = speech.elevenlabs_voice(&clip.speaker, &clip.voice);
+    let key = match &elevenlabs_voice {
+        Some(id) => narration_cache::key(
+            &format!("elevenlabs:{}", id),
+            elevenlabs::MODEL,
+            speech.speed,
+            &clip.text,
+        ),
+        None => speech.cache_key(&clip.voice, &clip.text),
+    };
+    if let Some(path) = narration_cache::lookup(&cache_dir, &key) {
+        return Ok(path);
+    }
 
-    let response = audio
-        .speech(
-            CreateSpeechRequestArgs::default()
-                .input(text)
-                .voice(voice)
-                .model(SpeechModel::Tts1)
-                .speed(1.3)
-                .build()
-                .map_err(AIError::OpenAI)?,
-        )
-        .await
-        .map_err(AIError::OpenAI)?;
-
-    let uuid = Uuid::new_v4();
-    let file_name = format!(
-        "{}_{}_{}.mp3",
-        save_name,
-        Local::now().format("%Y%m%d_%H%M%S"),
-        uuid
-    );
-    let folder_path = Path::new("./data/logs").join(save_name);
-    let file_path = folder_path.join(file_name);
-    fs::create_dir_all(&folder_path).map_err(AIError::Io)?;
-    response
-        .save(file_path.to_str().unwrap())
-        .await
-        .map_err(AIError::OpenAI)?;
+    let bytes = match elevenlabs_voice {
+        Some(id) => {
+            let api_key = speech.elevenlabs_api_key.as_deref().ok_or_else(|| {
+                AIError::ElevenLabs("no API key, enter one in the settings".to_string())
+            })?;
+            elevenlabs::speak(api_key, &id, &clip.text, speech.speed).await?
+        }
+        None => Audio::new(client)
+            .speech(
+                CreateSpeechRequestArgs::default()
+                    .input(clip.text.as_str())
+                    .voice(clip.voice.clone())
+                    .model(speech.model.clone())
+                    .speed(speech.speed)
+                    .build()
+                    .map_err(AIError::OpenAI)?,
+            )
+            .await
+            .map_err(AIError::OpenAI)?
+            .bytes
+            .to_vec(),
+    };
 
+    // Written aside first, so an interrupted write never passes for a cached line.
+    fs::create_dir_all(&cache_dir).map_err(AIError::Io)?;
+    let file_path = narration_cache::file_for(&cache_dir, &key);
+    let partial_path = file_path.with_extension("part");
+    fs::write(&partial_path, &bytes).map_err(AIError::Io)?;
+    fs::rename(&partial_path, &file_path).map_err(AIError::Io)?;
+
+    if let Err(e) = narration_cache::evict(&cache_dir, narration_cache::CACHE_LIMIT_BYTES) {
+        log::warn!("Failed to trim the narration cache: {}", e);
+    }
     Ok(file_path)
 }
 
-pub fn play_audio(file_path: PathBuf) -> Result<(), AIError> {
-    let (_stream, stream_handle) =
-        OutputStream::try_default().expect("Failed to get output stream");
+// The terminal bell, as a cue that needs no audio device, e.g. for a glitch.
+pub fn ring_bell() {
+    let mut stdout = std::io::stdout();
+    let _ = stdout.write_all(b"\x07").and_then(|()| stdout.flush());
+}
+
+// Names of the microphones present now, for the settings.
+pub fn input_device_names() -> Vec<String> {
+    match cpal::default_host().input_devices() {
+        Ok(devices) => devices.filter_map(|device| device.name().ok()).collect(),
+        Err(e) => {
+            log::warn!("Could not list input devices: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+// Names of the speakers present now, for the settings.
+pub fn output_device_names() -> Vec<String> {
+    match cpal::default_host().output_devices() {
+        Ok(devices) => devices.filter_map(|device| device.name().ok()).collect(),
+        Err(e) => {
+            log::warn!("Could not list output devices: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+// The device after (or before) `current` among the system default, as None, and `devices`. A device
+// that is gone counts as the default.
+pub fn next_device(devices: &[String], current: Option<&str>, forward: bool) -> Option<String> {
+    let count = devices.len() + 1;
+    let position = current
+        .and_then(|name| devices.iter().position(|device| device == name))
+        .map_or(0, |index| index + 1);
+    let next = if forward {
+        (position + 1) % count
+    } else {
+        (position + count - 1) % count
+    };
+    next.checked_sub(1).map(|index| devices[index].clone())
+}
+
+// The device called `name`, or None with a warning when it is no longer there.
+fn find_device(
+    devices: Result<impl Iterator<Item = cpal::Device>, cpal::DevicesError>,
+    name: &str,
+) -> Option<cpal::Device> {
+    let device = devices
+        .ok()?
+        .find(|device| device.name().is_ok_and(|device_name| device_name == name));
+    if device.is_none() {
+        log::warn!(
+            "Audio device '{}' is not available, using the default",
+            name
+        );
+    }
+    device
+}
+
+fn output_stream(device: Option<&str>) -> Result<(OutputStream, OutputStreamHandle), StreamError> {
+    if let Some(device) =
+        device.and_then(|name| find_device(cpal::default_host().output_devices(), name))
+    {
+        match OutputStream::try_from_device(&device) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => log::warn!("Could not open the chosen output device: {}", e),
+        }
+    }
+    OutputStream::try_default()
+}
+
+// Sentences shorter than this are spoken with the next one rather than asked for on their own.
+pub const MIN_CHUNK_CHARS: usize = 40;
+
+// A dialogue line cut into the pieces it is spoken in: sentences, with the short ones kept with the
+// next, so the first piece comes back from the speech API quickly and the rest follow as it plays.
+pub fn speech_chunks(text: &str) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut chars = text.trim().chars().peekable();
+    while let Some(c) = chars.next() {
+        current.push(c);
+        let ends_sentence = matches!(c, '.' | '!' | '?' | '…' | '。' | '！' | '？')
+            && chars
+                .peek()
+                .is_none_or(|next| next.is_whitespace() || matches!(c, '。' | '！' | '？'));
+        if ends_sentence && current.trim().chars().count() >= MIN_CHUNK_CHARS {
+            chunks.push(current.trim().to_string());
+            current.clear();
+        }
+    }
+    if !current.trim().is_empty() {
+        // A short end goes with the sentence before it.
+        match chunks.last_mut() {
+            Some(last) if current.trim().chars().count() < MIN_CHUNK_CHARS => {
+                last.push(' ');
+                last.push_str(current.trim());
+            }
+            _ => chunks.push(current.trim().to_string()),
+        }
+    }
+    chunks
+}
+
+// A piece of a turn's audio: the text, who says it and in which voice, and the dialogue line it
+// belongs to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clip {
+    pub line: usize,
+    pub speaker: String,
+    pub voice: Voice,
+    pub text: String,
+}
+
+// The clips of a turn in the order they are played: the player's action first when it is read
+// back, then each dialogue line in its speaker's voice.
+pub fn narration_clips(fluff: &Fluff, action: Option<&str>, speech: &SpeechSettings) -> Vec<Clip> {
+    let read_back = speech
+        .player_voice
+        .as_ref()
+        .zip(action)
+        .into_iter()
+        .flat_map(|(voice, action)| {
+            speech_chunks(action).into_iter().map(|text| Clip {
+                line: PLAYER_LINE,
+                speaker: PLAYER_SPEAKER.to_string(),
+                voice: voice.clone(),
+                text,
+            })
+        });
+    let dialogue = fluff.dialogue.iter().enumerate().flat_map(|(index, line)| {
+        let speaker = fluff
+            .speakers
+            .iter()
+            .find(|s| s.index == line.speaker_index);
+        let voice = speaker
+            .map(|s| speech.voice_for(s))
+            .or_else(|| speech.narrator_voice.clone())
+            .unwrap_or_default();
+        let name = speaker.map_or("Narrator", |s| s.name.as_str()).to_string();
+        speech_chunks(&line.text).into_iter().map(move |text| Clip {
+            line: index,
+            speaker: name.clone(),
+            voice: voice.clone(),
+            text,
+        })
+    });
+    read_back.chain(dialogue).collect()
+}
+
+// Play a turn's clips one after the other as they arrive, calling `started` when the dialogue line
+// being spoken changes. Returns once the sender is dropped and the last clip is played.
+pub fn play_narration(
+    clips: std::sync::mpsc::Receiver<(usize, PathBuf)>,
+    device: Option<&str>,
+    mut started: impl FnMut(usize),
+) {
+    let mut playing = None;
+    for (line, path) in clips {
+        if playing != Some(line) {
+            started(line);
+            playing = Some(line);
+        }
+        let _status = play_audio(path, device);
+    }
+}
+
+// Play a clip on the output device by name, or the default one when None or gone.
+pub fn play_audio(file_path: PathBuf, device: Option<&str>) -> Result<(), AIError> {
+    let (_stream, stream_handle) = output_stream(device).expect("Failed to get output stream");
     let sink = Sink::try_new(&stream_handle).expect("Failed to create audio sink");
 
     let file = File::open(file_path).expect("Failed to open audio file");
@@ -73,12 +329,16 @@ pub fn play_audio(file_path: PathBuf) -> Result<(), AIError> {
     Ok(())
 }
 
-const PATH: &str = "./data/recording.wav";
-
-pub fn record_audio(is_recording: Arc<AtomicBool>) -> Result<(), AudioError> {
+// Record into `path` from the input device by name, or the default one when None or gone.
+pub fn record_audio(
+    is_recording: Arc<AtomicBool>,
+    path: &Path,
+    device: Option<&str>,
+) -> Result<(), AudioError> {
     let host = cpal::default_host();
-    let device = host
-        .default_input_device()
+    let device = device
+        .and_then(|name| find_device(host.input_devices(), name))
+        .or_else(|| host.default_input_device())
         .ok_or_else(|| AudioError::AudioRecordingError("No input device available".into()))?;
 
     let config = device
@@ -86,7 +346,10 @@ pub fn record_audio(is_recording: Arc<AtomicBool>) -> Result<(), AudioError> {
         .map_err(|e| AudioError::AudioRecordingError(e.to_string()))?;
 
     let spec = wav_spec_from_config(&config);
-    let writer = hound::WavWriter::create(PATH, spec)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let writer = hound::WavWriter::create(path, spec)?;
     let writer = Arc::new(Mutex::new(Some(writer)));
     let writer_clone = writer.clone();
 
@@ -147,11 +410,11 @@ pub fn record_audio(is_recording: Arc<AtomicBool>) -> Result<(), AudioError> {
     Ok(())
 }
 
-pub fn start_recording(is_recording: &Arc<AtomicBool>) {
+pub fn start_recording(is_recording: &Arc<AtomicBool>, path: PathBuf, device: Option<String>) {
     let is_recording_clone = is_recording.clone();
 
     thread::spawn(move || {
-        if let Err(e) = record_audio(is_recording_clone) {
+        if let Err(e) = record_audio(is_recording_clone, &path, device.as_deref()) {
             eprintln!("Error recording audio: {:?}", e);
         }
     });
@@ -191,20 +454,23 @@ where
     }
 }
 
+// Transcribe the last recording. A language hint (ISO-639-1, e.g. "fr") keeps short French or
+// Japanese lines from being taken for another language.
 pub async fn transcribe_audio(
     client: &async_openai::Client<OpenAIConfig>,
+    recording_path: &Path,
+    model: &str,
+    language: Option<&str>,
 ) -> Result<String, AudioError> {
     let audio = Audio::new(client);
-    let recording_path = PATH;
 
+    let mut request = CreateTranscriptionRequestArgs::default();
+    request.file(recording_path).model(model);
+    if let Some(language) = language {
+        request.language(language);
+    }
     match audio
-        .transcribe(
-            CreateTranscriptionRequestArgs::default()
-                .file(recording_path)
-                .model("whisper-1")
-                .build()
-                .map_err(AudioError::OpenAI)?,
-        )
+        .transcribe(request.build().map_err(AudioError::OpenAI)?)
         .await
     {
         Ok(transcription) => Ok(transcription.text),
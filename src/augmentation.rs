@@ -0,0 +1,132 @@
+// augmentation.rs
+// What the common augmentations do to the derived attributes. Cyberware and bioware are kept on
+// the sheet by name, as the assistant writes them, e.g. "Wired Reflexes 2" or "Dermal Plating
+// (Rating 3)"; the name is looked up here for its essence cost, initiative dice and armor.
+// Augmentations not listed here cost no essence and change nothing.
+
+// One augmentation, by its rating.
+struct Known {
+    name: &'static str,
+    max_rating: u8,
+    essence: fn(u8) -> f32,
+    initiative_dice: fn(u8) -> u8,
+    armor: fn(u8) -> u8,
+}
+
+fn none(_rating: u8) -> u8 {
+    0
+}
+
+fn per_rating(rating: u8) -> u8 {
+    rating
+}
+
+const KNOWN: &[Known] = &[
+    Known {
+        name: "wired reflexes",
+        max_rating: 3,
+        essence: |rating| [2.0, 3.0, 5.0][rating as usize - 1],
+        initiative_dice: per_rating,
+        armor: none,
+    },
+    Known {
+        name: "synaptic booster",
+        max_rating: 3,
+        essence: |rating| 0.5 * rating as f32,
+        initiative_dice: per_rating,
+        armor: none,
+    },
+    Known {
+        name: "reaction enhancers",
+        max_rating: 3,
+        essence: |rating| 0.3 * rating as f32,
+        initiative_dice: none,
+        armor: none,
+    },
+    Known {
+        name: "dermal plating",
+        max_rating: 6,
+        essence: |rating| 0.5 * rating as f32,
+        initiative_dice: none,
+        armor: per_rating,
+    },
+    Known {
+        name: "orthoskin",
+        max_rating: 4,
+        essence: |rating| 0.25 * rating as f32,
+        initiative_dice: none,
+        armor: per_rating,
+    },
+    Known {
+        name: "muscle replacement",
+        max_rating: 4,
+        essence: |rating| rating as f32,
+        initiative_dice: none,
+        armor: none,
+    },
+    Known {
+        name: "cybereyes",
+        max_rating: 4,
+        essence: |rating| 0.1 + 0.1 * rating as f32,
+        initiative_dice: none,
+        armor: none,
+    },
+    Known {
+        name: "cyberears",
+        max_rating: 4,
+        essence: |rating| 0.1 + 0.1 * rating as f32,
+        initiative_dice: none,
+        armor: none,
+    },
+    Known {
+        name: "smartlink",
+        max_rating: 1,
+        essence: |_| 0.2,
+        initiative_dice: none,
+        armor: none,
+    },
+    Known {
+        name: "datajack",
+        max_rating: 1,
+        essence: |_| 0.1,
+        initiative_dice: none,
+        armor: none,
+    },
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AugmentationStats {
+    pub essence: f32,
+    pub initiative_dice: u8,
+    pub armor: u8,
+}
+
+// The stats of one augmentation, if it is a known one.
+pub fn lookup(name: &str) -> Option<AugmentationStats> {
+    let name = name.to_lowercase();
+    let known = KNOWN.iter().find(|known| name.starts_with(known.name))?;
+    let rest = &name[known.name.len()..];
+    let rating = rest
+        .split(|c: char| !c.is_ascii_digit())
+        .find(|digits| !digits.is_empty())
+        .and_then(|digits| digits.parse::<u8>().ok())
+        .unwrap_or(1)
+        .clamp(1, known.max_rating);
+    Some(AugmentationStats {
+        essence: (known.essence)(rating),
+        initiative_dice: (known.initiative_dice)(rating),
+        armor: (known.armor)(rating),
+    })
+}
+
+// What all of a character's augmentations add up to.
+pub fn total<'a>(names: impl IntoIterator<Item = &'a String>) -> AugmentationStats {
+    names.into_iter().filter_map(|name| lookup(name)).fold(
+        AugmentationStats::default(),
+        |total, stats| AugmentationStats {
+            essence: total.essence + stats.essence,
+            initiative_dice: total.initiative_dice.saturating_add(stats.initiative_dice),
+            armor: total.armor.saturating_add(stats.armor),
+        },
+    )
+}
@@ -0,0 +1,710 @@
+// backend.rs
+// The assistant calls a game turn makes, behind a trait so the turn loop can run against OpenAI or
+// against a scripted mock in tests.
+//
+// Only threads, runs and assistants go through here. Images, speech and transcription still use
+// the OpenAI client directly.
+
+use crate::archivist::{self, RunContext};
+use crate::assistant::{self, ASSISTANT_MODEL};
+use crate::error::ShadowrunError;
+use crate::message::{Fluff, FluffLine, GameMessage, Gender, Message, MessageType, Speaker};
+use crate::ruleset::RulesetKind;
+use crate::status::Shared;
+use async_openai::types::{
+    AssistantsApiResponseFormatOption, ChatCompletionRequestSystemMessageArgs,
+    ChatCompletionRequestUserMessageArgs, CreateChatCompletionRequestArgs,
+    CreateMessageRequestArgs, CreateRunRequestArgs, CreateThreadRequestArgs, FunctionCall,
+    MessageContent, MessageRole, RunStatus, RunToolCallObject, SubmitToolOutputsRunRequest,
+    ToolsOutputs, TruncationObject, TruncationObjectType,
+};
+use async_openai::{config::OpenAIConfig, Client};
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+
+// Where a run is, as far as the turn loop cares.
+#[derive(Debug, Clone)]
+pub enum RunState {
+    InProgress,
+    Completed,
+    RequiresAction(Vec<RunToolCallObject>),
+    Failed(String),
+}
+
+// Some thread messages in chronological order, with the cursor to the ones before them.
+#[derive(Debug, Clone)]
+pub struct MessagePage {
+    pub messages: Vec<Message>,
+    pub older_than: Option<String>,
+}
+
+#[async_trait]
+pub trait AssistantBackend: Send + Sync {
+    // Create the assistant for a new save and return its id.
+    async fn create_assistant(
+        &self,
+        save_name: &str,
+        ruleset: RulesetKind,
+    ) -> Result<String, ShadowrunError>;
+    async fn delete_assistant(&self, assistant_id: &str);
+    async fn rename_assistant(&self, assistant_id: &str, name: &str) -> Result<(), ShadowrunError>;
+    // Give the assistant the prompt of this release; see assistant::instructions_version.
+    async fn update_assistant(
+        &self,
+        assistant_id: &str,
+        ruleset: RulesetKind,
+    ) -> Result<(), ShadowrunError>;
+    async fn create_thread(&self) -> Result<String, ShadowrunError>;
+    async fn add_message(&self, thread_id: &str, content: &str) -> Result<(), ShadowrunError>;
+    // Start the assistant on the thread and return the run id.
+    async fn create_run(
+        &self,
+        thread_id: &str,
+        assistant_id: &str,
+        context: &RunContext,
+    ) -> Result<String, ShadowrunError>;
+    async fn run_state(&self, thread_id: &str, run_id: &str) -> Result<RunState, ShadowrunError>;
+    async fn submit_tool_outputs(
+        &self,
+        thread_id: &str,
+        run_id: &str,
+        tool_outputs: Vec<ToolsOutputs>,
+    ) -> Result<(), ShadowrunError>;
+    async fn cancel_run(&self, thread_id: &str, run_id: &str) -> Result<(), ShadowrunError>;
+    // Up to `limit` messages, newest first from `older_than` (or from the end of the thread).
+    async fn list_messages(
+        &self,
+        thread_id: &str,
+        limit: usize,
+        older_than: Option<&str>,
+    ) -> Result<MessagePage, ShadowrunError>;
+    // Ask the archivist for a summary; see archivist::request.
+    async fn summarize(&self, request: &str) -> Result<String, ShadowrunError>;
+    // Whether the assistant can be reached, to know when to send the actions queued offline.
+    async fn ping(&self) -> Result<(), ShadowrunError>;
+    // Whether OpenAI still has the save's assistant and thread; an old save's may be gone.
+    async fn assistant_exists(&self, assistant_id: &str) -> Result<bool, ShadowrunError>;
+    async fn thread_exists(&self, thread_id: &str) -> Result<bool, ShadowrunError>;
+}
+
+// Ok(false) for a lookup that failed for a missing id, the error for any other failure.
+fn found<T>(result: Result<T, ShadowrunError>) -> Result<bool, ShadowrunError> {
+    match result {
+        Ok(_) => Ok(true),
+        Err(ShadowrunError::NotFound(_)) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+// The model each agent runs on: the narrator plays the turns, the archivist writes the summaries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgentModels {
+    pub narrator: String,
+    pub archivist: String,
+}
+
+impl Default for AgentModels {
+    fn default() -> Self {
+        AgentModels {
+            narrator: ASSISTANT_MODEL.to_string(),
+            archivist: ASSISTANT_MODEL.to_string(),
+        }
+    }
+}
+
+pub struct OpenAIBackend {
+    client: Client<OpenAIConfig>,
+    // Shared with the GameAI, so a change in the settings applies from the next call.
+    models: Shared<AgentModels>,
+}
+
+impl OpenAIBackend {
+    pub fn new(client: Client<OpenAIConfig>, models: Shared<AgentModels>) -> Self {
+        OpenAIBackend { client, models }
+    }
+}
+
+#[async_trait]
+impl AssistantBackend for OpenAIBackend {
+    async fn create_assistant(
+        &self,
+        save_name: &str,
+        ruleset: RulesetKind,
+    ) -> Result<String, ShadowrunError> {
+        assistant::create_assistant(&self.client, save_name, ruleset)
+            .await
+            .map(|assistant| assistant.id)
+            .map_err(|e| ShadowrunError::OpenAI(e.to_string()))
+    }
+
+    async fn delete_assistant(&self, assistant_id: &str) {
+        assistant::delete_assistant(&self.client, assistant_id).await;
+    }
+
+    async fn rename_assistant(&self, assistant_id: &str, name: &str) -> Result<(), ShadowrunError> {
+        assistant::rename_assistant(&self.client, assistant_id, name)
+            .await
+            .map(|_| ())
+            .map_err(|e| ShadowrunError::OpenAI(e.to_string()))
+    }
+
+    async fn update_assistant(
+        &self,
+        assistant_id: &str,
+        ruleset: RulesetKind,
+    ) -> Result<(), ShadowrunError> {
+        assistant::update_assistant(&self.client, assistant_id, ruleset)
+            .await
+            .map(|_| ())
+            .map_err(|e| ShadowrunError::OpenAI(e.to_string()))
+    }
+
+    async fn create_thread(&self) -> Result<String, ShadowrunError> {
+        let thread = self
+            .client
+            .threads()
+            .create(CreateThreadRequestArgs::default().build()?)
+            .await?;
+        Ok(thread.id)
+    }
+
+    async fn add_message(&self, thread_id: &str, content: &str) -> Result<(), ShadowrunError> {
+        let message_request = CreateMessageRequestArgs::default()
+            .role(MessageRole::User)
+            .content(content)
+            .build()?;
+        self.client
+            .threads()
+            .messages(thread_id)
+            .create(message_request)
+            .await?;
+        Ok(())
+    }
+
+    async fn create_run(
+        &self,
+        thread_id: &str,
+        assistant_id: &str,
+        context: &RunContext,
+    ) -> Result<String, ShadowrunError> {
+        // Assistants made by older versions may have an older schema, so send it with every run.
+        let response_format =
+            assistant::define_schema().map_err(|e| ShadowrunError::Serialization(e.to_string()))?;
+        let mut run_request = CreateRunRequestArgs::default();
+        // The run's model takes over the one the assistant was created with.
+        run_request
+            .assistant_id(assistant_id)
+            .model(self.models.get().narrator)
+            .response_format(AssistantsApiResponseFormatOption::Format(response_format));
+        if let Some(recent_messages) = context.recent_messages {
+            run_request.truncation_strategy(TruncationObject {
+                r#type: TruncationObjectType::LastMessages,
+                last_messages: Some(recent_messages as u32),
+            });
+        }
+        if let Some(summary) = &context.summary {
+            run_request.additional_instructions(summary.as_str());
+        }
+        let run_request = run_request.build()?;
+        let run = self
+            .client
+            .threads()
+            .runs(thread_id)
+            .create(run_request)
+            .await?;
+        Ok(run.id)
+    }
+
+    async fn run_state(&self, thread_id: &str, run_id: &str) -> Result<RunState, ShadowrunError> {
+        let run = self
+            .client
+            .threads()
+            .runs(thread_id)
+            .retrieve(run_id)
+            .await?;
+        Ok(match run.status {
+            RunStatus::Completed => RunState::Completed,
+            RunStatus::RequiresAction => match run.required_action {
+                Some(action) if action.r#type == "submit_tool_outputs" => {
+                    RunState::RequiresAction(action.submit_tool_outputs.tool_calls)
+                }
+                Some(action) => {
+                    return Err(ShadowrunError::Game(format!(
+                        "Unknown required action type: {}",
+                        action.r#type
+                    )))
+                }
+                None => return Err(ShadowrunError::Game("No required action found".to_string())),
+            },
+            RunStatus::Failed | RunStatus::Cancelled | RunStatus::Expired => {
+                RunState::Failed(format!("{:#?}", run.status))
+            }
+            _ => RunState::InProgress,
+        })
+    }
+
+    async fn submit_tool_outputs(
+        &self,
+        thread_id: &str,
+        run_id: &str,
+        tool_outputs: Vec<ToolsOutputs>,
+    ) -> Result<(), ShadowrunError> {
+        let submit_request = SubmitToolOutputsRunRequest {
+            tool_outputs,
+            stream: None,
+        };
+        self.client
+            .threads()
+            .runs(thread_id)
+            .submit_tool_outputs(run_id, submit_request)
+            .await?;
+        Ok(())
+    }
+
+    async fn cancel_run(&self, thread_id: &str, run_id: &str) -> Result<(), ShadowrunError> {
+        self.client.threads().runs(thread_id).cancel(run_id).await?;
+        Ok(())
+    }
+
+    async fn list_messages(
+        &self,
+        thread_id: &str,
+        limit: usize,
+        older_than: Option<&str>,
+    ) -> Result<MessagePage, ShadowrunError> {
+        let limit = limit.to_string();
+        let mut params = vec![("order", "desc"), ("limit", limit.as_str())];
+        if let Some(cursor) = older_than {
+            params.push(("after", cursor));
+        }
+        let page = self
+            .client
+            .threads()
+            .messages(thread_id)
+            .list(&params)
+            .await?;
+
+        let messages = page
+            .data
+            .into_iter()
+            .rev()
+            .filter_map(|message| match message.content.first() {
+                Some(MessageContent::Text(text_content)) => {
+                    let message_type = match message.role {
+                        MessageRole::User => MessageType::User,
+                        MessageRole::Assistant => MessageType::Game,
+                    };
+                    Some(
+                        Message::new(message_type, text_content.text.value.clone())
+                            .at(message.created_at.into()),
+                    )
+                }
+                _ => None,
+            })
+            .collect();
+        Ok(MessagePage {
+            messages,
+            older_than: if page.has_more { page.last_id } else { None },
+        })
+    }
+
+    async fn ping(&self) -> Result<(), ShadowrunError> {
+        self.client.models().list().await?;
+        Ok(())
+    }
+
+    async fn assistant_exists(&self, assistant_id: &str) -> Result<bool, ShadowrunError> {
+        found(
+            self.client
+                .assistants()
+                .retrieve(assistant_id)
+                .await
+                .map_err(ShadowrunError::from),
+        )
+    }
+
+    async fn thread_exists(&self, thread_id: &str) -> Result<bool, ShadowrunError> {
+        found(
+            self.client
+                .threads()
+                .retrieve(thread_id)
+                .await
+                .map_err(ShadowrunError::from),
+        )
+    }
+
+    async fn summarize(&self, request: &str) -> Result<String, ShadowrunError> {
+        let request = CreateChatCompletionRequestArgs::default()
+            .model(self.models.get().archivist)
+            .messages([
+                ChatCompletionRequestSystemMessageArgs::default()
+                    .content(archivist::INSTRUCTIONS)
+                    .build()?
+                    .into(),
+                ChatCompletionRequestUserMessageArgs::default()
+                    .content(request)
+                    .build()?
+                    .into(),
+            ])
+            .build()?;
+        let response = self.client.chat().create(request).await?;
+        response
+            .choices
+            .into_iter()
+            .find_map(|choice| choice.message.content)
+            .ok_or_else(|| ShadowrunError::AI("The archivist sent no summary".to_string()))
+    }
+}
+
+// A plain narrated answer, as the narrator.
+pub fn narration(text: String) -> GameMessage {
+    GameMessage::new(
+        String::new(),
+        Fluff {
+            speakers: vec![Speaker {
+                index: 0,
+                name: "Narrator".to_string(),
+                gender: Gender::NonBinary,
+                voice: None,
+            }],
+            dialogue: vec![FluffLine {
+                speaker_index: 0,
+                text,
+                audio: None,
+            }],
+        },
+        None,
+    )
+}
+
+// What the mock assistant does for one run: call these tools, then answer.
+#[derive(Debug, Clone, Default)]
+pub struct MockTurn {
+    pub tool_calls: Vec<(String, String)>,
+    // A GameMessage as json, or plain text to narrate.
+    pub reply: String,
+    // Answer with the reply exactly as written, e.g. to send malformed json.
+    pub verbatim: bool,
+}
+
+impl MockTurn {
+    pub fn reply(reply: impl Into<String>) -> Self {
+        MockTurn {
+            reply: reply.into(),
+            ..MockTurn::default()
+        }
+    }
+
+    pub fn verbatim(reply: impl Into<String>) -> Self {
+        MockTurn {
+            verbatim: true,
+            ..MockTurn::reply(reply)
+        }
+    }
+
+    pub fn tool(mut self, name: &str, arguments: &str) -> Self {
+        self.tool_calls
+            .push((name.to_string(), arguments.to_string()));
+        self
+    }
+}
+
+struct MockRun {
+    thread_id: String,
+    tool_calls: Vec<RunToolCallObject>,
+    reply: String,
+    submitted: bool,
+    finished: bool,
+}
+
+#[derive(Default)]
+struct MockState {
+    turns: VecDeque<MockTurn>,
+    threads: HashMap<String, Vec<Message>>,
+    runs: HashMap<String, MockRun>,
+    tool_outputs: Vec<String>,
+    contexts: Vec<RunContext>,
+    summaries: usize,
+    next_id: usize,
+    offline: bool,
+    // Assistants and threads deleted, which calls then fail to find.
+    deleted: HashSet<String>,
+    updated: Vec<String>,
+    // The name of each assistant, by id.
+    names: HashMap<String, String>,
+}
+
+impl MockState {
+    fn id(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("{}_mock_{}", prefix, self.next_id)
+    }
+}
+
+// A deterministic assistant: each run plays the next queued turn, or narrates "[mock]" when none
+// is left. Threads live in memory.
+#[derive(Default)]
+pub struct MockBackend {
+    state: Mutex<MockState>,
+}
+
+impl MockBackend {
+    pub fn new(turns: impl IntoIterator<Item = MockTurn>) -> Self {
+        MockBackend {
+            state: Mutex::new(MockState {
+                turns: turns.into_iter().collect(),
+                ..MockState::default()
+            }),
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    // Fail every call as a dropped connection would, until set back.
+    pub fn set_offline(&self, offline: bool) {
+        self.state().offline = offline;
+    }
+
+    fn connection(&self) -> Result<(), ShadowrunError> {
+        if self.state().offline {
+            return Err(ShadowrunError::Network("mock connection down".to_string()));
+        }
+        Ok(())
+    }
+
+    // Delete a thread as OpenAI would, e.g. one expired from an old save.
+    pub fn delete_thread(&self, thread_id: &str) {
+        let mut state = self.state();
+        state.threads.remove(thread_id);
+        state.deleted.insert(thread_id.to_string());
+    }
+
+    fn find(&self, id: &str) -> Result<(), ShadowrunError> {
+        if self.state().deleted.contains(id) {
+            return Err(ShadowrunError::NotFound(format!(
+                "No mock found with id '{}'.",
+                id
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn assistant_name(&self, assistant_id: &str) -> Option<String> {
+        self.state().names.get(assistant_id).cloned()
+    }
+
+    // The assistants given the prompt of this release, in order.
+    pub fn updated_assistants(&self) -> Vec<String> {
+        self.state().updated.clone()
+    }
+
+    pub fn queue(&self, turn: MockTurn) {
+        self.state().turns.push_back(turn);
+    }
+
+    // Every tool output submitted so far, in order.
+    pub fn tool_outputs(&self) -> Vec<String> {
+        self.state().tool_outputs.clone()
+    }
+
+    // What each run was given of the story, in order.
+    pub fn contexts(&self) -> Vec<RunContext> {
+        self.state().contexts.clone()
+    }
+
+    pub fn thread(&self, thread_id: &str) -> Vec<Message> {
+        self.state()
+            .threads
+            .get(thread_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+#[async_trait]
+impl AssistantBackend for MockBackend {
+    async fn create_assistant(
+        &self,
+        save_name: &str,
+        _ruleset: RulesetKind,
+    ) -> Result<String, ShadowrunError> {
+        let mut state = self.state();
+        let assistant_id = state.id("asst");
+        state
+            .names
+            .insert(assistant_id.clone(), save_name.to_string());
+        Ok(assistant_id)
+    }
+
+    async fn delete_assistant(&self, assistant_id: &str) {
+        self.state().deleted.insert(assistant_id.to_string());
+    }
+
+    async fn rename_assistant(&self, assistant_id: &str, name: &str) -> Result<(), ShadowrunError> {
+        self.find(assistant_id)?;
+        self.state()
+            .names
+            .insert(assistant_id.to_string(), name.to_string());
+        Ok(())
+    }
+
+    async fn update_assistant(
+        &self,
+        assistant_id: &str,
+        _ruleset: RulesetKind,
+    ) -> Result<(), ShadowrunError> {
+        self.find(assistant_id)?;
+        self.state().updated.push(assistant_id.to_string());
+        Ok(())
+    }
+
+    async fn create_thread(&self) -> Result<String, ShadowrunError> {
+        let mut state = self.state();
+        let thread_id = state.id("thread");
+        state.threads.insert(thread_id.clone(), Vec::new());
+        Ok(thread_id)
+    }
+
+    async fn add_message(&self, thread_id: &str, content: &str) -> Result<(), ShadowrunError> {
+        self.connection()?;
+        self.find(thread_id)?;
+        self.state()
+            .threads
+            .entry(thread_id.to_string())
+            .or_default()
+            .push(Message::new(MessageType::User, content.to_string()));
+        Ok(())
+    }
+
+    async fn create_run(
+        &self,
+        thread_id: &str,
+        assistant_id: &str,
+        context: &RunContext,
+    ) -> Result<String, ShadowrunError> {
+        self.find(thread_id)?;
+        self.find(assistant_id)?;
+        let mut state = self.state();
+        state.contexts.push(context.clone());
+        let turn = state
+            .turns
+            .pop_front()
+            .unwrap_or_else(|| MockTurn::reply("[mock]"));
+        let reply = if turn.verbatim || serde_json::from_str::<GameMessage>(&turn.reply).is_ok() {
+            turn.reply
+        } else {
+            serde_json::to_string(&narration(turn.reply))?
+        };
+        let tool_calls = turn
+            .tool_calls
+            .into_iter()
+            .enumerate()
+            .map(|(index, (name, arguments))| RunToolCallObject {
+                id: format!("call_mock_{}", index),
+                r#type: "function".to_string(),
+                function: FunctionCall { name, arguments },
+            })
+            .collect();
+        let run_id = state.id("run");
+        state.runs.insert(
+            run_id.clone(),
+            MockRun {
+                thread_id: thread_id.to_string(),
+                tool_calls,
+                reply,
+                submitted: false,
+                finished: false,
+            },
+        );
+        Ok(run_id)
+    }
+
+    async fn run_state(&self, _thread_id: &str, run_id: &str) -> Result<RunState, ShadowrunError> {
+        let mut state = self.state();
+        let run = state
+            .runs
+            .get_mut(run_id)
+            .ok_or_else(|| ShadowrunError::AI(format!("No mock run {}", run_id)))?;
+        if !run.tool_calls.is_empty() && !run.submitted {
+            return Ok(RunState::RequiresAction(run.tool_calls.clone()));
+        }
+        if !run.finished {
+            run.finished = true;
+            let (thread_id, reply) = (run.thread_id.clone(), run.reply.clone());
+            state
+                .threads
+                .entry(thread_id)
+                .or_default()
+                .push(Message::new(MessageType::Game, reply));
+        }
+        Ok(RunState::Completed)
+    }
+
+    async fn submit_tool_outputs(
+        &self,
+        _thread_id: &str,
+        run_id: &str,
+        tool_outputs: Vec<ToolsOutputs>,
+    ) -> Result<(), ShadowrunError> {
+        let mut state = self.state();
+        if let Some(run) = state.runs.get_mut(run_id) {
+            run.submitted = true;
+        }
+        state
+            .tool_outputs
+            .extend(tool_outputs.into_iter().filter_map(|output| output.output));
+        Ok(())
+    }
+
+    async fn cancel_run(&self, _thread_id: &str, run_id: &str) -> Result<(), ShadowrunError> {
+        self.state().runs.remove(run_id);
+        Ok(())
+    }
+
+    async fn list_messages(
+        &self,
+        thread_id: &str,
+        limit: usize,
+        older_than: Option<&str>,
+    ) -> Result<MessagePage, ShadowrunError> {
+        self.find(thread_id)?;
+        let messages = self.thread(thread_id);
+        // The cursor is the index of the oldest message already returned.
+        let end = older_than
+            .and_then(|cursor| cursor.parse().ok())
+            .unwrap_or(messages.len())
+            .min(messages.len());
+        let start = end.saturating_sub(limit);
+        Ok(MessagePage {
+            messages: messages[start..end].to_vec(),
+            older_than: (start > 0).then(|| start.to_string()),
+        })
+    }
+
+    async fn ping(&self) -> Result<(), ShadowrunError> {
+        self.connection()
+    }
+
+    async fn assistant_exists(&self, assistant_id: &str) -> Result<bool, ShadowrunError> {
+        found(self.find(assistant_id))
+    }
+
+    async fn thread_exists(&self, thread_id: &str) -> Result<bool, ShadowrunError> {
+        found(self.find(thread_id))
+    }
+
+    async fn summarize(&self, request: &str) -> Result<String, ShadowrunError> {
+        let mut state = self.state();
+        state.summaries += 1;
+        Ok(format!(
+            "[mock summary {}] {} lines",
+            state.summaries,
+            request.lines().count()
+        ))
+    }
+}
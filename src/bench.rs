@@ -0,0 +1,153 @@
+// bench.rs
+// Timings for the hot drawing paths, rendered to an in-memory terminal, so a slow change shows up
+// before players notice it. Run with `--bench-ui` or `cargo bench`.
+
+use crate::archetype::ARCHETYPES;
+use crate::message::{Fluff, FluffLine, GameMessage, Gender, Message, MessageType, Speaker};
+use crate::transcript::TranscriptCache;
+use crate::ui::game::{draw_character_sheet, parse_markdown, HighlightedSection};
+use ratatui::{backend::TestBackend, style::Style, widgets::Paragraph, Terminal};
+use serde::Serialize;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_ITERATIONS: u32 = 100;
+// A long campaign, about what a few evenings of play leave in the transcript.
+pub const HISTORY_LENGTH: usize = 1000;
+
+const WIDTH: u16 = 160;
+const HEIGHT: u16 = 50;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct BenchResult {
+    pub name: String,
+    pub iterations: u32,
+    pub mean_micros: f64,
+    pub max_micros: f64,
+}
+
+impl fmt::Display for BenchResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:<40} {:>12.1} µs mean {:>12.1} µs max ({} runs)",
+            self.name, self.mean_micros, self.max_micros, self.iterations
+        )
+    }
+}
+
+fn measure(name: &str, iterations: u32, mut run: impl FnMut()) -> BenchResult {
+    // One untimed run, so first-use allocations don't count.
+    run();
+    let mut total = Duration::ZERO;
+    let mut max = Duration::ZERO;
+    for _ in 0..iterations {
+        let start = Instant::now();
+        run();
+        let elapsed = start.elapsed();
+        total += elapsed;
+        max = max.max(elapsed);
+    }
+    BenchResult {
+        name: name.to_string(),
+        iterations,
+        mean_micros: total.as_secs_f64() * 1e6 / iterations.max(1) as f64,
+        max_micros: max.as_secs_f64() * 1e6,
+    }
+}
+
+// A transcript of `count` messages shaped like real play: player actions, narrated answers and
+// the odd system line.
+pub fn sample_history(count: usize) -> Vec<Message> {
+    (0..count)
+        .map(|index| match index % 5 {
+            0 | 2 => Message::new(
+                MessageType::User,
+                format!(
+                    "I sneak past the **Renraku** guard and check door {}.",
+                    index
+                ),
+            ),
+            4 => Message::new(
+                MessageType::System,
+                format!("Sheet changes: Nuyen {}", index),
+            ),
+            _ => {
+                let game_message = GameMessage::new(
+                    format!(
+                        "- Stealth test: **{}** hits\n- Guard notices: no",
+                        index % 6
+                    ),
+                    Fluff {
+                        speakers: vec![Speaker {
+                            index: 0,
+                            name: "Narrator".to_string(),
+                            gender: Gender::NonBinary,
+                            voice: None,
+                        }],
+                        dialogue: vec![FluffLine {
+                            speaker_index: 0,
+                            text: "Rain hammers the neon signs of Redmond as the maglock blinks \
+                                   green. Somewhere below, a drone whines through the alley, its \
+                                   searchlight sweeping the puddles you just crossed."
+                                .repeat(2),
+                            audio: None,
+                        }],
+                    },
+                    None,
+                );
+                Message::new(
+                    MessageType::Game,
+                    serde_json::to_string(&game_message).unwrap_or_default(),
+                )
+            }
+        })
+        .collect()
+}
+
+pub fn run(iterations: u32) -> Vec<BenchResult> {
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT))
+        .expect("the test backend cannot fail to start");
+    let mut results = Vec::new();
+
+    let markdown =
+        "**Stealth** test: you roll *6 dice* and get **3 hits** - the guard never turns \
+                    around, and the **maglock** opens."
+            .to_string();
+    results.push(measure("parse_markdown", iterations, || {
+        std::hint::black_box(parse_markdown(markdown.clone(), Style::default()));
+    }));
+
+    let sheet = ARCHETYPES[0].builder().build();
+    results.push(measure("draw_character_sheet", iterations, || {
+        let _ = terminal.draw(|f| {
+            draw_character_sheet(f, &sheet, f.area(), &HighlightedSection::None);
+        });
+    }));
+
+    let history = sample_history(HISTORY_LENGTH);
+    let width = WIDTH as usize - 2;
+    let name = format!("transcript parse ({} messages)", HISTORY_LENGTH);
+    results.push(measure(&name, iterations, || {
+        let mut cache = TranscriptCache::new();
+        cache.update(&history, width, false);
+        std::hint::black_box(cache.len());
+    }));
+
+    // What each frame costs once the history is parsed: catch up and draw the bottom screenful.
+    let mut cache = TranscriptCache::new();
+    cache.update(&history, width, false);
+    let name = format!("transcript draw ({} messages)", HISTORY_LENGTH);
+    results.push(measure(&name, iterations, || {
+        cache.update(&history, width, false);
+        let start = cache.len().saturating_sub(HEIGHT as usize);
+        let _ = terminal.draw(|f| {
+            f.render_widget(
+                Paragraph::new(cache.lines(start, HEIGHT as usize)),
+                f.area(),
+            );
+        });
+    }));
+
+    results
+}
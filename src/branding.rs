@@ -0,0 +1,58 @@
+// branding.rs
+// A campaign's own title and art for the main and load menus, in place of the built-in ones.
+// `<save>.json` in the branding folder of the data folder gives them, both optional:
+// {"title": "...", "art": "..."}. The main menu shows those of the game it offers to continue,
+// the load menu those of the selected save.
+
+use crate::paths;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Branding {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub art: Option<String>,
+}
+
+pub fn path_for(save_name: &str) -> PathBuf {
+    paths::branding_dir().join(format!("{}.json", save_name))
+}
+
+impl Branding {
+    // The branding of `save_name`, or none if it has no file or the file can't be read.
+    pub fn load(save_name: &str) -> Self {
+        fs::read_to_string(path_for(save_name))
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+}
+
+// The branding last drawn, read again only when another save is shown or its file changes.
+#[derive(Default)]
+pub struct BrandingCache {
+    shown: Option<(String, Option<SystemTime>, Branding)>,
+}
+
+impl BrandingCache {
+    pub fn get(&mut self, save_name: &str) -> &Branding {
+        let modified = fs::metadata(path_for(save_name))
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        if self
+            .shown
+            .as_ref()
+            .is_none_or(|(shown, at, _)| shown != save_name || *at != modified)
+        {
+            self.shown = None;
+        }
+        &self
+            .shown
+            .get_or_insert_with(|| (save_name.to_string(), modified, Branding::load(save_name)))
+            .2
+    }
+}
@@ -0,0 +1,86 @@
+// campaign_stats.rs
+// Totals of a campaign for the stats screen: rolls and hits, money in and out, the characters
+// met and the time played. Worked out from the save's events, so older saves count what they
+// recorded since events were kept.
+
+use crate::events::GameEvent;
+use crate::game_state::{GameState, PlayStats};
+
+// Rolls are grouped by hits up to this many, the last group holding this many or more.
+pub const HIT_GROUPS: usize = 7;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CampaignStats {
+    pub save_name: String,
+    pub rolls: u32,
+    // Rolls that counted hits, as opposed to rolls that added the dice up.
+    pub hit_rolls: u32,
+    pub hits: u32,
+    pub glitches: u32,
+    // How many hit rolls had 0, 1, ... hits.
+    pub hit_counts: [u32; HIT_GROUPS],
+    pub nuyen_earned: u64,
+    pub nuyen_spent: u64,
+    // Everyone in the game but the player's character.
+    pub npcs_met: usize,
+    pub play: PlayStats,
+}
+
+impl CampaignStats {
+    pub fn average_hits(&self) -> Option<f32> {
+        (self.hit_rolls > 0).then(|| self.hits as f32 / self.hit_rolls as f32)
+    }
+}
+
+pub fn compute(game_state: &GameState) -> CampaignStats {
+    let mut stats = CampaignStats {
+        save_name: game_state.save_name.clone(),
+        play: game_state.stats,
+        ..Default::default()
+    };
+    let main_name = game_state
+        .main_character_sheet
+        .as_ref()
+        .map(|sheet| sheet.name.clone());
+    stats.npcs_met = game_state
+        .characters
+        .iter()
+        .filter(|character| Some(&character.name) != main_name.as_ref())
+        .count();
+
+    // Money is what the updates of the player's character did to their nuyen, so a new sheet's
+    // starting nuyen is not counted as earned.
+    let mut replay = GameState::new(&game_state.save_name);
+    for recorded in &game_state.events {
+        let nuyen =
+            |state: &GameState| state.main_character_sheet.as_ref().map(|sheet| sheet.nuyen);
+        let before = nuyen(&replay);
+        if replay.record_at(recorded.clone()).is_err() {
+            continue;
+        }
+        match &recorded.event {
+            GameEvent::Roll { roll } => {
+                stats.rolls += 1;
+                if roll.total.is_none() {
+                    stats.hit_rolls += 1;
+                    stats.hits += roll.hits as u32;
+                    stats.hit_counts[(roll.hits as usize).min(HIT_GROUPS - 1)] += 1;
+                    if roll.glitch || roll.critical_glitch {
+                        stats.glitches += 1;
+                    }
+                }
+            }
+            GameEvent::SheetUpdate { .. } => {
+                if let (Some(before), Some(after)) = (before, nuyen(&replay)) {
+                    if after > before {
+                        stats.nuyen_earned += (after - before) as u64;
+                    } else {
+                        stats.nuyen_spent += (before - after) as u64;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    stats
+}
@@ -1,6 +1,10 @@
 // Import necessary modules from external crates.
+use crate::augmentation::{self, AugmentationStats};
+use crate::ruleset::{Ruleset, Sr5, FULL_ESSENCE};
+use rand::Rng;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 
 // TODO: Add descriptions everywhere
@@ -80,6 +84,10 @@ pub struct CharacterSheet {
     #[serde(default)]
     pub inventory: HashMap<String, Item>,
     pub matrix_attributes: Option<MatrixAttributes>,
+
+    // Entries by name, the whole sheet of a freeform campaign, e.g. "Hit Points": "12/15".
+    #[serde(default)]
+    pub fields: BTreeMap<String, String>,
 }
 
 // Define a structure for categorizing different skills a character may have.
@@ -92,11 +100,82 @@ pub struct Skills {
 }
 
 // Define a structure for items that can be part of a character's inventory.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Item {
     pub name: String,
     pub quantity: u32,
     pub description: String,
+    #[serde(default)]
+    pub state: ItemState,
+    // What the item gives while equipped. Armor left out is read from an "Armor 12" description.
+    #[serde(default)]
+    pub armor: Option<u8>,
+    #[serde(default)]
+    pub reach: Option<u8>,
+    #[serde(default)]
+    pub recoil_compensation: Option<u8>,
+    // Kilograms for one; unknown weights count as nothing.
+    #[serde(default)]
+    pub weight: Option<f32>,
+}
+
+// Where an item is: in use, on the character, or left somewhere safe.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ItemState {
+    Equipped,
+    #[default]
+    Carried,
+    Stashed,
+}
+
+impl ItemState {
+    pub fn next(self) -> Self {
+        match self {
+            ItemState::Equipped => ItemState::Carried,
+            ItemState::Carried => ItemState::Stashed,
+            ItemState::Stashed => ItemState::Equipped,
+        }
+    }
+}
+
+impl fmt::Display for ItemState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = match self {
+            ItemState::Equipped => "equipped",
+            ItemState::Carried => "carried",
+            ItemState::Stashed => "stashed",
+        };
+        write!(f, "{}", state)
+    }
+}
+
+impl Item {
+    pub fn new(name: &str, quantity: u32, description: &str) -> Self {
+        Item {
+            name: name.to_string(),
+            quantity,
+            description: description.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn armor_rating(&self) -> u8 {
+        self.armor.unwrap_or_else(|| {
+            self.description
+                .split("Armor ")
+                .skip(1)
+                .find_map(|rest| {
+                    let digits =
+                        rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+                    rest[..digits].parse().ok()
+                })
+                .unwrap_or(0)
+        })
+    }
+
+    pub fn equipped(&self) -> bool {
+        self.state == ItemState::Equipped
+    }
 }
 
 // Define a structure for contacts within the game, representing relationships and connections.
@@ -146,7 +225,7 @@ impl CharacterSheet {
             magic: Some(builder.magic),
             resonance: Some(builder.resonance),
             initiative: (0, 1),
-            essence: 6.0,
+            essence: FULL_ESSENCE,
             edge_points: 1,
             physical_monitor: 9,
             stun_monitor: 9,
@@ -164,6 +243,7 @@ impl CharacterSheet {
             bioware: Vec::new(),
             matrix_attributes: None,
             inventory: builder.inventory,
+            fields: BTreeMap::new(),
         };
 
         // Apply race-specific attribute modifiers and update derived attributes.
@@ -179,26 +259,26 @@ impl CharacterSheet {
                 self.edge = self.edge.clamp(2, 7);
             }
             Race::Elf => {
-                self.agility = (self.agility + 1).min(7);
-                self.charisma = (self.charisma + 2).min(8);
+                self.agility = self.agility.saturating_add(1).min(7);
+                self.charisma = self.charisma.saturating_add(2).min(8);
             }
             Race::Dwarf => {
-                self.body = (self.body + 2).min(8);
+                self.body = self.body.saturating_add(2).min(8);
                 self.agility = self.agility.min(5);
                 self.reaction = self.reaction.min(5);
-                self.strength = (self.strength + 2).min(8);
-                self.willpower = (self.willpower + 1).min(7);
+                self.strength = self.strength.saturating_add(2).min(8);
+                self.willpower = self.willpower.saturating_add(1).min(7);
             }
             Race::Ork => {
-                self.body = (self.body + 3).min(9);
-                self.strength = (self.strength + 2).min(8);
+                self.body = self.body.saturating_add(3).min(9);
+                self.strength = self.strength.saturating_add(2).min(8);
                 self.logic = self.logic.min(5);
                 self.charisma = self.charisma.min(5);
             }
             Race::Troll => {
-                self.body = (self.body + 4).min(10);
+                self.body = self.body.saturating_add(4).min(10);
                 self.agility = self.agility.min(5);
-                self.strength = (self.strength + 4).min(10);
+                self.strength = self.strength.saturating_add(4).min(10);
                 self.logic = self.logic.min(5);
                 self.intuition = self.intuition.min(5);
                 self.charisma = self.charisma.min(4);
@@ -207,16 +287,120 @@ impl CharacterSheet {
     }
 
     // Update derived attributes based on basic and secondary attributes.
+    // By the 5th edition rules; a campaign on another ruleset derives its sheets again, see ruleset.rs.
     pub fn update_derived_attributes(&mut self) {
-        self.initiative = (self.reaction + self.intuition, 1);
-        self.physical_monitor = 8 + (self.body + 1) / 2;
-        self.stun_monitor = 8 + (self.willpower + 1) / 2;
-        self.physical_limit =
-            ((self.strength * 2 + self.body + self.reaction) as f32 / 3.0).ceil() as u8;
-        self.mental_limit =
-            ((self.logic * 2 + self.intuition + self.willpower) as f32 / 3.0).ceil() as u8;
-        self.social_limit =
-            ((self.charisma * 2 + self.willpower + self.essence as u8) as f32 / 3.0).ceil() as u8;
+        Sr5.derive(self);
+    }
+
+    // The inventory in a stable order, to show and pick from.
+    pub fn sorted_inventory(&self) -> Vec<(&String, &Item)> {
+        let mut items: Vec<_> = self.inventory.iter().collect();
+        items.sort_by(|a, b| a.0.cmp(b.0));
+        items
+    }
+
+    // Active skills as the sheet shows them: by category, then by name.
+    pub fn sorted_active_skills(&self) -> Vec<(&String, u8)> {
+        [
+            &self.skills.combat,
+            &self.skills.physical,
+            &self.skills.social,
+            &self.skills.technical,
+        ]
+        .into_iter()
+        .flat_map(|skills| {
+            let mut sorted: Vec<_> = skills
+                .iter()
+                .map(|(name, rating)| (name, *rating))
+                .collect();
+            sorted.sort();
+            sorted
+        })
+        .collect()
+    }
+
+    // Kilograms the character can carry without slowing down.
+    pub fn carry_capacity(&self) -> f32 {
+        (self.strength as f32 + self.body as f32) * 5.0
+    }
+
+    // Kilograms of gear on the character; stashed gear is left behind.
+    pub fn carried_weight(&self) -> f32 {
+        self.inventory
+            .values()
+            .filter(|item| item.state != ItemState::Stashed)
+            .map(|item| item.weight.unwrap_or(0.0) * item.quantity as f32)
+            .sum()
+    }
+
+    // Agility and reaction lost to an overload: one point for every 10 kg, or part of it, too many.
+    pub fn encumbrance_penalty(&self) -> u8 {
+        let over = self.carried_weight() - self.carry_capacity();
+        if over > 0.0 {
+            (over / 10.0).ceil().min(u8::MAX as f32) as u8
+        } else {
+            0
+        }
+    }
+
+    // What the known cyberware and bioware add up to.
+    pub fn augmentations(&self) -> AugmentationStats {
+        augmentation::total(self.cyberware.iter().chain(&self.bioware))
+    }
+
+    // Armor doesn't stack: the best equipped piece counts.
+    pub fn equipped_armor(&self) -> u8 {
+        self.inventory
+            .values()
+            .filter(|item| item.equipped())
+            .map(Item::armor_rating)
+            .max()
+            .unwrap_or(0)
+    }
+
+    // Dice added to or taken from an attack with `weapon`: its reach, and the recoil of firing
+    // `shots` rounds beyond what strength and the weapon's compensation soak up.
+    pub fn weapon_modifier(&self, weapon: &str, shots: u8) -> Result<i16, String> {
+        let item = self
+            .inventory
+            .get(weapon)
+            .or_else(|| {
+                self.inventory
+                    .values()
+                    .find(|item| item.name.eq_ignore_ascii_case(weapon))
+            })
+            .ok_or_else(|| format!("{} has no {}", self.name, weapon))?;
+        if !item.equipped() {
+            return Err(format!(
+                "{}'s {} is {}, not equipped",
+                self.name, item.name, item.state
+            ));
+        }
+        let compensation = 1 + self.strength.div_ceil(3) + item.recoil_compensation.unwrap_or(0);
+        let recoil = shots.saturating_sub(compensation);
+        Ok(item.reach.unwrap_or(0) as i16 - recoil as i16)
+    }
+
+    // The attribute a test of the active skill `skill` rolls with, and its limit (None for magic,
+    // whose limit is the Force), or None if the character doesn't have the skill.
+    pub fn skill_test(&self, skill: &str) -> Option<(&'static str, Option<&'static str>)> {
+        if let Some((_, attribute, limit)) = SKILL_TESTS.iter().find(|(name, _, _)| *name == skill)
+        {
+            return self
+                .get_all_active_skills()
+                .contains_key(skill)
+                .then_some((*attribute, *limit));
+        }
+        // Skills not listed go by their category.
+        if self.skills.combat.contains_key(skill) || self.skills.physical.contains_key(skill) {
+            Some(("Agility", Some("physical")))
+        } else if self.skills.social.contains_key(skill) {
+            Some(("Charisma", Some("social")))
+        } else if self.skills.technical.contains_key(skill) {
+            Some(("Logic", Some("mental")))
+        } else {
+            None
+        }
     }
 
     // Retrieve all active skills combined from different skill categories.
@@ -233,13 +417,15 @@ impl CharacterSheet {
     pub fn get_dice_pool(&self, attribute: &str, skill: &str) -> u8 {
         let attribute_value = match attribute.to_lowercase().as_str() {
             "body" => self.body,
-            "agility" => self.agility,
-            "reaction" => self.reaction,
+            "agility" => self.agility.saturating_sub(self.encumbrance_penalty()),
+            "reaction" => self.reaction.saturating_sub(self.encumbrance_penalty()),
             "strength" => self.strength,
             "willpower" => self.willpower,
             "logic" => self.logic,
             "intuition" => self.intuition,
             "charisma" => self.charisma,
+            "magic" => self.magic.unwrap_or(0),
+            "resonance" => self.resonance.unwrap_or(0),
             _ => 0,
         };
 
@@ -249,7 +435,13 @@ impl CharacterSheet {
             .cloned()
             .unwrap_or(0);
 
-        attribute_value + skill_value
+        attribute_value.saturating_add(skill_value)
+    }
+
+    // Physical, mental and social limits, or None under a ruleset without them.
+    pub fn limits(&self) -> Option<(u8, u8, u8)> {
+        let limits = (self.physical_limit, self.mental_limit, self.social_limit);
+        (limits != (0, 0, 0)).then_some(limits)
     }
 
     // Get the maximum limit for an action based on the type of limit (physical, mental, social).
@@ -420,12 +612,98 @@ pub enum UpdateOperation<T> {
     Remove(T),
 }
 
+// A field of the character sheet, spelled as the assistant's tools name it.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum AttributeKey {
+    Name,
+    Race,
+    Gender,
+    Backstory,
+    Main,
+    Body,
+    Agility,
+    Reaction,
+    Strength,
+    Willpower,
+    Logic,
+    Intuition,
+    Charisma,
+    Edge,
+    Magic,
+    Resonance,
+    Skills,
+    KnowledgeSkills,
+    Nuyen,
+    Lifestyle,
+    Contacts,
+    Qualities,
+    Cyberware,
+    Bioware,
+    Inventory,
+    MatrixAttributes,
+}
+
+impl AttributeKey {
+    pub fn name(self) -> &'static str {
+        match self {
+            AttributeKey::Name => "name",
+            AttributeKey::Race => "race",
+            AttributeKey::Gender => "gender",
+            AttributeKey::Backstory => "backstory",
+            AttributeKey::Main => "main",
+            AttributeKey::Body => "body",
+            AttributeKey::Agility => "agility",
+            AttributeKey::Reaction => "reaction",
+            AttributeKey::Strength => "strength",
+            AttributeKey::Willpower => "willpower",
+            AttributeKey::Logic => "logic",
+            AttributeKey::Intuition => "intuition",
+            AttributeKey::Charisma => "charisma",
+            AttributeKey::Edge => "edge",
+            AttributeKey::Magic => "magic",
+            AttributeKey::Resonance => "resonance",
+            AttributeKey::Skills => "skills",
+            AttributeKey::KnowledgeSkills => "knowledge_skills",
+            AttributeKey::Nuyen => "nuyen",
+            AttributeKey::Lifestyle => "lifestyle",
+            AttributeKey::Contacts => "contacts",
+            AttributeKey::Qualities => "qualities",
+            AttributeKey::Cyberware => "cyberware",
+            AttributeKey::Bioware => "bioware",
+            AttributeKey::Inventory => "inventory",
+            AttributeKey::MatrixAttributes => "matrix_attributes",
+        }
+    }
+
+    // The attribute the assistant calls `name`, or an error for one the sheet does not have.
+    pub fn parse(name: &str) -> Result<Self, String> {
+        serde_json::from_value(serde_json::Value::String(name.to_string()))
+            .map_err(|_| format!("Unknown attribute: {}", name))
+    }
+}
+
+impl fmt::Display for AttributeKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum CharacterSheetUpdate {
     UpdateAttribute {
-        attribute: String,
+        attribute: AttributeKey,
         operation: UpdateOperation<Value>,
     },
+    // Set free-form entries; None removes one.
+    SetFields {
+        fields: BTreeMap<String, Option<String>>,
+    },
+    // Equip, carry or stash an inventory item.
+    SetItemState {
+        item: String,
+        state: ItemState,
+    },
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -445,6 +723,25 @@ pub enum Value {
     OptionU8(Option<u8>),
 }
 
+impl Value {
+    // The value as the type `attribute` holds on the sheet.
+    fn into_field<T: DeserializeOwned>(
+        self,
+        attribute: AttributeKey,
+        operation: &str,
+    ) -> Result<T, String> {
+        serde_json::to_value(&self)
+            .ok()
+            .and_then(|value| serde_json::from_value(value).ok())
+            .ok_or_else(|| {
+                format!(
+                    "Invalid attribute-value pair for {}: {} {:#?}",
+                    operation, attribute, self
+                )
+            })
+    }
+}
+
 impl CharacterSheet {
     pub fn apply_update(&mut self, update: CharacterSheetUpdate) -> Result<(), String> {
         match update {
@@ -453,109 +750,190 @@ impl CharacterSheet {
                 operation,
             } => {
                 match operation {
-                    UpdateOperation::Modify(value) => self.modify_attribute(&attribute, value)?,
-                    UpdateOperation::Add(value) => self.add_to_attribute(&attribute, value)?,
+                    UpdateOperation::Modify(value) => self.modify_attribute(attribute, value)?,
+                    UpdateOperation::Add(value) => self.add_to_attribute(attribute, value)?,
                     UpdateOperation::Remove(value) => {
-                        self.remove_from_attribute(&attribute, value)?
+                        self.remove_from_attribute(attribute, value)?
                     }
                 }
                 self.update_derived_attributes();
                 Ok(())
             }
+            CharacterSheetUpdate::SetFields { fields } => {
+                for (key, value) in fields {
+                    match value {
+                        Some(value) => self.fields.insert(key, value),
+                        None => self.fields.remove(&key),
+                    };
+                }
+                Ok(())
+            }
+            CharacterSheetUpdate::SetItemState { item, state } => {
+                self.inventory
+                    .get_mut(&item)
+                    .ok_or_else(|| format!("{} has no {}", self.name, item))?
+                    .state = state;
+                self.update_derived_attributes();
+                Ok(())
+            }
         }
     }
 
-    fn modify_attribute(&mut self, attribute: &str, value: Value) -> Result<(), String> {
-        match (attribute, value.clone()) {
-            ("name", Value::String(v)) => self.name = v,
-            ("race", Value::Race(v)) => {
-                self.race = v;
+    fn modify_attribute(&mut self, attribute: AttributeKey, value: Value) -> Result<(), String> {
+        let operation = "modification";
+        match attribute {
+            AttributeKey::Name => self.name = value.into_field(attribute, operation)?,
+            AttributeKey::Race => {
+                self.race = value.into_field(attribute, operation)?;
                 self.apply_race_modifiers(self.race.clone());
             }
-            ("gender", Value::String(v)) => self.gender = v,
-            ("backstory", Value::String(v)) => self.backstory = v,
-            ("main", Value::U8(v)) => self.main = v != 0,
-            ("body", Value::U8(v)) => self.body = v,
-            ("agility", Value::U8(v)) => self.agility = v,
-            ("reaction", Value::U8(v)) => self.reaction = v,
-            ("strength", Value::U8(v)) => self.strength = v,
-            ("willpower", Value::U8(v)) => self.willpower = v,
-            ("logic", Value::U8(v)) => self.logic = v,
-            ("intuition", Value::U8(v)) => self.intuition = v,
-            ("charisma", Value::U8(v)) => self.charisma = v,
-            ("edge", Value::U8(v)) => self.edge = v,
-            ("magic", Value::OptionU8(v)) => self.magic = v,
-            ("resonance", Value::OptionU8(v)) => self.resonance = v,
-            ("skills", Value::Skills(v)) => self.skills = v,
-            ("knowledge_skills", Value::HashMapStringU8(v)) => self.knowledge_skills = v,
-            ("nuyen", Value::U32(v)) => self.nuyen = v,
-            ("lifestyle", Value::String(v)) => self.lifestyle = v,
-            ("contacts", Value::HashMapStringContact(v)) => self.contacts = v,
-            ("qualities", Value::VecQuality(v)) => self.qualities = v,
-            ("cyberware", Value::VecString(v)) => self.cyberware = v,
-            ("bioware", Value::VecString(v)) => self.bioware = v,
-            ("inventory", Value::HashMapStringItem(v)) => {
-                for (key, new_item) in v {
-                    if let Some(existing_item) = self.inventory.get_mut(&key) {
-                        // Update existing item
+            AttributeKey::Gender => self.gender = value.into_field(attribute, operation)?,
+            AttributeKey::Backstory => self.backstory = value.into_field(attribute, operation)?,
+            AttributeKey::Main => {
+                self.main = value.into_field::<u8>(attribute, operation)? != 0;
+            }
+            AttributeKey::Body => self.body = value.into_field(attribute, operation)?,
+            AttributeKey::Agility => self.agility = value.into_field(attribute, operation)?,
+            AttributeKey::Reaction => self.reaction = value.into_field(attribute, operation)?,
+            AttributeKey::Strength => self.strength = value.into_field(attribute, operation)?,
+            AttributeKey::Willpower => self.willpower = value.into_field(attribute, operation)?,
+            AttributeKey::Logic => self.logic = value.into_field(attribute, operation)?,
+            AttributeKey::Intuition => self.intuition = value.into_field(attribute, operation)?,
+            AttributeKey::Charisma => self.charisma = value.into_field(attribute, operation)?,
+            AttributeKey::Edge => self.edge = value.into_field(attribute, operation)?,
+            AttributeKey::Magic => self.magic = value.into_field(attribute, operation)?,
+            AttributeKey::Resonance => self.resonance = value.into_field(attribute, operation)?,
+            AttributeKey::Skills => self.skills = value.into_field(attribute, operation)?,
+            AttributeKey::KnowledgeSkills => {
+                self.knowledge_skills = value.into_field(attribute, operation)?;
+            }
+            AttributeKey::Nuyen => self.nuyen = value.into_field(attribute, operation)?,
+            AttributeKey::Lifestyle => self.lifestyle = value.into_field(attribute, operation)?,
+            AttributeKey::Contacts => self.contacts = value.into_field(attribute, operation)?,
+            AttributeKey::Qualities => self.qualities = value.into_field(attribute, operation)?,
+            AttributeKey::Cyberware => self.cyberware = value.into_field(attribute, operation)?,
+            AttributeKey::Bioware => self.bioware = value.into_field(attribute, operation)?,
+            AttributeKey::Inventory => {
+                let items: HashMap<String, Item> = value.into_field(attribute, operation)?;
+                for (key, new_item) in items {
+                    if new_item.quantity == 0 {
+                        // None left
+                        self.inventory.remove(&key);
+                    } else if let Some(existing_item) = self.inventory.get_mut(&key) {
+                        // Update existing item, keeping where it is
                         existing_item.quantity = new_item.quantity;
                         existing_item.description = new_item.description;
+                        existing_item.armor = new_item.armor.or(existing_item.armor);
+                        existing_item.reach = new_item.reach.or(existing_item.reach);
+                        existing_item.recoil_compensation = new_item
+                            .recoil_compensation
+                            .or(existing_item.recoil_compensation);
+                        existing_item.weight = new_item.weight.or(existing_item.weight);
                     } else {
                         // Add new item
                         self.inventory.insert(key, new_item);
                     }
                 }
             }
-            ("matrix_attributes", Value::OptionMatrixAttributes(v)) => self.matrix_attributes = v,
-            _ => {
-                return Err(format!(
-                    "Invalid attribute-value pair for modification: {} {:#?}",
-                    attribute, value
-                ))
+            AttributeKey::MatrixAttributes => {
+                self.matrix_attributes = value.into_field(attribute, operation)?;
             }
         }
         Ok(())
     }
 
-    fn add_to_attribute(&mut self, attribute: &str, value: Value) -> Result<(), String> {
-        match (attribute, value.clone()) {
-            ("nuyen", Value::U32(v)) => self.nuyen = self.nuyen.saturating_add(v),
-            ("contacts", Value::HashMapStringContact(v)) => self.contacts.extend(v),
-            ("qualities", Value::VecQuality(v)) => self.qualities.extend(v),
-            ("cyberware", Value::VecString(v)) => self.cyberware.extend(v),
-            ("bioware", Value::VecString(v)) => self.bioware.extend(v),
-            ("inventory", Value::HashMapStringItem(v)) => {
-                for (key, item) in v {
+    fn add_to_attribute(&mut self, attribute: AttributeKey, value: Value) -> Result<(), String> {
+        let operation = "addition";
+        match attribute {
+            AttributeKey::Nuyen => {
+                let nuyen: u32 = value.into_field(attribute, operation)?;
+                self.nuyen = self.nuyen.saturating_add(nuyen);
+            }
+            AttributeKey::Contacts => {
+                let contacts: HashMap<String, Contact> = value.into_field(attribute, operation)?;
+                self.contacts.extend(contacts);
+            }
+            AttributeKey::Qualities => {
+                let qualities: Vec<Quality> = value.into_field(attribute, operation)?;
+                self.qualities.extend(qualities);
+            }
+            AttributeKey::Cyberware => {
+                let cyberware: Vec<String> = value.into_field(attribute, operation)?;
+                self.cyberware.extend(cyberware);
+            }
+            AttributeKey::Bioware => {
+                let bioware: Vec<String> = value.into_field(attribute, operation)?;
+                self.bioware.extend(bioware);
+            }
+            AttributeKey::Inventory => {
+                let items: HashMap<String, Item> = value.into_field(attribute, operation)?;
+                for (key, item) in items.into_iter().filter(|(_, item)| item.quantity > 0) {
                     if let Some(existing_item) = self.inventory.get_mut(&key) {
-                        existing_item.quantity += item.quantity;
+                        existing_item.quantity =
+                            existing_item.quantity.saturating_add(item.quantity);
                     } else {
                         self.inventory.insert(key, item);
                     }
                 }
             }
-            _ => {
-                return Err(format!(
-                    "Invalid attribute-value pair for addition: {} {:#?}",
-                    attribute, value
-                ))
+            AttributeKey::Name
+            | AttributeKey::Race
+            | AttributeKey::Gender
+            | AttributeKey::Backstory
+            | AttributeKey::Main
+            | AttributeKey::Body
+            | AttributeKey::Agility
+            | AttributeKey::Reaction
+            | AttributeKey::Strength
+            | AttributeKey::Willpower
+            | AttributeKey::Logic
+            | AttributeKey::Intuition
+            | AttributeKey::Charisma
+            | AttributeKey::Edge
+            | AttributeKey::Magic
+            | AttributeKey::Resonance
+            | AttributeKey::Skills
+            | AttributeKey::KnowledgeSkills
+            | AttributeKey::Lifestyle
+            | AttributeKey::MatrixAttributes => {
+                return Err(format!("{} can only be modified, not added to", attribute))
             }
         }
         Ok(())
     }
 
-    fn remove_from_attribute(&mut self, attribute: &str, value: Value) -> Result<(), String> {
-        match (attribute, value.clone()) {
-            ("nuyen", Value::U32(v)) => self.nuyen = self.nuyen.saturating_sub(v),
-            ("contacts", Value::HashMapStringContact(v)) => {
-                for key in v.keys() {
+    fn remove_from_attribute(
+        &mut self,
+        attribute: AttributeKey,
+        value: Value,
+    ) -> Result<(), String> {
+        let operation = "removal";
+        match attribute {
+            AttributeKey::Nuyen => {
+                let nuyen: u32 = value.into_field(attribute, operation)?;
+                self.nuyen = self.nuyen.saturating_sub(nuyen);
+            }
+            AttributeKey::Contacts => {
+                let contacts: HashMap<String, Contact> = value.into_field(attribute, operation)?;
+                for key in contacts.keys() {
                     self.contacts.remove(key);
                 }
             }
-            ("qualities", Value::VecQuality(v)) => self.qualities.retain(|q| !v.contains(q)),
-            ("cyberware", Value::VecString(v)) => self.cyberware.retain(|item| !v.contains(item)),
-            ("bioware", Value::VecString(v)) => self.bioware.retain(|item| !v.contains(item)),
-            ("inventory", Value::HashMapStringItem(v)) => {
-                for (key, item) in v {
+            AttributeKey::Qualities => {
+                let qualities: Vec<Quality> = value.into_field(attribute, operation)?;
+                self.qualities.retain(|q| !qualities.contains(q));
+            }
+            AttributeKey::Cyberware => {
+                let cyberware: Vec<String> = value.into_field(attribute, operation)?;
+                self.cyberware.retain(|item| !cyberware.contains(item));
+            }
+            AttributeKey::Bioware => {
+                let bioware: Vec<String> = value.into_field(attribute, operation)?;
+                self.bioware.retain(|item| !bioware.contains(item));
+            }
+            AttributeKey::Inventory => {
+                let items: HashMap<String, Item> = value.into_field(attribute, operation)?;
+                for (key, item) in items {
                     if let Some(existing_item) = self.inventory.get_mut(&key) {
                         if existing_item.quantity <= item.quantity {
                             self.inventory.remove(&key);
@@ -565,13 +943,409 @@ impl CharacterSheet {
                     }
                 }
             }
-            _ => {
+            AttributeKey::Name
+            | AttributeKey::Race
+            | AttributeKey::Gender
+            | AttributeKey::Backstory
+            | AttributeKey::Main
+            | AttributeKey::Body
+            | AttributeKey::Agility
+            | AttributeKey::Reaction
+            | AttributeKey::Strength
+            | AttributeKey::Willpower
+            | AttributeKey::Logic
+            | AttributeKey::Intuition
+            | AttributeKey::Charisma
+            | AttributeKey::Edge
+            | AttributeKey::Magic
+            | AttributeKey::Resonance
+            | AttributeKey::Skills
+            | AttributeKey::KnowledgeSkills
+            | AttributeKey::Lifestyle
+            | AttributeKey::MatrixAttributes => {
                 return Err(format!(
-                    "Invalid attribute-value pair for removal: {} {:#?}",
-                    attribute, value
+                    "{} can only be modified, not removed from",
+                    attribute
                 ))
             }
         }
         Ok(())
     }
 }
+
+// Active skills whose linked attribute or limit is not the one of their category.
+const SKILL_TESTS: &[(&str, &str, Option<&str>)] = &[
+    ("Perception", "Intuition", Some("mental")),
+    ("Running", "Strength", Some("physical")),
+    ("Swimming", "Strength", Some("physical")),
+    ("Pilot Ground Craft", "Reaction", Some("physical")),
+    ("Spellcasting", "Magic", None),
+    ("Summoning", "Magic", None),
+    ("Counterspelling", "Magic", None),
+];
+
+// Archetypal roles the NPC generator knows how to stat out.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NpcRole {
+    Ganger,
+    CorpSecurity,
+    StreetMage,
+    Decker,
+    Fixer,
+    Civilian,
+}
+
+pub const NPC_ROLES: [NpcRole; 6] = [
+    NpcRole::Ganger,
+    NpcRole::CorpSecurity,
+    NpcRole::StreetMage,
+    NpcRole::Decker,
+    NpcRole::Fixer,
+    NpcRole::Civilian,
+];
+
+// Attribute order used by the role profiles below.
+const NPC_ATTRIBUTES: usize = 9;
+
+// A role's typical attributes, skills and gear. Ratings are (min, max) and rolled per NPC.
+struct NpcProfile {
+    attributes: [(u8, u8); NPC_ATTRIBUTES],
+    magic: (u8, u8),
+    combat: &'static [(&'static str, u8, u8)],
+    physical: &'static [(&'static str, u8, u8)],
+    social: &'static [(&'static str, u8, u8)],
+    technical: &'static [(&'static str, u8, u8)],
+    gear: &'static [(&'static str, &'static str)],
+    nuyen: (u32, u32),
+    lifestyle: &'static str,
+}
+
+impl NpcRole {
+    pub fn name(&self) -> &'static str {
+        match self {
+            NpcRole::Ganger => "Ganger",
+            NpcRole::CorpSecurity => "Corp Security",
+            NpcRole::StreetMage => "Street Mage",
+            NpcRole::Decker => "Decker",
+            NpcRole::Fixer => "Fixer",
+            NpcRole::Civilian => "Civilian",
+        }
+    }
+
+    // Parse a role from user or AI input, ignoring case, spaces and underscores.
+    pub fn parse(text: &str) -> Option<NpcRole> {
+        let wanted: String = text
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .collect::<String>()
+            .to_lowercase();
+        NPC_ROLES.iter().copied().find(|role| {
+            role.name()
+                .chars()
+                .filter(|c| c.is_alphanumeric())
+                .collect::<String>()
+                .to_lowercase()
+                == wanted
+        })
+    }
+
+    fn profile(&self) -> NpcProfile {
+        // Body, Agility, Reaction, Strength, Willpower, Logic, Intuition, Charisma, Edge.
+        match self {
+            NpcRole::Ganger => NpcProfile {
+                attributes: [
+                    (3, 5),
+                    (3, 4),
+                    (3, 4),
+                    (3, 5),
+                    (2, 3),
+                    (1, 3),
+                    (2, 4),
+                    (1, 3),
+                    (1, 2),
+                ],
+                magic: (0, 0),
+                combat: &[
+                    ("Blades", 2, 4),
+                    ("Pistols", 2, 4),
+                    ("Unarmed Combat", 2, 4),
+                ],
+                physical: &[("Running", 1, 3), ("Sneaking", 1, 3)],
+                social: &[("Intimidation", 2, 4)],
+                technical: &[],
+                gear: &[
+                    ("Knife", "Cheap but sharp."),
+                    ("Streetline Special", "Hold-out pistol."),
+                    ("Gang Colors", "Armor 4 synthleather jacket."),
+                ],
+                nuyen: (20, 500),
+                lifestyle: "Squatter",
+            },
+            NpcRole::CorpSecurity => NpcProfile {
+                attributes: [
+                    (3, 5),
+                    (3, 5),
+                    (3, 5),
+                    (3, 4),
+                    (3, 4),
+                    (2, 3),
+                    (3, 4),
+                    (2, 3),
+                    (1, 2),
+                ],
+                magic: (0, 0),
+                combat: &[
+                    ("Automatics", 3, 5),
+                    ("Pistols", 3, 5),
+                    ("Unarmed Combat", 2, 4),
+                ],
+                physical: &[("Perception", 3, 5), ("Running", 2, 3)],
+                social: &[("Intimidation", 2, 3), ("Etiquette", 1, 3)],
+                technical: &[("First Aid", 1, 3)],
+                gear: &[
+                    ("Ares Predator V", "Heavy pistol."),
+                    ("Stun Baton", "Standard issue shock prod."),
+                    ("Armor Jacket", "Armor 12, company logo on the back."),
+                    (
+                        "Commlink",
+                        "Corporate-issued, tracked by the security grid.",
+                    ),
+                ],
+                nuyen: (500, 2_000),
+                lifestyle: "Middle",
+            },
+            NpcRole::StreetMage => NpcProfile {
+                attributes: [
+                    (2, 3),
+                    (2, 3),
+                    (2, 4),
+                    (1, 3),
+                    (4, 6),
+                    (3, 5),
+                    (3, 5),
+                    (2, 4),
+                    (1, 3),
+                ],
+                magic: (3, 6),
+                combat: &[("Pistols", 1, 3)],
+                physical: &[("Perception", 2, 4)],
+                social: &[("Con", 1, 3)],
+                technical: &[
+                    ("Spellcasting", 3, 6),
+                    ("Counterspelling", 2, 4),
+                    ("Summoning", 1, 4),
+                ],
+                gear: &[
+                    ("Reagents", "A pouch of drams for spirits and rituals."),
+                    ("Lined Coat", "Armor 9."),
+                    (
+                        "Fetish",
+                        "Scratched-up talisman tied to one of their spells.",
+                    ),
+                ],
+                nuyen: (100, 1_500),
+                lifestyle: "Low",
+            },
+            NpcRole::Decker => NpcProfile {
+                attributes: [
+                    (1, 3),
+                    (2, 3),
+                    (2, 4),
+                    (1, 3),
+                    (3, 5),
+                    (4, 6),
+                    (3, 5),
+                    (1, 3),
+                    (2, 3),
+                ],
+                magic: (0, 0),
+                combat: &[("Pistols", 1, 2)],
+                physical: &[("Sneaking", 1, 3)],
+                social: &[],
+                technical: &[
+                    ("Hacking", 3, 6),
+                    ("Cybercombat", 2, 5),
+                    ("Computer", 3, 5),
+                    ("Electronic Warfare", 2, 4),
+                ],
+                gear: &[
+                    (
+                        "Cyberdeck",
+                        "Erika MCD-1, patched together with tape and optimism.",
+                    ),
+                    ("Commlink", "Meta Link with a cracked screen."),
+                ],
+                nuyen: (200, 3_000),
+                lifestyle: "Low",
+            },
+            NpcRole::Fixer => NpcProfile {
+                attributes: [
+                    (2, 3),
+                    (2, 3),
+                    (2, 3),
+                    (2, 3),
+                    (3, 5),
+                    (3, 5),
+                    (4, 5),
+                    (4, 6),
+                    (2, 4),
+                ],
+                magic: (0, 0),
+                combat: &[("Pistols", 2, 3)],
+                physical: &[("Perception", 3, 5)],
+                social: &[
+                    ("Negotiation", 4, 6),
+                    ("Con", 3, 5),
+                    ("Etiquette", 3, 5),
+                    ("Leadership", 1, 3),
+                ],
+                technical: &[("Computer", 1, 3)],
+                gear: &[
+                    ("Colt America L36", "Light pistol, rarely drawn."),
+                    ("Commlink", "Hermes Ikon, three burner personas loaded."),
+                    ("Fake SIN", "Rating 4."),
+                ],
+                nuyen: (2_000, 15_000),
+                lifestyle: "High",
+            },
+            NpcRole::Civilian => NpcProfile {
+                attributes: [
+                    (2, 3),
+                    (2, 3),
+                    (2, 3),
+                    (2, 3),
+                    (2, 3),
+                    (2, 4),
+                    (2, 4),
+                    (2, 4),
+                    (1, 2),
+                ],
+                magic: (0, 0),
+                combat: &[],
+                physical: &[("Perception", 1, 3)],
+                social: &[("Etiquette", 1, 3), ("Negotiation", 0, 2)],
+                technical: &[("Computer", 0, 2)],
+                gear: &[("Commlink", "Cheap Renraku Sensei.")],
+                nuyen: (50, 1_000),
+                lifestyle: "Low",
+            },
+        }
+    }
+}
+
+const NPC_FIRST_NAMES: &[&str] = &[
+    "Jax", "Mara", "Kenji", "Lena", "Dmitri", "Aiko", "Tomas", "Rosa", "Silas", "Nadia", "Hector",
+    "Yuki", "Bram", "Ines", "Marcus", "Zoe",
+];
+const NPC_STREET_NAMES: &[&str] = &[
+    "Razor", "Ghost", "Tinman", "Viper", "Static", "Hex", "Mongoose", "Patch", "Slick", "Ash",
+    "Cinder", "Wire",
+];
+const NPC_LAST_NAMES: &[&str] = &[
+    "Okafor", "Tanaka", "Kowalski", "Reyes", "Volkov", "Santos", "Nakamura", "Brennan", "Fischer",
+    "Moreau",
+];
+
+// How common each metatype is on the street, out of 100.
+const RACE_WEIGHTS: [(Race, u32); 5] = [
+    (Race::Human, 60),
+    (Race::Ork, 20),
+    (Race::Elf, 10),
+    (Race::Dwarf, 6),
+    (Race::Troll, 4),
+];
+
+fn random_race(rng: &mut impl Rng) -> Race {
+    let total: u32 = RACE_WEIGHTS.iter().map(|(_, weight)| weight).sum();
+    let mut roll = rng.gen_range(0..total);
+    for (race, weight) in RACE_WEIGHTS.iter() {
+        if roll < *weight {
+            return race.clone();
+        }
+        roll -= weight;
+    }
+    Race::Human
+}
+
+fn pick<'a>(rng: &mut impl Rng, options: &[&'a str]) -> &'a str {
+    options[rng.gen_range(0..options.len())]
+}
+
+fn roll_skills(rng: &mut impl Rng, skills: &[(&str, u8, u8)]) -> HashMap<String, u8> {
+    skills
+        .iter()
+        .map(|(name, min, max)| (name.to_string(), rng.gen_range(*min..=*max)))
+        .filter(|(_, rating)| *rating > 0)
+        .collect()
+}
+
+// Roll up a non-player character for a role without asking the AI.
+pub fn generate_npc(role: NpcRole, rng: &mut impl Rng) -> CharacterSheet {
+    let profile = role.profile();
+    let race = random_race(rng);
+    let gender = pick(rng, &["Female", "Male"]);
+
+    // Street-level roles go by their handle, the rest by their legal name.
+    let name = match role {
+        NpcRole::Ganger | NpcRole::StreetMage | NpcRole::Decker => format!(
+            "{} \"{}\"",
+            pick(rng, NPC_FIRST_NAMES),
+            pick(rng, NPC_STREET_NAMES)
+        ),
+        _ => format!(
+            "{} {}",
+            pick(rng, NPC_FIRST_NAMES),
+            pick(rng, NPC_LAST_NAMES)
+        ),
+    };
+
+    let mut attributes = [1u8; NPC_ATTRIBUTES];
+    for (value, (min, max)) in attributes.iter_mut().zip(profile.attributes.iter()) {
+        *value = rng.gen_range(*min..=*max);
+    }
+
+    let inventory = profile
+        .gear
+        .iter()
+        .map(|(name, description)| (name.to_string(), Item::new(name, 1, description)))
+        .collect();
+    let qualities = if profile.magic.1 > 0 {
+        vec![Quality {
+            name: "Magician".to_string(),
+            positive: true,
+        }]
+    } else {
+        Vec::new()
+    };
+
+    let mut sheet = CharacterSheetBuilder::new(
+        name,
+        race.clone(),
+        gender.to_string(),
+        format!("A {} {} met on the streets of Seattle.", race, role.name()),
+        false,
+    )
+    .body(attributes[0])
+    .agility(attributes[1])
+    .reaction(attributes[2])
+    .strength(attributes[3])
+    .willpower(attributes[4])
+    .logic(attributes[5])
+    .intuition(attributes[6])
+    .charisma(attributes[7])
+    .edge(attributes[8])
+    .magic(rng.gen_range(profile.magic.0..=profile.magic.1))
+    .skills(Skills {
+        combat: roll_skills(rng, profile.combat),
+        physical: roll_skills(rng, profile.physical),
+        social: roll_skills(rng, profile.social),
+        technical: roll_skills(rng, profile.technical),
+    })
+    .qualities(qualities)
+    .nuyen(rng.gen_range(profile.nuyen.0..=profile.nuyen.1))
+    .inventory(inventory)
+    .build();
+    sheet.lifestyle = profile.lifestyle.to_string();
+    sheet
+}
@@ -1,11 +1,71 @@
 use crossterm::{
-    event::DisableMouseCapture,
+    event::{
+        DisableBracketedPaste, DisableMouseCapture, EnableMouseCapture, KeyboardEnhancementFlags,
+        PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
     execute,
-    terminal::{disable_raw_mode, LeaveAlternateScreen},
+    terminal::{disable_raw_mode, supports_keyboard_enhancement, LeaveAlternateScreen},
 };
-use std::io::stdout;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static TUI_ON_STDERR: AtomicBool = AtomicBool::new(false);
+static KEYBOARD_ENHANCED: AtomicBool = AtomicBool::new(false);
+
+// Draw the interface on stderr, leaving stdout to the transcript mirror.
+pub fn draw_on_stderr() {
+    TUI_ON_STDERR.store(true, Ordering::Relaxed);
+}
+
+// The stream the terminal interface is drawn on.
+pub fn tui_output() -> Box<dyn Write + Send> {
+    if TUI_ON_STDERR.load(Ordering::Relaxed) {
+        Box::new(io::stderr())
+    } else {
+        Box::new(io::stdout())
+    }
+}
+
+// Ask the terminal to report keys with all their modifiers (the kitty keyboard protocol), so
+// Ctrl+Enter and Shift+Enter don't arrive as a plain Enter. Does nothing on terminals without it.
+pub fn enhance_keyboard() {
+    if supports_keyboard_enhancement().unwrap_or(false)
+        && execute!(
+            tui_output(),
+            PushKeyboardEnhancementFlags(
+                KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+                    | KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS
+            )
+        )
+        .is_ok()
+    {
+        KEYBOARD_ENHANCED.store(true, Ordering::Relaxed);
+    }
+}
+
+// Whether Ctrl+Enter and Shift+Enter can be told apart from Enter.
+pub fn keyboard_enhanced() -> bool {
+    KEYBOARD_ENHANCED.load(Ordering::Relaxed)
+}
+
+// Take the mouse for selecting the transcript, or leave it to the terminal's own selection.
+pub fn capture_mouse(on: bool) {
+    let _ = if on {
+        execute!(tui_output(), EnableMouseCapture)
+    } else {
+        execute!(tui_output(), DisableMouseCapture)
+    };
+}
 
 pub fn cleanup() {
+    if KEYBOARD_ENHANCED.swap(false, Ordering::Relaxed) {
+        let _ = execute!(tui_output(), PopKeyboardEnhancementFlags);
+    }
     let _ = disable_raw_mode();
-    let _ = execute!(stdout(), LeaveAlternateScreen, DisableMouseCapture);
+    let _ = execute!(
+        tui_output(),
+        DisableBracketedPaste,
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    );
 }
@@ -0,0 +1,112 @@
+// cli.rs
+// Command line flags for launching straight into a game or a scripted run.
+
+use log::LevelFilter;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+pub const USAGE: &str = "\
+Usage: sharad_ratatui [OPTIONS]
+
+Options:
+  --load <save>        Load a saved game on startup
+  --new <name>         Start a new game with this save name on startup
+  --no-update          Do not check for updates
+  --config <dir>       Directory holding settings.json (default: ~/.config/sharad)
+  --portable           Keep settings and data in ./sharad_data beside the executable
+  --log-level <level>  off, error, warn, info, debug or trace (default: the Log Level setting)
+  --mirror <file|->    Copy the transcript as plain text to a file, or to stdout with -
+  --headless <script>  Run the inputs in <script> without a terminal (needs --load, --new or --mock)
+  --mock               With --headless, replay the script's @tool and @reply lines instead of calling OpenAI
+  --bench-ui[=runs]    Time transcript and character sheet drawing off screen and print the results
+  -h, --help           Print this help
+  -V, --version        Print the version";
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CliArgs {
+    pub load: Option<String>,
+    pub new: Option<String>,
+    pub no_update: bool,
+    pub config_dir: Option<PathBuf>,
+    pub portable: bool,
+    pub log_level: Option<LevelFilter>,
+    pub mirror: Option<String>,
+    pub headless: Option<PathBuf>,
+    pub mock: bool,
+    pub bench_ui: Option<u32>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CliAction {
+    Run(CliArgs),
+    Help,
+    Version,
+}
+
+// Parse the arguments that follow the program name.
+pub fn parse<I>(args: I) -> Result<CliAction, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut cli = CliArgs::default();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        // Accept both "--flag value" and "--flag=value".
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => {
+                (flag.to_string(), Some(value.to_string()))
+            }
+            _ => (arg.clone(), None),
+        };
+        let mut value = |name: &str| -> Result<String, String> {
+            match inline_value.clone().or_else(|| args.next()) {
+                Some(value) if !value.is_empty() => Ok(value),
+                _ => Err(format!("{} needs a value", name)),
+            }
+        };
+
+        match flag.as_str() {
+            "-h" | "--help" => return Ok(CliAction::Help),
+            "-V" | "--version" => return Ok(CliAction::Version),
+            "--load" => cli.load = Some(value("--load")?),
+            "--new" => cli.new = Some(value("--new")?),
+            "--no-update" => cli.no_update = true,
+            "--config" => cli.config_dir = Some(PathBuf::from(value("--config")?)),
+            "--portable" => cli.portable = true,
+            "--log-level" => {
+                let level = value("--log-level")?;
+                cli.log_level = Some(
+                    LevelFilter::from_str(&level)
+                        .map_err(|_| format!("invalid log level '{}'", level))?,
+                );
+            }
+            "--mirror" => cli.mirror = Some(value("--mirror")?),
+            "--headless" => cli.headless = Some(PathBuf::from(value("--headless")?)),
+            "--mock" => cli.mock = true,
+            "--bench-ui" => {
+                // The number of runs is optional, so only an inline value is taken.
+                let runs = match &inline_value {
+                    Some(runs) => runs
+                        .parse()
+                        .map_err(|_| format!("invalid number of runs '{}'", runs))?,
+                    None => crate::bench::DEFAULT_ITERATIONS,
+                };
+                cli.bench_ui = Some(runs);
+            }
+            _ => return Err(format!("unknown argument '{}'", arg)),
+        }
+    }
+
+    if cli.load.is_some() && cli.new.is_some() {
+        return Err("--load and --new cannot be used together".to_string());
+    }
+    if cli.mock && cli.headless.is_none() {
+        return Err("--mock only works with --headless".to_string());
+    }
+    if cli.headless.is_some() && !cli.mock && cli.load.is_none() && cli.new.is_none() {
+        return Err("--headless needs a game to play: add --load, --new or --mock".to_string());
+    }
+
+    Ok(CliAction::Run(cli))
+}
@@ -0,0 +1,101 @@
+// clipboard.rs
+// Copy and paste. The system clipboard is used when there is one; without it (no display, or
+// Wayland without a portal) copies are kept in the game so Ctrl+V still pastes them. Copies can
+// also be sent to the terminal with OSC 52, which puts them in the clipboard of terminals that
+// allow it: the one the game is seen in, even over SSH, where the system clipboard is the remote
+// machine's.
+
+use crate::cleanup::tui_output;
+use crate::settings::TerminalCopy;
+use base64::Engine;
+use copypasta::{ClipboardContext, ClipboardProvider};
+use std::io::Write;
+
+pub const NO_SYSTEM_CLIPBOARD: &str = "No system clipboard is available: copies stay in the game, and Ctrl+V pastes them. Paste from other programs with your terminal's own paste.";
+
+pub struct Clipboard {
+    system: Option<ClipboardContext>,
+    // The last text copied, for when there is no system clipboard.
+    internal: Option<String>,
+    // Whether copies can be sent to the terminal; only while the interface is drawn.
+    osc52: bool,
+    pub terminal_copy: TerminalCopy,
+    warned: bool,
+}
+
+impl Clipboard {
+    pub fn new(terminal_copy: TerminalCopy) -> Self {
+        Clipboard {
+            system: ClipboardContext::new().ok(),
+            internal: None,
+            osc52: false,
+            terminal_copy,
+            warned: false,
+        }
+    }
+
+    // Without the system clipboard, as in headless runs.
+    pub fn internal() -> Self {
+        Clipboard {
+            system: None,
+            internal: None,
+            osc52: false,
+            terminal_copy: TerminalCopy::Auto,
+            warned: false,
+        }
+    }
+
+    pub fn send_to_terminal(&mut self) {
+        self.osc52 = true;
+    }
+
+    pub fn get(&mut self) -> Option<String> {
+        if let Some(contents) = self
+            .system
+            .as_mut()
+            .and_then(|system| system.get_contents().ok())
+        {
+            return Some(contents);
+        }
+        self.internal.clone()
+    }
+
+    pub fn set(&mut self, text: &str) {
+        let copied = self
+            .system
+            .as_mut()
+            .is_some_and(|system| system.set_contents(text.to_string()).is_ok());
+        if !copied {
+            self.internal = Some(text.to_string());
+        }
+        let to_terminal = match self.terminal_copy {
+            TerminalCopy::Auto => !copied,
+            TerminalCopy::Always => true,
+            TerminalCopy::Off => false,
+        };
+        if self.osc52 && to_terminal {
+            let mut output = tui_output();
+            let _ = output
+                .write_all(osc52(text).as_bytes())
+                .and_then(|_| output.flush());
+        }
+    }
+
+    // The warning about the missing system clipboard, the first time it matters.
+    pub fn take_warning(&mut self) -> Option<&'static str> {
+        if self.system.is_some() || self.warned {
+            return None;
+        }
+        self.warned = true;
+        Some(NO_SYSTEM_CLIPBOARD)
+    }
+}
+
+// The sequence that asks the terminal to put `text` in its clipboard. tmux passes it on with
+// `set -g set-clipboard on`.
+pub fn osc52(text: &str) -> String {
+    format!(
+        "\x1b]52;c;{}\x07",
+        base64::engine::general_purpose::STANDARD.encode(text)
+    )
+}
@@ -0,0 +1,172 @@
+// combat.rs
+// The combat tracker: who is in the fight, their initiative, and whose action it is. Each round
+// everyone rolls initiative and acts in passes, highest score first; every pass costs 10 from the
+// score, and those left above 0 act again. Wounds take 1 per 3 boxes of damage off initiative and
+// off every dice pool, and the environment modifier (smoke, darkness, rain) off every dice pool, so
+// rolls in the fight don't depend on the narrator remembering them.
+//
+// The tracker is kept in the game state through Combat events, so it is saved with the game.
+
+use crate::character::CharacterSheet;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+// What each initiative pass costs from the score.
+pub const PASS_COST: i16 = 10;
+// Boxes of damage per point of wound modifier.
+pub const BOXES_PER_WOUND: u8 = 3;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Combatant {
+    pub name: String,
+    // Initiative score and dice from the sheet when the fight started.
+    pub base: u8,
+    pub dice: u8,
+    // Score rolled this round, before wounds and passes.
+    pub rolled: i16,
+    pub physical_damage: u8,
+    pub stun_damage: u8,
+    // Whether they already acted in this pass.
+    pub acted: bool,
+}
+
+impl Combatant {
+    pub fn new(sheet: &CharacterSheet) -> Self {
+        Combatant {
+            name: sheet.name.clone(),
+            base: sheet.initiative.0,
+            dice: sheet.initiative.1,
+            rolled: 0,
+            physical_damage: 0,
+            stun_damage: 0,
+            acted: false,
+        }
+    }
+
+    // e.g. -2 with 6 boxes of physical damage.
+    pub fn wound_modifier(&self) -> i16 {
+        -((self.physical_damage / BOXES_PER_WOUND + self.stun_damage / BOXES_PER_WOUND) as i16)
+    }
+
+    // Initiative score in `pass`, counting from 1.
+    pub fn score(&self, pass: u8) -> i16 {
+        self.rolled + self.wound_modifier() - PASS_COST * (pass.max(1) as i16 - 1)
+    }
+
+    fn roll(&mut self, rng: &mut impl Rng) {
+        self.rolled = self.base as i16
+            + (0..self.dice)
+                .map(|_| rng.gen_range(1..=6) as i16)
+                .sum::<i16>();
+        self.acted = false;
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Combat {
+    pub round: u32,
+    pub pass: u8,
+    // Dice pool modifier for everyone in the fight, e.g. -3 in heavy smoke.
+    pub environment: i8,
+    pub combatants: Vec<Combatant>,
+    // Whose action it is, by name; None when no one has a score left to act.
+    pub current: Option<String>,
+}
+
+impl Combat {
+    // A fight between the characters of `sheets`, with the first round's initiative rolled.
+    pub fn start(sheets: &[&CharacterSheet], rng: &mut impl Rng) -> Self {
+        let mut combat = Combat {
+            round: 0,
+            pass: 1,
+            environment: 0,
+            combatants: sheets.iter().map(|sheet| Combatant::new(sheet)).collect(),
+            current: None,
+        };
+        combat.new_round(rng);
+        combat
+    }
+
+    fn new_round(&mut self, rng: &mut impl Rng) {
+        self.round += 1;
+        self.pass = 1;
+        self.combatants
+            .iter_mut()
+            .for_each(|combatant| combatant.roll(rng));
+        self.current = self.next_in_pass();
+    }
+
+    // Those who still act in this pass, highest score first.
+    pub fn order(&self) -> Vec<&Combatant> {
+        let mut order: Vec<&Combatant> = self
+            .combatants
+            .iter()
+            .filter(|combatant| !combatant.acted && combatant.score(self.pass) > 0)
+            .collect();
+        order.sort_by_key(|combatant| -combatant.score(self.pass));
+        order
+    }
+
+    fn next_in_pass(&self) -> Option<String> {
+        self.order().first().map(|combatant| combatant.name.clone())
+    }
+
+    // End the current action and move on to the next one, the next pass or the next round.
+    pub fn next(&mut self, rng: &mut impl Rng) {
+        if let Some(current) = self.current.take() {
+            if let Some(combatant) = self.find_mut(&current) {
+                combatant.acted = true;
+            }
+        }
+        self.current = self.next_in_pass();
+        if self.current.is_some() {
+            return;
+        }
+        let pass = self.pass + 1;
+        if self
+            .combatants
+            .iter()
+            .any(|combatant| combatant.score(pass) > 0)
+        {
+            self.pass = pass;
+            self.combatants
+                .iter_mut()
+                .for_each(|combatant| combatant.acted = false);
+            self.current = self.next_in_pass();
+        } else {
+            self.new_round(rng);
+        }
+    }
+
+    pub fn find(&self, name: &str) -> Option<&Combatant> {
+        self.combatants
+            .iter()
+            .find(|combatant| combatant.name == name)
+    }
+
+    fn find_mut(&mut self, name: &str) -> Option<&mut Combatant> {
+        self.combatants
+            .iter_mut()
+            .find(|combatant| combatant.name == name)
+    }
+
+    // Add the damage a combatant took, if they are in the fight.
+    pub fn take_damage(&mut self, name: &str, boxes: u8, stun: bool) -> bool {
+        let Some(combatant) = self.find_mut(name) else {
+            return false;
+        };
+        if stun {
+            combatant.stun_damage = combatant.stun_damage.saturating_add(boxes);
+        } else {
+            combatant.physical_damage = combatant.physical_damage.saturating_add(boxes);
+        }
+        true
+    }
+
+    // Wounds and environment, added to the dice pools of `name` during the fight.
+    pub fn dice_pool_modifier(&self, name: &str) -> i16 {
+        self.find(name).map_or(0, |combatant| {
+            combatant.wound_modifier() + self.environment as i16
+        })
+    }
+}
@@ -0,0 +1,714 @@
+// commands.rs
+// Slash commands typed in the player input, handled locally instead of being sent to the AI.
+//
+// Commands live in a registry so other parts of the game can add their own with `register`.
+
+use crate::app::{App, AppCommand};
+use crate::character::CharacterSheet;
+use crate::combat::Combat;
+use crate::damage::{self, resist_damage, DamageResistanceRequest};
+use crate::dice::{dice_roll, opposed_roll, ShownRoll};
+use crate::dice_expr::DiceExpr;
+use crate::events::GameEvent;
+use crate::image::{self, GeneratedImage};
+use crate::message::{Message, MessageType};
+use crate::metrics;
+use crate::mirror;
+use crate::paths;
+use crate::save::SaveManager;
+use crate::tasks::{Jump, TaskKind};
+use crate::webhook::{self, WebhookEvent};
+
+// Dice pools beyond this are typos, not Shadowrun.
+pub const MAX_ROLL_DICE: u8 = 60;
+
+pub struct SlashCommand {
+    pub name: &'static str,
+    pub usage: &'static str,
+    pub description: &'static str,
+    // Only offered with Debug Mode on.
+    pub debug_only: bool,
+    pub run: fn(&mut App, &str),
+}
+
+#[derive(Default)]
+pub struct CommandRegistry {
+    commands: Vec<SlashCommand>,
+}
+
+impl CommandRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // The registry with every command the game ships with.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        for command in builtin_commands() {
+            registry.register(command);
+        }
+        registry
+    }
+
+    // Add a command, replacing any command with the same name.
+    pub fn register(&mut self, command: SlashCommand) {
+        self.commands
+            .retain(|existing| existing.name != command.name);
+        self.commands.push(command);
+    }
+
+    pub fn find(&self, name: &str, debug_mode: bool) -> Option<&SlashCommand> {
+        self.available(debug_mode)
+            .find(|command| command.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn available(&self, debug_mode: bool) -> impl Iterator<Item = &SlashCommand> {
+        self.commands
+            .iter()
+            .filter(move |command| debug_mode || !command.debug_only)
+    }
+}
+
+// What a line of player input means.
+#[derive(Debug, PartialEq)]
+pub enum ParsedInput<'a> {
+    Command { name: &'a str, args: &'a str },
+    // Plain text for the AI; "//" escapes a leading slash.
+    Text(&'a str),
+}
+
+pub fn parse_input(input: &str) -> ParsedInput<'_> {
+    let input = input.trim();
+    if input.starts_with("//") {
+        return ParsedInput::Text(&input[1..]);
+    }
+    match input.strip_prefix('/') {
+        Some(command) if !command.is_empty() => {
+            let (name, args) = command.split_once(' ').unwrap_or((command, ""));
+            ParsedInput::Command {
+                name,
+                args: args.trim(),
+            }
+        }
+        _ => ParsedInput::Text(input),
+    }
+}
+
+fn builtin_commands() -> Vec<SlashCommand> {
+    vec![
+        SlashCommand {
+            name: "roll",
+            usage: "/roll <dice|expression|dice vs dice>",
+            description: "Roll a pool of d6 and count hits, dice like 2d6+3 or 12d6!>=5 limit 6, or an opposed test like 8 vs 6",
+            debug_only: false,
+            run: roll,
+        },
+        SlashCommand {
+            name: "combat",
+            usage: "/combat start [name, ...] | next | env <modifier> | end",
+            description: "Track initiative passes, wounds and the environment in a fight",
+            debug_only: false,
+            run: combat,
+        },
+        SlashCommand {
+            name: "soak",
+            usage: "/soak <damage>[P|S] [AP]",
+            description: "Roll your character's damage resistance, e.g. /soak 6P -2",
+            debug_only: false,
+            run: soak,
+        },
+        SlashCommand {
+            name: "sheet",
+            usage: "/sheet",
+            description: "Show a summary of your character",
+            debug_only: false,
+            run: sheet,
+        },
+        SlashCommand {
+            name: "save",
+            usage: "/save",
+            description: "Save the game now",
+            debug_only: false,
+            run: save,
+        },
+        SlashCommand {
+            name: "fork",
+            usage: "/fork [name]",
+            description: "Branch the story into a new save, keeping this one as it is",
+            debug_only: false,
+            run: fork,
+        },
+        SlashCommand {
+            name: "upgrade",
+            usage: "/upgrade [new]",
+            description: "Give the campaign's assistant the latest instructions, or a new assistant with them",
+            debug_only: false,
+            run: upgrade,
+        },
+        SlashCommand {
+            name: "recap",
+            usage: "/recap",
+            description: "Ask the Game Master for a recap of the story so far",
+            debug_only: false,
+            run: recap,
+        },
+        SlashCommand {
+            name: "image",
+            usage: "/image <prompt>",
+            description: "Generate an image from a prompt",
+            debug_only: false,
+            run: image,
+        },
+        SlashCommand {
+            name: "scene",
+            usage: "/scene <prompt>",
+            description: "Illustrate the current scene and add it to the save's gallery",
+            debug_only: false,
+            run: scene,
+        },
+        SlashCommand {
+            name: "metrics",
+            usage: "/metrics",
+            description: "Export your usage metrics, if Usage Metrics is on",
+            debug_only: false,
+            run: export_metrics,
+        },
+        SlashCommand {
+            name: "shop",
+            usage: "/shop",
+            description: "Buy gear for your character",
+            debug_only: false,
+            run: shop,
+        },
+        SlashCommand {
+            name: "stats",
+            usage: "/stats",
+            description: "Show the statistics of this campaign",
+            debug_only: false,
+            run: stats,
+        },
+        SlashCommand {
+            name: "tasks",
+            usage: "/tasks",
+            description: "Show the pictures, narration and transcriptions in progress, and cancel them",
+            debug_only: false,
+            run: |app, _| app.open_tasks_panel(),
+        },
+        SlashCommand {
+            name: "copy",
+            usage: "/copy",
+            description: "Copy the Game Master's last answer to the clipboard",
+            debug_only: false,
+            run: copy,
+        },
+        SlashCommand {
+            name: "export",
+            usage: "/export [copy]",
+            description: "Export the character sheet as Markdown, or copy it to the clipboard",
+            debug_only: false,
+            run: |app, args| app.export_character_sheet_markdown(args.trim() == "copy"),
+        },
+        SlashCommand {
+            name: "retry",
+            usage: "/retry",
+            description: "Send the action the Game Master failed to answer again",
+            debug_only: false,
+            run: |app, _| app.retry_failed_action(),
+        },
+        SlashCommand {
+            name: "help",
+            usage: "/help",
+            description: "List the available commands",
+            debug_only: false,
+            run: help,
+        },
+        SlashCommand {
+            name: "npc",
+            usage: "/npc [role]",
+            description: "Roll up an NPC and add it to the game",
+            debug_only: true,
+            run: |app, role| app.generate_debug_npc(role),
+        },
+    ]
+}
+
+fn system(app: &App, text: String) {
+    app.add_message(Message::new(MessageType::System, text));
+}
+
+pub fn describe_roll(roll: &ShownRoll) -> String {
+    let results: Vec<String> = roll.dice_results.iter().map(u8::to_string).collect();
+    let summary = roll.summary();
+    let (hits, glitch) = summary.split_at(summary.find(" - ").unwrap_or(summary.len()));
+    format!("{}: {} [{}]{}", roll.title, hits, results.join(" "), glitch)
+}
+
+// Two pools against each other, e.g. "/roll 8 vs 6": both rolls, then the net hits.
+fn opposed(app: &mut App, acting: &str, opposing: &str) {
+    let (acting, opposing) = match (acting.trim().parse::<u8>(), opposing.trim().parse::<u8>()) {
+        (Ok(acting), Ok(opposing)) if acting.max(opposing) <= MAX_ROLL_DICE => (acting, opposing),
+        _ => {
+            return system(
+                app,
+                format!(
+                    "Usage: /roll <dice> vs <dice>, with 0 to {} dice a side",
+                    MAX_ROLL_DICE
+                ),
+            )
+        }
+    };
+    let roll = opposed_roll((acting, None), (opposing, None));
+    let net_hits = roll.net_hits();
+    let rolls = [
+        ShownRoll::new(format!("Rolled {} dice", acting), &roll.acting),
+        ShownRoll::new(format!("Against {} dice", opposing), &roll.opposing),
+    ];
+    for shown in &rolls {
+        system(app, describe_roll(shown));
+    }
+    system(
+        app,
+        match net_hits {
+            net if net > 0 => format!(
+                "{} net hit{}, the roll wins.",
+                net,
+                if net == 1 { "" } else { "s" }
+            ),
+            _ => "No net hits, the opposition wins.".to_string(),
+        },
+    );
+    rolls.into_iter().for_each(|shown| app.show_roll(shown));
+}
+
+fn roll(app: &mut App, args: &str) {
+    if let Some((acting, opposing)) = args.split_once(" vs ") {
+        return opposed(app, acting, opposing);
+    }
+    let roll = match args.parse::<u8>() {
+        Ok(dice) if (1..=MAX_ROLL_DICE).contains(&dice) => ShownRoll::new(
+            format!("Rolled {} dice", dice),
+            &dice_roll(dice, None, None, None),
+        ),
+        Ok(_) => {
+            return system(
+                app,
+                format!("Usage: /roll <dice>, with 1 to {} dice", MAX_ROLL_DICE),
+            )
+        }
+        Err(_) => match args.parse::<DiceExpr>() {
+            Ok(expr) => expr.roll(format!("Rolled {}", expr)),
+            Err(e) => {
+                return system(
+                    app,
+                    format!(
+                        "Can't roll '{}': {}. Try /roll 12, /roll 2d6+3 or /roll 12d6!>=5 limit 6",
+                        args, e
+                    ),
+                )
+            }
+        },
+    };
+    system(app, describe_roll(&roll));
+    app.show_roll(roll);
+}
+
+fn combat(app: &mut App, args: &str) {
+    let Some(game) = app.current_game.clone() else {
+        return system(app, "There is no game yet.".to_string());
+    };
+    let Ok(mut game_state) = game.try_lock() else {
+        return system(app, "The game is busy, try again in a moment.".to_string());
+    };
+    let (action, rest) = args.trim().split_once(' ').unwrap_or((args.trim(), ""));
+    let combat = match (action, game_state.combat.clone()) {
+        ("start", _) => {
+            // The player's character and whoever else is named.
+            let mut names: Vec<&str> = game_state
+                .main_character_sheet
+                .iter()
+                .map(|sheet| sheet.name.as_str())
+                .collect();
+            for name in rest.split(',').map(str::trim) {
+                if !name.is_empty() && !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+            let sheets: Result<Vec<&CharacterSheet>, String> = names
+                .iter()
+                .map(|name| {
+                    game_state
+                        .characters
+                        .iter()
+                        .find(|sheet| sheet.name == *name)
+                        .ok_or_else(|| format!("{} is not a character of this game.", name))
+                })
+                .collect();
+            match sheets {
+                Ok(sheets) if !sheets.is_empty() => {
+                    Some(Combat::start(&sheets, &mut rand::thread_rng()))
+                }
+                Ok(_) => return system(app, "There is no one to fight.".to_string()),
+                Err(e) => return system(app, e),
+            }
+        }
+        ("next", Some(mut combat)) => {
+            combat.next(&mut rand::thread_rng());
+            Some(combat)
+        }
+        ("env", Some(mut combat)) => match rest.trim().parse::<i8>() {
+            Ok(environment) => {
+                combat.environment = environment;
+                Some(combat)
+            }
+            Err(_) => return system(app, "Usage: /combat env <modifier>, e.g. -3".to_string()),
+        },
+        ("end", Some(_)) => None,
+        ("next" | "env" | "end", None) => {
+            return system(app, "There is no fight going on.".to_string())
+        }
+        _ => {
+            return system(
+                app,
+                "Usage: /combat start [name, ...] | next | env <modifier> | end".to_string(),
+            )
+        }
+    };
+    let text = match &combat {
+        Some(combat) => match &combat.current {
+            Some(name) => format!(
+                "Round {}, pass {}: {} acts.",
+                combat.round, combat.pass, name
+            ),
+            None => format!("Round {}: no one can act.", combat.round),
+        },
+        None => "The fight is over.".to_string(),
+    };
+    let recorded = game_state.record(GameEvent::Combat { combat });
+    drop(game_state);
+    system(app, recorded.map_or_else(|e| e, |()| text));
+}
+
+fn soak(app: &mut App, args: &str) {
+    let Some(game) = app.current_game.clone() else {
+        return system(app, "There is no game yet.".to_string());
+    };
+    let Ok(mut game_state) = game.try_lock() else {
+        return system(app, "The game is busy, try again in a moment.".to_string());
+    };
+    let Some(character) = game_state
+        .main_character_sheet
+        .as_ref()
+        .map(|sheet| sheet.name.clone())
+    else {
+        return system(app, "There is no character to soak the damage.".to_string());
+    };
+    let rolled = DamageResistanceRequest::parse(&character, args).and_then(|request| {
+        let rolled = resist_damage(&request, &game_state)?;
+        Ok((request, rolled))
+    });
+    let (request, (roll, response)) = match rolled {
+        Ok(rolled) => rolled,
+        Err(e) => {
+            drop(game_state);
+            return system(
+                app,
+                format!("Can't soak '{}': {}. Try /soak 6P -2", args, e),
+            );
+        }
+    };
+    let recorded = game_state
+        .record(GameEvent::Roll { roll: roll.clone() })
+        .and_then(|()| damage::track(&mut game_state, &request, &response));
+    drop(game_state);
+    if let Err(e) = recorded {
+        return system(app, e);
+    }
+    system(
+        app,
+        format!(
+            "{}, {} {} damage taken",
+            describe_roll(&roll),
+            response.damage_taken,
+            if response.stun { "stun" } else { "physical" }
+        ),
+    );
+    app.show_roll(roll);
+}
+
+pub fn sheet_summary(sheet: &CharacterSheet) -> String {
+    let mut summary = format!(
+        "{} ({} {}) | BOD {} AGI {} REA {} STR {} WIL {} LOG {} INT {} CHA {} EDG {}",
+        sheet.name,
+        sheet.gender,
+        sheet.race,
+        sheet.body,
+        sheet.agility,
+        sheet.reaction,
+        sheet.strength,
+        sheet.willpower,
+        sheet.logic,
+        sheet.intuition,
+        sheet.charisma,
+        sheet.edge,
+    );
+    if let Some(magic) = sheet.magic {
+        summary.push_str(&format!(" MAG {}", magic));
+    }
+    if let Some(resonance) = sheet.resonance {
+        summary.push_str(&format!(" RES {}", resonance));
+    }
+    summary.push_str(&format!(
+        " | Physical {} Stun {} | Essence {:.1} | {}¥",
+        sheet.physical_monitor, sheet.stun_monitor, sheet.essence, sheet.nuyen
+    ));
+    summary
+}
+
+// A freeform sheet: its name and entries, e.g. "Mira | Class: Ranger | Hit Points: 12/15".
+pub fn fields_summary(sheet: &CharacterSheet) -> String {
+    std::iter::once(sheet.name.clone())
+        .chain(
+            sheet
+                .fields
+                .iter()
+                .map(|(key, value)| format!("{}: {}", key, value)),
+        )
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+fn sheet(app: &mut App, _args: &str) {
+    let text = match &app.last_known_character_sheet {
+        Some(sheet) if !app.last_known_ruleset.rules().shadowrun_sheet() => fields_summary(sheet),
+        Some(sheet) => sheet_summary(sheet),
+        None => "No character sheet yet.".to_string(),
+    };
+    system(app, text);
+}
+
+fn save(app: &mut App, _args: &str) {
+    let Some(game_state) = app.current_game.clone() else {
+        system(app, "There is no game to save.".to_string());
+        return;
+    };
+    let mut save_manager = app.save_manager.clone();
+    let sender = app.command_sender.clone();
+    // The AI may be holding the game state mid-run, so wait for it off the UI thread.
+    tokio::spawn(async move {
+        let game_state = game_state.lock().await.clone();
+        let save_name = game_state.save_name.clone();
+        save_manager.current_save = Some(game_state);
+        let message = match save_manager.save() {
+            Ok(()) => format!("Game '{}' saved.", save_name),
+            Err(e) => format!("Failed to save '{}': {}", save_name, e),
+        };
+        let _ = sender.send(AppCommand::SystemMessage(message));
+    });
+}
+
+fn shop(app: &mut App, _args: &str) {
+    if let Err(e) = app.open_shop() {
+        system(app, e);
+    }
+}
+
+fn stats(app: &mut App, _args: &str) {
+    if let Err(e) = app.open_campaign_stats() {
+        system(app, e);
+    }
+}
+
+fn export_metrics(app: &mut App, _args: &str) {
+    if metrics::snapshot().is_none() {
+        system(
+            app,
+            "Usage metrics are off. Turn them on in the settings.".to_string(),
+        );
+        return;
+    }
+    let text = match metrics::export(&paths::exports_dir()) {
+        Ok(path) => format!("Usage metrics exported to {}", path.display()),
+        Err(e) => format!("Failed to export usage metrics: {}", e),
+    };
+    system(app, text);
+}
+
+// The name for a branch of `save_name`: the one asked for, or "<save> fork" numbered past the
+// saves that exist.
+pub fn fork_name(save_name: &str, requested: &str, existing: &[String]) -> Result<String, String> {
+    if !requested.is_empty() {
+        return match existing.iter().any(|save| save == requested) {
+            true => Err(format!("A save named '{}' already exists.", requested)),
+            false => Ok(requested.to_string()),
+        };
+    }
+    let base = format!("{} fork", save_name);
+    Ok((1..)
+        .map(|n| match n {
+            1 => base.clone(),
+            n => format!("{} {}", base, n),
+        })
+        .find(|name| !existing.contains(name))
+        .unwrap_or(base))
+}
+
+fn fork(app: &mut App, args: &str) {
+    let Some(game_state) = &app.save_manager.current_save else {
+        system(app, "There is no game to fork.".to_string());
+        return;
+    };
+    let existing = SaveManager::scan_save_files();
+    let text = match fork_name(&game_state.save_name, args, &existing) {
+        Ok(name) => {
+            app.start_spinner();
+            match app.command_sender.send(AppCommand::ForkGame(name.clone())) {
+                Ok(()) => format!("Forking the story into '{}'...", name),
+                Err(e) => {
+                    app.stop_spinner();
+                    format!("Error sending fork command: {:#?}", e)
+                }
+            }
+        }
+        Err(e) => e,
+    };
+    system(app, text);
+}
+
+fn upgrade(app: &mut App, args: &str) {
+    if app.current_game.is_none() {
+        system(app, "There is no game to upgrade.".to_string());
+        return;
+    }
+    let recreate = match args {
+        "" => false,
+        "new" => true,
+        _ => {
+            system(app, "Usage: /upgrade [new]".to_string());
+            return;
+        }
+    };
+    app.start_spinner();
+    let text = match app
+        .command_sender
+        .send(AppCommand::UpgradeAssistant(recreate))
+    {
+        Ok(()) => "Upgrading the assistant...".to_string(),
+        Err(e) => {
+            app.stop_spinner();
+            format!("Error sending upgrade command: {:#?}", e)
+        }
+    };
+    system(app, text);
+}
+
+fn recap(app: &mut App, _args: &str) {
+    app.add_message(Message::new(MessageType::User, "/recap".to_string()));
+    let stats = app
+        .current_game
+        .as_ref()
+        .and_then(|game| game.try_lock().ok().map(|game_state| game_state.stats));
+    if let Some(stats) = stats {
+        system(app, format!("Played so far: {}.", stats.describe()));
+    }
+    app.start_spinner();
+    let request = "Give me a short recap of the story so far, without advancing it.".to_string();
+    if let Err(e) = app.command_sender.send(AppCommand::ProcessMessage(request)) {
+        app.stop_spinner();
+        system(app, format!("Error sending message command: {:#?}", e));
+    }
+}
+
+fn image(app: &mut App, prompt: &str) {
+    if prompt.is_empty() {
+        system(app, "Usage: /image <prompt>".to_string());
+        return;
+    }
+    let prompt = prompt.to_string();
+    let sender = app.command_sender.clone();
+    app.tasks
+        .spawn(TaskKind::Image, prompt.clone(), async move {
+            let (message, result) = match image::generate_and_save_image(&prompt).await {
+                Ok(path) => {
+                    let message = format!("Image saved to {}", path.display());
+                    webhook::post(WebhookEvent::Image(path.clone()));
+                    (message, Ok(Some(Jump::Image(path))))
+                }
+                Err(e) => (
+                    format!("Failed to generate image: {}", e),
+                    Err(e.to_string()),
+                ),
+            };
+            let _ = sender.send(AppCommand::SystemMessage(message));
+            result
+        });
+    system(app, "Generating image...".to_string());
+}
+
+fn scene(app: &mut App, prompt: &str) {
+    if prompt.is_empty() {
+        system(app, "Usage: /scene <prompt>".to_string());
+        return;
+    }
+    let Some(ai) = app
+        .ai_client
+        .as_ref()
+        .filter(|_| app.current_game.is_some())
+    else {
+        system(app, "There is no game to illustrate.".to_string());
+        return;
+    };
+    let images = ai.images.clone();
+    let prompt = prompt.to_string();
+    // The prompt doubles as the title, cut to fit the gallery.
+    let title: String = prompt.chars().take(40).collect();
+    let sender = app.command_sender.clone();
+    app.tasks.spawn(TaskKind::Image, title.clone(), async move {
+        match image::generate_scene_image(&title, &prompt).await {
+            Ok(scene) => {
+                webhook::post(WebhookEvent::Image(scene.path.clone()));
+                let jump = Jump::Image(scene.path.clone());
+                images.with(|images| images.push(GeneratedImage::Scene(scene)));
+                Ok(Some(jump))
+            }
+            Err(e) => {
+                let message = format!("Failed to illustrate the scene: {}", e);
+                let _ = sender.send(AppCommand::SystemMessage(message));
+                Err(e.to_string())
+            }
+        }
+    });
+    system(app, "Illustrating the scene...".to_string());
+}
+
+fn copy(app: &mut App, _args: &str) {
+    let last = app
+        .game_content
+        .borrow()
+        .iter()
+        .rev()
+        .find(|message| message.message_type == MessageType::Game)
+        .map(mirror::plain_text);
+    match last {
+        Some(text) => {
+            let text = text.trim_start_matches("Game Master:\n").to_string();
+            app.copy(&text);
+            system(app, "The Game Master's last answer was copied.".to_string());
+        }
+        None => system(app, "The Game Master hasn't answered yet.".to_string()),
+    }
+}
+
+fn help(app: &mut App, _args: &str) {
+    let lines: Vec<String> = app
+        .commands
+        .available(app.settings.debug_mode)
+        .map(|command| format!("{:<16} {}", command.usage, command.description))
+        .collect();
+    system(
+        app,
+        format!(
+            "Commands (start with // to send a slash to the Game Master):\n{}",
+            lines.join("\n")
+        ),
+    );
+}
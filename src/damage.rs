@@ -0,0 +1,145 @@
+// damage.rs
+// Damage resistance tests: Body plus armor, less the attack's armor penetration, against the damage
+// value of the attack. Each hit takes a box of damage off, and the rest is what the character
+// takes. The soak roll goes in the roll history like any other roll, and during a fight the damage
+// goes on the combat tracker, for the wound modifiers.
+
+use crate::dice::{dice_roll, DiceRoll, ShownRoll};
+use crate::events::GameEvent;
+use crate::game_state::GameState;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct DamageResistanceRequest {
+    pub character_name: String,
+    // Damage value of the attack, in boxes.
+    pub damage: u8,
+    // As the weapon lists it, e.g. -2; a positive value counts the same.
+    #[serde(default)]
+    pub armor_penetration: i8,
+    #[serde(default)]
+    pub stun: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DamageResistanceResponse {
+    pub dice_pool: u8,
+    pub armor: u8,
+    pub hits: u8,
+    pub glitch: bool,
+    pub critical_glitch: bool,
+    pub dice_results: Vec<u8>,
+    // Boxes the character still takes, physical or stun.
+    pub damage_taken: u8,
+    pub stun: bool,
+}
+
+// The dice pool of the test and the armor counted in it.
+pub fn resistance_pool(
+    request: &DamageResistanceRequest,
+    game_state: &GameState,
+) -> Result<(u8, u8), String> {
+    let character = game_state
+        .characters
+        .iter()
+        .find(|c| c.name == request.character_name)
+        .ok_or_else(|| format!("Character '{}' not found", request.character_name))?;
+    let armor = game_state
+        .ruleset
+        .rules()
+        .resistance_armor(character, request.armor_penetration);
+    Ok((character.body.saturating_add(armor), armor))
+}
+
+// What the rolled hits leave of the damage.
+pub fn resolve(
+    request: &DamageResistanceRequest,
+    dice_pool: u8,
+    armor: u8,
+    roll: &DiceRoll,
+) -> DamageResistanceResponse {
+    DamageResistanceResponse {
+        dice_pool,
+        armor,
+        hits: roll.hits,
+        glitch: roll.glitch,
+        critical_glitch: roll.critical_glitch,
+        dice_results: roll.dice_results.clone(),
+        damage_taken: request.damage.saturating_sub(roll.hits),
+        stun: request.stun,
+    }
+}
+
+// Roll the test, with the roll to show and record.
+pub fn resist_damage(
+    request: &DamageResistanceRequest,
+    game_state: &GameState,
+) -> Result<(ShownRoll, DamageResistanceResponse), String> {
+    let (dice_pool, armor) = resistance_pool(request, game_state)?;
+    // No limit on soak hits.
+    let roll = dice_roll(dice_pool, None, None, None);
+    let response = resolve(request, dice_pool, armor, &roll);
+    let title = format!(
+        "{}: Damage resistance vs {}{} (Body + {} armor)",
+        request.character_name,
+        request.damage,
+        if request.stun { "S" } else { "P" },
+        armor
+    );
+    Ok((ShownRoll::new(title, &roll), response))
+}
+
+// Put the damage taken on the combat tracker, if the character is in the fight.
+pub fn track(
+    game_state: &mut GameState,
+    request: &DamageResistanceRequest,
+    response: &DamageResistanceResponse,
+) -> Result<(), String> {
+    let Some(mut combat) = game_state.combat.clone() else {
+        return Ok(());
+    };
+    if response.damage_taken == 0
+        || !combat.take_damage(
+            &request.character_name,
+            response.damage_taken,
+            response.stun,
+        )
+    {
+        return Ok(());
+    }
+    game_state.record(GameEvent::Combat {
+        combat: Some(combat),
+    })
+}
+
+impl DamageResistanceRequest {
+    // From a damage code as the books write it, e.g. "6P -2" or "4S".
+    pub fn parse(character_name: &str, code: &str) -> Result<Self, String> {
+        let mut words = code.split_whitespace();
+        let damage = words.next().ok_or("no damage value")?;
+        let (value, stun) = match damage.char_indices().last() {
+            Some((at, 'S' | 's')) => (&damage[..at], true),
+            Some((at, 'P' | 'p')) => (&damage[..at], false),
+            _ => (damage, false),
+        };
+        let damage = value
+            .parse::<u8>()
+            .map_err(|_| format!("'{}' is not a damage value", value))?;
+        let armor_penetration = match words.next() {
+            Some(ap) => ap
+                .parse::<i8>()
+                .map_err(|_| format!("'{}' is not an armor penetration", ap))?,
+            None => 0,
+        };
+        if let Some(extra) = words.next() {
+            return Err(format!("unexpected '{}'", extra));
+        }
+        Ok(DamageResistanceRequest {
+            character_name: character_name.to_string(),
+            damage,
+            armor_penetration,
+            stun,
+        })
+    }
+}
@@ -1,5 +1,7 @@
 // Import required modules and crates.
+use crate::character::CharacterSheet;
 use crate::game_state::GameState;
+use crate::house_rules;
 use rand::Rng; // RNG utilities from the rand crate for generating random numbers.
 use serde::{Deserialize, Serialize}; // Serialization utilities for struct serialization.
 
@@ -13,6 +15,20 @@ pub struct DiceRollRequest {
     threshold: Option<u8>,       // Optional threshold for determining success.
     edge_action: Option<String>, // Optional action that uses "edge" to affect the roll.
     extra_dice: Option<u8>,      // Optional number of extra dice to roll.
+    #[serde(default)]
+    weapon: Option<String>, // The equipped item used to attack, for its reach and recoil.
+    #[serde(default)]
+    shots: Option<u8>, // Rounds fired in the action, for recoil.
+}
+
+impl DiceRollRequest {
+    // Short description of the roll, e.g. "Raven: Agility + Pistols".
+    pub fn describe(&self) -> String {
+        format!(
+            "{}: {} + {}",
+            self.character_name, self.attribute, self.skill
+        )
+    }
 }
 
 // Structure to encapsulate the response after a dice roll.
@@ -26,23 +42,51 @@ pub struct DiceRollResponse {
     pub success: bool,          // Whether the roll was overall a success.
 }
 
-// Function to perform a dice roll based on a request and game state.
-pub fn perform_dice_roll(
-    request: DiceRollRequest,
+// The dice pool and limit of a character's attribute + skill test: the sheet's pool, then the
+// weapon used and, while the character is in a fight, their wounds and the environment.
+fn test_pool(
     game_state: &GameState,
-) -> Result<DiceRollResponse, String> {
-    // Find the character by name from the game state.
+    character_name: &str,
+    attribute: &str,
+    skill: &str,
+    limit_type: &str,
+    weapon: Option<(&str, u8)>,
+) -> Result<(u8, Option<u8>), String> {
     let character = game_state
         .characters
         .iter()
-        .find(|c| c.name == request.character_name)
-        .ok_or_else(|| format!("Character '{}' not found", request.character_name))?;
-
-    // Calculate the total dice pool from character's attributes and skills.
-    let dice_pool = character.get_dice_pool(&request.attribute, &request.skill);
+        .find(|c| c.name == character_name)
+        .ok_or_else(|| format!("Character '{}' not found", character_name))?;
+    let mut modifier = 0;
+    if let Some((weapon, shots)) = weapon {
+        modifier += character.weapon_modifier(weapon, shots)?;
+    }
+    if let Some(combat) = &game_state.combat {
+        modifier += combat.dice_pool_modifier(&character.name);
+    }
+    let pool = character.get_dice_pool(attribute, skill) as i16 + modifier;
+    // The limit from the character's stats, if the rules have one, and the campaign's house rules.
+    let limit = game_state.ruleset.rules().limit(character, limit_type);
+    let (pool, limit) = house_rules::for_save(&game_state.save_name).test(pool, limit);
+    Ok((pool.clamp(0, u8::MAX as i16) as u8, limit))
+}
 
-    // Get the applicable limit for the dice roll from the character's stats.
-    let limit = Some(character.get_limit(&request.limit_type));
+// Function to perform a dice roll based on a request and game state.
+pub fn perform_dice_roll(
+    request: DiceRollRequest,
+    game_state: &GameState,
+) -> Result<DiceRollResponse, String> {
+    let (dice_pool, limit) = test_pool(
+        game_state,
+        &request.character_name,
+        &request.attribute,
+        &request.skill,
+        &request.limit_type,
+        request
+            .weapon
+            .as_deref()
+            .map(|weapon| (weapon, request.shots.unwrap_or(1))),
+    )?;
 
     // Parse the optional edge action.
     let edge_action = match request.edge_action.as_deref() {
@@ -72,6 +116,278 @@ pub fn perform_dice_roll(
     })
 }
 
+// An opposed test between two characters, e.g. Sneaking against the guard's Perception. An
+// opponent without a sheet rolls `opponent_dice` instead.
+#[derive(Deserialize)]
+pub struct OpposedTestRequest {
+    pub character_name: String,
+    pub attribute: String,
+    pub skill: String,
+    pub limit_type: String,
+    pub opponent_name: String,
+    pub opponent_attribute: Option<String>,
+    pub opponent_skill: Option<String>,
+    pub opponent_limit_type: Option<String>,
+    pub opponent_dice: Option<u8>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OpposedTestResponse {
+    pub hits: u8,
+    pub opponent_hits: u8,
+    // Hits beyond the opponent's; the acting character wins with more than 0, ties included.
+    pub net_hits: i16,
+    pub success: bool,
+    pub glitch: bool,
+    pub opponent_glitch: bool,
+    pub dice_results: Vec<u8>,
+    pub opponent_dice_results: Vec<u8>,
+}
+
+// Two pools rolled against each other.
+pub struct OpposedRoll {
+    pub acting: DiceRoll,
+    pub opposing: DiceRoll,
+}
+
+impl OpposedRoll {
+    // Hits beyond the opponent's; the defender wins ties.
+    pub fn net_hits(&self) -> i16 {
+        self.acting.hits as i16 - self.opposing.hits as i16
+    }
+}
+
+pub fn opposed_roll(acting: (u8, Option<u8>), opposing: (u8, Option<u8>)) -> OpposedRoll {
+    opposed_roll_with(&mut rand::thread_rng(), acting, opposing)
+}
+
+// The same, with the dice from `rng`.
+pub fn opposed_roll_with(
+    rng: &mut impl Rng,
+    (pool, limit): (u8, Option<u8>),
+    (opposing_pool, opposing_limit): (u8, Option<u8>),
+) -> OpposedRoll {
+    OpposedRoll {
+        acting: dice_roll_with(rng, pool, limit, None, None),
+        opposing: dice_roll_with(rng, opposing_pool, opposing_limit, None, None),
+    }
+}
+
+// Roll both sides of an opposed test from their sheets, with a roll to show for each.
+pub fn perform_opposed_test(
+    request: &OpposedTestRequest,
+    game_state: &GameState,
+) -> Result<(OpposedTestResponse, [ShownRoll; 2]), String> {
+    let acting = test_pool(
+        game_state,
+        &request.character_name,
+        &request.attribute,
+        &request.skill,
+        &request.limit_type,
+        None,
+    )?;
+    let (opposing, opposing_title) = match (
+        &request.opponent_attribute,
+        &request.opponent_skill,
+        request.opponent_dice,
+    ) {
+        (Some(attribute), Some(skill), _)
+            if game_state
+                .characters
+                .iter()
+                .any(|c| c.name == request.opponent_name) =>
+        {
+            let limit_type = request.opponent_limit_type.as_deref().unwrap_or("physical");
+            (
+                test_pool(
+                    game_state,
+                    &request.opponent_name,
+                    attribute,
+                    skill,
+                    limit_type,
+                    None,
+                )?,
+                format!("{}: {} + {}", request.opponent_name, attribute, skill),
+            )
+        }
+        (_, _, Some(dice)) => (
+            (dice, None),
+            format!("{}: {} dice", request.opponent_name, dice),
+        ),
+        _ => {
+            return Err(format!(
+                "{} has no sheet: give opponent_dice for their side",
+                request.opponent_name
+            ))
+        }
+    };
+    let roll = opposed_roll(acting, opposing);
+    let net_hits = roll.net_hits();
+    let title = format!(
+        "{}: {} + {} vs {}",
+        request.character_name, request.attribute, request.skill, request.opponent_name
+    );
+    let response = OpposedTestResponse {
+        hits: roll.acting.hits,
+        opponent_hits: roll.opposing.hits,
+        net_hits,
+        success: net_hits > 0,
+        glitch: roll.acting.glitch,
+        opponent_glitch: roll.opposing.glitch,
+        dice_results: roll.acting.dice_results.clone(),
+        opponent_dice_results: roll.opposing.dice_results.clone(),
+    };
+    Ok((
+        response,
+        [
+            ShownRoll::new(title, &roll.acting),
+            ShownRoll::new(opposing_title, &roll.opposing),
+        ],
+    ))
+}
+
+// A test for a character the narrator plays, written as the books do, e.g. "Agility + Pistols" or
+// "Intuition + Willpower", so their results come from their sheet instead of the story.
+#[derive(Deserialize)]
+pub struct NpcTestRequest {
+    pub npc_name: String,
+    pub test: String,
+    pub limit_type: Option<String>,
+    pub threshold: Option<u8>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NpcTestResponse {
+    pub npc_name: String,
+    pub dice_pool: u8,
+    // Whether the NPC lacks the skill and rolled the attribute less one.
+    pub defaulted: bool,
+    pub hits: u8,
+    pub glitch: bool,
+    pub critical_glitch: bool,
+    pub dice_results: Vec<u8>,
+    pub success: bool,
+}
+
+const ATTRIBUTES: &[&str] = &[
+    "body",
+    "agility",
+    "reaction",
+    "strength",
+    "willpower",
+    "logic",
+    "intuition",
+    "charisma",
+    "magic",
+    "resonance",
+];
+
+// The limit a test of `attribute` goes against when none is given.
+fn limit_of(attribute: &str) -> &'static str {
+    match attribute {
+        "body" | "agility" | "reaction" | "strength" => "physical",
+        "charisma" => "social",
+        _ => "mental",
+    }
+}
+
+// Roll an NPC's test from their sheet, with the roll to show.
+pub fn perform_npc_test(
+    request: &NpcTestRequest,
+    game_state: &GameState,
+) -> Result<(NpcTestResponse, ShownRoll), String> {
+    let main = game_state
+        .main_character_sheet
+        .as_ref()
+        .map(|sheet| sheet.name.as_str());
+    let npcs: Vec<&CharacterSheet> = game_state
+        .characters
+        .iter()
+        .filter(|sheet| Some(sheet.name.as_str()) != main)
+        .collect();
+    let npc = npcs
+        .iter()
+        .find(|sheet| sheet.name.eq_ignore_ascii_case(request.npc_name.trim()))
+        .ok_or_else(|| {
+            let known: Vec<&str> = npcs.iter().map(|sheet| sheet.name.as_str()).collect();
+            if Some(request.npc_name.trim()) == main {
+                format!(
+                    "{} is the player's character: use perform_dice_roll",
+                    request.npc_name
+                )
+            } else if known.is_empty() {
+                format!(
+                    "{} has no sheet and there are no NPCs yet: create one with generate_npc",
+                    request.npc_name
+                )
+            } else {
+                format!(
+                    "{} has no sheet; the NPCs are {}, or create one with generate_npc",
+                    request.npc_name,
+                    known.join(", ")
+                )
+            }
+        })?;
+
+    let (attribute, second) = request
+        .test
+        .split_once('+')
+        .map(|(attribute, second)| (attribute.trim(), second.trim()))
+        .ok_or_else(|| format!("'{}' is not a test like Agility + Pistols", request.test))?;
+    let attribute_key = attribute.to_lowercase();
+    if !ATTRIBUTES.contains(&attribute_key.as_str()) {
+        return Err(format!("{} is not an attribute", attribute));
+    }
+    let limit_type = request
+        .limit_type
+        .clone()
+        .unwrap_or_else(|| limit_of(&attribute_key).to_string());
+    // The skill as the sheet spells it, matched whatever its case like the attribute and name.
+    let skill = npc
+        .get_all_active_skills()
+        .into_keys()
+        .find(|skill| skill.to_lowercase() == second.to_lowercase());
+    let (dice_pool, limit) = test_pool(
+        game_state,
+        &npc.name,
+        attribute,
+        skill.as_deref().unwrap_or(second),
+        &limit_type,
+        None,
+    )?;
+    // Two attributes add up; a skill the NPC lacks defaults to the attribute less one.
+    let (dice_pool, defaulted) = if ATTRIBUTES.contains(&second.to_lowercase().as_str()) {
+        (
+            dice_pool.saturating_add(npc.get_dice_pool(second, "")),
+            false,
+        )
+    } else if skill.is_some() {
+        (dice_pool, false)
+    } else {
+        (dice_pool.saturating_sub(1), true)
+    };
+
+    let roll = dice_roll(dice_pool, limit, request.threshold, None);
+    let success = match request.threshold {
+        Some(threshold) => roll.hits >= threshold,
+        None => roll.hits > 0,
+    };
+    let shown = ShownRoll::new(format!("{}: {} + {}", npc.name, attribute, second), &roll);
+    Ok((
+        NpcTestResponse {
+            npc_name: npc.name.clone(),
+            dice_pool,
+            defaulted,
+            hits: roll.hits,
+            glitch: roll.glitch,
+            critical_glitch: roll.critical_glitch,
+            dice_results: roll.dice_results,
+            success,
+        },
+        shown,
+    ))
+}
+
 // Structure to hold the results of a dice roll.
 pub struct DiceRoll {
     pub hits: u8,
@@ -81,6 +397,62 @@ pub struct DiceRoll {
     pub dice_results: Vec<u8>,
 }
 
+// A roll to show the player as it happens, from the assistant's tool call or /roll.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShownRoll {
+    pub title: String,
+    pub dice_results: Vec<u8>,
+    pub hits: u8,
+    pub glitch: bool,
+    pub critical_glitch: bool,
+    // The dice added up, for an expression like 2d6+3 that doesn't count hits.
+    #[serde(default)]
+    pub total: Option<i32>,
+}
+
+impl ShownRoll {
+    pub fn new(title: String, roll: &DiceRoll) -> Self {
+        ShownRoll {
+            title,
+            dice_results: roll.dice_results.clone(),
+            hits: roll.hits,
+            glitch: roll.glitch,
+            critical_glitch: roll.critical_glitch,
+            total: None,
+        }
+    }
+
+    // A line for the transcript when the roll glitched, e.g. "Raven: Agility + Pistols: 1 hit -
+    // glitch!".
+    pub fn glitch_notice(&self) -> Option<String> {
+        (self.glitch || self.critical_glitch).then(|| format!("{}: {}", self.title, self.summary()))
+    }
+
+    // "3 hits", "0 hits - critical glitch!", or "total 11".
+    pub fn summary(&self) -> String {
+        if let Some(total) = self.total {
+            return format!("total {}", total);
+        }
+        let mut text = format!("{} hit{}", self.hits, if self.hits == 1 { "" } else { "s" });
+        if self.critical_glitch {
+            text.push_str(" - critical glitch!");
+        } else if self.glitch {
+            text.push_str(" - glitch!");
+        }
+        text
+    }
+}
+
+// Whether a roll glitched: at least half the dice of the pool came up 1.
+pub fn is_glitch(ones: usize, pool: usize) -> bool {
+    pool > 0 && ones * 2 >= pool
+}
+
+// Whether a transcript line reports a glitch, to show it apart.
+pub fn reports_glitch(text: &str) -> bool {
+    text.ends_with(" - glitch!") || text.ends_with(" - critical glitch!")
+}
+
 // Function to execute the dice roll logic.
 pub fn dice_roll(
     dice_pool: u8,
@@ -88,20 +460,42 @@ pub fn dice_roll(
     threshold: Option<u8>,
     edge_action: Option<EdgeAction>,
 ) -> DiceRoll {
-    let mut rng = rand::thread_rng(); // Random number generator.
+    dice_roll_with(
+        &mut rand::thread_rng(),
+        dice_pool,
+        limit,
+        threshold,
+        edge_action,
+    )
+}
+
+// The same, with the dice from `rng`, so a seeded roll can be replayed.
+pub fn dice_roll_with(
+    rng: &mut impl Rng,
+    dice_pool: u8,
+    limit: Option<u8>,
+    threshold: Option<u8>,
+    edge_action: Option<EdgeAction>,
+) -> DiceRoll {
+    // Extra dice from Edge count toward the pool for glitches.
+    let pool = dice_pool as usize
+        + match edge_action {
+            Some(EdgeAction::AddExtraDice(extra)) => extra as usize,
+            _ => 0,
+        };
     let mut dice_results = Vec::new(); // Store results of each die roll.
     let mut hits = 0; // Count of successful hits (dice results of 5 or 6).
     let mut ones = 0; // Count of dice results that are 1, which might indicate a glitch.
 
     // Roll the dice as per the dice pool count.
     for _ in 0..dice_pool {
-        let mut die_result = roll_die(&mut rng); // Roll a single die.
+        let mut die_result = roll_die(rng); // Roll a single die.
         dice_results.push(die_result);
 
         // Implement "Rule of Six" where a roll of 6 allows re-rolling.
         while die_result == 6 {
             hits += 1; // Count hits from the dice.
-            die_result = roll_die(&mut rng);
+            die_result = roll_die(rng);
             dice_results.push(die_result);
         }
 
@@ -114,13 +508,7 @@ pub fn dice_roll(
 
     // Apply any edge actions that may alter the outcome of the roll.
     if let Some(edge_action) = edge_action {
-        apply_edge_action(
-            &mut dice_results,
-            &mut hits,
-            &mut ones,
-            edge_action,
-            &mut rng,
-        );
+        apply_edge_action(&mut dice_results, &mut hits, &mut ones, edge_action, rng);
     }
 
     // Apply the limit to the number of hits if specified.
@@ -129,7 +517,7 @@ pub fn dice_roll(
     }
 
     // Determine if a glitch or a critical glitch occurred.
-    let glitch = ones > dice_pool as usize / 2;
+    let glitch = is_glitch(ones, pool);
     let critical_glitch = glitch && hits == 0;
 
     // Check for critical success if a threshold is specified.
@@ -0,0 +1,280 @@
+// dice_expr.rs
+// Dice written out, for rolls that are not an attribute + skill pool: `2d6+3` adds the dice up,
+// `12d6!>=5 limit 6` counts the dice of 5 or more, rerolling and keeping sixes, at most 6 hits.
+//
+//   expression := [count] "d" sides option*
+//   option     := "!" | (">=" | ">") number | ("+" | "-") number | "limit" number
+//
+// Each option may be given once. Hits and a total don't mix, so `>=` rules out `+` and `-`, and
+// `limit` needs `>=`.
+
+use crate::dice::{is_glitch, ShownRoll};
+use rand::Rng;
+use std::fmt;
+use thiserror::Error;
+
+pub const MAX_DICE: u8 = 100;
+pub const MAX_SIDES: u8 = 100;
+// How many times one die may explode, so a run of maximum faces can't go on forever.
+const MAX_EXPLOSIONS: usize = 20;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("{message} (at character {})", .position + 1)]
+pub struct DiceExprError {
+    pub position: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiceExpr {
+    pub count: u8,
+    pub sides: u8,
+    pub explode: bool,
+    // Dice that roll at least this count as hits; without it the dice are added up.
+    pub target: Option<u8>,
+    pub modifier: i32,
+    pub limit: Option<u8>,
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn skip_spaces(&mut self) {
+        let rest = &self.text[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    fn error(&self, message: impl Into<String>) -> DiceExprError {
+        DiceExprError {
+            position: self.position,
+            message: message.into(),
+        }
+    }
+
+    // Consume `token`, ignoring case, if it comes next.
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_spaces();
+        let rest = self.rest();
+        if rest.len() >= token.len() && rest[..token.len()].eq_ignore_ascii_case(token) {
+            self.position += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn number(&mut self, what: &str) -> Result<Option<u32>, DiceExprError> {
+        self.skip_spaces();
+        let digits = self.rest().len()
+            - self
+                .rest()
+                .trim_start_matches(|c: char| c.is_ascii_digit())
+                .len();
+        if digits == 0 {
+            return Ok(None);
+        }
+        let number = self.rest()[..digits]
+            .parse()
+            .map_err(|_| self.error(format!("{} is too large", what)))?;
+        self.position += digits;
+        Ok(Some(number))
+    }
+
+    fn required(&mut self, what: &str, after: &str) -> Result<u32, DiceExprError> {
+        self.number(what)?
+            .ok_or_else(|| self.error(format!("expected {} after '{}'", what, after)))
+    }
+
+    // A number from 1 to `max`.
+    fn bounded(&mut self, what: &str, after: &str, max: u8) -> Result<u8, DiceExprError> {
+        let start = self.position;
+        let number = self.required(what, after)?;
+        match u8::try_from(number) {
+            Ok(number) if (1..=max).contains(&number) => Ok(number),
+            _ => Err(DiceExprError {
+                position: start,
+                message: format!("{} must be from 1 to {}", what, max),
+            }),
+        }
+    }
+}
+
+impl std::str::FromStr for DiceExpr {
+    type Err = DiceExprError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { text, position: 0 };
+        parser.skip_spaces();
+        let start = parser.position;
+        let count = match parser.number("the number of dice")? {
+            Some(count) => match u8::try_from(count) {
+                Ok(count) if (1..=MAX_DICE).contains(&count) => count,
+                _ => {
+                    return Err(DiceExprError {
+                        position: start,
+                        message: format!("the number of dice must be from 1 to {}", MAX_DICE),
+                    })
+                }
+            },
+            None => 1,
+        };
+        if !parser.eat("d") {
+            return Err(parser.error("expected 'd', as in 2d6"));
+        }
+        let sides = parser.bounded("the number of sides", "d", MAX_SIDES)?;
+        if sides < 2 {
+            return Err(parser.error("a die needs at least 2 sides"));
+        }
+
+        let mut expr = DiceExpr {
+            count,
+            sides,
+            explode: false,
+            target: None,
+            modifier: 0,
+            limit: None,
+        };
+        // Where + or - and limit were given, to point at them if they don't fit.
+        let mut modifier_at = None;
+        let mut limit_at = None;
+        loop {
+            parser.skip_spaces();
+            let option_start = parser.position;
+            let twice = |name: &str| DiceExprError {
+                position: option_start,
+                message: format!("'{}' is given twice", name),
+            };
+            if parser.rest().is_empty() {
+                break;
+            } else if parser.eat("!") {
+                if expr.explode {
+                    return Err(twice("!"));
+                }
+                expr.explode = true;
+            } else if parser.eat(">=") || parser.eat(">") {
+                let strict = !parser.text[option_start..parser.position].contains('=');
+                let after = if strict { ">" } else { ">=" };
+                if expr.target.is_some() {
+                    return Err(twice(">="));
+                }
+                let target = parser.bounded("a target", after, sides)? as u32 + strict as u32;
+                if target > sides as u32 {
+                    return Err(parser.error(format!("no d{} rolls more than {}", sides, sides)));
+                }
+                expr.target = Some(target as u8);
+            } else if parser.eat("+") || parser.eat("-") {
+                let negative = parser.text[..parser.position].ends_with('-');
+                if modifier_at.is_some() {
+                    return Err(twice(if negative { "-" } else { "+" }));
+                }
+                let modifier = parser.required("a number", if negative { "-" } else { "+" })?;
+                let modifier = i32::try_from(modifier)
+                    .ok()
+                    .filter(|modifier| *modifier <= 1000)
+                    .ok_or_else(|| parser.error("the modifier is larger than 1000"))?;
+                expr.modifier = if negative { -modifier } else { modifier };
+                modifier_at = Some(option_start);
+            } else if parser.eat("limit") {
+                if expr.limit.is_some() {
+                    return Err(twice("limit"));
+                }
+                expr.limit = Some(parser.bounded("a limit", "limit", u8::MAX)?);
+                limit_at = Some(option_start);
+            } else {
+                return Err(parser.error(format!(
+                    "unexpected '{}'; options are !, >=, +, - and limit",
+                    parser.rest().chars().next().unwrap_or_default()
+                )));
+            }
+        }
+
+        if let (Some(_), Some(position)) = (expr.target, modifier_at) {
+            return Err(DiceExprError {
+                position,
+                message: "counting hits with '>=' can't be combined with '+' or '-'".to_string(),
+            });
+        }
+        if let (None, Some(position)) = (expr.target, limit_at) {
+            return Err(DiceExprError {
+                position,
+                message: "'limit' caps hits, so it needs a target like '>=5'".to_string(),
+            });
+        }
+        Ok(expr)
+    }
+}
+
+impl fmt::Display for DiceExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}d{}", self.count, self.sides)?;
+        if self.explode {
+            write!(f, "!")?;
+        }
+        if let Some(target) = self.target {
+            write!(f, ">={}", target)?;
+        }
+        if self.modifier != 0 {
+            write!(f, "{:+}", self.modifier)?;
+        }
+        if let Some(limit) = self.limit {
+            write!(f, " limit {}", limit)?;
+        }
+        Ok(())
+    }
+}
+
+impl DiceExpr {
+    pub fn roll(&self, title: String) -> ShownRoll {
+        self.roll_with(&mut rand::thread_rng(), title)
+    }
+
+    // The same, with the dice from `rng`.
+    pub fn roll_with(&self, rng: &mut impl Rng, title: String) -> ShownRoll {
+        let mut dice_results = Vec::new();
+        for _ in 0..self.count {
+            let mut result = rng.gen_range(1..=self.sides);
+            dice_results.push(result);
+            let mut explosions = 0;
+            while self.explode && result == self.sides && explosions < MAX_EXPLOSIONS {
+                result = rng.gen_range(1..=self.sides);
+                dice_results.push(result);
+                explosions += 1;
+            }
+        }
+
+        match self.target {
+            Some(target) => {
+                let hits = dice_results.iter().filter(|die| **die >= target).count();
+                let hits = (hits.min(u8::MAX as usize) as u8).min(self.limit.unwrap_or(u8::MAX));
+                let ones = dice_results.iter().filter(|die| **die == 1).count();
+                let glitch = is_glitch(ones, self.count as usize);
+                ShownRoll {
+                    title,
+                    dice_results,
+                    hits,
+                    glitch,
+                    critical_glitch: glitch && hits == 0,
+                    total: None,
+                }
+            }
+            None => {
+                let sum: i32 = dice_results.iter().map(|die| *die as i32).sum();
+                ShownRoll {
+                    title,
+                    dice_results,
+                    hits: 0,
+                    glitch: false,
+                    critical_glitch: false,
+                    total: Some(sum + self.modifier),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,76 @@
+// elevenlabs.rs
+// Narration spoken by ElevenLabs instead of OpenAI. Speakers are still given an OpenAI voice from
+// their name and gender, and each of those voices stands for an ElevenLabs one, so every speaker
+// keeps a voice of their own; the settings can give a speaker, or an OpenAI voice, another one.
+
+use crate::error::AIError;
+use async_openai::types::Voice;
+use std::collections::HashMap;
+
+pub const API_URL: &str = "https://api.elevenlabs.io/v1/text-to-speech";
+pub const MODEL: &str = "eleven_multilingual_v2";
+// The speeds ElevenLabs accepts; faster settings are read at the fastest.
+pub const SPEED_RANGE: (f32, f32) = (0.7, 1.2);
+
+// Premade ElevenLabs voices, close to the OpenAI voices they replace.
+pub const DEFAULT_VOICES: [(Voice, &str); 6] = [
+    (Voice::Alloy, "21m00Tcm4TlvDq8ikWAM"),   // Rachel
+    (Voice::Echo, "ErXwobaYiN019PkySvjV"),    // Antoni
+    (Voice::Fable, "TxGEqnHWrfWFTfGW9XjX"),   // Josh
+    (Voice::Onyx, "pNInz6obpgDQGcFmaJgB"),    // Adam
+    (Voice::Nova, "EXAVITQu4vr4xnJzrGpy"),    // Bella
+    (Voice::Shimmer, "MF3mGyEYCl7XYWbV9V3O"), // Elli
+];
+
+// How the settings name an OpenAI voice, e.g. "onyx".
+fn voice_name(voice: &Voice) -> String {
+    format!("{:?}", voice).to_lowercase()
+}
+
+// The ElevenLabs voice of `speaker`, who was given `voice`: theirs in `voices` by name, else the
+// one `voices` gives `voice`, else its default.
+pub fn voice_id(voices: &HashMap<String, String>, speaker: &str, voice: &Voice) -> String {
+    voices
+        .get(speaker)
+        .or_else(|| voices.get(&voice_name(voice)))
+        .cloned()
+        .or_else(|| {
+            DEFAULT_VOICES
+                .iter()
+                .find(|(default, _)| default == voice)
+                .map(|(_, id)| id.to_string())
+        })
+        .unwrap_or_else(|| DEFAULT_VOICES[0].1.to_string())
+}
+
+// `text` read in the voice `voice_id`, as mp3.
+pub async fn speak(
+    api_key: &str,
+    voice_id: &str,
+    text: &str,
+    speed: f32,
+) -> Result<Vec<u8>, AIError> {
+    let response = reqwest::Client::new()
+        .post(format!("{}/{}", API_URL, voice_id))
+        .header("xi-api-key", api_key)
+        .json(&serde_json::json!({
+            "text": text,
+            "model_id": MODEL,
+            "voice_settings": {
+                "speed": speed.clamp(SPEED_RANGE.0, SPEED_RANGE.1),
+            },
+        }))
+        .send()
+        .await
+        .map_err(|e| AIError::ElevenLabs(e.to_string()))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(AIError::ElevenLabs(format!("{}: {}", status, body)));
+    }
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| AIError::ElevenLabs(e.to_string()))?;
+    Ok(bytes.to_vec())
+}
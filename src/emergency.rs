@@ -0,0 +1,131 @@
+// emergency.rs
+// Last-resort save of the running game when the app panics or is told to terminate.
+//
+// The game loop keeps a recent copy of the game state and transcript here, so writing it out never
+// has to wait on a lock that the failing code might hold.
+
+use crate::game_state::GameState;
+use crate::message::Message;
+use crate::paths;
+use chrono::Local;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+const SUFFIX: &str = ".emergency.json";
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct EmergencySave {
+    pub saved_at: String,
+    pub reason: String,
+    pub game_state: GameState,
+    pub transcript: Vec<Message>,
+}
+
+// The copy of the game, with the revisions it was taken at.
+#[derive(Clone)]
+struct Snapshot {
+    game_state: GameState,
+    game_revision: usize,
+    transcript: Vec<Message>,
+    transcript_revision: u64,
+}
+
+static SNAPSHOT: Lazy<Mutex<Option<Snapshot>>> = Lazy::new(|| Mutex::new(None));
+
+// Remember the latest state of the game being played. Most ticks nothing happened, so the game and
+// the transcript are only copied when their revision moved on.
+pub fn track(
+    game_state: &GameState,
+    game_revision: usize,
+    transcript: &[Message],
+    transcript_revision: u64,
+) {
+    if let Ok(mut snapshot) = SNAPSHOT.lock() {
+        match snapshot.as_mut() {
+            Some(tracked) if tracked.game_state.save_name == game_state.save_name => {
+                if tracked.game_revision != game_revision {
+                    tracked.game_state = game_state.clone();
+                    tracked.game_revision = game_revision;
+                }
+                if tracked.transcript_revision != transcript_revision {
+                    tracked.transcript = transcript.to_vec();
+                    tracked.transcript_revision = transcript_revision;
+                }
+            }
+            _ => {
+                *snapshot = Some(Snapshot {
+                    game_state: game_state.clone(),
+                    game_revision,
+                    transcript: transcript.to_vec(),
+                    transcript_revision,
+                })
+            }
+        }
+    }
+}
+
+// Forget the tracked game, e.g. after returning to the main menu.
+pub fn untrack() {
+    if let Ok(mut snapshot) = SNAPSHOT.lock() {
+        *snapshot = None;
+    }
+}
+
+pub fn path_for(save_name: &str) -> PathBuf {
+    paths::save_dir().join(format!("{}{}", save_name, SUFFIX))
+}
+
+pub fn is_emergency_file(file_name: &str) -> bool {
+    file_name.ends_with(SUFFIX)
+}
+
+// Write the tracked game next to its save. Safe to call from a panic hook or signal handler.
+pub fn write(reason: &str) -> Option<PathBuf> {
+    // A panic while the snapshot was being updated poisons the lock; the data is still usable.
+    let snapshot = match SNAPSHOT.try_lock() {
+        Ok(snapshot) => snapshot.clone(),
+        Err(std::sync::TryLockError::Poisoned(poisoned)) => poisoned.into_inner().clone(),
+        Err(std::sync::TryLockError::WouldBlock) => None,
+    }?;
+    let Snapshot {
+        game_state,
+        transcript,
+        ..
+    } = snapshot;
+    let path = path_for(&game_state.save_name);
+    let save = EmergencySave {
+        saved_at: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        reason: reason.to_string(),
+        game_state,
+        transcript,
+    };
+    let json = serde_json::to_string_pretty(&save).ok()?;
+    fs::create_dir_all(paths::save_dir()).ok()?;
+    fs::write(&path, json).ok()?;
+    Some(path)
+}
+
+// The emergency save left by a previous run, if any.
+pub fn find() -> Option<EmergencySave> {
+    let mut files: Vec<PathBuf> = fs::read_dir(paths::save_dir())
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(is_emergency_file)
+        })
+        .collect();
+    files.sort();
+    files
+        .into_iter()
+        .find_map(|path| serde_json::from_str(&fs::read_to_string(path).ok()?).ok())
+}
+
+pub fn discard(save_name: &str) -> io::Result<()> {
+    fs::remove_file(path_for(save_name))
+}
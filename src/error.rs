@@ -32,6 +32,14 @@ pub enum ShadowrunError {
     Game(String),
     #[error("Network error: {0}")]
     Network(String),
+    // The connection was down before the player's action reached the assistant, so it can be
+    // sent again as is.
+    #[error("Offline: {0}")]
+    Offline(String),
+    // OpenAI no longer has the thread or the assistant asked for, e.g. one deleted from the
+    // dashboard or expired, so an old save can't reach it.
+    #[error("Not found: {0}")]
+    NotFound(String),
     #[error("UI error: {0}")]
     UI(String),
     #[error("Audio error: {0}")]
@@ -46,6 +54,25 @@ pub enum ShadowrunError {
     Unknown(String),
 }
 
+impl ShadowrunError {
+    // The kind of error, without its message, for usage metrics.
+    pub fn category(&self) -> &'static str {
+        match self {
+            ShadowrunError::AI(_) => "AI",
+            ShadowrunError::Game(_) => "Game",
+            ShadowrunError::Network(_) => "Network",
+            ShadowrunError::Offline(_) => "Offline",
+            ShadowrunError::NotFound(_) => "NotFound",
+            ShadowrunError::UI(_) => "UI",
+            ShadowrunError::Audio(_) => "Audio",
+            ShadowrunError::Serialization(_) => "Serialization",
+            ShadowrunError::IO(_) => "IO",
+            ShadowrunError::OpenAI(_) => "OpenAI",
+            ShadowrunError::Unknown(_) => "Unknown",
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct ErrorMessage {
     pub error: ShadowrunError,
@@ -77,6 +104,8 @@ impl ErrorHandler {
         }
         match &error {
             ShadowrunError::Network(msg) => error!("Network Error: {}", msg),
+            ShadowrunError::Offline(msg) => error!("Offline: {}", msg),
+            ShadowrunError::NotFound(msg) => error!("Not Found: {}", msg),
             ShadowrunError::Game(msg) => error!("Game Logic Error: {}", msg),
             ShadowrunError::UI(msg) => error!("UI Error: {}", msg),
             ShadowrunError::AI(msg) => error!("AI Error: {}", msg),
@@ -110,6 +139,49 @@ pub enum AppError {
     MaxAttemptsReached,
 }
 
+impl AppError {
+    // What kept a turn from being answered, in the player's words.
+    pub fn turn_failure(&self) -> String {
+        match self {
+            AppError::Shadowrun(ShadowrunError::Network(e)) => {
+                format!("The connection to OpenAI failed ({}).", e)
+            }
+            AppError::Shadowrun(ShadowrunError::Serialization(e)) => {
+                format!("The Game Master's answer could not be read ({}).", e)
+            }
+            AppError::Shadowrun(ShadowrunError::Game(e))
+                if e.starts_with("Failed to parse GameMessage") =>
+            {
+                format!("The Game Master's answer could not be read ({}).", e)
+            }
+            AppError::Shadowrun(ShadowrunError::NotFound(e)) => {
+                format!("OpenAI no longer has this game's conversation ({}).", e)
+            }
+            AppError::Shadowrun(ShadowrunError::OpenAI(e)) => {
+                format!("OpenAI refused the request ({}).", e)
+            }
+            AppError::Shadowrun(ShadowrunError::Unknown(e)) if e == "Timeout occurred" => {
+                "The Game Master took too long to answer.".to_string()
+            }
+            AppError::Timeout => "The Game Master took too long to answer.".to_string(),
+            AppError::Shadowrun(e) => format!("The turn failed ({}).", e),
+            AppError::AIClientNotInitialized => {
+                "There is no connection to OpenAI: check the API key in the settings.".to_string()
+            }
+            AppError::NoCurrentGame => "There is no game to send the action to.".to_string(),
+            e => format!("The turn failed ({}).", e),
+        }
+    }
+
+    // Whether sending the same action again could work.
+    pub fn retryable(&self) -> bool {
+        !matches!(
+            self,
+            AppError::AIClientNotInitialized | AppError::NoCurrentGame
+        )
+    }
+}
+
 impl From<String> for ShadowrunError {
     fn from(error: String) -> Self {
         ShadowrunError::Unknown(error)
@@ -168,7 +240,17 @@ impl From<std::io::Error> for ShadowrunError {
 
 impl From<async_openai::error::OpenAIError> for ShadowrunError {
     fn from(error: async_openai::error::OpenAIError) -> Self {
-        ShadowrunError::OpenAI(error.to_string())
+        match error {
+            // No answer came back at all, e.g. the connection is down.
+            async_openai::error::OpenAIError::Reqwest(e) => ShadowrunError::Network(e.to_string()),
+            // e.g. "No thread found with id 'thread_abc'."
+            async_openai::error::OpenAIError::ApiError(e)
+                if e.message.starts_with("No ") && e.message.contains(" found with id") =>
+            {
+                ShadowrunError::NotFound(e.message)
+            }
+            error => ShadowrunError::OpenAI(error.to_string()),
+        }
     }
 }
 
@@ -212,6 +294,9 @@ pub enum AIError {
 
     #[error("Thread join error: {:#}", 0)]
     ThreadJoinError(String),
+
+    #[error("ElevenLabs error: {0}")]
+    ElevenLabs(String),
 }
 
 #[derive(Debug, Error)]
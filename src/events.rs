@@ -0,0 +1,191 @@
+// events.rs
+// Everything that happens in a game, in order: messages, sheet updates, rolls, images. The save
+// keeps the whole stream, and the rest of GameState is what folding it gives, so replaying,
+// undoing, comparing two points of a game and branching off one all start from the same record.
+//
+// A save from before events starts its stream with a Snapshot of how it was.
+
+use crate::archivist::Archive;
+use crate::character::{CharacterSheet, CharacterSheetUpdate};
+use crate::combat::Combat;
+use crate::dice::ShownRoll;
+use crate::game_state::GameState;
+use crate::house_rules;
+use crate::image::SceneImage;
+use crate::message::MessageType;
+use crate::ruleset::RulesetKind;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum GameEvent {
+    // The state of a save that had no events yet.
+    Snapshot {
+        state: Box<GameState>,
+    },
+    Thread {
+        assistant_id: String,
+        thread_id: String,
+    },
+    // The rules the campaign is played by; the sheets are derived again by them.
+    Ruleset {
+        ruleset: RulesetKind,
+    },
+    // A message added to the thread: the player's, the narrator's, or one the game sent itself.
+    Message {
+        sender: MessageType,
+        text: String,
+    },
+    MainCharacter {
+        sheet: Option<CharacterSheet>,
+    },
+    // A character added, or replaced by name.
+    Character {
+        sheet: CharacterSheet,
+    },
+    SheetUpdate {
+        character: String,
+        update: CharacterSheetUpdate,
+    },
+    Roll {
+        roll: ShownRoll,
+    },
+    // The combat tracker as it is after a change, or None when the fight is over.
+    Combat {
+        combat: Option<Combat>,
+    },
+    // A run started, or None once it ended, so a run the game was closed during can be picked up.
+    Run {
+        run_id: Option<String>,
+    },
+    // The assistant was given the prompt with this version; see assistant::instructions_version.
+    Instructions {
+        version: u32,
+    },
+    // Time played since the last one, added when the game is saved.
+    Played {
+        seconds: u64,
+    },
+    Archive {
+        archive: Archive,
+    },
+    Portrait {
+        name: String,
+        path: PathBuf,
+    },
+    Scene {
+        scene: SceneImage,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RecordedEvent {
+    pub at: String,
+    pub event: GameEvent,
+}
+
+impl RecordedEvent {
+    pub fn now(event: GameEvent) -> Self {
+        RecordedEvent {
+            at: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            event,
+        }
+    }
+}
+
+impl GameEvent {
+    // Change the state as the event says, leaving its event stream alone. An update that does not
+    // fit the sheet changes nothing.
+    pub fn apply(&self, state: &mut GameState) -> Result<(), String> {
+        match self {
+            GameEvent::Snapshot { state: snapshot } => {
+                let events = std::mem::take(&mut state.events);
+                let save_name = std::mem::take(&mut state.save_name);
+                *state = GameState {
+                    events,
+                    save_name,
+                    ..(**snapshot).clone()
+                };
+            }
+            GameEvent::Thread {
+                assistant_id,
+                thread_id,
+            } => {
+                state.assistant_id = assistant_id.clone();
+                state.thread_id = thread_id.clone();
+            }
+            GameEvent::Ruleset { ruleset } => {
+                state.ruleset = *ruleset;
+                state
+                    .characters
+                    .iter_mut()
+                    .chain(state.main_character_sheet.iter_mut())
+                    .for_each(|sheet| house_rules::derive(*ruleset, &state.save_name, sheet));
+            }
+            GameEvent::Message { sender, .. } => {
+                state.archive.added(1);
+                if *sender == MessageType::User {
+                    state.stats.turns += 1;
+                }
+            }
+            GameEvent::MainCharacter { sheet } => {
+                let mut sheet = sheet.clone();
+                if let Some(sheet) = sheet.as_mut() {
+                    house_rules::derive(state.ruleset, &state.save_name, sheet);
+                }
+                state.main_character_sheet = sheet;
+            }
+            GameEvent::Character { sheet } => {
+                let mut sheet = sheet.clone();
+                house_rules::derive(state.ruleset, &state.save_name, &mut sheet);
+                match state.characters.iter_mut().find(|c| c.name == sheet.name) {
+                    Some(character) => *character = sheet,
+                    None => state.characters.push(sheet),
+                }
+            }
+            GameEvent::SheetUpdate { character, update } => {
+                let sheet = state
+                    .characters
+                    .iter_mut()
+                    .find(|c| c.name == *character)
+                    .ok_or_else(|| format!("{} is no longer in the game", character))?;
+                let mut updated = sheet.clone();
+                updated.apply_update(update.clone())?;
+                house_rules::settle(state.ruleset, &state.save_name, &mut updated);
+                *sheet = updated.clone();
+                // The main sheet is kept in step with its character.
+                if state
+                    .main_character_sheet
+                    .as_ref()
+                    .is_some_and(|main| main.name == *character)
+                {
+                    state.main_character_sheet = Some(updated);
+                }
+            }
+            GameEvent::Roll { .. } => {}
+            GameEvent::Combat { combat } => state.combat = combat.clone(),
+            GameEvent::Run { run_id } => state.pending_run = run_id.clone(),
+            GameEvent::Instructions { version } => state.instructions_version = Some(*version),
+            GameEvent::Played { seconds } => state.stats.seconds += seconds,
+            GameEvent::Archive { archive } => state.archive = archive.clone(),
+            GameEvent::Portrait { name, path } => {
+                state.portraits.insert(name.clone(), path.clone());
+            }
+            GameEvent::Scene { scene } => state.scenes.push(scene.clone()),
+        }
+        Ok(())
+    }
+}
+
+// The game that `events` make, from an empty save.
+pub fn fold(save_name: &str, events: &[RecordedEvent]) -> GameState {
+    let mut state = GameState::new(save_name);
+    for recorded in events {
+        if let Err(e) = state.record_at(recorded.clone()) {
+            log::warn!("Skipped an event of '{}' while replaying: {}", save_name, e);
+        }
+    }
+    state
+}
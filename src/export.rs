@@ -0,0 +1,587 @@
+// export.rs
+// Render a character sheet to a standalone HTML page (and PDF when a renderer is installed), or to
+// Markdown with aligned tables, which reads as plain text too, for pasting into a chat.
+
+use crate::character::{CharacterSheet, ItemState};
+use crate::paths;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+// Escape text for use inside HTML elements and attributes.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn section(title: &str, class: &str, body: &str) -> String {
+    format!(
+        "<section class=\"{}\"><h2>{}</h2>{}</section>\n",
+        class,
+        escape(title),
+        body
+    )
+}
+
+// Keep HashMap-backed lists in a stable order so exports diff cleanly.
+fn sorted<V>(map: &HashMap<String, V>) -> Vec<(&String, &V)> {
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
+fn grid(cells: &[String], columns: usize, class: &str) -> String {
+    let cells: String = cells
+        .iter()
+        .map(|cell| format!("<div>{}</div>", escape(cell)))
+        .collect();
+    format!(
+        "<div class=\"grid {}\" style=\"grid-template-columns: repeat({}, 1fr)\">{}</div>",
+        class, columns, cells
+    )
+}
+
+// Lay the sheet out in the same sections and order as the in-game character sheet.
+pub fn character_sheet_html(sheet: &CharacterSheet) -> String {
+    let mut body = String::new();
+
+    body.push_str(&section(
+        "Basic Information",
+        "basic",
+        &format!(
+            "<p><b>Name:</b> {} | <b>Race:</b> {} | <b>Gender:</b> {}</p>",
+            escape(&sheet.name),
+            escape(&sheet.race.to_string()),
+            escape(&sheet.gender)
+        ),
+    ));
+
+    let attributes = [
+        ("BODY", sheet.body),
+        ("AGILITY", sheet.agility),
+        ("REACTION", sheet.reaction),
+        ("STRENGTH", sheet.strength),
+        ("WILLPOWER", sheet.willpower),
+        ("LOGIC", sheet.logic),
+        ("INTUITION", sheet.intuition),
+        ("CHARISMA", sheet.charisma),
+        ("EDGE", sheet.edge),
+        ("MAGIC", sheet.magic.unwrap_or(0)),
+        ("RESONANCE", sheet.resonance.unwrap_or(0)),
+    ]
+    .iter()
+    .map(|(name, value)| format!("{}: {}", name, value))
+    .collect::<Vec<_>>();
+    body.push_str(&section(
+        "Attributes",
+        "attributes",
+        &grid(&attributes, 4, "green"),
+    ));
+
+    let derived = [
+        format!(
+            "Initiative: {}+{}d6",
+            sheet.initiative.0, sheet.initiative.1
+        ),
+        match sheet.limits() {
+            Some((physical, mental, social)) => {
+                format!("Limits: PHY:{} MEN:{} SOC:{}", physical, mental, social)
+            }
+            None => "Limits: none".to_string(),
+        },
+        format!(
+            "Monitors: PHY:{} STUN:{}",
+            sheet.physical_monitor, sheet.stun_monitor
+        ),
+        format!("Essence: {:.2}", sheet.essence),
+        format!("Edge Points: {}", sheet.edge_points),
+        format!("Armor: {}", sheet.armor),
+    ];
+    body.push_str(&section(
+        "Derived Attributes",
+        "derived",
+        &grid(&derived, 2, "cyan"),
+    ));
+
+    let categories = [
+        ("Combat", &sheet.skills.combat),
+        ("Physical", &sheet.skills.physical),
+        ("Social", &sheet.skills.social),
+        ("Technical", &sheet.skills.technical),
+        ("Knowledge", &sheet.knowledge_skills),
+    ];
+    let skill_rows: String = categories
+        .iter()
+        .map(|(category, skills)| {
+            let skills = sorted(skills)
+                .iter()
+                .map(|(skill, rating)| format!("{}:{}", escape(skill), rating))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("<tr><th>{}</th><td>{}</td></tr>", escape(category), skills)
+        })
+        .collect();
+    body.push_str(&section(
+        "Skills",
+        "skills",
+        &format!("<table>{}</table>", skill_rows),
+    ));
+
+    let qualities = sheet
+        .qualities
+        .iter()
+        .map(|q| {
+            format!(
+                "<span class=\"{}\">{}</span>",
+                if q.positive { "green" } else { "red" },
+                escape(&q.name)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    body.push_str(&section(
+        "Qualities",
+        "qualities",
+        &format!("<p>{}</p>", qualities),
+    ));
+
+    let resources = section(
+        "Resources",
+        "resources",
+        &format!(
+            "<p>Lifestyle: {}<br>Nuyen: {}</p>",
+            escape(&sheet.lifestyle),
+            sheet.nuyen
+        ),
+    );
+    let list = |items: &[String]| -> String {
+        let items: String = items
+            .iter()
+            .map(|item| format!("<li>{}</li>", escape(item)))
+            .collect();
+        format!("<ul>{}</ul>", items)
+    };
+    let augmentations = format!(
+        "<div class=\"columns\">{}{}</div>",
+        section("Cyberware", "cyberware", &list(&sheet.cyberware)),
+        section("Bioware", "bioware", &list(&sheet.bioware))
+    );
+
+    let contact_rows: String = sorted(&sheet.contacts)
+        .iter()
+        .map(|(name, contact)| {
+            format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td></tr><tr class=\"note\"><td colspan=\"3\">{}</td></tr>",
+                escape(name),
+                contact.loyalty,
+                contact.connection,
+                escape(&contact.description)
+            )
+        })
+        .collect();
+    let contacts = section(
+        "Contacts",
+        "contacts",
+        &format!(
+            "<table><tr class=\"yellow\"><th>Name</th><th>Loyalty</th><th>Connection</th></tr>{}</table>",
+            contact_rows
+        ),
+    );
+
+    let inventory_items: String = sorted(&sheet.inventory)
+        .iter()
+        .map(|(_, item)| {
+            let state = match item.state {
+                ItemState::Carried => String::new(),
+                state => format!(", {}", state),
+            };
+            format!(
+                "<li>{} (x{}{})<div class=\"note\">{}</div></li>",
+                escape(&item.name),
+                item.quantity,
+                state,
+                escape(&item.description)
+            )
+        })
+        .collect();
+    let inventory = section(
+        "Inventory",
+        "inventory",
+        &format!("<ul>{}</ul>", inventory_items),
+    );
+
+    body.push_str(&format!(
+        "<div class=\"columns\"><div>{}{}</div><div>{}{}</div></div>\n",
+        resources, inventory, augmentations, contacts
+    ));
+
+    if let Some(matrix) = &sheet.matrix_attributes {
+        let matrix = [
+            format!("Attack: {}", matrix.attack),
+            format!("Sleaze: {}", matrix.sleaze),
+            format!("Data Processing: {}", matrix.data_processing),
+            format!("Firewall: {}", matrix.firewall),
+        ];
+        body.push_str(&section(
+            "Matrix Attributes",
+            "matrix",
+            &grid(&matrix, 4, "cyan"),
+        ));
+    }
+
+    if !sheet.fields.is_empty() {
+        let entries: String = sheet
+            .fields
+            .iter()
+            .map(|(key, value)| {
+                format!(
+                    "<tr><td>{}</td><td>{}</td></tr>",
+                    escape(key),
+                    escape(value)
+                )
+            })
+            .collect();
+        body.push_str(&section(
+            "Sheet",
+            "fields",
+            &format!("<table>{}</table>", entries),
+        ));
+    }
+
+    body.push_str(&section(
+        "Backstory",
+        "backstory",
+        &format!("<p>{}</p>", escape(&sheet.backstory)),
+    ));
+
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<h1>{}</h1>\n{}</body>\n</html>\n",
+        escape(&sheet.name),
+        STYLE,
+        escape(&sheet.name),
+        body
+    )
+}
+
+// A cell of a Markdown table, on one line and without breaking the columns.
+fn cell(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace('|', "\\|")
+}
+
+// A Markdown table with its columns padded to the same width, so it lines up as plain text.
+fn table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let rows: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| row.iter().map(|text| cell(text)).collect())
+        .collect();
+    let widths: Vec<usize> = (0..header.len())
+        .map(|column| {
+            rows.iter()
+                .filter_map(|row| row.get(column))
+                .map(|text| text.chars().count())
+                .chain([header[column].chars().count(), 3])
+                .max()
+                .unwrap_or(3)
+        })
+        .collect();
+    let line = |cells: Vec<String>| -> String {
+        let cells: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(text, width)| format!("{:<width$}", text, width = width))
+            .collect();
+        format!("| {} |\n", cells.join(" | "))
+    };
+    let mut table = line(header.iter().map(|title| title.to_string()).collect());
+    table.push_str(&line(
+        widths.iter().map(|width| "-".repeat(*width)).collect(),
+    ));
+    for row in rows {
+        table.push_str(&line(row));
+    }
+    table
+}
+
+// The sheet in the sections and order of the in-game character sheet, as Markdown.
+pub fn character_sheet_markdown(sheet: &CharacterSheet) -> String {
+    let mut text = format!("# {}\n\n", sheet.name);
+    text.push_str(&format!(
+        "**Race:** {} | **Gender:** {}\n",
+        sheet.race, sheet.gender
+    ));
+
+    let attributes: Vec<Vec<String>> = [
+        ("Body", sheet.body),
+        ("Agility", sheet.agility),
+        ("Reaction", sheet.reaction),
+        ("Strength", sheet.strength),
+        ("Willpower", sheet.willpower),
+        ("Logic", sheet.logic),
+        ("Intuition", sheet.intuition),
+        ("Charisma", sheet.charisma),
+        ("Edge", sheet.edge),
+        ("Magic", sheet.magic.unwrap_or(0)),
+        ("Resonance", sheet.resonance.unwrap_or(0)),
+    ]
+    .iter()
+    .map(|(name, value)| vec![name.to_string(), value.to_string()])
+    .collect();
+    text.push_str("\n## Attributes\n\n");
+    text.push_str(&table(&["Attribute", "Rating"], &attributes));
+
+    let mut derived = vec![
+        vec![
+            "Initiative".to_string(),
+            format!("{}+{}d6", sheet.initiative.0, sheet.initiative.1),
+        ],
+        vec![
+            "Limits".to_string(),
+            match sheet.limits() {
+                Some((physical, mental, social)) => {
+                    format!("PHY:{} MEN:{} SOC:{}", physical, mental, social)
+                }
+                None => "none".to_string(),
+            },
+        ],
+        vec![
+            "Monitors".to_string(),
+            format!("PHY:{} STUN:{}", sheet.physical_monitor, sheet.stun_monitor),
+        ],
+        vec!["Essence".to_string(), format!("{:.2}", sheet.essence)],
+        vec!["Edge Points".to_string(), sheet.edge_points.to_string()],
+        vec!["Armor".to_string(), sheet.armor.to_string()],
+    ];
+    if let Some(matrix) = &sheet.matrix_attributes {
+        derived.push(vec![
+            "Matrix".to_string(),
+            format!(
+                "Attack:{} Sleaze:{} Data Processing:{} Firewall:{}",
+                matrix.attack, matrix.sleaze, matrix.data_processing, matrix.firewall
+            ),
+        ]);
+    }
+    text.push_str("\n## Derived Attributes\n\n");
+    text.push_str(&table(&["", "Value"], &derived));
+
+    let skills: Vec<Vec<String>> = [
+        ("Combat", &sheet.skills.combat),
+        ("Physical", &sheet.skills.physical),
+        ("Social", &sheet.skills.social),
+        ("Technical", &sheet.skills.technical),
+        ("Knowledge", &sheet.knowledge_skills),
+    ]
+    .iter()
+    .flat_map(|(category, skills)| {
+        sorted(skills)
+            .into_iter()
+            .map(|(skill, rating)| vec![category.to_string(), skill.clone(), rating.to_string()])
+    })
+    .collect();
+    text.push_str("\n## Skills\n\n");
+    text.push_str(&table(&["Category", "Skill", "Rating"], &skills));
+
+    if !sheet.qualities.is_empty() {
+        let qualities: Vec<Vec<String>> = sheet
+            .qualities
+            .iter()
+            .map(|quality| {
+                let kind = if quality.positive {
+                    "Positive"
+                } else {
+                    "Negative"
+                };
+                vec![quality.name.clone(), kind.to_string()]
+            })
+            .collect();
+        text.push_str("\n## Qualities\n\n");
+        text.push_str(&table(&["Quality", "Type"], &qualities));
+    }
+
+    text.push_str(&format!(
+        "\n## Resources\n\n**Lifestyle:** {} | **Nuyen:** {}\n",
+        sheet.lifestyle, sheet.nuyen
+    ));
+
+    let augmentations: Vec<Vec<String>> = sheet
+        .cyberware
+        .iter()
+        .map(|item| vec![item.clone(), "Cyberware".to_string()])
+        .chain(
+            sheet
+                .bioware
+                .iter()
+                .map(|item| vec![item.clone(), "Bioware".to_string()]),
+        )
+        .collect();
+    if !augmentations.is_empty() {
+        text.push_str("\n## Augmentations\n\n");
+        text.push_str(&table(&["Augmentation", "Type"], &augmentations));
+    }
+
+    if !sheet.inventory.is_empty() {
+        let inventory: Vec<Vec<String>> = sorted(&sheet.inventory)
+            .iter()
+            .map(|(_, item)| {
+                vec![
+                    item.name.clone(),
+                    item.quantity.to_string(),
+                    item.state.to_string(),
+                    item.description.clone(),
+                ]
+            })
+            .collect();
+        text.push_str("\n## Inventory\n\n");
+        text.push_str(&table(&["Item", "Qty", "State", "Description"], &inventory));
+    }
+
+    if !sheet.contacts.is_empty() {
+        let contacts: Vec<Vec<String>> = sorted(&sheet.contacts)
+            .iter()
+            .map(|(name, contact)| {
+                vec![
+                    name.to_string(),
+                    contact.loyalty.to_string(),
+                    contact.connection.to_string(),
+                    contact.description.clone(),
+                ]
+            })
+            .collect();
+        text.push_str("\n## Contacts\n\n");
+        text.push_str(&table(
+            &["Name", "Loyalty", "Connection", "Description"],
+            &contacts,
+        ));
+    }
+
+    if !sheet.fields.is_empty() {
+        let fields: Vec<Vec<String>> = sheet
+            .fields
+            .iter()
+            .map(|(key, value)| vec![key.clone(), value.clone()])
+            .collect();
+        text.push_str("\n## Sheet\n\n");
+        text.push_str(&table(&["Entry", "Value"], &fields));
+    }
+
+    if !sheet.backstory.is_empty() {
+        text.push_str(&format!("\n## Backstory\n\n{}\n", sheet.backstory));
+    }
+    text
+}
+
+// Turn a character name into something safe to use as a file name.
+fn file_stem(name: &str) -> String {
+    let stem: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    let stem = stem.trim_matches('_').to_string();
+    if stem.is_empty() {
+        "character".to_string()
+    } else {
+        stem
+    }
+}
+
+// Write the HTML export and return its path.
+pub fn export_character_sheet_html(sheet: &CharacterSheet, save_name: &str) -> io::Result<PathBuf> {
+    let export_dir = paths::exports_dir();
+    fs::create_dir_all(&export_dir)?;
+    let path = export_dir.join(format!(
+        "{}_{}.html",
+        file_stem(save_name),
+        file_stem(&sheet.name)
+    ));
+    fs::write(&path, character_sheet_html(sheet))?;
+    Ok(path)
+}
+
+// Write the Markdown export beside the HTML one and return its path.
+pub fn export_character_sheet_markdown(
+    sheet: &CharacterSheet,
+    save_name: &str,
+) -> io::Result<PathBuf> {
+    let export_dir = paths::exports_dir();
+    fs::create_dir_all(&export_dir)?;
+    let path = export_dir.join(format!(
+        "{}_{}.md",
+        file_stem(save_name),
+        file_stem(&sheet.name)
+    ));
+    fs::write(&path, character_sheet_markdown(sheet))?;
+    Ok(path)
+}
+
+// Convert an exported HTML file to PDF with the first headless renderer found on the PATH.
+pub async fn export_pdf(html_path: &Path) -> io::Result<PathBuf> {
+    let pdf_path = html_path.with_extension("pdf");
+    let html = html_path.to_string_lossy().to_string();
+    let pdf = pdf_path.to_string_lossy().to_string();
+
+    let mut renderers: Vec<(&str, Vec<String>)> = vec![(
+        "wkhtmltopdf",
+        vec!["--quiet".to_string(), html.clone(), pdf.clone()],
+    )];
+    for browser in ["chromium", "chromium-browser", "google-chrome"] {
+        renderers.push((
+            browser,
+            vec![
+                "--headless".to_string(),
+                "--disable-gpu".to_string(),
+                "--no-pdf-header-footer".to_string(),
+                format!("--print-to-pdf={}", pdf),
+                html.clone(),
+            ],
+        ));
+    }
+
+    for (program, args) in renderers {
+        match Command::new(program).args(&args).output().await {
+            Ok(output) if output.status.success() && pdf_path.exists() => return Ok(pdf_path),
+            // Not installed or failed: try the next one.
+            _ => continue,
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        "no PDF renderer found (install wkhtmltopdf or chromium)",
+    ))
+}
+
+const STYLE: &str = r#"
+body { background: #101010; color: #e0e0e0; font-family: "DejaVu Sans Mono", monospace; max-width: 60em; margin: 2em auto; }
+h1 { color: #e0c434; text-align: center; }
+h2 { color: #e0e0e0; font-size: 1em; margin: 0 0 0.5em 0; }
+section { border: 1px solid #e0e0e0; padding: 0.5em 1em; margin: 0.5em 0; }
+.basic p { text-align: center; }
+b, th, .yellow { color: #e0c434; text-align: left; }
+.grid { display: grid; gap: 0.25em 1em; }
+.green { color: #2ee88b; }
+.cyan { color: #34d3e0; }
+.red { color: #e04343; }
+.columns { display: grid; grid-template-columns: 1fr 1fr; gap: 0 1em; }
+.note { color: #888; font-size: 0.9em; }
+table { border-collapse: collapse; width: 100%; }
+td, th { padding: 0.1em 0.5em 0.1em 0; vertical-align: top; }
+ul { margin: 0; padding-left: 1.2em; }
+@media print {
+  * { -webkit-print-color-adjust: exact; print-color-adjust: exact; }
+  body { margin: 0; }
+}
+"#;
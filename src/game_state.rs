@@ -1,6 +1,13 @@
 // Import necessary modules from the local crate and external crates.
+use crate::archivist::Archive;
 use crate::character::{CharacterSheet, CharacterSheetUpdate};
+use crate::combat::Combat;
+use crate::events::{self, GameEvent, RecordedEvent};
+use crate::image::SceneImage;
+use crate::ruleset::RulesetKind;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 
 // Define a struct to manage the state of a game session, with serialization and deserialization.
 #[derive(Serialize, Deserialize, Clone)]
@@ -10,6 +17,60 @@ pub struct GameState {
     pub main_character_sheet: Option<CharacterSheet>,
     pub save_name: String,
     pub characters: Vec<CharacterSheet>,
+    #[serde(default)]
+    pub archive: Archive, // The summary of the turns runs no longer read.
+    #[serde(default)]
+    pub portraits: BTreeMap<String, PathBuf>, // The latest portrait of each character, by name.
+    #[serde(default)]
+    pub scenes: Vec<SceneImage>, // Illustrations of the story, oldest first.
+    #[serde(default)]
+    pub ruleset: RulesetKind, // Chosen when the campaign is created; see ruleset.rs.
+    #[serde(default)]
+    pub stats: PlayStats, // Time played and turns taken, over every session.
+    #[serde(default)]
+    pub combat: Option<Combat>, // The fight going on, if any; see combat.rs.
+    #[serde(default)]
+    pub pending_run: Option<String>, // The run being answered, kept until it ends.
+    #[serde(default)]
+    pub instructions_version: Option<u32>, // Of the prompt the assistant got; see assistant.rs.
+    #[serde(default)]
+    pub events: Vec<RecordedEvent>, // Everything that happened, oldest first; see events.rs.
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PlayStats {
+    pub seconds: u64,
+    pub turns: u32, // Messages the player sent.
+}
+
+impl PlayStats {
+    // What was played between `earlier` and these totals.
+    pub fn since(&self, earlier: PlayStats) -> PlayStats {
+        PlayStats {
+            seconds: self.seconds.saturating_sub(earlier.seconds),
+            turns: self.turns.saturating_sub(earlier.turns),
+        }
+    }
+
+    // e.g. "3h 12m over 48 turns".
+    pub fn describe(&self) -> String {
+        format!(
+            "{} over {} turn{}",
+            play_time(self.seconds),
+            self.turns,
+            if self.turns == 1 { "" } else { "s" }
+        )
+    }
+}
+
+// Play time to the minute, e.g. "3h 12m" or "45m".
+pub fn play_time(seconds: u64) -> String {
+    let minutes = seconds / 60;
+    if minutes < 60 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}h {}m", minutes / 60, minutes % 60)
+    }
 }
 
 // Implement the Debug trait manually to control what information is shown when debug printed.
@@ -26,24 +87,73 @@ impl std::fmt::Debug for GameState {
 
 // Additional implementation for GameState to handle file operations.
 impl GameState {
-    // Function to load a game state from a specified JSON file.
-
-    pub fn update_character_sheet(&mut self, update: CharacterSheetUpdate) -> Result<(), String> {
-        if let Some(ref mut sheet) = self.main_character_sheet {
-            sheet.apply_update(update.clone())?;
+    // A game with nothing in it yet, for events to fill in.
+    pub fn new(save_name: &str) -> Self {
+        GameState {
+            assistant_id: String::new(),
+            thread_id: String::new(),
+            main_character_sheet: None,
+            save_name: save_name.to_string(),
+            characters: Vec::new(),
+            archive: Default::default(),
+            portraits: Default::default(),
+            scenes: Vec::new(),
+            ruleset: RulesetKind::default(),
+            stats: PlayStats::default(),
+            combat: None,
+            pending_run: None,
+            instructions_version: None,
+            events: Vec::new(),
         }
+    }
 
-        if let Some(character) = self.characters.iter_mut().find(|c| {
-            c.name
-                == self
-                    .main_character_sheet
-                    .as_ref()
-                    .map(|cs| cs.name.clone())
-                    .unwrap_or_default()
-        }) {
-            character.apply_update(update)?;
-        }
+    // Apply an event and add it to the stream. One that cannot be applied is not kept.
+    pub fn record(&mut self, event: GameEvent) -> Result<(), String> {
+        self.record_at(RecordedEvent::now(event))
+    }
 
+    pub fn record_at(&mut self, recorded: RecordedEvent) -> Result<(), String> {
+        recorded.event.apply(self)?;
+        self.events.push(recorded);
         Ok(())
     }
+
+    // The game as it stood after its first `count` events, e.g. to undo or branch from there.
+    pub fn as_of(&self, count: usize) -> GameState {
+        events::fold(
+            &self.save_name,
+            &self.events[..count.min(self.events.len())],
+        )
+    }
+
+    // Start the stream of a save from before events with how it is now.
+    pub fn seed_events(&mut self) {
+        if self.events.is_empty() {
+            let snapshot = GameEvent::Snapshot {
+                state: Box::new(self.clone()),
+            };
+            self.events.push(RecordedEvent::now(snapshot));
+        }
+    }
+
+    // The portrait filed under a character, forgiving the AI's capitalisation.
+    pub fn portrait(&self, name: &str) -> Option<&PathBuf> {
+        self.portraits.get(name).or_else(|| {
+            self.portraits
+                .iter()
+                .find(|(portrait_name, _)| portrait_name.eq_ignore_ascii_case(name))
+                .map(|(_, path)| path)
+        })
+    }
+
+    // Update the main character; the update goes to their entry in the characters too.
+    pub fn update_character_sheet(&mut self, update: CharacterSheetUpdate) -> Result<(), String> {
+        let Some(name) = self.main_character_sheet.as_ref().map(|cs| cs.name.clone()) else {
+            return Ok(());
+        };
+        self.record(GameEvent::SheetUpdate {
+            character: name,
+            update,
+        })
+    }
 }
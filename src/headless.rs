@@ -0,0 +1,188 @@
+// headless.rs
+// Run a script of player inputs through the game without a terminal, against OpenAI or a mock.
+
+use crate::ai::GameAI;
+use crate::app::{App, AppCommand};
+use crate::backend::narration;
+use crate::cli::CliArgs;
+use crate::error::ShadowrunError;
+use crate::events::GameEvent;
+use crate::game_state::GameState;
+use crate::message::{AIMessage, GameMessage, Message, MessageType};
+use crate::spectator::SpectatorEvent;
+use async_openai::types::{FunctionCall, RunToolCallObject};
+use serde::Serialize;
+use std::error::Error;
+use tokio::sync::mpsc;
+
+// One line of a headless script.
+//
+// Plain lines are player inputs. For the mock assistant, `@tool <name> <json arguments>` and
+// `@reply <text or GameMessage json>` script what it does when answering the next input.
+// Blank lines and `#` comments are skipped.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptStep {
+    Input(String),
+    Tool { name: String, arguments: String },
+    Reply(String),
+}
+
+pub fn parse_script(script: &str) -> Vec<ScriptStep> {
+    script
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            if let Some(tool) = line.strip_prefix("@tool ") {
+                let (name, arguments) = tool.trim().split_once(' ').unwrap_or((tool, "{}"));
+                ScriptStep::Tool {
+                    name: name.trim().to_string(),
+                    arguments: arguments.trim().to_string(),
+                }
+            } else if let Some(reply) = line.strip_prefix("@reply ") {
+                ScriptStep::Reply(reply.trim().to_string())
+            } else {
+                ScriptStep::Input(line.to_string())
+            }
+        })
+        .collect()
+}
+
+// What a headless run leaves behind: everything said, and the final game state.
+#[derive(Serialize)]
+pub struct HeadlessReport {
+    pub transcript: Vec<SpectatorEvent>,
+    pub game_state: Option<GameState>,
+}
+
+impl HeadlessReport {
+    fn new(messages: &[Message], game_state: Option<GameState>) -> Self {
+        HeadlessReport {
+            transcript: messages.iter().map(SpectatorEvent::from_message).collect(),
+            game_state,
+        }
+    }
+}
+
+// Play the script against the real assistant, for a game given with --load or --new.
+pub async fn run(
+    mut app: App,
+    mut command_receiver: mpsc::UnboundedReceiver<AppCommand>,
+    mut ai_receiver: mpsc::UnboundedReceiver<AIMessage>,
+    cli: &CliArgs,
+    script: &str,
+) -> Result<HeadlessReport, Box<dyn Error>> {
+    // Nobody is listening.
+    app.settings.audio_output_enabled = false;
+
+    if let Some(save_name) = &cli.load {
+        app.load_game(save_name).await?;
+    } else if let Some(save_name) = &cli.new {
+        app.start_new_game(save_name.clone(), None).await?;
+        wait_for_response(&mut app, &mut command_receiver, &mut ai_receiver).await;
+    }
+
+    for step in parse_script(script) {
+        let ScriptStep::Input(input) = step else {
+            // Tool and reply directives only make sense for the mock assistant.
+            continue;
+        };
+        app.add_message(Message::new(MessageType::User, input.clone()));
+        app.process_message(input);
+        wait_for_response(&mut app, &mut command_receiver, &mut ai_receiver).await;
+    }
+
+    let game_state = match &app.current_game {
+        Some(game_state) => Some(game_state.lock().await.clone()),
+        None => None,
+    };
+    let messages = app.game_content.borrow().clone();
+    Ok(HeadlessReport::new(&messages, game_state))
+}
+
+// Handle app commands until the AI has answered the last message.
+pub async fn wait_for_response(
+    app: &mut App,
+    command_receiver: &mut mpsc::UnboundedReceiver<AppCommand>,
+    ai_receiver: &mut mpsc::UnboundedReceiver<AIMessage>,
+) {
+    loop {
+        tokio::select! {
+            Some(command) = command_receiver.recv() => match command {
+                AppCommand::AIResponse(result) => {
+                    app.handle_ai_response(result).await;
+                    return;
+                }
+                AppCommand::SystemMessage(message) => {
+                    app.add_message(Message::new(MessageType::System, message));
+                }
+                _ => {}
+            },
+            Some(AIMessage::Debug(message)) = ai_receiver.recv() => {
+                app.add_debug_message(message);
+            }
+            else => return,
+        }
+    }
+}
+
+// Play the script offline: tool calls run through the real handlers, replies come from the script.
+pub async fn run_mock(script: &str, save_name: &str) -> Result<HeadlessReport, ShadowrunError> {
+    // The client is never used, so no key is needed.
+    let mut ai = GameAI::new(String::new(), |_| {}).await?;
+    let mut game_state = GameState::new(save_name);
+    game_state.record(GameEvent::Thread {
+        assistant_id: "mock".to_string(),
+        thread_id: "mock".to_string(),
+    })?;
+    let mut messages = Vec::new();
+    let mut tools = Vec::new();
+    let mut reply = None;
+
+    for step in parse_script(script) {
+        match step {
+            ScriptStep::Tool { name, arguments } => tools.push((name, arguments)),
+            ScriptStep::Reply(text) => reply = Some(text),
+            ScriptStep::Input(input) => {
+                messages.push(Message::new(MessageType::User, input.clone()));
+                for (index, (name, arguments)) in tools.drain(..).enumerate() {
+                    let tool_call = RunToolCallObject {
+                        id: format!("call_mock_{}", index),
+                        r#type: "function".to_string(),
+                        function: FunctionCall {
+                            name: name.clone(),
+                            arguments,
+                        },
+                    };
+                    let output = match ai.handle_tool_call(&tool_call, &mut game_state).await {
+                        Ok(output) => output,
+                        Err(e) => format!("error: {}", e),
+                    };
+                    messages.push(Message::new(
+                        MessageType::System,
+                        format!("{}: {}", name, output),
+                    ));
+                }
+
+                let response = mock_response(reply.take(), &input)?;
+                let game_message = ai.update_game_state(&mut game_state, &response).await?;
+                messages.push(Message::new(
+                    MessageType::Game,
+                    serde_json::to_string(&game_message)?,
+                ));
+            }
+        }
+    }
+
+    Ok(HeadlessReport::new(&messages, Some(game_state)))
+}
+
+// A scripted reply is used as is when it is a GameMessage, otherwise narrated as plain text.
+fn mock_response(reply: Option<String>, input: &str) -> Result<String, ShadowrunError> {
+    let text = match reply {
+        Some(reply) if serde_json::from_str::<GameMessage>(&reply).is_ok() => return Ok(reply),
+        Some(reply) => reply,
+        None => format!("[mock] {}", input),
+    };
+    Ok(serde_json::to_string(&narration(text))?)
+}
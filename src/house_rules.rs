@@ -0,0 +1,187 @@
+// house_rules.rs
+// A campaign's own rules on top of its ruleset, so a table can play a house rule without changing
+// the game. `<save>.json` in the rules folder of the data folder gives them, every part optional:
+//
+// {"dice": {"pool": 1, "limits": false},
+//  "derived": {"initiative": 0, "initiative_dice": 1, "physical_monitor": 2, "stun_monitor": 0,
+//              "armor": 0},
+//  "sheet": {"max_attribute": 9, "max_skill": 12}}
+//
+// `dice` changes the pool of every test, and without limits hits are never capped. `derived` is
+// added to what the ruleset works out. `sheet` caps the attributes and skills an update can give.
+// Sheets are derived again as a save is replayed, so a change to `derived` shows on the next load.
+
+use crate::character::CharacterSheet;
+use crate::paths;
+use crate::ruleset::{RulesetKind, MAX_INITIATIVE_DICE};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct HouseRules {
+    pub dice: DiceRules,
+    pub derived: DerivedRules,
+    pub sheet: SheetRules,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct DiceRules {
+    // Dice added to every test, or taken from it when negative.
+    pub pool: i8,
+    // Whether the ruleset's limits cap the hits.
+    pub limits: bool,
+}
+
+impl Default for DiceRules {
+    fn default() -> Self {
+        DiceRules {
+            pool: 0,
+            limits: true,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct DerivedRules {
+    pub initiative: i8,
+    pub initiative_dice: i8,
+    pub physical_monitor: i8,
+    pub stun_monitor: i8,
+    pub armor: i8,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct SheetRules {
+    pub max_attribute: Option<u8>,
+    pub max_skill: Option<u8>,
+}
+
+pub fn path_for(save_name: &str) -> PathBuf {
+    paths::rules_dir().join(format!("{}.json", save_name))
+}
+
+impl HouseRules {
+    // The house rules of `save_name`, or none if it has no file. A file that can't be read is
+    // logged and played without.
+    pub fn load(save_name: &str) -> Self {
+        let path = path_for(save_name);
+        let Ok(text) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        serde_json::from_str(&text).unwrap_or_else(|e| {
+            log::warn!("Ignoring the house rules in {}: {}", path.display(), e);
+            Self::default()
+        })
+    }
+
+    // The pool and limit of a test, from those of the ruleset.
+    pub fn test(&self, pool: i16, limit: Option<u8>) -> (i16, Option<u8>) {
+        (
+            pool + self.dice.pool as i16,
+            limit.filter(|_| self.dice.limits),
+        )
+    }
+
+    // Bring the attributes and skills of an updated sheet down to the caps.
+    pub fn cap(&self, sheet: &mut CharacterSheet) {
+        if let Some(max) = self.sheet.max_attribute {
+            for attribute in [
+                &mut sheet.body,
+                &mut sheet.agility,
+                &mut sheet.reaction,
+                &mut sheet.strength,
+                &mut sheet.willpower,
+                &mut sheet.logic,
+                &mut sheet.intuition,
+                &mut sheet.charisma,
+                &mut sheet.edge,
+            ]
+            .into_iter()
+            .chain(sheet.magic.as_mut())
+            .chain(sheet.resonance.as_mut())
+            {
+                *attribute = (*attribute).min(max);
+            }
+        }
+        if let Some(max) = self.sheet.max_skill {
+            let skills = &mut sheet.skills;
+            for rating in [
+                &mut skills.combat,
+                &mut skills.physical,
+                &mut skills.social,
+                &mut skills.technical,
+            ]
+            .into_iter()
+            .flat_map(|group| group.values_mut())
+            {
+                *rating = (*rating).min(max);
+            }
+        }
+    }
+
+    // Add to the attributes the ruleset derived.
+    pub fn derive(&self, sheet: &mut CharacterSheet) {
+        let derived = &self.derived;
+        let (score, dice) = sheet.initiative;
+        sheet.initiative = (
+            score.saturating_add_signed(derived.initiative),
+            dice.saturating_add_signed(derived.initiative_dice)
+                .min(MAX_INITIATIVE_DICE),
+        );
+        sheet.physical_monitor = sheet
+            .physical_monitor
+            .saturating_add_signed(derived.physical_monitor);
+        sheet.stun_monitor = sheet
+            .stun_monitor
+            .saturating_add_signed(derived.stun_monitor);
+        sheet.armor = sheet.armor.saturating_add_signed(derived.armor);
+    }
+}
+
+// House rules as read, with when their file was written, so they are read again when it changes.
+type Loaded = (Option<SystemTime>, Arc<HouseRules>);
+
+static LOADED: Lazy<Mutex<HashMap<String, Loaded>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+// The house rules of `save_name` as its file is now.
+pub fn for_save(save_name: &str) -> Arc<HouseRules> {
+    let modified = fs::metadata(path_for(save_name))
+        .and_then(|metadata| metadata.modified())
+        .ok();
+    let mut loaded = LOADED
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    match loaded.get(save_name) {
+        Some((at, rules)) if *at == modified => rules.clone(),
+        _ => {
+            let rules = Arc::new(HouseRules::load(save_name));
+            loaded.insert(save_name.to_string(), (modified, rules.clone()));
+            rules
+        }
+    }
+}
+
+// Work out a sheet's derived attributes by the campaign's ruleset, then its house rules. A sheet
+// without Shadowrun attributes has none to add to.
+pub fn derive(ruleset: RulesetKind, save_name: &str, sheet: &mut CharacterSheet) {
+    let rules = ruleset.rules();
+    rules.derive(sheet);
+    if rules.shadowrun_sheet() {
+        for_save(save_name).derive(sheet);
+    }
+}
+
+// A sheet after an update, as the campaign keeps it: held to the caps, then derived.
+pub fn settle(ruleset: RulesetKind, save_name: &str, sheet: &mut CharacterSheet) {
+    for_save(save_name).cap(sheet);
+    derive(ruleset, save_name, sheet);
+}
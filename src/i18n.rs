@@ -0,0 +1,557 @@
+// i18n.rs
+// Translations of the menu labels, warnings and hints, following the Language setting.
+//
+// The English text is the key: each row gives its French, Japanese and Turkish versions. Text
+// without a row is shown in English, as the tests check every text the screens translate has one.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lang {
+    English,
+    French,
+    Japanese,
+    Turkish,
+}
+
+// The Language options, as stored in settings.json and sent to the Game Master.
+pub const LANGUAGES: &[(&str, Lang)] = &[
+    ("English", Lang::English),
+    ("Français", Lang::French),
+    ("日本語", Lang::Japanese),
+    ("Türkçe", Lang::Turkish),
+];
+
+impl Lang {
+    pub fn from_name(name: &str) -> Self {
+        LANGUAGES
+            .iter()
+            .find(|(language, _)| *language == name)
+            .map_or(Lang::English, |(_, lang)| *lang)
+    }
+
+    // The ISO-639-1 code, as speech recognition expects it.
+    pub fn code(self) -> &'static str {
+        match self {
+            Lang::English => "en",
+            Lang::French => "fr",
+            Lang::Japanese => "ja",
+            Lang::Turkish => "tr",
+        }
+    }
+}
+
+// English, then French, Japanese and Turkish.
+const STRINGS: &[(&str, [&str; 3])] = &[
+    // Shared
+    (
+        "Please resize the terminal to at least {}×{} (current: {}×{}).",
+        [
+            "Agrandissez le terminal à au moins {}×{} (actuellement : {}×{}).",
+            "ターミナルを{}×{}以上にしてください（現在: {}×{}）。",
+            "Lütfen terminali en az {}×{} yapın (şu an: {}×{}).",
+        ],
+    ),
+    (
+        "Press Esc to go back",
+        [
+            "Échap pour revenir",
+            "Escで戻る",
+            "Geri dönmek için Esc'ye basın",
+        ],
+    ),
+    // Main menu
+    (
+        "Continue '{}'",
+        ["Continuer '{}'", "「{}」を続ける", "'{}' ile devam et"],
+    ),
+    (
+        "Start a new game",
+        [
+            "Nouvelle partie",
+            "新しいゲームを始める",
+            "Yeni oyun başlat",
+        ],
+    ),
+    (
+        "Load a game",
+        ["Charger une partie", "ゲームをロード", "Oyun yükle"],
+    ),
+    (
+        "Create an image",
+        ["Créer une image", "画像を作成", "Görsel oluştur"],
+    ),
+    ("Settings", ["Paramètres", "設定", "Ayarlar"]),
+    ("Main menu", ["Menu principal", "メインメニュー", "Ana menü"]),
+    (
+        "Choose an archetype",
+        ["Choisir un archétype", "アーキタイプを選択", "Bir arketip seçin"],
+    ),
+    (
+        "Press q to quit",
+        ["q pour quitter", "qで終了", "Çıkmak için q'ya basın"],
+    ),
+    (
+        "'{}' was interrupted at {}: press r to recover it, d to discard",
+        [
+            "'{}' a été interrompue le {} : r pour la récupérer, d pour l'abandonner",
+            "「{}」は{}に中断されました：rで復元、dで破棄",
+            "'{}' {} tarihinde yarıda kaldı: kurtarmak için r, silmek için d",
+        ],
+    ),
+    // Load menu
+    (
+        "Press Enter or number to load save, Backspace twice to delete save, r for backups, n to rename, d to duplicate, / to filter, s to sort, Esc to go back",
+        [
+            "Entrée ou numéro pour charger, Retour arrière deux fois pour supprimer, r pour les copies, n pour renommer, d pour dupliquer, / pour filtrer, s pour trier, Échap pour revenir",
+            "Enterまたは番号でロード、Backspaceを2回で削除、rでバックアップ、nで名前を変更、dで複製、/で絞り込み、sで並べ替え、Escで戻る",
+            "Yüklemek için Enter veya numara, silmek için iki kez Backspace, yedekler için r, yeniden adlandırmak için n, çoğaltmak için d, süzmek için /, sıralamak için s, geri dönmek için Esc",
+        ],
+    ),
+    (
+        "No save files found in {}",
+        [
+            "Aucune sauvegarde trouvée dans {}",
+            "{}にセーブファイルがありません",
+            "{} içinde kayıt dosyası bulunamadı",
+        ],
+    ),
+    (
+        "Select a save file to load",
+        [
+            "Choisissez une sauvegarde à charger",
+            "ロードするセーブファイルを選択",
+            "Yüklenecek kayıt dosyasını seçin",
+        ],
+    ),
+    (
+        "No save files available",
+        [
+            "Aucune sauvegarde disponible",
+            "セーブファイルがありません",
+            "Kayıt dosyası yok",
+        ],
+    ),
+    (
+        " (Backspace again to delete)",
+        [
+            " (Retour arrière encore pour supprimer)",
+            "（もう一度Backspaceで削除）",
+            " (silmek için tekrar Backspace)",
+        ],
+    ),
+    ("Damaged save", ["Sauvegarde endommagée", "破損したセーブ", "Hasarlı kayıt"]),
+    (
+        "'{}' could not be loaded: {}.",
+        [
+            "'{}' n'a pas pu être chargée : {}.",
+            "「{}」をロードできませんでした：{}。",
+            "'{}' yüklenemedi: {}.",
+        ],
+    ),
+    (
+        "b  Restore a backup",
+        [
+            "b  Restaurer une copie",
+            "b  バックアップを復元",
+            "b  Bir yedeği geri yükle",
+        ],
+    ),
+    (
+        "l  Load it as it is",
+        [
+            "l  La charger telle quelle",
+            "l  このままロード",
+            "l  Olduğu gibi yükle",
+        ],
+    ),
+    (
+        "Backups of '{}'",
+        [
+            "Copies de '{}'",
+            "「{}」のバックアップ",
+            "'{}' yedekleri",
+        ],
+    ),
+    (
+        "Up/Down to choose, Enter to restore, Esc to go back",
+        [
+            "Haut/Bas pour choisir, Entrée pour restaurer, Échap pour revenir",
+            "上下で選択、Enterで復元、Escで戻る",
+            "Seçmek için Yukarı/Aşağı, geri yüklemek için Enter, geri dönmek için Esc",
+        ],
+    ),
+    (
+        "There is no backup to restore.",
+        [
+            "Aucune copie à restaurer.",
+            "復元できるバックアップがありません。",
+            "Geri yüklenecek yedek yok.",
+        ],
+    ),
+    ("Esc  Cancel", ["Échap  Annuler", "Esc  キャンセル", "Esc  İptal"]),
+    (
+        "Rename '{}'",
+        ["Renommer '{}'", "「{}」の名前を変更", "'{}' adını değiştir"],
+    ),
+    (
+        "[x] Rename the assistant too",
+        ["[x] Renommer aussi l'assistant", "[x] アシスタントの名前も変更", "[x] Asistanın adını da değiştir"],
+    ),
+    (
+        "[ ] Rename the assistant too",
+        ["[ ] Renommer aussi l'assistant", "[ ] アシスタントの名前も変更", "[ ] Asistanın adını da değiştir"],
+    ),
+    (
+        "Enter to rename, Tab for the assistant, Esc to go back",
+        [
+            "Entrée pour renommer, Tab pour l'assistant, Échap pour revenir",
+            "Enterで名前を変更、Tabでアシスタント、Escで戻る",
+            "Yeniden adlandırmak için Enter, asistan için Tab, geri dönmek için Esc",
+        ],
+    ),
+    // Archetype picker
+    (
+        "Up/Down to choose, Enter to start, Esc to go back",
+        [
+            "Haut/Bas pour choisir, Entrée pour commencer, Échap pour revenir",
+            "上下で選択、Enterで開始、Escで戻る",
+            "Seçmek için Yukarı/Aşağı, başlamak için Enter, geri dönmek için Esc",
+        ],
+    ),
+    // Game
+    ("Last turn", ["Dernier tour", "前のターン", "Son tur"]),
+    (
+        "Sheet updates to review",
+        [
+            "Changements de fiche à valider",
+            "確認待ちのシート更新",
+            "Onay bekleyen sayfa güncellemeleri",
+        ],
+    ),
+    (
+        "y accept   n reject   Y accept all   N reject all",
+        [
+            "y accepter   n refuser   Y tout accepter   N tout refuser",
+            "y 承認   n 却下   Y すべて承認   N すべて却下",
+            "y kabul   n reddet   Y tümünü kabul   N tümünü reddet",
+        ],
+    ),
+    (
+        "Loading older messages...",
+        [
+            "Chargement des messages précédents...",
+            "過去のメッセージを読み込み中...",
+            "Eski mesajlar yükleniyor...",
+        ],
+    ),
+    (
+        "Scroll up for older messages",
+        [
+            "Remontez pour les messages précédents",
+            "上にスクロールで過去のメッセージ",
+            "Eski mesajlar için yukarı kaydırın",
+        ],
+    ),
+    // Settings menu
+    ("Language", ["Langue", "言語", "Dil"]),
+    (
+        "OpenAI API Key",
+        ["Clé API OpenAI", "OpenAI APIキー", "OpenAI API Anahtarı"],
+    ),
+    (
+        "Voice Output",
+        ["Sortie vocale", "音声出力", "Sesli Çıkış"],
+    ),
+    ("Voice Input", ["Entrée vocale", "音声入力", "Sesli Giriş"]),
+    (
+        "Debug Mode",
+        ["Mode débogage", "デバッグモード", "Hata Ayıklama"],
+    ),
+    ("Spectator", ["Spectateur", "観戦", "İzleyici"]),
+    (
+        "Share Sheet",
+        ["Partager la fiche", "シート共有", "Sayfayı Paylaş"],
+    ),
+    (
+        "Discord Webhook",
+        ["Webhook Discord", "Discord Webhook", "Discord Webhook"],
+    ),
+    ("Updates", ["Mises à jour", "アップデート", "Güncellemeler"]),
+    (
+        "Log Level",
+        ["Niveau de log", "ログレベル", "Günlük Düzeyi"],
+    ),
+    (
+        "Accessibility",
+        ["Accessibilité", "アクセシビリティ", "Erişilebilirlik"],
+    ),
+    (
+        "Mirror Output",
+        ["Copie du texte", "テキスト出力", "Metin Kopyası"],
+    ),
+    (
+        "Save Compression",
+        ["Compression des sauvegardes", "セーブ圧縮", "Kayıt Sıkıştırma"],
+    ),
+    ("File", ["Fichier", "ファイル", "Dosya"]),
+    ("Theme", ["Thème", "テーマ", "Tema"]),
+    ("Default", ["Par défaut", "標準", "Varsayılan"]),
+    ("Colorblind", ["Daltonien", "色覚対応", "Renk Körü"]),
+    (
+        "High Contrast",
+        ["Contraste élevé", "ハイコントラスト", "Yüksek Kontrast"],
+    ),
+    ("Backups", ["Sauvegardes", "バックアップ", "Yedekler"]),
+    (
+        "Usage Metrics",
+        [
+            "Statistiques d'usage",
+            "利用統計",
+            "Kullanım İstatistikleri",
+        ],
+    ),
+    (
+        "Message Times",
+        [
+            "Heure des messages",
+            "メッセージの時刻",
+            "Mesaj Saatleri",
+        ],
+    ),
+    (
+        "Confirm Updates",
+        [
+            "Confirmer les changements",
+            "更新の確認",
+            "Güncelleme Onayı",
+        ],
+    ),
+    ("Scenes", ["Scènes", "シーン", "Sahneler"]),
+    (
+        "Voice Model",
+        ["Modèle vocal", "音声認識モデル", "Ses Modeli"],
+    ),
+    (
+        "Voice Language",
+        ["Langue vocale", "音声の言語", "Ses Dili"],
+    ),
+    ("Auto", ["Auto", "自動", "Otomatik"]),
+    (
+        "Speech Model",
+        ["Modèle de synthèse", "音声合成モデル", "Konuşma Modeli"],
+    ),
+    (
+        "Speech Speed",
+        ["Vitesse de lecture", "読み上げ速度", "Konuşma Hızı"],
+    ),
+    (
+        "Dice Animation",
+        ["Animation des dés", "ダイスアニメーション", "Zar Animasyonu"],
+    ),
+    ("Menu Art", ["Illustration du menu", "メニューのアート", "Menü Çizimi"]),
+    (
+        "Terminal Copy",
+        ["Copie par le terminal", "端末経由のコピー", "Terminal ile Kopyalama"],
+    ),
+    ("Always", ["Toujours", "常に", "Her Zaman"]),
+    (
+        "Frame Rate",
+        ["Images par seconde", "フレームレート", "Kare Hızı"],
+    ),
+    (
+        "Tick Rate",
+        ["Mises à jour par seconde", "更新頻度", "Güncelleme Hızı"],
+    ),
+    (
+        "Speech Backend",
+        ["Service vocal", "音声サービス", "Ses Hizmeti"],
+    ),
+    (
+        "ElevenLabs API Key",
+        ["Clé API ElevenLabs", "ElevenLabs APIキー", "ElevenLabs API Anahtarı"],
+    ),
+    (
+        "Input Mode",
+        ["Mode de saisie", "入力モード", "Giriş Modu"],
+    ),
+    ("Simple", ["Simple", "シンプル", "Basit"]),
+    (
+        "Submit Key",
+        ["Touche d'envoi", "送信キー", "Gönderme Tuşu"],
+    ),
+    (
+        "Enter While Typing",
+        ["Entrée pendant la saisie", "入力中のEnter", "Yazarken Enter"],
+    ),
+    ("New Line", ["Nouvelle ligne", "改行", "Yeni Satır"]),
+    (
+        "Mouse Selection",
+        ["Sélection à la souris", "マウスで選択", "Fareyle Seçim"],
+    ),
+    ("[Set]", ["[Définie]", "[設定済み]", "[Ayarlı]"]),
+    (
+        "Data Folder",
+        ["Dossier des données", "データフォルダー", "Veri Klasörü"],
+    ),
+    ("[Default]", ["[Par défaut]", "[標準]", "[Varsayılan]"]),
+    (
+        " [Used after a restart]",
+        [
+            " [Utilisé après un redémarrage]",
+            " [再起動後に使用]",
+            " [Yeniden başlatınca kullanılır]",
+        ],
+    ),
+    (
+        "[No key, press Enter]",
+        [
+            "[Pas de clé, appuyez sur Entrée]",
+            "[キーなし、Enterを押す]",
+            "[Anahtar yok, Enter'a basın]",
+        ],
+    ),
+    (
+        "Player Voice",
+        ["Voix du joueur", "プレイヤーの声", "Oyuncu Sesi"],
+    ),
+    ("Rolling…", ["Lancer…", "ロール中…", "Atılıyor…"]),
+    ("Ruleset", ["Règles", "ルールセット", "Kural Seti"]),
+    (
+        "Narrator Model",
+        ["Modèle du narrateur", "ナレーターのモデル", "Anlatıcı Modeli"],
+    ),
+    (
+        "Archivist Model",
+        ["Modèle de l'archiviste", "記録係のモデル", "Arşivci Modeli"],
+    ),
+    ("Gear Shop", ["Armurerie", "ギアショップ", "Ekipman Dükkanı"]),
+    ("Round", ["Tour", "ラウンド", "Tur"]),
+    (
+        "Campaign Statistics",
+        ["Statistiques de campagne", "キャンペーン統計", "Kampanya İstatistikleri"],
+    ),
+    (
+        "{} played, {} turns",
+        ["{} de jeu, {} tours", "プレイ時間 {}、{} ターン", "{} oynandı, {} tur"],
+    ),
+    (
+        "Last played {}",
+        ["Dernière partie le {}", "最終プレイ {}", "Son oynanma {}"],
+    ),
+    (
+        "Image: {} (p to open)",
+        ["Image : {} (p pour l'ouvrir)", "画像: {}（pで開く）", "Görsel: {} (açmak için p)"],
+    ),
+    ("No story yet.", ["Pas encore d'histoire.", "まだ物語はありません。", "Henüz hikaye yok."]),
+    (
+        "This save could not be read.",
+        ["Cette sauvegarde est illisible.", "このセーブは読み込めません。", "Bu kayıt okunamadı."],
+    ),
+    ("Preview", ["Aperçu", "プレビュー", "Önizleme"]),
+    ("Saves", ["Sauvegardes", "セーブ", "Kayıtlar"]),
+    ("by name", ["par nom", "名前順", "ada göre"]),
+    ("by last played", ["par dernière partie", "最近遊んだ順", "son oynanana göre"]),
+    ("by play time", ["par temps de jeu", "プレイ時間順", "oynama süresine göre"]),
+    (
+        "No save matches the filter.",
+        [
+            "Aucune sauvegarde ne correspond au filtre.",
+            "絞り込みに合うセーブがありません。",
+            "Süzgece uyan kayıt yok.",
+        ],
+    ),
+    (
+        "Input Device",
+        ["Micro", "入力デバイス", "Giriş Aygıtı"],
+    ),
+    (
+        "Output Device",
+        ["Sortie audio", "出力デバイス", "Çıkış Aygıtı"],
+    ),
+    (
+        "System Default",
+        ["Par défaut du système", "システム既定", "Sistem Varsayılanı"],
+    ),
+    (
+        "Narrator Voice",
+        ["Voix du narrateur", "ナレーターの声", "Anlatıcı Sesi"],
+    ),
+    (
+        "Game Language",
+        ["Langue du jeu", "ゲームの言語", "Oyun Dili"],
+    ),
+    (
+        "No scenes yet. Try /scene <prompt>.",
+        [
+            "Aucune scène pour l'instant. Essayez /scene <description>.",
+            "シーンはまだありません。/scene <説明> を試してください。",
+            "Henüz sahne yok. /scene <açıklama> deneyin.",
+        ],
+    ),
+    ("Logs", ["Journaux", "ログ", "Günlükler"]),
+    ("Macros", ["Macros", "マクロ", "Makrolar"]),
+    ("On", ["Activé", "オン", "Açık"]),
+    ("Off", ["Désactivé", "オフ", "Kapalı"]),
+    ("Local", ["Local", "ローカル", "Yerel"]),
+    ("Check Only", ["Vérifier", "確認のみ", "Yalnızca Kontrol"]),
+    ("Auto", ["Auto", "自動", "Otomatik"]),
+    ("[Valid]", ["[Valide]", "[有効]", "[Geçerli]"]),
+    ("[Not Valid]", ["[Non valide]", "[無効]", "[Geçersiz]"]),
+    (
+        "[{} defined, Enter to manage]",
+        [
+            "[{} définies, Entrée pour gérer]",
+            "[{}件、Enterで管理]",
+            "[{} tanımlı, yönetmek için Enter]",
+        ],
+    ),
+    (
+        "[Enter, or F12 anywhere]",
+        [
+            "[Entrée, ou F12 partout]",
+            "[Enter、またはどこでもF12]",
+            "[Enter veya her yerde F12]",
+        ],
+    ),
+    (
+        "[No URL, press Enter]",
+        [
+            "[Pas d'URL, appuyez sur Entrée]",
+            "[URLなし、Enterを押す]",
+            "[URL yok, Enter'a basın]",
+        ],
+    ),
+    (
+        "[Failed]",
+        ["[Échec]", "[失敗]", "[Başarısız]"],
+    ),
+];
+
+// The text in the given language, or the English text if it has no translation.
+pub fn tr(language: &str, text: &'static str) -> &'static str {
+    let column = match Lang::from_name(language) {
+        Lang::English => return text,
+        Lang::French => 0,
+        Lang::Japanese => 1,
+        Lang::Turkish => 2,
+    };
+    STRINGS
+        .iter()
+        .find(|(english, _)| *english == text)
+        .map_or(text, |(_, translations)| translations[column])
+}
+
+// Whether `text` has a row of translations.
+pub fn is_translated(text: &str) -> bool {
+    STRINGS.iter().any(|(english, _)| *english == text)
+}
+
+// Translate a text with "{}" placeholders and fill them in order.
+pub fn trf(language: &str, text: &'static str, args: &[&str]) -> String {
+    let mut parts = tr(language, text).split("{}");
+    let mut filled = parts.next().unwrap_or_default().to_string();
+    for (part, arg) in parts.zip(args.iter().chain(std::iter::repeat(&""))) {
+        filled.push_str(arg);
+        filled.push_str(part);
+    }
+    filled
+}
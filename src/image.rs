@@ -1,14 +1,50 @@
+use crate::paths;
 use crate::settings::Settings;
 use async_openai::{
     config::OpenAIConfig,
     types::{CreateImageRequestArgs, ImageModel, ImageResponseFormat, ImageSize},
     Client,
 };
+use chrono::Local;
+use serde::{Deserialize, Serialize};
 use std::error::Error;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use tokio::time::{timeout, Duration};
 
-pub async fn generate_and_save_image(prompt: &str) -> Result<(), Box<dyn Error>> {
+// An illustration of a place or moment of the story, kept in the save's gallery.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SceneImage {
+    pub title: String,
+    pub path: PathBuf,
+    pub created_at: i64, // Unix seconds.
+}
+
+// An image finished in the background, waiting to be filed in the game state.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeneratedImage {
+    Portrait { name: String, path: PathBuf },
+    Scene(SceneImage),
+}
+
+// Generate a portrait-sized image and open it in the system viewer.
+pub async fn generate_and_save_image(prompt: &str) -> Result<PathBuf, Box<dyn Error>> {
+    let path = generate_image(prompt, ImageSize::S1024x1792).await?;
+    open_image(&path)?;
+    Ok(path)
+}
+
+// Generate a landscape illustration of a scene; it is shown in the game, not opened.
+pub async fn generate_scene_image(title: &str, prompt: &str) -> Result<SceneImage, Box<dyn Error>> {
+    let path = generate_image(prompt, ImageSize::S1792x1024).await?;
+    Ok(SceneImage {
+        title: title.to_string(),
+        path,
+        created_at: Local::now().timestamp(),
+    })
+}
+
+async fn generate_image(prompt: &str, size: ImageSize) -> Result<PathBuf, Box<dyn Error>> {
     let settings = Settings::load()?;
     let api_key = match settings.openai_api_key {
         Some(key) => key,
@@ -22,7 +58,7 @@ pub async fn generate_and_save_image(prompt: &str) -> Result<(), Box<dyn Error>>
         .model(ImageModel::DallE3)
         .n(1)
         .response_format(ImageResponseFormat::Url)
-        .size(ImageSize::S1024x1792)
+        .size(size)
         .build()?;
 
     let response = match timeout(Duration::from_secs(120), client.images().create(request)).await {
@@ -34,25 +70,28 @@ pub async fn generate_and_save_image(prompt: &str) -> Result<(), Box<dyn Error>>
         return Err("No image URLs received.".into());
     }
 
-    let paths = response.save("./data").await?;
-    if let Some(path) = paths.first() {
-        // Convert the path to a string
-        let path_str = path.to_str().ok_or("Invalid path")?;
+    let paths = response.save(paths::images_dir()).await?;
+    paths
+        .first()
+        .cloned()
+        .ok_or_else(|| "No image file path received.".into())
+}
 
-        // Open the image using the default image viewer based on the OS
-        #[cfg(target_os = "macos")]
-        Command::new("open").arg(path_str).spawn()?;
+// Open the image using the default image viewer based on the OS.
+pub fn open_image(path: &Path) -> Result<(), Box<dyn Error>> {
+    // Convert the path to a string
+    let path_str = path.to_str().ok_or("Invalid path")?;
 
-        #[cfg(target_os = "windows")]
-        Command::new("cmd")
-            .args(&["/C", "start", "", path_str])
-            .spawn()?;
+    #[cfg(target_os = "macos")]
+    Command::new("open").arg(path_str).spawn()?;
 
-        #[cfg(target_os = "linux")]
-        Command::new("xdg-open").arg(path_str).spawn()?;
+    #[cfg(target_os = "windows")]
+    Command::new("cmd")
+        .args(&["/C", "start", "", path_str])
+        .spawn()?;
 
-        Ok(())
-    } else {
-        Err("No image file path received.".into())
-    }
+    #[cfg(target_os = "linux")]
+    Command::new("xdg-open").arg(path_str).spawn()?;
+
+    Ok(())
 }
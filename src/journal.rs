@@ -0,0 +1,598 @@
+// journal.rs
+// Append-only record of what changed in a save, so an update writes a line instead of the whole game.
+//
+// A save is `<name>.json`, a full snapshot, followed by `<name>.journal`, one change per line;
+// mostly the game's new events, see events.rs. Loading replays the journal over the snapshot. Every COMPACT_AFTER changes the journal is folded
+// into a new snapshot, which replaces the old one by rename so it is never left half written.
+// Snapshots are gzipped when Save Compression is on; loading tells them apart by their first bytes,
+// so plain ones, which can be edited by hand, still load.
+//
+// Each snapshot has a checksum next to it, and the versions it replaced are kept, timestamped, in
+// `backups/<name>/`, so a damaged save is caught on load and can fall back to one of them.
+
+use crate::archivist::Archive;
+use crate::character::CharacterSheet;
+use crate::events::{self, RecordedEvent};
+use crate::game_state::GameState;
+use crate::image::SceneImage;
+use chrono::Local;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use thiserror::Error;
+
+pub const COMPACT_AFTER: usize = 50;
+const SUFFIX: &str = ".journal";
+// Every gzip stream starts with these, and no JSON document can.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+static COMPRESS: AtomicBool = AtomicBool::new(false);
+
+// Whether new snapshots are gzipped. Saves already written load either way.
+pub fn set_compression(on: bool) {
+    COMPRESS.store(on, Ordering::Relaxed);
+}
+
+// Bumped by every write to any save, so a copy of a game can tell it fell behind.
+static REVISION: AtomicUsize = AtomicUsize::new(0);
+
+pub fn revision() -> usize {
+    REVISION.load(Ordering::Relaxed)
+}
+
+static BACKUP_COUNT: AtomicUsize = AtomicUsize::new(3);
+
+// How many previous versions of each save to keep; 0 keeps none.
+pub fn set_backup_count(count: usize) {
+    BACKUP_COUNT.store(count, Ordering::Relaxed);
+}
+
+#[derive(Debug, Error)]
+pub enum SaveError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    // `readable` is whether the snapshot still parses, so it can be loaded as it is.
+    #[error("'{save_name}' is damaged: {reason}")]
+    Corrupt {
+        save_name: String,
+        reason: String,
+        readable: bool,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum Change {
+    Thread {
+        assistant_id: String,
+        thread_id: String,
+    },
+    MainCharacter {
+        sheet: Option<CharacterSheet>,
+    },
+    // A character added, or replaced by name.
+    Character {
+        sheet: CharacterSheet,
+    },
+    // The whole list, when characters were removed or renamed.
+    Characters {
+        characters: Vec<CharacterSheet>,
+    },
+    Archive {
+        archive: Archive,
+    },
+    Portraits {
+        portraits: BTreeMap<String, PathBuf>,
+    },
+    Scenes {
+        scenes: Vec<SceneImage>,
+    },
+    Event {
+        event: RecordedEvent,
+    },
+    // The whole stream, when it was cut short rather than added to.
+    Events {
+        events: Vec<RecordedEvent>,
+    },
+    // The first line of a journal: the checksum of the snapshot it goes on top of.
+    Base {
+        checksum: String,
+    },
+}
+
+impl Change {
+    fn apply(self, state: &mut GameState) {
+        match self {
+            Change::Thread {
+                assistant_id,
+                thread_id,
+            } => {
+                state.assistant_id = assistant_id;
+                state.thread_id = thread_id;
+            }
+            Change::MainCharacter { sheet } => state.main_character_sheet = sheet,
+            Change::Character { sheet } => {
+                match state.characters.iter_mut().find(|c| c.name == sheet.name) {
+                    Some(character) => *character = sheet,
+                    None => state.characters.push(sheet),
+                }
+            }
+            Change::Characters { characters } => state.characters = characters,
+            Change::Archive { archive } => state.archive = archive,
+            Change::Portraits { portraits } => state.portraits = portraits,
+            Change::Scenes { scenes } => state.scenes = scenes,
+            Change::Event { event } => {
+                if let Err(e) = state.record_at(event) {
+                    log::warn!("Skipped an event of '{}': {}", state.save_name, e);
+                }
+            }
+            Change::Events { events } => {
+                *state = events::fold(&state.save_name, &events);
+            }
+            Change::Base { .. } => {}
+        }
+    }
+}
+
+// Sheets are compared by their saved form, which is all the journal cares about.
+fn same<T: Serialize + ?Sized>(a: &T, b: &T) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+// The changes that turn `old` into `new`: its new events, then whatever they do not account for.
+pub fn diff(old: &GameState, new: &GameState) -> Vec<Change> {
+    let mut changes = Vec::new();
+    // Events are only added at the end or cut off, so the stream was added to if it is no shorter
+    // and still has the last old event where it was. Comparing it alone keeps a record cheap.
+    let extended = new.events.len() >= old.events.len()
+        && old.events.last().is_none_or(|last| {
+            let kept = &new.events[old.events.len() - 1];
+            kept.at == last.at && same(&kept.event, &last.event)
+        });
+    let replayed = if extended {
+        let mut replayed = old.clone();
+        for event in &new.events[old.events.len()..] {
+            changes.push(Change::Event {
+                event: event.clone(),
+            });
+            Change::Event {
+                event: event.clone(),
+            }
+            .apply(&mut replayed);
+        }
+        replayed
+    } else {
+        changes.push(Change::Events {
+            events: new.events.clone(),
+        });
+        events::fold(&new.save_name, &new.events)
+    };
+    changes.extend(field_changes(&replayed, new));
+    changes
+}
+
+fn field_changes(old: &GameState, new: &GameState) -> Vec<Change> {
+    let mut changes = Vec::new();
+    if old.assistant_id != new.assistant_id || old.thread_id != new.thread_id {
+        changes.push(Change::Thread {
+            assistant_id: new.assistant_id.clone(),
+            thread_id: new.thread_id.clone(),
+        });
+    }
+    if !same(&old.main_character_sheet, &new.main_character_sheet) {
+        changes.push(Change::MainCharacter {
+            sheet: new.main_character_sheet.clone(),
+        });
+    }
+    let removed = old
+        .characters
+        .iter()
+        .any(|old| !new.characters.iter().any(|new| new.name == old.name));
+    if removed {
+        changes.push(Change::Characters {
+            characters: new.characters.clone(),
+        });
+    } else {
+        for character in &new.characters {
+            let unchanged = old
+                .characters
+                .iter()
+                .any(|old| old.name == character.name && same(old, character));
+            if !unchanged {
+                changes.push(Change::Character {
+                    sheet: character.clone(),
+                });
+            }
+        }
+    }
+    if old.archive != new.archive {
+        changes.push(Change::Archive {
+            archive: new.archive.clone(),
+        });
+    }
+    if old.portraits != new.portraits {
+        changes.push(Change::Portraits {
+            portraits: new.portraits.clone(),
+        });
+    }
+    if old.scenes != new.scenes {
+        changes.push(Change::Scenes {
+            scenes: new.scenes.clone(),
+        });
+    }
+    changes
+}
+
+// A snapshot as it goes to disk: the JSON, gzipped if compression is on.
+fn encode(json: String) -> io::Result<Vec<u8>> {
+    if !COMPRESS.load(Ordering::Relaxed) {
+        return Ok(json.into_bytes());
+    }
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(json.as_bytes())?;
+    encoder.finish()
+}
+
+// The JSON of a snapshot read from disk, unzipped if it starts like gzip.
+fn decode(bytes: &[u8]) -> io::Result<Cow<'_, [u8]>> {
+    if !bytes.starts_with(&GZIP_MAGIC) {
+        return Ok(Cow::Borrowed(bytes));
+    }
+    let mut json = Vec::new();
+    GzDecoder::new(bytes).read_to_end(&mut json)?;
+    Ok(Cow::Owned(json))
+}
+
+pub fn path_for(dir: &Path, save_name: &str) -> PathBuf {
+    dir.join(format!("{}{}", save_name, SUFFIX))
+}
+
+pub fn snapshot_path(dir: &Path, save_name: &str) -> PathBuf {
+    dir.join(format!("{}.json", save_name))
+}
+
+pub fn checksum_path(dir: &Path, save_name: &str) -> PathBuf {
+    dir.join(format!("{}.json.sum", save_name))
+}
+
+// What the load menu shows of a save; see save::SaveMetadata.
+pub fn metadata_path(dir: &Path, save_name: &str) -> PathBuf {
+    dir.join(format!("{}.meta", save_name))
+}
+
+pub fn backup_dir(dir: &Path, save_name: &str) -> PathBuf {
+    dir.join("backups").join(save_name)
+}
+
+// The backups of a save, newest first.
+pub fn backups(dir: &Path, save_name: &str) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(backup_dir(dir, save_name)) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .collect();
+    // Named by timestamp, so the names sort by age.
+    backups.sort();
+    backups.reverse();
+    backups
+}
+
+// Keep a version of a save as its newest backup, and drop the oldest beyond the backup count.
+pub fn back_up(dir: &Path, save_name: &str, bytes: &[u8]) -> io::Result<()> {
+    let count = BACKUP_COUNT.load(Ordering::Relaxed);
+    if count == 0 {
+        return Ok(());
+    }
+    let backup_dir = backup_dir(dir, save_name);
+    fs::create_dir_all(&backup_dir)?;
+    let name = Local::now().format("%Y-%m-%d %H-%M-%S%.6f");
+    fs::write(backup_dir.join(format!("{}.json", name)), bytes)?;
+    for old in backups(dir, save_name).into_iter().skip(count) {
+        fs::remove_file(old)?;
+    }
+    Ok(())
+}
+
+// CRC-32, as used by zip and PNG.
+pub fn checksum(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+fn write_sums(dir: &Path, save_name: &str, sums: &str) -> io::Result<()> {
+    let temp_path = dir.join(format!("{}.json.sum.tmp", save_name));
+    fs::write(&temp_path, sums)?;
+    fs::rename(&temp_path, checksum_path(dir, save_name))
+}
+
+fn write_checksum(dir: &Path, save_name: &str, bytes: &[u8]) -> io::Result<()> {
+    write_sums(dir, save_name, &format!("{:08x}\n", checksum(bytes)))
+}
+
+// Put `bytes` in place of the snapshot. Until the new snapshot is renamed in, its checksum file
+// lists both versions, so a crash on either side of the rename leaves a save that verifies.
+fn replace_snapshot(dir: &Path, save_name: &str, bytes: &[u8]) -> io::Result<()> {
+    let save_path = snapshot_path(dir, save_name);
+    let temp_path = dir.join(format!("{}.json.tmp", save_name));
+    fs::write(&temp_path, bytes)?;
+    let previous = match fs::read_to_string(checksum_path(dir, save_name)) {
+        Ok(sums) => sums.lines().next().map(str::to_string),
+        // A save from before checksums has none of its own to keep.
+        Err(_) => fs::read(&save_path)
+            .ok()
+            .map(|old| format!("{:08x}", checksum(&old))),
+    };
+    let mut sums = format!("{:08x}\n", checksum(bytes));
+    if let Some(previous) = previous {
+        sums += &previous;
+        sums.push('\n');
+    }
+    write_sums(dir, save_name, &sums)?;
+    fs::rename(&temp_path, &save_path)?;
+    write_checksum(dir, save_name, bytes)
+}
+
+// Check a snapshot against its checksum. Saves from before checksums have none and pass.
+fn verify(dir: &Path, save_name: &str, bytes: &[u8]) -> Result<(), String> {
+    let actual = format!("{:08x}", checksum(bytes));
+    match fs::read_to_string(checksum_path(dir, save_name)) {
+        Ok(expected) if expected.lines().any(|sum| sum.trim() == actual) => Ok(()),
+        Ok(_) => Err("its checksum does not match".to_string()),
+        Err(_) => Ok(()),
+    }
+}
+
+// Accept a snapshot as it is now, e.g. after it was edited by hand, with its journal on top.
+pub fn accept(dir: &Path, save_name: &str) -> io::Result<()> {
+    let bytes = fs::read(snapshot_path(dir, save_name))?;
+    write_checksum(dir, save_name, &bytes)?;
+    rebase(dir, save_name, checksum(&bytes))
+}
+
+fn base_line(checksum: u32) -> io::Result<String> {
+    let base = Change::Base {
+        checksum: format!("{:08x}", checksum),
+    };
+    Ok(serde_json::to_string(&base)? + "\n")
+}
+
+// Start a journal over on another snapshot, when the one it was written on changed in place.
+fn rebase(dir: &Path, save_name: &str, checksum: u32) -> io::Result<()> {
+    let journal = match fs::read_to_string(path_for(dir, save_name)) {
+        Ok(journal) => journal,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    let Some((first, rest)) = journal.split_once('\n') else {
+        return Ok(());
+    };
+    if !matches!(serde_json::from_str(first), Ok(Change::Base { .. })) {
+        return Ok(());
+    }
+    let temp_path = dir.join(format!("{}{}.tmp", save_name, SUFFIX));
+    fs::write(&temp_path, base_line(checksum)? + rest)?;
+    fs::rename(&temp_path, path_for(dir, save_name))
+}
+
+// Put a backup in place of the snapshot. The journal belongs to the replaced version, so it goes.
+pub fn restore_backup(dir: &Path, save_name: &str, backup: &Path) -> io::Result<()> {
+    let bytes = fs::read(backup)?;
+    // The version being replaced is kept too, unless it is damaged.
+    if let Ok((_, current)) = Journal::load(dir, save_name) {
+        back_up(
+            dir,
+            save_name,
+            serde_json::to_string_pretty(&current)?.as_bytes(),
+        )?;
+    }
+    replace_snapshot(dir, save_name, &bytes)?;
+    match fs::remove_file(path_for(dir, save_name)) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+// Give a save another name: its snapshot, with the journal folded in and the new name inside, and
+// its backups move over, and the old files go. Returns the game as renamed.
+pub fn rename(dir: &Path, from: &str, to: &str) -> Result<GameState, SaveError> {
+    if snapshot_path(dir, to).exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("a save named '{}' already exists", to),
+        )
+        .into());
+    }
+    let (_, mut state) = Journal::load(dir, from)?;
+    state.save_name = to.to_string();
+    if backup_dir(dir, from).exists() {
+        fs::create_dir_all(dir.join("backups"))?;
+        fs::rename(backup_dir(dir, from), backup_dir(dir, to))?;
+    }
+    Journal::new(dir, to).compact(&state)?;
+    for path in files_for(dir, from) {
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+    }
+    Ok(state)
+}
+
+// Every file that belongs to a save, for deleting it. Its backups have a folder of their own.
+pub fn files_for(dir: &Path, save_name: &str) -> [PathBuf; 4] {
+    [
+        snapshot_path(dir, save_name),
+        path_for(dir, save_name),
+        checksum_path(dir, save_name),
+        metadata_path(dir, save_name),
+    ]
+}
+
+// Where a save is written, and what is already on disk for it.
+pub struct Journal {
+    dir: PathBuf,
+    save_name: String,
+    written: Option<GameState>,
+    entries: usize,
+    // The checksum of the snapshot on disk, which the journal is written on.
+    base: u32,
+}
+
+impl Journal {
+    // A journal that knows nothing on disk yet; its first record writes a full snapshot.
+    pub fn new(dir: &Path, save_name: &str) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            save_name: save_name.to_string(),
+            written: None,
+            entries: 0,
+            base: 0,
+        }
+    }
+
+    // Read the snapshot and replay its journal. A torn last line, from a crash mid-write, is dropped,
+    // and so is a journal left behind by a compaction that stopped before removing it, as the
+    // snapshot already has everything in it.
+    pub fn load(dir: &Path, save_name: &str) -> Result<(Self, GameState), SaveError> {
+        let snapshot = fs::read(snapshot_path(dir, save_name))?;
+        let parsed = decode(&snapshot)
+            .and_then(|json| serde_json::from_slice::<GameState>(&json).map_err(io::Error::from));
+        let corrupt = |reason: String, readable: bool| SaveError::Corrupt {
+            save_name: save_name.to_string(),
+            reason,
+            readable,
+        };
+        verify(dir, save_name, &snapshot).map_err(|reason| corrupt(reason, parsed.is_ok()))?;
+        let mut state = parsed.map_err(|e| corrupt(e.to_string(), false))?;
+        let base = checksum(&snapshot);
+        let mut entries = 0;
+        let mut torn = false;
+        match fs::read_to_string(path_for(dir, save_name)) {
+            Ok(journal) => {
+                for line in journal.lines().filter(|line| !line.trim().is_empty()) {
+                    match serde_json::from_str::<Change>(line) {
+                        Ok(Change::Base { checksum }) if checksum != format!("{:08x}", base) => {
+                            log::warn!(
+                                "Skipped the journal of '{}' left by a compaction",
+                                save_name
+                            );
+                            torn = true;
+                            break;
+                        }
+                        Ok(Change::Base { .. }) => {}
+                        Ok(change) => {
+                            change.apply(&mut state);
+                            entries += 1;
+                        }
+                        Err(e) => {
+                            log::warn!("Stopped replaying the journal of '{}': {}", save_name, e);
+                            torn = true;
+                            break;
+                        }
+                    }
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        let journal = Self {
+            dir: dir.to_path_buf(),
+            save_name: save_name.to_string(),
+            // Appending after a torn line would glue onto it, and after a skipped journal would
+            // be skipped with it, so start over with a snapshot.
+            written: (!torn).then(|| state.clone()),
+            entries,
+            base,
+        };
+        // After `written`, so the seed is journaled with the next change.
+        state.seed_events();
+        Ok((journal, state))
+    }
+
+    pub fn entries(&self) -> usize {
+        self.entries
+    }
+
+    // Persist a state: append what changed, or write a snapshot when the journal has grown long.
+    pub fn record(&mut self, state: &GameState) -> io::Result<()> {
+        let changes = match &self.written {
+            Some(written) => diff(written, state),
+            None => return self.compact(state),
+        };
+        if changes.is_empty() {
+            return Ok(());
+        }
+        if self.entries + changes.len() > COMPACT_AFTER {
+            return self.compact(state);
+        }
+
+        // A journal starts with the snapshot it belongs to, so a later one isn't mistaken for it.
+        let mut lines = if self.entries == 0 {
+            base_line(self.base)?
+        } else {
+            String::new()
+        };
+        for change in &changes {
+            lines.push_str(&serde_json::to_string(change)?);
+            lines.push('\n');
+        }
+        // One write per update, so a crash loses at most the line being written.
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path_for(&self.dir, &self.save_name))?;
+        file.write_all(lines.as_bytes())?;
+        REVISION.fetch_add(1, Ordering::Relaxed);
+        self.entries += changes.len();
+        self.written = Some(state.clone());
+        Ok(())
+    }
+
+    // Write a full snapshot and start an empty journal, keeping the previous version as the backup.
+    pub fn compact(&mut self, state: &GameState) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let save_path = snapshot_path(&self.dir, &self.save_name);
+        match &self.written {
+            // What was last written includes the journal, so it is the whole previous version.
+            Some(written) => back_up(
+                &self.dir,
+                &self.save_name,
+                serde_json::to_string_pretty(written)?.as_bytes(),
+            )?,
+            None if save_path.exists() => {
+                back_up(&self.dir, &self.save_name, &fs::read(&save_path)?)?
+            }
+            None => {}
+        }
+        let serialized = encode(serde_json::to_string_pretty(state)?)?;
+        replace_snapshot(&self.dir, &self.save_name, &serialized)?;
+        // A journal left here by a crash starts with the old snapshot's checksum, so loading skips it.
+        match fs::remove_file(path_for(&self.dir, &self.save_name)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+        REVISION.fetch_add(1, Ordering::Relaxed);
+        self.base = checksum(&serialized);
+        self.entries = 0;
+        self.written = Some(state.clone());
+        Ok(())
+    }
+}
@@ -2,20 +2,65 @@ pub mod ai;
 pub mod ai_response;
 pub mod app;
 pub mod app_state;
+pub mod archetype;
+pub mod archivist;
 pub mod assistant;
 pub mod audio;
+pub mod augmentation;
+pub mod backend;
+pub mod bench;
+pub mod branding;
+pub mod campaign_stats;
 pub mod character;
 pub mod cleanup;
+pub mod cli;
+pub mod clipboard;
+pub mod combat;
+pub mod commands;
+pub mod damage;
 pub mod dice;
+pub mod dice_expr;
+pub mod elevenlabs;
+pub mod emergency;
 pub mod error;
+pub mod events;
+pub mod export;
 pub mod game_state;
+pub mod headless;
+pub mod house_rules;
+pub mod i18n;
 pub mod image;
+pub mod journal;
+pub mod log_viewer;
+pub mod logging;
+pub mod macros;
 pub mod message;
+pub mod metrics;
+pub mod mirror;
+pub mod narration_cache;
+pub mod paths;
+pub mod plugins;
+pub mod pool_hint;
+pub mod review;
+pub mod roll_popup;
+pub mod ruleset;
 pub mod save;
+pub mod selection;
 pub mod settings;
 pub mod settings_state;
+pub mod sheet_diff;
+pub mod shop;
+pub mod spectator;
+pub mod status;
+pub mod tasks;
+pub mod thumbnail;
+pub mod tool_args;
+pub mod transcript;
 pub mod ui;
+pub mod update;
 pub mod utils;
+pub mod webhook;
+pub mod wizard;
 
 // Re-export commonly used items for easier access
 pub use ai::GameAI;
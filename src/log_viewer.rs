@@ -0,0 +1,177 @@
+// log_viewer.rs
+// State of the Logs screen: the tail of the log file, filtered by level and a search query.
+
+use crate::logging;
+use crossterm::event::{Event, KeyCode, KeyEvent};
+use log::{Level, LevelFilter};
+use std::fs;
+use std::str::FromStr;
+use tui_input::backend::crossterm::EventHandler;
+use tui_input::Input;
+
+// Only the end of the file is kept; older lines are a text editor's job.
+pub const MAX_LINES: usize = 2000;
+const PAGE: usize = 20;
+
+// Most to least verbose, as the level filter cycles through them.
+pub const LEVEL_FILTERS: [LevelFilter; 5] = [
+    LevelFilter::Trace,
+    LevelFilter::Debug,
+    LevelFilter::Info,
+    LevelFilter::Warn,
+    LevelFilter::Error,
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogLine {
+    pub level: Option<Level>,
+    pub text: String,
+}
+
+// Split log text into lines. Lines written as "[time] LEVEL target: message" carry their level;
+// continuation lines of a multi-line message inherit it.
+pub fn parse_lines(text: &str) -> Vec<LogLine> {
+    let mut level = None;
+    text.lines()
+        .map(|line| {
+            if let Some((_, rest)) = line
+                .strip_prefix('[')
+                .and_then(|line| line.split_once("] "))
+            {
+                level = rest
+                    .split_whitespace()
+                    .next()
+                    .and_then(|word| Level::from_str(word).ok());
+            }
+            LogLine {
+                level,
+                text: line.to_string(),
+            }
+        })
+        .collect()
+}
+
+// Lines at or above `filter` that contain `query`, ignoring case.
+pub fn filter_lines<'a>(
+    lines: &'a [LogLine],
+    filter: LevelFilter,
+    query: &str,
+) -> Vec<&'a LogLine> {
+    let query = query.to_lowercase();
+    lines
+        .iter()
+        .filter(|line| line.level.is_none_or(|level| level <= filter))
+        .filter(|line| query.is_empty() || line.text.to_lowercase().contains(&query))
+        .collect()
+}
+
+pub enum LogViewerAction {
+    None,
+    Close,
+}
+
+pub struct LogViewer {
+    pub lines: Vec<LogLine>,
+    pub filter: LevelFilter,
+    pub search: Input,
+    pub searching: bool,
+    // Lines scrolled up from the end; 0 follows new lines as they are written.
+    pub scroll: usize,
+    file_len: Option<u64>,
+}
+
+impl Default for LogViewer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogViewer {
+    pub fn new() -> Self {
+        let mut viewer = LogViewer {
+            lines: Vec::new(),
+            filter: LevelFilter::Trace,
+            search: Input::default(),
+            searching: false,
+            scroll: 0,
+            file_len: None,
+        };
+        viewer.refresh();
+        viewer
+    }
+
+    // Re-read the log file when it has changed since the last look.
+    pub fn refresh(&mut self) {
+        let path = logging::log_path();
+        let len = fs::metadata(&path).map(|metadata| metadata.len()).ok();
+        if len == self.file_len {
+            return;
+        }
+        self.file_len = len;
+        let text = fs::read_to_string(&path).unwrap_or_default();
+        let mut lines = parse_lines(&text);
+        let excess = lines.len().saturating_sub(MAX_LINES);
+        lines.drain(..excess);
+        self.lines = lines;
+    }
+
+    pub fn visible(&self) -> Vec<&LogLine> {
+        filter_lines(&self.lines, self.filter, self.search.value())
+    }
+
+    fn cycle_filter(&mut self, forward: bool) {
+        let count = LEVEL_FILTERS.len();
+        let current = LEVEL_FILTERS
+            .iter()
+            .position(|filter| *filter == self.filter)
+            .unwrap_or(0);
+        let next = if forward {
+            (current + 1) % count
+        } else {
+            (current + count - 1) % count
+        };
+        self.filter = LEVEL_FILTERS[next];
+        self.scroll = 0;
+    }
+
+    fn scroll_up(&mut self, lines: usize) {
+        let max = self.visible().len().saturating_sub(1);
+        self.scroll = (self.scroll + lines).min(max);
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> LogViewerAction {
+        if self.searching {
+            match key.code {
+                KeyCode::Enter | KeyCode::Esc => self.searching = false,
+                _ => {
+                    self.search.handle_event(&Event::Key(key));
+                    self.scroll = 0;
+                }
+            }
+            return LogViewerAction::None;
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => return LogViewerAction::Close,
+            KeyCode::Char('/') => self.searching = true,
+            KeyCode::Char('c') => {
+                self.search.reset();
+                self.scroll = 0;
+            }
+            KeyCode::Char('r') => {
+                self.file_len = None;
+                self.refresh();
+            }
+            KeyCode::Left => self.cycle_filter(false),
+            KeyCode::Right => self.cycle_filter(true),
+            KeyCode::Up => self.scroll_up(1),
+            KeyCode::Down => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::PageUp => self.scroll_up(PAGE),
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_sub(PAGE),
+            KeyCode::Home => self.scroll_up(usize::MAX / 2),
+            KeyCode::End => self.scroll = 0,
+            _ => {}
+        }
+        LogViewerAction::None
+    }
+}
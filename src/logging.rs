@@ -0,0 +1,73 @@
+// logging.rs
+// Minimal file logger behind the `log` facade.
+
+use crate::paths;
+use chrono::Local;
+use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+pub const DEFAULT_LOG_LEVEL: LevelFilter = LevelFilter::Warn;
+
+struct FileLogger {
+    file: Mutex<Option<File>>,
+}
+
+static LOGGER: FileLogger = FileLogger {
+    file: Mutex::new(None),
+};
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        if let Some(file) = self.file.lock().unwrap().as_mut() {
+            let _ = writeln!(
+                file,
+                "[{}] {:<5} {}: {}",
+                Local::now().format("%Y-%m-%d %H:%M:%S"),
+                record.level(),
+                record.target(),
+                record.args()
+            );
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(file) = self.file.lock().unwrap().as_mut() {
+            let _ = file.flush();
+        }
+    }
+}
+
+pub fn log_path() -> PathBuf {
+    paths::logs_dir().join("sharad.log")
+}
+
+// Install the logger. Without a writable log file, records are dropped rather than failing startup.
+pub fn init(level: LevelFilter) -> Result<(), SetLoggerError> {
+    let path = log_path();
+    *LOGGER.file.lock().unwrap() = open_log_file(&path);
+    log::set_logger(&LOGGER)?;
+    set_level(level);
+    Ok(())
+}
+
+// Change the level at runtime; takes effect for the next record.
+pub fn set_level(level: LevelFilter) {
+    log::set_max_level(level);
+}
+
+fn open_log_file(path: &Path) -> Option<File> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).ok()?;
+    }
+    OpenOptions::new().create(true).append(true).open(path).ok()
+}
@@ -0,0 +1,229 @@
+// macros.rs
+// Player-defined text macros: "!overwatch" in the input expands to a saved paragraph.
+//
+// Macros are kept in macros.json next to settings.json and managed from the Settings menu.
+
+use crate::paths;
+use crossterm::event::{Event, KeyCode, KeyEvent};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use tui_input::backend::crossterm::EventHandler;
+use tui_input::Input;
+
+pub const MACRO_PREFIX: char = '!';
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Macros {
+    pub macros: BTreeMap<String, String>,
+}
+
+pub fn macros_path() -> PathBuf {
+    paths::config_dir().join("macros.json")
+}
+
+// Macro names are single words so they can be spotted in a sentence.
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+}
+
+impl Macros {
+    pub fn load() -> Self {
+        fs::read_to_string(macros_path())
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = macros_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    // Replace every "!name" that starts a word with its text. Unknown names are left alone.
+    pub fn expand(&self, input: &str) -> String {
+        let mut expanded = String::with_capacity(input.len());
+        let mut rest = input;
+        let mut at_word_start = true;
+
+        while let Some(c) = rest.chars().next() {
+            if c == MACRO_PREFIX && at_word_start {
+                let name_len = rest[1..]
+                    .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+                    .unwrap_or(rest.len() - 1);
+                if let Some(text) = self.macros.get(&rest[1..1 + name_len]) {
+                    expanded.push_str(text);
+                    rest = &rest[1 + name_len..];
+                    at_word_start = false;
+                    continue;
+                }
+            }
+            expanded.push(c);
+            at_word_start = c.is_whitespace();
+            rest = &rest[c.len_utf8()..];
+        }
+        expanded
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MacroField {
+    Name,
+    Text,
+}
+
+pub enum MacroEditorAction {
+    None,
+    Close,
+}
+
+// State of the macro manager screen: a list, and a form for adding or editing one macro.
+pub struct MacroEditor {
+    pub macros: Macros,
+    pub selected: usize,
+    // Some while the form is open, with the macro being renamed if it is an edit.
+    pub editing: Option<Option<String>>,
+    pub field: MacroField,
+    pub name: Input,
+    pub text: Input,
+    pub message: Option<String>,
+}
+
+impl MacroEditor {
+    pub fn new(macros: Macros) -> Self {
+        MacroEditor {
+            macros,
+            selected: 0,
+            editing: None,
+            field: MacroField::Name,
+            name: Input::default(),
+            text: Input::default(),
+            message: None,
+        }
+    }
+
+    pub fn selected_name(&self) -> Option<&String> {
+        self.macros.macros.keys().nth(self.selected)
+    }
+
+    // The input that keystrokes and pastes go to, if the form is open.
+    pub fn active_input(&mut self) -> Option<&mut Input> {
+        self.editing.as_ref()?;
+        Some(match self.field {
+            MacroField::Name => &mut self.name,
+            MacroField::Text => &mut self.text,
+        })
+    }
+
+    fn open_form(&mut self, existing: Option<String>) {
+        let text = existing
+            .as_ref()
+            .and_then(|name| self.macros.macros.get(name))
+            .cloned()
+            .unwrap_or_default();
+        self.name = Input::new(existing.clone().unwrap_or_default());
+        self.text = Input::new(text);
+        self.field = if existing.is_some() {
+            MacroField::Text
+        } else {
+            MacroField::Name
+        };
+        self.editing = Some(existing);
+        self.message = None;
+    }
+
+    fn submit_form(&mut self) {
+        let name = self
+            .name
+            .value()
+            .trim()
+            .trim_start_matches(MACRO_PREFIX)
+            .to_string();
+        let text = self.text.value().trim().to_string();
+        if !is_valid_name(&name) {
+            self.message = Some("Names are one word: letters, digits, _ or -".to_string());
+            self.field = MacroField::Name;
+            return;
+        }
+        if text.is_empty() {
+            self.message = Some("The macro needs some text".to_string());
+            self.field = MacroField::Text;
+            return;
+        }
+        if let Some(Some(previous)) = self.editing.take() {
+            self.macros.macros.remove(&previous);
+        }
+        self.macros.macros.insert(name.clone(), text);
+        self.selected = self
+            .macros
+            .macros
+            .keys()
+            .position(|key| *key == name)
+            .unwrap_or(0);
+        self.persist();
+    }
+
+    fn persist(&mut self) {
+        self.message = match self.macros.save() {
+            Ok(()) => None,
+            Err(e) => Some(format!("Failed to save macros: {}", e)),
+        };
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> MacroEditorAction {
+        if self.editing.is_some() {
+            match key.code {
+                KeyCode::Esc => {
+                    self.editing = None;
+                    self.message = None;
+                }
+                KeyCode::Tab | KeyCode::BackTab | KeyCode::Up | KeyCode::Down => {
+                    self.field = match self.field {
+                        MacroField::Name => MacroField::Text,
+                        MacroField::Text => MacroField::Name,
+                    };
+                }
+                KeyCode::Enter => match self.field {
+                    MacroField::Name => self.field = MacroField::Text,
+                    MacroField::Text => self.submit_form(),
+                },
+                _ => {
+                    if let Some(input) = self.active_input() {
+                        input.handle_event(&Event::Key(key));
+                    }
+                }
+            }
+            return MacroEditorAction::None;
+        }
+
+        let count = self.macros.macros.len();
+        match key.code {
+            KeyCode::Esc => return MacroEditorAction::Close,
+            KeyCode::Up if count > 0 => self.selected = (self.selected + count - 1) % count,
+            KeyCode::Down if count > 0 => self.selected = (self.selected + 1) % count,
+            KeyCode::Char('a') => self.open_form(None),
+            KeyCode::Enter | KeyCode::Char('e') => {
+                if let Some(name) = self.selected_name().cloned() {
+                    self.open_form(Some(name));
+                }
+            }
+            KeyCode::Char('d') | KeyCode::Delete => {
+                if let Some(name) = self.selected_name().cloned() {
+                    self.macros.macros.remove(&name);
+                    self.selected = self.selected.min(count.saturating_sub(2));
+                    self.persist();
+                }
+            }
+            _ => {}
+        }
+        MacroEditorAction::None
+    }
+}
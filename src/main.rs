@@ -1,12 +1,15 @@
 // Import necessary modules from the local crate and external crates.
 use crate::app::{App, AppCommand};
-use crate::cleanup::cleanup;
+use crate::cleanup::{cleanup, tui_output};
+use crate::cli::CliAction;
 use crate::error::ShadowrunError;
 use crate::message::{AIMessage, Message, MessageType};
+use crate::mirror::MirrorTarget;
+use crate::thumbnail::Thumbnails;
 
 use crossterm::{
-    event::{Event, KeyEventKind}, // Event handling from crossterm for input events.
-    execute,                      // Helper macro to execute terminal commands.
+    event::{EnableBracketedPaste, Event, KeyEventKind}, // Event handling from crossterm for input events.
+    execute, // Helper macro to execute terminal commands.
     terminal::{enable_raw_mode, EnterAlternateScreen, SetSize}, // Terminal manipulation utilities.
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
@@ -21,20 +24,65 @@ pub mod ai;
 pub mod ai_response;
 pub mod app;
 pub mod app_state;
+pub mod archetype;
+pub mod archivist;
 pub mod assistant;
 pub mod audio;
+pub mod augmentation;
+pub mod backend;
+pub mod bench;
+pub mod branding;
+pub mod campaign_stats;
 pub mod character;
 pub mod cleanup;
+pub mod cli;
+pub mod clipboard;
+pub mod combat;
+pub mod commands;
+pub mod damage;
 pub mod dice;
+pub mod dice_expr;
+pub mod elevenlabs;
+pub mod emergency;
 pub mod error;
+pub mod events;
+pub mod export;
 pub mod game_state;
+pub mod headless;
+pub mod house_rules;
+pub mod i18n;
 pub mod image;
+pub mod journal;
+pub mod log_viewer;
+pub mod logging;
+pub mod macros;
 pub mod message;
+pub mod metrics;
+pub mod mirror;
+pub mod narration_cache;
+pub mod paths;
+pub mod plugins;
+pub mod pool_hint;
+pub mod review;
+pub mod roll_popup;
+pub mod ruleset;
 pub mod save;
+pub mod selection;
 pub mod settings;
 pub mod settings_state;
+pub mod sheet_diff;
+pub mod shop;
+pub mod spectator;
+pub mod status;
+pub mod tasks;
+pub mod thumbnail;
+pub mod tool_args;
+pub mod transcript;
 pub mod ui;
+pub mod update;
 pub mod utils;
+pub mod webhook;
+pub mod wizard;
 
 // Constants for minimum terminal size.
 const MIN_WIDTH: u16 = 100;
@@ -46,7 +94,7 @@ fn ensure_minimum_terminal_size() -> io::Result<()> {
                                                         // If the current size is less than minimum, resize to the minimum required.
     if width < MIN_WIDTH || height < MIN_HEIGHT {
         execute!(
-            io::stdout(),
+            tui_output(),
             SetSize(MIN_WIDTH.max(width), MIN_HEIGHT.max(height))
         )?;
     }
@@ -56,21 +104,86 @@ fn ensure_minimum_terminal_size() -> io::Result<()> {
 // Entry point for the Tokio runtime.
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Parse the command line before touching the terminal so help and errors print normally.
+    let cli = match cli::parse(std::env::args().skip(1)) {
+        Ok(CliAction::Run(cli)) => cli,
+        Ok(CliAction::Help) => {
+            println!("{}", cli::USAGE);
+            return Ok(());
+        }
+        Ok(CliAction::Version) => {
+            println!("sharad_ratatui {}", env!("CARGO_PKG_VERSION"));
+            return Ok(());
+        }
+        Err(e) => {
+            eprintln!("Error: {}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    let migration = paths::init(cli.config_dir.clone(), cli.portable);
+    let log_level = cli.log_level.unwrap_or_else(|| {
+        settings::Settings::load()
+            .map(|settings| settings.log_level.filter())
+            .unwrap_or(logging::DEFAULT_LOG_LEVEL)
+    });
+    if let Err(e) = logging::init(log_level) {
+        eprintln!("Failed to start logging: {}", e);
+    }
+    if let Some(migration) = migration {
+        log::info!("{}", migration);
+    }
+    for name in plugins::load_scripts(&paths::plugins_dir()) {
+        log::info!("Loaded the plugin tool {}", name);
+    }
+
+    if let Some(runs) = cli.bench_ui {
+        for result in bench::run(runs) {
+            println!("{}", result);
+        }
+        return Ok(());
+    }
+
+    if let Some(script) = &cli.headless {
+        let script = std::fs::read_to_string(script)?;
+        let report = if cli.mock {
+            let save_name = cli.new.as_deref().unwrap_or("headless");
+            headless::run_mock(&script, save_name).await?
+        } else {
+            let (ai_sender, ai_receiver) = mpsc::unbounded_channel::<AIMessage>();
+            let (app, command_receiver) = App::new(ai_sender).await;
+            let _error_receiver = error::initialize_global_error_handler().await;
+            headless::run(app, command_receiver, ai_receiver, &cli, &script).await?
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    // With the transcript mirrored to stdout, the interface moves to stderr.
+    let mirror_target = cli.mirror.as_deref().map(MirrorTarget::from_arg);
+    if mirror_target == Some(MirrorTarget::Stdout) {
+        cleanup::draw_on_stderr();
+    }
+
     // Set up the terminal in raw mode.
     enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?; // Enter an alternate screen.
+    let mut output = tui_output();
+    execute!(output, EnterAlternateScreen, EnableBracketedPaste)?; // Enter an alternate screen.
+    cleanup::enhance_keyboard();
 
     // Ensure terminal dimensions are correct.
     ensure_minimum_terminal_size()?;
 
     // Initialize terminal backend.
-    let backend = CrosstermBackend::new(stdout);
+    let backend = CrosstermBackend::new(output);
     let mut terminal = Terminal::new(backend)?;
 
     // Set panic hook for cleanup and better panic info.
     panic::set_hook(Box::new(|panic_info| {
+        let emergency_save = emergency::write("panic");
         cleanup();
+        if let Some(path) = emergency_save {
+            println!("Your game was saved to {}", path.display());
+        }
         if let Some(location) = panic_info.location() {
             println!(
                 "Panic occurred in file '{}' at line {}",
@@ -87,8 +200,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let (ai_sender, ai_receiver) = mpsc::unbounded_channel::<AIMessage>();
 
     // Initialize the application.
-    let (app, command_receiver) = App::new(ai_sender).await;
+    let (mut app, command_receiver) = App::new(ai_sender).await;
+    // Before the first event is read, as the terminal answers on stdin.
+    if !app.settings.accessibility_mode {
+        *app.thumbnails.get_mut() = Thumbnails::detect();
+    }
+    app.clipboard.send_to_terminal();
+    cleanup::capture_mouse(app.settings.mouse_selection);
     let error_receiver = error::initialize_global_error_handler().await;
+    app.mirror_override = mirror_target;
+    app.sync_mirror();
+    app.launch(&cli);
+    // App holds a RefCell and the clipboard, so it never leaves this task.
+    #[allow(clippy::arc_with_non_send_sync)]
     let app = Arc::new(Mutex::new(app));
 
     // Run the application and handle errors.
@@ -103,33 +227,66 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     {
         eprintln!("Error: {:#?}", err);
     }
+    cleanup();
 
     Ok(())
 }
 
+// Resolves when the terminal is closed or the process is asked to stop.
+#[cfg(unix)]
+async fn termination_signal() -> &'static str {
+    use tokio::signal::unix::{signal, SignalKind};
+    let (Ok(mut terminate), Ok(mut hangup)) = (
+        signal(SignalKind::terminate()),
+        signal(SignalKind::hangup()),
+    ) else {
+        return std::future::pending().await;
+    };
+    tokio::select! {
+        _ = terminate.recv() => "SIGTERM",
+        _ = hangup.recv() => "SIGHUP",
+    }
+}
+
+#[cfg(not(unix))]
+async fn termination_signal() -> &'static str {
+    let _ = tokio::signal::ctrl_c().await;
+    "Ctrl+C"
+}
+
 // Asynchronous function to continuously run and update the application.
 async fn run_app(
-    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    terminal: &mut Terminal<CrosstermBackend<Box<dyn io::Write + Send>>>,
     app: Arc<Mutex<App>>,
     mut command_receiver: mpsc::UnboundedReceiver<AppCommand>,
     mut ai_receiver: mpsc::UnboundedReceiver<AIMessage>,
     mut error_receiver: mpsc::UnboundedReceiver<ShadowrunError>,
 ) -> io::Result<()> {
+    let mut last_frame = Instant::now();
     let mut last_tick = Instant::now();
-    let tick_rate = Duration::from_millis(16);
+    let mut last_draw: Option<Instant> = None;
     let _ai_client = app.lock().await.initialize_ai_client().await;
+    let termination = termination_signal();
+    tokio::pin!(termination);
 
     loop {
-        let timeout = tick_rate
-            .checked_sub(last_tick.elapsed())
+        // Read every frame, as the rates can be changed in the settings.
+        let (frame_interval, tick_interval, redraw_interval) = {
+            let app = app.lock().await;
+            (
+                app.frame_interval(),
+                app.tick_interval(),
+                app.redraw_interval(),
+            )
+        };
+        let timeout = frame_interval
+            .checked_sub(last_frame.elapsed())
             .unwrap_or_else(|| Duration::from_secs(0));
+        // Anything but the timeout or an empty poll may change what is shown.
+        let mut changed = true;
 
         tokio::select! {
-            _ = sleep(timeout) => {
-                let mut app = app.lock().await;
-                app.on_tick();
-                app.clean_old_errors(Duration::from_secs(5));
-            }
+            _ = sleep(timeout) => changed = false,
             event_result = tokio::task::spawn_blocking(|| crossterm::event::poll(Duration::from_millis(1))) => {
                 match event_result {
                     Ok(Ok(true)) => {
@@ -138,13 +295,20 @@ async fn run_app(
                                 let mut app = app.lock().await;
                                 app.handle_input(key);
                             }
-                            Ok(_) => {}, // Ignore non-key events and non-press key events
+                            Ok(Event::Mouse(mouse)) => {
+                                app.lock().await.handle_mouse(mouse);
+                            }
+                            // Pasted text arrives in one piece, so its line breaks don't act as Enter.
+                            Ok(Event::Paste(text)) => {
+                                app.lock().await.paste(&text);
+                            }
+                            Ok(_) => {}, // Ignore other events and non-press key events
                             Err(e) => {
                                 eprintln!("Error reading event: {:#?}", e);
                             }
                         }
                     }
-                    Ok(Ok(false)) => {}, // No event available
+                    Ok(Ok(false)) => changed = false, // No event available
                     Ok(Err(e)) => {
                         eprintln!("Error polling for event: {:#?}", e);
                     }
@@ -166,14 +330,50 @@ async fn run_app(
                         app.handle_ai_response(result).await;
                         app.scroll_to_bottom();
                     },
+                    AppCommand::Reconnected => {
+                        let mut app = app.lock().await;
+                        app.handle_reconnected();
+                        app.scroll_to_bottom();
+                    },
                     AppCommand::LoadGame(save_name) => {
-                        if let Err(e) = app.lock().await.load_game(&save_name).await {
-                            app.lock().await.add_message(Message::new( MessageType::System, format!("Failed to load game: {:#?}", e)));
+                        let mut app = app.lock().await;
+                        if let Err(e) = app.load_game(&save_name).await {
+                            app.handle_load_error(e);
+                        }
+                    },
+                    AppCommand::ForkGame(fork_name) => {
+                        let mut app = app.lock().await;
+                        let result = app.fork_game(&fork_name).await;
+                        app.stop_spinner();
+                        if let Err(e) = result {
+                            app.add_message(Message::new(MessageType::System, format!("Failed to fork the game: {}", e)));
+                        }
+                    },
+                    AppCommand::DuplicateSave(save_name, copy_name) => {
+                        let mut app = app.lock().await;
+                        let result = app.duplicate_save(&save_name, &copy_name).await;
+                        app.stop_spinner();
+                        if let Err(e) = result {
+                            app.add_message(Message::new(MessageType::System, format!("Failed to duplicate the save: {}", e)));
+                        }
+                    },
+                    AppCommand::UpgradeAssistant(recreate) => {
+                        let mut app = app.lock().await;
+                        let result = app.upgrade_assistant(recreate).await;
+                        app.stop_spinner();
+                        if let Err(e) = result {
+                            app.add_message(Message::new(MessageType::System, format!("Failed to upgrade the assistant: {}", e)));
                         }
                     },
-                    AppCommand::StartNewGame(save_name) => {
+                    AppCommand::RenameSave(from, to, rename_assistant) => {
+                        let mut app = app.lock().await;
+                        if let Err(e) = app.rename_save(&from, &to, rename_assistant).await {
+                            app.add_message(Message::new(MessageType::System, format!("Failed to rename the save: {}", e)));
+                        }
+                    },
+                    AppCommand::StartNewGame(save_name, seed) => {
                         let app = app.clone();
-                        if let Err(e) = app.lock().await.start_new_game(save_name).await {
+                        if let Err(e) = app.lock().await.start_new_game(save_name, seed).await {
                             app.lock().await.add_message(Message::new( MessageType::System, format!("Failed to start new game: {:#?}", e)));
                         };
                     },
@@ -210,6 +410,17 @@ async fn run_app(
                         ));
                         app.add_debug_message(format!("Transcription error: {}", error));
                     }
+                    AppCommand::SystemMessage(message) => {
+                        let mut app = app.lock().await;
+                        app.add_message(Message::new(MessageType::System, message));
+                        app.scroll_to_bottom();
+                    }
+                    AppCommand::UpdateNotice(notice) => {
+                        app.lock().await.update_notice = Some(notice);
+                    }
+                    AppCommand::OlderMessagesLoaded(thread_id, page) => {
+                        app.lock().await.prepend_older_messages(thread_id, page);
+                    }
                 }
             },
             Some(ai_message) = ai_receiver.recv() => {
@@ -222,31 +433,49 @@ async fn run_app(
                         if let Some(last_message) = app.game_content.borrow().last() {
                             if last_message.message_type == MessageType::System {
                                 app.game_content.borrow_mut().pop();
+                                app.transcript_changed();
                             }
                         }
                         app.handle_ai_response(response).await;
                     }
                 }
             }
+            reason = &mut termination => {
+                if let Some(path) = emergency::write(reason) {
+                    log::warn!("{}: game saved to {}", reason, path.display());
+                }
+                return Ok(());
+            }
             Some(error) = error_receiver.recv() => {
                 app.lock().await.add_error(error);
             }
         }
 
-        terminal.draw(|f| {
-            let mut app = tokio::task::block_in_place(|| app.blocking_lock());
-            ui::draw(f, &mut app)
-        })?;
+        if last_tick.elapsed() >= tick_interval {
+            let mut app = app.lock().await;
+            app.on_tick();
+            app.clean_old_errors(Duration::from_secs(5));
+            last_tick = Instant::now();
+        }
+
+        // While nothing moves, frames are only drawn when something happened, and now and then.
+        if changed || last_draw.is_none_or(|drawn| drawn.elapsed() >= redraw_interval) {
+            terminal.draw(|f| {
+                let mut app = tokio::task::block_in_place(|| app.blocking_lock());
+                ui::draw(f, &mut app)
+            })?;
+            last_draw = Some(Instant::now());
+        }
 
         if app.lock().await.should_quit {
             return Ok(());
         }
 
-        // Ensure consistent tick rate
-        let elapsed = last_tick.elapsed();
-        if elapsed < tick_rate {
-            tokio::time::sleep(tick_rate - elapsed).await;
+        // Ensure consistent frame rate
+        let elapsed = last_frame.elapsed();
+        if elapsed < frame_interval {
+            tokio::time::sleep(frame_interval - elapsed).await;
         }
-        last_tick = Instant::now();
+        last_frame = Instant::now();
     }
 }
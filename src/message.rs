@@ -4,12 +4,13 @@ use std::path::PathBuf;
 use crate::character::CharacterSheet;
 use crate::error::AppError;
 use async_openai::types::Voice;
+use chrono::Local;
 use serde::{Deserialize, Serialize};
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 
 // Define an enumeration to categorize message types within the game.
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum MessageType {
     User,   // Message generated by the user.
     Game,   // Message related to game mechanics or progress.
@@ -68,34 +69,68 @@ impl GameMessage {
     }
 }
 
+// Read an answer of the assistant as the response schema describes it. Beyond the json shape,
+// every line must be spoken by one of the listed speakers.
+pub fn parse_game_message(response: &str) -> Result<GameMessage, String> {
+    let game_message: GameMessage =
+        serde_json::from_str(response).map_err(|e| format!("invalid json: {}", e))?;
+    let fluff = &game_message.fluff;
+    if let Some(line) = fluff.dialogue.iter().find(|line| {
+        !fluff
+            .speakers
+            .iter()
+            .any(|speaker| speaker.index == line.speaker_index)
+    }) {
+        return Err(format!(
+            "fluff.dialogue has a line for speaker_index {}, which is not in fluff.speakers",
+            line.speaker_index
+        ));
+    }
+    Ok(game_message)
+}
+
 // General structure for all messages used in the system.
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Message {
     pub message_type: MessageType, // The type of the message (user, game, system).
     pub content: String,           // The content of the message.
+    #[serde(default)]
+    pub timestamp: Option<i64>, // When it was written, in Unix seconds; None in older transcripts.
 }
 
 impl Fluff {
+    // Each dialogue line as rendered, with its index in `dialogue`. Lines without a known speaker
+    // are left out.
+    pub fn rendered_lines(&self) -> Vec<(usize, String)> {
+        self.dialogue
+            .iter()
+            .enumerate()
+            .filter_map(|(index, dialogue)| {
+                let speaker = self
+                    .speakers
+                    .iter()
+                    .find(|s| s.index == dialogue.speaker_index)?;
+                Some((
+                    index,
+                    format!(
+                        "{} {}\n",
+                        (if &speaker.name != "Narrator" {
+                            format!("\n{}: ", speaker.name)
+                        } else {
+                            "\n".to_string()
+                        }),
+                        dialogue.text,
+                    ),
+                ))
+            })
+            .collect()
+    }
+
     pub fn render(&self) -> String {
-        let mut rendered_fluff = String::new();
-        for dialogue in self.dialogue.iter() {
-            if let Some(speaker) = self
-                .speakers
-                .iter()
-                .find(|s| s.index == dialogue.speaker_index)
-            {
-                rendered_fluff.push_str(&format!(
-                    "{} {}\n",
-                    (if &speaker.name != "Narrator" {
-                        format!("\n{}: ", speaker.name)
-                    } else {
-                        "\n".to_string()
-                    }),
-                    dialogue.text,
-                ));
-            }
-        }
-        rendered_fluff.to_string()
+        self.rendered_lines()
+            .into_iter()
+            .map(|(_, line)| line)
+            .collect()
     }
 }
 
@@ -110,21 +145,21 @@ impl Speaker {
 
         match self.gender {
             Gender::NonBinary => {
-                self.voice = Some(if hash % 2 == 0 {
+                self.voice = Some(if hash.is_multiple_of(2) {
                     Voice::Alloy
                 } else {
                     Voice::Fable
                 });
             }
             Gender::Female => {
-                self.voice = Some(if hash % 2 == 0 {
+                self.voice = Some(if hash.is_multiple_of(2) {
                     Voice::Shimmer
                 } else {
                     Voice::Nova
                 });
             }
             Gender::Male => {
-                self.voice = Some(if hash % 2 == 0 {
+                self.voice = Some(if hash.is_multiple_of(2) {
                     Voice::Onyx
                 } else {
                     Voice::Echo
@@ -141,11 +176,13 @@ impl std::fmt::Debug for Message {
         f.debug_struct("Message")
             .field("message_type", &self.message_type)
             .field("content", &self.content)
+            .field("timestamp", &self.timestamp)
             .finish()
     }
 }
 
 // Enumeration for AI-generated messages with different purposes.
+#[allow(clippy::large_enum_variant)]
 pub enum AIMessage {
     Debug(String),
     Response(Result<GameMessage, AppError>),
@@ -158,6 +195,13 @@ impl Message {
         Message {
             message_type,
             content,
+            timestamp: Some(Local::now().timestamp()),
         }
     }
+
+    // The same message, dated when it was really written, e.g. from the thread history.
+    pub fn at(mut self, timestamp: i64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
 }
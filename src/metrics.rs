@@ -0,0 +1,112 @@
+// metrics.rs
+// Opt-in usage counts: which features get played with and which kinds of errors happen, never what
+// was typed, said or generated.
+//
+// Off until Usage Metrics is turned on in the settings. The counts stay in metrics.json in the data
+// folder and only leave it when the player exports them with /metrics.
+
+use crate::paths;
+use chrono::Local;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Metrics {
+    pub since: String,
+    pub features: BTreeMap<String, u64>,
+    pub errors: BTreeMap<String, u64>,
+}
+
+struct Recorder {
+    file: PathBuf,
+    metrics: Metrics,
+}
+
+static RECORDER: Lazy<Mutex<Option<Recorder>>> = Lazy::new(|| Mutex::new(None));
+
+fn recorder() -> std::sync::MutexGuard<'static, Option<Recorder>> {
+    RECORDER
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+// Where the counts are kept while Usage Metrics is on.
+pub fn default_file() -> PathBuf {
+    paths::data_dir().join("metrics.json")
+}
+
+// Start counting into a file, picking up what it already holds, or stop with None.
+pub fn configure(file: Option<PathBuf>) {
+    let mut recorder = recorder();
+    let Some(file) = file else {
+        *recorder = None;
+        return;
+    };
+    if recorder
+        .as_ref()
+        .is_some_and(|current| current.file == file)
+    {
+        return;
+    }
+    let metrics = fs::read_to_string(&file)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_else(|| Metrics {
+            since: Local::now().format("%Y-%m-%d").to_string(),
+            ..Metrics::default()
+        });
+    *recorder = Some(Recorder { file, metrics });
+}
+
+fn count(pick: impl FnOnce(&mut Metrics) -> &mut BTreeMap<String, u64>, key: &str) {
+    let mut recorder = recorder();
+    let Some(recorder) = recorder.as_mut() else {
+        return;
+    };
+    *pick(&mut recorder.metrics)
+        .entry(key.to_string())
+        .or_default() += 1;
+    let written = serde_json::to_string_pretty(&recorder.metrics)
+        .map_err(io::Error::from)
+        .and_then(|json| {
+            if let Some(parent) = recorder.file.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&recorder.file, json)
+        });
+    if let Err(e) = written {
+        log::warn!("Failed to write usage metrics: {}", e);
+    }
+}
+
+// Count one use of a feature, named by the code, e.g. "command/roll".
+pub fn record(feature: &str) {
+    count(|metrics| &mut metrics.features, feature);
+}
+
+// Count one error of a category, e.g. "Network"; the message itself is not kept.
+pub fn record_error(category: &str) {
+    count(|metrics| &mut metrics.errors, category);
+}
+
+// The counts so far, or None while Usage Metrics is off.
+pub fn snapshot() -> Option<Metrics> {
+    recorder().as_ref().map(|recorder| recorder.metrics.clone())
+}
+
+// Write the counts to a dated file in `dir`, to share by hand.
+pub fn export(dir: &Path) -> io::Result<PathBuf> {
+    let metrics = snapshot().ok_or_else(|| io::Error::other("Usage metrics are off"))?;
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!(
+        "usage-metrics-{}.json",
+        Local::now().format("%Y-%m-%d")
+    ));
+    fs::write(&path, serde_json::to_string_pretty(&metrics)?)?;
+    Ok(path)
+}
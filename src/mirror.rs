@@ -0,0 +1,110 @@
+// mirror.rs
+// Accessible copy of the transcript: every message as plain labelled text, appended to a file or
+// written to stdout so a screen reader can follow the game outside the terminal interface.
+
+use crate::message::{GameMessage, Message, MessageType, UserMessage};
+use crate::paths;
+use once_cell::sync::Lazy;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MirrorTarget {
+    Stdout,
+    File(PathBuf),
+}
+
+impl MirrorTarget {
+    // "-" means stdout, anything else is a file path.
+    pub fn from_arg(arg: &str) -> Self {
+        if arg == "-" {
+            MirrorTarget::Stdout
+        } else {
+            MirrorTarget::File(PathBuf::from(arg))
+        }
+    }
+}
+
+struct Mirror {
+    target: MirrorTarget,
+    writer: Box<dyn Write + Send>,
+}
+
+static MIRROR: Lazy<Mutex<Option<Mirror>>> = Lazy::new(|| Mutex::new(None));
+
+// Where the Mirror Output setting writes when no --mirror was given.
+pub fn default_file() -> PathBuf {
+    paths::data_dir().join("transcript.txt")
+}
+
+// Start mirroring to a target, or stop with None. Files are appended to, never truncated.
+pub fn configure(target: Option<MirrorTarget>) -> io::Result<()> {
+    let mut mirror = MIRROR
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let Some(target) = target else {
+        *mirror = None;
+        return Ok(());
+    };
+    if mirror
+        .as_ref()
+        .is_some_and(|current| current.target == target)
+    {
+        return Ok(());
+    }
+    let writer: Box<dyn Write + Send> = match &target {
+        MirrorTarget::Stdout => Box::new(io::stdout()),
+        MirrorTarget::File(path) => {
+            if let Some(parent) = path
+                .parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+            {
+                fs::create_dir_all(parent)?;
+            }
+            Box::new(OpenOptions::new().create(true).append(true).open(path)?)
+        }
+    };
+    *mirror = Some(Mirror { target, writer });
+    Ok(())
+}
+
+pub fn publish(message: &Message) {
+    let mut mirror = MIRROR
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(mirror) = mirror.as_mut() {
+        let text = plain_text(message);
+        if writeln!(mirror.writer, "{}\n", text)
+            .and_then(|_| mirror.writer.flush())
+            .is_err()
+        {
+            log::warn!("Failed to write to the transcript mirror");
+        }
+    }
+}
+
+// A message as one block of text that reads well aloud: who is speaking, then what they said.
+pub fn plain_text(message: &Message) -> String {
+    let text = match message.message_type {
+        MessageType::Game => match serde_json::from_str::<GameMessage>(&message.content) {
+            Ok(game_message) => {
+                let fluff = game_message.fluff.render();
+                let parts: Vec<&str> = [game_message.crunch.trim(), fluff.trim()]
+                    .into_iter()
+                    .filter(|part| !part.is_empty())
+                    .collect();
+                format!("Game Master:\n{}", parts.join("\n"))
+            }
+            Err(_) => format!("Game Master:\n{}", message.content.trim()),
+        },
+        MessageType::User => match serde_json::from_str::<UserMessage>(&message.content) {
+            Ok(user_message) => format!("You: {}", user_message.player_action.trim()),
+            Err(_) => format!("You: {}", message.content.trim()),
+        },
+        MessageType::System => format!("System: {}", message.content.trim()),
+    };
+    // Markdown markers would be read out as symbols.
+    text.replace("**", "").replace("### ", "")
+}
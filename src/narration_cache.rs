@@ -0,0 +1,71 @@
+// narration_cache.rs
+// Narration audio kept by what was said and how, so a line read again, after a reload or in
+// another save, is not generated and paid for twice.
+//
+// Files are named by a hash of the voice, model, speed and text. Past CACHE_LIMIT_BYTES the least
+// recently played files are deleted.
+
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+pub const CACHE_LIMIT_BYTES: u64 = 200 * 1024 * 1024;
+
+// FNV-1a, so names stay the same across builds, unlike the standard library's hasher.
+fn hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+// The name of a line's audio; `voice` and `model` as the API spells them.
+pub fn key(voice: &str, model: &str, speed: f32, text: &str) -> String {
+    let described = format!("{}\n{}\n{:.2}\n{}", voice, model, speed, text);
+    format!("{:016x}", hash(described.as_bytes()))
+}
+
+pub fn file_for(dir: &Path, key: &str) -> PathBuf {
+    dir.join(format!("{}.mp3", key))
+}
+
+// The audio already made for `key`, marked as just played so it is evicted last.
+pub fn lookup(dir: &Path, key: &str) -> Option<PathBuf> {
+    let path = file_for(dir, key);
+    if !path.is_file() {
+        return None;
+    }
+    if let Err(e) = File::options()
+        .write(true)
+        .open(&path)
+        .and_then(|file| file.set_modified(SystemTime::now()))
+    {
+        log::debug!("Could not mark {} as played: {}", path.display(), e);
+    }
+    Some(path)
+}
+
+// Delete the least recently played files until the rest fit in `limit` bytes. Returns how many
+// were deleted.
+pub fn evict(dir: &Path, limit: u64) -> io::Result<usize> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|extension| extension == "mp3") {
+            let metadata = fs::metadata(&path)?;
+            files.push((metadata.modified()?, metadata.len(), path));
+        }
+    }
+    files.sort_by_key(|(modified, _, _)| std::cmp::Reverse(*modified));
+    let mut kept = 0;
+    let mut deleted = 0;
+    for (_, size, path) in files {
+        if kept + size <= limit {
+            kept += size;
+        } else {
+            fs::remove_file(path)?;
+            deleted += 1;
+        }
+    }
+    Ok(deleted)
+}
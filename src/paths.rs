@@ -0,0 +1,240 @@
+// paths.rs
+// Where the game keeps its settings and data, following the platform's conventions.
+//
+// Settings live in the config directory, everything else (saves, audio logs, images, exports,
+// recordings and logs) in the data directory. Both can be overridden: the config directory with
+// `--config`, the data directory with `data_dir` in settings.json.
+//
+// In portable mode (`--portable`, or a `sharad.portable` file next to the executable) both live in
+// a `sharad_data` folder beside the executable instead, e.g. to play from a USB stick.
+
+use crate::settings::Settings;
+use once_cell::sync::OnceCell;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+const APP_DIR: &str = "sharad";
+// Where everything lived before this module existed, relative to the working directory.
+pub const LEGACY_DATA_DIR: &str = "./data";
+pub const PORTABLE_MARKER: &str = "sharad.portable";
+pub const PORTABLE_DIR: &str = "sharad_data";
+
+static CONFIG_DIR: OnceCell<PathBuf> = OnceCell::new();
+static DATA_DIR: OnceCell<PathBuf> = OnceCell::new();
+// The `data_dir` setting the data directory was picked from, as it only changes on a restart.
+static DATA_SETTING: OnceCell<Option<PathBuf>> = OnceCell::new();
+
+// Platform defaults for (config, data), from an environment lookup so tests can fake one.
+pub fn default_dirs(var: impl Fn(&str) -> Option<String>) -> (PathBuf, PathBuf) {
+    let var = |name: &str| {
+        var(name)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+    };
+    let home = var("HOME")
+        .or_else(|| var("USERPROFILE"))
+        .unwrap_or_else(|| PathBuf::from("."));
+
+    if cfg!(target_os = "windows") {
+        let config = var("APPDATA").unwrap_or_else(|| home.join("AppData").join("Roaming"));
+        let data = var("LOCALAPPDATA").unwrap_or_else(|| home.join("AppData").join("Local"));
+        (config.join(APP_DIR), data.join(APP_DIR))
+    } else if cfg!(target_os = "macos") && var("XDG_CONFIG_HOME").is_none() {
+        let support = home
+            .join("Library")
+            .join("Application Support")
+            .join(APP_DIR);
+        (support.clone(), support)
+    } else {
+        let config = var("XDG_CONFIG_HOME").unwrap_or_else(|| home.join(".config"));
+        let data = var("XDG_DATA_HOME").unwrap_or_else(|| home.join(".local").join("share"));
+        (config.join(APP_DIR), data.join(APP_DIR))
+    }
+}
+
+fn env_default_dirs() -> (PathBuf, PathBuf) {
+    default_dirs(|name| env::var(name).ok())
+}
+
+fn executable_dir() -> Option<PathBuf> {
+    env::current_exe().ok()?.parent().map(Path::to_path_buf)
+}
+
+// The portable data folder, when asked for with the flag or a marker file beside `exe_dir`.
+pub fn portable_dir(exe_dir: &Path, flag: bool) -> Option<PathBuf> {
+    (flag || exe_dir.join(PORTABLE_MARKER).exists()).then(|| exe_dir.join(PORTABLE_DIR))
+}
+
+// Set up both directories and move data from the legacy ./data folder. Call once at startup,
+// before anything reads settings or writes files. Returns a note for the player when data moved.
+pub fn init(config_override: Option<PathBuf>, portable: bool) -> Option<String> {
+    let (default_config, default_data) =
+        match executable_dir().and_then(|exe_dir| portable_dir(&exe_dir, portable)) {
+            Some(dir) => (dir.clone(), dir),
+            None => env_default_dirs(),
+        };
+    let config = CONFIG_DIR.get_or_init(|| config_override.unwrap_or(default_config));
+    let legacy = Path::new(LEGACY_DATA_DIR);
+
+    // Settings first: they may point the data somewhere else.
+    let mut moved = migrate_entry(legacy, config, "settings.json").unwrap_or(false);
+    let data_override = Settings::load().ok().and_then(|settings| settings.data_dir);
+    DATA_SETTING.get_or_init(|| data_override.clone());
+    let data = DATA_DIR.get_or_init(|| data_override.unwrap_or(default_data));
+
+    match migrate_legacy_data(legacy, data) {
+        Ok(count) => moved |= count > 0,
+        Err(e) => log::warn!("Failed to migrate {}: {}", legacy.display(), e),
+    }
+    moved.then(|| {
+        format!(
+            "Moved your data from {} to {}",
+            legacy.display(),
+            data.display()
+        )
+    })
+}
+
+pub fn config_dir() -> PathBuf {
+    CONFIG_DIR
+        .get()
+        .cloned()
+        .unwrap_or_else(|| env_default_dirs().0)
+}
+
+pub fn data_dir() -> PathBuf {
+    DATA_DIR
+        .get()
+        .cloned()
+        .unwrap_or_else(|| env_default_dirs().1)
+}
+
+// Whether the `data_dir` setting was changed since startup, so the data directory in use is not
+// the one it names until the game restarts.
+pub fn data_dir_changed(setting: Option<&Path>) -> bool {
+    DATA_SETTING
+        .get()
+        .is_some_and(|used| used.as_deref() != setting)
+}
+
+pub fn settings_file() -> PathBuf {
+    config_dir().join("settings.json")
+}
+
+pub fn save_dir() -> PathBuf {
+    data_dir().join("save")
+}
+
+// Logs, plus the narration audio of older versions, one folder per save.
+pub fn logs_dir() -> PathBuf {
+    data_dir().join("logs")
+}
+
+pub fn narration_dir() -> PathBuf {
+    data_dir().join("narration")
+}
+
+pub fn debug_log_file() -> PathBuf {
+    logs_dir().join("sharad_debug.log")
+}
+
+pub fn images_dir() -> PathBuf {
+    data_dir().join("images")
+}
+
+pub fn exports_dir() -> PathBuf {
+    data_dir().join("exports")
+}
+
+// Tool scripts for the assistant; see plugins.rs.
+pub fn plugins_dir() -> PathBuf {
+    data_dir().join("plugins")
+}
+
+// Campaigns' own menu art; see branding.rs.
+pub fn branding_dir() -> PathBuf {
+    data_dir().join("branding")
+}
+
+// Campaigns' house rules; see house_rules.rs.
+pub fn rules_dir() -> PathBuf {
+    data_dir().join("rules")
+}
+
+pub fn recordings_dir() -> PathBuf {
+    data_dir().join("recordings")
+}
+
+// A file of its own for each recording, so games running side by side don't overwrite each other.
+pub fn new_recording_file() -> PathBuf {
+    recordings_dir().join(format!("recording-{}.wav", Uuid::new_v4()))
+}
+
+// Move `name` from `from` into `to` unless something is already there. Returns whether it moved.
+fn migrate_entry(from: &Path, to: &Path, name: &str) -> io::Result<bool> {
+    let source = from.join(name);
+    let target = to.join(name);
+    if !source.exists() || target.exists() || same_path(from, to) {
+        return Ok(false);
+    }
+    fs::create_dir_all(to)?;
+    // Renaming fails across file systems, so fall back to copying.
+    if fs::rename(&source, &target).is_err() {
+        copy_recursive(&source, &target)?;
+        if source.is_dir() {
+            fs::remove_dir_all(&source)?;
+        } else {
+            fs::remove_file(&source)?;
+        }
+    }
+    Ok(true)
+}
+
+// Move everything left in the legacy folder into the data directory, then remove it if empty.
+// Images used to be saved straight into the legacy folder; they go to the images folder now.
+pub fn migrate_legacy_data(legacy: &Path, data: &Path) -> io::Result<usize> {
+    if !legacy.is_dir() || same_path(legacy, data) {
+        return Ok(0);
+    }
+    let mut moved = 0;
+    for entry in fs::read_dir(legacy)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        let is_image = entry.path().extension().is_some_and(|ext| ext == "png");
+        let target = if is_image {
+            data.join("images")
+        } else {
+            data.to_path_buf()
+        };
+        if migrate_entry(legacy, &target, &name)? {
+            moved += 1;
+        }
+    }
+    if fs::read_dir(legacy)?.next().is_none() {
+        fs::remove_dir(legacy)?;
+    }
+    Ok(moved)
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+fn copy_recursive(from: &Path, to: &Path) -> io::Result<()> {
+    if from.is_dir() {
+        fs::create_dir_all(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        fs::copy(from, to).map(|_| ())
+    }
+}
@@ -0,0 +1,195 @@
+// plugins.rs
+// Tools from outside the game, offered to the assistant next to the built-in ones, e.g. a custom
+// gear catalog.
+//
+// A crate registers a ToolPlugin before the game starts. A script needs no Rust: a `.json` file in
+// the plugins folder gives the tool's name, description and parameters like the files in
+// assets/assistant_functions, plus the command to run. The command reads
+// {"arguments": ..., "character": ...} on stdin and its stdout is the tool's output.
+//
+// Tools are given to an assistant when its save is created, so older saves don't see new ones.
+
+use crate::character::CharacterSheet;
+use crate::error::ShadowrunError;
+use crate::game_state::GameState;
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+// How long a script may take before the call fails.
+pub const SCRIPT_TIMEOUT: Duration = Duration::from_secs(30);
+
+// A tool as the assistant sees it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ToolDefinition {
+    pub name: String,
+    pub description: String,
+    pub parameters: Value,
+    #[serde(default)]
+    pub strict: bool,
+}
+
+#[async_trait]
+pub trait ToolPlugin: Send + Sync {
+    fn definition(&self) -> ToolDefinition;
+    // Answer a call; `arguments` is the JSON the assistant sent. The game is only read.
+    async fn call(&self, arguments: &str, game_state: &GameState)
+        -> Result<String, ShadowrunError>;
+}
+
+static PLUGINS: Lazy<RwLock<Vec<Arc<dyn ToolPlugin>>>> = Lazy::new(|| RwLock::new(Vec::new()));
+
+fn plugins() -> Vec<Arc<dyn ToolPlugin>> {
+    PLUGINS
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+// Add a tool. Its name must not be taken by a built-in tool or another plugin.
+pub fn register(plugin: Arc<dyn ToolPlugin>) -> Result<(), ShadowrunError> {
+    let name = plugin.definition().name;
+    if name.is_empty() || crate::assistant::builtin_tool_names().contains(&name) {
+        return Err(ShadowrunError::Game(format!(
+            "A plugin cannot be called '{}'",
+            name
+        )));
+    }
+    let mut plugins = PLUGINS
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if plugins.iter().any(|other| other.definition().name == name) {
+        return Err(ShadowrunError::Game(format!(
+            "A plugin called '{}' is already registered",
+            name
+        )));
+    }
+    plugins.push(plugin);
+    Ok(())
+}
+
+pub fn definitions() -> Vec<ToolDefinition> {
+    plugins().iter().map(|plugin| plugin.definition()).collect()
+}
+
+pub fn find(name: &str) -> Option<Arc<dyn ToolPlugin>> {
+    plugins()
+        .into_iter()
+        .find(|plugin| plugin.definition().name == name)
+}
+
+// A plugins folder file: a tool definition and the command that answers it.
+#[derive(Deserialize, Debug, Clone)]
+pub struct ScriptManifest {
+    #[serde(flatten)]
+    pub definition: ToolDefinition,
+    // The program and its arguments, run from the folder of the manifest.
+    pub command: Vec<String>,
+}
+
+pub struct ScriptPlugin {
+    manifest: ScriptManifest,
+    dir: PathBuf,
+}
+
+impl ScriptPlugin {
+    pub fn load(path: &Path) -> Result<Self, ShadowrunError> {
+        let manifest: ScriptManifest = serde_json::from_str(&fs::read_to_string(path)?)
+            .map_err(|e| ShadowrunError::Game(format!("{}: {}", path.display(), e)))?;
+        if manifest.command.is_empty() {
+            return Err(ShadowrunError::Game(format!(
+                "{}: the command is empty",
+                path.display()
+            )));
+        }
+        Ok(ScriptPlugin {
+            manifest,
+            dir: path.parent().unwrap_or(Path::new(".")).to_path_buf(),
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct ScriptInput<'a> {
+    arguments: Value,
+    character: Option<&'a CharacterSheet>,
+}
+
+#[async_trait]
+impl ToolPlugin for ScriptPlugin {
+    fn definition(&self) -> ToolDefinition {
+        self.manifest.definition.clone()
+    }
+
+    async fn call(
+        &self,
+        arguments: &str,
+        game_state: &GameState,
+    ) -> Result<String, ShadowrunError> {
+        let name = &self.manifest.definition.name;
+        let input = serde_json::to_vec(&ScriptInput {
+            arguments: serde_json::from_str(arguments)?,
+            character: game_state.main_character_sheet.as_ref(),
+        })?;
+        let mut child = Command::new(&self.manifest.command[0])
+            .args(&self.manifest.command[1..])
+            .current_dir(&self.dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+        // A script that doesn't read its input closes stdin early, which is fine.
+        if let Some(mut stdin) = child.stdin.take() {
+            if let Err(e) = stdin.write_all(&input).await {
+                log::debug!("{} did not read its input: {}", name, e);
+            }
+        }
+        let output = tokio::time::timeout(SCRIPT_TIMEOUT, child.wait_with_output())
+            .await
+            .map_err(|_| ShadowrunError::Game(format!("{} took too long", name)))??;
+        if !output.status.success() {
+            return Err(ShadowrunError::Game(format!(
+                "{} failed: {}",
+                name,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+}
+
+// Register every script in `dir`. Returns the names added; a broken manifest is logged and skipped.
+pub fn load_scripts(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .collect();
+    paths.sort();
+    let mut added = Vec::new();
+    for path in paths {
+        let registered = ScriptPlugin::load(&path).and_then(|plugin| {
+            let name = plugin.manifest.definition.name.clone();
+            register(Arc::new(plugin)).map(|()| name)
+        });
+        match registered {
+            Ok(name) => added.push(name),
+            Err(e) => log::warn!("Skipped the plugin {}: {}", path.display(), e),
+        }
+    }
+    added
+}
@@ -0,0 +1,228 @@
+// pool_hint.rs
+// The test the player's action probably calls for, guessed from the words they type, e.g. "I
+// shoot the guard" → Agility + Pistols. The pool comes from their sheet, so they see how many
+// dice they have before sending, and can add the test to the message for the Game Master.
+
+use crate::character::CharacterSheet;
+
+// Words that point to a test, the attribute and the skills that could go with it (the sheet's
+// best one is suggested), and the limit.
+struct Test {
+    words: &'static [&'static str],
+    attribute: &'static str,
+    skills: &'static [&'static str],
+    limit: &'static str,
+}
+
+const TESTS: &[Test] = &[
+    Test {
+        words: &["shoot", "fire at", "open fire", "snipe", "gun down"],
+        attribute: "Agility",
+        skills: &["Pistols", "Automatics", "Longarms"],
+        limit: "physical",
+    },
+    Test {
+        words: &["stab", "slash", "knife", "sword"],
+        attribute: "Agility",
+        skills: &["Blades"],
+        limit: "physical",
+    },
+    Test {
+        words: &["club", "bludgeon", "baton"],
+        attribute: "Agility",
+        skills: &["Clubs"],
+        limit: "physical",
+    },
+    Test {
+        words: &["punch", "kick", "grapple", "wrestle", "tackle", "headbutt"],
+        attribute: "Agility",
+        skills: &["Unarmed Combat"],
+        limit: "physical",
+    },
+    Test {
+        words: &["throw", "toss", "hurl"],
+        attribute: "Agility",
+        skills: &["Throwing Weapons"],
+        limit: "physical",
+    },
+    Test {
+        words: &["sneak", "creep", "hide", "tiptoe", "slip past"],
+        attribute: "Agility",
+        skills: &["Sneaking"],
+        limit: "physical",
+    },
+    Test {
+        words: &["climb", "jump", "leap", "dodge", "vault", "balance"],
+        attribute: "Agility",
+        skills: &["Gymnastics"],
+        limit: "physical",
+    },
+    Test {
+        words: &["run", "sprint", "chase", "flee"],
+        attribute: "Strength",
+        skills: &["Running"],
+        limit: "physical",
+    },
+    Test {
+        words: &["swim", "dive"],
+        attribute: "Strength",
+        skills: &["Swimming"],
+        limit: "physical",
+    },
+    Test {
+        words: &["drive", "steer", "floor it"],
+        attribute: "Reaction",
+        skills: &["Pilot Ground Craft"],
+        limit: "physical",
+    },
+    Test {
+        words: &[
+            "look", "search", "listen", "spot", "notice", "scan", "examine", "inspect",
+        ],
+        attribute: "Intuition",
+        skills: &["Perception"],
+        limit: "mental",
+    },
+    Test {
+        words: &["hack", "crack", "breach", "decrypt"],
+        attribute: "Logic",
+        skills: &["Hacking"],
+        limit: "mental",
+    },
+    Test {
+        words: &["jam", "spoof", "trace the signal"],
+        attribute: "Logic",
+        skills: &["Electronic Warfare"],
+        limit: "mental",
+    },
+    Test {
+        words: &["research", "database", "matrix search"],
+        attribute: "Logic",
+        skills: &["Computer"],
+        limit: "mental",
+    },
+    Test {
+        words: &["heal", "bandage", "patch up", "first aid", "stabilize"],
+        attribute: "Logic",
+        skills: &["First Aid"],
+        limit: "mental",
+    },
+    Test {
+        words: &["lie", "bluff", "trick", "pretend", "fast talk"],
+        attribute: "Charisma",
+        skills: &["Con"],
+        limit: "social",
+    },
+    Test {
+        words: &["persuade", "convince", "haggle", "negotiate", "bargain"],
+        attribute: "Charisma",
+        skills: &["Negotiation"],
+        limit: "social",
+    },
+    Test {
+        words: &["threaten", "intimidate", "scare", "menace"],
+        attribute: "Charisma",
+        skills: &["Intimidation"],
+        limit: "social",
+    },
+    Test {
+        words: &["blend in", "fit in", "small talk", "charm"],
+        attribute: "Charisma",
+        skills: &["Etiquette"],
+        limit: "social",
+    },
+    Test {
+        words: &["rally", "inspire", "take command"],
+        attribute: "Charisma",
+        skills: &["Leadership"],
+        limit: "social",
+    },
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolHint {
+    pub attribute: &'static str,
+    pub skill: &'static str,
+    pub pool: u8,
+    // None under a ruleset without limits.
+    pub limit: Option<u8>,
+}
+
+impl PoolHint {
+    // e.g. "Agility + Pistols: 9 dice, limit 6".
+    pub fn label(&self) -> String {
+        let mut label = format!("{} + {}: {} dice", self.attribute, self.skill, self.pool);
+        if let Some(limit) = self.limit {
+            label.push_str(&format!(", limit {}", limit));
+        }
+        label
+    }
+
+    // What is added to the message, e.g. "(Agility + Pistols, 9 dice)".
+    pub fn tag(&self) -> String {
+        format!("({} + {}, {} dice)", self.attribute, self.skill, self.pool)
+    }
+}
+
+// Whether `word` is `keyword` or one of its forms: shoots, shooting, dodged, stabbing...
+fn inflects(word: &str, keyword: &str) -> bool {
+    let stem = keyword.strip_suffix('e').unwrap_or(keyword);
+    let Some(suffix) = word.strip_prefix(stem) else {
+        return false;
+    };
+    let suffix = suffix
+        .strip_prefix(&keyword[stem.len()..])
+        .unwrap_or(suffix);
+    // A doubled last letter, as in running or stabbed.
+    let suffix = match (suffix.chars().next(), stem.chars().last()) {
+        (Some(first), Some(last)) if first == last && suffix.len() > 2 => &suffix[1..],
+        _ => suffix,
+    };
+    ["", "s", "es", "d", "ed", "ing"].contains(&suffix)
+}
+
+// Where in `words` the keyword (one word or several) first appears.
+fn position(words: &[&str], keyword: &str) -> Option<usize> {
+    let parts: Vec<&str> = keyword.split(' ').collect();
+    let (last, first) = parts.split_last()?;
+    words
+        .windows(parts.len())
+        .position(|window| window[..first.len()] == *first && inflects(window[first.len()], last))
+}
+
+// The likely test for `text`, with the pool of `sheet`, or None when nothing in it points to one.
+pub fn suggest(text: &str, sheet: &CharacterSheet) -> Option<PoolHint> {
+    if text.trim_start().starts_with('/') {
+        return None;
+    }
+    let lowered = text.to_lowercase();
+    let words: Vec<&str> = lowered
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect();
+    let test = TESTS
+        .iter()
+        .filter_map(|test| {
+            let at = test
+                .words
+                .iter()
+                .filter_map(|keyword| position(&words, keyword))
+                .min()?;
+            Some((at, test))
+        })
+        .min_by_key(|(at, _)| *at)?
+        .1;
+    let (skill, pool) = test
+        .skills
+        .iter()
+        .map(|skill| (*skill, sheet.get_dice_pool(test.attribute, skill)))
+        .max_by_key(|(_, pool)| *pool)?;
+    let hint = PoolHint {
+        attribute: test.attribute,
+        skill,
+        pool,
+        limit: sheet.limits().map(|_| sheet.get_limit(test.limit)),
+    };
+    // Already added to the message.
+    (!text.contains(&hint.tag())).then_some(hint)
+}
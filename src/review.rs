@@ -0,0 +1,64 @@
+// review.rs
+// Character updates from the AI held for the player to accept or reject, when Confirm Updates is on.
+//
+// Each update is tried on a copy of the sheet when it arrives, so the player sees what it would do
+// and a malformed update is refused to the AI straight away.
+
+use crate::character::{CharacterSheet, CharacterSheetUpdate};
+use crate::events::GameEvent;
+use crate::game_state::GameState;
+use crate::house_rules;
+use crate::ruleset::RulesetKind;
+use crate::sheet_diff::sheet_changes;
+
+#[derive(Debug, Clone)]
+pub struct PendingUpdate {
+    pub character_name: String,
+    pub update: CharacterSheetUpdate,
+    // What it would change, e.g. "Nuyen 5000 → 3500".
+    pub changes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ReviewQueue {
+    pub enabled: bool,
+    pub pending: Vec<PendingUpdate>,
+}
+
+impl ReviewQueue {
+    // Hold an update for the player. One that would change nothing isn't worth asking about.
+    pub fn hold(
+        &mut self,
+        character: &CharacterSheet,
+        update: CharacterSheetUpdate,
+        ruleset: RulesetKind,
+        save_name: &str,
+    ) -> Result<(), String> {
+        let mut preview = character.clone();
+        preview.apply_update(update.clone())?;
+        house_rules::settle(ruleset, save_name, &mut preview);
+        let changes = sheet_changes(character, &preview);
+        if !changes.is_empty() {
+            self.pending.push(PendingUpdate {
+                character_name: character.name.clone(),
+                update,
+                changes,
+            });
+        }
+        Ok(())
+    }
+}
+
+// The updates of a turn being reviewed, and the one the cursor is on.
+pub struct UpdateReview {
+    pub pending: Vec<PendingUpdate>,
+    pub selected: usize,
+}
+
+// Apply an accepted update to its character, keeping the main sheet in step.
+pub fn apply(game_state: &mut GameState, pending: &PendingUpdate) -> Result<(), String> {
+    game_state.record(GameEvent::SheetUpdate {
+        character: pending.character_name.clone(),
+        update: pending.update.clone(),
+    })
+}
@@ -0,0 +1,77 @@
+// roll_popup.rs
+// A roll made from the character sheet: Enter on a skill opens it with the skill's pool and limit,
+// the player adds a modifier for the situation, and the result goes into the conversation so the
+// Game Master can narrate it.
+
+use crate::character::CharacterSheet;
+use crate::dice::{dice_roll, ShownRoll};
+use crossterm::event::{KeyCode, KeyEvent};
+
+// Modifiers beyond this are typos.
+pub const MAX_MODIFIER: i16 = 20;
+
+pub enum RollPopupAction {
+    None,
+    Roll,
+    Close,
+}
+
+pub struct RollPopup {
+    pub character: String,
+    pub attribute: &'static str,
+    pub skill: String,
+    pub pool: u8,
+    pub limit: Option<u8>,
+    // Dice added or taken off for the situation.
+    pub modifier: i16,
+}
+
+impl RollPopup {
+    // The popup for a test of `skill`, or None if the sheet doesn't have it.
+    pub fn for_skill(sheet: &CharacterSheet, skill: &str) -> Option<Self> {
+        let (attribute, limit_type) = sheet.skill_test(skill)?;
+        Some(RollPopup {
+            character: sheet.name.clone(),
+            attribute,
+            skill: skill.to_string(),
+            pool: sheet.get_dice_pool(attribute, skill),
+            limit: limit_type
+                .filter(|_| sheet.limits().is_some())
+                .map(|limit_type| sheet.get_limit(limit_type)),
+            modifier: 0,
+        })
+    }
+
+    // The pool with the modifier, never below 0.
+    pub fn dice(&self) -> u8 {
+        (self.pool as i16 + self.modifier).clamp(0, u8::MAX as i16) as u8
+    }
+
+    // e.g. "Raven: Agility + Pistols".
+    pub fn title(&self) -> String {
+        format!("{}: {} + {}", self.character, self.attribute, self.skill)
+    }
+
+    pub fn roll(&self) -> ShownRoll {
+        let mut title = self.title();
+        if self.modifier != 0 {
+            title.push_str(&format!(" {:+}", self.modifier));
+        }
+        ShownRoll::new(title, &dice_roll(self.dice(), self.limit, None, None))
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> RollPopupAction {
+        match key.code {
+            KeyCode::Esc => return RollPopupAction::Close,
+            KeyCode::Enter => return RollPopupAction::Roll,
+            KeyCode::Left | KeyCode::Char('-') => {
+                self.modifier = (self.modifier - 1).max(-MAX_MODIFIER)
+            }
+            KeyCode::Right | KeyCode::Char('+') => {
+                self.modifier = (self.modifier + 1).min(MAX_MODIFIER)
+            }
+            _ => {}
+        }
+        RollPopupAction::None
+    }
+}
@@ -0,0 +1,203 @@
+// ruleset.rs
+// The rules a campaign is played by: initiative, condition monitors and limits. Shadowrun 5th
+// edition is the default; Sixth World has no limits, so hits are never capped. Freeform is for
+// other games: the sheet is only its named entries and the assistant is a generic game master, so
+// saves, narration and images work the same for any setting.
+//
+// A campaign takes its ruleset from the Ruleset setting when it is created and keeps it, even if
+// the setting changes later.
+
+use crate::character::CharacterSheet;
+use serde::{Deserialize, Serialize};
+
+pub const FULL_ESSENCE: f32 = 6.0;
+// Augmented or not, no one rolls more than 5D6 for initiative.
+pub const MAX_INITIATIVE_DICE: u8 = 5;
+
+pub trait Ruleset: Send + Sync {
+    fn name(&self) -> &'static str;
+    // Told to the assistant when the game starts.
+    fn briefing(&self) -> &'static str;
+    // The assistant's instructions, in assets.
+    fn instructions_file(&self) -> &'static str {
+        "assistant_instructions/instructions.json"
+    }
+    // Whether the assistant is given the built-in tool `name`. Plugins are always given.
+    fn offers_tool(&self, _name: &str) -> bool {
+        true
+    }
+    // Whether sheets have Shadowrun attributes and skills to show, or only their entries.
+    fn shadowrun_sheet(&self) -> bool {
+        true
+    }
+    // Initiative score and dice.
+    fn initiative(&self, sheet: &CharacterSheet) -> (u8, u8);
+    // Boxes of the physical and stun condition monitors.
+    fn condition_monitors(&self, sheet: &CharacterSheet) -> (u8, u8);
+    // Physical, mental and social limits, or None when hits are not capped.
+    fn limits(&self, sheet: &CharacterSheet) -> Option<(u8, u8, u8)>;
+
+    // Fill in the attributes that come from the others, the augmentations and the equipped gear.
+    // Without limits they are left at 0.
+    fn derive(&self, sheet: &mut CharacterSheet) {
+        let augmentations = sheet.augmentations();
+        // Kept to two decimals, as essence costs are written.
+        sheet.essence = ((FULL_ESSENCE - augmentations.essence).max(0.0) * 100.0).round() / 100.0;
+        sheet.initiative = self.initiative(sheet);
+        sheet.armor = sheet.equipped_armor().saturating_add(augmentations.armor);
+        (sheet.physical_monitor, sheet.stun_monitor) = self.condition_monitors(sheet);
+        (sheet.physical_limit, sheet.mental_limit, sheet.social_limit) =
+            self.limits(sheet).unwrap_or_default();
+    }
+
+    // The armor a damage resistance test counts, after the attack's armor penetration.
+    fn resistance_armor(&self, sheet: &CharacterSheet, armor_penetration: i8) -> u8 {
+        sheet.armor.saturating_sub(armor_penetration.unsigned_abs())
+    }
+
+    // The cap on hits of a roll against `limit_type` (physical, mental, social), if any.
+    fn limit(&self, sheet: &CharacterSheet, limit_type: &str) -> Option<u8> {
+        self.limits(sheet).map(|_| sheet.get_limit(limit_type))
+    }
+}
+
+pub struct Sr5;
+
+impl Ruleset for Sr5 {
+    fn name(&self) -> &'static str {
+        "Shadowrun 5th Edition"
+    }
+
+    fn briefing(&self) -> &'static str {
+        "Play by the Shadowrun 5th Edition rules."
+    }
+
+    fn initiative(&self, sheet: &CharacterSheet) -> (u8, u8) {
+        let reaction = sheet.reaction.saturating_sub(sheet.encumbrance_penalty());
+        let dice = 1 + sheet.augmentations().initiative_dice;
+        (
+            reaction.saturating_add(sheet.intuition),
+            dice.min(MAX_INITIATIVE_DICE),
+        )
+    }
+
+    fn condition_monitors(&self, sheet: &CharacterSheet) -> (u8, u8) {
+        (8 + sheet.body.div_ceil(2), 8 + sheet.willpower.div_ceil(2))
+    }
+
+    fn limits(&self, sheet: &CharacterSheet) -> Option<(u8, u8, u8)> {
+        // Worked out in f32, so attributes set out of range by the assistant cannot overflow.
+        let limit = |double: u8, first: u8, second: u8| {
+            ((double as f32 * 2.0 + first as f32 + second as f32) / 3.0).ceil() as u8
+        };
+        Some((
+            limit(sheet.strength, sheet.body, sheet.reaction),
+            limit(sheet.logic, sheet.intuition, sheet.willpower),
+            limit(sheet.charisma, sheet.willpower, sheet.essence as u8),
+        ))
+    }
+}
+
+pub struct Sr6;
+
+impl Ruleset for Sr6 {
+    fn name(&self) -> &'static str {
+        "Shadowrun Sixth World"
+    }
+
+    fn briefing(&self) -> &'static str {
+        "Play by the Shadowrun Sixth World rules: there are no limits, so hits are never capped \
+         and the limit_type of a dice roll is ignored."
+    }
+
+    fn initiative(&self, sheet: &CharacterSheet) -> (u8, u8) {
+        let reaction = sheet.reaction.saturating_sub(sheet.encumbrance_penalty());
+        let dice = 1 + sheet.augmentations().initiative_dice;
+        (
+            reaction.saturating_add(sheet.intuition),
+            dice.min(MAX_INITIATIVE_DICE),
+        )
+    }
+
+    fn condition_monitors(&self, sheet: &CharacterSheet) -> (u8, u8) {
+        (8 + sheet.body.div_ceil(2), 8 + sheet.willpower.div_ceil(2))
+    }
+
+    fn limits(&self, _sheet: &CharacterSheet) -> Option<(u8, u8, u8)> {
+        None
+    }
+
+    // Armor goes into the defense rating instead, so damage is soaked with Body alone.
+    fn resistance_armor(&self, _sheet: &CharacterSheet, _armor_penetration: i8) -> u8 {
+        0
+    }
+}
+
+// The built-in tools a freeform campaign is given; the others are about Shadowrun sheets and rolls.
+pub const FREEFORM_TOOLS: &[&str] = &[
+    "update_sheet_fields",
+    "roll_dice",
+    "generate_character_image",
+    "generate_scene_image",
+];
+
+pub struct Freeform;
+
+impl Ruleset for Freeform {
+    fn name(&self) -> &'static str {
+        "Freeform"
+    }
+
+    fn briefing(&self) -> &'static str {
+        "This is not a Shadowrun game: ask the player what they want to play, and keep every \
+         character sheet in free-form entries."
+    }
+
+    fn instructions_file(&self) -> &'static str {
+        "assistant_instructions/freeform.json"
+    }
+
+    fn offers_tool(&self, name: &str) -> bool {
+        FREEFORM_TOOLS.contains(&name)
+    }
+
+    fn shadowrun_sheet(&self) -> bool {
+        false
+    }
+
+    // A freeform sheet keeps its numbers in its entries.
+    fn initiative(&self, _sheet: &CharacterSheet) -> (u8, u8) {
+        (0, 0)
+    }
+
+    fn condition_monitors(&self, _sheet: &CharacterSheet) -> (u8, u8) {
+        (0, 0)
+    }
+
+    fn limits(&self, _sheet: &CharacterSheet) -> Option<(u8, u8, u8)> {
+        None
+    }
+}
+
+// The ruleset of a campaign, as kept in its save.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RulesetKind {
+    #[default]
+    Sr5,
+    Sr6,
+    Freeform,
+}
+
+// In the order of the Ruleset setting.
+pub const RULESETS: [RulesetKind; 3] = [RulesetKind::Sr5, RulesetKind::Sr6, RulesetKind::Freeform];
+
+impl RulesetKind {
+    pub fn rules(self) -> &'static dyn Ruleset {
+        match self {
+            RulesetKind::Sr5 => &Sr5,
+            RulesetKind::Sr6 => &Sr6,
+            RulesetKind::Freeform => &Freeform,
+        }
+    }
+}
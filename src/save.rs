@@ -1,17 +1,149 @@
-use crate::game_state::GameState;
+use crate::emergency;
+use crate::events::GameEvent;
+use crate::game_state::{GameState, PlayStats};
+use crate::journal::{self, Journal};
+use crate::message::{GameMessage, MessageType};
+use crate::paths;
 
-use chrono::Local;
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
-use std::fs::{create_dir_all, read_dir, remove_file, write, File, OpenOptions};
-use std::io::Write;
-use std::path::Path;
+use std::collections::HashMap;
+use std::fs::{self, read_dir, remove_dir_all, remove_file, rename};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
-pub const SAVE_DIR: &str = "./data/save";
+// The journal of every save written or loaded this run, shared by the clones of SaveManager.
+static JOURNALS: Lazy<Mutex<HashMap<String, Journal>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+// `name` trimmed, if a save can take it: one that is not already taken and makes a file name.
+pub fn check_save_name(name: &str, existing: &[String]) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("The name is empty.".to_string());
+    }
+    // The emergency suffix is matched on the file name, which a name like "foo.emergency" makes.
+    if name.starts_with('.')
+        || name.contains(['/', '\\'])
+        || emergency::is_emergency_file(&format!("{}.json", name))
+    {
+        return Err(format!("'{}' can't be used as a save name.", name));
+    }
+    if existing.iter().any(|save| save == name) {
+        return Err(format!("A save named '{}' already exists.", name));
+    }
+    Ok(name.to_string())
+}
+
+// The longest backstory excerpt the load menu shows.
+pub const EXCERPT_CHARS: usize = 240;
+
+// The start of `text`: its first paragraph, cut after a word when longer than EXCERPT_CHARS.
+pub fn excerpt(text: &str) -> String {
+    let paragraph = text.trim().split("\n\n").next().unwrap_or_default().trim();
+    if paragraph.chars().count() <= EXCERPT_CHARS {
+        return paragraph.to_string();
+    }
+    let cut: String = paragraph.chars().take(EXCERPT_CHARS).collect();
+    let cut = cut
+        .rsplit_once(' ')
+        .map_or(cut.as_str(), |(words, _)| words);
+    format!("{}…", cut.trim_end_matches([',', ';', ':', ' ']))
+}
+
+// What the load menu shows of a save: who plays in it, for how long, and where the story is.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SaveMetadata {
+    pub character: Option<String>,
+    pub race: Option<String>,
+    // The start of the main character's backstory; see excerpt.
+    #[serde(default)]
+    pub backstory: String,
+    // When something last happened in it, e.g. "2024-09-01 21:14".
+    pub last_played: Option<String>,
+    pub stats: PlayStats,
+    // The main character's portrait, else the latest scene.
+    pub thumbnail: Option<PathBuf>,
+    // The archivist's summary, else the latest narration.
+    pub summary: String,
+}
+
+impl SaveMetadata {
+    pub fn of(game_state: &GameState) -> Self {
+        let sheet = game_state.main_character_sheet.as_ref();
+        let latest_narration = || {
+            game_state
+                .events
+                .iter()
+                .rev()
+                .find_map(|recorded| match &recorded.event {
+                    GameEvent::Message {
+                        sender: MessageType::Game,
+                        text,
+                    } => serde_json::from_str::<GameMessage>(text).ok(),
+                    _ => None,
+                })
+                .map(|message| message.fluff.render().trim().to_string())
+                .unwrap_or_default()
+        };
+        SaveMetadata {
+            character: sheet.map(|sheet| sheet.name.clone()),
+            race: sheet.map(|sheet| sheet.race.to_string()),
+            backstory: sheet.map_or_else(String::new, |sheet| excerpt(&sheet.backstory)),
+            last_played: game_state
+                .events
+                .last()
+                .map(|recorded| recorded.at.chars().take(16).collect()),
+            stats: game_state.stats,
+            thumbnail: sheet
+                .and_then(|sheet| game_state.portrait(&sheet.name))
+                .or_else(|| game_state.scenes.last().map(|scene| &scene.path))
+                .cloned(),
+            summary: if game_state.archive.summary.is_empty() {
+                latest_narration()
+            } else {
+                game_state.archive.summary.clone()
+            },
+        }
+    }
+}
+
+// The order of the load menu. Saves it has no metadata of come last in the last two.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SaveSort {
+    #[default]
+    Name,
+    // Most recent first.
+    LastPlayed,
+    // Longest first.
+    PlayTime,
+}
+
+impl SaveSort {
+    pub fn next(self) -> Self {
+        match self {
+            SaveSort::Name => SaveSort::LastPlayed,
+            SaveSort::LastPlayed => SaveSort::PlayTime,
+            SaveSort::PlayTime => SaveSort::Name,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SaveSort::Name => "by name",
+            SaveSort::LastPlayed => "by last played",
+            SaveSort::PlayTime => "by play time",
+        }
+    }
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct SaveManager {
     pub available_saves: Vec<String>,
     pub current_save: Option<GameState>,
+    // Of the available saves: as written with each, or loaded once the menu selected it. An old
+    // save written without it, or one that didn't load, has none.
+    #[serde(default)]
+    pub metadata: HashMap<String, SaveMetadata>,
 }
 
 impl Default for SaveManager {
@@ -25,21 +157,99 @@ impl SaveManager {
         Self {
             available_saves: Self::scan_save_files(),
             current_save: None,
+            metadata: HashMap::new(),
         }
     }
 
+    // List the saves again, with what the load menu shows of each as written with it. No save
+    // is loaded for this; see describe.
+    pub fn refresh(&mut self) {
+        self.available_saves = Self::scan_save_files();
+        self.metadata = self
+            .available_saves
+            .iter()
+            .filter_map(|save_name| Some((save_name.clone(), read_metadata(save_name)?)))
+            .collect();
+    }
+
+    // Load a save the menu selected whose metadata wasn't written, and write it for next time.
+    pub fn describe(&mut self, save_name: &str) {
+        if self.metadata.contains_key(save_name)
+            || !self.available_saves.iter().any(|save| save == save_name)
+        {
+            return;
+        }
+        match Journal::load(&paths::save_dir(), save_name) {
+            Ok((_, state)) => {
+                write_metadata(&state);
+                self.metadata
+                    .insert(save_name.to_string(), SaveMetadata::of(&state));
+            }
+            Err(e) => log::warn!("Failed to describe save '{}': {}", save_name, e),
+        }
+    }
+
+    // The saves the load menu lists: those whose name or character contains `filter`, whatever
+    // the case, in the order of `sort`.
+    pub fn listed(&self, filter: &str, sort: SaveSort) -> Vec<String> {
+        let filter = filter.trim().to_lowercase();
+        let mut listed: Vec<String> = self
+            .available_saves
+            .iter()
+            .filter(|save_name| {
+                save_name.to_lowercase().contains(&filter)
+                    || self
+                        .metadata
+                        .get(*save_name)
+                        .and_then(|metadata| metadata.character.as_ref())
+                        .is_some_and(|character| character.to_lowercase().contains(&filter))
+            })
+            .cloned()
+            .collect();
+        listed.sort_by_key(|save_name| save_name.to_lowercase());
+        match sort {
+            SaveSort::Name => {}
+            SaveSort::LastPlayed => listed.sort_by_cached_key(|save_name| {
+                std::cmp::Reverse(
+                    self.metadata
+                        .get(save_name)
+                        .and_then(|metadata| metadata.last_played.clone()),
+                )
+            }),
+            SaveSort::PlayTime => listed.sort_by_key(|save_name| {
+                std::cmp::Reverse(
+                    self.metadata
+                        .get(save_name)
+                        .map(|metadata| metadata.stats.seconds),
+                )
+            }),
+        }
+        listed
+    }
+
     pub fn scan_save_files() -> Vec<String> {
-        let save_dir = Path::new(SAVE_DIR);
+        let save_dir = paths::save_dir();
         if !save_dir.exists() {
             return Vec::new();
         }
 
-        read_dir(save_dir)
-            .unwrap()
+        // An unreadable folder lists no saves rather than taking the menu down with it.
+        let entries = match read_dir(&save_dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                log::warn!("Failed to read {}: {}", save_dir.display(), e);
+                return Vec::new();
+            }
+        };
+        entries
             .filter_map(|entry| {
                 let entry = entry.ok()?;
                 let path = entry.path();
-                if path.is_file() && path.extension()? == "json" {
+                let file_name = path.file_name()?.to_str()?;
+                if path.is_file()
+                    && path.extension()? == "json"
+                    && !emergency::is_emergency_file(file_name)
+                {
                     path.file_stem()?.to_str().map(String::from)
                 } else {
                     None
@@ -49,49 +259,70 @@ impl SaveManager {
     }
 
     pub fn load_from_file(mut self, save_name: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let path = format!("{}/{}.json", SAVE_DIR, save_name);
-        let file = File::open(path).map_err(|e| {
+        let (journal, game_state) = Journal::load(&paths::save_dir(), save_name).map_err(|e| {
             eprintln!("Failed to open file: {}", e);
             e
         })?;
-
-        self.current_save = serde_json::from_reader(file)?;
-        if let Ok(mut file) = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open("sharad_debug.log")
-        {
-            let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
-            let _ = writeln!(
-                file,
-                "[{}] load_from_file:\n {:#?}",
-                timestamp, self.current_save
-            );
-        }
+        self.current_save = Some(game_state);
+        journals().insert(save_name.to_string(), journal);
+        log::debug!("Loaded save '{}'", save_name);
         Ok(self)
     }
 
     pub fn save(self) -> Result<(), std::io::Error> {
-        create_dir_all(SAVE_DIR)?;
-        let current_save = self.current_save.ok_or(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "There is no game to save",
-        ))?;
-        let save_path = format!("{}/{}.json", SAVE_DIR, current_save.save_name);
-        let serialized = serde_json::to_string_pretty(&current_save)
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
-        write(save_path, serialized)?;
+        let current_save = self
+            .current_save
+            .ok_or(std::io::Error::other("There is no game to save"))?;
+        journals()
+            .entry(current_save.save_name.clone())
+            .or_insert_with(|| Journal::new(&paths::save_dir(), &current_save.save_name))
+            .record(&current_save)?;
+        write_metadata(&current_save);
+        Ok(())
+    }
+
+    // Write the whole game over the save, e.g. when putting a recovered copy in its place.
+    pub fn save_snapshot(self) -> Result<(), std::io::Error> {
+        let current_save = self
+            .current_save
+            .ok_or(std::io::Error::other("There is no game to save"))?;
+        journals()
+            .entry(current_save.save_name.clone())
+            .or_insert_with(|| Journal::new(&paths::save_dir(), &current_save.save_name))
+            .compact(&current_save)?;
+        write_metadata(&current_save);
         Ok(())
     }
 
+    // Give a save, its backups and its narration audio another name; see journal::rename.
+    pub fn rename_save(
+        &mut self,
+        from: &str,
+        to: &str,
+    ) -> Result<GameState, Box<dyn std::error::Error>> {
+        journals().remove(from);
+        let game_state = journal::rename(&paths::save_dir(), from, to)?;
+        let audio_folder_path = paths::logs_dir().join(from);
+        if audio_folder_path.exists() {
+            rename(audio_folder_path, paths::logs_dir().join(to))?;
+        }
+        self.refresh();
+        Ok(game_state)
+    }
+
     pub fn delete_save(mut self, save_name: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let save_path = format!("{}/{}.json", SAVE_DIR, save_name);
-        let audio_folder_path = format!("./data/logs/{}", save_name);
+        let [save_path, extra_files @ ..] = journal::files_for(&paths::save_dir(), save_name);
+        let audio_folder_path = paths::logs_dir().join(save_name);
 
+        journals().remove(save_name);
+        for path in extra_files {
+            let _ = remove_file(path);
+        }
+        let _ = remove_dir_all(journal::backup_dir(&paths::save_dir(), save_name));
         match remove_file(save_path) {
             Ok(()) => {
                 delete_folder_contents(&audio_folder_path)?;
-                self.available_saves = Self::scan_save_files();
+                self.refresh();
                 Ok(())
             }
             Err(e) => Err(Box::new(e)),
@@ -99,12 +330,52 @@ impl SaveManager {
     }
 }
 
-fn delete_folder_contents(folder_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+// Keep what the load menu shows of a game next to its save. The save itself is already written,
+// so a failure here only costs the menu a load; see SaveManager::describe.
+fn write_metadata(game_state: &GameState) {
+    let path = metadata_path(&game_state.save_name);
+    let written = serde_json::to_vec(&SaveMetadata::of(game_state))
+        .map_err(std::io::Error::from)
+        .and_then(|json| fs::write(&path, json));
+    if let Err(e) = written {
+        log::warn!("Failed to write {}: {}", path.display(), e);
+    }
+}
+
+// The metadata written with a save, unless the save was written again after it.
+fn read_metadata(save_name: &str) -> Option<SaveMetadata> {
+    let dir = paths::save_dir();
+    let modified = |path: &Path| fs::metadata(path).and_then(|metadata| metadata.modified());
+    let written = modified(&metadata_path(save_name)).ok()?;
+    let stale = [
+        journal::snapshot_path(&dir, save_name),
+        journal::path_for(&dir, save_name),
+    ]
+    .iter()
+    .filter_map(|path| modified(path).ok())
+    .any(|changed| changed > written);
+    if stale {
+        return None;
+    }
+    serde_json::from_slice(&fs::read(metadata_path(save_name)).ok()?).ok()
+}
+
+fn metadata_path(save_name: &str) -> PathBuf {
+    journal::metadata_path(&paths::save_dir(), save_name)
+}
+
+fn journals() -> std::sync::MutexGuard<'static, HashMap<String, Journal>> {
+    JOURNALS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn delete_folder_contents(folder_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
     for entry in std::fs::read_dir(folder_path)? {
         let entry = entry?;
         let path = entry.path();
         if path.is_dir() {
-            delete_folder_contents(path.to_str().unwrap())?;
+            delete_folder_contents(&path)?;
             std::fs::remove_dir(path)?;
         } else {
             std::fs::remove_file(path)?;
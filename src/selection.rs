@@ -0,0 +1,103 @@
+// selection.rs
+// Text of the transcript selected with the mouse. The selection is kept in transcript lines, so it
+// stays on the same text while the transcript scrolls, and in columns of the area the lines are
+// drawn in. What it covers is worked out the way the paragraph lays the lines out: the lines are
+// already wrapped to fit, and it only trims the spaces they start with and aligns them.
+
+use ratatui::layout::Alignment;
+use ratatui::text::Line;
+use std::ops::Range;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+// A cell of the transcript: a line, counting from the first, and a column of the area.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Point {
+    pub line: usize,
+    pub column: u16,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Selection {
+    // Where the button was pressed, and where it is now.
+    pub anchor: Point,
+    pub head: Point,
+}
+
+impl Selection {
+    pub fn new(at: Point) -> Self {
+        Selection {
+            anchor: at,
+            head: at,
+        }
+    }
+
+    // A click without a drag.
+    pub fn is_empty(&self) -> bool {
+        self.anchor == self.head
+    }
+
+    // The first and last cell, in reading order.
+    pub fn ordered(&self) -> (Point, Point) {
+        (self.anchor.min(self.head), self.anchor.max(self.head))
+    }
+
+    // The columns selected on `line`, or None if it is outside the selection.
+    pub fn columns(&self, line: usize) -> Option<Range<u16>> {
+        let (start, end) = self.ordered();
+        if line < start.line || line > end.line {
+            return None;
+        }
+        let from = if line == start.line { start.column } else { 0 };
+        let to = if line == end.line {
+            end.column.saturating_add(1)
+        } else {
+            u16::MAX
+        };
+        Some(from..to)
+    }
+}
+
+// The text of `line` as drawn in an area `width` cells wide, and the column it starts at.
+pub fn drawn_line(line: &Line, width: u16) -> (String, u16) {
+    let text: String = line
+        .spans
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect();
+    let text = text.trim_start().to_string();
+    let line_width = text.width() as u16;
+    let offset = match line.alignment.unwrap_or(Alignment::Left) {
+        Alignment::Left => 0,
+        Alignment::Center => (width / 2).saturating_sub(line_width / 2),
+        Alignment::Right => width.saturating_sub(line_width),
+    };
+    (text, offset)
+}
+
+// The characters of `text`, drawn from column `offset`, that are in `columns`.
+fn cut(text: &str, offset: u16, columns: &Range<u16>) -> String {
+    let mut x = offset;
+    let mut cut = String::new();
+    for c in text.chars() {
+        let width = c.width().unwrap_or(0) as u16;
+        if x < columns.end && x + width.max(1) > columns.start {
+            cut.push(c);
+        }
+        x += width;
+    }
+    cut
+}
+
+// The selected text, from `lines`, the transcript lines from `first` on, drawn `width` wide.
+pub fn selected_text(selection: &Selection, lines: &[Line], first: usize, width: u16) -> String {
+    lines
+        .iter()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let columns = selection.columns(first + index)?;
+            let (text, offset) = drawn_line(line, width);
+            Some(cut(&text, offset, &columns).trim_end().to_string())
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
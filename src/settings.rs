@@ -1,11 +1,22 @@
 use async_openai::error::OpenAIError;
 // Import necessary libraries and modules for API interaction, file I/O, and serialization.
-use async_openai::{config::OpenAIConfig, Client};
+use async_openai::{config::OpenAIConfig, types::Voice, Client};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::io::{self, Write};
 
+use crate::backend::AgentModels;
 use crate::error::send_global_error;
+use crate::i18n::{Lang, LANGUAGES};
+use crate::paths;
+use crate::ruleset::RulesetKind;
+use std::path::PathBuf;
+
+// Where settings are loaded from and saved to.
+pub fn settings_path() -> String {
+    paths::settings_file().to_string_lossy().to_string()
+}
 
 // Define a structure to hold application settings with serialization and deserialization capabilities.
 #[derive(Serialize, Deserialize, Clone)]
@@ -15,17 +26,325 @@ pub struct Settings {
     pub audio_output_enabled: bool, // Flag to enable or disable audio output.
     pub audio_input_enabled: bool, // Flag to enable or disable audio input.
     pub debug_mode: bool, // Flag to enable or disable debug mode.
+    #[serde(default)]
+    pub spectator_mode: SpectatorMode, // Where the read-only spectator stream is served.
+    #[serde(default)]
+    pub spectator_share_sheet: bool, // Whether spectators can see the character sheet.
+    #[serde(default = "default_spectator_port")]
+    pub spectator_port: u16, // Port for the spectator stream.
+    #[serde(default)]
+    pub discord_webhook_enabled: bool, // Post the session log to a Discord channel.
+    #[serde(default)]
+    pub discord_webhook_url: Option<String>, // Discord webhook to post to.
+    #[serde(default)]
+    pub update_mode: UpdateMode, // Whether to look for, or install, new releases.
+    #[serde(default)]
+    pub log_level: LogLevel, // How much goes into the log file; --log-level overrides it.
+    #[serde(default)]
+    pub data_dir: Option<PathBuf>, // Overrides where saves, logs and recordings are kept.
+    #[serde(default)]
+    pub accessibility_mode: bool, // Plain, linear screens for screen readers.
+    #[serde(default)]
+    pub theme: Theme, // Colour palette of the interface.
+    #[serde(default)]
+    pub mirror_transcript: bool, // Append the transcript as plain text to transcript.txt.
+    #[serde(default)]
+    pub compress_saves: bool, // Gzip save snapshots instead of writing plain JSON.
+    #[serde(default = "default_backup_count")]
+    pub backup_count: usize, // Previous versions kept of each save.
+    #[serde(default)]
+    pub confirm_updates: bool, // Hold the AI's character sheet updates for the player to approve.
+    #[serde(default)]
+    pub usage_metrics: bool, // Count feature use and error kinds locally, never content.
+    #[serde(default)]
+    pub message_times: bool, // Show how long ago each message was written beside the transcript.
+    #[serde(default = "default_transcription_model")]
+    pub transcription_model: String, // Speech recognition model for voice input.
+    #[serde(default = "default_transcription_language")]
+    pub transcription_language: String, // "Auto", "Game Language" or a language from LANGUAGES.
+    #[serde(default = "default_speech_model")]
+    pub speech_model: String, // Text to speech model for the narration.
+    #[serde(default = "default_speech_speed")]
+    pub speech_speed: f32, // How fast the narration is read, 1.0 being the model's pace.
+    #[serde(default)]
+    pub narrator_voice: Option<Voice>, // The Narrator's voice, or None to pick one like any speaker.
+    #[serde(default)]
+    pub player_voice: Option<Voice>, // Reads the player's action before the narration; None for silence.
+    #[serde(default = "default_dice_animation")]
+    pub dice_animation: bool, // Tumble the dice of a roll before showing the results.
+    #[serde(default)]
+    pub ruleset: RulesetKind, // The rules new campaigns are played by.
+    #[serde(default)]
+    pub input_device: Option<String>, // Microphone by name, or None for the system default.
+    #[serde(default)]
+    pub output_device: Option<String>, // Speakers by name, or None for the system default.
+    #[serde(default = "default_chat_model")]
+    pub narrator_model: String, // Model that plays the game turns.
+    #[serde(default = "default_chat_model")]
+    pub archivist_model: String, // Model that summarizes the story for long campaigns.
+    #[serde(default)]
+    pub last_save: Option<String>, // The save opened last, which the main menu offers to continue.
+    #[serde(default = "default_menu_art")]
+    pub menu_art: bool, // Draw the art on the main and load menus.
+    #[serde(default)]
+    pub terminal_copy: TerminalCopy, // When copies are also sent to the terminal with OSC 52.
+    #[serde(default = "default_frame_rate")]
+    pub frame_rate: u32, // Frames drawn per second while something on screen moves.
+    #[serde(default = "default_tick_rate")]
+    pub tick_rate: u32, // Background updates per second: dice, images, the emergency save.
+    #[serde(default)]
+    pub speech_backend: SpeechBackend, // Who speaks the narration.
+    #[serde(default)]
+    pub elevenlabs_api_key: Option<String>,
+    // ElevenLabs voice ids by speaker name (e.g. "Narrator") or by the OpenAI voice a speaker is
+    // given (e.g. "onyx"); the others keep elevenlabs::DEFAULT_VOICES.
+    #[serde(default)]
+    pub elevenlabs_voices: HashMap<String, String>,
+    #[serde(default)]
+    pub input_style: InputStyle, // Modal or always-typing text inputs.
+    #[serde(default)]
+    pub submit_key: SubmitKey, // The chord that sends the player's message.
+    #[serde(default)]
+    pub enter_new_line: bool, // Enter starts a new line while typing the message.
+    #[serde(default = "default_mouse_selection")]
+    pub mouse_selection: bool, // Select and copy the transcript with the mouse.
+}
+
+// The chord that sends the player's message. Besides Enter, it sends right from the input.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SubmitKey {
+    #[default]
+    Enter,
+    CtrlS,
+    CtrlEnter,
+}
+
+impl SubmitKey {
+    pub fn label(self) -> &'static str {
+        match self {
+            SubmitKey::Enter => "Enter",
+            SubmitKey::CtrlS => "Ctrl+S",
+            SubmitKey::CtrlEnter => "Ctrl+Enter",
+        }
+    }
+}
+
+// How the text inputs take keys: Vim-like, with 'e' to start typing and single-key shortcuts
+// otherwise, or Simple, always typing, with Enter to send.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InputStyle {
+    #[default]
+    Vim,
+    Simple,
+}
+
+// The text to speech service the narration is generated with.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SpeechBackend {
+    #[default]
+    OpenAI,
+    ElevenLabs,
+}
+
+// Who can reach the spectator stream: nobody, this machine only, or the local network.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SpectatorMode {
+    #[default]
+    Off,
+    Local,
+    Lan,
+}
+
+// What to do when a newer release is published.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UpdateMode {
+    Off,
+    #[default]
+    CheckOnly,
+    Auto,
+}
+
+// When copied text is also written to the terminal as an OSC 52 sequence, which sets the
+// clipboard of the terminal the game is seen in, e.g. the local one over SSH.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TerminalCopy {
+    // Only without a system clipboard.
+    #[default]
+    Auto,
+    Always,
+    Off,
+}
+
+// Least to most verbose, as listed in the settings menu.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LogLevel {
+    Error,
+    #[default]
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+pub const LOG_LEVELS: [LogLevel; 5] = [
+    LogLevel::Error,
+    LogLevel::Warn,
+    LogLevel::Info,
+    LogLevel::Debug,
+    LogLevel::Trace,
+];
+
+impl LogLevel {
+    pub fn filter(self) -> log::LevelFilter {
+        match self {
+            LogLevel::Error => log::LevelFilter::Error,
+            LogLevel::Warn => log::LevelFilter::Warn,
+            LogLevel::Info => log::LevelFilter::Info,
+            LogLevel::Debug => log::LevelFilter::Debug,
+            LogLevel::Trace => log::LevelFilter::Trace,
+        }
+    }
+}
+
+// Colour palettes, as listed in the settings menu.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Theme {
+    #[default]
+    Default,
+    Colorblind,
+    HighContrast,
+}
+
+pub const THEMES: [Theme; 3] = [Theme::Default, Theme::Colorblind, Theme::HighContrast];
+
+pub const BACKUP_COUNTS: [usize; 5] = [0, 1, 3, 5, 10];
+
+pub const FRAME_RATES: [u32; 3] = [60, 30, 15];
+pub const TICK_RATES: [u32; 3] = [60, 20, 4];
+// Frames per second when nothing moves, enough for messages that expire.
+pub const IDLE_FRAME_RATE: u32 = 4;
+
+pub const TRANSCRIPTION_MODELS: [&str; 3] =
+    ["whisper-1", "gpt-4o-mini-transcribe", "gpt-4o-transcribe"];
+
+// Voice Language options before the languages themselves: let the model guess, or follow the game.
+pub const TRANSCRIPTION_AUTO: &str = "Auto";
+pub const TRANSCRIPTION_GAME_LANGUAGE: &str = "Game Language";
+
+pub const SPEECH_MODELS: [&str; 3] = ["tts-1", "tts-1-hd", "gpt-4o-mini-tts"];
+
+// Models the narrator and the archivist can run on, cheapest first.
+pub const CHAT_MODELS: [&str; 4] = ["gpt-4o-mini", "gpt-4o", "gpt-4.1-mini", "gpt-4.1"];
+
+pub const SPEECH_SPEEDS: [f32; 5] = [1.0, 1.15, 1.3, 1.5, 1.75];
+
+// The Narrator Voice options after "Auto", in the order of the menu.
+pub const NARRATOR_VOICES: [Voice; 6] = [
+    Voice::Alloy,
+    Voice::Echo,
+    Voice::Fable,
+    Voice::Onyx,
+    Voice::Nova,
+    Voice::Shimmer,
+];
+
+fn default_speech_model() -> String {
+    SPEECH_MODELS[0].to_string()
+}
+
+fn default_chat_model() -> String {
+    CHAT_MODELS[0].to_string()
+}
+
+fn default_speech_speed() -> f32 {
+    1.3
+}
+
+fn default_dice_animation() -> bool {
+    true
+}
+
+fn default_menu_art() -> bool {
+    true
+}
+
+fn default_mouse_selection() -> bool {
+    true
+}
+
+fn default_frame_rate() -> u32 {
+    FRAME_RATES[0]
+}
+
+fn default_tick_rate() -> u32 {
+    TICK_RATES[1]
+}
+
+fn default_transcription_model() -> String {
+    TRANSCRIPTION_MODELS[0].to_string()
+}
+
+fn default_transcription_language() -> String {
+    TRANSCRIPTION_AUTO.to_string()
+}
+
+fn default_spectator_port() -> u16 {
+    crate::spectator::DEFAULT_SPECTATOR_PORT
+}
+
+fn default_backup_count() -> usize {
+    3
 }
 
 // Implement the Default trait for Settings to provide a method to create default settings.
 impl Default for Settings {
     fn default() -> Self {
         Settings {
-            language: "English".to_string(), // Default language setting.
-            openai_api_key: None,            // No API key by default.
-            audio_output_enabled: true,      // Audio output enabled by default.
-            audio_input_enabled: true,       // Audio input enabled by default.
-            debug_mode: false,               // Debug mode disabled by default.
+            language: "English".to_string(),    // Default language setting.
+            openai_api_key: None,               // No API key by default.
+            audio_output_enabled: true,         // Audio output enabled by default.
+            audio_input_enabled: true,          // Audio input enabled by default.
+            debug_mode: false,                  // Debug mode disabled by default.
+            spectator_mode: SpectatorMode::Off, // Spectator stream disabled by default.
+            spectator_share_sheet: false,       // Character sheet stays private by default.
+            spectator_port: default_spectator_port(),
+            discord_webhook_enabled: false, // Webhook disabled by default.
+            discord_webhook_url: None,      // No webhook configured by default.
+            update_mode: UpdateMode::CheckOnly, // Notify about updates by default.
+            log_level: LogLevel::Warn,      // Warnings and errors only by default.
+            data_dir: None,                 // Platform data directory by default.
+            accessibility_mode: false,      // Decorated screens by default.
+            theme: Theme::Default,          // Original colours by default.
+            mirror_transcript: false,       // No transcript mirror by default.
+            compress_saves: false,          // Plain JSON saves by default.
+            backup_count: default_backup_count(),
+            confirm_updates: false, // AI updates apply straight away by default.
+            usage_metrics: false,   // Nothing is counted unless the player opts in.
+            message_times: false,
+            transcription_model: default_transcription_model(),
+            transcription_language: default_transcription_language(), // Detected per recording.
+            speech_model: default_speech_model(),
+            speech_speed: default_speech_speed(),
+            narrator_voice: None,
+            player_voice: None,
+            dice_animation: default_dice_animation(),
+            ruleset: RulesetKind::default(),
+            input_device: None,
+            output_device: None,
+            narrator_model: default_chat_model(),
+            archivist_model: default_chat_model(),
+            last_save: None,
+            menu_art: default_menu_art(),
+            terminal_copy: TerminalCopy::Auto,
+            frame_rate: default_frame_rate(),
+            tick_rate: default_tick_rate(),
+            speech_backend: SpeechBackend::OpenAI,
+            elevenlabs_api_key: None,
+            elevenlabs_voices: HashMap::new(),
+            input_style: InputStyle::Vim,
+            submit_key: SubmitKey::Enter,
+            enter_new_line: false,
+            mouse_selection: default_mouse_selection(),
         }
     }
 }
@@ -37,15 +356,34 @@ impl Settings {
         Self::default()
     }
 
+    // The model of each agent, as the backend takes them.
+    pub fn agent_models(&self) -> AgentModels {
+        AgentModels {
+            narrator: self.narrator_model.clone(),
+            archivist: self.archivist_model.clone(),
+        }
+    }
+
+    // The language code to hint voice input with, or None to let the model detect it.
+    pub fn transcription_language_code(&self) -> Option<&'static str> {
+        match self.transcription_language.as_str() {
+            TRANSCRIPTION_AUTO => None,
+            TRANSCRIPTION_GAME_LANGUAGE => Some(Lang::from_name(&self.language).code()),
+            language => LANGUAGES
+                .iter()
+                .find(|(name, _)| *name == language)
+                .map(|(_, lang)| lang.code()),
+        }
+    }
+
     // Load settings from a default file path.
     pub fn load() -> io::Result<Self> {
-        Self::load_settings_from_file("./data/settings.json")
+        Self::load_settings_from_file(&settings_path())
     }
 
     // Save current settings to a default file path.
     pub fn save(&self) -> io::Result<()> {
-        std::fs::create_dir_all("./data")?; // Ensure the data directory exists.
-        self.save_to_file("./data/settings.json")
+        self.save_to_file(&settings_path())
     }
 
     // Load settings from a specified file path.
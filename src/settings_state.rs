@@ -1,8 +1,122 @@
 // settings_state.rs
 
-use crate::settings::Settings;
+use crate::i18n::LANGUAGES;
+use crate::ruleset::RULESETS;
+use crate::settings::{
+    InputStyle, Settings, SpectatorMode, SpeechBackend, SubmitKey, TerminalCopy, UpdateMode,
+    BACKUP_COUNTS, CHAT_MODELS, FRAME_RATES, LOG_LEVELS, NARRATOR_VOICES, SPEECH_MODELS,
+    SPEECH_SPEEDS, THEMES, TICK_RATES, TRANSCRIPTION_MODELS,
+};
 use serde::{Deserialize, Serialize};
 
+// Rows of the settings menu, in display order, with the options each row cycles through.
+// Rows without options (like the API key) open their own input screen instead.
+pub const SETTINGS_ITEMS: &[(&str, &[&str])] = &[
+    ("Language", &["English", "Français", "日本語", "Türkçe"]),
+    ("OpenAI API Key", &[]),
+    ("Voice Output", &["On", "Off"]),
+    ("Voice Input", &["On", "Off"]),
+    ("Debug Mode", &["Off", "On"]),
+    ("Spectator", &["Off", "Local", "LAN"]),
+    ("Share Sheet", &["Off", "On"]),
+    ("Discord Webhook", &["Off", "On"]),
+    ("Updates", &["Off", "Check Only", "Auto"]),
+    ("Log Level", &["Error", "Warn", "Info", "Debug", "Trace"]),
+    ("Accessibility", &["Off", "On"]),
+    ("Theme", &["Default", "Colorblind", "High Contrast"]),
+    ("Mirror Output", &["Off", "File"]),
+    ("Save Compression", &["Off", "Gzip"]),
+    ("Backups", &["Off", "1", "3", "5", "10"]),
+    ("Confirm Updates", &["Off", "On"]),
+    ("Usage Metrics", &["Off", "On"]),
+    ("Message Times", &["Off", "On"]),
+    ("Voice Model", &TRANSCRIPTION_MODELS),
+    (
+        "Voice Language",
+        &[
+            "Auto",
+            "Game Language",
+            "English",
+            "Français",
+            "日本語",
+            "Türkçe",
+        ],
+    ),
+    ("Speech Model", &SPEECH_MODELS),
+    ("Speech Speed", &["1.0x", "1.15x", "1.3x", "1.5x", "1.75x"]),
+    (
+        "Narrator Voice",
+        &["Auto", "Alloy", "Echo", "Fable", "Onyx", "Nova", "Shimmer"],
+    ),
+    ("Input Device", &[]),
+    ("Output Device", &[]),
+    ("Dice Animation", &["On", "Off"]),
+    ("Ruleset", &["SR5", "SR6", "Freeform"]),
+    ("Narrator Model", &CHAT_MODELS),
+    ("Archivist Model", &CHAT_MODELS),
+    ("Menu Art", &["On", "Off"]),
+    ("Terminal Copy", &["Auto", "Always", "Off"]),
+    ("Frame Rate", &["60 fps", "30 fps", "15 fps"]),
+    ("Tick Rate", &["60/s", "20/s", "4/s"]),
+    (
+        "Player Voice",
+        &["Off", "Alloy", "Echo", "Fable", "Onyx", "Nova", "Shimmer"],
+    ),
+    ("Speech Backend", &["OpenAI", "ElevenLabs"]),
+    ("ElevenLabs API Key", &[]),
+    ("Input Mode", &["Vim", "Simple"]),
+    ("Submit Key", &["Enter", "Ctrl+S", "Ctrl+Enter"]),
+    ("Enter While Typing", &["Default", "New Line"]),
+    ("Mouse Selection", &["On", "Off"]),
+    ("Data Folder", &[]),
+    ("Logs", &[]),
+    ("Macros", &[]),
+];
+
+pub const LANGUAGE: usize = 0;
+pub const API_KEY: usize = 1;
+pub const AUDIO_OUTPUT: usize = 2;
+pub const AUDIO_INPUT: usize = 3;
+pub const DEBUG_MODE: usize = 4;
+pub const SPECTATOR: usize = 5;
+pub const SPECTATOR_SHEET: usize = 6;
+pub const WEBHOOK: usize = 7;
+pub const UPDATES: usize = 8;
+pub const LOG_LEVEL: usize = 9;
+pub const ACCESSIBILITY: usize = 10;
+pub const THEME: usize = 11;
+pub const MIRROR: usize = 12;
+pub const SAVE_COMPRESSION: usize = 13;
+pub const BACKUPS: usize = 14;
+pub const CONFIRM_UPDATES: usize = 15;
+pub const USAGE_METRICS: usize = 16;
+pub const MESSAGE_TIMES: usize = 17;
+pub const VOICE_MODEL: usize = 18;
+pub const VOICE_LANGUAGE: usize = 19;
+pub const SPEECH_MODEL: usize = 20;
+pub const SPEECH_SPEED: usize = 21;
+pub const NARRATOR_VOICE: usize = 22;
+pub const INPUT_DEVICE: usize = 23;
+pub const OUTPUT_DEVICE: usize = 24;
+pub const DICE_ANIMATION: usize = 25;
+pub const RULESET: usize = 26;
+pub const NARRATOR_MODEL: usize = 27;
+pub const ARCHIVIST_MODEL: usize = 28;
+pub const MENU_ART: usize = 29;
+pub const TERMINAL_COPY: usize = 30;
+pub const FRAME_RATE: usize = 31;
+pub const TICK_RATE: usize = 32;
+pub const PLAYER_VOICE: usize = 33;
+pub const SPEECH_BACKEND: usize = 34;
+pub const ELEVENLABS_KEY: usize = 35;
+pub const INPUT_STYLE: usize = 36;
+pub const SUBMIT_KEY: usize = 37;
+pub const ENTER_NEW_LINE: usize = 38;
+pub const MOUSE_SELECTION: usize = 39;
+pub const DATA_DIR: usize = 40;
+pub const LOGS: usize = 41;
+pub const MACROS: usize = 42;
+
 #[derive(Serialize, Deserialize)]
 pub struct SettingsState {
     pub selected_setting: usize,
@@ -14,17 +128,146 @@ impl SettingsState {
         SettingsState {
             selected_setting: 0,
             selected_options: vec![
-                match settings.language.as_str() {
-                    "English" => 0,
-                    "Français" => 1,
-                    "日本語" => 2,
-                    _ => 0,
-                },
+                LANGUAGES
+                    .iter()
+                    .position(|(language, _)| *language == settings.language)
+                    .unwrap_or(0),
                 0, // API Key (always 0 as it's not a toggle)
                 if settings.audio_output_enabled { 0 } else { 1 },
                 if settings.audio_input_enabled { 0 } else { 1 },
                 if settings.debug_mode { 1 } else { 0 },
+                match settings.spectator_mode {
+                    SpectatorMode::Off => 0,
+                    SpectatorMode::Local => 1,
+                    SpectatorMode::Lan => 2,
+                },
+                if settings.spectator_share_sheet { 1 } else { 0 },
+                if settings.discord_webhook_enabled {
+                    1
+                } else {
+                    0
+                },
+                match settings.update_mode {
+                    UpdateMode::Off => 0,
+                    UpdateMode::CheckOnly => 1,
+                    UpdateMode::Auto => 2,
+                },
+                LOG_LEVELS
+                    .iter()
+                    .position(|level| *level == settings.log_level)
+                    .unwrap_or(1),
+                if settings.accessibility_mode { 1 } else { 0 },
+                THEMES
+                    .iter()
+                    .position(|theme| *theme == settings.theme)
+                    .unwrap_or(0),
+                if settings.mirror_transcript { 1 } else { 0 },
+                if settings.compress_saves { 1 } else { 0 },
+                BACKUP_COUNTS
+                    .iter()
+                    .position(|count| *count == settings.backup_count)
+                    .unwrap_or(2),
+                if settings.confirm_updates { 1 } else { 0 },
+                if settings.usage_metrics { 1 } else { 0 },
+                if settings.message_times { 1 } else { 0 },
+                TRANSCRIPTION_MODELS
+                    .iter()
+                    .position(|model| *model == settings.transcription_model)
+                    .unwrap_or(0),
+                SETTINGS_ITEMS[VOICE_LANGUAGE]
+                    .1
+                    .iter()
+                    .position(|language| *language == settings.transcription_language)
+                    .unwrap_or(0),
+                SPEECH_MODELS
+                    .iter()
+                    .position(|model| *model == settings.speech_model)
+                    .unwrap_or(0),
+                SPEECH_SPEEDS
+                    .iter()
+                    .position(|speed| (speed - settings.speech_speed).abs() < 0.01)
+                    .unwrap_or(2),
+                settings.narrator_voice.as_ref().map_or(0, |voice| {
+                    NARRATOR_VOICES
+                        .iter()
+                        .position(|option| option == voice)
+                        .map_or(0, |index| index + 1)
+                }),
+                0, // Input Device (kept by name in the settings)
+                0, // Output Device (kept by name in the settings)
+                if settings.dice_animation { 0 } else { 1 },
+                RULESETS
+                    .iter()
+                    .position(|ruleset| *ruleset == settings.ruleset)
+                    .unwrap_or(0),
+                CHAT_MODELS
+                    .iter()
+                    .position(|model| *model == settings.narrator_model)
+                    .unwrap_or(0),
+                CHAT_MODELS
+                    .iter()
+                    .position(|model| *model == settings.archivist_model)
+                    .unwrap_or(0),
+                if settings.menu_art { 0 } else { 1 },
+                match settings.terminal_copy {
+                    TerminalCopy::Auto => 0,
+                    TerminalCopy::Always => 1,
+                    TerminalCopy::Off => 2,
+                },
+                FRAME_RATES
+                    .iter()
+                    .position(|rate| *rate == settings.frame_rate)
+                    .unwrap_or(0),
+                TICK_RATES
+                    .iter()
+                    .position(|rate| *rate == settings.tick_rate)
+                    .unwrap_or(1),
+                settings.player_voice.as_ref().map_or(0, |voice| {
+                    NARRATOR_VOICES
+                        .iter()
+                        .position(|option| option == voice)
+                        .map_or(0, |index| index + 1)
+                }),
+                match settings.speech_backend {
+                    SpeechBackend::OpenAI => 0,
+                    SpeechBackend::ElevenLabs => 1,
+                },
+                0, // ElevenLabs API Key (opens its input screen)
+                match settings.input_style {
+                    InputStyle::Vim => 0,
+                    InputStyle::Simple => 1,
+                },
+                match settings.submit_key {
+                    SubmitKey::Enter => 0,
+                    SubmitKey::CtrlS => 1,
+                    SubmitKey::CtrlEnter => 2,
+                },
+                settings.enter_new_line as usize,
+                if settings.mouse_selection { 0 } else { 1 },
+                0, // Data Folder (opens its own input screen)
+                0, // Logs (opens the log viewer)
+                0, // Macros (opens the macro manager)
             ],
         }
     }
+
+    pub fn option_count(&self, setting: usize) -> usize {
+        SETTINGS_ITEMS
+            .get(setting)
+            .map_or(0, |(_, options)| options.len())
+    }
+
+    // Step the selected option of a row forward or backward, wrapping around.
+    pub fn cycle_option(&mut self, setting: usize, forward: bool) {
+        let count = self.option_count(setting);
+        if count == 0 {
+            return;
+        }
+        let current = self.selected_options[setting];
+        self.selected_options[setting] = if forward {
+            (current + 1) % count
+        } else {
+            (current + count - 1) % count
+        };
+    }
 }
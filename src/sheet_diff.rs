@@ -0,0 +1,103 @@
+// sheet_diff.rs
+// What changed on a character sheet over a turn, in words, e.g. "Nuyen 5000 → 3500".
+//
+// Sheets are compared in their saved form, so new fields show up without changes here.
+
+use crate::character::CharacterSheet;
+use serde_json::Value;
+
+pub fn sheet_changes(old: &CharacterSheet, new: &CharacterSheet) -> Vec<String> {
+    let mut changes = Vec::new();
+    match (serde_json::to_value(old), serde_json::to_value(new)) {
+        (Ok(old), Ok(new)) => compare("", &old, &new, &mut changes),
+        _ => log::warn!("Could not compare character sheets"),
+    }
+    changes
+}
+
+// "physical_monitor" reads as "Physical Monitor"; map keys like "Ares Predator" are kept as is.
+fn label(key: &str) -> String {
+    key.split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn show(value: &Value) -> String {
+    match value {
+        Value::Null => "none".to_string(),
+        Value::String(text) => text.clone(),
+        // Essence is a float, and 5.8 should not read as 5.800000190734863.
+        Value::Number(number) => match number.as_f64() {
+            Some(float) if !number.is_u64() && !number.is_i64() => {
+                format!("{}", (float * 100.0).round() / 100.0)
+            }
+            _ => number.to_string(),
+        },
+        Value::Array(items) => items.iter().map(show).collect::<Vec<_>>().join(", "),
+        Value::Object(map) => map.get("name").map_or_else(|| value.to_string(), show),
+        other => other.to_string(),
+    }
+}
+
+// A list entry by its name, so reordering isn't reported.
+fn entry_name(value: &Value) -> String {
+    match value {
+        Value::Object(map) => map.get("name").map_or_else(|| show(value), show),
+        _ => show(value),
+    }
+}
+
+fn compare(path: &str, old: &Value, new: &Value, changes: &mut Vec<String>) {
+    if old == new {
+        return;
+    }
+    match (old, new) {
+        (Value::Object(old_map), Value::Object(new_map)) => {
+            // Fields of a named entry, like an item's quantity, say whose they are.
+            let field = |key: &String| {
+                if path.is_empty() {
+                    label(key)
+                } else if new_map.contains_key("name") {
+                    format!("{} {}", path, key)
+                } else {
+                    key.clone()
+                }
+            };
+            for (key, new_value) in new_map {
+                let field = field(key);
+                match old_map.get(key) {
+                    Some(old_value) => compare(&field, old_value, new_value, changes),
+                    None => changes.push(format!("added {}", field)),
+                }
+            }
+            for key in old_map.keys().filter(|key| !new_map.contains_key(*key)) {
+                changes.push(format!("removed {}", field(key)));
+            }
+        }
+        // Qualities, cyberware and bioware are sets of named entries; the initiative pair is a value.
+        (Value::Array(old_items), Value::Array(new_items))
+            if !old_items.iter().chain(new_items).all(Value::is_number) =>
+        {
+            let old_names: Vec<String> = old_items.iter().map(entry_name).collect();
+            let new_names: Vec<String> = new_items.iter().map(entry_name).collect();
+            for name in new_names.iter().filter(|name| !old_names.contains(name)) {
+                changes.push(format!("added {}", name));
+            }
+            for name in old_names.iter().filter(|name| !new_names.contains(name)) {
+                changes.push(format!("removed {}", name));
+            }
+        }
+        (Value::String(_), Value::String(new_text)) if new_text.len() > 40 => {
+            changes.push(format!("{} changed", path));
+        }
+        _ => changes.push(format!("{} {} → {}", path, show(old), show(new))),
+    }
+}
@@ -0,0 +1,206 @@
+// shop.rs
+// A gear shop for the player's character. Buying makes the same nuyen and inventory updates the
+// assistant's tools make, so the sheet, the save and the events can't tell the two apart, and the
+// assistant is told about each purchase so the story knows.
+//
+// The catalog ships in assets/gear/catalog.json; a gear.json next to settings.json replaces it.
+
+use crate::character::{
+    AttributeKey, CharacterSheet, CharacterSheetUpdate, Item, UpdateOperation, Value,
+};
+use crate::paths;
+use crossterm::event::{KeyCode, KeyEvent};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use thiserror::Error;
+
+const DEFAULT_CATALOG: &str = include_str!("../assets/gear/catalog.json");
+// The rarest gear sold over the counter; anything above has to be found in the game.
+pub const MAX_AVAILABILITY: u8 = 12;
+pub const MAX_QUANTITY: u32 = 99;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Legality {
+    #[default]
+    #[serde(rename = "")]
+    Legal,
+    #[serde(rename = "R")]
+    Restricted,
+    #[serde(rename = "F")]
+    Forbidden,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GearItem {
+    pub name: String,
+    pub category: String,
+    pub cost: u32,
+    pub availability: u8,
+    #[serde(default)]
+    pub legality: Legality,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub reach: Option<u8>,
+    #[serde(default)]
+    pub recoil_compensation: Option<u8>,
+    #[serde(default)]
+    pub weight: Option<f32>,
+}
+
+impl GearItem {
+    // Availability as the books write it, e.g. 5R.
+    pub fn availability_label(&self) -> String {
+        let legality = match self.legality {
+            Legality::Legal => "",
+            Legality::Restricted => "R",
+            Legality::Forbidden => "F",
+        };
+        format!("{}{}", self.availability, legality)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Catalog {
+    pub items: Vec<GearItem>,
+}
+
+pub fn catalog_path() -> PathBuf {
+    paths::config_dir().join("gear.json")
+}
+
+impl Catalog {
+    pub fn builtin() -> Self {
+        serde_json::from_str(DEFAULT_CATALOG).expect("the built-in gear catalog is valid")
+    }
+
+    // The player's gear.json if there is one, else the built-in catalog.
+    pub fn load() -> Result<Self, String> {
+        match fs::read_to_string(catalog_path()) {
+            Ok(data) => serde_json::from_str(&data)
+                .map_err(|e| format!("{} is not a valid catalog: {}", catalog_path().display(), e)),
+            Err(_) => Ok(Self::builtin()),
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ShopError {
+    #[error("{item} costs {cost}¥ and {character} only has {nuyen}¥")]
+    NotEnoughNuyen {
+        item: String,
+        character: String,
+        cost: u64,
+        nuyen: u32,
+    },
+    #[error(
+        "{item} has availability {availability}, too rare for the shop; ask around in the game"
+    )]
+    Unavailable { item: String, availability: String },
+}
+
+// The sheet updates for buying `quantity` of `item`, if the character can.
+pub fn purchase(
+    item: &GearItem,
+    quantity: u32,
+    sheet: &CharacterSheet,
+) -> Result<Vec<CharacterSheetUpdate>, ShopError> {
+    if item.availability > MAX_AVAILABILITY {
+        return Err(ShopError::Unavailable {
+            item: item.name.clone(),
+            availability: item.availability_label(),
+        });
+    }
+    let cost = item.cost as u64 * quantity as u64;
+    if cost > sheet.nuyen as u64 {
+        return Err(ShopError::NotEnoughNuyen {
+            item: item.name.clone(),
+            character: sheet.name.clone(),
+            cost,
+            nuyen: sheet.nuyen,
+        });
+    }
+    let bought = Item {
+        reach: item.reach,
+        recoil_compensation: item.recoil_compensation,
+        weight: item.weight,
+        ..Item::new(&item.name, quantity, &item.description)
+    };
+    Ok(vec![
+        CharacterSheetUpdate::UpdateAttribute {
+            attribute: AttributeKey::Nuyen,
+            operation: UpdateOperation::Remove(Value::U32(cost as u32)),
+        },
+        CharacterSheetUpdate::UpdateAttribute {
+            attribute: AttributeKey::Inventory,
+            operation: UpdateOperation::Add(Value::HashMapStringItem(HashMap::from([(
+                item.name.clone(),
+                bought,
+            )]))),
+        },
+    ])
+}
+
+// What the assistant is told after a purchase.
+pub fn purchase_note(character: &str, item: &GearItem, quantity: u32) -> String {
+    format!(
+        "Between turns, {} bought {} x {} ({}) for {}¥ in the gear shop. The sheet is already \
+         updated: the nuyen is spent and the gear is in the inventory. Take it into account from \
+         now on, without answering this message.",
+        character,
+        quantity,
+        item.name,
+        item.category,
+        item.cost as u64 * quantity as u64
+    )
+}
+
+pub enum ShopAction {
+    None,
+    Buy(usize),
+    Close,
+}
+
+// State of the shop screen: the catalog, the item picked and how many of it.
+pub struct Shop {
+    pub catalog: Catalog,
+    pub selected: usize,
+    pub quantity: u32,
+    pub message: Option<String>,
+}
+
+impl Shop {
+    pub fn new(catalog: Catalog) -> Self {
+        Shop {
+            catalog,
+            selected: 0,
+            quantity: 1,
+            message: None,
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> ShopAction {
+        let len = self.catalog.items.len();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => return ShopAction::Close,
+            KeyCode::Up if len > 0 => {
+                self.selected = (self.selected + len - 1) % len;
+                self.quantity = 1;
+            }
+            KeyCode::Down if len > 0 => {
+                self.selected = (self.selected + 1) % len;
+                self.quantity = 1;
+            }
+            KeyCode::Left | KeyCode::Char('-') => self.quantity = self.quantity.max(2) - 1,
+            KeyCode::Right | KeyCode::Char('+') => {
+                self.quantity = (self.quantity + 1).min(MAX_QUANTITY)
+            }
+            KeyCode::Enter if len > 0 => return ShopAction::Buy(self.selected),
+            _ => return ShopAction::None,
+        }
+        self.message = None;
+        ShopAction::None
+    }
+}
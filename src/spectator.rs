@@ -0,0 +1,429 @@
+// spectator.rs
+// Read-only HTTP/SSE stream of the live session so friends can follow along in a browser.
+
+use crate::character::CharacterSheet;
+use crate::message::{GameMessage, Message, MessageType, UserMessage};
+use serde::Serialize;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+pub const DEFAULT_SPECTATOR_PORT: u16 = 8642;
+
+// Requests larger than this are not something a browser would send us.
+const MAX_REQUEST_SIZE: usize = 8 * 1024;
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+// A client that hasn't sent its request by then is dropped rather than holding a task open.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SpectatorEvent {
+    Message {
+        message_type: MessageType,
+        text: String,
+        crunch: Option<String>,
+    },
+    Sheet {
+        sheet: Box<CharacterSheet>,
+    },
+    // Sent when the transcript is replaced (new game, load) so the page starts over.
+    Reset,
+}
+
+impl SpectatorEvent {
+    // Turn a transcript entry into what spectators see, mirroring the in-game transcript.
+    pub fn from_message(message: &Message) -> Self {
+        let (text, crunch) = match message.message_type {
+            MessageType::Game => match serde_json::from_str::<GameMessage>(&message.content) {
+                Ok(game_message) => (
+                    game_message.fluff.render().trim().to_string(),
+                    Some(game_message.crunch),
+                ),
+                Err(_) => (message.content.clone(), None),
+            },
+            MessageType::User => match serde_json::from_str::<UserMessage>(&message.content) {
+                Ok(user_message) => (user_message.player_action, None),
+                Err(_) => (message.content.clone(), None),
+            },
+            MessageType::System => (message.content.clone(), None),
+        };
+        SpectatorEvent::Message {
+            message_type: message.message_type.clone(),
+            text,
+            crunch,
+        }
+    }
+}
+
+// Events go out numbered, so a stream can skip those already in the snapshot it sent.
+type Numbered = (u64, SpectatorEvent);
+
+#[derive(Default)]
+struct SpectatorShared {
+    history: Vec<SpectatorEvent>,
+    sheet: Option<CharacterSheet>,
+    // The number of the last event sent; the history and sheet include everything up to it.
+    seq: u64,
+}
+
+impl SpectatorShared {
+    // Sent while the lock is held, so the numbers go out in order.
+    fn send(&mut self, sender: &broadcast::Sender<Numbered>, event: SpectatorEvent) {
+        self.seq += 1;
+        // No receivers just means nobody is watching right now.
+        let _ = sender.send((self.seq, event));
+    }
+}
+
+pub struct SpectatorServer {
+    pub address: SocketAddr,
+    share_sheet: bool,
+    sender: broadcast::Sender<Numbered>,
+    shared: Arc<Mutex<SpectatorShared>>,
+    handle: JoinHandle<()>,
+}
+
+impl SpectatorServer {
+    // Bind right away so a busy port is reported to the caller instead of failing silently.
+    pub fn start(address: SocketAddr, share_sheet: bool) -> io::Result<Self> {
+        let listener = std::net::TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        let listener = TcpListener::from_std(listener)?;
+        let address = listener.local_addr()?;
+
+        let (sender, _) = broadcast::channel(256);
+        let shared = Arc::new(Mutex::new(SpectatorShared::default()));
+
+        let handle = tokio::spawn(accept_loop(
+            listener,
+            sender.clone(),
+            shared.clone(),
+            share_sheet,
+        ));
+
+        Ok(SpectatorServer {
+            address,
+            share_sheet,
+            sender,
+            shared,
+            handle,
+        })
+    }
+
+    pub fn share_sheet(&self) -> bool {
+        self.share_sheet
+    }
+
+    pub fn publish_message(&self, message: &Message) {
+        let event = SpectatorEvent::from_message(message);
+        let mut shared = self.shared.lock().unwrap();
+        shared.history.push(event.clone());
+        shared.send(&self.sender, event);
+    }
+
+    pub fn publish_sheet(&self, sheet: &CharacterSheet) {
+        if !self.share_sheet {
+            return;
+        }
+        let mut shared = self.shared.lock().unwrap();
+        shared.sheet = Some(sheet.clone());
+        shared.send(
+            &self.sender,
+            SpectatorEvent::Sheet {
+                sheet: Box::new(sheet.clone()),
+            },
+        );
+    }
+
+    // Replace the whole transcript, e.g. after loading a save.
+    pub fn reset(&self, messages: &[Message], sheet: Option<&CharacterSheet>) {
+        let mut shared = self.shared.lock().unwrap();
+        shared.history = messages.iter().map(SpectatorEvent::from_message).collect();
+        shared.sheet = if self.share_sheet {
+            sheet.cloned()
+        } else {
+            None
+        };
+        shared.send(&self.sender, SpectatorEvent::Reset);
+    }
+}
+
+impl Drop for SpectatorServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+async fn accept_loop(
+    listener: TcpListener,
+    sender: broadcast::Sender<Numbered>,
+    shared: Arc<Mutex<SpectatorShared>>,
+    share_sheet: bool,
+) {
+    // Connection tasks are tracked so stopping the server also closes open streams.
+    let mut connections = tokio::task::JoinSet::new();
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let stream = match accepted {
+                    Ok((stream, _)) => stream,
+                    // Out of file descriptors, say: retrying at once would only spin until it
+                    // passes, so wait a little first.
+                    Err(e) => {
+                        log::warn!("Spectator stream failed to accept a connection: {}", e);
+                        tokio::time::sleep(ACCEPT_RETRY_DELAY).await;
+                        continue;
+                    }
+                };
+                connections.spawn(handle_connection(
+                    stream,
+                    sender.subscribe(),
+                    shared.clone(),
+                    share_sheet,
+                ));
+            }
+            Some(_) = connections.join_next(), if !connections.is_empty() => {}
+        }
+    }
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    receiver: broadcast::Receiver<Numbered>,
+    shared: Arc<Mutex<SpectatorShared>>,
+    share_sheet: bool,
+) -> io::Result<()> {
+    let Ok(request) = tokio::time::timeout(REQUEST_TIMEOUT, read_request_line(&mut stream)).await
+    else {
+        return write_response(
+            &mut stream,
+            "408 Request Timeout",
+            "text/plain",
+            "Request timeout",
+        )
+        .await;
+    };
+    let Some((method, path)) = request? else {
+        return write_response(&mut stream, "400 Bad Request", "text/plain", "Bad request").await;
+    };
+
+    if method != "GET" {
+        return write_response(
+            &mut stream,
+            "405 Method Not Allowed",
+            "text/plain",
+            "Spectator stream is read-only",
+        )
+        .await;
+    }
+
+    // Ignore any query string.
+    match path.split('?').next().unwrap_or_default() {
+        "/" => {
+            write_response(
+                &mut stream,
+                "200 OK",
+                "text/html; charset=utf-8",
+                SPECTATOR_PAGE,
+            )
+            .await
+        }
+        "/events" => stream_events(stream, receiver, shared).await,
+        "/sheet" if share_sheet => {
+            let sheet = shared.lock().unwrap().sheet.clone();
+            let body = serde_json::to_string(&sheet)?;
+            write_response(&mut stream, "200 OK", "application/json", &body).await
+        }
+        _ => write_response(&mut stream, "404 Not Found", "text/plain", "Not found").await,
+    }
+}
+
+// Read the request head and return its method and path. The rest of the headers are not needed.
+async fn read_request_line(stream: &mut TcpStream) -> io::Result<Option<(String, String)>> {
+    let mut buffer = Vec::with_capacity(1024);
+    let mut chunk = [0u8; 1024];
+    while !buffer.windows(4).any(|w| w == b"\r\n\r\n") {
+        if buffer.len() > MAX_REQUEST_SIZE {
+            return Ok(None);
+        }
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+
+    let head = String::from_utf8_lossy(&buffer);
+    let mut parts = head.lines().next().unwrap_or_default().split_whitespace();
+    match (parts.next(), parts.next()) {
+        (Some(method), Some(path)) => Ok(Some((method.to_string(), path.to_string()))),
+        _ => Ok(None),
+    }
+}
+
+async fn write_response(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &str,
+) -> io::Result<()> {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+async fn stream_events(
+    mut stream: TcpStream,
+    mut receiver: broadcast::Receiver<Numbered>,
+    shared: Arc<Mutex<SpectatorShared>>,
+) -> io::Result<()> {
+    stream
+        .write_all(
+            b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n",
+        )
+        .await?;
+
+    let mut sent = send_snapshot(&mut stream, &shared).await?;
+
+    let mut keep_alive = tokio::time::interval(KEEP_ALIVE_INTERVAL);
+    loop {
+        tokio::select! {
+            event = receiver.recv() => match event {
+                // Already in the last snapshot.
+                Ok((seq, _)) if seq <= sent => {}
+                // A reset replaces the history, so replay the new one from the shared state.
+                Ok((_, SpectatorEvent::Reset)) | Err(broadcast::error::RecvError::Lagged(_)) => {
+                    sent = send_snapshot(&mut stream, &shared).await?;
+                }
+                Ok((seq, event)) => {
+                    send_event(&mut stream, &event).await?;
+                    sent = seq;
+                }
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            },
+            _ = keep_alive.tick() => {
+                stream.write_all(b": keep-alive\n\n").await?;
+            }
+        }
+    }
+}
+
+// Send the page everything it should show now, and return the number of the last event it has.
+async fn send_snapshot(
+    stream: &mut TcpStream,
+    shared: &Arc<Mutex<SpectatorShared>>,
+) -> io::Result<u64> {
+    let mut events = vec![SpectatorEvent::Reset];
+    let seq = {
+        let shared = shared.lock().unwrap();
+        events.extend(shared.history.iter().cloned());
+        if let Some(sheet) = &shared.sheet {
+            events.push(SpectatorEvent::Sheet {
+                sheet: Box::new(sheet.clone()),
+            });
+        }
+        shared.seq
+    };
+    for event in events.iter() {
+        send_event(stream, event).await?;
+    }
+    Ok(seq)
+}
+
+async fn send_event(stream: &mut TcpStream, event: &SpectatorEvent) -> io::Result<()> {
+    // serde_json never emits raw newlines, so one `data:` line per event is enough.
+    let payload = serde_json::to_string(event)?;
+    stream
+        .write_all(format!("data: {}\n\n", payload).as_bytes())
+        .await
+}
+
+const SPECTATOR_PAGE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Sharad - Spectator</title>
+<style>
+  body { background: #0b0b0b; color: #d0d0d0; font-family: monospace; margin: 0; display: flex; height: 100vh; }
+  #transcript { flex: 3; overflow-y: auto; padding: 1em 2em; border-right: 1px solid #333; }
+  #sheet { flex: 2; overflow-y: auto; padding: 1em 2em; display: none; }
+  h1 { color: #2ee88b; font-size: 1.2em; }
+  .msg { white-space: pre-wrap; margin: 1em 0; }
+  .Game { color: #2ee88b; }
+  .User { color: #34d3e0; text-align: right; }
+  .System { color: #e0c434; text-align: center; }
+  .crunch { color: #777; font-size: 0.9em; }
+  #status { color: #777; font-size: 0.8em; }
+  table { border-collapse: collapse; }
+  td { padding: 0 1em 0 0; }
+</style>
+</head>
+<body>
+<div id="transcript"><h1>Sharad - Spectator</h1><div id="status">Connecting...</div><div id="messages"></div></div>
+<div id="sheet"></div>
+<script>
+  const messages = document.getElementById("messages");
+  const sheet = document.getElementById("sheet");
+  const status = document.getElementById("status");
+  const transcript = document.getElementById("transcript");
+
+  function el(tag, cls, text) {
+    const node = document.createElement(tag);
+    if (cls) node.className = cls;
+    if (text !== undefined) node.textContent = text;
+    return node;
+  }
+
+  function renderSheet(s) {
+    sheet.style.display = "block";
+    sheet.replaceChildren(el("h1", null, s.name + " (" + s.race + ")"));
+    const table = el("table");
+    const rows = [
+      ["Body", s.body], ["Agility", s.agility], ["Reaction", s.reaction], ["Strength", s.strength],
+      ["Willpower", s.willpower], ["Logic", s.logic], ["Intuition", s.intuition], ["Charisma", s.charisma],
+      ["Edge", s.edge], ["Essence", s.essence], ["Nuyen", s.nuyen],
+      ["Physical", s.physical_monitor], ["Stun", s.stun_monitor],
+    ];
+    for (const [label, value] of rows) {
+      const tr = el("tr");
+      tr.append(el("td", null, label), el("td", null, String(value)));
+      table.append(tr);
+    }
+    sheet.append(table);
+    if (s.backstory) sheet.append(el("p", "msg", s.backstory));
+  }
+
+  const source = new EventSource("/events");
+  source.onopen = () => { status.textContent = "Live"; };
+  source.onerror = () => { status.textContent = "Disconnected, retrying..."; };
+  source.onmessage = (e) => {
+    const event = JSON.parse(e.data);
+    if (event.kind === "reset") {
+      messages.replaceChildren();
+    } else if (event.kind === "sheet") {
+      renderSheet(event.sheet);
+    } else if (event.kind === "message") {
+      const stick = transcript.scrollTop + transcript.clientHeight >= transcript.scrollHeight - 20;
+      const node = el("div", "msg " + event.message_type, event.text);
+      if (event.crunch) node.prepend(el("div", "crunch", event.crunch));
+      messages.append(node);
+      if (stick) transcript.scrollTop = transcript.scrollHeight;
+    }
+  };
+</script>
+</body>
+</html>
+"#;
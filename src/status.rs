@@ -0,0 +1,107 @@
+// status.rs
+// What the assistant and the narration are busy with, shared between their tasks and the UI.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// The stages of a turn, in the order they usually come; tool calls can repeat with thinking.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum RunActivity {
+    #[default]
+    Idle,
+    // Sending the player's message and starting the run.
+    Submitting,
+    Thinking,
+    ToolCall(String),
+    // The run is done; its answer is being read and checked.
+    Composing,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Narration {
+    #[default]
+    Idle,
+    Generating,
+    // Reading out a dialogue line of the game message with this transcript fingerprint.
+    Playing {
+        message: u64,
+        line: usize,
+    },
+}
+
+// A value written by background tasks and read when drawing.
+#[derive(Debug, Default)]
+pub struct Shared<T>(Arc<Mutex<T>>);
+
+impl<T> Clone for Shared<T> {
+    fn clone(&self) -> Self {
+        Shared(Arc::clone(&self.0))
+    }
+}
+
+impl<T: Clone> Shared<T> {
+    pub fn new(value: T) -> Self {
+        Shared(Arc::new(Mutex::new(value)))
+    }
+
+    pub fn get(&self) -> T {
+        match self.0.lock() {
+            Ok(value) => value.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    pub fn set(&self, value: T) {
+        if let Ok(mut current) = self.0.lock() {
+            *current = value;
+        }
+    }
+
+    // Read or change the value in place, for values too big to copy on every access.
+    pub fn with<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        match self.0.lock() {
+            Ok(mut value) => f(&mut value),
+            Err(poisoned) => f(&mut poisoned.into_inner()),
+        }
+    }
+}
+
+impl RunActivity {
+    pub fn label(&self) -> String {
+        match self {
+            RunActivity::Idle => "Idle".to_string(),
+            RunActivity::Submitting => "Submitting".to_string(),
+            RunActivity::Thinking => "GM is thinking".to_string(),
+            RunActivity::ToolCall(name) => format!("Running tools: {}", tool_label(name)),
+            RunActivity::Composing => "Composing".to_string(),
+        }
+    }
+
+    // The label with how long the player has been waiting, e.g. "GM is thinking · 12s".
+    pub fn label_with_elapsed(&self, elapsed: Duration) -> String {
+        format!("{} · {}s", self.label(), elapsed.as_secs())
+    }
+}
+
+// What a tool does, in the player's words.
+fn tool_label(name: &str) -> &str {
+    match name {
+        "perform_dice_roll" => "dice roll",
+        "create_character_sheet" => "character creation",
+        "generate_npc" => "NPC",
+        "generate_character_image" => "portrait",
+        "generate_scene_image" => "scene illustration",
+        name if name.starts_with("update_") => "sheet update",
+        name => name,
+    }
+}
+
+impl Narration {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Narration::Idle => "Ready",
+            Narration::Generating => "Generating",
+            Narration::Playing { .. } => "Playing",
+        }
+    }
+}
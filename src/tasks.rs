@@ -0,0 +1,245 @@
+// tasks.rs
+// Work the game does in the background: pictures, narration and transcription. Each task is
+// registered with its kind and when it started, so the tasks panel can show what is running and
+// cancel a task that is stuck, e.g. on a request that never answers. A task that finishes says
+// where its result can be seen, so the player can be told about it wherever they are.
+
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::AbortHandle;
+
+// Finished tasks still listed in the panel.
+pub const KEPT_FINISHED: usize = 8;
+// How long the notice of a task that finished away from the game stays up.
+pub const TOAST_DURATION: Duration = Duration::from_secs(8);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskKind {
+    Image,
+    Speech,
+    Transcription,
+}
+
+impl TaskKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            TaskKind::Image => "Image",
+            TaskKind::Speech => "Narration",
+            TaskKind::Transcription => "Transcription",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskStatus {
+    Running,
+    Done,
+    Failed(String),
+    Cancelled,
+}
+
+impl TaskStatus {
+    pub fn label(&self) -> String {
+        match self {
+            TaskStatus::Running => "Running".to_string(),
+            TaskStatus::Done => "Done".to_string(),
+            TaskStatus::Failed(e) => format!("Failed: {}", e),
+            TaskStatus::Cancelled => "Cancelled".to_string(),
+        }
+    }
+}
+
+// Where the result of a finished task is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Jump {
+    // A picture, opened in the system viewer.
+    Image(PathBuf),
+    // The game a narration was read for, by save name.
+    Game(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct TaskInfo {
+    pub id: u64,
+    pub kind: TaskKind,
+    // What it works on, e.g. the prompt of a picture.
+    pub description: String,
+    pub status: TaskStatus,
+    pub started: Instant,
+    pub finished: Option<Instant>,
+    pub jump: Option<Jump>,
+}
+
+impl TaskInfo {
+    // How long it has been running, or ran.
+    pub fn elapsed(&self) -> Duration {
+        self.finished.unwrap_or_else(Instant::now) - self.started
+    }
+}
+
+// The notice of a finished task, with where F2 goes to see its result.
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub text: String,
+    pub jump: Option<Jump>,
+    pub shown: Instant,
+}
+
+impl Toast {
+    pub fn expired(&self) -> bool {
+        self.shown.elapsed() >= TOAST_DURATION
+    }
+}
+
+struct Entry {
+    info: TaskInfo,
+    // None once it finished, or in the moment before the task is spawned.
+    abort: Option<AbortHandle>,
+}
+
+#[derive(Default)]
+struct Registry {
+    next_id: u64,
+    entries: Vec<Entry>,
+    // Tasks that finished or failed since the UI last asked; cancelled ones aren't news.
+    unseen: Vec<TaskInfo>,
+}
+
+impl Registry {
+    fn running_mut(&mut self, id: u64) -> Option<&mut Entry> {
+        self.entries
+            .iter_mut()
+            .find(|entry| entry.info.id == id && entry.info.status == TaskStatus::Running)
+    }
+
+    fn end(&mut self, id: u64, status: TaskStatus) -> bool {
+        let Some(entry) = self.running_mut(id) else {
+            return false;
+        };
+        if let Some(abort) = entry.abort.take() {
+            if status == TaskStatus::Cancelled {
+                abort.abort();
+            }
+        }
+        entry.info.status = status;
+        entry.info.finished = Some(Instant::now());
+        if entry.info.status != TaskStatus::Cancelled {
+            let info = entry.info.clone();
+            self.unseen.push(info);
+        }
+        // The oldest finished tasks go first.
+        let finished = self
+            .entries
+            .iter()
+            .filter(|entry| entry.info.status != TaskStatus::Running)
+            .count();
+        let mut extra = finished.saturating_sub(KEPT_FINISHED);
+        self.entries.retain(|entry| {
+            let drop = extra > 0 && entry.info.status != TaskStatus::Running;
+            extra -= drop as usize;
+            !drop
+        });
+        true
+    }
+}
+
+// The registry, shared between the tasks and the UI.
+#[derive(Clone, Default)]
+pub struct Tasks(Arc<Mutex<Registry>>);
+
+impl Tasks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn with<R>(&self, f: impl FnOnce(&mut Registry) -> R) -> R {
+        match self.0.lock() {
+            Ok(mut registry) => f(&mut registry),
+            Err(poisoned) => f(&mut poisoned.into_inner()),
+        }
+    }
+
+    // Run `task` in the background. An error it ends with is shown in the panel; what it ends
+    // with otherwise is where its result can be seen, if anywhere.
+    pub fn spawn<F>(&self, kind: TaskKind, description: impl Into<String>, task: F) -> u64
+    where
+        F: Future<Output = Result<Option<Jump>, String>> + Send + 'static,
+    {
+        let id = self.with(|registry| {
+            registry.next_id += 1;
+            let id = registry.next_id;
+            registry.entries.push(Entry {
+                info: TaskInfo {
+                    id,
+                    kind,
+                    description: description.into(),
+                    status: TaskStatus::Running,
+                    started: Instant::now(),
+                    finished: None,
+                    jump: None,
+                },
+                abort: None,
+            });
+            id
+        });
+        let tasks = self.clone();
+        let handle = tokio::spawn(async move {
+            let (status, jump) = match task.await {
+                Ok(jump) => (TaskStatus::Done, jump),
+                Err(e) => (TaskStatus::Failed(e), None),
+            };
+            tasks.with(|registry| {
+                if let Some(entry) = registry.running_mut(id) {
+                    entry.info.jump = jump;
+                }
+                registry.end(id, status)
+            });
+        });
+        self.with(|registry| {
+            if let Some(entry) = registry.running_mut(id) {
+                entry.abort = Some(handle.abort_handle());
+            } else if registry
+                .entries
+                .iter()
+                .any(|entry| entry.info.id == id && entry.info.status == TaskStatus::Cancelled)
+            {
+                // Cancelled before it could be stopped.
+                handle.abort();
+            }
+        });
+        id
+    }
+
+    // Stop a running task; false if it already ended.
+    pub fn cancel(&self, id: u64) -> bool {
+        self.with(|registry| registry.end(id, TaskStatus::Cancelled))
+    }
+
+    // Running and recently finished tasks, oldest first.
+    pub fn list(&self) -> Vec<TaskInfo> {
+        self.with(|registry| {
+            registry
+                .entries
+                .iter()
+                .map(|entry| entry.info.clone())
+                .collect()
+        })
+    }
+
+    // The tasks that ended on their own since the last call, oldest first.
+    pub fn take_finished(&self) -> Vec<TaskInfo> {
+        self.with(|registry| std::mem::take(&mut registry.unseen))
+    }
+
+    pub fn running(&self) -> usize {
+        self.with(|registry| {
+            registry
+                .entries
+                .iter()
+                .filter(|entry| entry.info.status == TaskStatus::Running)
+                .count()
+        })
+    }
+}
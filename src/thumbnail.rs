@@ -0,0 +1,169 @@
+// thumbnail.rs
+// Pictures drawn inside the terminal, e.g. the portrait in the load menu's preview. Terminals
+// with a graphics protocol (Kitty, Sixel, iTerm2) show the picture itself, the others an
+// approximation in half blocks. Those with only the 16 basic colours, like the Linux console, get
+// half blocks of their own: the picture is scaled down to two pixels a cell and each pixel takes
+// the nearest colour the console has.
+
+use ::image::{imageops::FilterType, DynamicImage, Rgb};
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use ratatui_image::picker::Picker;
+use ratatui_image::protocol::StatefulProtocol;
+use std::path::{Path, PathBuf};
+
+// Used when the terminal doesn't say how big its cells are.
+const FONT_SIZE: (u16, u16) = (8, 16);
+
+// The basic colours as the Linux console shows them, the first 8 being the only backgrounds.
+const BASIC_COLORS: [(Color, [u8; 3]); 16] = [
+    (Color::Black, [0, 0, 0]),
+    (Color::Red, [170, 0, 0]),
+    (Color::Green, [0, 170, 0]),
+    (Color::Yellow, [170, 85, 0]),
+    (Color::Blue, [0, 0, 170]),
+    (Color::Magenta, [170, 0, 170]),
+    (Color::Cyan, [0, 170, 170]),
+    (Color::Gray, [170, 170, 170]),
+    (Color::DarkGray, [85, 85, 85]),
+    (Color::LightRed, [255, 85, 85]),
+    (Color::LightGreen, [85, 255, 85]),
+    (Color::LightYellow, [255, 255, 85]),
+    (Color::LightBlue, [85, 85, 255]),
+    (Color::LightMagenta, [255, 85, 255]),
+    (Color::LightCyan, [85, 255, 255]),
+    (Color::White, [255, 255, 255]),
+];
+
+// A picture drawn as text for a width and height in cells.
+type Art = (PathBuf, u16, u16, Option<Vec<Line<'static>>>);
+
+pub struct Thumbnails {
+    picker: Picker,
+    // The picture last asked for, decoded once and resized to where it is drawn; None if it
+    // could not be read, so it isn't tried again on every frame.
+    shown: Option<(PathBuf, Option<Box<dyn StatefulProtocol>>)>,
+    basic_colors: bool,
+    art: Option<Art>,
+}
+
+impl Default for Thumbnails {
+    // Half blocks, which every terminal can show.
+    fn default() -> Self {
+        Thumbnails {
+            picker: Picker::new(FONT_SIZE),
+            shown: None,
+            basic_colors: false,
+            art: None,
+        }
+    }
+}
+
+// Whether the terminal shows only the 16 basic colours, from its TERM and COLORTERM.
+pub fn basic_colors_only(term: Option<&str>, colorterm: Option<&str>) -> bool {
+    let full_color = colorterm.is_some_and(|colorterm| {
+        colorterm.eq_ignore_ascii_case("truecolor") || colorterm.eq_ignore_ascii_case("24bit")
+    });
+    term.is_some_and(|term| term == "linux" || term == "vt100" || term == "dumb") && !full_color
+}
+
+// The basic colour closest to `pixel`, among the backgrounds only when `background`.
+pub fn nearest_basic_color(pixel: Rgb<u8>, background: bool) -> Color {
+    let colors = if background {
+        &BASIC_COLORS[..8]
+    } else {
+        &BASIC_COLORS[..]
+    };
+    colors
+        .iter()
+        .min_by_key(|(_, rgb)| {
+            rgb.iter()
+                .zip(pixel.0)
+                .map(|(a, b)| (*a as i32 - b as i32).pow(2))
+                .sum::<i32>()
+        })
+        .map_or(Color::Reset, |(color, _)| *color)
+}
+
+// `image` in at most `width` by `height` cells of upper half blocks, the top pixel of each cell
+// in the foreground and the bottom one in the background.
+pub fn half_block_art(image: &DynamicImage, width: u16, height: u16) -> Vec<Line<'static>> {
+    if width == 0 || height == 0 {
+        return Vec::new();
+    }
+    let small = image
+        .resize(width as u32, height as u32 * 2, FilterType::Triangle)
+        .to_rgb8();
+    (0..small.height())
+        .step_by(2)
+        .map(|y| {
+            let spans: Vec<Span> = (0..small.width())
+                .map(|x| {
+                    let top = *small.get_pixel(x, y);
+                    let bottom = if y + 1 < small.height() {
+                        *small.get_pixel(x, y + 1)
+                    } else {
+                        top
+                    };
+                    Span::styled(
+                        "▀",
+                        Style::default()
+                            .fg(nearest_basic_color(top, false))
+                            .bg(nearest_basic_color(bottom, true)),
+                    )
+                })
+                .collect();
+            Line::from(spans)
+        })
+        .collect()
+}
+
+impl Thumbnails {
+    // Ask the terminal what it can draw. It answers on stdin, so this runs after entering the
+    // alternate screen and before reading any event.
+    pub fn detect() -> Self {
+        let mut picker = Picker::from_termios().unwrap_or_else(|_| Picker::new(FONT_SIZE));
+        picker.guess_protocol();
+        Thumbnails {
+            picker,
+            shown: None,
+            basic_colors: basic_colors_only(
+                std::env::var("TERM").ok().as_deref(),
+                std::env::var("COLORTERM").ok().as_deref(),
+            ),
+            art: None,
+        }
+    }
+
+    // Whether pictures are drawn with `art` rather than `get`.
+    pub fn basic_colors(&self) -> bool {
+        self.basic_colors
+    }
+
+    // The picture at `path` as coloured text for `width` by `height` cells, or None if it can't
+    // be read.
+    pub fn art(&mut self, path: &Path, width: u16, height: u16) -> Option<&[Line<'static>]> {
+        if self
+            .art
+            .as_ref()
+            .is_none_or(|(shown, w, h, _)| shown != path || *w != width || *h != height)
+        {
+            let art = ::image::open(path)
+                .ok()
+                .map(|image| half_block_art(&image, width, height));
+            self.art = Some((path.to_path_buf(), width, height, art));
+        }
+        self.art.as_ref()?.3.as_deref()
+    }
+
+    // The picture at `path`, ready to draw, or None if it can't be read.
+    pub fn get(&mut self, path: &Path) -> Option<&mut Box<dyn StatefulProtocol>> {
+        if self.shown.as_ref().is_none_or(|(shown, _)| shown != path) {
+            let protocol = ::image::open(path)
+                .ok()
+                .map(|image| self.picker.new_resize_protocol(image));
+            self.shown = Some((path.to_path_buf(), protocol));
+        }
+        self.shown.as_mut()?.1.as_mut()
+    }
+}
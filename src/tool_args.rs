@@ -0,0 +1,290 @@
+// tool_args.rs
+// The arguments of the assistant's tools, as described in assets/assistant_functions.
+//
+// A call is parsed in one go before anything is applied, so malformed arguments give an error that
+// names the field and nothing is half updated. The error goes back to the assistant as the tool
+// output.
+
+use crate::character::{
+    AttributeKey, Contact, Item, MatrixAttributes, Quality, UpdateOperation, Value,
+};
+use crate::error::ShadowrunError;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+
+pub fn parse<T: DeserializeOwned>(tool: &str, arguments: &str) -> Result<T, ShadowrunError> {
+    serde_json::from_str(arguments)
+        .map_err(|e| ShadowrunError::Game(format!("Invalid arguments for {}: {}", tool, e)))
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Add,
+    Remove,
+    Modify,
+}
+
+impl Operation {
+    pub fn with(self, value: Value) -> UpdateOperation<Value> {
+        match self {
+            Operation::Add => UpdateOperation::Add(value),
+            Operation::Remove => UpdateOperation::Remove(value),
+            Operation::Modify => UpdateOperation::Modify(value),
+        }
+    }
+
+    // For tools whose definition only offers Add and Remove.
+    pub fn without_modify(self, tool: &str) -> Result<Self, ShadowrunError> {
+        match self {
+            Operation::Modify => Err(ShadowrunError::Game(format!(
+                "{} only supports Add and Remove",
+                tool
+            ))),
+            operation => Ok(operation),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct BasicAttributesArgs {
+    pub character_name: String,
+    // In the order of AttributeKey, so a new race is applied before the attributes it modifies.
+    pub updates: BTreeMap<AttributeKey, serde_json::Value>,
+}
+
+// A skill rating, given either as {"name": ..., "rating": ...} entries or as a name → rating map.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum SkillRatings {
+    List(Vec<SkillRating>),
+    Map(HashMap<String, u8>),
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SkillRating {
+    pub name: String,
+    pub rating: u8,
+}
+
+impl SkillRatings {
+    pub fn into_map(self) -> HashMap<String, u8> {
+        match self {
+            SkillRatings::List(list) => list
+                .into_iter()
+                .map(|skill| (skill.name, skill.rating))
+                .collect(),
+            SkillRatings::Map(map) => map,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct SkillCategories {
+    pub combat: Option<SkillRatings>,
+    pub physical: Option<SkillRatings>,
+    pub social: Option<SkillRatings>,
+    pub technical: Option<SkillRatings>,
+    pub knowledge: Option<SkillRatings>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SkillUpdates {
+    #[serde(default)]
+    pub skills: SkillCategories,
+    // Where the function definition puts them; older calls nest them as skills.knowledge.
+    pub knowledge_skills: Option<SkillRatings>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SkillsArgs {
+    pub character_name: String,
+    pub updates: SkillUpdates,
+}
+
+// An inventory entry; removing by name alone takes one away.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ItemArgs {
+    pub name: String,
+    #[serde(default = "one")]
+    pub quantity: u32,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub armor: Option<u8>,
+    #[serde(default)]
+    pub reach: Option<u8>,
+    #[serde(default)]
+    pub recoil_compensation: Option<u8>,
+    #[serde(default)]
+    pub weight: Option<f32>,
+}
+
+fn one() -> u32 {
+    1
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ItemDetails {
+    #[serde(default = "one")]
+    pub quantity: u32,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub armor: Option<u8>,
+    #[serde(default)]
+    pub reach: Option<u8>,
+    #[serde(default)]
+    pub recoil_compensation: Option<u8>,
+    #[serde(default)]
+    pub weight: Option<f32>,
+}
+
+// The definition asks for one item, but the assistant also sends lists and maps by name.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum InventoryItems {
+    One(ItemArgs),
+    Many(Vec<ItemArgs>),
+    Names(Vec<String>),
+    ByName(HashMap<String, ItemDetails>),
+}
+
+impl InventoryItems {
+    pub fn into_items(self) -> HashMap<String, Item> {
+        let items: Vec<ItemArgs> = match self {
+            InventoryItems::One(item) => vec![item],
+            InventoryItems::Many(items) => items,
+            InventoryItems::Names(names) => names
+                .into_iter()
+                .map(|name| ItemArgs {
+                    name,
+                    quantity: 1,
+                    description: String::new(),
+                    armor: None,
+                    reach: None,
+                    recoil_compensation: None,
+                    weight: None,
+                })
+                .collect(),
+            InventoryItems::ByName(items) => items
+                .into_iter()
+                .map(|(name, details)| ItemArgs {
+                    name,
+                    quantity: details.quantity,
+                    description: details.description,
+                    armor: details.armor,
+                    reach: details.reach,
+                    recoil_compensation: details.recoil_compensation,
+                    weight: details.weight,
+                })
+                .collect(),
+        };
+        items
+            .into_iter()
+            .map(|item| {
+                (
+                    item.name.clone(),
+                    Item {
+                        name: item.name,
+                        quantity: item.quantity,
+                        description: item.description,
+                        armor: item.armor,
+                        reach: item.reach,
+                        recoil_compensation: item.recoil_compensation,
+                        weight: item.weight,
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect()
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct InventoryArgs {
+    pub character_name: String,
+    pub operation: Operation,
+    #[serde(alias = "items")]
+    pub item: InventoryItems,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct QualitiesArgs {
+    pub character_name: String,
+    pub operation: Operation,
+    pub qualities: Vec<Quality>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ContactsArgs {
+    pub character_name: String,
+    pub operation: Operation,
+    pub contacts: Vec<Contact>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct MatrixAttributesArgs {
+    pub character_name: String,
+    pub matrix_attributes: MatrixAttributes,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct RollDiceArgs {
+    pub expression: String,
+    pub reason: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SheetFieldsArgs {
+    pub character_name: String,
+    pub fields: BTreeMap<String, Option<String>>,
+    #[serde(default)]
+    pub main_character: bool,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SceneImageArgs {
+    pub title: String,
+    pub image_generation_prompt: String,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AugmentationType {
+    Cyberware,
+    Bioware,
+}
+
+impl AugmentationType {
+    // The character sheet field it updates.
+    pub fn attribute(self) -> AttributeKey {
+        match self {
+            AugmentationType::Cyberware => AttributeKey::Cyberware,
+            AugmentationType::Bioware => AttributeKey::Bioware,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct AugmentationsArgs {
+    pub character_name: String,
+    pub operation: Operation,
+    pub augmentation_type: AugmentationType,
+    pub augmentations: Vec<String>,
+}
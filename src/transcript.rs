@@ -0,0 +1,217 @@
+// transcript.rs
+// Wrapped and styled lines of the game transcript, kept between frames.
+//
+// Each message is parsed once for a given width. New messages are parsed as they arrive, and the
+// whole cache is only rebuilt on resize or when the way messages are drawn changes.
+//
+// Times in the gutter are relative, so they are worked out when drawn instead of cached.
+
+use crate::message::Message;
+use crate::ui::game::{dialogue_lines, parse_message};
+use crate::ui::theme::palette;
+use chrono::{DateTime, Local};
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+
+// A pause this long between two messages starts a new session.
+pub const SESSION_GAP_SECONDS: i64 = 2 * 60 * 60;
+// Columns taken by the message times, left of the transcript.
+pub const GUTTER_WIDTH: u16 = 5;
+
+struct ParsedMessage {
+    fingerprint: u64,
+    lines: Vec<Line<'static>>,
+    // Where the message itself starts, after any session separator.
+    first_line: usize,
+    timestamp: Option<i64>,
+    // The lines of each dialogue line, from `first_line`, for game messages.
+    dialogue: Vec<(usize, Range<usize>)>,
+}
+
+// "Session of 2025-06-01" when `message` comes after a long pause since `previous`.
+pub fn session_break(previous: &Message, message: &Message) -> Option<String> {
+    let (Some(before), Some(after)) = (previous.timestamp, message.timestamp) else {
+        return None;
+    };
+    if after - before < SESSION_GAP_SECONDS {
+        return None;
+    }
+    let date = DateTime::from_timestamp(after, 0)?.with_timezone(&Local);
+    Some(format!("Session of {}", date.format("%Y-%m-%d")))
+}
+
+// How long before `now` a message was written, short enough for the gutter: "now", "5m", "3h", "2d".
+pub fn relative_time(timestamp: i64, now: i64) -> String {
+    let seconds = (now - timestamp).max(0);
+    match seconds {
+        0..=59 => "now".to_string(),
+        60..=3599 => format!("{}m", seconds / 60),
+        3600..=86399 => format!("{}h", seconds / 3600),
+        _ => format!("{}d", seconds / 86400),
+    }
+}
+
+#[derive(Default)]
+pub struct TranscriptCache {
+    width: usize,
+    accessible: bool,
+    messages: Vec<ParsedMessage>,
+    line_count: usize,
+}
+
+// Tells messages apart while they are in the transcript, e.g. the one being narrated.
+pub fn fingerprint(message: &Message) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    message.content.hash(&mut hasher);
+    message.message_type.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl TranscriptCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Drop every parsed line, e.g. after the theme changed or a different game was loaded.
+    pub fn invalidate(&mut self) {
+        self.messages.clear();
+        self.line_count = 0;
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    // Number of lines the whole transcript takes.
+    pub fn len(&self) -> usize {
+        self.line_count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.line_count == 0
+    }
+
+    // Catch up with the transcript, parsing only the messages that are new or changed.
+    pub fn update(&mut self, messages: &[Message], width: usize, accessible: bool) {
+        if width != self.width || accessible != self.accessible {
+            self.width = width;
+            self.accessible = accessible;
+            self.invalidate();
+        }
+        while self.messages.len() > messages.len() {
+            self.drop_last();
+        }
+        // Messages are appended, but the last one can be swapped, like a "thinking" notice that is
+        // replaced by the answer.
+        while let Some(last) = self.messages.last() {
+            if last.fingerprint == fingerprint(&messages[self.messages.len() - 1]) {
+                break;
+            }
+            self.drop_last();
+        }
+        for index in self.messages.len()..messages.len() {
+            let message = &messages[index];
+            let mut lines = Vec::new();
+            if let Some(label) = index
+                .checked_sub(1)
+                .and_then(|previous| session_break(&messages[previous], message))
+            {
+                lines.push(if accessible {
+                    Line::raw(label)
+                } else {
+                    Line::styled(
+                        format!("── {} ──", label),
+                        Style::default().fg(palette().muted),
+                    )
+                    .centered()
+                });
+            }
+            let first_line = lines.len();
+            lines.extend(parse_message(message, width, accessible));
+            self.line_count += lines.len();
+            self.messages.push(ParsedMessage {
+                fingerprint: fingerprint(message),
+                lines,
+                first_line,
+                timestamp: message.timestamp,
+                dialogue: dialogue_lines(message, width, accessible),
+            });
+        }
+    }
+
+    fn drop_last(&mut self) {
+        if let Some(parsed) = self.messages.pop() {
+            self.line_count -= parsed.lines.len();
+        }
+    }
+
+    // The first message with lines from `start`, and how many lines come before it.
+    fn first_visible(&self, start: usize) -> (usize, usize) {
+        // Skip whole messages; long campaigns have far more lines above than on screen.
+        let mut first_message = 0;
+        let mut skipped = 0;
+        while let Some(parsed) = self.messages.get(first_message) {
+            if skipped + parsed.lines.len() > start {
+                break;
+            }
+            skipped += parsed.lines.len();
+            first_message += 1;
+        }
+        (first_message, skipped)
+    }
+
+    // The transcript lines of a dialogue line, in the latest message with `fingerprint`.
+    pub fn dialogue_line(&self, fingerprint: u64, line: usize) -> Option<Range<usize>> {
+        let mut before = self.line_count;
+        for parsed in self.messages.iter().rev() {
+            before -= parsed.lines.len();
+            if parsed.fingerprint == fingerprint {
+                let (_, lines) = parsed.dialogue.iter().find(|(index, _)| *index == line)?;
+                let start = before + parsed.first_line + lines.start;
+                return Some(start..start + lines.len());
+            }
+        }
+        None
+    }
+
+    // The lines from `start`, borrowing the cached text instead of copying it.
+    pub fn lines(&self, start: usize, count: usize) -> Vec<Line<'_>> {
+        let (first_message, skipped) = self.first_visible(start);
+        self.messages[first_message..]
+            .iter()
+            .flat_map(|parsed| parsed.lines.iter())
+            .skip(start - skipped)
+            .take(count)
+            .map(|line| Line {
+                spans: line
+                    .spans
+                    .iter()
+                    .map(|span| Span::styled(span.content.as_ref(), span.style))
+                    .collect(),
+                style: line.style,
+                alignment: line.alignment,
+            })
+            .collect()
+    }
+
+    // The time of each message beside its first line, for the same lines as `lines`.
+    pub fn gutter(&self, start: usize, count: usize, now: i64) -> Vec<Line<'static>> {
+        let (first_message, skipped) = self.first_visible(start);
+        self.messages[first_message..]
+            .iter()
+            .flat_map(|parsed| {
+                (0..parsed.lines.len()).map(move |line| match parsed.timestamp {
+                    Some(timestamp) if line == parsed.first_line => {
+                        Line::raw(format!("{} ", relative_time(timestamp, now)))
+                    }
+                    _ => Line::raw(""),
+                })
+            })
+            .skip(start - skipped)
+            .take(count)
+            .collect()
+    }
+}
@@ -1,22 +1,18 @@
 // ui/api_key_input.rs
 
 use crate::app::App;
+use crate::ui::theme::palette;
+use crate::ui::utils::{draw_too_small, MIN_HEIGHT, MIN_WIDTH};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Position},
     prelude::Alignment,
-    style::{Color, Style},
+    style::Style,
     widgets::*,
     Frame,
 };
 
 pub fn draw_api_key_input(f: &mut Frame, app: &App) {
-    let size = f.area();
-
-    if size.width < 100 || size.height < 50 {
-        let warning = Paragraph::new("Terminal too small. Please resize.")
-            .style(Style::default().fg(Color::Red))
-            .alignment(Alignment::Center);
-        f.render_widget(warning, size);
+    if draw_too_small(f, &app.settings.language, MIN_WIDTH, MIN_HEIGHT) {
         return;
     }
     let chunks = Layout::default()
@@ -34,22 +30,22 @@ pub fn draw_api_key_input(f: &mut Frame, app: &App) {
         .split(f.area());
 
     let title = Paragraph::new(" Enter a valid OpenAI API Key ")
-        .style(Style::default().fg(Color::Cyan))
+        .style(Style::default().fg(palette().info))
         .alignment(Alignment::Center);
     f.render_widget(title, chunks[0]);
 
     let input = Paragraph::new(app.api_key_input.value())
-        .style(Style::default().fg(Color::Yellow))
+        .style(Style::default().fg(palette().highlight))
         .block(Block::default().borders(Borders::ALL).title(" API Key "));
     f.render_widget(input, chunks[1]);
 
     let instructions = Paragraph::new(" Press Enter to confirm, Esc to cancel ")
-        .style(Style::default().fg(Color::Gray))
+        .style(Style::default().fg(palette().muted))
         .alignment(Alignment::Center);
     f.render_widget(instructions, chunks[2]);
 
     let paste_info = Paragraph::new(" Use Ctrl+V to paste ")
-        .style(Style::default().fg(Color::Gray))
+        .style(Style::default().fg(palette().muted))
         .alignment(Alignment::Center);
     f.render_widget(paste_info, chunks[3]);
 
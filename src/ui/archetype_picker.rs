@@ -0,0 +1,89 @@
+// ui/archetype_picker.rs
+
+use super::main_menu::{render_art, render_header, render_status, render_title};
+use crate::app::App;
+use crate::archetype::ARCHETYPES;
+use crate::ui::theme::palette;
+use crate::ui::utils::{draw_too_small, MIN_HEIGHT, MIN_WIDTH};
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::*,
+    Frame,
+};
+
+pub fn draw_archetype_picker(f: &mut Frame, app: &App) {
+    if draw_too_small(f, &app.settings.language, MIN_WIDTH, MIN_HEIGHT) {
+        return;
+    }
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Max(3),
+                Constraint::Max(20),
+                Constraint::Max(7),
+                Constraint::Max(3),
+                Constraint::Min(ARCHETYPES.len() as u16 + 2),
+                Constraint::Max(3),
+                Constraint::Max(3),
+            ]
+            .as_ref(),
+        )
+        .split(f.area());
+
+    render_header(f, chunks[0]);
+    render_art(f, app, chunks[1]);
+    render_title(f, app, chunks[2]);
+
+    let console = Paragraph::new("Choose a starting archetype")
+        .style(Style::default().fg(palette().highlight))
+        .alignment(Alignment::Center);
+    f.render_widget(console, chunks[3]);
+
+    render_archetype_menu(f, app, chunks[4]);
+    render_description(f, app, chunks[5]);
+    render_status(f, app, chunks[6]);
+}
+
+fn render_archetype_menu(f: &mut Frame, app: &App, area: Rect) {
+    let selected = app.archetype_menu_state.selected().unwrap_or(0);
+    let names = ["Custom (create with the AI)", "Build it yourself (offline)"]
+        .into_iter()
+        .chain(ARCHETYPES.iter().map(|archetype| archetype.name));
+
+    let text: Vec<Line> = names
+        .enumerate()
+        .map(|(i, name)| {
+            if i == selected {
+                Line::from(Span::styled(
+                    format!("> {}", name),
+                    Style::default()
+                        .fg(palette().highlight)
+                        .add_modifier(Modifier::BOLD),
+                ))
+            } else {
+                Line::from(Span::raw(format!("  {}", name)))
+            }
+        })
+        .collect();
+
+    let menu = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(palette().text));
+    f.render_widget(menu, area);
+}
+
+fn render_description(f: &mut Frame, app: &App, area: Rect) {
+    let description = match app.archetype_menu_state.selected().unwrap_or(0) {
+        0 => "Start from scratch and build your character together with the AI.",
+        1 => "Build a complete character with the priority system, no AI needed until the game starts.",
+        i => ARCHETYPES[i - 2].description,
+    };
+    let paragraph = Paragraph::new(description)
+        .style(Style::default().fg(palette().muted))
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    f.render_widget(paragraph, area);
+}
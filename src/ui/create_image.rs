@@ -1,21 +1,17 @@
 use crate::app::{App, InputMode};
+use crate::ui::theme::palette;
+use crate::ui::utils::{draw_too_small, MIN_HEIGHT, MIN_WIDTH};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Position},
     prelude::Alignment,
-    style::{Color, Style},
+    style::Style,
     widgets::*,
     Frame,
 };
 
 // Function to draw the image creation interface in the application.
 pub fn draw_create_image(f: &mut Frame, app: &App) {
-    let size = f.area();
-
-    if size.width < 100 || size.height < 50 {
-        let warning = Paragraph::new("Terminal too small. Please resize.")
-            .style(Style::default().fg(Color::Red))
-            .alignment(Alignment::Center);
-        f.render_widget(warning, size);
+    if draw_too_small(f, &app.settings.language, MIN_WIDTH, MIN_HEIGHT) {
         return;
     }
     let chunks = Layout::default()
@@ -33,24 +29,25 @@ pub fn draw_create_image(f: &mut Frame, app: &App) {
         .split(f.area());
 
     let title = Paragraph::new(" Enter an image prompt ")
-        .style(Style::default().fg(Color::Cyan))
+        .style(Style::default().fg(palette().info))
         .alignment(Alignment::Center);
     f.render_widget(title, chunks[0]);
 
     let input = Paragraph::new(app.image_prompt.value())
-        .style(Style::default().fg(Color::White))
+        .style(Style::default().fg(palette().text))
         .block(
             Block::default()
                 .borders(Borders::ALL)
                 .title(match app.input_mode {
                     InputMode::Normal => " Press 'e' to edit or 'r' to record",
+                    InputMode::Editing if app.simple_input() => " Ctrl+R to record ",
                     InputMode::Editing => " Editing ",
                     InputMode::Recording => " Recording… Press 'Esc' to stop",
                 })
                 .border_style(Style::default().fg(match app.input_mode {
-                    InputMode::Normal => Color::DarkGray,
-                    InputMode::Editing => Color::Yellow,
-                    InputMode::Recording => Color::Red,
+                    InputMode::Normal => palette().dim,
+                    InputMode::Editing => palette().highlight,
+                    InputMode::Recording => palette().failure,
                 })),
         );
     f.render_widget(input, chunks[1]);
@@ -61,7 +58,7 @@ pub fn draw_create_image(f: &mut Frame, app: &App) {
         InputMode::Recording => " RECORDING ",
     };
     let instructions = Paragraph::new(format!("{} | Enter: confirm | Esc: cancel", mode_indicator))
-        .style(Style::default().fg(Color::Gray))
+        .style(Style::default().fg(palette().muted))
         .alignment(Alignment::Center);
     f.render_widget(instructions, chunks[2]);
 
@@ -0,0 +1,61 @@
+// ui/data_dir_input.rs
+
+use crate::app::App;
+use crate::ui::theme::palette;
+use crate::ui::utils::{draw_too_small, MIN_HEIGHT, MIN_WIDTH};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Position},
+    prelude::Alignment,
+    style::Style,
+    widgets::*,
+    Frame,
+};
+
+pub fn draw_data_dir_input(f: &mut Frame, app: &App) {
+    if draw_too_small(f, &app.settings.language, MIN_WIDTH, MIN_HEIGHT) {
+        return;
+    }
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(f.area().height / 3)
+        .constraints(
+            [
+                Constraint::Length(3),
+                Constraint::Length(3),
+                Constraint::Length(3),
+                Constraint::Min(1),
+            ]
+            .as_ref(),
+        )
+        .split(f.area());
+
+    let title = Paragraph::new(" Enter a data folder (leave empty for the default) ")
+        .style(Style::default().fg(palette().info))
+        .alignment(Alignment::Center);
+    f.render_widget(title, chunks[0]);
+
+    let input = Paragraph::new(app.data_dir_input.value())
+        .style(Style::default().fg(palette().highlight))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Data Folder "),
+        );
+    f.render_widget(input, chunks[1]);
+
+    let instructions = Paragraph::new(" Press Enter to confirm, Esc to cancel ")
+        .style(Style::default().fg(palette().muted))
+        .alignment(Alignment::Center);
+    f.render_widget(instructions, chunks[2]);
+
+    let paste_info = Paragraph::new(" Used after a restart; files already there are not moved ")
+        .style(Style::default().fg(palette().muted))
+        .alignment(Alignment::Center);
+    f.render_widget(paste_info, chunks[3]);
+
+    // Set cursor
+    f.set_cursor_position(Position::new(
+        chunks[1].x + app.data_dir_input.cursor() as u16 + 1,
+        chunks[1].y + 1,
+    ));
+}
@@ -0,0 +1,199 @@
+// ui/dice.rs
+// A small popup that tumbles the dice of a roll before settling on the results, left to right,
+// and the one that sets up a roll from the character sheet.
+//
+// With Dice Animation off the results show settled right away.
+
+use crate::dice::ShownRoll;
+use crate::i18n::tr;
+use crate::roll_popup::RollPopup;
+use crate::ui::theme::palette;
+use crate::ui::utils::truncate_to_width;
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::*,
+    Frame,
+};
+use std::time::{Duration, Instant};
+use unicode_width::UnicodeWidthStr;
+
+// How long the dice tumble, the last one settling at the end.
+pub const TUMBLE: Duration = Duration::from_millis(900);
+// How long the settled results stay up.
+pub const SHOWN: Duration = Duration::from_millis(2500);
+// How often a tumbling die changes face.
+const FACE_CHANGE: Duration = Duration::from_millis(80);
+const DICE_PER_ROW: usize = 12;
+
+pub struct DiceAnimation {
+    pub roll: ShownRoll,
+    started: Instant,
+    tumble: Duration,
+}
+
+impl DiceAnimation {
+    pub fn new(roll: ShownRoll, animated: bool) -> Self {
+        DiceAnimation {
+            roll,
+            started: Instant::now(),
+            tumble: if animated { TUMBLE } else { Duration::ZERO },
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    // Every die's face `elapsed` into the roll, and whether it has settled.
+    pub fn faces(&self, elapsed: Duration) -> Vec<(u8, bool)> {
+        let count = self.roll.dice_results.len() as u32;
+        let frame = (elapsed.as_millis() / FACE_CHANGE.as_millis()) as u64;
+        self.roll
+            .dice_results
+            .iter()
+            .enumerate()
+            .map(|(index, result)| {
+                if elapsed >= self.tumble * (index as u32 + 1) / count {
+                    (*result, true)
+                } else {
+                    // A face that changes every FACE_CHANGE, differently for each die.
+                    let spin =
+                        (index as u64 + 1).wrapping_mul(2654435761) ^ frame.wrapping_mul(40503);
+                    ((spin % 6) as u8 + 1, false)
+                }
+            })
+            .collect()
+    }
+
+    pub fn settled(&self, elapsed: Duration) -> bool {
+        elapsed >= self.tumble
+    }
+
+    pub fn finished(&self, elapsed: Duration) -> bool {
+        elapsed >= self.tumble + SHOWN
+    }
+}
+
+pub fn draw_dice_roll(f: &mut Frame, animation: &DiceAnimation, language: &str, area: Rect) {
+    let elapsed = animation.elapsed();
+    let faces = animation.faces(elapsed);
+    // Hits are only marked when the roll counts them.
+    let counts_hits = animation.roll.total.is_none();
+    let rows: Vec<Line> = faces
+        .chunks(DICE_PER_ROW)
+        .map(|row| {
+            Line::from(
+                row.iter()
+                    .map(|(face, settled)| {
+                        let style = match (face, settled) {
+                            (_, false) => Style::default().fg(palette().muted),
+                            (5 | 6, true) if counts_hits => Style::default()
+                                .fg(palette().success)
+                                .add_modifier(Modifier::BOLD),
+                            (1, true) if counts_hits => Style::default().fg(palette().failure),
+                            _ => Style::default().fg(palette().text),
+                        };
+                        Span::styled(format!("[{}]", face), style)
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect();
+
+    let mut lines = rows;
+    lines.push(Line::raw(""));
+    lines.push(if animation.settled(elapsed) {
+        let style = if animation.roll.critical_glitch || animation.roll.glitch {
+            Style::default()
+                .fg(palette().failure)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(palette().highlight)
+        };
+        Line::styled(animation.roll.summary(), style)
+    } else {
+        Line::styled(
+            tr(language, "Rolling…"),
+            Style::default().fg(palette().muted),
+        )
+    });
+
+    // "[6]", or "[20]" for an expression's bigger dice.
+    let face_width = animation
+        .roll
+        .dice_results
+        .iter()
+        .map(|result| result.to_string().len() as u16 + 2)
+        .max()
+        .unwrap_or(3);
+    let dice_width = faces.len().clamp(1, DICE_PER_ROW) as u16 * face_width;
+    let title = truncate_to_width(&animation.roll.title, 40);
+    let width = (dice_width.max(title.width() as u16 + 2).max(20) + 4).min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Thick)
+        .border_style(Style::default().fg(palette().highlight))
+        .title(format!(" {} ", title));
+    f.render_widget(Clear, popup);
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .alignment(Alignment::Center),
+        popup,
+    );
+}
+
+// The test, its pool and limit, and the modifier the player is setting.
+pub fn draw_roll_popup(f: &mut Frame, popup: &RollPopup, area: Rect) {
+    let mut pool = format!("{} dice", popup.dice());
+    if popup.modifier != 0 {
+        pool.push_str(&format!(" ({} {:+})", popup.pool, popup.modifier));
+    }
+    if let Some(limit) = popup.limit {
+        pool.push_str(&format!(", limit {}", limit));
+    }
+    let lines = vec![
+        Line::styled(
+            pool,
+            Style::default()
+                .fg(palette().highlight)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Line::raw(""),
+        Line::styled(
+            "←/→ modifier   Enter roll   Esc cancel",
+            Style::default().fg(palette().muted),
+        ),
+    ];
+    let title = truncate_to_width(&popup.title(), 40);
+    let width = (title.width() as u16 + 6).max(44).min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let rect = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Thick)
+        .border_style(Style::default().fg(palette().highlight))
+        .title(format!(" {} ", title));
+    f.render_widget(Clear, rect);
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .alignment(Alignment::Center),
+        rect,
+    );
+}
@@ -1,5 +1,6 @@
 // ui/draw.rs
 
+use crate::ui::theme::palette;
 use std::time::Duration;
 
 use crate::app_state::AppState;
@@ -9,13 +10,17 @@ use crate::{app::App, error::ErrorMessage};
 use ratatui::widgets::{List, ListItem};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
+    style::Style,
     text::Span,
     widgets::{Block, Borders, Clear},
     Frame,
 };
 
-use super::{api_key_input, create_image, game, load_game, main_menu, save_name_input, settings};
+use super::{
+    api_key_input, archetype_picker, create_image, data_dir_input, elevenlabs_key_input, game,
+    load_game, logs, macros, main_menu, save_name_input, settings, shop, stats, tasks,
+    webhook_input, wizard,
+};
 
 pub fn draw(f: &mut Frame, app: &mut App) {
     match app.state {
@@ -25,7 +30,16 @@ pub fn draw(f: &mut Frame, app: &mut App) {
         AppState::CreateImage => create_image::draw_create_image(f, app),
         AppState::SettingsMenu => settings::draw_settings(f, app),
         AppState::InputApiKey => api_key_input::draw_api_key_input(f, app),
+        AppState::InputWebhookUrl => webhook_input::draw_webhook_url_input(f, app),
+        AppState::InputElevenLabsKey => elevenlabs_key_input::draw_elevenlabs_key_input(f, app),
+        AppState::InputDataDir => data_dir_input::draw_data_dir_input(f, app),
         AppState::InputSaveName => save_name_input::draw_save_name_input(f, app),
+        AppState::ChooseArchetype => archetype_picker::draw_archetype_picker(f, app),
+        AppState::CharacterWizard => wizard::draw_character_wizard(f, app),
+        AppState::Logs => logs::draw_logs(f, app),
+        AppState::Macros => macros::draw_macros(f, app),
+        AppState::CampaignStats => stats::draw_campaign_stats(f, app),
+        AppState::Shop => shop::draw_shop(f, app),
     }
     let area = f.area();
 
@@ -43,6 +57,7 @@ pub fn draw(f: &mut Frame, app: &mut App) {
 
     // Draw error messages
     draw_error_messages(f, app, chunks[0]);
+    tasks::draw_toasts(f, app);
 }
 
 fn draw_error_messages(f: &mut Frame, app: &App, area: Rect) {
@@ -62,39 +77,47 @@ fn draw_error_messages(f: &mut Frame, app: &App, area: Rect) {
                 let error_text = match &error_message.error {
                     ShadowrunError::Network(msg) => Span::styled(
                         format!("Network Error: {}", msg),
-                        Style::default().fg(Color::Red),
+                        Style::default().fg(palette().failure),
+                    ),
+                    ShadowrunError::Offline(msg) => Span::styled(
+                        format!("Offline: {}", msg),
+                        Style::default().fg(palette().failure),
+                    ),
+                    ShadowrunError::NotFound(msg) => Span::styled(
+                        format!("Not Found: {}", msg),
+                        Style::default().fg(palette().failure),
                     ),
                     ShadowrunError::Audio(msg) => Span::styled(
                         format!("Audio Error: {}", msg),
-                        Style::default().fg(Color::Red),
+                        Style::default().fg(palette().failure),
                     ),
                     ShadowrunError::IO(msg) => Span::styled(
                         format!("IO Error: {}", msg),
-                        Style::default().fg(Color::Red),
+                        Style::default().fg(palette().failure),
                     ),
                     ShadowrunError::OpenAI(msg) => Span::styled(
                         format!("OpenAI Error: {}", msg),
-                        Style::default().fg(Color::Red),
+                        Style::default().fg(palette().failure),
                     ),
                     ShadowrunError::Serialization(msg) => Span::styled(
                         format!("Serialization Error: {}", msg),
-                        Style::default().fg(Color::Red),
+                        Style::default().fg(palette().failure),
                     ),
                     ShadowrunError::Unknown(msg) => Span::styled(
                         format!("Unknown Error: {}", msg),
-                        Style::default().fg(Color::Red),
+                        Style::default().fg(palette().failure),
                     ),
                     ShadowrunError::Game(msg) => Span::styled(
                         format!("Game Error: {}", msg),
-                        Style::default().fg(Color::Red),
+                        Style::default().fg(palette().failure),
                     ),
                     ShadowrunError::UI(msg) => Span::styled(
                         format!("UI Error: {}", msg),
-                        Style::default().fg(Color::Red),
+                        Style::default().fg(palette().failure),
                     ),
                     ShadowrunError::AI(msg) => Span::styled(
                         format!("AI Error: {}", msg),
-                        Style::default().fg(Color::Red),
+                        Style::default().fg(palette().failure),
                     ),
                 };
                 ListItem::new(error_text)
@@ -106,7 +129,7 @@ fn draw_error_messages(f: &mut Frame, app: &App, area: Rect) {
             Block::default()
                 .borders(Borders::ALL)
                 .title(" Error: ")
-                .border_style(Style::default().fg(Color::Red)),
+                .border_style(Style::default().fg(palette().failure)),
         );
 
         f.render_widget(Clear, area); // Clear the area behind the block
@@ -1,27 +1,48 @@
 use crate::app::{App, InputMode};
-use crate::character::CharacterSheet;
-use crate::message::{GameMessage, MessageType, UserMessage};
-use crate::ui::utils::spinner_frame;
-use ratatui::style::Styled;
+use crate::character::{CharacterSheet, ItemState};
+use crate::cleanup::keyboard_enhanced;
+use crate::combat::Combat;
+use crate::dice::reports_glitch;
+use crate::i18n::tr;
+use crate::message::{GameMessage, Message, MessageType, UserMessage};
+use crate::mirror;
+use crate::ruleset::RulesetKind;
+use crate::selection::drawn_line;
+use crate::settings::SubmitKey;
+use crate::status::{Narration, RunActivity};
+use crate::transcript::{relative_time, GUTTER_WIDTH};
+use crate::ui::dice::{draw_dice_roll, draw_roll_popup};
+use crate::ui::tasks::draw_tasks_panel;
+use crate::ui::theme::{palette, section_block};
+use crate::ui::utils::{
+    centered_rect, draw_too_small, truncate_to_width, wrap_to_width, MIN_HEIGHT, MIN_WIDTH,
+};
+use chrono::Local;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Position, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::*,
     Frame,
 };
 use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::Range;
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
+// Main area, left column chunks and game info chunks for the last drawn size.
+type LayoutCache = Option<(Rect, Vec<Rect>, Vec<Rect>)>;
+
 thread_local! {
-    static CACHED_LAYOUTS: RefCell<Option<(Rect, Vec<Rect>, Vec<Rect>)>> = RefCell::new(None);
+    static CACHED_LAYOUTS: RefCell<LayoutCache> = const { RefCell::new(None) };
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum HighlightedSection {
     None,
     Backstory,
+    Skill(String),         // String is the skill name
     InventoryItem(String), // String is the item name
     Contact(String),       // String is the contact name
 }
@@ -30,69 +51,124 @@ pub fn draw_in_game(f: &mut Frame, app: &mut App) {
     let size = f.area();
     *app.debug_info.borrow_mut() = format!("Terminal size: {}x{}", size.width, size.height);
 
-    if size.width < 101 || size.height < 50 {
-        let warning = Paragraph::new("Terminal too small. Please resize.")
-            .style(Style::default().fg(Color::Red))
-            .alignment(Alignment::Center);
-        f.render_widget(warning, size);
+    if draw_too_small(f, &app.settings.language, MIN_WIDTH + 1, MIN_HEIGHT) {
         return;
     }
 
+    // The bottom line holds the status bar, with the debug line under it in debug mode.
+    let reserved = if app.settings.debug_mode { 2 } else { 1 };
+    let body = Rect::new(size.x, size.y, size.width, size.height - reserved);
+    let status_area = Rect::new(size.x, body.bottom(), size.width, 1);
+
     let (_main_chunk, left_chunk, game_info_area) = CACHED_LAYOUTS.with(|cache| {
         let mut cache = cache.borrow_mut();
-        if cache.as_ref().map_or(true, |&(area, _, _)| area != size) {
+        if cache.as_ref().is_none_or(|&(area, _, _)| area != body) {
             let main_chunk = Layout::default()
                 .direction(Direction::Horizontal)
                 .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
-                .split(size);
+                .split(body);
 
             let left_chunk = Layout::default()
                 .direction(Direction::Vertical)
                 .constraints([Constraint::Percentage(80), Constraint::Percentage(20)])
                 .split(main_chunk[0]);
 
-            let new_cache = (size, main_chunk.to_vec(), left_chunk.to_vec());
+            let new_cache = (body, main_chunk.to_vec(), left_chunk.to_vec());
             *cache = Some(new_cache);
         }
 
         let (_, ref main_chunks, ref left_chunks) = cache.as_ref().unwrap();
         (main_chunks.clone(), left_chunks.clone(), main_chunks[1])
     });
-    draw_game_content(f, app, left_chunk[0]);
+    // The combat tracker, under the sheet, while a fight is on.
+    if let Some(game_state) = app
+        .current_game
+        .as_ref()
+        .and_then(|game| game.try_lock().ok())
+    {
+        app.last_known_combat = game_state.combat.clone();
+    }
+    let game_info_area = match &app.last_known_combat {
+        Some(combat) => {
+            let height = (combat.combatants.len() as u16 + 3).min(12);
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(height)])
+                .split(game_info_area);
+            draw_combat_tracker(f, combat, &app.settings.language, chunks[1]);
+            chunks[0]
+        }
+        None => game_info_area,
+    };
+    // What the last turn changed on the sheet, under it, until the next turn.
+    let game_info_area = if app.sheet_changes.is_empty() {
+        game_info_area
+    } else {
+        let height = (app.sheet_changes.len() as u16 + 2).min(8);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(height)])
+            .split(game_info_area);
+        draw_sheet_changes(f, app, chunks[1]);
+        chunks[0]
+    };
+    // The scene gallery takes the right of the transcript while it is open.
+    let transcript_area = if app.show_scenes {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(0), Constraint::Length(SCENE_PANEL_WIDTH)])
+            .split(left_chunk[0]);
+        draw_scene_gallery(f, app, chunks[1]);
+        chunks[0]
+    } else {
+        left_chunk[0]
+    };
+    draw_game_content(f, app, transcript_area);
 
     draw_user_input(f, app, left_chunk[1]);
     app.update_spinner();
-    if app.spinner_active {
-        let spinner_area = Rect::new(
-            left_chunk[0].x,
-            left_chunk[0].bottom() - 1,
-            left_chunk[0].width,
-            1,
-        );
-
-        let spinner_text = spinner_frame(&app.spinner);
-        let spinner_widget = Paragraph::new(spinner_text)
-            .style(Style::default().fg(Color::Green))
-            .alignment(Alignment::Center);
-
-        f.render_widget(spinner_widget, spinner_area);
-    }
+    draw_status_bar(f, app, status_area);
 
     if let Some(game_state) = &app.current_game {
         match game_state.try_lock() {
             Ok(locked_game_state) => {
-                if let Some(sheet) = &locked_game_state.main_character_sheet {
+                let viewed = app
+                    .viewed_character
+                    .as_ref()
+                    .and_then(|name| {
+                        locked_game_state
+                            .characters
+                            .iter()
+                            .find(|character| character.name == *name)
+                    })
+                    .or(locked_game_state.main_character_sheet.as_ref());
+                if let Some(sheet) = viewed {
                     app.last_known_character_sheet = Some(sheet.clone());
+                    app.last_known_ruleset = locked_game_state.ruleset;
+                    app.last_known_characters = locked_game_state
+                        .characters
+                        .iter()
+                        .map(|character| character.name.clone())
+                        .collect();
+                    app.last_known_portrait = locked_game_state.portrait(&sheet.name).cloned();
+                    app.last_known_scenes = locked_game_state.scenes.clone();
 
                     // Split the game_info_area into two parts: character sheet and details
                     let character_sheet_area = game_info_area;
 
-                    draw_character_sheet(f, sheet, character_sheet_area, &app.highlighted_section);
+                    draw_sheet(
+                        f,
+                        sheet,
+                        app.last_known_ruleset,
+                        character_sheet_area,
+                        &app.highlighted_section,
+                    );
+                    draw_sheet_hint(f, app, character_sheet_area);
                     draw_detailed_info(f, sheet, left_chunk[0], &app.highlighted_section);
                 } else {
                     app.last_known_character_sheet = None;
                     let no_character = Paragraph::new("No character sheet available.")
-                        .style(Style::default().fg(Color::Yellow))
+                        .style(Style::default().fg(palette().highlight))
                         .alignment(Alignment::Center);
                     f.render_widget(no_character, game_info_area);
                 }
@@ -104,19 +180,21 @@ pub fn draw_in_game(f: &mut Frame, app: &mut App) {
                         character_sheet_area.x,
                         character_sheet_area.bottom(),
                         character_sheet_area.width,
-                        size.height - character_sheet_area.bottom(),
+                        body.bottom() - character_sheet_area.bottom(),
                     );
 
-                    draw_character_sheet(
+                    draw_sheet(
                         f,
                         last_sheet,
+                        app.last_known_ruleset,
                         character_sheet_area,
                         &app.highlighted_section,
                     );
+                    draw_sheet_hint(f, app, character_sheet_area);
                     draw_detailed_info(f, last_sheet, details_area, &app.highlighted_section);
                 } else {
                     let no_character = Paragraph::new("No character sheet available.")
-                        .style(Style::default().fg(Color::Yellow))
+                        .style(Style::default().fg(palette().highlight))
                         .alignment(Alignment::Center);
                     f.render_widget(no_character, game_info_area);
                 }
@@ -125,22 +203,279 @@ pub fn draw_in_game(f: &mut Frame, app: &mut App) {
     } else {
         app.add_debug_message("No active game".to_string());
         let no_game = Paragraph::new("No active game.")
-            .style(Style::default().fg(Color::Yellow))
+            .style(Style::default().fg(palette().highlight))
             .alignment(Alignment::Center);
         f.render_widget(no_game, game_info_area);
     }
 
+    if app.update_review.is_some() {
+        draw_update_review(f, app, body);
+    }
+    if let Some(popup) = &app.roll_popup {
+        draw_roll_popup(f, popup, left_chunk[0]);
+    }
+    if app.tasks_panel.is_some() {
+        draw_tasks_panel(f, app, left_chunk[0]);
+    }
+    if let Some(animation) = &app.dice_animation {
+        draw_dice_roll(f, animation, &app.settings.language, left_chunk[0]);
+    }
+
     // Debug mode rendering
     if app.settings.debug_mode {
         let debug_area = Rect::new(size.x, size.bottom() - 1, size.width, 1);
-        let debug_text =
-            Paragraph::new(app.debug_info.borrow().clone()).style(Style::default().fg(Color::Gray));
+        let debug_text = Paragraph::new(app.debug_info.borrow().clone())
+            .style(Style::default().fg(palette().muted));
         f.render_widget(debug_text, debug_area);
     }
 }
 
+// One line summing up what the game is doing: assistant, run, narration and recording.
+fn draw_status_bar(f: &mut Frame, app: &App, area: Rect) {
+    let separator = || Span::styled(" │ ", Style::default().fg(palette().dim));
+
+    let mut activity = app
+        .ai_client
+        .as_ref()
+        .map(|ai| ai.activity.get())
+        .unwrap_or_default();
+    // The run only starts once the request is sent, but the player is already waiting.
+    if activity == RunActivity::Idle && app.spinner_active {
+        activity = RunActivity::Submitting;
+    }
+    let activity_span = match &activity {
+        RunActivity::Idle => Span::styled(activity.label(), Style::default().fg(palette().muted)),
+        // The spinner and the seconds would be read out on every frame.
+        _ if app.settings.accessibility_mode => {
+            Span::styled(activity.label(), Style::default().fg(palette().success))
+        }
+        _ => Span::styled(
+            format!(
+                "{} {}",
+                app.spinner.get_frame(),
+                activity.label_with_elapsed(app.spinner_started.elapsed())
+            ),
+            Style::default().fg(palette().success),
+        ),
+    };
+
+    let narration = if !app.settings.audio_output_enabled {
+        Span::styled("Narration: Off", Style::default().fg(palette().dim))
+    } else {
+        let narration = app.narration.get();
+        let color = match narration {
+            Narration::Idle => palette().muted,
+            Narration::Generating => palette().highlight,
+            Narration::Playing { .. } => palette().success,
+        };
+        Span::styled(
+            format!("Narration: {}", narration.label()),
+            Style::default().fg(color),
+        )
+    };
+
+    let recording = if app.input_mode == InputMode::Recording {
+        Span::styled(
+            "● Recording (Esc to stop)",
+            Style::default()
+                .fg(palette().failure)
+                .add_modifier(Modifier::BOLD),
+        )
+    } else if app.settings.audio_input_enabled {
+        Span::styled("Mic: Ready", Style::default().fg(palette().muted))
+    } else {
+        Span::styled("Mic: Off", Style::default().fg(palette().dim))
+    };
+
+    let backend = match &app.ai_client {
+        Some(_) if app.offline => Span::styled(
+            format!("● Offline · {} queued", app.offline_queue.len()),
+            Style::default()
+                .fg(palette().failure)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Some(_) => Span::styled(
+            format!("OpenAI · {}", app.settings.narrator_model),
+            Style::default().fg(palette().info),
+        ),
+        None => Span::styled(
+            "OpenAI · not connected",
+            Style::default().fg(palette().failure),
+        ),
+    };
+
+    let mut spans = vec![
+        Span::raw(" "),
+        backend,
+        separator(),
+        activity_span,
+        separator(),
+        narration,
+        separator(),
+        recording,
+    ];
+    let running = app.tasks.running();
+    if running > 0 {
+        spans.push(separator());
+        spans.push(Span::styled(
+            format!("Tasks: {} ('t')", running),
+            Style::default().fg(palette().info),
+        ));
+    }
+    let line = Line::from(spans);
+    f.render_widget(Paragraph::new(line), area);
+}
+
+const SCENE_PANEL_WIDTH: u16 = 32;
+
+// The scenes illustrated in this save, newest first. Terminals can't show the pictures, so each
+// is a card to open in the image viewer.
+fn draw_scene_gallery(f: &mut Frame, app: &App, area: Rect) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(palette().dim))
+        .title(format!(" {} ", tr(&app.settings.language, "Scenes")))
+        .title_bottom(Line::styled(
+            " g: hide · v: view ",
+            Style::default().fg(palette().muted),
+        ));
+    let width = block.inner(area).width as usize;
+    let now = Local::now().timestamp();
+    let mut lines = Vec::new();
+    for (index, scene) in app.last_known_scenes.iter().rev().enumerate() {
+        let marker = if index == 0 { "▸ " } else { "  " };
+        for (row, line) in wrap_to_width(&scene.title, width.saturating_sub(2))
+            .into_iter()
+            .enumerate()
+        {
+            lines.push(Line::styled(
+                format!("{}{}", if row == 0 { marker } else { "  " }, line),
+                Style::default().fg(palette().highlight),
+            ));
+        }
+        let file = scene
+            .path
+            .file_name()
+            .map_or_else(String::new, |name| name.to_string_lossy().to_string());
+        lines.push(Line::styled(
+            truncate_to_width(
+                &format!("  {} · {}", relative_time(scene.created_at, now), file),
+                width,
+            ),
+            Style::default().fg(palette().muted),
+        ));
+        lines.push(Line::raw(""));
+    }
+    if lines.is_empty() {
+        lines.push(Line::styled(
+            tr(
+                &app.settings.language,
+                "No scenes yet. Try /scene <prompt>.",
+            ),
+            Style::default().fg(palette().muted),
+        ));
+    }
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        area,
+    );
+}
+
+// Over the sheet's top border: whose sheet it is among the characters, and the portrait keys.
+fn draw_sheet_hint(f: &mut Frame, app: &App, area: Rect) {
+    let mut hints = Vec::new();
+    let count = app.last_known_characters.len();
+    if count > 1 {
+        let position = app
+            .last_known_character_sheet
+            .as_ref()
+            .and_then(|sheet| {
+                app.last_known_characters
+                    .iter()
+                    .position(|name| *name == sheet.name)
+            })
+            .map_or(1, |index| index + 1);
+        hints.push(format!("{}/{} · n: next", position, count));
+    }
+    if app.last_known_portrait.is_some() {
+        hints.push("p: portrait".to_string());
+    }
+    if hints.is_empty() || area.height == 0 {
+        return;
+    }
+    let hint = Line::styled(
+        format!(" {} ", hints.join(" · ")),
+        Style::default().fg(palette().muted),
+    )
+    .right_aligned();
+    let width = (hint.width() as u16 + 1).min(area.width);
+    let hint_area = Rect {
+        x: area.right() - width,
+        width: width.saturating_sub(1),
+        height: 1,
+        ..area
+    };
+    f.render_widget(Paragraph::new(hint), hint_area);
+}
+
 // Function to draw the character sheet.
-fn draw_character_sheet(
+// A Shadowrun sheet, or the entries of a freeform one.
+fn draw_sheet(
+    f: &mut Frame,
+    sheet: &CharacterSheet,
+    ruleset: RulesetKind,
+    area: Rect,
+    highlighted: &HighlightedSection,
+) {
+    if ruleset.rules().shadowrun_sheet() {
+        draw_character_sheet(f, sheet, area, highlighted);
+    } else {
+        draw_freeform_sheet(f, sheet, area, highlighted);
+    }
+}
+
+fn draw_freeform_sheet(
+    f: &mut Frame,
+    sheet: &CharacterSheet,
+    area: Rect,
+    highlighted: &HighlightedSection,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)])
+        .split(area);
+
+    let name = Paragraph::new(Line::from(vec![
+        Span::styled(
+            "Name: ",
+            Style::default()
+                .fg(palette().highlight)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(&sheet.name),
+    ]))
+    .block(section_block(
+        "Basic Information",
+        matches!(highlighted, HighlightedSection::Backstory),
+    ))
+    .alignment(Alignment::Center);
+    f.render_widget(name, chunks[0]);
+
+    let entries: Vec<String> = if sheet.fields.is_empty() {
+        vec!["Nothing on the sheet yet.".to_string()]
+    } else {
+        sheet
+            .fields
+            .iter()
+            .map(|(key, value)| format!("{}: {}", key, value))
+            .collect()
+    };
+    f.render_widget(create_table(&entries, "Sheet"), chunks[1]);
+}
+
+pub fn draw_character_sheet(
     f: &mut Frame,
     sheet: &CharacterSheet,
     area: Rect,
@@ -176,10 +511,55 @@ pub fn draw_detailed_info(
     let detail_text = match highlighted {
         HighlightedSection::None => unreachable!(), // We've already returned in this case
         HighlightedSection::Backstory => sheet.backstory.clone(),
-        HighlightedSection::InventoryItem(_) => sheet
-            .inventory
-            .values()
-            .map(|item| format!("{}: {} (x{})", item.name, item.description, item.quantity))
+        HighlightedSection::Skill(selected) => sheet
+            .sorted_active_skills()
+            .into_iter()
+            .map(|(skill, rating)| {
+                let test = match sheet.skill_test(skill) {
+                    Some((attribute, _)) => format!(
+                        "{} + {}: {} dice",
+                        attribute,
+                        skill,
+                        sheet.get_dice_pool(attribute, skill)
+                    ),
+                    None => String::new(),
+                };
+                format!(
+                    "{}{} {} ({})",
+                    if skill == selected { "> " } else { "  " },
+                    skill,
+                    rating,
+                    test
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        HighlightedSection::InventoryItem(selected) => sheet
+            .sorted_inventory()
+            .into_iter()
+            .map(|(key, item)| {
+                let mut gear = vec![item.state.to_string()];
+                if item.armor_rating() > 0 {
+                    gear.push(format!("armor {}", item.armor_rating()));
+                }
+                if let Some(reach) = item.reach.filter(|reach| *reach > 0) {
+                    gear.push(format!("reach {}", reach));
+                }
+                if let Some(rc) = item.recoil_compensation.filter(|rc| *rc > 0) {
+                    gear.push(format!("RC {}", rc));
+                }
+                if let Some(weight) = item.weight.filter(|weight| *weight > 0.0) {
+                    gear.push(format!("{} kg", weight));
+                }
+                format!(
+                    "{}{} (x{}, {}): {}",
+                    if key == selected { "> " } else { "  " },
+                    item.name,
+                    item.quantity,
+                    gear.join(", "),
+                    item.description
+                )
+            })
             .collect::<Vec<_>>()
             .join("\n\n"),
         HighlightedSection::Contact(_) => sheet
@@ -194,12 +574,12 @@ pub fn draw_detailed_info(
             .collect::<Vec<_>>()
             .join("\n\n"),
     };
-    // Wrap the text to fit within the area width
-    let wrapped_text = textwrap::wrap(&detail_text, area.width as usize - 4); // -4 for margins
-    let content_height = wrapped_text.len() as u16 + 2; // +2 for top and bottom margins
-
     // Calculate the size and position of the floating frame
     let width = area.width.saturating_sub(4).max(20); // Minimum width of 20
+
+    // Wrap the text to the inside of the frame, so the height matches what is drawn
+    let wrapped_text = wrap_to_width(&detail_text, width.saturating_sub(2) as usize);
+    let content_height = wrapped_text.len() as u16 + 2; // +2 for top and bottom margins
     let height = content_height.min(area.height.saturating_sub(4));
     let x = area.x + (area.width - width) / 2;
     let y = area.y + (area.height - height) / 2;
@@ -209,10 +589,13 @@ pub fn draw_detailed_info(
     // Create a block for the floating frame
     let block = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::White))
+        .border_style(Style::default().fg(palette().text))
         .title(match highlighted {
             HighlightedSection::Backstory => " Backstory ",
-            HighlightedSection::InventoryItem(_) => " Inventory Details ",
+            HighlightedSection::Skill(_) => " Skills: ←/→ skill, Enter roll ",
+            HighlightedSection::InventoryItem(_) => {
+                " Inventory Details: ←/→ item, s equip / carry / stash "
+            }
             HighlightedSection::Contact(_) => " Contact Details ",
             _ => " Details ",
         })
@@ -226,7 +609,7 @@ pub fn draw_detailed_info(
     let inner_area = block.inner(details_area);
 
     let detail_paragraph = Paragraph::new(wrapped_text.join("\n"))
-        .style(Style::default().fg(Color::White))
+        .style(Style::default().fg(palette().text))
         .alignment(Alignment::Left)
         .wrap(Wrap { trim: true });
 
@@ -245,7 +628,7 @@ fn draw_basic_info(
         Span::styled(
             "Name: ",
             Style::default()
-                .fg(Color::Yellow)
+                .fg(palette().highlight)
                 .add_modifier(Modifier::BOLD),
         ),
         Span::raw(&sheet.name),
@@ -253,7 +636,7 @@ fn draw_basic_info(
         Span::styled(
             "Race: ",
             Style::default()
-                .fg(Color::Yellow)
+                .fg(palette().highlight)
                 .add_modifier(Modifier::BOLD),
         ),
         Span::raw(format!("{}", sheet.race)),
@@ -261,24 +644,16 @@ fn draw_basic_info(
         Span::styled(
             "Gender: ",
             Style::default()
-                .fg(Color::Yellow)
+                .fg(palette().highlight)
                 .add_modifier(Modifier::BOLD),
         ),
         Span::raw(&sheet.gender),
     ];
     let basic_info = Paragraph::new(Line::from(info))
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(
-                    if matches!(highlighted, HighlightedSection::Backstory) {
-                        Color::Yellow
-                    } else {
-                        Color::White
-                    },
-                ))
-                .title(" Basic Information "),
-        )
+        .block(section_block(
+            "Basic Information",
+            matches!(highlighted, HighlightedSection::Backstory),
+        ))
         .alignment(Alignment::Center)
         .wrap(Wrap { trim: true });
     f.render_widget(basic_info, area);
@@ -306,7 +681,7 @@ fn draw_attributes(
     f: &mut Frame,
     sheet: &CharacterSheet,
     area: Rect,
-    highlighted: &HighlightedSection,
+    _highlighted: &HighlightedSection,
 ) {
     let attributes = vec![
         ("BODY", sheet.body),
@@ -328,7 +703,7 @@ fn draw_attributes(
             Row::new(chunk.iter().map(|(name, value)| {
                 Cell::from(Span::styled(
                     format!("{}: {}", name, value),
-                    Style::default().fg(Color::Green),
+                    Style::default().fg(palette().primary),
                 ))
             }))
         })
@@ -336,7 +711,7 @@ fn draw_attributes(
 
     let table = Table::new(rows, vec![Constraint::Percentage(25); 4])
         .block(Block::default().borders(Borders::ALL).title(" Attributes "))
-        .style(Style::default().fg(Color::White))
+        .style(Style::default().fg(palette().text))
         .highlight_style(Style::default().add_modifier(Modifier::BOLD))
         .column_spacing(1);
 
@@ -349,17 +724,19 @@ fn draw_derived_attributes(
     f: &mut Frame,
     sheet: &CharacterSheet,
     area: Rect,
-    highlighted: &HighlightedSection,
+    _highlighted: &HighlightedSection,
 ) {
     let derived = [
         format!(
             "Initiative:  {}+{}d6",
             sheet.initiative.0, sheet.initiative.1
         ),
-        format!(
-            "Limits:  PHY:{} MEN:{} SOC:{}",
-            sheet.physical_limit, sheet.mental_limit, sheet.social_limit
-        ),
+        match sheet.limits() {
+            Some((physical, mental, social)) => {
+                format!("Limits:  PHY:{} MEN:{} SOC:{}", physical, mental, social)
+            }
+            None => "Limits:  none".to_string(),
+        },
         format!(
             "Monitors:  PHY:{} SOC:{}",
             sheet.physical_monitor, sheet.stun_monitor
@@ -369,16 +746,15 @@ fn draw_derived_attributes(
         format!("Armor:  {}", sheet.armor),
     ];
 
-    let rows: Vec<Row> = derived
-        .chunks(2)
-        .map(|chunk| {
-            Row::new(
-                chunk
-                    .iter()
-                    .map(|attr| Cell::from(Span::styled(attr, Style::default().fg(Color::Cyan)))),
-            )
-        })
-        .collect();
+    let rows: Vec<Row> =
+        derived
+            .chunks(2)
+            .map(|chunk| {
+                Row::new(chunk.iter().map(|attr| {
+                    Cell::from(Span::styled(attr, Style::default().fg(palette().info)))
+                }))
+            })
+            .collect();
 
     let table = Table::new(rows, vec![Constraint::Percentage(50); 2])
         .block(
@@ -386,7 +762,7 @@ fn draw_derived_attributes(
                 .borders(Borders::ALL)
                 .title(" Derived Attributes "),
         )
-        .style(Style::default().fg(Color::White))
+        .style(Style::default().fg(palette().text))
         .highlight_style(Style::default().add_modifier(Modifier::BOLD))
         .column_spacing(1);
 
@@ -429,19 +805,18 @@ fn draw_skills(
         ("Knowledge", &sheet.knowledge_skills),
     ];
 
+    // The skills column: 80% of the inside of the block, less the column spacing.
+    let skills_width = (area.width.saturating_sub(2) as usize * 80 / 100).saturating_sub(1);
+
     let rows: Vec<Row> = categories
         .iter()
         .map(|(category, skills)| {
-            let skills_str = skills
-                .iter()
-                .map(|(skill, rating)| format!("{}:{}", skill, rating))
-                .collect::<Vec<_>>()
-                .join(", ");
+            let skills_str = skills_line(skills, skills_width);
             Row::new(vec![
                 Cell::from(Span::styled(
                     *category,
                     Style::default()
-                        .fg(Color::Yellow)
+                        .fg(palette().highlight)
                         .add_modifier(Modifier::BOLD),
                 )),
                 Cell::from(skills_str),
@@ -453,12 +828,42 @@ fn draw_skills(
         rows,
         vec![Constraint::Percentage(20), Constraint::Percentage(80)],
     )
-    .block(Block::default().borders(Borders::ALL).title(" Skills "))
-    .style(Style::default().fg(Color::White))
+    .block(section_block(
+        "Skills",
+        matches!(highlighted, HighlightedSection::Skill(_)),
+    ))
+    .style(Style::default().fg(palette().text))
     .highlight_style(Style::default().add_modifier(Modifier::BOLD))
     .column_spacing(1);
 
-    f.render_widget(table, area);
+    // The row of the skill picked in the details.
+    let mut state = TableState::default();
+    if let HighlightedSection::Skill(selected) = highlighted {
+        state.select(
+            categories
+                .iter()
+                .position(|(_, skills)| skills.contains_key(selected)),
+        );
+    }
+    f.render_stateful_widget(table, area, &mut state);
+}
+
+// List skills as "name:rating" on one line of `width` cells, shortening the names as much as
+// needed to keep every rating visible.
+pub fn skills_line(skills: &HashMap<String, u8>, width: usize) -> String {
+    let line = |name_width: usize| {
+        skills
+            .iter()
+            .map(|(skill, rating)| format!("{}:{}", truncate_to_width(skill, name_width), rating))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let longest = skills.keys().map(|skill| skill.width()).max().unwrap_or(0);
+    (3..=longest)
+        .rev()
+        .map(line)
+        .find(|candidate| candidate.width() <= width)
+        .unwrap_or_else(|| truncate_to_width(&line(3), width))
 }
 
 // Function to handle the display of qualities.
@@ -467,21 +872,26 @@ fn draw_qualities(
     f: &mut Frame,
     sheet: &CharacterSheet,
     area: Rect,
-    highlighted: &HighlightedSection,
+    _highlighted: &HighlightedSection,
 ) {
     let qualities: Vec<Span> = sheet
         .qualities
         .iter()
         .enumerate()
         .map(|(i, q)| {
-            let color = if q.positive { Color::Green } else { Color::Red };
+            // Marked + or - as well as coloured, for players who can't tell the colours apart.
+            let (sign, color) = if q.positive {
+                ('+', palette().positive)
+            } else {
+                ('-', palette().negative)
+            };
             let separator = if i == sheet.qualities.len() - 1 {
                 ""
             } else {
                 ", "
             };
             Span::styled(
-                format!("{}{}", q.name, separator),
+                format!("{}{}{}", sign, q.name, separator),
                 Style::default().fg(color),
             )
         })
@@ -535,7 +945,7 @@ fn draw_resources(
     f: &mut Frame,
     sheet: &CharacterSheet,
     area: Rect,
-    highlighted: &HighlightedSection,
+    _highlighted: &HighlightedSection,
 ) {
     let info = vec![
         format!("Lifestyle: {}", sheet.lifestyle),
@@ -550,7 +960,7 @@ fn draw_augmentations(
     f: &mut Frame,
     sheet: &CharacterSheet,
     area: Rect,
-    highlighted: &HighlightedSection,
+    _highlighted: &HighlightedSection,
 ) {
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
@@ -564,9 +974,9 @@ fn draw_augmentations(
             Line::from(Span::styled(
                 cw.clone(),
                 Style::default().fg(if sheet.cyberware.is_empty() {
-                    Color::DarkGray
+                    palette().dim
                 } else {
-                    Color::White
+                    palette().text
                 }),
             ))
         })
@@ -579,9 +989,9 @@ fn draw_augmentations(
             Line::from(Span::styled(
                 bw.clone(),
                 Style::default().fg(if sheet.bioware.is_empty() {
-                    Color::DarkGray
+                    palette().dim
                 } else {
-                    Color::White
+                    palette().text
                 }),
             ))
         })
@@ -607,7 +1017,7 @@ fn draw_contacts(
 ) {
     let header_cells = ["Name", "Loyalty", "Connection"]
         .iter()
-        .map(|h| Cell::from(*h).style(Style::default().fg(Color::Yellow)));
+        .map(|h| Cell::from(*h).style(Style::default().fg(palette().highlight)));
     let header = Row::new(header_cells)
         .style(Style::default())
         .height(1)
@@ -617,7 +1027,7 @@ fn draw_contacts(
         .contacts
         .iter()
         .map(|(name, contact)| {
-            let style = Style::default().fg(Color::White);
+            let style = Style::default().fg(palette().text);
             let cells = vec![
                 Cell::from(name.clone()).style(style),
                 Cell::from(contact.loyalty.to_string()),
@@ -632,18 +1042,10 @@ fn draw_contacts(
         Constraint::Percentage(30),
         Constraint::Percentage(40),
     ];
-    let table = Table::new(rows, widths).header(header).block(
-        Block::default()
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(
-                if matches!(highlighted, HighlightedSection::Contact(_)) {
-                    Color::Yellow
-                } else {
-                    Color::White
-                },
-            ))
-            .title(" Contacts "),
-    );
+    let table = Table::new(rows, widths).header(header).block(section_block(
+        "Contacts",
+        matches!(highlighted, HighlightedSection::Contact(_)),
+    ));
 
     f.render_widget(table, area);
 }
@@ -655,36 +1057,69 @@ fn draw_inventory(
     highlighted: &HighlightedSection,
 ) {
     let inventory_items: Vec<Row> = sheet
-        .inventory
-        .values()
-        .map(|item| {
-            let style = Style::default().fg(Color::White);
+        .sorted_inventory()
+        .into_iter()
+        .map(|(_, item)| {
+            let style = match item.state {
+                ItemState::Equipped => Style::default().fg(palette().highlight),
+                ItemState::Carried => Style::default().fg(palette().text),
+                ItemState::Stashed => Style::default().fg(palette().dim),
+            };
+            let state = match item.state {
+                ItemState::Carried => String::new(),
+                state => format!(" [{}]", state),
+            };
             Row::new(vec![Cell::from(format!(
-                "{} (x{})",
-                item.name, item.quantity
+                "{} (x{}){}",
+                item.name, item.quantity, state
             ))
             .style(style)])
         })
         .collect();
 
+    let block = section_block(
+        "Inventory",
+        matches!(highlighted, HighlightedSection::InventoryItem(_)),
+    );
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(inner);
+
     let widths = vec![Constraint::Percentage(100)];
     let inventory_table = Table::new(inventory_items, widths)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(" Inventory ")
-                .border_style(Style::default().fg(
-                    if matches!(highlighted, HighlightedSection::InventoryItem(_)) {
-                        Color::Yellow
-                    } else {
-                        Color::White
-                    },
-                )),
-        )
         .widths([Constraint::Percentage(100)])
         .column_spacing(1);
+    f.render_widget(inventory_table, chunks[0]);
+    draw_encumbrance(f, sheet, chunks[1]);
+}
 
-    f.render_widget(inventory_table, area);
+// How much of the character's carry capacity their gear takes, and the cost of going over.
+fn draw_encumbrance(f: &mut Frame, sheet: &CharacterSheet, area: Rect) {
+    let load = sheet.carried_weight();
+    let capacity = sheet.carry_capacity();
+    let penalty = sheet.encumbrance_penalty();
+    let (label, color) = if penalty > 0 {
+        (
+            format!("{:.1}/{:.0} kg, -{} AGI/REA", load, capacity, penalty),
+            palette().failure,
+        )
+    } else {
+        (format!("{:.1}/{:.0} kg", load, capacity), palette().success)
+    };
+    let ratio = if capacity > 0.0 {
+        (load / capacity).clamp(0.0, 1.0) as f64
+    } else {
+        1.0
+    };
+    let gauge = LineGauge::default()
+        .ratio(ratio)
+        .label(label)
+        .filled_style(Style::default().fg(color))
+        .unfilled_style(Style::default().fg(palette().dim));
+    f.render_widget(gauge, area);
 }
 
 // Helper function to create a styled table from given information.
@@ -694,7 +1129,7 @@ fn create_table<'a>(info: &'a [String], title: &'a str) -> Table<'a> {
         .map(|item| {
             Row::new(vec![Cell::from(Span::styled(
                 item.to_string(),
-                Style::default().fg(Color::White),
+                Style::default().fg(palette().text),
             ))])
         })
         .collect();
@@ -707,12 +1142,152 @@ fn create_table<'a>(info: &'a [String], title: &'a str) -> Table<'a> {
                 .borders(Borders::ALL)
                 .title(format!(" {} ", title)),
         )
-        .style(Style::default().fg(Color::White))
+        .style(Style::default().fg(palette().text))
         .highlight_style(Style::default().add_modifier(Modifier::BOLD))
         .highlight_symbol(">>")
         .column_spacing(1)
 }
 
+// The AI's held sheet updates, one per line, with what each would change.
+fn draw_update_review(f: &mut Frame, app: &App, area: Rect) {
+    let Some(review) = &app.update_review else {
+        return;
+    };
+    let popup = centered_rect(70, 50, area);
+    let width = popup.width.saturating_sub(4) as usize;
+    let mut lines: Vec<Line> = Vec::new();
+    for (i, pending) in review.pending.iter().enumerate() {
+        let text = format!("{}: {}", pending.character_name, pending.changes.join(", "));
+        let (marker, style) = if i == review.selected {
+            ("> ", palette().selected())
+        } else {
+            ("  ", Style::default().fg(palette().text))
+        };
+        for (n, line) in wrap_to_width(&text, width.saturating_sub(2))
+            .into_iter()
+            .enumerate()
+        {
+            let marker = if n == 0 { marker } else { "  " };
+            lines.push(Line::styled(format!("{}{}", marker, line), style));
+        }
+    }
+    lines.push(Line::raw(""));
+    lines.push(Line::styled(
+        tr(
+            &app.settings.language,
+            "y accept   n reject   Y accept all   N reject all",
+        ),
+        Style::default().fg(palette().muted),
+    ));
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Thick)
+        .border_style(Style::default().fg(palette().highlight))
+        .title(format!(
+            " {} ",
+            tr(&app.settings.language, "Sheet updates to review")
+        ));
+    f.render_widget(Clear, popup);
+    f.render_widget(Paragraph::new(lines).block(block), popup);
+}
+
+// Everyone in the fight by initiative score in this pass, the one acting highlighted. Those out of
+// the pass, having acted or with no score left, are dimmed at the bottom.
+fn draw_combat_tracker(f: &mut Frame, combat: &Combat, language: &str, area: Rect) {
+    let order = combat.order();
+    let mut combatants: Vec<_> = combat.combatants.iter().collect();
+    combatants.sort_by_key(|combatant| {
+        (
+            !order.iter().any(|acting| acting.name == combatant.name),
+            -combatant.score(combat.pass),
+        )
+    });
+    let rows: Vec<Row> = combatants
+        .into_iter()
+        .map(|combatant| {
+            let acting = combat.current.as_deref() == Some(combatant.name.as_str());
+            let waiting = order.iter().any(|next| next.name == combatant.name);
+            let style = if acting {
+                palette().selected()
+            } else if waiting {
+                Style::default().fg(palette().text)
+            } else {
+                Style::default().fg(palette().dim)
+            };
+            let wounds = match combatant.wound_modifier() {
+                0 => String::new(),
+                modifier => modifier.to_string(),
+            };
+            Row::new(vec![
+                Cell::from(format!(
+                    "{}{}",
+                    if acting { "> " } else { "  " },
+                    combatant.name
+                )),
+                Cell::from(combatant.score(combat.pass).max(0).to_string()),
+                Cell::from(format!(
+                    "{}P {}S",
+                    combatant.physical_damage, combatant.stun_damage
+                )),
+                Cell::from(wounds),
+            ])
+            .style(style)
+        })
+        .collect();
+    let environment = match combat.environment {
+        0 => String::new(),
+        modifier => format!(", environment {}", modifier),
+    };
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(10),
+            Constraint::Length(5),
+            Constraint::Length(9),
+            Constraint::Length(4),
+        ],
+    )
+    .header(
+        Row::new(vec!["", "Init", "Damage", "Mod"]).style(
+            Style::default()
+                .fg(palette().highlight)
+                .add_modifier(Modifier::BOLD),
+        ),
+    )
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(palette().failure))
+            .title(format!(
+                " {} {}, pass {}{} ",
+                tr(language, "Round"),
+                combat.round,
+                combat.pass,
+                environment
+            )),
+    );
+    f.render_widget(table, area);
+}
+
+fn draw_sheet_changes(f: &mut Frame, app: &App, area: Rect) {
+    let width = area.width.saturating_sub(4) as usize;
+    let lines: Vec<Line> = app
+        .sheet_changes
+        .iter()
+        .map(|change| {
+            Line::styled(
+                format!("• {}", truncate_to_width(change, width)),
+                Style::default().fg(palette().info),
+            )
+        })
+        .collect();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(palette().text))
+        .title(format!(" {} ", tr(&app.settings.language, "Last turn")));
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
 pub fn draw_game_content(f: &mut Frame, app: &mut App, area: Rect) {
     let save_name = app
         .save_manager
@@ -720,43 +1295,85 @@ pub fn draw_game_content(f: &mut Frame, app: &mut App, area: Rect) {
         .clone()
         .map_or_else(|| String::from("Loading..."), |save| save.save_name);
 
-    let fluff_block = Block::default()
+    // Older history loads when scrolling past the top, so say when there is more or it is coming.
+    let history_hint = match &app.older_history {
+        Some(history) if history.loading => tr(&app.settings.language, "Loading older messages..."),
+        Some(_) if app.game_content_scroll == 0 => {
+            tr(&app.settings.language, "Scroll up for older messages")
+        }
+        _ => "",
+    };
+
+    let mut fluff_block = Block::default()
         .title(if save_name.is_empty() {
             " Game will start momentarily ".to_string()
         } else {
             format!(" {} ", save_name)
         })
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Green));
+        .border_style(Style::default().fg(palette().primary));
+    if !history_hint.is_empty() {
+        fluff_block = fluff_block.title_top(
+            Line::styled(
+                format!(" {} ", history_hint),
+                Style::default().fg(palette().muted),
+            )
+            .right_aligned(),
+        );
+    }
 
     f.render_widget(&fluff_block, area);
 
-    let fluff_area = fluff_block.inner(area);
+    let mut fluff_area = fluff_block.inner(area);
+    // How long ago each message was written, in a column of its own so alignment is kept.
+    let gutter_area = app.settings.message_times.then(|| {
+        let gutter_width = GUTTER_WIDTH.min(fluff_area.width);
+        let gutter = Rect {
+            width: gutter_width,
+            ..fluff_area
+        };
+        fluff_area.x += gutter_width;
+        fluff_area.width -= gutter_width;
+        gutter
+    });
 
     let max_width = fluff_area.width.saturating_sub(2) as usize;
     let max_height = fluff_area.height.saturating_sub(2) as usize;
 
-    if app.cached_game_content.is_none()
-        || app.cached_content_len != app.game_content.borrow().len()
-    {
-        app.update_cached_content(max_width);
-    }
-
-    let all_lines = app.cached_game_content.as_ref().unwrap();
-
-    app.total_lines = all_lines.len();
+    app.refresh_transcript(max_width);
     *app.debug_info.borrow_mut() += &format!(", Total lines: {}", app.total_lines);
 
-    let visible_lines: Vec<Line> = all_lines
-        .iter()
-        .skip(app.game_content_scroll)
-        .take(max_height)
-        .map(|(line, alignment)| {
-            let mut new_line = line.clone();
-            new_line.alignment = Some(*alignment);
-            new_line
-        })
-        .collect();
+    let spoken = match app.narration.get() {
+        Narration::Playing { message, line } => Some((message, line)),
+        _ => None,
+    };
+    let spoken_lines =
+        spoken.and_then(|(message, line)| app.transcript.dialogue_line(message, line));
+    // Scroll to each narrated line as it starts, and leave the scroll to the player in between.
+    if spoken != app.followed_narration {
+        app.followed_narration = spoken;
+        if let Some(lines) = &spoken_lines {
+            if lines.start < app.game_content_scroll
+                || lines.end > app.game_content_scroll + max_height
+            {
+                app.game_content_scroll = lines.start;
+            }
+        }
+    }
+
+    let mut visible_lines = app.transcript.lines(app.game_content_scroll, max_height);
+    if let Some(lines) = spoken_lines {
+        let highlight = Style::default()
+            .fg(palette().highlight)
+            .add_modifier(Modifier::BOLD);
+        for (offset, line) in visible_lines.iter_mut().enumerate() {
+            if lines.contains(&(app.game_content_scroll + offset)) {
+                for span in &mut line.spans {
+                    span.style = span.style.patch(highlight);
+                }
+            }
+        }
+    }
 
     *app.debug_info.borrow_mut() += &format!(", Visible lines: {}", visible_lines.len());
 
@@ -765,82 +1382,229 @@ pub fn draw_game_content(f: &mut Frame, app: &mut App, area: Rect) {
         .wrap(Wrap { trim: true });
 
     f.render_widget(content, fluff_area);
+    // The rows lines were drawn in, for the mouse.
+    let transcript_area = Rect {
+        height: max_height as u16,
+        ..fluff_area
+    };
+    app.transcript_area = Some(transcript_area);
+    draw_selection(f, app, transcript_area);
+    if let Some(gutter_area) = gutter_area {
+        let gutter = app.transcript.gutter(
+            app.game_content_scroll,
+            max_height,
+            Local::now().timestamp(),
+        );
+        f.render_widget(
+            Paragraph::new(gutter)
+                .style(Style::default().fg(palette().dim))
+                .alignment(Alignment::Right),
+            gutter_area,
+        );
+    }
 
     app.visible_lines = max_height;
     app.update_scroll();
     app.update_debug_info();
 }
 
-pub fn parse_game_content(app: &App, max_width: usize) -> Vec<(Line<'static>, Alignment)> {
-    let mut all_lines = Vec::new();
-
-    for message in app.game_content.borrow().iter() {
-        let (content, base_style, alignment) = match message.message_type {
-            MessageType::Game => {
-                if let Ok(game_message) = serde_json::from_str::<GameMessage>(&message.content) {
-                    (
-                        format!(
-                            "crunch:\n{}\n\nfluff:\n{}",
-                            game_message.crunch,
-                            game_message.fluff.render()
-                        ),
-                        Style::default().fg(Color::Green),
-                        Alignment::Left,
-                    )
-                } else {
-                    (
-                        message.content.clone(),
-                        Style::default().fg(Color::Green),
-                        Alignment::Left,
-                    )
-                }
-            }
-            MessageType::User => {
-                if let Ok(user_message) = serde_json::from_str::<UserMessage>(&message.content) {
-                    (
-                        format!("\nPlayer action:\n{}", user_message.player_action),
-                        Style::default().fg(Color::Cyan),
-                        Alignment::Right,
-                    )
-                } else {
-                    (
-                        message.content.clone(),
-                        Style::default().fg(Color::Cyan),
-                        Alignment::Right,
-                    )
-                }
-            }
-            MessageType::System => (
-                message.content.clone(),
-                Style::default().fg(Color::Yellow),
-                Alignment::Center,
-            ),
+// The text selected with the mouse, in reverse video over the drawn transcript.
+fn draw_selection(f: &mut Frame, app: &App, area: Rect) {
+    let Some(selection) = app.selection.filter(|selection| !selection.is_empty()) else {
+        return;
+    };
+    let lines = app
+        .transcript
+        .lines(app.game_content_scroll, area.height as usize);
+    for (row, line) in lines.iter().enumerate() {
+        let Some(columns) = selection.columns(app.game_content_scroll + row) else {
+            continue;
         };
+        let (text, offset) = drawn_line(line, area.width);
+        let start = columns.start.max(offset);
+        let end = columns
+            .end
+            .min(offset.saturating_add(text.width() as u16))
+            .min(area.width);
+        if start < end {
+            f.buffer_mut().set_style(
+                Rect::new(area.x + start, area.y + row as u16, end - start, 1),
+                Style::default().add_modifier(Modifier::REVERSED),
+            );
+        }
+    }
+}
 
-        let wrapped_lines = textwrap::wrap(&content, max_width);
-        for line in wrapped_lines {
-            let parsed_line = parse_markdown(line.to_string(), base_style);
-            all_lines.push((parsed_line, alignment));
+// The lines of one transcript message, wrapped to `max_width` and aligned by who is speaking.
+pub fn parse_message(message: &Message, max_width: usize, accessible: bool) -> Vec<Line<'static>> {
+    // One labelled block per message, all left-aligned and unstyled, so it reads top to bottom.
+    if accessible {
+        let mut lines: Vec<Line<'static>> = wrap_to_width(&mirror::plain_text(message), max_width)
+            .into_iter()
+            .map(Line::raw)
+            .collect();
+        lines.push(Line::raw(""));
+        return lines;
+    }
+
+    let (content, base_style, alignment) = match message.message_type {
+        MessageType::Game => {
+            if let Ok(game_message) = serde_json::from_str::<GameMessage>(&message.content) {
+                (
+                    format!(
+                        "{}{}",
+                        game_header(&game_message),
+                        game_message.fluff.render()
+                    ),
+                    Style::default().fg(palette().primary),
+                    Alignment::Left,
+                )
+            } else {
+                (
+                    message.content.clone(),
+                    Style::default().fg(palette().primary),
+                    Alignment::Left,
+                )
+            }
+        }
+        MessageType::User => {
+            if let Ok(user_message) = serde_json::from_str::<UserMessage>(&message.content) {
+                (
+                    format!("\nPlayer action:\n{}", user_message.player_action),
+                    Style::default().fg(palette().info),
+                    Alignment::Right,
+                )
+            } else {
+                (
+                    message.content.clone(),
+                    Style::default().fg(palette().info),
+                    Alignment::Right,
+                )
+            }
         }
+        // Glitches stand out from other rolls and notices.
+        MessageType::System if reports_glitch(&message.content) => (
+            message.content.clone(),
+            Style::default()
+                .fg(palette().failure)
+                .add_modifier(Modifier::BOLD),
+            Alignment::Center,
+        ),
+        MessageType::System => (
+            message.content.clone(),
+            Style::default().fg(palette().highlight),
+            Alignment::Center,
+        ),
+    };
+
+    wrap_to_width(&content, max_width)
+        .into_iter()
+        .map(|line| parse_markdown(line, base_style).alignment(alignment))
+        .collect()
+}
+
+fn game_header(game_message: &GameMessage) -> String {
+    format!("crunch:\n{}\n\nfluff:\n", game_message.crunch)
+}
+
+// Which of the lines `parse_message` gives each dialogue line of a game message takes, by its index
+// in the fluff. Empty in accessibility mode, where the transcript is left unstyled.
+pub fn dialogue_lines(
+    message: &Message,
+    max_width: usize,
+    accessible: bool,
+) -> Vec<(usize, Range<usize>)> {
+    if accessible || message.message_type != MessageType::Game {
+        return Vec::new();
+    }
+    let Ok(game_message) = serde_json::from_str::<GameMessage>(&message.content) else {
+        return Vec::new();
+    };
+    let mut content = game_header(&game_message);
+    let mut ranges = Vec::new();
+    for (index, rendered) in game_message.fluff.rendered_lines() {
+        // `content` ends with a newline, whose empty last line is the blank one before the speaker.
+        let start = wrap_to_width(&content, max_width).len();
+        content.push_str(&rendered);
+        let end = wrap_to_width(content.trim_end_matches('\n'), max_width).len();
+        ranges.push((index, start..end.max(start)));
     }
+    ranges
+}
 
-    all_lines
+// The keys of the input, which follow the Input Mode, Submit Key and Enter While Typing settings.
+fn input_title(app: &App) -> String {
+    let new_line = if app.settings.enter_new_line {
+        "Enter"
+    } else if keyboard_enhanced() {
+        "Shift+Enter"
+    } else {
+        "Alt+Enter"
+    };
+    // Only with the kitty keyboard protocol can Ctrl+Enter be told from Enter.
+    let send = match app.settings.submit_key {
+        SubmitKey::CtrlS => Some("Ctrl+S"),
+        _ if keyboard_enhanced() => Some("Ctrl+Enter"),
+        _ => None,
+    };
+    match app.input_mode {
+        InputMode::Normal => match app.settings.submit_key {
+            SubmitKey::Enter => " Press 'e' to edit, 'r' to record, ' Tab ' for sheet details, 'x' to export the sheet, 'n' for other characters, 'g' for scenes ".to_string(),
+            submit_key => format!(
+                " Press 'e' to edit, {} to send, 'r' to record, ' Tab ' for sheet details, 'x' to export the sheet, 'n' for other characters, 'g' for scenes ",
+                submit_key.label()
+            ),
+        },
+        InputMode::Editing if app.simple_input() => format!(
+            " {} to send, {} for a new line, Ctrl+R to record, Esc to leave ",
+            if app.enter_sends() {
+                "Enter"
+            } else {
+                send.unwrap_or("Ctrl+Enter")
+            },
+            new_line
+        ),
+        InputMode::Editing => {
+            let finish = if app.settings.enter_new_line {
+                "Esc"
+            } else {
+                "Enter"
+            };
+            match send {
+                Some(send) => format!(
+                    " Editing: {} to finish, {} to send, {} for a new line ",
+                    finish, send, new_line
+                ),
+                None => format!(" Editing: {} to finish, {} for a new line ", finish, new_line),
+            }
+        }
+        InputMode::Recording => " Recording… Press 'Esc' to stop ".to_string(),
+    }
 }
 
 pub fn draw_user_input(f: &mut Frame, app: &App, area: Rect) {
-    let block = Block::default()
-        .title(match app.input_mode {
-            InputMode::Normal => {
-                " Press 'e' to edit, 'r' to record, and ' Tab ' to see character sheet details "
-            }
-            InputMode::Editing => " Editing ",
-            InputMode::Recording => " Recording… Press 'Esc' to stop ",
-        })
+    // The test the action being typed probably calls for, on the bottom border.
+    let hint = match app.input_mode {
+        InputMode::Editing => app.pool_hint(),
+        _ => None,
+    };
+    let mut block = Block::default();
+    if let Some(hint) = hint {
+        block = block.title_bottom(
+            Line::styled(
+                format!(" {} — Tab to add ", hint.label()),
+                Style::default().fg(palette().info),
+            )
+            .right_aligned(),
+        );
+    }
+    let block = block
+        .title(input_title(app))
         .borders(Borders::ALL)
         .border_style(Style::default().fg(match app.input_mode {
-            InputMode::Normal => Color::DarkGray,
-            InputMode::Editing => Color::White,
-            InputMode::Recording => Color::Red,
+            InputMode::Normal => palette().dim,
+            InputMode::Editing => palette().text,
+            InputMode::Recording => palette().failure,
         }));
 
     let inner_area = block.inner(area);
@@ -850,54 +1614,48 @@ pub fn draw_user_input(f: &mut Frame, app: &App, area: Rect) {
 
     let text = app.user_input.value();
 
-    // Wrap the text manually, considering grapheme clusters and their widths
+    // Wrap the text manually, considering grapheme clusters and their widths, and place the
+    // cursor, which tui-input counts in chars, on the wrapped lines.
+    let cursor = app.user_input.cursor();
     let mut wrapped_lines = Vec::new();
     let mut current_line = String::new();
     let mut current_width = 0;
+    let (mut cursor_x, mut cursor_y) = (0, 0);
+    let mut chars = 0;
 
     for grapheme in text.graphemes(true) {
         let grapheme_width = grapheme.width();
         if current_width + grapheme_width > max_width {
-            wrapped_lines.push(current_line);
-            current_line = String::new();
+            wrapped_lines.push(std::mem::take(&mut current_line));
             current_width = 0;
         }
+        if chars == cursor {
+            (cursor_x, cursor_y) = (current_width, wrapped_lines.len());
+        }
+        chars += grapheme.chars().count();
+        // A new line typed with Shift+Enter or pasted.
+        if grapheme == "\n" || grapheme == "\r\n" {
+            wrapped_lines.push(std::mem::take(&mut current_line));
+            current_width = 0;
+            continue;
+        }
         current_line.push_str(grapheme);
         current_width += grapheme_width;
     }
+    if chars <= cursor {
+        (cursor_x, cursor_y) = (current_width, wrapped_lines.len());
+    }
     if !current_line.is_empty() {
         wrapped_lines.push(current_line);
     }
 
-    // Calculate cursor position
-    let cursor_position = app.user_input.visual_cursor();
-    let mut cursor_x = 0;
-    let mut cursor_y = 0;
-    let mut total_width = 0;
-
-    for (line_idx, line) in wrapped_lines.iter().enumerate() {
-        let line_width: usize = line.width();
-        if total_width + line_width >= cursor_position {
-            cursor_y = line_idx;
-            cursor_x = cursor_position - total_width;
-            break;
-        }
-        total_width += line_width;
-        cursor_y = line_idx + 1;
-    }
-
-    // Ensure cursor_x doesn't exceed the line width
-    if cursor_y < wrapped_lines.len() {
-        cursor_x = cursor_x.min(wrapped_lines[cursor_y].width());
-    }
-
     let joined_lines = wrapped_lines.join("\n");
 
     let input = Paragraph::new(joined_lines)
         .style(Style::default().fg(match app.input_mode {
-            InputMode::Normal => Color::DarkGray,
-            InputMode::Editing => Color::Yellow,
-            InputMode::Recording => Color::Red,
+            InputMode::Normal => palette().dim,
+            InputMode::Editing => palette().highlight,
+            InputMode::Recording => palette().failure,
         }))
         .alignment(Alignment::Left)
         .wrap(Wrap { trim: false });
@@ -921,7 +1679,7 @@ pub fn draw_user_input(f: &mut Frame, app: &App, area: Rect) {
 
 // Function to parse markdown-like text to formatted spans.
 
-fn parse_markdown(line: String, base_style: Style) -> Line<'static> {
+pub fn parse_markdown(line: String, base_style: Style) -> Line<'static> {
     let mut spans = Vec::new();
     let mut current_text = String::new();
     let mut in_bold = false;
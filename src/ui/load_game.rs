@@ -1,24 +1,28 @@
 // ui/load_game.rs
 
 use super::main_menu::{render_art, render_header, render_status, render_title};
-use super::utils::centered_rect;
+use super::utils::{centered_rect, draw_too_small, MIN_HEIGHT, MIN_WIDTH};
 use crate::app::App;
+use crate::game_state::play_time;
+use crate::i18n::{tr, trf};
+use crate::paths;
+use crate::ui::theme::palette;
 use ratatui::{
-    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
-    style::{Color, Modifier, Style},
+    layout::{Alignment, Constraint, Direction, Layout, Position, Rect},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::*,
     Frame,
 };
+use ratatui_image::StatefulImage;
+
+// Columns the portrait takes in the preview.
+const THUMBNAIL_WIDTH: u16 = 24;
 
 pub fn draw_load_game(f: &mut Frame, app: &App) {
     let size = f.area();
 
-    if size.width < 100 || size.height < 50 {
-        let warning = Paragraph::new("Terminal too small. Please resize.")
-            .style(Style::default().fg(Color::Red))
-            .alignment(Alignment::Center);
-        f.render_widget(warning, size);
+    if draw_too_small(f, &app.settings.language, MIN_WIDTH, MIN_HEIGHT) {
         return;
     }
     let chunks = Layout::default()
@@ -37,45 +41,232 @@ pub fn draw_load_game(f: &mut Frame, app: &App) {
         .split(f.area());
 
     render_header(f, chunks[0]);
-    render_art(f, chunks[1]);
-    render_title(f, chunks[2]);
+    render_art(f, app, chunks[1]);
+    render_title(f, app, chunks[2]);
     render_console(f, app, chunks[3]);
     render_load_game_menu(f, app, chunks[4]);
     render_status(f, app, chunks[5]);
+
+    if app.save_recovery.is_some() {
+        render_recovery_dialog(f, app, size);
+    }
+    if app.backup_menu.is_some() {
+        render_backup_menu(f, app, size);
+    }
+    if app.save_rename.is_some() {
+        render_rename_dialog(f, app, size);
+    }
+}
+
+fn render_rename_dialog(f: &mut Frame, app: &App, area: Rect) {
+    let Some(rename) = &app.save_rename else {
+        return;
+    };
+    let language = &app.settings.language;
+    let mut lines = vec![
+        Line::styled(
+            format!("> {}", rename.input.value()),
+            Style::default().fg(palette().highlight),
+        ),
+        Line::raw(""),
+        Line::raw(tr(
+            language,
+            if rename.rename_assistant {
+                "[x] Rename the assistant too"
+            } else {
+                "[ ] Rename the assistant too"
+            },
+        )),
+    ];
+    if let Some(error) = &rename.error {
+        lines.push(Line::styled(
+            error.clone(),
+            Style::default().fg(palette().failure),
+        ));
+    }
+    lines.push(Line::raw(""));
+    lines.push(Line::styled(
+        tr(
+            language,
+            "Enter to rename, Tab for the assistant, Esc to go back",
+        ),
+        Style::default().fg(palette().muted),
+    ));
+
+    let dialog_area = centered_rect(60, 25, area);
+    let dialog = Paragraph::new(lines)
+        .wrap(Wrap { trim: true })
+        .style(Style::default().fg(palette().text))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(palette().highlight))
+                .title(format!(
+                    " {} ",
+                    trf(language, "Rename '{}'", &[&rename.save_name])
+                )),
+        );
+    f.render_widget(Clear, dialog_area);
+    f.render_widget(dialog, dialog_area);
+    // The cursor after the prompt, where the name is typed.
+    f.set_cursor_position(Position::new(
+        dialog_area.x + 3 + rename.input.visual_cursor() as u16,
+        dialog_area.y + 1,
+    ));
+}
+
+fn render_backup_menu(f: &mut Frame, app: &App, area: Rect) {
+    let Some(menu) = &app.backup_menu else {
+        return;
+    };
+    let language = &app.settings.language;
+    let mut lines: Vec<Line> = if menu.backups.is_empty() {
+        vec![Line::raw(tr(language, "There is no backup to restore."))]
+    } else {
+        menu.backups
+            .iter()
+            .enumerate()
+            .map(|(i, backup)| {
+                let name = backup
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                if i == menu.selected {
+                    Line::styled(format!("> {}", name), palette().selected())
+                } else {
+                    Line::raw(format!("  {}", name))
+                }
+            })
+            .collect()
+    };
+    lines.push(Line::raw(""));
+    lines.push(Line::styled(
+        tr(
+            language,
+            "Up/Down to choose, Enter to restore, Esc to go back",
+        ),
+        Style::default().fg(palette().muted),
+    ));
+
+    let menu_area = centered_rect(60, 40, area);
+    let list = Paragraph::new(lines)
+        .style(Style::default().fg(palette().text))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(palette().highlight))
+                .title(format!(
+                    " {} ",
+                    trf(language, "Backups of '{}'", &[&menu.save_name])
+                )),
+        );
+    f.render_widget(Clear, menu_area);
+    f.render_widget(list, menu_area);
+}
+
+fn render_recovery_dialog(f: &mut Frame, app: &App, area: Rect) {
+    let Some(recovery) = &app.save_recovery else {
+        return;
+    };
+    let language = &app.settings.language;
+    let mut lines = vec![
+        Line::raw(trf(
+            language,
+            "'{}' could not be loaded: {}.",
+            &[&recovery.save_name, &recovery.reason],
+        )),
+        Line::raw(""),
+    ];
+    let option = |text: &'static str| {
+        Line::styled(tr(language, text), Style::default().fg(palette().highlight))
+    };
+    if recovery.has_backup {
+        lines.push(option("b  Restore a backup"));
+    }
+    if recovery.readable {
+        lines.push(option("l  Load it as it is"));
+    }
+    if !recovery.has_backup && !recovery.readable {
+        lines.push(Line::raw(tr(language, "There is no backup to restore.")));
+    }
+    lines.push(option("Esc  Cancel"));
+
+    let dialog_area = centered_rect(60, 25, area);
+    let dialog = Paragraph::new(lines)
+        .wrap(Wrap { trim: true })
+        .style(Style::default().fg(palette().text))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Thick)
+                .border_style(Style::default().fg(palette().failure))
+                .title(format!(" {} ", tr(language, "Damaged save"))),
+        );
+    f.render_widget(Clear, dialog_area);
+    f.render_widget(dialog, dialog_area);
 }
 
 fn render_console(f: &mut Frame, app: &App, area: Rect) {
     let console_text = if app.save_manager.available_saves.is_empty() {
-        "No save files found in ./data/save/"
+        trf(
+            &app.settings.language,
+            "No save files found in {}",
+            &[&paths::save_dir().display().to_string()],
+        )
     } else {
-        "Select a save file to load"
+        tr(&app.settings.language, "Select a save file to load").to_string()
     };
 
     let console = Paragraph::new(console_text)
-        .style(Style::default().fg(Color::Yellow))
+        .style(Style::default().fg(palette().highlight))
         .alignment(Alignment::Center)
         .block(Block::default().borders(Borders::NONE));
 
     f.render_widget(console, area);
 }
 
+// The saves on the left, the one selected previewed on the right.
 fn render_load_game_menu(f: &mut Frame, app: &App, area: Rect) {
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .split(area);
+    render_save_list(f, app, columns[0]);
+    render_save_preview(f, app, columns[1]);
+}
+
+fn render_save_list(f: &mut Frame, app: &App, area: Rect) {
+    let language = &app.settings.language;
+    let listed = app.listed_saves();
     let text: Vec<Line> = if app.save_manager.available_saves.is_empty() {
-        vec![Line::from(Span::raw("No save files available"))]
+        vec![Line::from(Span::raw(tr(
+            language,
+            "No save files available",
+        )))]
+    } else if listed.is_empty() {
+        vec![Line::from(Span::raw(tr(
+            language,
+            "No save matches the filter.",
+        )))]
     } else {
-        app.save_manager
-            .available_saves
+        listed
             .iter()
             .enumerate()
             .map(|(i, save)| {
                 if Some(i) == app.load_game_menu_state.selected() {
+                    // Said in words too, not only by turning red.
+                    let pending_delete = if app.backspace_counter {
+                        tr(&app.settings.language, " (Backspace again to delete)")
+                    } else {
+                        ""
+                    };
                     Line::from(Span::styled(
-                        format!("{}. {}", (i + 1), save),
+                        format!("{}. {}{}", (i + 1), save, pending_delete),
                         Style::default()
                             .fg(if !app.backspace_counter {
-                                Color::Yellow
+                                palette().highlight
                             } else {
-                                Color::Red
+                                palette().failure
                             })
                             .add_modifier(Modifier::BOLD),
                     ))
@@ -86,28 +277,140 @@ fn render_load_game_menu(f: &mut Frame, app: &App, area: Rect) {
             .collect()
     };
 
-    let outer_block = Block::default()
-        .borders(Borders::NONE)
-        .style(Style::default().fg(Color::DarkGray));
+    // e.g. " Saves, by last played: /seat ".
+    let mut title = format!(" {}", tr(language, "Saves"));
+    title.push_str(&format!(", {}", tr(language, app.save_sort.label())));
+    if app.filtering_saves || !app.save_filter.value().is_empty() {
+        title.push_str(&format!(": /{}", app.save_filter.value()));
+    }
+    title.push(' ');
+    let menu = Paragraph::new(text)
+        .alignment(Alignment::Left)
+        .style(Style::default().fg(palette().text))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(if app.filtering_saves {
+                    palette().highlight
+                } else {
+                    palette().dim
+                }))
+                .title(title)
+                .padding(Padding::horizontal(1)),
+        );
 
-    let menu_area = centered_rect(100, 100, area);
-    f.render_widget(outer_block, menu_area);
+    f.render_widget(menu, area);
+}
 
-    let inner_area = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(1),
-            Constraint::Fill(1),
-            Constraint::Length(1),
-        ])
-        .split(menu_area.inner(Margin {
-            vertical: 1,
-            horizontal: (area.width - text.iter().map(|t| t.width() as u16).max().unwrap()) / 2,
-        }))[1];
+fn render_save_preview(f: &mut Frame, app: &App, area: Rect) {
+    let language = &app.settings.language;
+    let selected = app.selected_save();
+    let lines: Vec<Line> = match selected
+        .as_ref()
+        .map(|save| app.save_manager.metadata.get(save))
+    {
+        None => Vec::new(),
+        Some(None) => vec![Line::styled(
+            tr(language, "This save could not be read."),
+            Style::default().fg(palette().failure),
+        )],
+        Some(Some(metadata)) => {
+            let mut lines = Vec::new();
+            if let Some(character) = &metadata.character {
+                let who = match &metadata.race {
+                    Some(race) => format!("{}, {}", character, race),
+                    None => character.clone(),
+                };
+                lines.push(Line::styled(
+                    who,
+                    Style::default()
+                        .fg(palette().highlight)
+                        .add_modifier(Modifier::BOLD),
+                ));
+            }
+            if let Some(last_played) = &metadata.last_played {
+                lines.push(Line::raw(trf(language, "Last played {}", &[last_played])));
+            }
+            lines.push(Line::raw(trf(
+                language,
+                "{} played, {} turns",
+                &[
+                    &play_time(metadata.stats.seconds),
+                    &metadata.stats.turns.to_string(),
+                ],
+            )));
+            if !metadata.backstory.is_empty() {
+                lines.push(Line::raw(""));
+                lines.push(Line::styled(
+                    metadata.backstory.clone(),
+                    Style::default()
+                        .fg(palette().muted)
+                        .add_modifier(Modifier::ITALIC),
+                ));
+            }
+            if let Some(thumbnail) = &metadata.thumbnail {
+                let file = thumbnail
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                lines.push(Line::styled(
+                    trf(language, "Image: {} (p to open)", &[&file]),
+                    Style::default().fg(palette().muted),
+                ));
+            }
+            lines.push(Line::raw(""));
+            if metadata.summary.is_empty() {
+                lines.push(Line::styled(
+                    tr(language, "No story yet."),
+                    Style::default().fg(palette().muted),
+                ));
+            } else {
+                lines.extend(
+                    metadata
+                        .summary
+                        .lines()
+                        .map(|line| Line::raw(line.to_string())),
+                );
+            }
+            lines
+        }
+    };
 
-    let menu = Paragraph::new(text)
-        .alignment(Alignment::Left)
-        .style(Style::default().fg(Color::White));
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(palette().dim))
+        .padding(Padding::horizontal(1))
+        .title(format!(" {} ", tr(language, "Preview")));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    // The picture beside the text, unless the screen is read out.
+    let thumbnail = selected
+        .as_ref()
+        .and_then(|save| app.save_manager.metadata.get(save))
+        .and_then(|metadata| metadata.thumbnail.as_ref())
+        .filter(|_| !app.settings.accessibility_mode);
+    let mut thumbnails = app.thumbnails.borrow_mut();
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(THUMBNAIL_WIDTH), Constraint::Fill(1)])
+        .spacing(1)
+        .split(inner);
+    let drawn = match thumbnail {
+        Some(path) if thumbnails.basic_colors() => thumbnails
+            .art(path, columns[0].width, columns[0].height)
+            .map(|art| f.render_widget(Paragraph::new(art.to_vec()), columns[0]))
+            .is_some(),
+        Some(path) => thumbnails
+            .get(path)
+            .map(|picture| f.render_stateful_widget(StatefulImage::new(None), columns[0], picture))
+            .is_some(),
+        None => false,
+    };
+    let text_area = if drawn { columns[1] } else { inner };
 
-    f.render_widget(menu, inner_area);
+    let preview = Paragraph::new(lines)
+        .wrap(Wrap { trim: true })
+        .style(Style::default().fg(palette().text));
+    f.render_widget(preview, text_area);
 }
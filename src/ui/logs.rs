@@ -0,0 +1,139 @@
+// ui/logs.rs
+
+use super::main_menu::render_header;
+use crate::app::App;
+use crate::log_viewer::{LogViewer, LEVEL_FILTERS};
+use crate::ui::theme::palette;
+use crate::ui::utils::{draw_too_small, MIN_HEIGHT, MIN_WIDTH};
+use log::Level;
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Position, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::*,
+    Frame,
+};
+
+pub fn draw_logs(f: &mut Frame, app: &mut App) {
+    let size = f.area();
+
+    if draw_too_small(f, &app.settings.language, MIN_WIDTH, MIN_HEIGHT) {
+        return;
+    }
+    let Some(viewer) = app.log_viewer.as_mut() else {
+        return;
+    };
+    // Pick up whatever was logged since the last frame.
+    viewer.refresh();
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Max(3),
+                Constraint::Length(3),
+                Constraint::Min(10),
+                Constraint::Max(3),
+            ]
+            .as_ref(),
+        )
+        .split(size);
+
+    render_header(f, chunks[0]);
+    render_filters(f, viewer, chunks[1]);
+    render_lines(f, viewer, chunks[2]);
+
+    let hint = if viewer.searching {
+        " Type to search, Enter or Esc when done "
+    } else {
+        " ←/→ level   / search   c clear search   ↑/↓ PgUp/PgDn scroll   End follow   r reload   Esc back "
+    };
+    let hint = Paragraph::new(hint)
+        .style(Style::default().fg(palette().muted))
+        .alignment(Alignment::Center);
+    f.render_widget(hint, chunks[3]);
+}
+
+fn render_filters(f: &mut Frame, viewer: &LogViewer, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(50), Constraint::Min(20)].as_ref())
+        .split(area);
+
+    let levels: Vec<Span> = LEVEL_FILTERS
+        .iter()
+        .flat_map(|filter| {
+            let style = if *filter == viewer.filter {
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(palette().highlight)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(palette().dim)
+            };
+            [Span::styled(format!(" {} ", filter), style), Span::raw(" ")]
+        })
+        .collect();
+    let levels = Paragraph::new(Line::from(levels))
+        .block(Block::default().borders(Borders::ALL).title(" Level "));
+    f.render_widget(levels, chunks[0]);
+
+    let search_style = if viewer.searching {
+        Style::default().fg(palette().highlight)
+    } else {
+        Style::default().fg(palette().muted)
+    };
+    let search = Paragraph::new(viewer.search.value())
+        .style(search_style)
+        .block(Block::default().borders(Borders::ALL).title(" Search "));
+    f.render_widget(search, chunks[1]);
+
+    if viewer.searching {
+        f.set_cursor_position(Position::new(
+            chunks[1].x + viewer.search.cursor() as u16 + 1,
+            chunks[1].y + 1,
+        ));
+    }
+}
+
+fn render_lines(f: &mut Frame, viewer: &LogViewer, area: Rect) {
+    let visible = viewer.visible();
+    let height = area.height.saturating_sub(2) as usize;
+    let end = visible.len().saturating_sub(viewer.scroll);
+    let start = end.saturating_sub(height);
+
+    let lines: Vec<Line> = visible[start..end]
+        .iter()
+        .map(|line| Line::styled(line.text.as_str(), level_style(line.level)))
+        .collect();
+
+    let title = if viewer.scroll == 0 {
+        format!(" Logs ({} lines, following) ", visible.len())
+    } else {
+        format!(" Logs ({} lines, {} up) ", visible.len(), viewer.scroll)
+    };
+    let empty = lines.is_empty();
+    let mut logs = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .border_style(Style::default().fg(palette().info)),
+    );
+    if empty {
+        logs = Paragraph::new("Nothing logged at this level yet")
+            .style(Style::default().fg(palette().dim))
+            .alignment(Alignment::Center)
+            .block(Block::default().borders(Borders::ALL).title(" Logs "));
+    }
+    f.render_widget(logs, area);
+}
+
+fn level_style(level: Option<Level>) -> Style {
+    match level {
+        Some(Level::Error) => Style::default().fg(palette().failure),
+        Some(Level::Warn) => Style::default().fg(palette().highlight),
+        Some(Level::Info) => Style::default().fg(palette().text),
+        Some(Level::Debug) => Style::default().fg(palette().muted),
+        Some(Level::Trace) | None => Style::default().fg(palette().dim),
+    }
+}
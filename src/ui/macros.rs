@@ -0,0 +1,133 @@
+// ui/macros.rs
+
+use super::main_menu::render_header;
+use crate::app::App;
+use crate::macros::{MacroEditor, MacroField, MACRO_PREFIX};
+use crate::ui::theme::palette;
+use crate::ui::utils::{draw_too_small, MIN_HEIGHT, MIN_WIDTH};
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Position, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::*,
+    Frame,
+};
+
+pub fn draw_macros(f: &mut Frame, app: &App) {
+    let size = f.area();
+
+    if draw_too_small(f, &app.settings.language, MIN_WIDTH, MIN_HEIGHT) {
+        return;
+    }
+    let Some(editor) = app.macro_editor.as_ref() else {
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Max(3),
+                Constraint::Min(10),
+                Constraint::Length(if editor.editing.is_some() { 8 } else { 0 }),
+                Constraint::Max(3),
+            ]
+            .as_ref(),
+        )
+        .split(size);
+
+    render_header(f, chunks[0]);
+    render_list(f, editor, chunks[1]);
+    if editor.editing.is_some() {
+        render_form(f, editor, chunks[2]);
+    }
+
+    let footer = match &editor.message {
+        Some(message) => Paragraph::new(message.as_str()).style(Style::default().fg(palette().failure)),
+        None if editor.editing.is_some() => Paragraph::new(
+            " Tab to switch field, Enter to save, Esc to cancel, Ctrl+V to paste ",
+        )
+        .style(Style::default().fg(palette().muted)),
+        None => Paragraph::new(
+            " a add   Enter edit   d delete   Esc back   |   type !name in the game input to use a macro ",
+        )
+        .style(Style::default().fg(palette().muted)),
+    };
+    f.render_widget(footer.alignment(Alignment::Center), chunks[3]);
+}
+
+fn render_list(f: &mut Frame, editor: &MacroEditor, area: Rect) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Macros ")
+        .border_style(Style::default().fg(palette().info));
+
+    if editor.macros.macros.is_empty() {
+        let empty = Paragraph::new("No macros yet. Press a to add one.")
+            .style(Style::default().fg(palette().dim))
+            .alignment(Alignment::Center)
+            .block(block);
+        f.render_widget(empty, area);
+        return;
+    }
+
+    let items: Vec<ListItem> = editor
+        .macros
+        .macros
+        .iter()
+        .map(|(name, text)| {
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{}{:<16} ", MACRO_PREFIX, name),
+                    Style::default().fg(palette().highlight),
+                ),
+                Span::raw(text.as_str()),
+            ]))
+        })
+        .collect();
+    let list = List::new(items).block(block).highlight_style(
+        Style::default()
+            .bg(palette().dim)
+            .add_modifier(Modifier::BOLD),
+    );
+    let mut state = ListState::default();
+    state.select(Some(editor.selected));
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+fn render_form(f: &mut Frame, editor: &MacroEditor, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Length(5)].as_ref())
+        .split(area);
+
+    let style = |field: MacroField| {
+        if editor.field == field {
+            Style::default().fg(palette().highlight)
+        } else {
+            Style::default().fg(palette().muted)
+        }
+    };
+    let name = Paragraph::new(editor.name.value())
+        .style(style(MacroField::Name))
+        .block(Block::default().borders(Borders::ALL).title(" Name "));
+    f.render_widget(name, chunks[0]);
+
+    let text = Paragraph::new(editor.text.value())
+        .style(style(MacroField::Text))
+        .wrap(Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL).title(" Text "));
+    f.render_widget(text, chunks[1]);
+
+    // The text wraps, so follow the cursor down as if every line were full.
+    let (input, area) = match editor.field {
+        MacroField::Name => (&editor.name, chunks[0]),
+        MacroField::Text => (&editor.text, chunks[1]),
+    };
+    let width = area.width.saturating_sub(2).max(1);
+    let cursor = input.visual_cursor() as u16;
+    f.set_cursor_position(Position::new(
+        area.x + 1 + cursor % width,
+        area.y + 1 + (cursor / width).min(area.height.saturating_sub(3)),
+    ));
+}
@@ -2,50 +2,80 @@
 
 // Import required modules and structs from other parts of the application or external crates.
 use super::constants::{ART, TITLE}; // Constants like ART and TITLE for UI.
-use super::utils::centered_rect; // Utility function for centering rectangles in the UI.
-use crate::app::App; // Main application struct.
+use super::utils::{centered_rect, draw_too_small}; // Utility functions for laying out the UI.
+use crate::app::{App, MainMenuItem}; // Main application struct and its menu.
 use crate::app_state::AppState; // Enum for managing application state.
+use crate::branding::Branding;
+use crate::i18n::{tr, trf};
 use crate::message::MessageType; // Enum for different types of messages.
+use crate::ui::theme::palette;
 use ratatui::{
     // Library for building text-based user interfaces.
     layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::*,
     Frame,
 };
 
-// Function to draw the main menu interface.
-pub fn draw_main_menu(f: &mut Frame, app: &App) {
-    let size = f.area();
+// The art, in its frame, and the ASCII title.
+const ART_SIZE: (u16, u16) = (80, 18);
+const TITLE_HEIGHT: u16 = 7;
+// Header, console, menu and status: what the menu needs without any decoration.
+const MENU_HEIGHT: u16 = 3 + 3 + 6 + 3;
+const MIN_WIDTH: u16 = 40;
 
-    if size.width < 100 || size.height < 50 {
-        let warning = Paragraph::new("Terminal too small. Please resize.")
-            .style(Style::default().fg(Color::Red))
-            .alignment(Alignment::Center);
-        f.render_widget(warning, size);
-        return;
+// The rows of the main menu: header, art, title, console, menu and status. On smaller
+// terminals the art goes first, then the ASCII title gives way to a line of text; None when
+// even the menu doesn't fit.
+pub fn menu_layout(area: Rect, show_art: bool) -> Option<std::rc::Rc<[Rect]>> {
+    if area.width < MIN_WIDTH || area.height < MENU_HEIGHT + 1 {
+        return None;
     }
-    // Define layout constraints for different sections of the main menu.
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints(
-            [
+    let title = if area.height >= MENU_HEIGHT + TITLE_HEIGHT + 3 {
+        TITLE_HEIGHT
+    } else {
+        1
+    };
+    let art = if show_art
+        && area.width >= ART_SIZE.0 + 2
+        && area.height >= MENU_HEIGHT + title + ART_SIZE.1 + 2
+    {
+        ART_SIZE.1 + 2
+    } else {
+        0
+    };
+    Some(
+        Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
                 Constraint::Max(3),
-                Constraint::Max(20),
-                Constraint::Max(7),
+                Constraint::Length(art),
+                Constraint::Length(title),
                 Constraint::Fill(1),
                 Constraint::Min(6),
                 Constraint::Max(3),
-            ]
-            .as_ref(),
-        )
-        .split(f.area());
+            ])
+            .split(area),
+    )
+}
+
+// Function to draw the main menu interface.
+pub fn draw_main_menu(f: &mut Frame, app: &App) {
+    let size = f.area();
+
+    let show_art = app.settings.menu_art && !app.settings.accessibility_mode;
+    if draw_too_small(f, &app.settings.language, MIN_WIDTH, MENU_HEIGHT + 1) {
+        return;
+    }
+    let Some(chunks) = menu_layout(size, show_art) else {
+        return;
+    };
 
     // Render individual parts of the main menu using the layout defined above.
     render_header(f, chunks[0]);
-    render_art(f, chunks[1]);
-    render_title(f, chunks[2]);
+    render_art(f, app, chunks[1]);
+    render_title(f, app, chunks[2]);
     render_console(f, app, chunks[3]);
     render_menu(f, app, chunks[4]);
     render_status(f, app, chunks[5]);
@@ -54,35 +84,69 @@ pub fn draw_main_menu(f: &mut Frame, app: &App) {
 // Function to render the header section of the menu.
 pub fn render_header(f: &mut Frame, area: Rect) {
     let header = Paragraph::new(format!("Sharad Ratatui v{}", env!("CARGO_PKG_VERSION")))
-        .style(Style::default().fg(Color::DarkGray))
+        .style(Style::default().fg(palette().dim))
         .block(Block::default())
         .alignment(Alignment::Center);
     f.render_widget(header, area);
 }
 
 // Function to render the art section of the menu.
-pub fn render_art(f: &mut Frame, area: Rect) {
-    let outer_block = Block::default().style(Style::default().fg(Color::DarkGray));
+pub fn render_art(f: &mut Frame, app: &App, area: Rect) {
+    // Decoration only, and noise to a screen reader.
+    if app.settings.accessibility_mode
+        || !app.settings.menu_art
+        || area.width < ART_SIZE.0
+        || area.height < ART_SIZE.1
+    {
+        return;
+    }
+    let outer_block = Block::default().style(Style::default().fg(palette().dim));
     f.render_widget(outer_block, area);
 
-    let center_x = area.x + (area.width - 80) / 2; // Calculate center x for inner rectangle.
-    let center_y = area.y + (area.height - 18) / 2; // Calculate center y for inner rectangle.
-    let inner_rect = Rect::new(center_x, center_y, 80, 18);
+    let center_x = area.x + (area.width - ART_SIZE.0) / 2; // Calculate center x for inner rectangle.
+    let center_y = area.y + (area.height - ART_SIZE.1) / 2; // Calculate center y for inner rectangle.
+    let inner_rect = Rect::new(center_x, center_y, ART_SIZE.0, ART_SIZE.1);
 
     let inner_block = Block::default()
         .borders(Borders::ALL)
-        .style(Style::default().fg(Color::Green));
+        .style(Style::default().fg(palette().primary));
     f.render_widget(inner_block, inner_rect);
 
-    let art = Paragraph::new(ART)
+    // A campaign's own art goes in the same frame, cut to fit.
+    let art = branding(app, |branding| branding.art.clone()).unwrap_or_else(|| ART.to_string());
+    let art = Paragraph::new(art)
         .alignment(Alignment::Center)
-        .style(Style::default().fg(Color::Green));
+        .style(Style::default().fg(palette().primary));
     f.render_widget(art, inner_rect);
 }
 
+// A part of the branding of the campaign the menu is showing, if it has one.
+fn branding(app: &App, part: impl Fn(&Branding) -> Option<String>) -> Option<String> {
+    let save_name = app.branded_save()?;
+    part(app.branding.borrow_mut().get(&save_name)).filter(|text| !text.trim().is_empty())
+}
+
 // Function to render the title section of the menu.
-pub fn render_title(f: &mut Frame, area: Rect) {
-    let outer_block = Block::default().style(Style::default().fg(Color::DarkGray));
+pub fn render_title(f: &mut Frame, app: &App, area: Rect) {
+    // The ASCII-art title reads as a wall of symbols, so name the screen in plain words instead,
+    // as on terminals too small for it.
+    if app.settings.accessibility_mode || area.height < TITLE_HEIGHT {
+        let screen = match app.state {
+            AppState::LoadMenu => "Load a game",
+            AppState::SettingsMenu => "Settings",
+            AppState::ChooseArchetype => "Choose an archetype",
+            _ => "Main menu",
+        };
+        let title = Paragraph::new(format!(
+            "Sharad Ratatui: {}",
+            tr(&app.settings.language, screen)
+        ))
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(palette().text));
+        f.render_widget(title, area);
+        return;
+    }
+    let outer_block = Block::default().style(Style::default().fg(palette().dim));
     let title_outer_area = centered_rect(100, 100, area);
     f.render_widget(&outer_block, title_outer_area);
 
@@ -98,15 +162,17 @@ pub fn render_title(f: &mut Frame, area: Rect) {
             horizontal: 1,
         }))[1];
 
-    let title = Paragraph::new(TITLE)
+    let title =
+        branding(app, |branding| branding.title.clone()).unwrap_or_else(|| TITLE.to_string());
+    let title = Paragraph::new(title)
         .alignment(Alignment::Center)
-        .style(Style::default().fg(Color::Green));
+        .style(Style::default().fg(palette().primary));
     f.render_widget(title, title_inner_area);
 }
 
 // Function to render the console section of the menu.
 pub fn render_console(f: &mut Frame, app: &App, area: Rect) {
-    let outer_block = Block::default().style(Style::default().fg(Color::DarkGray));
+    let outer_block = Block::default().style(Style::default().fg(palette().dim));
     let console_outer_area = centered_rect(100, 100, area);
     f.render_widget(&outer_block, console_outer_area);
 
@@ -134,34 +200,40 @@ pub fn render_console(f: &mut Frame, app: &App, area: Rect) {
 
     let text = Paragraph::new(finaly.unwrap_or("".to_string()))
         .alignment(Alignment::Center)
-        .style(Style::default().fg(Color::Yellow));
+        .style(Style::default().fg(palette().highlight));
     f.render_widget(text, console_inner_area);
 }
 
 // Function to render the interactive menu section of the main menu.
 pub fn render_menu(f: &mut Frame, app: &App, area: Rect) {
     // Define menu items to be displayed.
-    let menu_items = [
-        "Start a new game",
-        "Load a game",
-        "Create an image",
-        "Settings",
-    ];
+    let language = &app.settings.language;
+    let menu_items: Vec<String> = app
+        .main_menu_items()
+        .into_iter()
+        .map(|item| match item {
+            MainMenuItem::Continue(save_name) => trf(language, "Continue '{}'", &[&save_name]),
+            MainMenuItem::NewGame => tr(language, "Start a new game").to_string(),
+            MainMenuItem::LoadGame => tr(language, "Load a game").to_string(),
+            MainMenuItem::CreateImage => tr(language, "Create an image").to_string(),
+            MainMenuItem::Settings => tr(language, "Settings").to_string(),
+        })
+        .collect();
 
     // Map menu items to text lines, applying different styles to the selected item.
     let text: Vec<Line> = menu_items
-        .iter()
+        .into_iter()
         .enumerate()
-        .map(|(i, &item)| {
+        .map(|(i, item)| {
             let number = format!("{}. ", i + 1);
             let content = item;
             if i == app.main_menu_state.selected().unwrap_or(0) {
                 Line::from(vec![
-                    Span::styled(number, Style::default().fg(Color::Yellow)),
+                    Span::styled(number, Style::default().fg(palette().highlight)),
                     Span::styled(
                         content,
                         Style::default()
-                            .fg(Color::Yellow)
+                            .fg(palette().highlight)
                             .add_modifier(Modifier::BOLD),
                     ),
                 ])
@@ -173,7 +245,7 @@ pub fn render_menu(f: &mut Frame, app: &App, area: Rect) {
 
     let outer_block = Block::default()
         .borders(Borders::NONE)
-        .style(Style::default().fg(Color::DarkGray));
+        .style(Style::default().fg(palette().dim));
 
     let menu_area = centered_rect(100, 100, area);
     f.render_widget(outer_block, menu_area);
@@ -192,7 +264,7 @@ pub fn render_menu(f: &mut Frame, app: &App, area: Rect) {
 
     let menu = Paragraph::new(text)
         .alignment(Alignment::Left)
-        .style(Style::default().fg(Color::White));
+        .style(Style::default().fg(palette().text));
     f.render_widget(menu, inner_area);
 }
 
@@ -202,12 +274,33 @@ pub fn render_status(f: &mut Frame, app: &App, area: Rect) {
     let status_message = match app.state {
         AppState::MainMenu => "Press q to quit",
         AppState::LoadMenu => {
-            "Press Enter or number to load save, Backspace twice to delete save, Esc to go back"
+            "Press Enter or number to load save, Backspace twice to delete save, r for backups, n to rename, d to duplicate, / to filter, s to sort, Esc to go back"
         }
+        AppState::ChooseArchetype => "Up/Down to choose, Enter to start, Esc to go back",
         _ => "Press Esc to go back",
     };
-    let status = Paragraph::new(status_message)
-        .style(Style::default().fg(Color::DarkGray))
+    let language = &app.settings.language;
+    let mut line = vec![Span::raw(tr(language, status_message))];
+    if let (AppState::MainMenu, Some(recovery)) = (&app.state, &app.pending_recovery) {
+        line = vec![Span::styled(
+            trf(
+                language,
+                "'{}' was interrupted at {}: press r to recover it, d to discard",
+                &[&recovery.game_state.save_name, &recovery.saved_at],
+            ),
+            Style::default().fg(palette().highlight),
+        )];
+    } else if let (AppState::MainMenu, Some(notice)) = (&app.state, &app.update_notice) {
+        line.insert(
+            0,
+            Span::styled(
+                format!("{} | ", notice),
+                Style::default().fg(palette().info),
+            ),
+        );
+    }
+    let status = Paragraph::new(Line::from(line))
+        .style(Style::default().fg(palette().dim))
         .block(Block::default().borders(Borders::NONE))
         .alignment(Alignment::Center);
     f.render_widget(status, area);
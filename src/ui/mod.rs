@@ -1,14 +1,26 @@
 // ui/mod.rs
 
 mod api_key_input;
+mod archetype_picker;
 mod constants;
 mod create_image;
+mod data_dir_input;
+pub mod dice;
 mod draw;
+mod elevenlabs_key_input;
 pub mod game;
 mod load_game;
-mod main_menu;
+mod logs;
+mod macros;
+pub mod main_menu;
 mod save_name_input;
 mod settings;
+mod shop;
+mod stats;
+mod tasks;
+pub mod theme;
 pub mod utils;
+mod webhook_input;
+mod wizard;
 
 pub use draw::draw;
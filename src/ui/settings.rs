@@ -1,26 +1,29 @@
 // ui/settings.rs
 
 use crate::app::App;
+use crate::i18n::{tr, trf};
+use crate::paths;
+use crate::settings::SpectatorMode;
+use crate::settings_state::{
+    API_KEY, DATA_DIR, ELEVENLABS_KEY, INPUT_DEVICE, LOGS, MACROS, OUTPUT_DEVICE, SETTINGS_ITEMS,
+    SPECTATOR, WEBHOOK,
+};
 use crate::ui::main_menu::{
     render_art, render_console, render_header, render_status, render_title,
 };
-use crate::ui::utils::centered_rect;
+use crate::ui::theme::palette;
+use crate::ui::utils::{centered_rect, draw_too_small, truncate_to_width, MIN_HEIGHT, MIN_WIDTH};
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::*,
     Frame,
 };
+use unicode_width::UnicodeWidthStr;
 
 pub fn draw_settings(f: &mut Frame, app: &mut App) {
-    let size = f.area();
-
-    if size.width < 101 || size.height < 50 {
-        let warning = Paragraph::new("Terminal too small. Please resize.")
-            .style(Style::default().fg(Color::Red))
-            .alignment(Alignment::Center);
-        f.render_widget(warning, size);
+    if draw_too_small(f, &app.settings.language, MIN_WIDTH + 1, MIN_HEIGHT) {
         return;
     }
     let chunks = Layout::default()
@@ -31,7 +34,7 @@ pub fn draw_settings(f: &mut Frame, app: &mut App) {
                 Constraint::Max(20),
                 Constraint::Max(7),
                 Constraint::Min(2),
-                Constraint::Min(6),
+                Constraint::Min(20),
                 Constraint::Max(3),
             ]
             .as_ref(),
@@ -39,23 +42,23 @@ pub fn draw_settings(f: &mut Frame, app: &mut App) {
         .split(f.area());
 
     render_header(f, chunks[0]);
-    render_art(f, chunks[1]);
-    render_title(f, chunks[2]);
+    render_art(f, app, chunks[1]);
+    render_title(f, app, chunks[2]);
     render_console(f, app, chunks[3]);
     render_settings(f, app, chunks[4]);
     render_status(f, app, chunks[5]);
 }
 
 pub fn render_settings(f: &mut Frame, app: &App, area: Rect) {
-    let settings = [
-        ("Language", vec!["English", "Français", "日本語"]),
-        ("OpenAI API Key", vec![]),
-        ("Voice Output", vec!["On", "Off"]),
-        ("Voice Input", vec!["On", "Off"]),
-        ("Debug Mode", vec!["Off", "On"]),
-    ];
-
-    let text: Vec<Line> = settings
+    let language = &app.settings.language;
+    // Pad labels by display width so translated and wide-character labels line up.
+    let label_width = SETTINGS_ITEMS
+        .iter()
+        .map(|(setting, _)| tr(language, setting).width() + 1)
+        .max()
+        .unwrap_or(0)
+        .max(15);
+    let text: Vec<Line> = SETTINGS_ITEMS
         .iter()
         .enumerate()
         .map(|(number, (setting, options))| {
@@ -63,39 +66,129 @@ pub fn render_settings(f: &mut Frame, app: &App, area: Rect) {
 
             let highlight_line_style = if is_selected_setting {
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(palette().highlight)
                     .add_modifier(Modifier::BOLD)
             } else {
-                Style::default().fg(Color::White)
+                Style::default().fg(palette().text)
             };
 
             let mut spans = vec![
                 Span::styled(
                     format!("{}. ", number + 1),
-                    Style::default().fg(Color::Gray),
+                    Style::default().fg(palette().muted),
+                ),
+                Span::styled(
+                    format!(
+                        "{}{}",
+                        tr(language, setting),
+                        " ".repeat(label_width - tr(language, setting).width())
+                    ),
+                    highlight_line_style,
                 ),
-                Span::styled(format!("{:<15}", setting), highlight_line_style),
             ];
 
-            if number == 1 {
-                // API Key setting
+            if number == API_KEY {
                 let api_key_status = if app.settings.openai_api_key.is_some() {
-                    Span::styled("[Valid]", Style::default().fg(Color::Green))
+                    Span::styled(
+                        tr(language, "[Valid]"),
+                        Style::default().fg(palette().success),
+                    )
                 } else {
-                    Span::styled("[Not Valid]", Style::default().fg(Color::Red))
+                    Span::styled(
+                        tr(language, "[Not Valid]"),
+                        Style::default().fg(palette().failure),
+                    )
                 };
                 spans.push(api_key_status);
+            } else if number == ELEVENLABS_KEY {
+                spans.push(match app.settings.elevenlabs_api_key {
+                    Some(_) => Span::styled(
+                        tr(language, "[Set]"),
+                        Style::default().fg(palette().success),
+                    ),
+                    None => Span::styled(
+                        tr(language, "[No key, press Enter]"),
+                        Style::default().fg(palette().dim),
+                    ),
+                });
+            } else if number == DATA_DIR {
+                spans.push(Span::styled(
+                    app.settings.data_dir.as_ref().map_or_else(
+                        || tr(language, "[Default]").to_string(),
+                        |dir| format!("[{}]", truncate_to_width(&dir.display().to_string(), 40)),
+                    ),
+                    Style::default().fg(palette().dim),
+                ));
+                if paths::data_dir_changed(app.settings.data_dir.as_deref()) {
+                    spans.push(Span::styled(
+                        tr(language, " [Used after a restart]"),
+                        Style::default().fg(palette().info),
+                    ));
+                }
+            } else if number == MACROS {
+                spans.push(Span::styled(
+                    trf(
+                        language,
+                        "[{} defined, Enter to manage]",
+                        &[&app.macros.macros.len().to_string()],
+                    ),
+                    Style::default().fg(palette().dim),
+                ));
+            } else if number == INPUT_DEVICE || number == OUTPUT_DEVICE {
+                let device = if number == INPUT_DEVICE {
+                    &app.settings.input_device
+                } else {
+                    &app.settings.output_device
+                };
+                let style = if is_selected_setting {
+                    palette().selected()
+                } else {
+                    Style::default().fg(palette().text)
+                };
+                spans.push(Span::styled(
+                    format!(
+                        "[{}]",
+                        device.as_deref().map_or_else(
+                            || tr(language, "System Default").to_string(),
+                            |name| truncate_to_width(name, 40)
+                        )
+                    ),
+                    style,
+                ));
+            } else if number == LOGS {
+                spans.push(Span::styled(
+                    tr(language, "[Enter, or F12 anywhere]"),
+                    Style::default().fg(palette().dim),
+                ));
             } else {
                 let selected_option = app.settings_state.selected_options[number];
                 spans.extend(options.iter().enumerate().map(|(option_number, option)| {
                     let is_selected_option = option_number == selected_option;
                     let option_style = if is_selected_option {
-                        Style::default().fg(Color::Green)
+                        palette().selected()
                     } else {
-                        Style::default().fg(Color::White)
+                        Style::default().fg(palette().text)
                     };
-                    Span::styled(format!("[{}] ", option), option_style)
+                    Span::styled(format!("[{}] ", tr(language, option)), option_style)
                 }));
+                if number == SPECTATOR && app.settings.spectator_mode != SpectatorMode::Off {
+                    spans.push(match &app.spectator {
+                        Some(spectator) => Span::styled(
+                            format!("http://{}", spectator.address),
+                            Style::default().fg(palette().dim),
+                        ),
+                        None => Span::styled(
+                            tr(language, "[Failed]"),
+                            Style::default().fg(palette().failure),
+                        ),
+                    });
+                }
+                if number == WEBHOOK && app.settings.discord_webhook_url.is_none() {
+                    spans.push(Span::styled(
+                        tr(language, "[No URL, press Enter]"),
+                        Style::default().fg(palette().failure),
+                    ));
+                }
             }
 
             Line::from(spans)
@@ -104,8 +197,10 @@ pub fn render_settings(f: &mut Frame, app: &App, area: Rect) {
 
     let outer_block = Block::default()
         .borders(Borders::NONE)
-        .style(Style::default().fg(Color::DarkGray));
+        .style(Style::default().fg(palette().dim));
 
+    // Centre on the widest row, and start at the left edge when one is wider than the screen.
+    let widest = text.iter().map(Line::width).max().unwrap_or(0) as u16;
     let settings_area = centered_rect(100, 100, area);
     f.render_widget(outer_block, settings_area);
 
@@ -118,12 +213,20 @@ pub fn render_settings(f: &mut Frame, app: &App, area: Rect) {
         ])
         .split(settings_area.inner(Margin {
             vertical: 1,
-            horizontal: (area.width - text[0].width() as u16) / 2,
+            horizontal: area.width.saturating_sub(widest) / 2,
         }))[1];
 
+    // There are more rows than fit on a small terminal, so keep the selected one in view.
+    let visible = inner_area.height as usize;
+    let scroll = app
+        .settings_state
+        .selected_setting
+        .saturating_sub(visible.saturating_sub(1) / 2)
+        .min(SETTINGS_ITEMS.len().saturating_sub(visible));
     let settings_widget = Paragraph::new(text)
+        .scroll((scroll as u16, 0))
         .alignment(Alignment::Left)
-        .style(Style::default().fg(Color::White));
+        .style(Style::default().fg(palette().text));
 
     f.render_widget(settings_widget, inner_area);
 }
@@ -0,0 +1,144 @@
+// ui/shop.rs
+// The gear shop: the catalog with what the character can afford, the chosen item and its quantity.
+
+use super::main_menu::render_header;
+use crate::app::App;
+use crate::i18n::tr;
+use crate::shop::{Shop, MAX_AVAILABILITY};
+use crate::ui::theme::palette;
+use crate::ui::utils::draw_too_small;
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::*,
+    Frame,
+};
+
+// The catalog with a few rows, the details and the status line.
+const MIN_SIZE: (u16, u16) = (60, 21);
+
+pub fn draw_shop(f: &mut Frame, app: &App) {
+    let Some(shop) = app.shop.as_ref() else {
+        return;
+    };
+    if draw_too_small(f, &app.settings.language, MIN_SIZE.0, MIN_SIZE.1) {
+        return;
+    }
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Max(3),
+            Constraint::Min(10),
+            Constraint::Length(5),
+            Constraint::Max(3),
+        ])
+        .split(f.area());
+    render_header(f, chunks[0]);
+
+    // The sheet as it is now; None while the assistant holds the game.
+    let buyer = app
+        .current_game
+        .as_ref()
+        .and_then(|game| game.try_lock().ok())
+        .and_then(|game_state| {
+            let sheet = game_state.main_character_sheet.as_ref()?;
+            Some((sheet.name.clone(), sheet.nuyen))
+        });
+    let title = match &buyer {
+        Some((name, nuyen)) => format!(
+            " {} — {}, {}¥ ",
+            tr(&app.settings.language, "Gear Shop"),
+            name,
+            nuyen
+        ),
+        None => format!(" {} ", tr(&app.settings.language, "Gear Shop")),
+    };
+    render_catalog(f, shop, buyer.map(|(_, nuyen)| nuyen), &title, chunks[1]);
+    render_selected(f, shop, chunks[2]);
+
+    let footer = match &shop.message {
+        Some(message) => {
+            Paragraph::new(message.as_str()).style(Style::default().fg(palette().highlight))
+        }
+        None => Paragraph::new(" ↑/↓ choose   ←/→ quantity   Enter buy   Esc back ")
+            .style(Style::default().fg(palette().muted)),
+    };
+    f.render_widget(footer.alignment(Alignment::Center), chunks[3]);
+}
+
+fn render_catalog(f: &mut Frame, shop: &Shop, nuyen: Option<u32>, title: &str, area: Rect) {
+    let rows: Vec<Row> = shop
+        .catalog
+        .items
+        .iter()
+        .map(|item| {
+            let buyable = item.availability <= MAX_AVAILABILITY
+                && nuyen.is_none_or(|nuyen| item.cost <= nuyen);
+            let style = if buyable {
+                Style::default().fg(palette().text)
+            } else {
+                Style::default().fg(palette().dim)
+            };
+            Row::new(vec![
+                Cell::from(item.name.clone()),
+                Cell::from(item.category.clone()),
+                Cell::from(item.availability_label()),
+                Cell::from(format!("{}¥", item.cost)),
+            ])
+            .style(style)
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(45),
+            Constraint::Percentage(25),
+            Constraint::Percentage(10),
+            Constraint::Percentage(20),
+        ],
+    )
+    .header(
+        Row::new(vec!["Item", "Category", "Avail", "Cost"]).style(
+            Style::default()
+                .fg(palette().highlight)
+                .add_modifier(Modifier::BOLD),
+        ),
+    )
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(palette().info))
+            .title(title.to_string()),
+    )
+    .highlight_style(
+        Style::default()
+            .bg(palette().dim)
+            .add_modifier(Modifier::BOLD),
+    );
+    let mut state = TableState::default();
+    state.select(Some(shop.selected));
+    f.render_stateful_widget(table, area, &mut state);
+}
+
+fn render_selected(f: &mut Frame, shop: &Shop, area: Rect) {
+    let Some(item) = shop.catalog.items.get(shop.selected) else {
+        return;
+    };
+    let lines = vec![
+        Line::from(vec![
+            Span::styled(
+                format!("{} x {}", shop.quantity, item.name),
+                Style::default()
+                    .fg(palette().highlight)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(format!("  {}¥", item.cost as u64 * shop.quantity as u64)),
+        ]),
+        Line::raw(item.description.as_str()),
+    ];
+    let selected = Paragraph::new(lines)
+        .wrap(Wrap { trim: true })
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(selected, area);
+}
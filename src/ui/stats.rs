@@ -0,0 +1,147 @@
+// ui/stats.rs
+// The campaign statistics screen: totals on the left, bar charts of hits and nuyen on the right.
+
+use super::main_menu::render_header;
+use super::utils::draw_too_small;
+use crate::app::App;
+use crate::campaign_stats::{CampaignStats, HIT_GROUPS};
+use crate::game_state::play_time;
+use crate::i18n::tr;
+use crate::ui::theme::palette;
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::*,
+    Frame,
+};
+
+// The totals and both charts.
+const MIN_SIZE: (u16, u16) = (60, 16);
+
+pub fn draw_campaign_stats(f: &mut Frame, app: &App) {
+    let Some(stats) = app.campaign_stats.as_ref() else {
+        return;
+    };
+    if draw_too_small(f, &app.settings.language, MIN_SIZE.0, MIN_SIZE.1) {
+        return;
+    }
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Max(3), Constraint::Min(10), Constraint::Max(3)])
+        .split(f.area());
+    render_header(f, chunks[0]);
+
+    let body = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .split(chunks[1]);
+    let charts = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(body[1]);
+
+    let title = format!(
+        " {} — {} ",
+        tr(&app.settings.language, "Campaign Statistics"),
+        stats.save_name
+    );
+    render_totals(f, stats, &title, body[0]);
+    render_hits(f, stats, charts[0]);
+    render_nuyen(f, stats, charts[1]);
+
+    let hint = Paragraph::new(" Esc back ")
+        .style(Style::default().fg(palette().muted))
+        .alignment(Alignment::Center);
+    f.render_widget(hint, chunks[2]);
+}
+
+fn render_totals(f: &mut Frame, stats: &CampaignStats, title: &str, area: Rect) {
+    let row = |label: &str, value: String| {
+        Line::from(vec![
+            Span::styled(
+                format!("{:<18}", label),
+                Style::default()
+                    .fg(palette().highlight)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(value),
+        ])
+    };
+    let lines = vec![
+        row("Play time", play_time(stats.play.seconds)),
+        row("Turns played", stats.play.turns.to_string()),
+        row("Rolls made", stats.rolls.to_string()),
+        row(
+            "Average hits",
+            stats
+                .average_hits()
+                .map_or_else(|| "-".to_string(), |average| format!("{:.1}", average)),
+        ),
+        row("Glitches", stats.glitches.to_string()),
+        row("Nuyen earned", format!("{}¥", stats.nuyen_earned)),
+        row("Nuyen spent", format!("{}¥", stats.nuyen_spent)),
+        row("NPCs met", stats.npcs_met.to_string()),
+    ];
+    let totals = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(palette().highlight))
+            .title(title.to_string()),
+    );
+    f.render_widget(totals, area);
+}
+
+fn render_hits(f: &mut Frame, stats: &CampaignStats, area: Rect) {
+    let labels: Vec<String> = (0..HIT_GROUPS)
+        .map(|hits| match hits {
+            last if last == HIT_GROUPS - 1 => format!("{}+", last),
+            hits => hits.to_string(),
+        })
+        .collect();
+    let bars: Vec<Bar> = labels
+        .iter()
+        .zip(stats.hit_counts.iter())
+        .map(|(label, count)| {
+            Bar::default()
+                .label(Line::from(label.clone()))
+                .value(*count as u64)
+        })
+        .collect();
+    let chart = BarChart::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Rolls by hits "),
+        )
+        .data(BarGroup::default().bars(&bars))
+        .bar_width(5)
+        .bar_gap(2)
+        .bar_style(Style::default().fg(palette().success))
+        .value_style(
+            Style::default()
+                .fg(palette().text)
+                .add_modifier(Modifier::REVERSED),
+        );
+    f.render_widget(chart, area);
+}
+
+fn render_nuyen(f: &mut Frame, stats: &CampaignStats, area: Rect) {
+    let bars = [
+        Bar::default()
+            .label(Line::from("Earned"))
+            .value(stats.nuyen_earned)
+            .style(Style::default().fg(palette().success)),
+        Bar::default()
+            .label(Line::from("Spent"))
+            .value(stats.nuyen_spent)
+            .style(Style::default().fg(palette().failure)),
+    ];
+    let chart = BarChart::default()
+        .block(Block::default().borders(Borders::ALL).title(" Nuyen "))
+        .direction(Direction::Horizontal)
+        .data(BarGroup::default().bars(&bars))
+        .bar_width(1)
+        .bar_gap(1);
+    f.render_widget(chart, area);
+}
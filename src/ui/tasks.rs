@@ -0,0 +1,121 @@
+// ui/tasks.rs
+// The tasks panel: what runs in the background, for how long, and how it ended, with the
+// selected task cancelled on 'c'; and the notices of tasks that finished while the player was
+// away from the game.
+
+use crate::app::App;
+use crate::tasks::TaskStatus;
+use crate::ui::theme::palette;
+use crate::ui::utils::truncate_to_width;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::*,
+    Frame,
+};
+
+const WIDTH: u16 = 72;
+const TOAST_WIDTH: u16 = 56;
+
+pub fn draw_tasks_panel(f: &mut Frame, app: &App, area: Rect) {
+    let selected = app.tasks_panel.unwrap_or(0);
+    let tasks = app.tasks.list();
+    let mut lines: Vec<Line> = tasks
+        .iter()
+        .enumerate()
+        .map(|(index, task)| {
+            let color = match task.status {
+                TaskStatus::Running => palette().highlight,
+                TaskStatus::Done => palette().success,
+                TaskStatus::Failed(_) => palette().failure,
+                TaskStatus::Cancelled => palette().muted,
+            };
+            let mut style = Style::default().fg(color);
+            if index == selected {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+            let text = format!(
+                "{:<13} {:>4}s  {}  {}",
+                task.kind.label(),
+                task.elapsed().as_secs(),
+                task.status.label(),
+                task.description
+            );
+            Line::from(Span::styled(
+                truncate_to_width(&text, WIDTH.saturating_sub(4) as usize),
+                style,
+            ))
+        })
+        .collect();
+    if lines.is_empty() {
+        lines.push(Line::styled(
+            "Nothing runs in the background.",
+            Style::default().fg(palette().muted),
+        ));
+    }
+    lines.push(Line::raw(""));
+    lines.push(Line::styled(
+        "↑/↓ select   c cancel   Esc close",
+        Style::default().fg(palette().muted),
+    ));
+
+    let width = WIDTH.min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let rect = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Thick)
+        .border_style(Style::default().fg(palette().highlight))
+        .title(" Background tasks ");
+    f.render_widget(Clear, rect);
+    f.render_widget(Paragraph::new(lines).block(block), rect);
+}
+
+// The notices in the bottom right corner, the latest last, with F2 on the one it opens.
+pub fn draw_toasts(f: &mut Frame, app: &App) {
+    let followed = app.followed_toast();
+    let lines: Vec<Line> = app
+        .toasts
+        .iter()
+        .enumerate()
+        .filter(|(_, toast)| !toast.expired())
+        .map(|(index, toast)| {
+            let color = match toast.jump {
+                Some(_) => palette().success,
+                None => palette().failure,
+            };
+            let mut text = toast.text.clone();
+            if Some(index) == followed {
+                text = format!("[F2] {}", text);
+            }
+            Line::from(Span::styled(
+                truncate_to_width(&text, TOAST_WIDTH.saturating_sub(2) as usize),
+                Style::default().fg(color),
+            ))
+        })
+        .collect();
+    if lines.is_empty() {
+        return;
+    }
+    let area = f.area();
+    let width = TOAST_WIDTH.min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let rect = Rect {
+        x: area.x + area.width - width,
+        y: area.y + area.height - height,
+        width,
+        height,
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(palette().highlight))
+        .title(" Finished ");
+    f.render_widget(Clear, rect);
+    f.render_widget(Paragraph::new(lines).block(block), rect);
+}
@@ -0,0 +1,117 @@
+// ui/theme.rs
+// Colour palettes for the Theme setting, named by what a colour means rather than how it looks.
+//
+// Colour is never the only cue: selected options are also underlined, highlighted sections get a
+// thick border and a marker, and qualities are prefixed with + or -.
+
+use crate::settings::Theme;
+use ratatui::{
+    style::{Color, Modifier, Style},
+    widgets::{Block, BorderType, Borders},
+};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    // Art, transcript and sheet values.
+    pub primary: Color,
+    pub success: Color,
+    pub failure: Color,
+    pub positive: Color,
+    pub negative: Color,
+    // Selection, labels and highlighted sections.
+    pub highlight: Color,
+    pub info: Color,
+    pub text: Color,
+    pub muted: Color,
+    pub dim: Color,
+}
+
+const DEFAULT: Palette = Palette {
+    primary: Color::Green,
+    success: Color::Green,
+    failure: Color::Red,
+    positive: Color::Green,
+    negative: Color::Red,
+    highlight: Color::Yellow,
+    info: Color::Cyan,
+    text: Color::White,
+    muted: Color::Gray,
+    dim: Color::DarkGray,
+};
+
+// Okabe-Ito colours, told apart with deuteranopia and protanopia: blue for good, orange for bad.
+const COLORBLIND: Palette = Palette {
+    primary: Color::Rgb(0, 158, 115),
+    success: Color::Rgb(0, 114, 178),
+    failure: Color::Rgb(230, 159, 0),
+    positive: Color::Rgb(86, 180, 233),
+    negative: Color::Rgb(213, 94, 0),
+    highlight: Color::Rgb(240, 228, 66),
+    info: Color::Rgb(86, 180, 233),
+    text: Color::White,
+    muted: Color::Gray,
+    dim: Color::Gray,
+};
+
+// Bright colours only, so nothing fades into a dark background.
+const HIGH_CONTRAST: Palette = Palette {
+    primary: Color::White,
+    success: Color::LightCyan,
+    failure: Color::LightRed,
+    positive: Color::LightCyan,
+    negative: Color::LightRed,
+    highlight: Color::LightYellow,
+    info: Color::LightCyan,
+    text: Color::White,
+    muted: Color::White,
+    dim: Color::Gray,
+};
+
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+impl Palette {
+    pub fn of(theme: Theme) -> Palette {
+        match theme {
+            Theme::Default => DEFAULT,
+            Theme::Colorblind => COLORBLIND,
+            Theme::HighContrast => HIGH_CONTRAST,
+        }
+    }
+
+    // The chosen option of a settings row.
+    pub fn selected(&self) -> Style {
+        Style::default()
+            .fg(self.success)
+            .add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+    }
+}
+
+// Switch the palette every screen draws with.
+pub fn set(theme: Theme) {
+    CURRENT.store(theme as u8, Ordering::Relaxed);
+}
+
+pub fn palette() -> Palette {
+    Palette::of(match CURRENT.load(Ordering::Relaxed) {
+        1 => Theme::Colorblind,
+        2 => Theme::HighContrast,
+        _ => Theme::Default,
+    })
+}
+
+// A bordered character sheet section; the highlighted one stands out by shape as well as colour.
+pub fn section_block(title: &str, highlighted: bool) -> Block<'static> {
+    let palette = palette();
+    let block = Block::default().borders(Borders::ALL);
+    if highlighted {
+        block
+            .border_type(BorderType::Thick)
+            .border_style(Style::default().fg(palette.highlight))
+            .title(format!(" ▶ {} ", title))
+    } else {
+        block
+            .border_style(Style::default().fg(palette.text))
+            .title(format!(" {} ", title))
+    }
+}
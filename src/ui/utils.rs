@@ -1,6 +1,54 @@
 // ui/utils.rs
 
-use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use crate::i18n::trf;
+use crate::ui::theme::palette;
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use ratatui::style::Style;
+use ratatui::widgets::{Clear, Paragraph, Wrap};
+use ratatui::Frame;
+use textwrap::{Options, WordSeparator};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+// The smallest terminal most screens are laid out for.
+pub const MIN_WIDTH: u16 = 100;
+pub const MIN_HEIGHT: u16 = 50;
+
+// Ask for a bigger terminal instead of drawing a screen that doesn't fit. True if the terminal is
+// smaller than `width` x `height`, and then nothing else should be drawn; the screen comes back
+// by itself once the terminal is resized.
+pub fn draw_too_small(f: &mut Frame, language: &str, width: u16, height: u16) -> bool {
+    let area = f.area();
+    if area.width >= width && area.height >= height {
+        return false;
+    }
+    let text = trf(
+        language,
+        "Please resize the terminal to at least {}×{} (current: {}×{}).",
+        &[
+            &width.to_string(),
+            &height.to_string(),
+            &area.width.to_string(),
+            &area.height.to_string(),
+        ],
+    );
+    // In the middle of the screen, on as many lines as it takes.
+    let lines = wrap_to_width(&text, area.width as usize).len() as u16;
+    let message_area = Rect {
+        y: area.y + area.height.saturating_sub(lines) / 2,
+        height: lines.min(area.height),
+        ..area
+    };
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(text)
+            .wrap(Wrap { trim: true })
+            .style(Style::default().fg(palette().failure))
+            .alignment(Alignment::Center),
+        message_area,
+    );
+    true
+}
 
 pub fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
@@ -22,6 +70,37 @@ pub fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         .split(popup_layout[1])[1]
 }
 
+// Wrap text to a width in terminal cells. Wide characters count as two cells, and text without
+// spaces (Japanese, Chinese) may break between characters.
+pub fn wrap_to_width(text: &str, width: usize) -> Vec<String> {
+    let options = Options::new(width.max(1)).word_separator(WordSeparator::UnicodeBreakProperties);
+    textwrap::wrap(text, options)
+        .into_iter()
+        .map(|line| line.into_owned())
+        .collect()
+}
+
+// Cut text to fit a width in terminal cells, ending with "…" when something was cut.
+pub fn truncate_to_width(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    let mut truncated = String::new();
+    let mut used = 0;
+    for grapheme in text.graphemes(true) {
+        let grapheme_width = grapheme.width();
+        if used + grapheme_width + 1 > width {
+            break;
+        }
+        truncated.push_str(grapheme);
+        used += grapheme_width;
+    }
+    if width > 0 {
+        truncated.push('…');
+    }
+    truncated
+}
+
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
@@ -31,6 +110,12 @@ pub struct Spinner {
     current_frame: Arc<AtomicUsize>,
 }
 
+impl Default for Spinner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Spinner {
     pub fn new() -> Self {
         Spinner {
@@ -47,7 +132,3 @@ impl Spinner {
         SPINNER_CHARS[frame]
     }
 }
-
-pub fn spinner_frame(spinner: &Spinner) -> String {
-    format!(" Game Master is thinking {} ", spinner.get_frame())
-}
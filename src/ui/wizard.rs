@@ -0,0 +1,300 @@
+// ui/wizard.rs
+
+use super::main_menu::render_header;
+use crate::app::App;
+use crate::ruleset::RulesetKind;
+use crate::ui::theme::palette;
+use crate::ui::utils::{draw_too_small, MIN_HEIGHT, MIN_WIDTH};
+use crate::wizard::{
+    race_allowed, CharacterWizard, WizardStep, ATTRIBUTE_NAMES, GENDERS, MAGIC, MAGIC_TYPES,
+    METATYPE, PRIORITY_CATEGORIES, PRIORITY_LETTERS, RACES, SKILL_LIST, STEPS,
+};
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::*,
+    Frame,
+};
+
+pub fn draw_character_wizard(f: &mut Frame, app: &App) {
+    let size = f.area();
+
+    if draw_too_small(f, &app.settings.language, MIN_WIDTH, MIN_HEIGHT) {
+        return;
+    }
+    let Some(wizard) = app.character_wizard.as_ref() else {
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Max(3),
+                Constraint::Max(3),
+                Constraint::Min(20),
+                Constraint::Max(3),
+                Constraint::Max(3),
+            ]
+            .as_ref(),
+        )
+        .split(size);
+
+    render_header(f, chunks[0]);
+    render_steps(f, wizard, chunks[1]);
+
+    let body = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+        .split(chunks[2]);
+    render_step(f, wizard, body[0]);
+    render_preview(f, wizard, app.settings.ruleset, body[1]);
+
+    let message = match &wizard.message {
+        Some(message) => {
+            Paragraph::new(message.as_str()).style(Style::default().fg(palette().failure))
+        }
+        None => Paragraph::new(step_hint(wizard.step)).style(Style::default().fg(palette().muted)),
+    };
+    f.render_widget(message.alignment(Alignment::Center), chunks[3]);
+
+    let status = Paragraph::new(
+        "Up/Down to select, Left/Right to change, Enter or Tab for next step, Esc to go back",
+    )
+    .style(Style::default().fg(palette().dim))
+    .alignment(Alignment::Center);
+    f.render_widget(status, chunks[4]);
+}
+
+fn step_hint(step: WizardStep) -> String {
+    match step {
+        WizardStep::Identity => "Type a name, then pick a gender.".to_string(),
+        WizardStep::Priorities => {
+            "Assign one priority letter to each category; changing one swaps it with another."
+                .to_string()
+        }
+        WizardStep::Metatype => "Right or Space picks the highlighted metatype.".to_string(),
+        WizardStep::Magic => "Right or Space picks the highlighted magic type.".to_string(),
+        WizardStep::Attributes => {
+            "Values in brackets include your metatype's modifiers.".to_string()
+        }
+        WizardStep::Skills => "Skills can be raised up to rating 6.".to_string(),
+        WizardStep::Review => "Press Enter to start the game with this character.".to_string(),
+    }
+}
+
+fn render_steps(f: &mut Frame, wizard: &CharacterWizard, area: Rect) {
+    let mut spans = Vec::new();
+    for (i, step) in STEPS.iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::styled(" > ", Style::default().fg(palette().dim)));
+        }
+        let style = if *step == wizard.step {
+            Style::default()
+                .fg(palette().highlight)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(palette().muted)
+        };
+        spans.push(Span::styled(step.title(), style));
+    }
+    let steps = Paragraph::new(Line::from(spans))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::BOTTOM));
+    f.render_widget(steps, area);
+}
+
+// A menu row, highlighted when it is under the cursor.
+fn row(selected: bool, text: String) -> Line<'static> {
+    if selected {
+        Line::from(Span::styled(
+            format!("> {}", text),
+            Style::default()
+                .fg(palette().highlight)
+                .add_modifier(Modifier::BOLD),
+        ))
+    } else {
+        Line::from(Span::raw(format!("  {}", text)))
+    }
+}
+
+fn render_step(f: &mut Frame, wizard: &CharacterWizard, area: Rect) {
+    let lines: Vec<Line> = match wizard.step {
+        WizardStep::Identity => vec![
+            row(
+                wizard.cursor == 0,
+                format!("Name:   {}", wizard.name.value()),
+            ),
+            row(
+                wizard.cursor == 1,
+                format!("Gender: < {} >", GENDERS[wizard.gender]),
+            ),
+        ],
+        WizardStep::Priorities => PRIORITY_CATEGORIES
+            .iter()
+            .enumerate()
+            .map(|(i, category)| {
+                row(
+                    wizard.cursor == i,
+                    format!(
+                        "{:<12} < {} >",
+                        category,
+                        PRIORITY_LETTERS[wizard.priority(i)]
+                    ),
+                )
+            })
+            .collect(),
+        WizardStep::Metatype => RACES
+            .iter()
+            .enumerate()
+            .map(|(i, race)| {
+                let chosen = if wizard.race == i { "[x]" } else { "[ ]" };
+                let note = if race_allowed(race, wizard.priority(METATYPE)) {
+                    ""
+                } else {
+                    " (needs a higher priority)"
+                };
+                row(wizard.cursor == i, format!("{} {}{}", chosen, race, note))
+            })
+            .collect(),
+        WizardStep::Magic => MAGIC_TYPES
+            .iter()
+            .enumerate()
+            .map(|(i, magic_type)| {
+                let chosen = if wizard.magic_type == i { "[x]" } else { "[ ]" };
+                let rating = match magic_type.rating(wizard.priority(MAGIC)) {
+                    Some(rating) => format!(" (rating {})", rating),
+                    None => " (needs a higher priority)".to_string(),
+                };
+                row(
+                    wizard.cursor == i,
+                    format!("{} {}{}", chosen, magic_type.name(), rating),
+                )
+            })
+            .collect(),
+        WizardStep::Attributes => {
+            let finals = wizard.final_attributes();
+            let mut lines = vec![Line::from(Span::styled(
+                format!(
+                    "Points: {} / {}",
+                    wizard.attribute_points_spent(),
+                    wizard.attribute_budget()
+                ),
+                Style::default().fg(palette().info),
+            ))];
+            lines.extend(ATTRIBUTE_NAMES.iter().enumerate().map(|(i, name)| {
+                row(
+                    wizard.cursor == i,
+                    format!("{:<10} < {} > ({})", name, wizard.attributes[i], finals[i]),
+                )
+            }));
+            lines
+        }
+        WizardStep::Skills => {
+            let mut lines = vec![Line::from(Span::styled(
+                format!(
+                    "Points: {} / {}",
+                    wizard.skill_points_spent(),
+                    wizard.skill_budget()
+                ),
+                Style::default().fg(palette().info),
+            ))];
+            // Keep the cursor on screen when the list is taller than the panel.
+            let visible = area.height.saturating_sub(3) as usize;
+            let start = wizard.cursor.saturating_sub(visible.saturating_sub(1));
+            lines.extend(SKILL_LIST.iter().enumerate().skip(start).take(visible).map(
+                |(i, (category, name))| {
+                    row(
+                        wizard.cursor == i,
+                        format!("{:<20} < {} >  {:?}", name, wizard.skills[i], category),
+                    )
+                },
+            ));
+            lines
+        }
+        WizardStep::Review => {
+            let errors = wizard.validate();
+            if errors.is_empty() {
+                vec![Line::from(Span::styled(
+                    "Your character is complete.",
+                    Style::default().fg(palette().success),
+                ))]
+            } else {
+                errors
+                    .into_iter()
+                    .map(|error| {
+                        Line::from(Span::styled(
+                            format!("- {}", error),
+                            Style::default().fg(palette().failure),
+                        ))
+                    })
+                    .collect()
+            }
+        }
+    };
+
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" {} ", wizard.step.title())),
+        )
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, area);
+}
+
+fn render_preview(f: &mut Frame, wizard: &CharacterWizard, ruleset: RulesetKind, area: Rect) {
+    let mut sheet = wizard.preview();
+    ruleset.rules().derive(&mut sheet);
+    let label =
+        |text: &str| Span::styled(text.to_string(), Style::default().fg(palette().highlight));
+
+    let mut lines = vec![
+        Line::from(vec![label("Name: "), Span::raw(sheet.name.clone())]),
+        Line::from(vec![
+            label("Race: "),
+            Span::raw(sheet.race.to_string()),
+            label("  Gender: "),
+            Span::raw(sheet.gender.clone()),
+        ]),
+        Line::from(""),
+    ];
+    let finals = wizard.final_attributes();
+    for (name, value) in ATTRIBUTE_NAMES.iter().zip(finals.iter()) {
+        lines.push(Line::from(vec![
+            label(&format!("{:<10}", name)),
+            Span::styled(value.to_string(), Style::default().fg(palette().primary)),
+        ]));
+    }
+    lines.push(Line::from(vec![
+        label(&format!("{:<10}", "Magic")),
+        Span::raw(sheet.magic.unwrap_or(0).to_string()),
+    ]));
+    lines.push(Line::from(vec![
+        label(&format!("{:<10}", "Resonance")),
+        Span::raw(sheet.resonance.unwrap_or(0).to_string()),
+    ]));
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        label("Initiative: "),
+        Span::raw(format!("{}+{}d6", sheet.initiative.0, sheet.initiative.1)),
+    ]));
+    lines.push(Line::from(vec![
+        label("Limits: "),
+        Span::raw(match sheet.limits() {
+            Some((physical, mental, social)) => {
+                format!("PHY:{} MEN:{} SOC:{}", physical, mental, social)
+            }
+            None => "none".to_string(),
+        }),
+    ]));
+    lines.push(Line::from(vec![
+        label("Nuyen: "),
+        Span::raw(sheet.nuyen.to_string()),
+    ]));
+
+    let preview =
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(" Character "));
+    f.render_widget(preview, area);
+}
@@ -0,0 +1,97 @@
+// update.rs
+// Background check for newer releases on GitHub, with optional install through cargo.
+
+use crate::app::AppCommand;
+use crate::settings::UpdateMode;
+use std::time::Duration;
+use tokio::process::Command;
+use tokio::sync::mpsc;
+
+const RELEASES_URL: &str = "https://api.github.com/repos/ProHaller/sharad_ratatui/releases/latest";
+const CRATE_NAME: &str = "sharad_ratatui";
+// A slow GitHub should never be noticeable; give up quietly instead.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Numeric parts of a version like "v0.1.32", ignoring any pre-release suffix.
+fn version_parts(version: &str) -> Vec<u64> {
+    version
+        .trim()
+        .trim_start_matches('v')
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+pub fn is_newer(latest: &str, current: &str) -> bool {
+    let (mut latest, mut current) = (version_parts(latest), version_parts(current));
+    let len = latest.len().max(current.len());
+    latest.resize(len, 0);
+    current.resize(len, 0);
+    latest > current
+}
+
+async fn latest_version() -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let release: serde_json::Value = reqwest::Client::new()
+        .get(RELEASES_URL)
+        // GitHub rejects API requests without a user agent.
+        .header("User-Agent", CRATE_NAME)
+        .timeout(CHECK_TIMEOUT)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(release["tag_name"]
+        .as_str()
+        .ok_or("release has no tag")?
+        .trim_start_matches('v')
+        .to_string())
+}
+
+async fn install(version: &str) -> Result<(), String> {
+    let output = Command::new("cargo")
+        .args(["install", CRATE_NAME, "--locked", "--version", version])
+        .output()
+        .await
+        .map_err(|e| format!("cargo not found: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr)
+            .lines()
+            .last()
+            .unwrap_or("cargo install failed")
+            .to_string())
+    }
+}
+
+// Check in the background and report anything worth telling the player as an UpdateNotice.
+pub fn spawn_check(mode: UpdateMode, sender: mpsc::UnboundedSender<AppCommand>) {
+    if mode == UpdateMode::Off {
+        return;
+    }
+    tokio::spawn(async move {
+        let latest = match latest_version().await {
+            Ok(latest) => latest,
+            Err(e) => {
+                log::info!("Update check failed: {}", e);
+                return;
+            }
+        };
+        if !is_newer(&latest, env!("CARGO_PKG_VERSION")) {
+            return;
+        }
+
+        let notice = match mode {
+            UpdateMode::Auto => match install(&latest).await {
+                Ok(()) => format!("Updated to v{}, restart to play it", latest),
+                Err(e) => format!("v{} is available (auto-update failed: {})", latest, e),
+            },
+            _ => format!("v{} is available", latest),
+        };
+        let _ = sender.send(AppCommand::UpdateNotice(notice));
+    });
+}
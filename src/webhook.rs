@@ -0,0 +1,224 @@
+// webhook.rs
+// Optional Discord webhook integration that mirrors the session log into a channel.
+
+use crate::dice::DiceRollResponse;
+use crate::error::{send_global_error, ShadowrunError};
+use crate::message::GameMessage;
+use once_cell::sync::Lazy;
+use reqwest::{multipart, StatusCode};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, Duration, Instant};
+
+// Discord allows a handful of requests per couple of seconds per webhook; stay well under it.
+const MIN_POST_INTERVAL: Duration = Duration::from_millis(1500);
+// Discord rejects messages longer than this.
+const MAX_CONTENT_LENGTH: usize = 2000;
+const MAX_ATTEMPTS: usize = 3;
+
+static WEBHOOK: Lazy<Mutex<Option<WebhookPoster>>> = Lazy::new(|| Mutex::new(None));
+
+pub enum WebhookEvent {
+    GameMessage(Box<GameMessage>),
+    DiceRoll {
+        summary: String,
+        response: DiceRollResponse,
+    },
+    Image(PathBuf),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Payload {
+    Text(String),
+    File(PathBuf),
+}
+
+struct WebhookPoster {
+    url: String,
+    sender: mpsc::UnboundedSender<WebhookEvent>,
+    handle: JoinHandle<()>,
+}
+
+impl Drop for WebhookPoster {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+// Point the webhook at a new URL, or turn it off with None. Must be called inside the tokio runtime.
+pub fn configure(url: Option<String>) {
+    let mut webhook = WEBHOOK.lock().unwrap();
+    match url {
+        Some(url) => {
+            if webhook.as_ref().is_some_and(|poster| poster.url == url) {
+                return;
+            }
+            let (sender, receiver) = mpsc::unbounded_channel();
+            let handle = tokio::spawn(run_poster(url.clone(), receiver));
+            *webhook = Some(WebhookPoster {
+                url,
+                sender,
+                handle,
+            });
+        }
+        None => *webhook = None,
+    }
+}
+
+// Queue an event for posting. Does nothing when the webhook is disabled.
+pub fn post(event: WebhookEvent) {
+    if let Some(poster) = WEBHOOK.lock().unwrap().as_ref() {
+        let _ = poster.sender.send(event);
+    }
+}
+
+async fn run_poster(url: String, mut receiver: mpsc::UnboundedReceiver<WebhookEvent>) {
+    let client = reqwest::Client::new();
+    let mut last_post: Option<Instant> = None;
+
+    while let Some(event) = receiver.recv().await {
+        for payload in payloads(event) {
+            if let Some(last_post) = last_post {
+                sleep(MIN_POST_INTERVAL.saturating_sub(last_post.elapsed())).await;
+            }
+            if let Err(e) = send_payload(&client, &url, &payload).await {
+                send_global_error(ShadowrunError::Network(format!(
+                    "Discord webhook post failed: {}",
+                    e
+                )))
+                .await;
+            }
+            last_post = Some(Instant::now());
+        }
+    }
+}
+
+pub async fn send_payload(
+    client: &reqwest::Client,
+    url: &str,
+    payload: &Payload,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    for _ in 0..MAX_ATTEMPTS {
+        // Multipart forms can't be reused, so the request is rebuilt for every attempt.
+        let request = match payload {
+            Payload::Text(content) => client
+                .post(url)
+                .json(&serde_json::json!({ "content": content })),
+            Payload::File(path) => {
+                let bytes = tokio::fs::read(path).await?;
+                let file_name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "image.png".to_string());
+                let form = multipart::Form::new()
+                    .text("content", "Generated image")
+                    .part(
+                        "files[0]",
+                        multipart::Part::bytes(bytes).file_name(file_name),
+                    );
+                client.post(url).multipart(form)
+            }
+        };
+
+        let response = request.send().await?;
+        if response.status() != StatusCode::TOO_MANY_REQUESTS {
+            response.error_for_status()?;
+            return Ok(());
+        }
+
+        // Discord tells us how long to back off, in seconds.
+        let retry_after = response
+            .json::<serde_json::Value>()
+            .await
+            .ok()
+            .and_then(|body| body["retry_after"].as_f64())
+            .unwrap_or(1.0);
+        sleep(Duration::from_secs_f64(retry_after.clamp(0.0, 60.0))).await;
+    }
+    Err("rate limited by Discord".into())
+}
+
+pub fn payloads(event: WebhookEvent) -> Vec<Payload> {
+    match event {
+        WebhookEvent::GameMessage(game_message) => {
+            let mut text = String::new();
+            for line in game_message.fluff.dialogue.iter() {
+                let speaker = game_message
+                    .fluff
+                    .speakers
+                    .iter()
+                    .find(|s| s.index == line.speaker_index);
+                match speaker {
+                    Some(speaker) if speaker.name != "Narrator" => {
+                        text.push_str(&format!("**{}:** {}\n\n", speaker.name, line.text))
+                    }
+                    _ => text.push_str(&format!("{}\n\n", line.text)),
+                }
+            }
+            split_content(text.trim())
+                .into_iter()
+                .map(Payload::Text)
+                .collect()
+        }
+        WebhookEvent::DiceRoll { summary, response } => {
+            let dice: Vec<String> = response
+                .dice_results
+                .iter()
+                .map(|d| d.to_string())
+                .collect();
+            let mut text = format!(
+                "🎲 **{}**: {} hits [{}] - {}",
+                summary,
+                response.hits,
+                dice.join(", "),
+                if response.success {
+                    "success"
+                } else {
+                    "failure"
+                }
+            );
+            if response.critical_glitch {
+                text.push_str(", **critical glitch!**");
+            } else if response.glitch {
+                text.push_str(", glitch");
+            }
+            if response.critical_success {
+                text.push_str(", **critical success!**");
+            }
+            vec![Payload::Text(text)]
+        }
+        WebhookEvent::Image(path) => vec![Payload::File(path)],
+    }
+}
+
+// Split text into Discord-sized messages, preferring line breaks as cut points.
+pub fn split_content(text: &str) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+
+    for line in text.split_inclusive('\n') {
+        if current.chars().count() + line.chars().count() > MAX_CONTENT_LENGTH {
+            if !current.is_empty() {
+                chunks.push(std::mem::take(&mut current));
+            }
+            // A single line that is too long on its own gets hard-wrapped.
+            let mut chars = line.chars().peekable();
+            while chars.peek().is_some() {
+                let piece: String = chars.by_ref().take(MAX_CONTENT_LENGTH).collect();
+                if piece.chars().count() == MAX_CONTENT_LENGTH {
+                    chunks.push(piece);
+                } else {
+                    current = piece;
+                }
+            }
+        } else {
+            current.push_str(line);
+        }
+    }
+    if !current.trim().is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
@@ -0,0 +1,578 @@
+// wizard.rs
+// Offline, step-by-step character creation using the priority system.
+
+use crate::character::{CharacterSheet, CharacterSheetBuilder, Quality, Race, Skills};
+use crossterm::event::{Event, KeyCode, KeyEvent};
+use std::collections::HashMap;
+use tui_input::backend::crossterm::EventHandler;
+use tui_input::Input;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WizardStep {
+    Identity,
+    Priorities,
+    Metatype,
+    Magic,
+    Attributes,
+    Skills,
+    Review,
+}
+
+pub const STEPS: [WizardStep; 7] = [
+    WizardStep::Identity,
+    WizardStep::Priorities,
+    WizardStep::Metatype,
+    WizardStep::Magic,
+    WizardStep::Attributes,
+    WizardStep::Skills,
+    WizardStep::Review,
+];
+
+impl WizardStep {
+    pub fn title(&self) -> &'static str {
+        match self {
+            WizardStep::Identity => "Identity",
+            WizardStep::Priorities => "Priorities",
+            WizardStep::Metatype => "Metatype",
+            WizardStep::Magic => "Magic",
+            WizardStep::Attributes => "Attributes",
+            WizardStep::Skills => "Skills",
+            WizardStep::Review => "Review",
+        }
+    }
+
+    fn index(&self) -> usize {
+        STEPS.iter().position(|step| step == self).unwrap_or(0)
+    }
+}
+
+// Priority categories, in the order they are shown on the priority table.
+pub const PRIORITY_CATEGORIES: [&str; 5] =
+    ["Metatype", "Attributes", "Magic", "Skills", "Resources"];
+pub const METATYPE: usize = 0;
+const ATTRIBUTES: usize = 1;
+pub const MAGIC: usize = 2;
+const SKILLS: usize = 3;
+const RESOURCES: usize = 4;
+
+// 0 is priority A, 4 is priority E.
+pub const PRIORITY_LETTERS: [&str; 5] = ["A", "B", "C", "D", "E"];
+const ATTRIBUTE_POINTS: [u8; 5] = [24, 20, 16, 14, 12];
+const SKILL_POINTS: [u32; 5] = [46, 36, 28, 22, 18];
+const NUYEN: [u32; 5] = [450_000, 275_000, 140_000, 50_000, 6_000];
+
+pub const RACES: [Race; 5] = [Race::Human, Race::Elf, Race::Dwarf, Race::Ork, Race::Troll];
+pub const GENDERS: [&str; 3] = ["Female", "Male", "Non-binary"];
+
+pub const ATTRIBUTE_NAMES: [&str; 9] = [
+    "Body",
+    "Agility",
+    "Reaction",
+    "Strength",
+    "Willpower",
+    "Logic",
+    "Intuition",
+    "Charisma",
+    "Edge",
+];
+const MAX_BASE_ATTRIBUTE: u8 = 6;
+const MAX_SKILL_RATING: u8 = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkillCategory {
+    Combat,
+    Physical,
+    Social,
+    Technical,
+}
+
+pub const SKILL_LIST: &[(SkillCategory, &str)] = &[
+    (SkillCategory::Combat, "Automatics"),
+    (SkillCategory::Combat, "Blades"),
+    (SkillCategory::Combat, "Clubs"),
+    (SkillCategory::Combat, "Gunnery"),
+    (SkillCategory::Combat, "Longarms"),
+    (SkillCategory::Combat, "Pistols"),
+    (SkillCategory::Combat, "Throwing Weapons"),
+    (SkillCategory::Combat, "Unarmed Combat"),
+    (SkillCategory::Physical, "Gymnastics"),
+    (SkillCategory::Physical, "Perception"),
+    (SkillCategory::Physical, "Running"),
+    (SkillCategory::Physical, "Sneaking"),
+    (SkillCategory::Physical, "Swimming"),
+    (SkillCategory::Social, "Con"),
+    (SkillCategory::Social, "Etiquette"),
+    (SkillCategory::Social, "Intimidation"),
+    (SkillCategory::Social, "Leadership"),
+    (SkillCategory::Social, "Negotiation"),
+    (SkillCategory::Technical, "Computer"),
+    (SkillCategory::Technical, "Cybercombat"),
+    (SkillCategory::Technical, "Electronic Warfare"),
+    (SkillCategory::Technical, "First Aid"),
+    (SkillCategory::Technical, "Hacking"),
+    (SkillCategory::Technical, "Pilot Ground Craft"),
+    (SkillCategory::Technical, "Spellcasting"),
+    (SkillCategory::Technical, "Summoning"),
+    (SkillCategory::Technical, "Counterspelling"),
+];
+const MAGICIAN_SKILLS: [&str; 3] = ["Spellcasting", "Summoning", "Counterspelling"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MagicType {
+    Mundane,
+    Magician,
+    Adept,
+    Technomancer,
+}
+
+pub const MAGIC_TYPES: [MagicType; 4] = [
+    MagicType::Mundane,
+    MagicType::Magician,
+    MagicType::Adept,
+    MagicType::Technomancer,
+];
+
+impl MagicType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            MagicType::Mundane => "Mundane",
+            MagicType::Magician => "Magician",
+            MagicType::Adept => "Adept",
+            MagicType::Technomancer => "Technomancer",
+        }
+    }
+
+    // Magic or Resonance rating granted at a priority, None if the priority is too low.
+    pub fn rating(&self, priority: usize) -> Option<u8> {
+        match (self, priority) {
+            (MagicType::Mundane, _) => Some(0),
+            (MagicType::Magician | MagicType::Technomancer, 0) => Some(6),
+            (MagicType::Magician | MagicType::Technomancer, 1) => Some(4),
+            (MagicType::Magician | MagicType::Technomancer, 2) => Some(3),
+            (MagicType::Adept, 0 | 1) => Some(6),
+            (MagicType::Adept, 2) => Some(4),
+            (MagicType::Adept, 3) => Some(2),
+            _ => None,
+        }
+    }
+}
+
+// Metatypes available at a given metatype priority.
+pub fn race_allowed(race: &Race, priority: usize) -> bool {
+    match race {
+        Race::Human => true,
+        Race::Elf => priority <= 3,
+        Race::Dwarf | Race::Ork => priority <= 2,
+        Race::Troll => priority <= 1,
+    }
+}
+
+pub enum WizardAction {
+    None,
+    Cancel,
+    Finish(Box<CharacterSheet>),
+}
+
+pub struct CharacterWizard {
+    pub step: WizardStep,
+    pub cursor: usize,
+    pub name: Input,
+    pub gender: usize,
+    // Priority index (0 = A) per category, always a permutation of 0..5.
+    pub priorities: [usize; 5],
+    pub race: usize,
+    pub magic_type: usize,
+    pub attributes: [u8; 9],
+    pub skills: Vec<u8>,
+    pub message: Option<String>,
+}
+
+impl Default for CharacterWizard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CharacterWizard {
+    pub fn new() -> Self {
+        CharacterWizard {
+            step: WizardStep::Identity,
+            cursor: 0,
+            name: Input::default(),
+            gender: 0,
+            priorities: [4, 0, 3, 1, 2],
+            race: 0,
+            magic_type: 0,
+            attributes: [1; 9],
+            skills: vec![0; SKILL_LIST.len()],
+            message: None,
+        }
+    }
+
+    pub fn priority(&self, category: usize) -> usize {
+        self.priorities[category]
+    }
+
+    pub fn attribute_budget(&self) -> u8 {
+        ATTRIBUTE_POINTS[self.priority(ATTRIBUTES)]
+    }
+
+    pub fn attribute_points_spent(&self) -> u8 {
+        self.attributes.iter().map(|value| value - 1).sum()
+    }
+
+    pub fn skill_budget(&self) -> u32 {
+        SKILL_POINTS[self.priority(SKILLS)]
+    }
+
+    pub fn skill_points_spent(&self) -> u32 {
+        self.skills.iter().map(|&rating| rating as u32).sum()
+    }
+
+    pub fn nuyen(&self) -> u32 {
+        NUYEN[self.priority(RESOURCES)]
+    }
+
+    pub fn magic_kind(&self) -> MagicType {
+        MAGIC_TYPES[self.magic_type]
+    }
+
+    fn row_count(&self) -> usize {
+        match self.step {
+            WizardStep::Identity => 2,
+            WizardStep::Priorities => PRIORITY_CATEGORIES.len(),
+            WizardStep::Metatype => RACES.len(),
+            WizardStep::Magic => MAGIC_TYPES.len(),
+            WizardStep::Attributes => ATTRIBUTE_NAMES.len(),
+            WizardStep::Skills => SKILL_LIST.len(),
+            WizardStep::Review => 0,
+        }
+    }
+
+    // The character as it would be built right now, with race modifiers applied.
+    pub fn preview(&self) -> CharacterSheet {
+        self.preview_with(&self.attributes)
+    }
+
+    fn preview_with(&self, attributes: &[u8; 9]) -> CharacterSheet {
+        let mut skills = Skills {
+            combat: HashMap::new(),
+            physical: HashMap::new(),
+            social: HashMap::new(),
+            technical: HashMap::new(),
+        };
+        for (&(category, name), &rating) in SKILL_LIST.iter().zip(self.skills.iter()) {
+            if rating == 0 {
+                continue;
+            }
+            let skills = match category {
+                SkillCategory::Combat => &mut skills.combat,
+                SkillCategory::Physical => &mut skills.physical,
+                SkillCategory::Social => &mut skills.social,
+                SkillCategory::Technical => &mut skills.technical,
+            };
+            skills.insert(name.to_string(), rating);
+        }
+
+        let magic_type = self.magic_kind();
+        let rating = magic_type.rating(self.priority(MAGIC)).unwrap_or(0);
+        let mut qualities = Vec::new();
+        if magic_type != MagicType::Mundane {
+            qualities.push(Quality {
+                name: magic_type.name().to_string(),
+                positive: true,
+            });
+        }
+
+        CharacterSheetBuilder::new(
+            self.name.value().trim().to_string(),
+            RACES[self.race].clone(),
+            GENDERS[self.gender].to_string(),
+            String::new(),
+            true,
+        )
+        .body(attributes[0])
+        .agility(attributes[1])
+        .reaction(attributes[2])
+        .strength(attributes[3])
+        .willpower(attributes[4])
+        .logic(attributes[5])
+        .intuition(attributes[6])
+        .charisma(attributes[7])
+        .edge(attributes[8])
+        .magic(if magic_type == MagicType::Technomancer {
+            0
+        } else {
+            rating
+        })
+        .resonance(if magic_type == MagicType::Technomancer {
+            rating
+        } else {
+            0
+        })
+        .skills(skills)
+        .qualities(qualities)
+        .nuyen(self.nuyen())
+        .build()
+    }
+
+    // Final value of every attribute once race modifiers are applied.
+    pub fn final_attributes(&self) -> [u8; 9] {
+        attribute_values(&self.preview())
+    }
+
+    // True if the last point bought in this attribute is lost to the race's maximum.
+    fn is_capped(&self, attribute: usize, attributes: &[u8; 9]) -> bool {
+        if attributes[attribute] <= 1 {
+            return false;
+        }
+        let value = attribute_values(&self.preview_with(attributes))[attribute];
+        let mut lower = *attributes;
+        lower[attribute] -= 1;
+        let mut highest = *attributes;
+        highest[attribute] = MAX_BASE_ATTRIBUTE;
+        // A point that only lifts a racial minimum (human Edge) still counts once it is exceeded.
+        attribute_values(&self.preview_with(&lower))[attribute] == value
+            && attribute_values(&self.preview_with(&highest))[attribute] == value
+    }
+
+    // Everything that stops the character from being finished.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if self.name.value().trim().is_empty() {
+            errors.push("Give your character a name.".to_string());
+        }
+
+        let race = &RACES[self.race];
+        if !race_allowed(race, self.priority(METATYPE)) {
+            errors.push(format!(
+                "{} needs a higher Metatype priority than {}.",
+                race,
+                PRIORITY_LETTERS[self.priority(METATYPE)]
+            ));
+        }
+
+        let magic_type = self.magic_kind();
+        if magic_type.rating(self.priority(MAGIC)).is_none() {
+            errors.push(format!(
+                "{} needs a higher Magic priority than {}.",
+                magic_type.name(),
+                PRIORITY_LETTERS[self.priority(MAGIC)]
+            ));
+        }
+
+        let spent = self.attribute_points_spent();
+        let budget = self.attribute_budget();
+        if spent != budget {
+            errors.push(format!(
+                "Spend exactly {} attribute points ({} spent).",
+                budget, spent
+            ));
+        }
+        for (index, name) in ATTRIBUTE_NAMES.iter().enumerate() {
+            if self.is_capped(index, &self.attributes) {
+                errors.push(format!(
+                    "{} is over the {} maximum; those points are wasted.",
+                    name, race
+                ));
+            }
+        }
+
+        let spent = self.skill_points_spent();
+        let budget = self.skill_budget();
+        if spent > budget {
+            errors.push(format!(
+                "Too many skill points spent ({} of {}).",
+                spent, budget
+            ));
+        }
+        if magic_type != MagicType::Magician {
+            for (&(_, name), &rating) in SKILL_LIST.iter().zip(self.skills.iter()) {
+                if rating > 0 && MAGICIAN_SKILLS.contains(&name) {
+                    errors.push(format!("{} requires the Magician magic type.", name));
+                }
+            }
+        }
+
+        errors
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> WizardAction {
+        self.message = None;
+        match key.code {
+            KeyCode::Esc => return self.previous_step(),
+            KeyCode::Tab => self.next_step(),
+            KeyCode::BackTab => return self.previous_step(),
+            KeyCode::Enter => {
+                if self.step == WizardStep::Review {
+                    let errors = self.validate();
+                    if errors.is_empty() {
+                        return WizardAction::Finish(Box::new(self.preview()));
+                    }
+                    self.message = Some(errors[0].clone());
+                } else {
+                    self.next_step();
+                }
+            }
+            KeyCode::Up => {
+                let rows = self.row_count();
+                if rows > 0 {
+                    self.cursor = (self.cursor + rows - 1) % rows;
+                }
+            }
+            KeyCode::Down => {
+                let rows = self.row_count();
+                if rows > 0 {
+                    self.cursor = (self.cursor + 1) % rows;
+                }
+            }
+            KeyCode::Left => self.adjust(false),
+            KeyCode::Right => self.adjust(true),
+            _ if self.step == WizardStep::Identity && self.cursor == 0 => {
+                self.name.handle_event(&Event::Key(key));
+            }
+            KeyCode::Char(' ') => self.adjust(true),
+            _ => {}
+        }
+        WizardAction::None
+    }
+
+    fn next_step(&mut self) {
+        if let Some(&step) = STEPS.get(self.step.index() + 1) {
+            self.step = step;
+            self.cursor = 0;
+        }
+    }
+
+    fn previous_step(&mut self) -> WizardAction {
+        match self.step.index() {
+            0 => WizardAction::Cancel,
+            index => {
+                self.step = STEPS[index - 1];
+                self.cursor = 0;
+                WizardAction::None
+            }
+        }
+    }
+
+    fn adjust(&mut self, increase: bool) {
+        match self.step {
+            WizardStep::Identity => {
+                if self.cursor == 1 {
+                    self.gender = cycle(self.gender, GENDERS.len(), increase);
+                }
+            }
+            WizardStep::Priorities => {
+                // Swap letters with whichever category holds the target priority.
+                let category = self.cursor;
+                let current = self.priorities[category];
+                let target = cycle(current, PRIORITY_LETTERS.len(), increase);
+                if let Some(other) = self.priorities.iter().position(|&p| p == target) {
+                    self.priorities.swap(category, other);
+                }
+            }
+            WizardStep::Metatype | WizardStep::Magic => {}
+            WizardStep::Attributes => {
+                let index = self.cursor;
+                if increase {
+                    if self.attribute_points_spent() >= self.attribute_budget() {
+                        self.message = Some("No attribute points left.".to_string());
+                        return;
+                    }
+                    if self.attributes[index] >= MAX_BASE_ATTRIBUTE {
+                        return;
+                    }
+                    let mut raised = self.attributes;
+                    raised[index] += 1;
+                    if self.is_capped(index, &raised) {
+                        self.message = Some(format!(
+                            "{} is already at the {} maximum.",
+                            ATTRIBUTE_NAMES[index], RACES[self.race]
+                        ));
+                        return;
+                    }
+                    self.attributes = raised;
+                } else if self.attributes[index] > 1 {
+                    self.attributes[index] -= 1;
+                }
+            }
+            WizardStep::Skills => {
+                let index = self.cursor;
+                if increase {
+                    if self.skill_points_spent() >= self.skill_budget() {
+                        self.message = Some("No skill points left.".to_string());
+                    } else if self.skills[index] < MAX_SKILL_RATING {
+                        self.skills[index] += 1;
+                    }
+                } else {
+                    self.skills[index] = self.skills[index].saturating_sub(1);
+                }
+            }
+            WizardStep::Review => {}
+        }
+
+        // Metatype and magic are picked with the cursor itself.
+        if matches!(self.step, WizardStep::Metatype | WizardStep::Magic) {
+            self.select_row();
+        }
+    }
+
+    // Choose the highlighted metatype or magic type.
+    fn select_row(&mut self) {
+        match self.step {
+            WizardStep::Metatype => {
+                if race_allowed(&RACES[self.cursor], self.priority(METATYPE)) {
+                    self.race = self.cursor;
+                    // Points that the new race's maximums would waste are refunded.
+                    for index in 0..self.attributes.len() {
+                        while self.is_capped(index, &self.attributes) {
+                            self.attributes[index] -= 1;
+                        }
+                    }
+                } else {
+                    self.message = Some(format!(
+                        "{} needs a higher Metatype priority.",
+                        RACES[self.cursor]
+                    ));
+                }
+            }
+            WizardStep::Magic => {
+                if MAGIC_TYPES[self.cursor]
+                    .rating(self.priority(MAGIC))
+                    .is_some()
+                {
+                    self.magic_type = self.cursor;
+                } else {
+                    self.message = Some(format!(
+                        "{} needs a higher Magic priority.",
+                        MAGIC_TYPES[self.cursor].name()
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn cycle(value: usize, count: usize, forward: bool) -> usize {
+    if forward {
+        (value + 1) % count
+    } else {
+        (value + count - 1) % count
+    }
+}
+
+fn attribute_values(sheet: &CharacterSheet) -> [u8; 9] {
+    [
+        sheet.body,
+        sheet.agility,
+        sheet.reaction,
+        sheet.strength,
+        sheet.willpower,
+        sheet.logic,
+        sheet.intuition,
+        sheet.charisma,
+        sheet.edge,
+    ]
+}
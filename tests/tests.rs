@@ -169,7 +169,8 @@ fn test_character_sheet_update_from_json() {
 
     // Step 5: Parse the update arguments
     let update = character::CharacterSheetUpdate::UpdateAttribute {
-        attribute: args["update"]["attribute"].as_str().unwrap().to_string(),
+        attribute: character::AttributeKey::parse(args["update"]["attribute"].as_str().unwrap())
+            .unwrap(),
         operation: match args["update"]["operation"].as_str().unwrap() {
             "Add" => character::UpdateOperation::Add(character::Value::VecQuality(
                 args["update"]["value"]
@@ -201,3 +202,5512 @@ fn test_character_sheet_update_from_json() {
 
     println!("Updated Character Sheet: {:?}", character_sheet);
 }
+
+#[tokio::test]
+async fn test_spectator_stream_replays_transcript() {
+    use sharad_ratatui::spectator::SpectatorServer;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let spectator = SpectatorServer::start("127.0.0.1:0".parse().unwrap(), false)
+        .expect("Failed to start spectator server");
+    spectator.publish_message(&Message::new(
+        MessageType::System,
+        "Welcome, chummer".to_string(),
+    ));
+
+    let mut stream = tokio::net::TcpStream::connect(spectator.address)
+        .await
+        .expect("Failed to connect to spectator server");
+    stream
+        .write_all(b"GET /events HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .await
+        .unwrap();
+
+    // Read until the replayed message shows up.
+    let mut received = String::new();
+    let mut buffer = [0u8; 1024];
+    while !received.contains("Welcome, chummer") {
+        let read =
+            tokio::time::timeout(std::time::Duration::from_secs(5), stream.read(&mut buffer))
+                .await
+                .expect("Timed out waiting for spectator events")
+                .unwrap();
+        assert!(read > 0, "Spectator stream closed early");
+        received.push_str(&String::from_utf8_lossy(&buffer[..read]));
+    }
+
+    assert!(received.starts_with("HTTP/1.1 200 OK"));
+    assert!(received.contains("text/event-stream"));
+    assert!(received.contains(r#""kind":"reset""#));
+
+    // A load resets the transcript and then adds to it; the page gets each message once, whether
+    // it arrived in the new snapshot or after it.
+    spectator.reset(
+        &[Message::new(MessageType::System, "Game loaded".to_string())],
+        None,
+    );
+    for text in ["Back in the shadows", "The end"] {
+        spectator.publish_message(&Message::new(MessageType::System, text.to_string()));
+    }
+    received.clear();
+    while !received.contains("The end") {
+        let read =
+            tokio::time::timeout(std::time::Duration::from_secs(5), stream.read(&mut buffer))
+                .await
+                .expect("Timed out waiting for spectator events")
+                .unwrap();
+        assert!(read > 0, "Spectator stream closed early");
+        received.push_str(&String::from_utf8_lossy(&buffer[..read]));
+    }
+    assert_eq!(received.matches("Back in the shadows").count(), 1);
+    assert_eq!(received.matches("Game loaded").count(), 1);
+}
+
+#[test]
+fn test_character_sheet_html_export() {
+    let json_str = fs::read_to_string("tests/dummy_create_character_sheet.json")
+        .expect("Failed to read dummy create character JSON file");
+    let json_value: serde_json::Value =
+        serde_json::from_str(&json_str).expect("Failed to parse JSON");
+    let mut character_sheet = create_character_from_args(&json_value["function"]["arguments"]);
+    character_sheet.backstory = "Grew up on <Redmond> streets & survived".to_string();
+
+    let html = sharad_ratatui::export::character_sheet_html(&character_sheet);
+
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("Alex &#39;Raven&#39; Hayes"));
+    assert!(html.contains("AGILITY: 5"));
+    assert!(html.contains("Pistols:4"));
+    // User and AI provided text must be escaped.
+    assert!(html.contains("Grew up on &lt;Redmond&gt; streets &amp; survived"));
+    assert!(!html.contains("<Redmond>"));
+}
+
+#[test]
+fn test_character_sheet_markdown_export() {
+    let json_str = fs::read_to_string("tests/dummy_create_character_sheet.json")
+        .expect("Failed to read dummy create character JSON file");
+    let json_value: serde_json::Value =
+        serde_json::from_str(&json_str).expect("Failed to parse JSON");
+    let mut character_sheet = create_character_from_args(&json_value["function"]["arguments"]);
+    character_sheet.backstory = "Grew up on the streets".to_string();
+    character_sheet
+        .fields
+        .insert("Note".to_string(), "owes | the fixer\nmoney".to_string());
+
+    let markdown = sharad_ratatui::export::character_sheet_markdown(&character_sheet);
+
+    assert!(markdown.starts_with("# Alex 'Raven' Hayes\n"));
+    assert!(markdown.contains("## Skills"));
+    assert!(markdown.contains("## Backstory\n\nGrew up on the streets"));
+    // Cells stay on one line and keep the columns.
+    assert!(markdown.contains("owes \\| the fixer money"));
+    let skills: Vec<&str> = markdown
+        .split("## Skills\n\n")
+        .nth(1)
+        .unwrap()
+        .lines()
+        .take_while(|line| line.starts_with('|'))
+        .collect();
+    assert!(skills.iter().any(|line| line.contains("| Pistols ")));
+    let width = skills[0].chars().count();
+    assert!(skills.iter().all(|line| line.chars().count() == width));
+}
+
+#[test]
+fn test_archetypes_build_valid_sheets() {
+    use sharad_ratatui::archetype::ARCHETYPES;
+
+    for archetype in ARCHETYPES {
+        let sheet = archetype.builder().build();
+        assert!(sheet.main, "{} should be a main character", archetype.name);
+        assert!(
+            sheet.physical_monitor > 8,
+            "{} has no derived attributes",
+            archetype.name
+        );
+        assert!(
+            !sheet.inventory.is_empty(),
+            "{} starts without gear",
+            archetype.name
+        );
+    }
+}
+
+#[test]
+fn test_character_wizard_validates_priorities_and_race_maximums() {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use sharad_ratatui::wizard::{CharacterWizard, WizardAction, WizardStep};
+
+    let press = |wizard: &mut CharacterWizard, code: KeyCode| {
+        wizard.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    };
+
+    let mut wizard = CharacterWizard::new();
+    for c in "Kit".chars() {
+        press(&mut wizard, KeyCode::Char(c));
+    }
+    assert_eq!(wizard.name.value(), "Kit");
+    // Default priorities give Attributes A: nothing spent yet, so the character is incomplete.
+    assert_eq!(wizard.attribute_budget(), 24);
+    assert!(!wizard.validate().is_empty());
+
+    wizard.attributes = [6, 6, 5, 3, 2, 3, 4, 2, 2];
+    assert_eq!(wizard.attribute_points_spent(), 24);
+    assert!(wizard.validate().is_empty(), "{:?}", wizard.validate());
+
+    // More points than the budget allows are refused.
+    wizard.step = WizardStep::Attributes;
+    wizard.cursor = 4;
+    press(&mut wizard, KeyCode::Right);
+    assert_eq!(wizard.attributes[4], 2);
+    assert!(wizard.message.is_some());
+
+    // Dwarves need Metatype C or better, and cap Agility at 5.
+    wizard.step = WizardStep::Metatype;
+    wizard.cursor = 2;
+    press(&mut wizard, KeyCode::Right);
+    assert_eq!(wizard.race, 0, "Dwarf is not available at Metatype E");
+    wizard.priorities = [2, 0, 3, 1, 4];
+    press(&mut wizard, KeyCode::Right);
+    assert_eq!(wizard.race, 2);
+    assert_eq!(
+        wizard.attributes[1], 5,
+        "wasted Agility points are refunded"
+    );
+    assert_eq!(
+        wizard.final_attributes()[0],
+        8,
+        "Dwarf body modifier applied"
+    );
+
+    wizard.step = WizardStep::Review;
+    let errors = wizard.validate();
+    assert!(
+        errors.iter().any(|e| e.contains("attribute points")),
+        "{:?}",
+        errors
+    );
+    assert!(matches!(
+        press(&mut wizard, KeyCode::Enter),
+        WizardAction::None
+    ));
+
+    wizard.attributes[4] = 3;
+    match press(&mut wizard, KeyCode::Enter) {
+        WizardAction::Finish(sheet) => {
+            assert_eq!(sheet.name, "Kit");
+            assert_eq!(sheet.agility, 5);
+            assert_eq!(sheet.nuyen, 6_000);
+        }
+        _ => panic!("a valid character should finish the wizard"),
+    }
+}
+
+#[test]
+fn test_generated_npcs_are_plausible() {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use sharad_ratatui::character::{generate_npc, NpcRole, NPC_ROLES};
+
+    assert_eq!(NpcRole::parse("corp security"), Some(NpcRole::CorpSecurity));
+    assert_eq!(NpcRole::parse("Street_Mage"), Some(NpcRole::StreetMage));
+    assert_eq!(NpcRole::parse("dragon"), None);
+
+    let mut rng = StdRng::seed_from_u64(3336);
+    for role in NPC_ROLES {
+        for _ in 0..20 {
+            let npc = generate_npc(role, &mut rng);
+            assert!(!npc.main, "{} should not be a main character", npc.name);
+            assert!(!npc.name.is_empty());
+            assert!(npc.body >= 1 && npc.agility >= 1 && npc.charisma >= 1);
+            assert!(!npc.inventory.is_empty(), "{:?} has no gear", role);
+            assert!(npc
+                .get_all_active_skills()
+                .values()
+                .all(|rating| (1..=6).contains(rating)));
+            if role == NpcRole::StreetMage {
+                assert!(npc.magic.unwrap_or(0) >= 3);
+            } else {
+                assert_eq!(npc.magic, Some(0));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_cli_flags() {
+    use sharad_ratatui::cli::{parse, CliAction, CliArgs};
+    use std::path::PathBuf;
+
+    let args = |args: &[&str]| parse(args.iter().map(|arg| arg.to_string()));
+
+    assert_eq!(args(&[]), Ok(CliAction::Run(CliArgs::default())));
+    assert_eq!(args(&["--help", "--bogus"]), Ok(CliAction::Help));
+    assert_eq!(
+        args(&[
+            "--new",
+            "Seattle Run",
+            "--no-update",
+            "--config=/tmp/sharad",
+            "--portable",
+            "--log-level",
+            "debug",
+            "--mirror",
+            "-",
+            "--headless",
+            "script.txt",
+        ]),
+        Ok(CliAction::Run(CliArgs {
+            load: None,
+            new: Some("Seattle Run".to_string()),
+            no_update: true,
+            config_dir: Some(PathBuf::from("/tmp/sharad")),
+            portable: true,
+            log_level: Some(log::LevelFilter::Debug),
+            mirror: Some("-".to_string()),
+            headless: Some(PathBuf::from("script.txt")),
+            mock: false,
+            bench_ui: None,
+        }))
+    );
+    assert_eq!(
+        args(&["--bench-ui=5"]),
+        Ok(CliAction::Run(CliArgs {
+            bench_ui: Some(5),
+            ..CliArgs::default()
+        }))
+    );
+
+    assert!(args(&["--load"]).is_err());
+    assert!(args(&["--log-level", "loud"]).is_err());
+    assert!(args(&["--load", "a", "--new", "b"]).is_err());
+    assert!(args(&["--headless", "script.txt"]).is_err());
+    assert!(args(&["--headless", "script.txt", "--mock"]).is_ok());
+    assert!(args(&["--mock"]).is_err());
+    assert!(args(&["--bench-ui=many"]).is_err());
+    assert!(args(&["--bogus"]).is_err());
+}
+
+#[tokio::test]
+async fn test_headless_mock_run_applies_tool_calls() {
+    use sharad_ratatui::headless::run_mock;
+    use sharad_ratatui::spectator::SpectatorEvent;
+
+    let json_str = fs::read_to_string("tests/dummy_create_character_sheet.json")
+        .expect("Failed to read dummy create character JSON file");
+    let json_value: serde_json::Value =
+        serde_json::from_str(&json_str).expect("Failed to parse JSON");
+    let create_args = json_value["function"]["arguments"].to_string();
+
+    let script = format!(
+        r#"# Character creation, then a skill bump and a dice roll.
+@tool create_character_sheet {}
+@reply Welcome to the shadows, Raven.
+I want to play a street samurai
+
+@tool update_skills {{"character_name": "Alex 'Raven' Hayes", "updates": {{"skills": {{"combat": [{{"name": "Blades", "rating": 6}}]}}}}}}
+@tool perform_dice_roll {{"character_name": "Alex 'Raven' Hayes", "attribute": "agility", "skill": "Blades", "limit_type": "physical"}}
+I slash at the guard
+@tool no_such_tool {{}}
+I look around
+"#,
+        create_args
+    );
+
+    let report = run_mock(&script, "regression").await.unwrap();
+    let game_state = report.game_state.expect("mock run keeps a game state");
+
+    let sheet = game_state
+        .main_character_sheet
+        .as_ref()
+        .expect("create_character_sheet sets the main character");
+    assert_eq!(sheet.name, "Alex 'Raven' Hayes");
+    assert_eq!(sheet.skills.combat.get("Blades"), Some(&6));
+    assert_eq!(game_state.save_name, "regression");
+
+    let texts: Vec<(String, String)> = report
+        .transcript
+        .iter()
+        .map(|event| match event {
+            SpectatorEvent::Message {
+                message_type, text, ..
+            } => (format!("{:?}", message_type), text.clone()),
+            _ => panic!("unexpected transcript event"),
+        })
+        .collect();
+    assert_eq!(
+        texts[0],
+        ("User".into(), "I want to play a street samurai".into())
+    );
+    assert!(texts[1].1.starts_with("create_character_sheet: "));
+    assert_eq!(
+        texts[2],
+        ("Game".into(), "Welcome to the shadows, Raven.".into())
+    );
+    assert!(texts
+        .iter()
+        .any(|(kind, text)| kind == "System" && text.starts_with("perform_dice_roll: {\"hits\"")));
+    assert!(texts
+        .iter()
+        .any(|(_, text)| text.starts_with("no_such_tool: error:")));
+    assert_eq!(
+        texts.last().unwrap(),
+        &("Game".to_string(), "[mock] I look around".to_string())
+    );
+}
+
+#[test]
+fn test_update_version_comparison() {
+    use sharad_ratatui::update::is_newer;
+
+    assert!(is_newer("v0.1.33", "0.1.32"));
+    assert!(is_newer("0.2.0", "0.1.32"));
+    assert!(is_newer("1.0", "0.9.9"));
+    assert!(!is_newer("v0.1.32", "0.1.32"));
+    assert!(!is_newer("0.1.32-beta", "0.1.32"));
+    assert!(!is_newer("0.1.4", "0.1.32"));
+}
+
+#[test]
+fn test_data_directories_portable_mode_and_legacy_migration() {
+    use sharad_ratatui::paths::{default_dirs, migrate_legacy_data, portable_dir, PORTABLE_MARKER};
+    use std::path::PathBuf;
+
+    if cfg!(target_os = "linux") {
+        let env = |name: &str| match name {
+            "HOME" => Some("/home/runner".to_string()),
+            "XDG_DATA_HOME" => Some("/srv/data".to_string()),
+            "XDG_CONFIG_HOME" => Some(String::new()),
+            _ => None,
+        };
+        let (config, data) = default_dirs(env);
+        assert_eq!(config, PathBuf::from("/home/runner/.config/sharad"));
+        assert_eq!(data, PathBuf::from("/srv/data/sharad"));
+    }
+
+    let root = std::env::temp_dir().join(format!("sharad_paths_{}", std::process::id()));
+    let legacy = root.join("data");
+    let data = root.join("share");
+    fs::create_dir_all(legacy.join("save")).unwrap();
+    fs::write(legacy.join("save").join("run.json"), "{}").unwrap();
+    fs::write(legacy.join("portrait.png"), "png").unwrap();
+    fs::create_dir_all(data.join("exports")).unwrap();
+    fs::write(data.join("exports").join("kept.html"), "new").unwrap();
+    fs::create_dir_all(legacy.join("exports")).unwrap();
+    fs::write(legacy.join("exports").join("kept.html"), "old").unwrap();
+
+    assert_eq!(migrate_legacy_data(&legacy, &data).unwrap(), 2);
+    assert!(data.join("save").join("run.json").exists());
+    assert!(data.join("images").join("portrait.png").exists());
+    // Existing data is never overwritten, so the legacy folder stays for the player to sort out.
+    assert_eq!(
+        fs::read_to_string(data.join("exports").join("kept.html")).unwrap(),
+        "new"
+    );
+    assert!(legacy.join("exports").exists());
+
+    fs::remove_dir_all(legacy.join("exports")).unwrap();
+    assert_eq!(migrate_legacy_data(&legacy, &data).unwrap(), 0);
+    assert!(!legacy.exists());
+
+    assert_eq!(portable_dir(&root, false), None);
+    assert_eq!(portable_dir(&root, true), Some(root.join("sharad_data")));
+    fs::write(root.join(PORTABLE_MARKER), "").unwrap();
+    assert_eq!(portable_dir(&root, false), Some(root.join("sharad_data")));
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_log_viewer_filters_by_level_and_search() {
+    use log::{Level, LevelFilter};
+    use sharad_ratatui::log_viewer::{filter_lines, parse_lines};
+
+    let lines = parse_lines(
+        "[2024-05-01 10:00:00] INFO  sharad_ratatui::app: Loaded save Seattle\n\
+         [2024-05-01 10:00:01] ERROR sharad_ratatui::ai: Run failed\n\
+         caused by: timeout\n\
+         [2024-05-01 10:00:02] DEBUG sharad_ratatui::app: Tool call update_basic_attributes",
+    );
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[0].level, Some(Level::Info));
+    assert_eq!(lines[2].level, Some(Level::Error));
+
+    assert_eq!(filter_lines(&lines, LevelFilter::Trace, "").len(), 4);
+    assert_eq!(filter_lines(&lines, LevelFilter::Info, "").len(), 3);
+    let errors = filter_lines(&lines, LevelFilter::Error, "");
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[1].text, "caused by: timeout");
+    assert_eq!(filter_lines(&lines, LevelFilter::Trace, "SEATTLE").len(), 1);
+    assert!(filter_lines(&lines, LevelFilter::Warn, "seattle").is_empty());
+}
+
+#[test]
+fn test_log_level_setting() {
+    use sharad_ratatui::settings::{LogLevel, Settings, LOG_LEVELS};
+    use sharad_ratatui::settings_state::{SettingsState, LOG_LEVEL, SETTINGS_ITEMS};
+
+    // Settings saved before the option existed keep the old default.
+    let old: Settings = serde_json::from_str(
+        r#"{"language":"English","openai_api_key":null,"audio_output_enabled":true,
+            "audio_input_enabled":true,"debug_mode":false}"#,
+    )
+    .unwrap();
+    assert_eq!(old.log_level, LogLevel::Warn);
+
+    let settings = Settings {
+        log_level: LogLevel::Debug,
+        ..Settings::default()
+    };
+    let mut state = SettingsState::from_settings(&settings);
+    assert_eq!(state.selected_options.len(), SETTINGS_ITEMS.len());
+    assert_eq!(
+        LOG_LEVELS[state.selected_options[LOG_LEVEL]].filter(),
+        log::LevelFilter::Debug
+    );
+    state.cycle_option(LOG_LEVEL, true);
+    assert_eq!(
+        LOG_LEVELS[state.selected_options[LOG_LEVEL]],
+        LogLevel::Trace
+    );
+}
+
+#[test]
+fn test_emergency_save_files_are_kept_apart_from_saves() {
+    use sharad_ratatui::emergency::{self, is_emergency_file, path_for};
+
+    // Nothing is written while no game is being played.
+    emergency::untrack();
+    assert!(emergency::write("test").is_none());
+
+    let path = path_for("Seattle Run");
+    let file_name = path.file_name().unwrap().to_str().unwrap();
+    assert_eq!(file_name, "Seattle Run.emergency.json");
+    assert!(is_emergency_file(file_name));
+    assert!(!is_emergency_file("Seattle Run.json"));
+}
+
+#[tokio::test]
+async fn test_status_is_shared_between_tasks() {
+    use sharad_ratatui::status::{Narration, RunActivity, Shared};
+
+    let activity = Shared::new(RunActivity::Idle);
+    let writer = activity.clone();
+    tokio::spawn(async move { writer.set(RunActivity::ToolCall("perform_dice_roll".to_string())) })
+        .await
+        .unwrap();
+    assert_eq!(activity.get().label(), "Running tools: dice roll");
+    assert_eq!(
+        RunActivity::ToolCall("update_skills".to_string())
+            .label_with_elapsed(std::time::Duration::from_millis(12_400)),
+        "Running tools: sheet update · 12s"
+    );
+
+    let ai = ai::GameAI::new(String::new(), |_| {}).await.unwrap();
+    assert_eq!(ai.activity.get(), RunActivity::Idle);
+    assert_eq!(
+        Narration::Playing {
+            message: 0,
+            line: 0
+        }
+        .label(),
+        "Playing"
+    );
+}
+
+#[test]
+fn test_slash_command_parsing_and_registry() {
+    use sharad_ratatui::commands::{
+        describe_roll, parse_input, CommandRegistry, ParsedInput, SlashCommand,
+    };
+    use sharad_ratatui::dice::{dice_roll, ShownRoll};
+
+    assert_eq!(
+        parse_input("  /roll 12 "),
+        ParsedInput::Command {
+            name: "roll",
+            args: "12"
+        }
+    );
+    assert_eq!(
+        parse_input("/image a neon alley in the rain"),
+        ParsedInput::Command {
+            name: "image",
+            args: "a neon alley in the rain"
+        }
+    );
+    assert_eq!(
+        parse_input("I draw my gun"),
+        ParsedInput::Text("I draw my gun")
+    );
+    assert_eq!(parse_input("//shrug"), ParsedInput::Text("/shrug"));
+    assert_eq!(parse_input("/"), ParsedInput::Text("/"));
+
+    let mut registry = CommandRegistry::with_builtins();
+    for name in ["roll", "sheet", "save", "recap", "image", "help"] {
+        assert!(registry.find(name, false).is_some(), "missing /{}", name);
+    }
+    assert!(registry.find("ROLL", false).is_some());
+    assert!(registry.find("npc", false).is_none());
+    assert!(registry.find("npc", true).is_some());
+
+    let builtins = registry.available(true).count();
+    registry.register(SlashCommand {
+        name: "roll",
+        usage: "/roll",
+        description: "Replaced",
+        debug_only: false,
+        run: |_, _| {},
+    });
+    assert_eq!(registry.available(true).count(), builtins);
+    assert_eq!(
+        registry.find("roll", false).unwrap().description,
+        "Replaced"
+    );
+
+    let roll = ShownRoll::new("Rolled 6 dice".to_string(), &dice_roll(6, None, None, None));
+    assert!(describe_roll(&roll).starts_with("Rolled 6 dice: "));
+}
+
+#[test]
+fn test_macros_expand_whole_words_only() {
+    use sharad_ratatui::macros::{is_valid_name, Macros};
+
+    let mut macros = Macros::default();
+    macros.macros.insert(
+        "overwatch".to_string(),
+        "I take cover on the roof and watch the street through my scope.".to_string(),
+    );
+    macros
+        .macros
+        .insert("hi".to_string(), "Hoi, chummer".to_string());
+
+    assert_eq!(
+        macros.expand("!overwatch"),
+        "I take cover on the roof and watch the street through my scope."
+    );
+    assert_eq!(macros.expand("!hi! How's biz?"), "Hoi, chummer! How's biz?");
+    assert_eq!(macros.expand("Wait, !hi"), "Wait, Hoi, chummer");
+    // Not at the start of a word, unknown, or a bare prefix: left alone.
+    assert_eq!(macros.expand("Boom!hi"), "Boom!hi");
+    assert_eq!(macros.expand("!unknown !"), "!unknown !");
+    assert_eq!(macros.expand("日本 !hi"), "日本 Hoi, chummer");
+
+    assert!(is_valid_name("street-sam_2"));
+    assert!(!is_valid_name("two words"));
+    assert!(!is_valid_name(""));
+}
+
+#[test]
+fn test_menu_labels_follow_the_language_setting() {
+    use sharad_ratatui::i18n::{tr, trf, Lang, LANGUAGES};
+    use sharad_ratatui::settings::Settings;
+    use sharad_ratatui::settings_state::{SettingsState, LANGUAGE, SETTINGS_ITEMS};
+
+    assert_eq!(tr("English", "Settings"), "Settings");
+    assert_eq!(tr("Français", "Settings"), "Paramètres");
+    assert_eq!(tr("日本語", "Settings"), "設定");
+    assert_eq!(tr("Türkçe", "Settings"), "Ayarlar");
+    // Unknown languages and untranslated text fall back to English.
+    assert_eq!(tr("Klingon", "Load a game"), "Load a game");
+    assert_eq!(tr("Français", "Not in the table"), "Not in the table");
+    assert_eq!(
+        trf("Français", "No save files found in {}", &["./saves"]),
+        "Aucune sauvegarde trouvée dans ./saves"
+    );
+    assert_eq!(
+        trf("日本語", "No save files found in {}", &["./saves"]),
+        "./savesにセーブファイルがありません"
+    );
+
+    // Every Language option maps to a language, and round-trips through the settings menu.
+    let languages: Vec<&str> = LANGUAGES.iter().map(|(name, _)| *name).collect();
+    assert_eq!(SETTINGS_ITEMS[LANGUAGE].1, languages.as_slice());
+    assert_eq!(Lang::from_name("Türkçe"), Lang::Turkish);
+    let settings = Settings {
+        language: "Türkçe".to_string(),
+        ..Settings::default()
+    };
+    assert_eq!(
+        SettingsState::from_settings(&settings).selected_options[LANGUAGE],
+        3
+    );
+}
+
+// The string literals passed as the text of a tr or trf call in `source`.
+fn translated_literals(source: &str) -> Vec<String> {
+    let mut literals = Vec::new();
+    for (start, call) in source.match_indices("tr") {
+        let before = source[..start].chars().next_back();
+        if before.is_some_and(|c| c.is_alphanumeric() || c == '_') {
+            continue;
+        }
+        let rest = &source[start + call.len()..];
+        let Some(rest) = rest.strip_prefix("f(").or_else(|| rest.strip_prefix('(')) else {
+            continue;
+        };
+        // Past the language, to the text.
+        let mut depth = 0;
+        let Some(comma) = rest.find(|c| {
+            match c {
+                '(' => depth += 1,
+                ')' if depth == 0 => return true,
+                ')' => depth -= 1,
+                ',' if depth == 0 => return true,
+                _ => {}
+            }
+            false
+        }) else {
+            continue;
+        };
+        let Some(text) = rest[comma..]
+            .strip_prefix(',')
+            .and_then(|text| text.trim_start().strip_prefix('"'))
+        else {
+            continue;
+        };
+        let mut literal = String::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => break,
+                '\\' => match chars.next() {
+                    Some('n') => literal.push('\n'),
+                    Some(escaped) => literal.push(escaped),
+                    None => break,
+                },
+                c => literal.push(c),
+            }
+        }
+        literals.push(literal);
+    }
+    literals
+}
+
+#[test]
+fn test_every_translated_text_has_translations() {
+    use sharad_ratatui::i18n::is_translated;
+    use sharad_ratatui::settings_state::SETTINGS_ITEMS;
+
+    fn sources(dir: &std::path::Path, found: &mut Vec<std::path::PathBuf>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                sources(&path, found);
+            } else if path.extension().is_some_and(|extension| extension == "rs") {
+                found.push(path);
+            }
+        }
+    }
+    let mut files = Vec::new();
+    sources(std::path::Path::new("src"), &mut files);
+
+    let mut texts: Vec<String> = SETTINGS_ITEMS
+        .iter()
+        .map(|(label, _)| label.to_string())
+        .collect();
+    for file in &files {
+        texts.extend(translated_literals(&fs::read_to_string(file).unwrap()));
+    }
+    assert!(texts.iter().any(|text| text == "No save files found in {}"));
+    let missing: Vec<&String> = texts.iter().filter(|text| !is_translated(text)).collect();
+    assert!(missing.is_empty(), "No translations for {:?}", missing);
+}
+
+#[test]
+fn test_wide_characters_wrap_and_truncate_by_cell_width() {
+    use sharad_ratatui::ui::game::skills_line;
+    use sharad_ratatui::ui::utils::{truncate_to_width, wrap_to_width};
+    use unicode_width::UnicodeWidthStr;
+
+    // No spaces to break on, and every character is two cells wide.
+    let fluff = "東京の夜は雨に濡れたネオンが輝いている。君はバーの隅で情報屋を待っている。";
+    let lines = wrap_to_width(fluff, 20);
+    assert!(lines.len() > 1);
+    assert!(lines.iter().all(|line| line.width() <= 20));
+    assert_eq!(lines.concat(), fluff);
+
+    assert_eq!(truncate_to_width("ハッキング", 6), "ハッ…");
+    assert_eq!(truncate_to_width("ハッキング", 10), "ハッキング");
+    assert_eq!(truncate_to_width("Hacking", 5), "Hack…");
+
+    let mut skills = HashMap::new();
+    skills.insert("銃器".to_string(), 5);
+    skills.insert("ハッキング".to_string(), 6);
+    skills.insert("隠密行動".to_string(), 4);
+    let line = skills_line(&skills, 32);
+    assert!(line.width() <= 32);
+    assert!(line.contains("ハッキング:6"));
+    // Too narrow for everything: names are shortened, ratings stay.
+    let line = skills_line(&skills, 24);
+    assert!(line.width() <= 24);
+    assert!(line.contains(":6") && line.contains(":5") && line.contains(":4"));
+}
+
+#[test]
+fn test_transcript_mirror_writes_plain_labelled_text() {
+    use sharad_ratatui::message::{Fluff, GameMessage, Message, MessageType};
+    use sharad_ratatui::mirror::{self, MirrorTarget};
+
+    assert_eq!(MirrorTarget::from_arg("-"), MirrorTarget::Stdout);
+    assert_eq!(
+        MirrorTarget::from_arg("out.txt"),
+        MirrorTarget::File(std::path::PathBuf::from("out.txt"))
+    );
+
+    let game = GameMessage::new(
+        "**Hits:** 3".to_string(),
+        Fluff {
+            speakers: vec![],
+            dialogue: vec![],
+        },
+        None,
+    );
+    let game = Message::new(MessageType::Game, serde_json::to_string(&game).unwrap());
+    assert_eq!(mirror::plain_text(&game), "Game Master:\nHits: 3");
+    let system = Message::new(MessageType::System, "Game saved.".to_string());
+    assert_eq!(mirror::plain_text(&system), "System: Game saved.");
+
+    let dir = std::env::temp_dir().join(format!("sharad_mirror_{}", std::process::id()));
+    let file = dir.join("transcript.txt");
+    mirror::configure(Some(MirrorTarget::File(file.clone()))).unwrap();
+    mirror::publish(&system);
+    mirror::configure(None).unwrap();
+    mirror::publish(&game);
+    assert_eq!(
+        fs::read_to_string(&file).unwrap(),
+        "System: Game saved.\n\n"
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_discord_webhook_posts_split_messages_and_backs_off() {
+    use sharad_ratatui::dice::DiceRollResponse;
+    use sharad_ratatui::message::{Fluff, FluffLine, GameMessage, Gender, Speaker};
+    use sharad_ratatui::webhook::{self, Payload, WebhookEvent};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // Long text is cut at 2000 characters, not bytes, and never inside one.
+    let text = "ネオ東京".repeat(600);
+    let chunks = webhook::split_content(&text);
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(chunks[0].chars().count(), 2000);
+    assert_eq!(chunks.concat(), text);
+    // Line breaks are preferred as cut points.
+    let lines = format!("{}\n{}", "a".repeat(1500), "b".repeat(1500));
+    let chunks = webhook::split_content(&lines);
+    assert_eq!(
+        chunks,
+        [format!("{}\n", "a".repeat(1500)), "b".repeat(1500)]
+    );
+
+    let line = |speaker_index, text: &str| FluffLine {
+        speaker_index,
+        text: text.to_string(),
+        audio: None,
+    };
+    let speaker = |index, name: &str| Speaker {
+        index,
+        name: name.to_string(),
+        gender: Gender::NonBinary,
+        voice: None,
+    };
+    let game = GameMessage::new(
+        "**Hits:** 3".to_string(),
+        Fluff {
+            speakers: vec![speaker(0, "Narrator"), speaker(1, "Raven")],
+            dialogue: vec![line(0, "The door gives."), line(1, "We're in.")],
+        },
+        None,
+    );
+    assert_eq!(
+        webhook::payloads(WebhookEvent::GameMessage(Box::new(game))),
+        [Payload::Text(
+            "The door gives.\n\n**Raven:** We're in.".to_string()
+        )]
+    );
+    let roll = WebhookEvent::DiceRoll {
+        summary: "Raven picks the lock".to_string(),
+        response: DiceRollResponse {
+            hits: 0,
+            glitch: true,
+            critical_glitch: false,
+            critical_success: false,
+            dice_results: vec![1, 1, 4],
+            success: false,
+        },
+    };
+    assert_eq!(
+        webhook::payloads(roll),
+        [Payload::Text(
+            "🎲 **Raven picks the lock**: 0 hits [1, 1, 4] - failure, glitch".to_string()
+        )]
+    );
+    let image = std::path::PathBuf::from("scene.png");
+    assert_eq!(
+        webhook::payloads(WebhookEvent::Image(image.clone())),
+        [Payload::File(image)]
+    );
+
+    // A 429 is retried after the retry_after Discord sends with it.
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/webhook", listener.local_addr().unwrap());
+    let server = tokio::spawn(async move {
+        let mut bodies = Vec::new();
+        for (status, body) in [
+            ("429 Too Many Requests", r#"{"retry_after":0.3}"#),
+            ("204 No Content", ""),
+        ] {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 1024];
+            // The head, then as much body as it says there is.
+            let body_start = loop {
+                let read = stream.read(&mut buffer).await.unwrap();
+                assert!(read > 0, "Webhook request cut short");
+                request.extend_from_slice(&buffer[..read]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                let Some(end) = text.find("\r\n\r\n") else {
+                    continue;
+                };
+                let length = text[..end]
+                    .lines()
+                    .find_map(|line| {
+                        let line = line.to_ascii_lowercase();
+                        Some(
+                            line.strip_prefix("content-length:")?
+                                .trim()
+                                .parse()
+                                .unwrap(),
+                        )
+                    })
+                    .unwrap_or(0);
+                if request.len() >= end + 4 + length {
+                    break end + 4;
+                }
+            };
+            bodies.push(String::from_utf8_lossy(&request[body_start..]).to_string());
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        }
+        bodies
+    });
+    let started = std::time::Instant::now();
+    let payload = Payload::Text("Hoi, chummer".to_string());
+    webhook::send_payload(&reqwest::Client::new(), &url, &payload)
+        .await
+        .unwrap();
+    assert!(started.elapsed() >= std::time::Duration::from_millis(300));
+    let bodies = server.await.unwrap();
+    assert_eq!(bodies.len(), 2);
+    assert!(bodies
+        .iter()
+        .all(|body| body == r#"{"content":"Hoi, chummer"}"#));
+}
+
+#[test]
+fn test_themes_keep_meaning_apart() {
+    use ratatui::style::{Color, Modifier};
+    use sharad_ratatui::settings::{Settings, Theme, THEMES};
+    use sharad_ratatui::settings_state::{SettingsState, THEME};
+    use sharad_ratatui::ui::theme::{self, Palette};
+
+    for theme in THEMES {
+        let palette = Palette::of(theme);
+        assert_ne!(palette.success, palette.failure);
+        assert_ne!(palette.positive, palette.negative);
+        // The selected option is marked by more than its colour.
+        assert!(palette
+            .selected()
+            .add_modifier
+            .contains(Modifier::UNDERLINED));
+    }
+    let colorblind = Palette::of(Theme::Colorblind);
+    for color in [
+        colorblind.success,
+        colorblind.failure,
+        colorblind.positive,
+        colorblind.negative,
+    ] {
+        assert!(![Color::Red, Color::Green, Color::LightRed, Color::LightGreen].contains(&color));
+    }
+
+    theme::set(Theme::HighContrast);
+    assert_eq!(theme::palette(), Palette::of(Theme::HighContrast));
+    theme::set(Theme::Default);
+
+    let settings = Settings {
+        theme: Theme::Colorblind,
+        ..Settings::default()
+    };
+    assert_eq!(
+        SettingsState::from_settings(&settings).selected_options[THEME],
+        1
+    );
+}
+
+#[test]
+fn test_transcript_cache_parses_only_what_changed() {
+    use sharad_ratatui::message::{Message, MessageType};
+    use sharad_ratatui::transcript::TranscriptCache;
+
+    let system = |text: &str| Message::new(MessageType::System, text.to_string());
+    let line_text = |cache: &TranscriptCache, start: usize, count: usize| -> Vec<String> {
+        cache
+            .lines(start, count)
+            .iter()
+            .map(|line| line.to_string())
+            .collect()
+    };
+
+    let mut messages = vec![system("Welcome to Seattle."), system("Thinking...")];
+    let mut cache = TranscriptCache::new();
+    cache.update(&messages, 40, false);
+    assert_eq!(cache.len(), 2);
+
+    // The last message is swapped for the answer, then the player speaks.
+    messages.pop();
+    messages.push(system("The rain never stops in the Barrens."));
+    messages.push(system("I check my commlink."));
+    cache.update(&messages, 40, false);
+    assert_eq!(
+        line_text(&cache, 1, 5),
+        [
+            "The rain never stops in the Barrens.",
+            "I check my commlink."
+        ]
+    );
+
+    // Narrower: everything is wrapped again.
+    cache.update(&messages, 20, false);
+    assert_eq!(cache.len(), 4);
+    assert_eq!(
+        line_text(&cache, 1, 2),
+        ["The rain never stops", "in the Barrens."]
+    );
+
+    messages.clear();
+    cache.update(&messages, 20, false);
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_save_journal_appends_changes_and_compacts() {
+    use sharad_ratatui::archetype::ARCHETYPES;
+    use sharad_ratatui::journal::{self, Journal, COMPACT_AFTER};
+
+    let dir = std::env::temp_dir().join(format!("sharad_journal_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let sheet = ARCHETYPES[0].builder().build();
+    let mut state = GameState {
+        assistant_id: "asst".to_string(),
+        thread_id: "thread".to_string(),
+        main_character_sheet: Some(sheet.clone()),
+        save_name: "Run".to_string(),
+        characters: vec![sheet],
+        archive: Default::default(),
+        portraits: Default::default(),
+        scenes: Vec::new(),
+        ruleset: Default::default(),
+        stats: Default::default(),
+        combat: None,
+        pending_run: None,
+        instructions_version: None,
+        events: Vec::new(),
+    };
+
+    // The first record is a full snapshot, later ones only append what changed.
+    let mut journal = Journal::new(&dir, "Run");
+    journal.record(&state).unwrap();
+    assert!(!journal::path_for(&dir, "Run").exists());
+    state.main_character_sheet.as_mut().unwrap().nuyen += 1000;
+    state.characters[0].nuyen += 1000;
+    journal.record(&state).unwrap();
+    journal.record(&state).unwrap();
+    assert_eq!(journal.entries(), 2);
+
+    // A line torn by a crash doesn't stop the save from loading.
+    let mut file = fs::OpenOptions::new()
+        .append(true)
+        .open(journal::path_for(&dir, "Run"))
+        .unwrap();
+    std::io::Write::write_all(&mut file, br#"{"change":"charac"#).unwrap();
+    let (mut journal, loaded) = Journal::load(&dir, "Run").unwrap();
+    assert_eq!(
+        loaded.main_character_sheet.unwrap().nuyen,
+        state.characters[0].nuyen
+    );
+    assert_eq!(loaded.characters[0].nuyen, state.characters[0].nuyen);
+    assert_eq!(journal.entries(), 2);
+
+    for nuyen in 0..COMPACT_AFTER as u32 {
+        state.characters[0].nuyen = nuyen;
+        journal.record(&state).unwrap();
+    }
+    assert!(journal.entries() < COMPACT_AFTER);
+    let (_, loaded) = Journal::load(&dir, "Run").unwrap();
+    assert_eq!(loaded.characters[0].nuyen, COMPACT_AFTER as u32 - 1);
+
+    // A gzipped snapshot is told apart by its first bytes and loads like a plain one.
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    std::io::Write::write_all(&mut encoder, &serde_json::to_vec(&state).unwrap()).unwrap();
+    fs::write(dir.join("Packed.json"), encoder.finish().unwrap()).unwrap();
+    let (_, loaded) = Journal::load(&dir, "Packed").unwrap();
+    assert_eq!(loaded.thread_id, state.thread_id);
+    assert_eq!(loaded.characters[0].nuyen, state.characters[0].nuyen);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_damaged_saves_are_detected_and_recovered_from_backup() {
+    use sharad_ratatui::archetype::ARCHETYPES;
+    use sharad_ratatui::journal::{self, Journal, SaveError};
+
+    let dir = std::env::temp_dir().join(format!("sharad_integrity_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let sheet = ARCHETYPES[0].builder().build();
+    let mut state = GameState {
+        assistant_id: "asst".to_string(),
+        thread_id: "thread".to_string(),
+        main_character_sheet: Some(sheet.clone()),
+        save_name: "Run".to_string(),
+        characters: vec![sheet],
+        archive: Default::default(),
+        portraits: Default::default(),
+        scenes: Vec::new(),
+        ruleset: Default::default(),
+        stats: Default::default(),
+        combat: None,
+        pending_run: None,
+        instructions_version: None,
+        events: Vec::new(),
+    };
+    let mut journal = Journal::new(&dir, "Run");
+    journal.record(&state).unwrap();
+    state.thread_id = "newer thread".to_string();
+    journal.compact(&state).unwrap();
+    assert!(Journal::load(&dir, "Run").is_ok());
+
+    // A crash before a new snapshot is renamed in leaves both checksums listed, and the old
+    // snapshot still verifies.
+    let sums = journal::checksum_path(&dir, "Run");
+    let current = fs::read_to_string(&sums).unwrap();
+    fs::write(&sums, format!("0badc0de\n{}", current)).unwrap();
+    assert!(Journal::load(&dir, "Run").is_ok());
+    fs::write(&sums, current).unwrap();
+
+    // An edit by hand still parses, so it can be accepted as it is.
+    let snapshot = journal::snapshot_path(&dir, "Run");
+    let edited = fs::read_to_string(&snapshot)
+        .unwrap()
+        .replace("newer thread", "edited thread");
+    fs::write(&snapshot, edited).unwrap();
+    match Journal::load(&dir, "Run") {
+        Err(SaveError::Corrupt { readable, .. }) => assert!(readable),
+        other => panic!("expected a damaged save, got {:?}", other.map(|_| ())),
+    }
+    journal::accept(&dir, "Run").unwrap();
+    assert_eq!(
+        Journal::load(&dir, "Run").unwrap().1.thread_id,
+        "edited thread"
+    );
+
+    // A truncated snapshot falls back to the version before it.
+    fs::write(&snapshot, r#"{"assistant_id": "asst", "thr"#).unwrap();
+    match Journal::load(&dir, "Run") {
+        Err(SaveError::Corrupt { readable, .. }) => assert!(!readable),
+        other => panic!("expected a damaged save, got {:?}", other.map(|_| ())),
+    }
+    let backup = journal::backups(&dir, "Run")[0].clone();
+    journal::restore_backup(&dir, "Run", &backup).unwrap();
+    assert_eq!(Journal::load(&dir, "Run").unwrap().1.thread_id, "thread");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_backups_rotate_keeping_the_newest() {
+    use sharad_ratatui::archetype::ARCHETYPES;
+    use sharad_ratatui::journal::{self, Journal};
+
+    let dir = std::env::temp_dir().join(format!("sharad_backups_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let sheet = ARCHETYPES[0].builder().build();
+    let mut state = GameState {
+        assistant_id: "asst".to_string(),
+        thread_id: "thread 0".to_string(),
+        main_character_sheet: Some(sheet.clone()),
+        save_name: "Run".to_string(),
+        characters: vec![sheet],
+        archive: Default::default(),
+        portraits: Default::default(),
+        scenes: Vec::new(),
+        ruleset: Default::default(),
+        stats: Default::default(),
+        combat: None,
+        pending_run: None,
+        instructions_version: None,
+        events: Vec::new(),
+    };
+    journal::set_backup_count(2);
+    let mut journal = Journal::new(&dir, "Run");
+    for run in 0..5 {
+        state.thread_id = format!("thread {}", run);
+        journal.compact(&state).unwrap();
+    }
+
+    // Every overwrite kept the version before it, but only the last two are left.
+    let backups = journal::backups(&dir, "Run");
+    assert_eq!(backups.len(), 2);
+    assert!(fs::read_to_string(&backups[0])
+        .unwrap()
+        .contains("thread 3"));
+    assert!(fs::read_to_string(&backups[1])
+        .unwrap()
+        .contains("thread 2"));
+
+    // Restoring one keeps the version it replaces.
+    journal::restore_backup(&dir, "Run", &backups[1]).unwrap();
+    assert_eq!(Journal::load(&dir, "Run").unwrap().1.thread_id, "thread 2");
+    let backups = journal::backups(&dir, "Run");
+    assert!(fs::read_to_string(&backups[0])
+        .unwrap()
+        .contains("thread 4"));
+
+    journal::set_backup_count(3);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_sheet_changes_are_described_in_words() {
+    use sharad_ratatui::archetype::ARCHETYPES;
+    use sharad_ratatui::character::Item;
+    use sharad_ratatui::sheet_diff::sheet_changes;
+
+    let mut before = ARCHETYPES[0].builder().build();
+    before.nuyen = 5000;
+    before.inventory.insert(
+        "Ares Predator".to_string(),
+        Item {
+            name: "Ares Predator".to_string(),
+            quantity: 1,
+            description: "Heavy pistol".to_string(),
+            ..Default::default()
+        },
+    );
+    before.inventory.insert(
+        "Medkit".to_string(),
+        Item {
+            name: "Medkit".to_string(),
+            quantity: 2,
+            description: "Rating 3".to_string(),
+            ..Default::default()
+        },
+    );
+    let mut after = before.clone();
+    assert!(sheet_changes(&before, &after).is_empty());
+
+    after.nuyen = 3500;
+    after.inventory.remove("Ares Predator");
+    after.inventory.get_mut("Medkit").unwrap().quantity = 1;
+    after.qualities.push(Quality {
+        name: "Lucky".to_string(),
+        positive: true,
+    });
+    after.essence -= 0.2;
+
+    let changes = sheet_changes(&before, &after);
+    assert!(changes.contains(&"Nuyen 5000 → 3500".to_string()));
+    assert!(changes.contains(&"removed Ares Predator".to_string()));
+    assert!(changes.contains(&"Medkit quantity 2 → 1".to_string()));
+    assert!(changes.contains(&"added Lucky".to_string()));
+    assert!(changes
+        .iter()
+        .any(|change| change.starts_with("Essence ") && !change.contains("0000")));
+    assert_eq!(changes.len(), 5);
+}
+
+#[tokio::test]
+async fn test_confirm_updates_holds_ai_changes_for_review() {
+    use async_openai::types::{FunctionCall, RunToolCallObject};
+    use sharad_ratatui::archetype::ARCHETYPES;
+    use sharad_ratatui::review;
+
+    let mut ai = GameAI::new(String::new(), |_| {}).await.unwrap();
+    ai.review.with(|queue| queue.enabled = true);
+    let mut sheet = ARCHETYPES[0].builder().build();
+    sheet.name = "Raven".to_string();
+    sheet.skills.combat.insert("Blades".to_string(), 3);
+    let mut game_state = GameState {
+        assistant_id: "mock".to_string(),
+        thread_id: "mock".to_string(),
+        main_character_sheet: Some(sheet.clone()),
+        save_name: "review".to_string(),
+        characters: vec![sheet],
+        archive: Default::default(),
+        portraits: Default::default(),
+        scenes: Vec::new(),
+        ruleset: Default::default(),
+        stats: Default::default(),
+        combat: None,
+        pending_run: None,
+        instructions_version: None,
+        events: Vec::new(),
+    };
+    let tool_call = RunToolCallObject {
+        id: "call_1".to_string(),
+        r#type: "function".to_string(),
+        function: FunctionCall {
+            name: "update_skills".to_string(),
+            arguments: r#"{"character_name": "Raven", "updates": {"skills": {"combat": [{"name": "Blades", "rating": 6}]}}}"#.to_string(),
+        },
+    };
+
+    // Nothing changes until the player accepts, and the assistant is told so.
+    let output = ai
+        .handle_tool_call(&tool_call, &mut game_state)
+        .await
+        .unwrap();
+    assert!(output.contains("approval"));
+    let blades =
+        |state: &GameState| state.main_character_sheet.as_ref().unwrap().skills.combat["Blades"];
+    assert_eq!(blades(&game_state), 3);
+    let pending = ai.review.with(|queue| std::mem::take(&mut queue.pending));
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].changes, vec!["Blades 3 → 6".to_string()]);
+
+    review::apply(&mut game_state, &pending[0]).unwrap();
+    assert_eq!(blades(&game_state), 6);
+    assert_eq!(game_state.characters[0].skills.combat["Blades"], 6);
+}
+
+#[test]
+fn test_usage_metrics_are_opt_in_and_export() {
+    use sharad_ratatui::metrics;
+
+    let dir = std::env::temp_dir().join(format!("sharad_metrics_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let file = dir.join("metrics.json");
+
+    metrics::configure(Some(file.clone()));
+    metrics::record("test/export");
+    metrics::record("test/export");
+    metrics::record_error("Network");
+    let counts = metrics::snapshot().unwrap();
+    assert_eq!(counts.features["test/export"], 2);
+    assert!(counts.errors["Network"] >= 1);
+
+    // Counts survive a restart, and an export is a plain copy of them.
+    metrics::configure(None);
+    assert!(metrics::snapshot().is_none());
+    metrics::record("test/export");
+    metrics::configure(Some(file));
+    assert_eq!(metrics::snapshot().unwrap().features["test/export"], 2);
+    let exported = metrics::export(&dir.join("exports")).unwrap();
+    let exported: metrics::Metrics =
+        serde_json::from_str(&fs::read_to_string(exported).unwrap()).unwrap();
+    assert_eq!(exported.features["test/export"], 2);
+
+    metrics::configure(None);
+    assert!(metrics::export(&dir.join("exports")).is_err());
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_ui_bench_renders_every_case() {
+    use sharad_ratatui::bench;
+
+    assert_eq!(bench::sample_history(10).len(), 10);
+    let results = bench::run(1);
+    let names: Vec<&str> = results.iter().map(|result| result.name.as_str()).collect();
+    assert_eq!(names.len(), 4);
+    assert!(names.contains(&"parse_markdown"));
+    assert!(names.contains(&"draw_character_sheet"));
+    assert!(results.iter().all(|result| result.mean_micros > 0.0));
+}
+
+// The data folders can only be set once per process, so the tests playing through the app share
+// them and take turns.
+static DATA_DIR: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+#[tokio::test]
+async fn test_app_plays_full_turns_against_the_mock_assistant() {
+    use sharad_ratatui::ai::GameAI;
+    use sharad_ratatui::app::App;
+    use sharad_ratatui::backend::{MockBackend, MockTurn};
+    use sharad_ratatui::headless::wait_for_response;
+    use sharad_ratatui::{paths, settings::Settings};
+    use std::sync::Arc;
+    use tokio::sync::mpsc;
+
+    // Keep the settings and the save out of the player's folders.
+    let _data_dir = DATA_DIR.lock().await;
+    let dir = std::env::temp_dir().join(format!("sharad_mock_app_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("config")).unwrap();
+    let settings = Settings {
+        data_dir: Some(dir.join("data")),
+        audio_output_enabled: false,
+        ..Settings::default()
+    };
+    let settings_file = dir.join("config").join("settings.json");
+    settings
+        .save_to_file(settings_file.to_str().unwrap())
+        .unwrap();
+    paths::init(Some(dir.join("config")), false);
+    assert_eq!(paths::data_dir(), dir.join("data"));
+
+    let json_str = fs::read_to_string("tests/dummy_create_character_sheet.json")
+        .expect("Failed to read dummy create character JSON file");
+    let json_value: serde_json::Value =
+        serde_json::from_str(&json_str).expect("Failed to parse JSON");
+    let create_args = json_value["function"]["arguments"].to_string();
+    let backend = Arc::new(MockBackend::new([
+        MockTurn::reply("Welcome to the shadows, Raven.").tool("create_character_sheet", &create_args),
+        MockTurn::reply("The blade bites deep.").tool(
+            "update_skills",
+            r#"{"character_name": "Alex 'Raven' Hayes", "updates": {"skills": {"combat": [{"name": "Blades", "rating": 6}]}}}"#,
+        ),
+    ]));
+
+    let (ai_sender, mut ai_receiver) = mpsc::unbounded_channel();
+    let (mut app, mut commands) = App::new(ai_sender.clone()).await;
+    app.ai_client = Some(GameAI::with_backend(backend.clone(), |_| {}));
+
+    // The opening turn creates the character through its tool call.
+    app.start_new_game("mock run".to_string(), None)
+        .await
+        .unwrap();
+    wait_for_response(&mut app, &mut commands, &mut ai_receiver).await;
+    let game = app.current_game.clone().expect("a game is running");
+    let thread_id = game.lock().await.thread_id.clone();
+    assert_eq!(
+        game.lock()
+            .await
+            .main_character_sheet
+            .as_ref()
+            .map(|sheet| sheet.name.clone()),
+        Some("Alex 'Raven' Hayes".to_string())
+    );
+
+    // A player turn: the tool output goes back to the assistant and the answer to the screen.
+    app.add_message(Message::new(
+        MessageType::User,
+        "I slash at the guard".to_string(),
+    ));
+    app.process_message("I slash at the guard".to_string());
+    wait_for_response(&mut app, &mut commands, &mut ai_receiver).await;
+    let blades = game
+        .lock()
+        .await
+        .main_character_sheet
+        .as_ref()
+        .unwrap()
+        .skills
+        .combat["Blades"];
+    assert_eq!(blades, 6);
+    assert_eq!(backend.tool_outputs().len(), 2);
+    assert!(app
+        .game_content
+        .borrow()
+        .iter()
+        .any(|message| message.content.contains("The blade bites deep.")));
+    assert!(app
+        .sheet_changes
+        .iter()
+        .any(|change| change.contains("Blades")));
+
+    // Loading the save reads the same thread back.
+    let (mut reloaded, _commands) = App::new(ai_sender).await;
+    reloaded.ai_client = Some(GameAI::with_backend(backend.clone(), |_| {}));
+    reloaded.load_game("mock run").await.unwrap();
+    let thread = backend.thread(&thread_id);
+    assert_eq!(thread.len(), 5);
+    assert!(reloaded
+        .game_content
+        .borrow()
+        .iter()
+        .any(|message| message.content.contains("Welcome to the shadows, Raven.")));
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_tool_arguments_are_typed() {
+    use async_openai::types::{FunctionCall, RunToolCallObject};
+    use sharad_ratatui::archetype::ARCHETYPES;
+    use sharad_ratatui::character::Item;
+    use sharad_ratatui::tool_args::{self, InventoryArgs, Operation, QualitiesArgs};
+
+    // Malformed arguments name what is wrong.
+    let error = tool_args::parse::<QualitiesArgs>(
+        "update_qualities",
+        r#"{"character_name": "Raven", "operation": "Add", "qualities": [{"name": "Lucky"}]}"#,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(error.contains("update_qualities") && error.contains("positive"));
+    assert!(tool_args::parse::<QualitiesArgs>(
+        "update_qualities",
+        r#"{"character_name": "Raven", "operation": "Add", "qualities": [], "mood": "grim"}"#,
+    )
+    .unwrap_err()
+    .to_string()
+    .contains("mood"));
+    assert!(tool_args::parse::<InventoryArgs>(
+        "update_inventory",
+        r#"{"character_name": "Raven", "operation": "Steal", "item": {"name": "Medkit"}}"#,
+    )
+    .is_err());
+
+    // The item shapes the assistant sends all read the same.
+    for item in [
+        r#""item": {"name": "Medkit", "quantity": 2, "description": "Stim patches"}"#,
+        r#""items": [{"name": "Medkit", "quantity": 2}]"#,
+        r#""items": {"Medkit": {"quantity": 2}}"#,
+    ] {
+        let args: InventoryArgs = tool_args::parse(
+            "update_inventory",
+            &format!(
+                r#"{{"character_name": "Raven", "operation": "Add", {}}}"#,
+                item
+            ),
+        )
+        .unwrap();
+        assert_eq!(args.operation, Operation::Add);
+        assert_eq!(args.item.into_items()["Medkit"].quantity, 2);
+    }
+
+    let mut ai = GameAI::new(String::new(), |_| {}).await.unwrap();
+    let mut sheet = ARCHETYPES[0].builder().build();
+    sheet.name = "Raven".to_string();
+    sheet.inventory.insert(
+        "Medkit".to_string(),
+        Item {
+            name: "Medkit".to_string(),
+            quantity: 3,
+            description: String::new(),
+            ..Default::default()
+        },
+    );
+    let mut game_state = GameState {
+        assistant_id: "mock".to_string(),
+        thread_id: "mock".to_string(),
+        main_character_sheet: Some(sheet.clone()),
+        save_name: "typed".to_string(),
+        characters: vec![sheet],
+        archive: Default::default(),
+        portraits: Default::default(),
+        scenes: Vec::new(),
+        ruleset: Default::default(),
+        stats: Default::default(),
+        combat: None,
+        pending_run: None,
+        instructions_version: None,
+        events: Vec::new(),
+    };
+    let call = |name: &str, arguments: &str| RunToolCallObject {
+        id: "call_1".to_string(),
+        r#type: "function".to_string(),
+        function: FunctionCall {
+            name: name.to_string(),
+            arguments: arguments.to_string(),
+        },
+    };
+
+    // Removing honours the quantity, and a name alone takes one away.
+    ai.handle_tool_call(
+        &call(
+            "update_inventory",
+            r#"{"character_name": "Raven", "operation": "Remove", "item": {"name": "Medkit", "quantity": 2, "description": ""}}"#,
+        ),
+        &mut game_state,
+    )
+    .await
+    .unwrap();
+    let medkits = |state: &GameState| {
+        state
+            .main_character_sheet
+            .as_ref()
+            .unwrap()
+            .inventory
+            .get("Medkit")
+            .map(|item| item.quantity)
+    };
+    assert_eq!(medkits(&game_state), Some(1));
+    ai.handle_tool_call(
+        &call(
+            "update_inventory",
+            r#"{"character_name": "Raven", "operation": "Remove", "items": ["Medkit"]}"#,
+        ),
+        &mut game_state,
+    )
+    .await
+    .unwrap();
+    assert_eq!(medkits(&game_state), None);
+
+    // Skills in the map form of the function definition, with knowledge skills beside them.
+    ai.handle_tool_call(
+        &call(
+            "update_skills",
+            r#"{"character_name": "Raven", "updates": {"skills": {"combat": {"Blades": 5}}, "knowledge_skills": {"Gangs": 3}}}"#,
+        ),
+        &mut game_state,
+    )
+    .await
+    .unwrap();
+    let sheet = game_state.main_character_sheet.as_ref().unwrap();
+    assert_eq!(sheet.skills.combat["Blades"], 5);
+    assert_eq!(sheet.knowledge_skills["Gangs"], 3);
+
+    assert!(ai
+        .handle_tool_call(
+            &call(
+                "update_augmentations",
+                r#"{"character_name": "Raven", "operation": "Modify", "augmentation_type": "cyberware", "augmentations": []}"#,
+            ),
+            &mut game_state,
+        )
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_answers_breaking_the_schema_are_repaired() {
+    use sharad_ratatui::ai::{GameConversationState, MAX_REPAIR_ATTEMPTS};
+    use sharad_ratatui::assistant::response_schema;
+    use sharad_ratatui::backend::{MockBackend, MockTurn};
+    use sharad_ratatui::message::parse_game_message;
+    use std::sync::Arc;
+
+    // The GameMessage types and the schema sent to OpenAI describe the same fields.
+    let schema = response_schema().unwrap();
+    let properties = |value: &serde_json::Value| -> Vec<String> {
+        let mut keys: Vec<String> = value["properties"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect();
+        keys.sort();
+        keys
+    };
+    let fluff = &schema["schema"]["properties"]["fluff"];
+    assert_eq!(properties(&schema["schema"]), ["crunch", "fluff"]);
+    assert_eq!(properties(fluff), ["dialogue", "speakers"]);
+    let reply = r#"{"crunch": "", "fluff": {"speakers": [{"index": 0, "name": "Narrator", "gender": "NonBinary"}], "dialogue": [{"speaker_index": 0, "text": "Rain."}]}}"#;
+    assert!(parse_game_message(reply).is_ok());
+    for gender in fluff["properties"]["speakers"]["items"]["properties"]["gender"]["enum"]
+        .as_array()
+        .unwrap()
+    {
+        let reply = reply.replace("NonBinary", gender.as_str().unwrap());
+        assert!(parse_game_message(&reply).is_ok());
+    }
+    assert!(
+        parse_game_message(&reply.replace("\"speaker_index\": 0", "\"speaker_index\": 3"))
+            .unwrap_err()
+            .contains("speaker_index 3")
+    );
+
+    let backend = Arc::new(MockBackend::new([
+        MockTurn::verbatim(r#"{"crunch": "cut off"#),
+        MockTurn::reply("The door opens."),
+    ]));
+    let mut ai = GameAI::with_backend(backend.clone(), |_| {});
+    ai.start_new_conversation(
+        "asst_mock",
+        GameConversationState {
+            assistant_id: "asst_mock".to_string(),
+            thread_id: String::new(),
+            character_sheet: None,
+        },
+    )
+    .await
+    .unwrap();
+    let (thread_id, _) = ai.get_conversation_ids().await.unwrap();
+    let mut game_state = GameState {
+        assistant_id: "asst_mock".to_string(),
+        thread_id: thread_id.clone(),
+        main_character_sheet: None,
+        save_name: "repair".to_string(),
+        characters: Vec::new(),
+        archive: Default::default(),
+        portraits: Default::default(),
+        scenes: Vec::new(),
+        ruleset: Default::default(),
+        stats: Default::default(),
+        combat: None,
+        pending_run: None,
+        instructions_version: None,
+        events: Vec::new(),
+    };
+
+    let answer = ai
+        .send_message("I open the door", &mut game_state)
+        .await
+        .unwrap();
+    assert_eq!(answer.fluff.dialogue[0].text, "The door opens.");
+    assert!(backend.thread(&thread_id).iter().any(|message| message
+        .content
+        .contains("did not follow the response schema")));
+
+    // An assistant that keeps breaking it still fails the turn, after a few tries.
+    for _ in 0..=MAX_REPAIR_ATTEMPTS {
+        backend.queue(MockTurn::verbatim("not json"));
+    }
+    assert!(ai.send_message("I wait", &mut game_state).await.is_err());
+}
+
+#[tokio::test]
+async fn test_old_turns_are_summarized_and_left_out_of_runs() {
+    use sharad_ratatui::ai::{GameAI, GameConversationState};
+    use sharad_ratatui::archivist::RECENT_MESSAGES;
+    use sharad_ratatui::backend::{MockBackend, MockTurn};
+    use sharad_ratatui::game_state::GameState;
+    use std::sync::Arc;
+
+    let turns = 35;
+    let backend =
+        Arc::new(MockBackend::new((0..turns).map(|turn| {
+            MockTurn::reply(format!("Turn {} plays out.", turn))
+        })));
+    let mut ai = GameAI::with_backend(backend.clone(), |_| {});
+    ai.start_new_conversation(
+        "asst_mock",
+        GameConversationState {
+            assistant_id: "asst_mock".to_string(),
+            thread_id: String::new(),
+            character_sheet: None,
+        },
+    )
+    .await
+    .unwrap();
+    let (thread_id, _) = ai.get_conversation_ids().await.unwrap();
+    let mut game_state = GameState {
+        assistant_id: "asst_mock".to_string(),
+        thread_id: thread_id.clone(),
+        main_character_sheet: None,
+        save_name: "archive".to_string(),
+        characters: Vec::new(),
+        archive: Default::default(),
+        portraits: Default::default(),
+        scenes: Vec::new(),
+        ruleset: Default::default(),
+        stats: Default::default(),
+        combat: None,
+        pending_run: None,
+        instructions_version: None,
+        events: Vec::new(),
+    };
+    for turn in 0..turns {
+        ai.send_message(&format!("Action {}", turn), &mut game_state)
+            .await
+            .unwrap();
+    }
+
+    // Every run reads only the recent messages; once the oldest left, the summary comes along.
+    let contexts = backend.contexts();
+    assert_eq!(contexts.len(), turns);
+    assert!(contexts
+        .iter()
+        .all(|context| context.recent_messages == Some(RECENT_MESSAGES)));
+    assert!(contexts[0].summary.is_none());
+    assert!(contexts
+        .last()
+        .unwrap()
+        .summary
+        .as_ref()
+        .unwrap()
+        .contains("[mock summary 1]"));
+    assert!(game_state.archive.summary.starts_with("[mock summary"));
+
+    // The thread, and so the save's transcript, keeps everything.
+    assert_eq!(backend.thread(&thread_id).len(), 1 + 2 * turns);
+}
+
+#[tokio::test]
+async fn test_forks_start_a_new_thread_from_a_summary() {
+    use sharad_ratatui::archivist::{self, Archive};
+    use sharad_ratatui::backend::{AssistantBackend, MockBackend};
+    use sharad_ratatui::commands::fork_name;
+
+    let backend = MockBackend::new([]);
+    let thread_id = backend.create_thread().await.unwrap();
+    for line in ["I enter the bar", "The fixer waves you over."] {
+        backend.add_message(&thread_id, line).await.unwrap();
+    }
+    let archive = Archive {
+        summary: "Raven took a job from Mr. Johnson.".to_string(),
+        uncovered: Some(2),
+    };
+
+    let (fork_id, fork_archive) = archivist::fork(&backend, &thread_id, &archive)
+        .await
+        .unwrap();
+    assert_ne!(fork_id, thread_id);
+    let fork = backend.thread(&fork_id);
+    assert_eq!(fork.len(), 1);
+    assert!(fork[0].content.contains("[mock summary 1]"));
+    assert_eq!(fork_archive.uncovered, Some(1));
+    // The original timeline is untouched.
+    assert_eq!(backend.thread(&thread_id).len(), 2);
+
+    let existing = vec!["run".to_string(), "run fork".to_string()];
+    assert_eq!(fork_name("run", "", &existing).unwrap(), "run fork 2");
+    assert_eq!(fork_name("run", "what if", &existing).unwrap(), "what if");
+    assert!(fork_name("run", "run", &existing).is_err());
+}
+
+#[test]
+fn test_transcript_marks_sessions_and_message_times() {
+    use sharad_ratatui::message::{Message, MessageType};
+    use sharad_ratatui::transcript::{relative_time, TranscriptCache, SESSION_GAP_SECONDS};
+
+    let start = 1_750_000_000;
+    let system = |text: &str, at: i64| Message::new(MessageType::System, text.to_string()).at(at);
+    let messages = vec![
+        system("The job pays well.", start),
+        system("I take it.", start + 60),
+        // The next evening.
+        system("Back at the bar.", start + SESSION_GAP_SECONDS + 3600),
+    ];
+    let mut cache = TranscriptCache::new();
+    cache.update(&messages, 60, true);
+    let lines: Vec<String> = cache
+        .lines(0, 10)
+        .iter()
+        .map(|line| line.to_string())
+        .collect();
+    let separator = lines
+        .iter()
+        .position(|line| line.starts_with("Session of "))
+        .expect("a separator before the second session");
+    assert!(lines[separator + 1].contains("Back at the bar."));
+    assert_eq!(
+        lines
+            .iter()
+            .filter(|line| line.starts_with("Session"))
+            .count(),
+        1
+    );
+
+    // The gutter dates each message on its first line, and nothing beside the separator.
+    let now = start + SESSION_GAP_SECONDS + 3600 + 90;
+    let gutter: Vec<String> = cache
+        .gutter(0, 10, now)
+        .iter()
+        .map(|line| line.to_string())
+        .collect();
+    assert_eq!(gutter.len(), lines.len());
+    assert_eq!(gutter[0].trim(), "3h");
+    assert_eq!(gutter[separator], "");
+    assert_eq!(gutter[separator + 1].trim(), "1m");
+    assert_eq!(relative_time(now, now), "now");
+    assert_eq!(relative_time(now - 3 * 86400, now), "3d");
+
+    // Transcripts saved before messages had times still load.
+    let old: Message =
+        serde_json::from_str(r#"{"message_type": "User", "content": "Hoi"}"#).unwrap();
+    assert_eq!(old.timestamp, None);
+}
+
+#[test]
+fn test_portraits_are_filed_per_character() {
+    use sharad_ratatui::archetype::ARCHETYPES;
+    use sharad_ratatui::journal::{self, Journal};
+    use std::path::PathBuf;
+
+    let dir = std::env::temp_dir().join(format!("sharad_portraits_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let mut runner = ARCHETYPES[0].builder().build();
+    runner.name = "Raven".to_string();
+    let mut fixer = ARCHETYPES[1].builder().build();
+    fixer.name = "Dodger".to_string();
+    let mut state = GameState {
+        assistant_id: "asst".to_string(),
+        thread_id: "thread".to_string(),
+        main_character_sheet: Some(runner.clone()),
+        save_name: "Run".to_string(),
+        characters: vec![runner, fixer],
+        archive: Default::default(),
+        portraits: Default::default(),
+        scenes: Vec::new(),
+        ruleset: Default::default(),
+        stats: Default::default(),
+        combat: None,
+        pending_run: None,
+        instructions_version: None,
+        events: Vec::new(),
+    };
+    let mut journal = Journal::new(&dir, "Run");
+    journal.record(&state).unwrap();
+
+    state
+        .portraits
+        .insert("dodger".to_string(), PathBuf::from("images/dodger.png"));
+    state
+        .portraits
+        .insert("Raven".to_string(), PathBuf::from("images/raven.png"));
+    journal.record(&state).unwrap();
+    assert!(journal::path_for(&dir, "Run").exists());
+
+    let (_, loaded) = Journal::load(&dir, "Run").unwrap();
+    assert_eq!(
+        loaded.portrait("Raven"),
+        Some(&PathBuf::from("images/raven.png"))
+    );
+    // The AI's spelling of a name need not match the sheet's case.
+    assert_eq!(
+        loaded.portrait("Dodger"),
+        Some(&PathBuf::from("images/dodger.png"))
+    );
+    assert_eq!(loaded.portrait("Mr. Johnson"), None);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_scene_illustrations_are_kept_in_the_gallery() {
+    use sharad_ratatui::image::SceneImage;
+    use sharad_ratatui::journal::Journal;
+    use sharad_ratatui::tool_args::{self, SceneImageArgs};
+    use std::path::PathBuf;
+
+    // The tool's definition and its arguments agree.
+    let definition: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string("assets/assistant_functions/generate_scene_image.json").unwrap(),
+    )
+    .unwrap();
+    assert_eq!(definition["name"], "generate_scene_image");
+    let args: SceneImageArgs = tool_args::parse(
+        "generate_scene_image",
+        r#"{"title": "The Kingfisher bar", "image_generation_prompt": "A smoky bar in the rain"}"#,
+    )
+    .unwrap();
+    assert_eq!(args.title, "The Kingfisher bar");
+    assert!(
+        tool_args::parse::<SceneImageArgs>("generate_scene_image", r#"{"title": "x"}"#).is_err()
+    );
+
+    let dir = std::env::temp_dir().join(format!("sharad_scenes_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let mut state = GameState {
+        assistant_id: "asst".to_string(),
+        thread_id: "thread".to_string(),
+        main_character_sheet: None,
+        save_name: "Run".to_string(),
+        characters: Vec::new(),
+        archive: Default::default(),
+        portraits: Default::default(),
+        scenes: Vec::new(),
+        ruleset: Default::default(),
+        stats: Default::default(),
+        combat: None,
+        pending_run: None,
+        instructions_version: None,
+        events: Vec::new(),
+    };
+    let mut journal = Journal::new(&dir, "Run");
+    journal.record(&state).unwrap();
+    state.scenes.push(SceneImage {
+        title: args.title,
+        path: PathBuf::from("images/kingfisher.png"),
+        created_at: 1_750_000_000,
+    });
+    journal.record(&state).unwrap();
+    let (_, loaded) = Journal::load(&dir, "Run").unwrap();
+    assert_eq!(loaded.scenes, state.scenes);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_transcription_model_and_language_hint_settings() {
+    use sharad_ratatui::settings::Settings;
+    use sharad_ratatui::settings_state::{SettingsState, VOICE_LANGUAGE, VOICE_MODEL};
+
+    // Older settings files keep the old behaviour: whisper-1, language detected.
+    let settings: Settings = serde_json::from_str(
+        r#"{"language": "Français", "openai_api_key": null, "audio_output_enabled": true,
+            "audio_input_enabled": true, "debug_mode": false}"#,
+    )
+    .unwrap();
+    assert_eq!(settings.transcription_model, "whisper-1");
+    assert_eq!(settings.transcription_language_code(), None);
+
+    let follow_game = Settings {
+        transcription_language: "Game Language".to_string(),
+        ..settings.clone()
+    };
+    assert_eq!(follow_game.transcription_language_code(), Some("fr"));
+    let japanese = Settings {
+        transcription_model: "gpt-4o-transcribe".to_string(),
+        transcription_language: "日本語".to_string(),
+        ..settings
+    };
+    assert_eq!(japanese.transcription_language_code(), Some("ja"));
+
+    let state = SettingsState::from_settings(&japanese);
+    assert_eq!(state.selected_options[VOICE_MODEL], 2);
+    assert_eq!(state.selected_options[VOICE_LANGUAGE], 4);
+}
+
+#[test]
+fn test_speech_settings_choose_model_speed_and_narrator_voice() {
+    use async_openai::types::{SpeechModel, Voice};
+    use sharad_ratatui::audio::SpeechSettings;
+    use sharad_ratatui::message::{Gender, Speaker};
+    use sharad_ratatui::settings::Settings;
+    use sharad_ratatui::settings_state::{
+        SettingsState, NARRATOR_VOICE, SPEECH_MODEL, SPEECH_SPEED,
+    };
+
+    // Older settings files keep the old narration: tts-1 at 1.3x, the Narrator's voice picked.
+    let settings: Settings = serde_json::from_str(
+        r#"{"language": "English", "openai_api_key": null, "audio_output_enabled": true,
+            "audio_input_enabled": true, "debug_mode": false}"#,
+    )
+    .unwrap();
+    let speech = SpeechSettings::from_settings(&settings);
+    assert_eq!(speech.model, SpeechModel::Tts1);
+    assert_eq!(speech.speed, 1.3);
+    let narrator = Speaker {
+        index: 0,
+        name: "Narrator".to_string(),
+        gender: Gender::NonBinary,
+        voice: Some(Voice::Echo),
+    };
+    assert_eq!(speech.voice_for(&narrator), Voice::Echo);
+
+    let chosen = Settings {
+        speech_model: "gpt-4o-mini-tts".to_string(),
+        speech_speed: 1.0,
+        narrator_voice: Some(Voice::Fable),
+        ..settings
+    };
+    let speech = SpeechSettings::from_settings(&chosen);
+    assert_eq!(
+        speech.model,
+        SpeechModel::Other("gpt-4o-mini-tts".to_string())
+    );
+    assert_eq!(speech.voice_for(&narrator), Voice::Fable);
+    // Other speakers keep their own voice.
+    let fixer = Speaker {
+        name: "Fixer".to_string(),
+        ..narrator
+    };
+    assert_eq!(speech.voice_for(&fixer), Voice::Echo);
+
+    let state = SettingsState::from_settings(&chosen);
+    assert_eq!(state.selected_options[SPEECH_MODEL], 2);
+    assert_eq!(state.selected_options[SPEECH_SPEED], 0);
+    assert_eq!(state.selected_options[NARRATOR_VOICE], 3);
+}
+
+#[test]
+fn test_the_players_action_can_be_read_back_before_the_narration() {
+    use async_openai::types::Voice;
+    use sharad_ratatui::audio::{narration_clips, SpeechSettings, PLAYER_LINE};
+    use sharad_ratatui::backend::narration;
+    use sharad_ratatui::settings::Settings;
+    use sharad_ratatui::settings_state::{SettingsState, PLAYER_VOICE};
+
+    let message = narration("The door opens.".to_string());
+    let action = Some("I push the door.");
+    let clips = |settings: &Settings| -> Vec<(usize, Voice, String)> {
+        narration_clips(
+            &message.fluff,
+            action,
+            &SpeechSettings::from_settings(settings),
+        )
+        .into_iter()
+        .map(|clip| (clip.line, clip.voice, clip.text))
+        .collect()
+    };
+
+    // Off by default: only the Game Master speaks.
+    let settings = Settings {
+        narrator_voice: Some(Voice::Onyx),
+        ..Settings::default()
+    };
+    assert_eq!(
+        clips(&settings),
+        [(0, Voice::Onyx, "The door opens.".to_string())]
+    );
+
+    let settings = Settings {
+        player_voice: Some(Voice::Nova),
+        ..settings
+    };
+    assert_eq!(
+        clips(&settings),
+        [
+            (PLAYER_LINE, Voice::Nova, "I push the door.".to_string()),
+            (0, Voice::Onyx, "The door opens.".to_string()),
+        ]
+    );
+    assert_eq!(
+        SettingsState::from_settings(&settings).selected_options[PLAYER_VOICE],
+        5
+    );
+}
+
+#[test]
+fn test_narration_cache_reuses_lines_and_evicts_the_oldest() {
+    use async_openai::types::Voice;
+    use sharad_ratatui::audio::SpeechSettings;
+    use sharad_ratatui::narration_cache;
+    use sharad_ratatui::settings::Settings;
+    use std::time::{Duration, SystemTime};
+
+    let speech = SpeechSettings::from_settings(&Settings::default());
+    let key = speech.cache_key(&Voice::Onyx, "Hoi, chummer.");
+    assert_eq!(key, speech.cache_key(&Voice::Onyx, "Hoi, chummer."));
+    assert_ne!(key, speech.cache_key(&Voice::Nova, "Hoi, chummer."));
+    assert_ne!(key, speech.cache_key(&Voice::Onyx, "Hoi, omae."));
+    let faster = SpeechSettings {
+        speed: 1.5,
+        ..speech.clone()
+    };
+    assert_ne!(key, faster.cache_key(&Voice::Onyx, "Hoi, chummer."));
+
+    let dir = std::env::temp_dir().join(format!("sharad_narration_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    assert_eq!(narration_cache::lookup(&dir, &key), None);
+
+    // Three lines of 10 bytes, played an hour, two hours and three hours ago.
+    let now = SystemTime::now();
+    for (name, hours) in [("recent", 1), ("older", 2), ("oldest", 3)] {
+        let path = narration_cache::file_for(&dir, name);
+        std::fs::write(&path, [0u8; 10]).unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(now - Duration::from_secs(hours * 3600))
+            .unwrap();
+    }
+    // Reusing the oldest makes it the most recently played.
+    assert_eq!(
+        narration_cache::lookup(&dir, "oldest"),
+        Some(narration_cache::file_for(&dir, "oldest"))
+    );
+    assert_eq!(narration_cache::evict(&dir, 25).unwrap(), 1);
+    assert!(narration_cache::file_for(&dir, "oldest").exists());
+    assert!(narration_cache::file_for(&dir, "recent").exists());
+    assert!(!narration_cache::file_for(&dir, "older").exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_narrated_dialogue_lines_are_found_in_the_transcript() {
+    use sharad_ratatui::message::{Message, MessageType};
+    use sharad_ratatui::transcript::{fingerprint, TranscriptCache};
+
+    let game = Message::new(
+        MessageType::Game,
+        r#"{"crunch": "", "fluff": {"speakers": [{"index": 0, "name": "Narrator", "gender": "NonBinary"}, {"index": 1, "name": "Fixer", "gender": "Female"}], "dialogue": [{"speaker_index": 0, "text": "The rain never stops in the Barrens."}, {"speaker_index": 1, "text": "You're late, chummer."}]}}"#.to_string(),
+    );
+    let messages = vec![
+        Message::new(MessageType::System, "Welcome to Seattle.".to_string()),
+        game.clone(),
+    ];
+    let mut cache = TranscriptCache::new();
+    cache.update(&messages, 20, false);
+
+    let spoken = |line: usize| -> Vec<String> {
+        let lines = cache.dialogue_line(fingerprint(&game), line).unwrap();
+        cache
+            .lines(lines.start, lines.len())
+            .iter()
+            .map(|line| line.to_string().trim().to_string())
+            .collect()
+    };
+    assert_eq!(spoken(0), ["The rain never", "stops in the", "Barrens."]);
+    assert_eq!(spoken(1), ["Fixer:  You're late,", "chummer."]);
+    assert_eq!(cache.dialogue_line(fingerprint(&game), 2), None);
+    assert_eq!(cache.dialogue_line(fingerprint(&messages[0]), 0), None);
+}
+
+#[test]
+fn test_audio_devices_cycle_and_fall_back_to_the_default() {
+    use sharad_ratatui::audio::next_device;
+    use sharad_ratatui::settings::Settings;
+
+    let devices = ["USB Headset".to_string(), "Built-in Speakers".to_string()];
+    assert_eq!(
+        next_device(&devices, None, true).as_deref(),
+        Some("USB Headset")
+    );
+    assert_eq!(
+        next_device(&devices, Some("USB Headset"), true).as_deref(),
+        Some("Built-in Speakers")
+    );
+    assert_eq!(next_device(&devices, Some("Built-in Speakers"), true), None);
+    assert_eq!(
+        next_device(&devices, None, false).as_deref(),
+        Some("Built-in Speakers")
+    );
+    // A device that was unplugged counts as the default.
+    assert_eq!(
+        next_device(&devices, Some("Old Webcam"), true).as_deref(),
+        Some("USB Headset")
+    );
+    assert_eq!(next_device(&[], None, true), None);
+
+    let settings: Settings = serde_json::from_str(
+        r#"{"language": "English", "openai_api_key": null, "audio_output_enabled": true,
+            "audio_input_enabled": true, "debug_mode": false}"#,
+    )
+    .unwrap();
+    assert_eq!(settings.input_device, None);
+    assert_eq!(settings.output_device, None);
+}
+
+#[test]
+fn test_each_recording_gets_a_file_of_its_own() {
+    use sharad_ratatui::paths;
+
+    let first = paths::new_recording_file();
+    let second = paths::new_recording_file();
+    assert_ne!(first, second);
+    assert_eq!(first.parent(), Some(paths::recordings_dir().as_path()));
+    assert_eq!(first.extension().and_then(|e| e.to_str()), Some("wav"));
+}
+
+#[test]
+fn test_dice_popup_settles_left_to_right() {
+    use sharad_ratatui::commands::describe_roll;
+    use sharad_ratatui::dice::ShownRoll;
+    use sharad_ratatui::ui::dice::{DiceAnimation, SHOWN, TUMBLE};
+    use std::time::Duration;
+
+    let roll = ShownRoll {
+        title: "Raven: Agility + Pistols".to_string(),
+        dice_results: vec![6, 2, 1, 5],
+        hits: 2,
+        glitch: false,
+        critical_glitch: false,
+        total: None,
+    };
+    assert_eq!(
+        describe_roll(&roll),
+        "Raven: Agility + Pistols: 2 hits [6 2 1 5]"
+    );
+    let glitched = ShownRoll {
+        hits: 0,
+        glitch: true,
+        critical_glitch: true,
+        ..roll.clone()
+    };
+    assert_eq!(glitched.summary(), "0 hits - critical glitch!");
+
+    let animation = DiceAnimation::new(roll.clone(), true);
+    let faces = animation.faces(Duration::ZERO);
+    assert!(faces
+        .iter()
+        .all(|(face, settled)| (1..=6).contains(face) && !settled));
+    // Halfway through, the first two dice show their results and the others still tumble.
+    let halfway = animation.faces(TUMBLE / 2);
+    assert_eq!(&halfway[..2], &[(6, true), (2, true)]);
+    assert!(halfway[2..].iter().all(|(_, settled)| !settled));
+    assert!(!animation.settled(TUMBLE / 2));
+    assert_eq!(
+        animation.faces(TUMBLE),
+        [(6, true), (2, true), (1, true), (5, true)]
+    );
+    assert!(!animation.finished(TUMBLE));
+    assert!(animation.finished(TUMBLE + SHOWN));
+
+    // With Dice Animation off the results are there from the start.
+    let instant = DiceAnimation::new(roll, false);
+    assert!(instant.settled(Duration::ZERO));
+    assert_eq!(instant.faces(Duration::ZERO)[0], (6, true));
+    assert!(instant.finished(SHOWN));
+}
+
+#[test]
+fn test_glitches_need_half_the_pool_to_come_up_ones() {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use sharad_ratatui::dice::{dice_roll_with, is_glitch, reports_glitch, EdgeAction};
+
+    // Half is enough, rounding up for odd pools; no dice, no glitch.
+    assert!(is_glitch(2, 4));
+    assert!(!is_glitch(1, 4));
+    assert!(is_glitch(3, 5));
+    assert!(!is_glitch(2, 5));
+    assert!(is_glitch(1, 1));
+    assert!(!is_glitch(0, 0));
+
+    let mut rng = StdRng::seed_from_u64(7);
+    let mut glitches = 0;
+    for pool in (1..=6).cycle().take(600) {
+        let roll = dice_roll_with(&mut rng, pool, None, None, None);
+        // Without Edge every die that is not a six is the last result it gives.
+        let ones = roll.dice_results.iter().filter(|die| **die == 1).count();
+        assert_eq!(roll.glitch, is_glitch(ones, pool as usize));
+        assert_eq!(roll.critical_glitch, roll.glitch && roll.hits == 0);
+        glitches += roll.glitch as usize;
+    }
+    assert!(glitches > 0);
+
+    // Extra dice from Edge make the pool bigger, so a lone one on two base dice is no glitch.
+    for _ in 0..200 {
+        let roll = dice_roll_with(&mut rng, 2, None, None, Some(EdgeAction::AddExtraDice(4)));
+        let ones = roll.dice_results.iter().filter(|die| **die == 1).count();
+        assert_eq!(roll.glitch, is_glitch(ones, 6));
+    }
+
+    assert!(reports_glitch(
+        "Rolled 3 dice: 0 hits [1 1 3] - critical glitch!"
+    ));
+    assert!(reports_glitch("Raven: Agility + Pistols: 1 hit - glitch!"));
+    assert!(!reports_glitch("Rolled 3 dice: 2 hits [5 6 3]"));
+}
+
+#[test]
+fn test_random_sheet_updates_keep_the_sheet_consistent() {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use sharad_ratatui::archetype::ARCHETYPES;
+    use sharad_ratatui::character::{
+        AttributeKey, CharacterSheetUpdate, Contact, Item, MatrixAttributes, Quality, Race, Skills,
+        UpdateOperation, Value,
+    };
+    use std::collections::HashMap;
+
+    const ATTRIBUTES: [&str; 26] = [
+        "name",
+        "race",
+        "gender",
+        "backstory",
+        "main",
+        "body",
+        "agility",
+        "reaction",
+        "strength",
+        "willpower",
+        "logic",
+        "intuition",
+        "charisma",
+        "edge",
+        "magic",
+        "resonance",
+        "skills",
+        "knowledge_skills",
+        "nuyen",
+        "lifestyle",
+        "contacts",
+        "qualities",
+        "cyberware",
+        "bioware",
+        "inventory",
+        "matrix_attributes",
+    ];
+    const NAMES: [&str; 3] = ["Medkit", "Ammo", "Stim Patch"];
+
+    fn names(rng: &mut StdRng) -> Vec<String> {
+        (0..rng.gen_range(0..=2))
+            .map(|_| NAMES[rng.gen_range(0..NAMES.len())].to_string())
+            .collect()
+    }
+    fn ratings(rng: &mut StdRng) -> HashMap<String, u8> {
+        names(rng)
+            .into_iter()
+            .map(|name| (name, rng.gen()))
+            .collect()
+    }
+    // A value of the type the attribute takes.
+    fn value_for(rng: &mut StdRng, attribute: &str) -> Value {
+        match attribute {
+            "name" | "gender" | "backstory" | "lifestyle" => {
+                Value::String(NAMES[rng.gen_range(0..NAMES.len())].to_string())
+            }
+            "race" => Value::Race(
+                [Race::Human, Race::Elf, Race::Dwarf, Race::Ork, Race::Troll][rng.gen_range(0..5)]
+                    .clone(),
+            ),
+            "magic" | "resonance" => Value::OptionU8(rng.gen_bool(0.5).then(|| rng.gen())),
+            "skills" => Value::Skills(Skills {
+                combat: ratings(rng),
+                physical: ratings(rng),
+                social: ratings(rng),
+                technical: ratings(rng),
+            }),
+            "knowledge_skills" => Value::HashMapStringU8(ratings(rng)),
+            "nuyen" => Value::U32(rng.gen()),
+            "contacts" => Value::HashMapStringContact(
+                names(rng)
+                    .into_iter()
+                    .map(|name| {
+                        let contact = Contact {
+                            name: name.clone(),
+                            description: String::new(),
+                            loyalty: rng.gen(),
+                            connection: rng.gen(),
+                        };
+                        (name, contact)
+                    })
+                    .collect(),
+            ),
+            "qualities" => Value::VecQuality(
+                names(rng)
+                    .into_iter()
+                    .map(|name| Quality {
+                        name,
+                        positive: rng.gen(),
+                    })
+                    .collect(),
+            ),
+            "cyberware" | "bioware" => Value::VecString(names(rng)),
+            "inventory" => Value::HashMapStringItem(
+                names(rng)
+                    .into_iter()
+                    .map(|name| {
+                        // Quantities from none to the most a u32 holds.
+                        let quantity = match rng.gen_range(0..4) {
+                            0 => 0,
+                            1 => u32::MAX,
+                            _ => rng.gen_range(1..10),
+                        };
+                        let item = Item {
+                            name: name.clone(),
+                            quantity,
+                            description: String::new(),
+                            ..Default::default()
+                        };
+                        (name, item)
+                    })
+                    .collect(),
+            ),
+            "matrix_attributes" => {
+                Value::OptionMatrixAttributes(rng.gen_bool(0.5).then(|| MatrixAttributes {
+                    attack: rng.gen(),
+                    sleaze: rng.gen(),
+                    data_processing: rng.gen(),
+                    firewall: rng.gen(),
+                }))
+            }
+            _ => Value::U8(rng.gen()),
+        }
+    }
+
+    let mut rng = StdRng::seed_from_u64(3387);
+    for archetype in ARCHETYPES.iter() {
+        let mut sheet = archetype.builder().build();
+        for _ in 0..300 {
+            let attribute = ATTRIBUTES[rng.gen_range(0..ATTRIBUTES.len())];
+            // Now and then a value meant for another attribute, which should be refused.
+            let value = if rng.gen_bool(0.1) {
+                let other = ATTRIBUTES[rng.gen_range(0..ATTRIBUTES.len())];
+                value_for(&mut rng, other)
+            } else {
+                value_for(&mut rng, attribute)
+            };
+            let removed: Vec<String> = match &value {
+                Value::HashMapStringItem(items) => items.keys().cloned().collect(),
+                _ => Vec::new(),
+            };
+            let operation = match rng.gen_range(0..3) {
+                0 => UpdateOperation::Add(value),
+                1 => UpdateOperation::Remove(value),
+                _ => UpdateOperation::Modify(value),
+            };
+            let removing_inventory =
+                attribute == "inventory" && matches!(operation, UpdateOperation::Remove(_));
+            let before = sheet.clone();
+
+            let result = sheet.apply_update(CharacterSheetUpdate::UpdateAttribute {
+                attribute: AttributeKey::parse(attribute).unwrap(),
+                operation,
+            });
+
+            if result.is_err() {
+                assert_eq!(
+                    serde_json::to_value(&sheet).unwrap(),
+                    serde_json::to_value(&before).unwrap(),
+                    "a refused update to {} changed the sheet",
+                    attribute
+                );
+            }
+            assert!(
+                sheet.inventory.values().all(|item| item.quantity > 0),
+                "an item with no quantity was kept after updating {}",
+                attribute
+            );
+            let mut derived = sheet.clone();
+            derived.update_derived_attributes();
+            assert_eq!(
+                serde_json::to_value(&derived).unwrap(),
+                serde_json::to_value(&sheet).unwrap(),
+                "derived attributes are stale after updating {}",
+                attribute
+            );
+            if removing_inventory {
+                for (name, item) in &before.inventory {
+                    if !removed.contains(name) {
+                        assert_eq!(sheet.inventory[name].quantity, item.quantity);
+                    }
+                }
+                for name in removed
+                    .iter()
+                    .filter(|name| !before.inventory.contains_key(*name))
+                {
+                    assert!(!sheet.inventory.contains_key(name));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_attribute_names_are_checked_when_parsed() {
+    use sharad_ratatui::character::{AttributeKey, CharacterSheetUpdate};
+    use sharad_ratatui::tool_args::{self, BasicAttributesArgs};
+
+    for name in ["body", "knowledge_skills", "matrix_attributes", "race"] {
+        let attribute = AttributeKey::parse(name).unwrap();
+        assert_eq!(attribute.to_string(), name);
+        assert_eq!(serde_json::to_value(attribute).unwrap(), name);
+    }
+    assert!(AttributeKey::parse("essence").is_err());
+
+    let error = tool_args::parse::<BasicAttributesArgs>(
+        "update_basic_attributes",
+        r#"{"character_name": "Raven", "updates": {"body": 5, "essence": 6}}"#,
+    )
+    .unwrap_err();
+    assert!(error.to_string().contains("essence"), "{}", error);
+
+    // A race comes before the attributes its modifiers touch.
+    let args: BasicAttributesArgs = tool_args::parse(
+        "update_basic_attributes",
+        r#"{"character_name": "Raven", "updates": {"body": 5, "race": "Troll"}}"#,
+    )
+    .unwrap();
+    let order: Vec<AttributeKey> = args.updates.keys().copied().collect();
+    assert_eq!(order, vec![AttributeKey::Race, AttributeKey::Body]);
+
+    // A held update saved with an attribute the sheet does not have no longer loads.
+    assert!(serde_json::from_str::<CharacterSheetUpdate>(
+        r#"{"UpdateAttribute": {"attribute": "essence", "operation": {"Modify": 6}}}"#
+    )
+    .is_err());
+}
+
+#[test]
+fn test_game_state_is_the_fold_of_its_events() {
+    use sharad_ratatui::archetype::ARCHETYPES;
+    use sharad_ratatui::character::{AttributeKey, CharacterSheetUpdate, UpdateOperation, Value};
+    use sharad_ratatui::events::{self, GameEvent};
+    use sharad_ratatui::journal::{self, Journal};
+    use sharad_ratatui::message::MessageType;
+
+    let dir = std::env::temp_dir().join(format!("sharad_events_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let sheet = ARCHETYPES[0].builder().build();
+    let name = sheet.name.clone();
+    let spend = |nuyen| CharacterSheetUpdate::UpdateAttribute {
+        attribute: AttributeKey::Nuyen,
+        operation: UpdateOperation::Remove(Value::U32(nuyen)),
+    };
+
+    let mut state = GameState::new("Run");
+    let mut journal = Journal::new(&dir, "Run");
+    state
+        .record(GameEvent::Thread {
+            assistant_id: "asst".to_string(),
+            thread_id: "thread".to_string(),
+        })
+        .unwrap();
+    state
+        .record(GameEvent::MainCharacter {
+            sheet: Some(sheet.clone()),
+        })
+        .unwrap();
+    state.record(GameEvent::Character { sheet }).unwrap();
+    journal.record(&state).unwrap();
+    let nuyen = state.characters[0].nuyen;
+    for text in ["I buy a medkit", "It costs 500"] {
+        state
+            .record(GameEvent::Message {
+                sender: MessageType::User,
+                text: text.to_string(),
+            })
+            .unwrap();
+    }
+    state
+        .record(GameEvent::SheetUpdate {
+            character: name.clone(),
+            update: spend(500),
+        })
+        .unwrap();
+    // An update for someone not in the game is refused and left out of the stream.
+    assert!(state
+        .record(GameEvent::SheetUpdate {
+            character: "Nobody".to_string(),
+            update: spend(1),
+        })
+        .is_err());
+    assert_eq!(state.events.len(), 6);
+    journal.record(&state).unwrap();
+
+    assert_eq!(state.characters[0].nuyen, nuyen - 500);
+    assert_eq!(
+        state.main_character_sheet.as_ref().unwrap().nuyen,
+        nuyen - 500
+    );
+    assert_eq!(state.archive.uncovered, None);
+    let folded = events::fold("Run", &state.events);
+    assert_eq!(
+        serde_json::to_value(&folded).unwrap(),
+        serde_json::to_value(&state).unwrap()
+    );
+    // Undoing the purchase is folding one event fewer.
+    assert_eq!(state.as_of(5).characters[0].nuyen, nuyen);
+
+    // The journal carries the events, and loading folds them back into the same game.
+    let (_, loaded) = Journal::load(&dir, "Run").unwrap();
+    assert_eq!(
+        serde_json::to_value(&loaded).unwrap(),
+        serde_json::to_value(&state).unwrap()
+    );
+
+    // A compaction that stops after the snapshot is replaced leaves the journal behind, and
+    // loading doesn't replay it onto the snapshot that already has it.
+    let leftover = fs::read(journal::path_for(&dir, "Run")).unwrap();
+    journal.compact(&state).unwrap();
+    fs::write(journal::path_for(&dir, "Run"), leftover).unwrap();
+    let (mut journal, loaded) = Journal::load(&dir, "Run").unwrap();
+    assert_eq!(loaded.events.len(), 6);
+    assert_eq!(loaded.characters[0].nuyen, nuyen - 500);
+
+    // A stream cut short and added to again, back to the same length, is seen as replaced.
+    let mut undone = state.as_of(5);
+    undone
+        .record(GameEvent::Message {
+            sender: MessageType::User,
+            text: "I keep my nuyen".to_string(),
+        })
+        .unwrap();
+    journal.record(&undone).unwrap();
+    let (_, loaded) = Journal::load(&dir, "Run").unwrap();
+    assert_eq!(loaded.events.len(), 6);
+    assert_eq!(loaded.characters[0].nuyen, nuyen);
+
+    // A save from before events starts its stream with how it was.
+    let mut old = state.clone();
+    old.events.clear();
+    fs::write(
+        journal::snapshot_path(&dir, "Old"),
+        serde_json::to_string(&old).unwrap(),
+    )
+    .unwrap();
+    let (_, loaded) = Journal::load(&dir, "Old").unwrap();
+    assert!(matches!(
+        loaded.events[..],
+        [sharad_ratatui::events::RecordedEvent {
+            event: GameEvent::Snapshot { .. },
+            ..
+        }]
+    ));
+    assert_eq!(loaded.as_of(1).characters[0].nuyen, nuyen - 500);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_script_plugins_are_offered_and_called() {
+    use async_openai::types::{FunctionCall, RunToolCallObject};
+    use sharad_ratatui::ai::GameAI;
+    use sharad_ratatui::plugins;
+
+    let dir = std::env::temp_dir().join(format!("sharad_plugins_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    // The script answers with the arguments it was given.
+    fs::write(
+        dir.join("gear_catalog.json"),
+        r#"{
+  "name": "test_gear_catalog",
+  "description": "Look up the street price of a piece of gear",
+  "parameters": {"type": "object", "properties": {"item": {"type": "string"}}},
+  "command": ["sh", "-c", "cat"]
+}"#,
+    )
+    .unwrap();
+    // Built-in tools keep their names.
+    fs::write(
+        dir.join("dice.json"),
+        r#"{"name": "perform_dice_roll", "description": "", "parameters": {}, "command": ["true"]}"#,
+    )
+    .unwrap();
+    fs::write(dir.join("broken.json"), "{").unwrap();
+
+    assert_eq!(plugins::load_scripts(&dir), vec!["test_gear_catalog"]);
+    assert!(plugins::definitions()
+        .iter()
+        .any(|definition| definition.name == "test_gear_catalog"));
+
+    let mut ai = GameAI::new(String::new(), |_| {}).await.unwrap();
+    let mut game_state = GameState::new("plugins");
+    let call = RunToolCallObject {
+        id: "call_1".to_string(),
+        r#type: "function".to_string(),
+        function: FunctionCall {
+            name: "test_gear_catalog".to_string(),
+            arguments: r#"{"item": "Ares Predator"}"#.to_string(),
+        },
+    };
+    let output = ai.handle_tool_call(&call, &mut game_state).await.unwrap();
+    let echoed: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(echoed["arguments"]["item"], "Ares Predator");
+    assert!(echoed["character"].is_null());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_dice_expressions_parse_and_roll() {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use sharad_ratatui::commands::describe_roll;
+    use sharad_ratatui::dice_expr::DiceExpr;
+
+    let pool: DiceExpr = "12d6!>=5 limit 6".parse().unwrap();
+    assert_eq!(
+        pool,
+        DiceExpr {
+            count: 12,
+            sides: 6,
+            explode: true,
+            target: Some(5),
+            modifier: 0,
+            limit: Some(6),
+        }
+    );
+    assert_eq!(pool.to_string(), "12d6!>=5 limit 6");
+    let sum: DiceExpr = " 2D6 - 3 ".parse().unwrap();
+    assert_eq!((sum.count, sum.sides, sum.modifier), (2, 6, -3));
+    assert_eq!(sum.to_string(), "2d6-3");
+    assert_eq!("d20".parse::<DiceExpr>().unwrap().count, 1);
+    assert_eq!("4d6>4".parse::<DiceExpr>().unwrap().target, Some(5));
+
+    // Errors say what was expected and where.
+    for (text, position, message) in [
+        ("12", 2, "expected 'd'"),
+        ("2d", 2, "expected the number of sides after 'd'"),
+        ("0d6", 0, "the number of dice must be from 1 to 100"),
+        ("2d6>=7", 5, "a target must be from 1 to 6"),
+        ("2d6!!", 4, "'!' is given twice"),
+        ("2d6 x", 4, "unexpected 'x'"),
+        ("12d6>=5+1", 7, "can't be combined"),
+        ("2d6 limit 3", 4, "needs a target"),
+    ] {
+        let error = text.parse::<DiceExpr>().unwrap_err();
+        assert_eq!(error.position, position, "{}: {}", text, error);
+        assert!(error.message.contains(message), "{}: {}", text, error);
+    }
+
+    let mut rng = StdRng::seed_from_u64(3392);
+    for _ in 0..200 {
+        let roll = sum.roll_with(&mut rng, sum.to_string());
+        let dice: i32 = roll.dice_results.iter().map(|die| *die as i32).sum();
+        assert_eq!(roll.dice_results.len(), 2);
+        assert_eq!(roll.total, Some(dice - 3));
+
+        let roll = pool.roll_with(&mut rng, pool.to_string());
+        let hits = roll.dice_results.iter().filter(|die| **die >= 5).count();
+        assert_eq!(roll.hits as usize, hits.min(6));
+        assert_eq!(roll.total, None);
+        // Only a six adds a die.
+        let sixes = roll.dice_results.iter().filter(|die| **die == 6).count();
+        assert_eq!(roll.dice_results.len(), 12 + sixes);
+    }
+
+    let roll = sum.roll_with(&mut rng, "Rolled 2d6-3".to_string());
+    assert!(describe_roll(&roll).starts_with("Rolled 2d6-3: total "));
+}
+
+#[test]
+fn test_campaigns_keep_the_ruleset_they_were_created_with() {
+    use sharad_ratatui::archetype::ARCHETYPES;
+    use sharad_ratatui::events::GameEvent;
+    use sharad_ratatui::ruleset::RulesetKind;
+
+    let sheet = ARCHETYPES[0].builder().build();
+    let limits = sheet.limits().expect("5th edition sheets have limits");
+    assert_eq!(
+        RulesetKind::Sr5.rules().limit(&sheet, "physical"),
+        Some(limits.0)
+    );
+    assert_eq!(RulesetKind::Sr6.rules().limit(&sheet, "physical"), None);
+
+    let mut state = GameState::new("Sixth World");
+    state
+        .record(GameEvent::Ruleset {
+            ruleset: RulesetKind::Sr6,
+        })
+        .unwrap();
+    state
+        .record(GameEvent::MainCharacter {
+            sheet: Some(sheet.clone()),
+        })
+        .unwrap();
+    state
+        .record(GameEvent::Character {
+            sheet: sheet.clone(),
+        })
+        .unwrap();
+    assert_eq!(state.characters[0].limits(), None);
+    assert_eq!(state.main_character_sheet.as_ref().unwrap().limits(), None);
+    assert_eq!(state.characters[0].physical_monitor, sheet.physical_monitor);
+
+    // The ruleset is saved with the campaign, and saves from before rulesets play by 5th edition.
+    let saved: GameState = serde_json::from_str(&serde_json::to_string(&state).unwrap()).unwrap();
+    assert_eq!(saved.ruleset, RulesetKind::Sr6);
+    let mut old = serde_json::to_value(&state).unwrap();
+    old.as_object_mut().unwrap().remove("ruleset");
+    let old: GameState = serde_json::from_value(old).unwrap();
+    assert_eq!(old.ruleset, RulesetKind::Sr5);
+}
+
+#[tokio::test]
+async fn test_freeform_sheets_are_kept_as_entries() {
+    use async_openai::types::{FunctionCall, RunToolCallObject};
+    use sharad_ratatui::ai::GameAI;
+    use sharad_ratatui::assistant::builtin_tool_names;
+    use sharad_ratatui::events::GameEvent;
+    use sharad_ratatui::ruleset::{RulesetKind, FREEFORM_TOOLS};
+
+    let builtin = builtin_tool_names();
+    assert!(FREEFORM_TOOLS
+        .iter()
+        .all(|tool| builtin.contains(&tool.to_string())));
+    let rules = RulesetKind::Freeform.rules();
+    assert!(!rules.offers_tool("perform_dice_roll"));
+    assert!(!rules.shadowrun_sheet());
+
+    let mut ai = GameAI::new(String::new(), |_| {}).await.unwrap();
+    let mut game_state = GameState::new("Freeform");
+    game_state
+        .record(GameEvent::Ruleset {
+            ruleset: RulesetKind::Freeform,
+        })
+        .unwrap();
+    let call = |arguments: &str| RunToolCallObject {
+        id: "call_1".to_string(),
+        r#type: "function".to_string(),
+        function: FunctionCall {
+            name: "update_sheet_fields".to_string(),
+            arguments: arguments.to_string(),
+        },
+    };
+
+    // The first update creates the player's character.
+    ai.handle_tool_call(
+        &call(r#"{"character_name": "Mira", "fields": {"Class": "Ranger", "Torch": "lit"}, "main_character": true}"#),
+        &mut game_state,
+    )
+    .await
+    .unwrap();
+    ai.handle_tool_call(
+        &call(r#"{"character_name": "Mira", "fields": {"Hit Points": "12/15", "Torch": null}}"#),
+        &mut game_state,
+    )
+    .await
+    .unwrap();
+
+    let sheet = game_state.main_character_sheet.as_ref().unwrap();
+    assert_eq!(
+        commands::fields_summary(sheet),
+        "Mira | Class: Ranger | Hit Points: 12/15"
+    );
+    assert_eq!(game_state.characters.len(), 1);
+    assert_eq!(sheet.limits(), None);
+    assert_eq!(sheet.physical_monitor, 0);
+}
+
+#[test]
+fn test_play_time_and_turns_add_up_over_sessions() {
+    use sharad_ratatui::events::GameEvent;
+    use sharad_ratatui::game_state::{play_time, PlayStats};
+    use sharad_ratatui::message::MessageType;
+
+    let mut state = GameState::new("Run");
+    for (sender, text) in [
+        (MessageType::User, "I look around"),
+        (MessageType::Game, "A dim bar."),
+        (MessageType::User, "I order a drink"),
+    ] {
+        state
+            .record(GameEvent::Message {
+                sender,
+                text: text.to_string(),
+            })
+            .unwrap();
+    }
+    state.record(GameEvent::Played { seconds: 1500 }).unwrap();
+    let first_session = state.stats;
+    state.record(GameEvent::Played { seconds: 9000 }).unwrap();
+
+    assert_eq!(
+        state.stats,
+        PlayStats {
+            seconds: 10500,
+            turns: 2
+        }
+    );
+    // Replaying the save gives the same totals.
+    assert_eq!(state.as_of(state.events.len()).stats, state.stats);
+    assert_eq!(state.stats.describe(), "2h 55m over 2 turns");
+    assert_eq!(
+        state.stats.since(first_session).describe(),
+        "2h 30m over 0 turns"
+    );
+    assert_eq!(play_time(59), "0m");
+}
+
+#[test]
+fn test_campaign_stats_count_rolls_money_and_npcs() {
+    use sharad_ratatui::archetype::ARCHETYPES;
+    use sharad_ratatui::campaign_stats;
+    use sharad_ratatui::character::{AttributeKey, CharacterSheetUpdate, UpdateOperation, Value};
+    use sharad_ratatui::dice::ShownRoll;
+    use sharad_ratatui::events::GameEvent;
+
+    let mut state = GameState::new("Run");
+    let runner = ARCHETYPES[0].builder().build();
+    let mut fixer = ARCHETYPES[1].builder().build();
+    fixer.name = "Fixer".to_string();
+    state
+        .record(GameEvent::MainCharacter {
+            sheet: Some(runner.clone()),
+        })
+        .unwrap();
+    state
+        .record(GameEvent::Character {
+            sheet: runner.clone(),
+        })
+        .unwrap();
+    state.record(GameEvent::Character { sheet: fixer }).unwrap();
+    let roll = |hits, total| ShownRoll {
+        title: "Roll".to_string(),
+        dice_results: vec![5; 8],
+        hits,
+        glitch: false,
+        critical_glitch: false,
+        total,
+    };
+    for roll in [roll(2, None), roll(9, None), roll(0, Some(7))] {
+        state.record(GameEvent::Roll { roll }).unwrap();
+    }
+    for operation in [
+        UpdateOperation::Add(Value::U32(1000)),
+        UpdateOperation::Remove(Value::U32(300)),
+    ] {
+        state
+            .record(GameEvent::SheetUpdate {
+                character: runner.name.clone(),
+                update: CharacterSheetUpdate::UpdateAttribute {
+                    attribute: AttributeKey::Nuyen,
+                    operation,
+                },
+            })
+            .unwrap();
+    }
+
+    let stats = campaign_stats::compute(&state);
+    assert_eq!(stats.rolls, 3);
+    // The 2d6+7 style roll adds its dice up, so it has no hits to count.
+    assert_eq!(stats.hit_rolls, 2);
+    assert_eq!(stats.average_hits(), Some(5.5));
+    assert_eq!(stats.hit_counts, [0, 0, 1, 0, 0, 0, 1]);
+    // The runner's starting nuyen is not counted as earned.
+    assert_eq!((stats.nuyen_earned, stats.nuyen_spent), (1000, 300));
+    assert_eq!(stats.npcs_met, 1);
+}
+
+#[tokio::test]
+async fn test_shop_purchases_are_sheet_updates_the_assistant_hears_about() {
+    use sharad_ratatui::ai::{GameAI, GameConversationState};
+    use sharad_ratatui::archetype::ARCHETYPES;
+    use sharad_ratatui::backend::MockBackend;
+    use sharad_ratatui::events::GameEvent;
+    use sharad_ratatui::shop::{self, Catalog, ShopError};
+    use std::sync::Arc;
+
+    let catalog = Catalog::builtin();
+    let item = |name: &str| {
+        catalog
+            .items
+            .iter()
+            .find(|item| item.name.starts_with(name))
+            .unwrap()
+            .clone()
+    };
+    let medkit = item("Medkit");
+    let mut sheet = ARCHETYPES[0].builder().build();
+    sheet.nuyen = 2000;
+    let mut state = GameState::new("Run");
+    state
+        .record(GameEvent::MainCharacter {
+            sheet: Some(sheet.clone()),
+        })
+        .unwrap();
+    state
+        .record(GameEvent::Character {
+            sheet: sheet.clone(),
+        })
+        .unwrap();
+
+    for update in shop::purchase(&medkit, 1, &sheet).unwrap() {
+        state
+            .record(GameEvent::SheetUpdate {
+                character: sheet.name.clone(),
+                update,
+            })
+            .unwrap();
+    }
+    let bought = state.main_character_sheet.clone().unwrap();
+    assert_eq!(bought.nuyen, 500);
+    assert_eq!(bought.inventory[&medkit.name].quantity, 1);
+
+    assert_eq!(
+        shop::purchase(&medkit, 1, &bought).unwrap_err(),
+        ShopError::NotEnoughNuyen {
+            item: medkit.name.clone(),
+            character: bought.name.clone(),
+            cost: 1500,
+            nuyen: 500,
+        }
+    );
+    let rare = shop::GearItem {
+        availability: shop::MAX_AVAILABILITY + 4,
+        ..item("Survival Knife")
+    };
+    assert!(matches!(
+        shop::purchase(&rare, 1, &bought),
+        Err(ShopError::Unavailable { .. })
+    ));
+
+    // The assistant is told in its thread, without a run.
+    let backend = Arc::new(MockBackend::new([]));
+    let ai = GameAI::with_backend(backend.clone(), |_| {});
+    ai.start_new_conversation(
+        "asst_mock",
+        GameConversationState {
+            assistant_id: "asst_mock".to_string(),
+            thread_id: String::new(),
+            character_sheet: None,
+        },
+    )
+    .await
+    .unwrap();
+    let (thread_id, _) = ai.get_conversation_ids().await.unwrap();
+    let note = shop::purchase_note(&bought.name, &medkit, 1);
+    ai.tell(&note, &mut state).await.unwrap();
+    assert!(backend
+        .thread(&thread_id)
+        .iter()
+        .any(|message| message.content == note));
+    assert_eq!(state.stats.turns, 0);
+}
+
+#[test]
+fn test_equipped_gear_feeds_armor_and_weapon_dice() {
+    use sharad_ratatui::character::{CharacterSheetUpdate, Item, ItemState};
+    use sharad_ratatui::events::GameEvent;
+
+    let mut sheet = CharacterSheetBuilder::new(
+        "Raven".to_string(),
+        Race::Human,
+        "Female".to_string(),
+        "A street samurai".to_string(),
+        true,
+    )
+    .build();
+    sheet.strength = 4;
+    sheet.inventory.insert(
+        "Lined Coat".to_string(),
+        Item::new("Lined Coat", 1, "Armor 9, a long coat."),
+    );
+    sheet.inventory.insert(
+        "Armor Jacket".to_string(),
+        Item {
+            armor: Some(12),
+            ..Item::new("Armor Jacket", 1, "Heavy, with plates sewn in.")
+        },
+    );
+    sheet.inventory.insert(
+        "HK-227".to_string(),
+        Item {
+            recoil_compensation: Some(1),
+            ..Item::new("HK-227", 1, "Submachine gun.")
+        },
+    );
+    sheet.inventory.insert(
+        "Stun Baton".to_string(),
+        Item {
+            reach: Some(1),
+            ..Item::new("Stun Baton", 1, "Electrified.")
+        },
+    );
+    // An item saved before items had a state is carried.
+    let old: Item =
+        serde_json::from_str(r#"{"name": "Medkit", "quantity": 1, "description": ""}"#).unwrap();
+    assert_eq!(old.state, ItemState::Carried);
+
+    let mut state = GameState::new("Run");
+    state
+        .record(GameEvent::MainCharacter {
+            sheet: Some(sheet.clone()),
+        })
+        .unwrap();
+    state.record(GameEvent::Character { sheet }).unwrap();
+    let equip = |state: &mut GameState, item: &str, item_state| {
+        state
+            .record(GameEvent::SheetUpdate {
+                character: "Raven".to_string(),
+                update: CharacterSheetUpdate::SetItemState {
+                    item: item.to_string(),
+                    state: item_state,
+                },
+            })
+            .unwrap();
+    };
+    let raven = |state: &GameState| state.main_character_sheet.clone().unwrap();
+    assert_eq!(raven(&state).armor, 0);
+    equip(&mut state, "Lined Coat", ItemState::Equipped);
+    assert_eq!(raven(&state).armor, 9);
+    // Armor doesn't stack, the best piece counts.
+    equip(&mut state, "Armor Jacket", ItemState::Equipped);
+    assert_eq!(raven(&state).armor, 12);
+    equip(&mut state, "Armor Jacket", ItemState::Stashed);
+    assert_eq!(raven(&state).armor, 9);
+
+    // Only equipped weapons can be used.
+    assert!(raven(&state).weapon_modifier("HK-227", 1).is_err());
+    assert!(raven(&state).weapon_modifier("Monowhip", 1).is_err());
+    equip(&mut state, "HK-227", ItemState::Equipped);
+    equip(&mut state, "Stun Baton", ItemState::Equipped);
+    // Strength 4 and the smg's compensation soak up 1 + 2 + 1 rounds.
+    assert_eq!(raven(&state).weapon_modifier("HK-227", 3), Ok(0));
+    assert_eq!(raven(&state).weapon_modifier("hk-227", 6), Ok(-2));
+    assert_eq!(raven(&state).weapon_modifier("Stun Baton", 1), Ok(1));
+    assert_eq!(
+        state
+            .as_of(state.events.len())
+            .main_character_sheet
+            .unwrap()
+            .armor,
+        9
+    );
+}
+
+#[test]
+fn test_overloaded_characters_lose_agility_and_reaction() {
+    use sharad_ratatui::archetype::ARCHETYPES;
+    use sharad_ratatui::character::{CharacterSheetUpdate, Item, ItemState};
+    use sharad_ratatui::events::GameEvent;
+
+    let mut sheet = ARCHETYPES[0].builder().build();
+    sheet.strength = 3;
+    sheet.body = 3;
+    sheet.agility = 5;
+    sheet.reaction = 4;
+    sheet.intuition = 3;
+    sheet.inventory.clear();
+    sheet.inventory.insert(
+        "Ammo".to_string(),
+        Item {
+            weight: Some(0.5),
+            ..Item::new("Ammo", 20, "Boxes of rounds.")
+        },
+    );
+    sheet.inventory.insert(
+        "Anvil".to_string(),
+        Item {
+            weight: Some(25.0),
+            ..Item::new("Anvil", 1, "Don't ask.")
+        },
+    );
+    let mut state = GameState::new("Run");
+    state
+        .record(GameEvent::MainCharacter {
+            sheet: Some(sheet.clone()),
+        })
+        .unwrap();
+    state.record(GameEvent::Character { sheet }).unwrap();
+    let raven = |state: &GameState| state.main_character_sheet.clone().unwrap();
+
+    // 35 kg against a capacity of 30: one point off agility and reaction.
+    let loaded = raven(&state);
+    assert_eq!(loaded.carry_capacity(), 30.0);
+    assert_eq!(loaded.carried_weight(), 35.0);
+    assert_eq!(loaded.encumbrance_penalty(), 1);
+    assert_eq!(loaded.get_dice_pool("agility", "Nothing"), 4);
+    assert_eq!(loaded.get_dice_pool("reaction", "Nothing"), 3);
+    assert_eq!(loaded.get_dice_pool("body", "Nothing"), 3);
+    assert_eq!(loaded.initiative, (6, 1));
+
+    // Stashed gear is left behind.
+    state
+        .record(GameEvent::SheetUpdate {
+            character: loaded.name.clone(),
+            update: CharacterSheetUpdate::SetItemState {
+                item: "Anvil".to_string(),
+                state: ItemState::Stashed,
+            },
+        })
+        .unwrap();
+    let unloaded = raven(&state);
+    assert_eq!(unloaded.carried_weight(), 10.0);
+    assert_eq!(unloaded.encumbrance_penalty(), 0);
+    assert_eq!(unloaded.get_dice_pool("agility", "Nothing"), 5);
+    assert_eq!(unloaded.initiative, (7, 1));
+}
+
+#[test]
+fn test_augmentations_set_essence_initiative_dice_and_armor() {
+    use sharad_ratatui::archetype::ARCHETYPES;
+    use sharad_ratatui::augmentation;
+    use sharad_ratatui::character::{AttributeKey, CharacterSheetUpdate, UpdateOperation, Value};
+    use sharad_ratatui::events::GameEvent;
+
+    assert_eq!(
+        augmentation::lookup("Wired Reflexes (Rating 2)").map(|stats| stats.initiative_dice),
+        Some(2)
+    );
+    assert_eq!(augmentation::lookup("Chrome Teeth"), None);
+
+    let mut sheet = ARCHETYPES[0].builder().build();
+    sheet.cyberware.clear();
+    sheet.bioware.clear();
+    sheet.inventory.clear();
+    let name = sheet.name.clone();
+    let mut state = GameState::new("Run");
+    state
+        .record(GameEvent::MainCharacter {
+            sheet: Some(sheet.clone()),
+        })
+        .unwrap();
+    state.record(GameEvent::Character { sheet }).unwrap();
+    let augment = |state: &mut GameState, attribute, names: &[&str]| {
+        state
+            .record(GameEvent::SheetUpdate {
+                character: name.clone(),
+                update: CharacterSheetUpdate::UpdateAttribute {
+                    attribute,
+                    operation: UpdateOperation::Add(Value::VecString(
+                        names.iter().map(|name| name.to_string()).collect(),
+                    )),
+                },
+            })
+            .unwrap();
+    };
+    let sheet = |state: &GameState| state.main_character_sheet.clone().unwrap();
+    assert_eq!(sheet(&state).essence, 6.0);
+    assert_eq!(sheet(&state).initiative.1, 1);
+
+    augment(
+        &mut state,
+        AttributeKey::Cyberware,
+        &[
+            "Wired Reflexes 2",
+            "Dermal Plating 2",
+            "Datajack",
+            "Chrome Teeth",
+        ],
+    );
+    let chromed = sheet(&state);
+    assert_eq!(chromed.essence, 1.9);
+    assert_eq!(chromed.initiative.1, 3);
+    assert_eq!(chromed.armor, 2);
+
+    // Initiative dice stop at 5D6, and essence at 0.
+    augment(&mut state, AttributeKey::Bioware, &["Synaptic Booster 3"]);
+    augment(
+        &mut state,
+        AttributeKey::Cyberware,
+        &["Muscle Replacement 4"],
+    );
+    let overdone = sheet(&state);
+    assert_eq!(overdone.initiative.1, 5);
+    assert_eq!(overdone.essence, 0.0);
+}
+
+#[test]
+fn test_damage_resistance_counts_body_and_armor_less_penetration() {
+    use sharad_ratatui::archetype::ARCHETYPES;
+    use sharad_ratatui::damage::{
+        resist_damage, resistance_pool, resolve, DamageResistanceRequest,
+    };
+    use sharad_ratatui::dice::DiceRoll;
+    use sharad_ratatui::events::GameEvent;
+    use sharad_ratatui::ruleset::RulesetKind;
+
+    let request = DamageResistanceRequest::parse("Raven", "6P -2").unwrap();
+    assert_eq!(
+        (request.damage, request.armor_penetration, request.stun),
+        (6, -2, false)
+    );
+    assert!(DamageResistanceRequest::parse("Raven", "4s").unwrap().stun);
+    assert!(DamageResistanceRequest::parse("Raven", "six").is_err());
+    assert!(DamageResistanceRequest::parse("Raven", "").is_err());
+
+    let mut sheet = ARCHETYPES[0].builder().build();
+    sheet.name = "Raven".to_string();
+    let (body, armor) = (sheet.body, sheet.armor);
+    assert!(armor >= 2);
+    let mut state = GameState::new("Soak");
+    state.record(GameEvent::Character { sheet }).unwrap();
+    assert_eq!(
+        resistance_pool(&request, &state),
+        Ok((body + armor - 2, armor - 2))
+    );
+    // Penetration never takes armor below 0.
+    let piercing = DamageResistanceRequest::parse("Raven", "6P -40").unwrap();
+    assert_eq!(resistance_pool(&piercing, &state), Ok((body, 0)));
+    assert!(resistance_pool(
+        &DamageResistanceRequest::parse("Nobody", "6P").unwrap(),
+        &state
+    )
+    .is_err());
+
+    // Each hit takes a box off.
+    let roll = |hits| DiceRoll {
+        hits,
+        glitch: false,
+        critical_glitch: false,
+        critical_success: false,
+        dice_results: vec![],
+    };
+    assert_eq!(resolve(&request, 9, 7, &roll(4)).damage_taken, 2);
+    assert_eq!(resolve(&request, 9, 7, &roll(8)).damage_taken, 0);
+
+    let (shown, response) = resist_damage(&request, &state).unwrap();
+    assert_eq!(response.damage_taken, 6u8.saturating_sub(response.hits));
+    assert!(shown.title.contains("Damage resistance"));
+
+    // Sixth World soaks with Body alone.
+    state
+        .record(GameEvent::Ruleset {
+            ruleset: RulesetKind::Sr6,
+        })
+        .unwrap();
+    assert_eq!(resistance_pool(&request, &state), Ok((body, 0)));
+}
+
+#[test]
+fn test_combat_passes_cost_ten_and_wounds_slow_and_weaken() {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use sharad_ratatui::archetype::ARCHETYPES;
+    use sharad_ratatui::combat::{Combat, PASS_COST};
+    use sharad_ratatui::dice::{perform_dice_roll, DiceRollRequest};
+    use sharad_ratatui::events::GameEvent;
+
+    let mut rng = StdRng::seed_from_u64(7);
+    let mut fast = ARCHETYPES[0].builder().build();
+    fast.name = "Raven".to_string();
+    let mut slow = ARCHETYPES[0].builder().build();
+    slow.name = "Ganger".to_string();
+    let mut combat = Combat::start(&[&fast, &slow], &mut rng);
+    assert_eq!((combat.round, combat.pass), (1, 1));
+    // Fix the scores: Raven acts twice this round, the ganger once.
+    combat.combatants[0].rolled = 15;
+    combat.combatants[1].rolled = 8;
+    combat.current = Some("Raven".to_string());
+
+    let order: Vec<_> = combat.order().iter().map(|c| c.name.clone()).collect();
+    assert_eq!(order, ["Raven", "Ganger"]);
+    combat.next(&mut rng);
+    assert_eq!(combat.current.as_deref(), Some("Ganger"));
+    combat.next(&mut rng);
+    assert_eq!(combat.pass, 2);
+    assert_eq!(combat.current.as_deref(), Some("Raven"));
+    assert_eq!(combat.find("Raven").unwrap().score(2), 15 - PASS_COST);
+    combat.next(&mut rng);
+    assert_eq!((combat.round, combat.pass), (2, 1));
+    assert!(combat.current.is_some());
+
+    // 1 per 3 boxes of each track, off initiative and dice pools, with the environment.
+    assert!(combat.take_damage("Ganger", 4, false));
+    assert!(combat.take_damage("Ganger", 3, true));
+    assert!(!combat.take_damage("Nobody", 3, true));
+    let ganger = combat.find("Ganger").unwrap();
+    assert_eq!(ganger.wound_modifier(), -2);
+    assert_eq!(ganger.score(1), ganger.rolled - 2);
+    combat.environment = -3;
+    assert_eq!(combat.dice_pool_modifier("Ganger"), -5);
+    assert_eq!(combat.dice_pool_modifier("Raven"), -3);
+    assert_eq!(combat.dice_pool_modifier("Bystander"), 0);
+
+    // The tracker is kept by events, and dice pools in the fight take the modifiers.
+    let mut state = GameState::new("Fight");
+    state.record(GameEvent::Character { sheet: slow }).unwrap();
+    combat.environment = -60;
+    state
+        .record(GameEvent::Combat {
+            combat: Some(combat.clone()),
+        })
+        .unwrap();
+    assert_eq!(state.as_of(state.events.len()).combat, Some(combat));
+    let request: DiceRollRequest = serde_json::from_str(
+        r#"{"character_name": "Ganger", "attribute": "agility", "skill": "Pistols", "limit_type": "physical"}"#,
+    )
+    .unwrap();
+    assert!(perform_dice_roll(request, &state)
+        .unwrap()
+        .dice_results
+        .is_empty());
+    state.record(GameEvent::Combat { combat: None }).unwrap();
+    assert!(state.combat.is_none());
+}
+
+#[tokio::test]
+async fn test_house_rules_change_tests_updates_and_derived_attributes() {
+    use sharad_ratatui::archetype::ARCHETYPES;
+    use sharad_ratatui::character::{AttributeKey, CharacterSheetUpdate, UpdateOperation, Value};
+    use sharad_ratatui::dice::{perform_npc_test, NpcTestRequest};
+    use sharad_ratatui::events::GameEvent;
+    use sharad_ratatui::house_rules::{self, HouseRules};
+    use sharad_ratatui::{paths, settings::Settings};
+
+    let _data_dir = DATA_DIR.lock().await;
+    let dir = std::env::temp_dir().join(format!("sharad_house_rules_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("config")).unwrap();
+    Settings {
+        data_dir: Some(dir.join("data")),
+        audio_output_enabled: false,
+        ..Settings::default()
+    }
+    .save_to_file(dir.join("config").join("settings.json").to_str().unwrap())
+    .unwrap();
+    paths::init(Some(dir.join("config")), false);
+
+    let mut guard = ARCHETYPES[0].builder().build();
+    guard.name = "Guard".to_string();
+    guard.skills.technical.insert("Perception".to_string(), 4);
+    let before = guard.clone();
+    // Without a file, the campaign plays by its ruleset alone.
+    let save_name = "house rules run";
+    assert_eq!(*house_rules::for_save(save_name), HouseRules::default());
+
+    fs::create_dir_all(paths::rules_dir()).unwrap();
+    fs::write(
+        house_rules::path_for(save_name),
+        r#"{"dice": {"pool": 2, "limits": false},
+            "derived": {"physical_monitor": 2, "initiative_dice": 1},
+            "sheet": {"max_attribute": 5}}"#,
+    )
+    .unwrap();
+    let mut state = GameState::new(save_name);
+    state.record(GameEvent::Character { sheet: guard }).unwrap();
+    let guard = state.characters[0].clone();
+    assert_eq!(guard.physical_monitor, before.physical_monitor + 2);
+    assert_eq!(guard.initiative.1, before.initiative.1 + 1);
+
+    let (response, _) = perform_npc_test(
+        &NpcTestRequest {
+            npc_name: "Guard".to_string(),
+            test: "Intuition + Perception".to_string(),
+            limit_type: None,
+            threshold: None,
+        },
+        &state,
+    )
+    .unwrap();
+    assert_eq!(response.dice_pool, guard.intuition + 4 + 2);
+
+    // An update can't raise an attribute past the cap.
+    state
+        .record(GameEvent::SheetUpdate {
+            character: "Guard".to_string(),
+            update: CharacterSheetUpdate::UpdateAttribute {
+                attribute: AttributeKey::Body,
+                operation: UpdateOperation::Modify(Value::U8(8)),
+            },
+        })
+        .unwrap();
+    assert_eq!(state.characters[0].body, 5);
+
+    // A file that can't be read is played without.
+    fs::write(house_rules::path_for(save_name), "{not json").unwrap();
+    assert_eq!(HouseRules::load(save_name), HouseRules::default());
+    let _ = fs::remove_file(house_rules::path_for(save_name));
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_pool_hints_follow_the_action_typed() {
+    use sharad_ratatui::archetype::ARCHETYPES;
+    use sharad_ratatui::pool_hint::suggest;
+
+    let mut sheet = ARCHETYPES[0].builder().build();
+    sheet.agility = 5;
+    sheet.intuition = 3;
+    sheet.skills.combat.clear();
+    sheet.skills.combat.insert("Automatics".to_string(), 4);
+    sheet.skills.combat.insert("Pistols".to_string(), 2);
+    sheet.skills.physical.insert("Perception".to_string(), 3);
+    sheet.inventory.clear();
+
+    // The sheet's best skill for the action, and the first action typed.
+    let hint = suggest("I'm shooting at the guard", &sheet).unwrap();
+    assert_eq!(
+        (hint.attribute, hint.skill, hint.pool),
+        ("Agility", "Automatics", 9)
+    );
+    assert_eq!(hint.limit, Some(sheet.physical_limit));
+    assert_eq!(hint.tag(), "(Agility + Automatics, 9 dice)");
+    let hint = suggest("I look around, then shoot", &sheet).unwrap();
+    assert_eq!((hint.skill, hint.pool), ("Perception", 6));
+    assert!(hint.label().starts_with("Intuition + Perception: 6 dice"));
+    assert_eq!(suggest("I ran away", &sheet), None);
+    assert_eq!(suggest("Running for it", &sheet).unwrap().skill, "Running");
+    assert_eq!(
+        suggest("I try to blend in", &sheet).unwrap().skill,
+        "Etiquette"
+    );
+
+    // Nothing for commands, plain talk, or a test already added.
+    assert_eq!(suggest("/roll 6", &sheet), None);
+    assert_eq!(suggest("What does the Johnson want?", &sheet), None);
+    assert_eq!(
+        suggest("Shoot (Agility + Automatics, 9 dice)", &sheet),
+        None
+    );
+}
+
+#[test]
+fn test_sheet_rolls_use_the_skills_linked_attribute_and_limit() {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use sharad_ratatui::archetype::ARCHETYPES;
+    use sharad_ratatui::roll_popup::{RollPopup, RollPopupAction, MAX_MODIFIER};
+
+    let mut sheet = ARCHETYPES[0].builder().build();
+    sheet.name = "Raven".to_string();
+    sheet.skills.combat = [("Pistols".to_string(), 4)].into();
+    sheet.skills.physical = [("Perception".to_string(), 2), ("Gymnastics".to_string(), 1)].into();
+    sheet.skills.social.clear();
+    sheet.skills.technical.clear();
+    sheet.inventory.clear();
+
+    let order: Vec<&str> = sheet
+        .sorted_active_skills()
+        .into_iter()
+        .map(|(skill, _)| skill.as_str())
+        .collect();
+    assert_eq!(order, ["Pistols", "Gymnastics", "Perception"]);
+    assert_eq!(
+        sheet.skill_test("Perception"),
+        Some(("Intuition", Some("mental")))
+    );
+    assert_eq!(
+        sheet.skill_test("Pistols"),
+        Some(("Agility", Some("physical")))
+    );
+    assert_eq!(sheet.skill_test("Hacking"), None);
+
+    let mut popup = RollPopup::for_skill(&sheet, "Perception").unwrap();
+    assert_eq!(popup.title(), "Raven: Intuition + Perception");
+    assert_eq!(popup.pool, sheet.intuition + 2);
+    assert_eq!(popup.limit, Some(sheet.mental_limit));
+    assert!(RollPopup::for_skill(&sheet, "Hacking").is_none());
+
+    let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+    popup.handle_key(key(KeyCode::Right));
+    popup.handle_key(key(KeyCode::Right));
+    assert_eq!(popup.dice(), popup.pool + 2);
+    for _ in 0..40 {
+        popup.handle_key(key(KeyCode::Left));
+    }
+    assert_eq!(popup.modifier, -MAX_MODIFIER);
+    assert_eq!(popup.dice(), 0);
+    assert!(popup.roll().dice_results.is_empty());
+    assert!(popup.roll().title.ends_with(&format!("{}", -MAX_MODIFIER)));
+    assert!(matches!(
+        popup.handle_key(key(KeyCode::Enter)),
+        RollPopupAction::Roll
+    ));
+    assert!(matches!(
+        popup.handle_key(key(KeyCode::Esc)),
+        RollPopupAction::Close
+    ));
+
+    // Magicians roll Magic, with no limit but the Force.
+    sheet.magic = Some(5);
+    sheet.skills.technical = [("Spellcasting".to_string(), 3)].into();
+    let spell = RollPopup::for_skill(&sheet, "Spellcasting").unwrap();
+    assert_eq!(
+        (spell.attribute, spell.pool, spell.limit),
+        ("Magic", 8, None)
+    );
+}
+
+#[test]
+fn test_opposed_tests_roll_both_sides_and_count_net_hits() {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use sharad_ratatui::archetype::ARCHETYPES;
+    use sharad_ratatui::dice::{opposed_roll_with, perform_opposed_test, OpposedTestRequest};
+    use sharad_ratatui::events::GameEvent;
+
+    // The same seed rolls the same contest.
+    let contest = |seed| {
+        let roll = opposed_roll_with(&mut StdRng::seed_from_u64(seed), (10, None), (6, Some(2)));
+        (
+            roll.acting.dice_results.clone(),
+            roll.opposing.hits,
+            roll.net_hits(),
+        )
+    };
+    assert_eq!(contest(3406), contest(3406));
+    let roll = opposed_roll_with(&mut StdRng::seed_from_u64(1), (10, None), (20, Some(2)));
+    assert!(roll.opposing.hits <= 2);
+    assert_eq!(
+        roll.net_hits(),
+        roll.acting.hits as i16 - roll.opposing.hits as i16
+    );
+
+    let mut sneak = ARCHETYPES[0].builder().build();
+    sneak.name = "Raven".to_string();
+    let mut guard = ARCHETYPES[0].builder().build();
+    guard.name = "Guard".to_string();
+    let mut state = GameState::new("Opposed");
+    state.record(GameEvent::Character { sheet: sneak }).unwrap();
+    state.record(GameEvent::Character { sheet: guard }).unwrap();
+    let request = |opponent: &str, dice: Option<u8>| OpposedTestRequest {
+        character_name: "Raven".to_string(),
+        attribute: "agility".to_string(),
+        skill: "Sneaking".to_string(),
+        limit_type: "physical".to_string(),
+        opponent_name: opponent.to_string(),
+        opponent_attribute: Some("intuition".to_string()),
+        opponent_skill: Some("Perception".to_string()),
+        opponent_limit_type: Some("mental".to_string()),
+        opponent_dice: dice,
+    };
+    let (response, rolls) = perform_opposed_test(&request("Guard", None), &state).unwrap();
+    assert_eq!(
+        response.net_hits,
+        response.hits as i16 - response.opponent_hits as i16
+    );
+    assert_eq!(response.success, response.net_hits > 0);
+    assert_eq!(rolls[0].title, "Raven: agility + Sneaking vs Guard");
+    assert_eq!(rolls[1].title, "Guard: intuition + Perception");
+    assert_eq!(rolls[1].dice_results, response.opponent_dice_results);
+
+    // An opponent without a sheet needs a pool.
+    assert!(perform_opposed_test(&request("Drone", None), &state).is_err());
+    let (response, rolls) = perform_opposed_test(&request("Drone", Some(0)), &state).unwrap();
+    assert_eq!(response.opponent_hits, 0);
+    assert_eq!(rolls[1].title, "Drone: 0 dice");
+}
+
+#[test]
+fn test_npc_tests_roll_from_the_npcs_sheet() {
+    use sharad_ratatui::archetype::ARCHETYPES;
+    use sharad_ratatui::dice::{perform_npc_test, NpcTestRequest};
+    use sharad_ratatui::events::GameEvent;
+
+    let mut runner = ARCHETYPES[0].builder().build();
+    runner.name = "Raven".to_string();
+    let mut guard = ARCHETYPES[0].builder().build();
+    guard.name = "Guard".to_string();
+    guard.skills.technical.insert("Perception".to_string(), 4);
+    let mut state = GameState::new("NPC tests");
+    state
+        .record(GameEvent::MainCharacter {
+            sheet: Some(runner.clone()),
+        })
+        .unwrap();
+    state
+        .record(GameEvent::Character { sheet: runner })
+        .unwrap();
+    state.record(GameEvent::Character { sheet: guard }).unwrap();
+    let guard = state.characters.iter().find(|c| c.name == "Guard").unwrap();
+    let request = |npc: &str, test: &str| NpcTestRequest {
+        npc_name: npc.to_string(),
+        test: test.to_string(),
+        limit_type: None,
+        threshold: Some(2),
+    };
+
+    let (response, roll) =
+        perform_npc_test(&request("guard", "Intuition + Perception"), &state).unwrap();
+    assert_eq!(response.npc_name, "Guard");
+    assert_eq!(roll.title, "Guard: Intuition + Perception");
+    // Sixes are rolled again, so there can be more dice than the pool.
+    assert!(response.dice_results.len() >= response.dice_pool as usize);
+    assert_eq!(response.success, response.hits >= 2);
+    assert!(!response.defaulted);
+    assert_eq!(response.dice_pool, guard.intuition + 4);
+    // Skills are found whatever their case, as attributes and names are.
+    let (response, _) =
+        perform_npc_test(&request("Guard", "intuition + perception"), &state).unwrap();
+    assert!(!response.defaulted);
+    assert_eq!(response.dice_pool, guard.intuition + 4);
+
+    // Two attributes add up; a skill the NPC lacks is the attribute less one.
+    let (response, _) =
+        perform_npc_test(&request("Guard", "Intuition + Willpower"), &state).unwrap();
+    assert_eq!(response.dice_pool, guard.intuition + guard.willpower);
+    let (response, _) = perform_npc_test(
+        &request("Guard", "Logic + Underwater Basket Weaving"),
+        &state,
+    )
+    .unwrap();
+    assert!(response.defaulted);
+    assert_eq!(response.dice_pool, guard.logic - 1);
+
+    // The player's character and NPCs without sheets are refused, not rolled as 0.
+    assert!(
+        perform_npc_test(&request("Raven", "Agility + Pistols"), &state)
+            .unwrap_err()
+            .contains("perform_dice_roll")
+    );
+    let unknown = perform_npc_test(&request("Lofwyr", "Charisma + Con"), &state).unwrap_err();
+    assert!(unknown.contains("Guard") && unknown.contains("generate_npc"));
+    assert!(perform_npc_test(&request("Guard", "Shooting"), &state).is_err());
+}
+
+#[test]
+fn test_agent_models_come_from_the_settings() {
+    use sharad_ratatui::backend::AgentModels;
+    use sharad_ratatui::settings::{Settings, CHAT_MODELS};
+    use sharad_ratatui::settings_state::{SettingsState, ARCHIVIST_MODEL, NARRATOR_MODEL};
+
+    // Older settings files run both agents on the model they always used.
+    let settings: Settings = serde_json::from_str(
+        r#"{"language": "English", "openai_api_key": null, "audio_output_enabled": true,
+            "audio_input_enabled": true, "debug_mode": false}"#,
+    )
+    .unwrap();
+    assert_eq!(settings.agent_models(), AgentModels::default());
+
+    let chosen = Settings {
+        narrator_model: "gpt-4o".to_string(),
+        archivist_model: "gpt-4o-mini".to_string(),
+        ..settings
+    };
+    assert_eq!(
+        chosen.agent_models(),
+        AgentModels {
+            narrator: "gpt-4o".to_string(),
+            archivist: "gpt-4o-mini".to_string(),
+        }
+    );
+    let state = SettingsState::from_settings(&chosen);
+    assert_eq!(
+        CHAT_MODELS[state.selected_options[NARRATOR_MODEL]],
+        "gpt-4o"
+    );
+    assert_eq!(
+        CHAT_MODELS[state.selected_options[ARCHIVIST_MODEL]],
+        "gpt-4o-mini"
+    );
+}
+
+#[test]
+fn test_narration_is_spoken_in_sentence_chunks() {
+    use sharad_ratatui::audio::{speech_chunks, MIN_CHUNK_CHARS};
+
+    let text = "Rain hammers the neon signs of Redmond as you step out of the cab. Hoi. The fixer \
+                waits under the awning, smoking something that isn't tobacco! \"You're late,\" \
+                she says. Ok?";
+    let chunks = speech_chunks(text);
+    assert_eq!(
+        chunks,
+        vec![
+            "Rain hammers the neon signs of Redmond as you step out of the cab.",
+            // A short end goes with the sentence before it.
+            "Hoi. The fixer waits under the awning, smoking something that isn't tobacco! \
+             \"You're late,\" she says. Ok?",
+        ]
+    );
+    // Nothing is lost or reordered.
+    assert_eq!(
+        chunks.join(" ").split_whitespace().collect::<Vec<_>>(),
+        text.split_whitespace().collect::<Vec<_>>()
+    );
+    // A short line, and one with no sentence end, are spoken whole.
+    assert_eq!(speech_chunks("Hoi, chummer."), vec!["Hoi, chummer."]);
+    let long = "a ".repeat(MIN_CHUNK_CHARS);
+    assert_eq!(speech_chunks(&long), vec![long.trim()]);
+    assert!(speech_chunks("  ").is_empty());
+    // Japanese ends sentences without a space.
+    let sentence = "雨がネオンの看板を激しく叩きつけ、通りは水浸しになっている。";
+    assert_eq!(
+        speech_chunks(&sentence.repeat(4)),
+        vec![sentence.repeat(2); 2]
+    );
+}
+
+#[tokio::test]
+async fn test_plugin_calls_in_a_row_run_at_the_same_time() {
+    use async_openai::types::{FunctionCall, RunToolCallObject};
+    use async_trait::async_trait;
+    use sharad_ratatui::ai::GameAI;
+    use sharad_ratatui::error::ShadowrunError;
+    use sharad_ratatui::plugins::{self, ToolDefinition, ToolPlugin};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    const DELAY: Duration = Duration::from_millis(300);
+
+    // A slow lookup that answers with the main character's nuyen.
+    struct Fence(&'static str);
+    #[async_trait]
+    impl ToolPlugin for Fence {
+        fn definition(&self) -> ToolDefinition {
+            ToolDefinition {
+                name: self.0.to_string(),
+                description: "Check what the runner can pay".to_string(),
+                parameters: serde_json::json!({"type": "object", "properties": {}}),
+                strict: false,
+            }
+        }
+        async fn call(
+            &self,
+            _arguments: &str,
+            game_state: &GameState,
+        ) -> Result<String, ShadowrunError> {
+            tokio::time::sleep(DELAY).await;
+            Ok(format!(
+                "{} {}",
+                self.0,
+                game_state
+                    .main_character_sheet
+                    .as_ref()
+                    .map_or(0, |sheet| sheet.nuyen)
+            ))
+        }
+    }
+    plugins::register(Arc::new(Fence("test_fence_a"))).unwrap();
+    plugins::register(Arc::new(Fence("test_fence_b"))).unwrap();
+
+    let mut sheet = sharad_ratatui::archetype::ARCHETYPES[0].builder().build();
+    sheet.nuyen = 1000;
+    let mut ai = GameAI::new(String::new(), |_| {}).await.unwrap();
+    let mut game_state = GameState::new("concurrent tools");
+    ai.update_character_sheet(&mut game_state, sheet.clone())
+        .unwrap();
+    let call = |id: &str, name: &str, arguments: String| RunToolCallObject {
+        id: id.to_string(),
+        r#type: "function".to_string(),
+        function: FunctionCall {
+            name: name.to_string(),
+            arguments,
+        },
+    };
+    let spend = serde_json::json!({
+        "character_name": sheet.name,
+        "updates": {"nuyen": 600}
+    })
+    .to_string();
+    let calls = [
+        call("call_1", "test_fence_a", "{}".to_string()),
+        call("call_2", "test_fence_b", "{}".to_string()),
+        call("call_3", "update_basic_attributes", spend),
+        call("call_4", "test_fence_a", "{}".to_string()),
+    ];
+
+    let started = Instant::now();
+    let outputs = ai.run_tool_calls(&calls, &mut game_state).await;
+    // The first two overlap; the last waits for the update before it and sees it.
+    let elapsed = started.elapsed();
+    assert!(elapsed >= DELAY * 2 && elapsed < DELAY * 3, "{:?}", elapsed);
+    let ids: Vec<_> = outputs
+        .iter()
+        .map(|output| output.tool_call_id.clone().unwrap())
+        .collect();
+    assert_eq!(ids, ["call_1", "call_2", "call_3", "call_4"]);
+    let output = |index: usize| outputs[index].output.clone().unwrap();
+    assert_eq!(output(0), "test_fence_a 1000");
+    assert_eq!(output(1), "test_fence_b 1000");
+    assert_eq!(output(3), "test_fence_a 600");
+}
+
+#[tokio::test]
+async fn test_actions_typed_offline_are_queued_and_sent_on_reconnection() {
+    use sharad_ratatui::ai::GameAI;
+    use sharad_ratatui::app::{App, AppCommand};
+    use sharad_ratatui::backend::{MockBackend, MockTurn};
+    use sharad_ratatui::headless::wait_for_response;
+    use sharad_ratatui::{paths, settings::Settings};
+    use std::sync::Arc;
+    use tokio::sync::mpsc;
+
+    let _data_dir = DATA_DIR.lock().await;
+    let dir = std::env::temp_dir().join(format!("sharad_mock_app_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("config")).unwrap();
+    Settings {
+        data_dir: Some(dir.join("data")),
+        audio_output_enabled: false,
+        ..Settings::default()
+    }
+    .save_to_file(dir.join("config").join("settings.json").to_str().unwrap())
+    .unwrap();
+    paths::init(Some(dir.join("config")), false);
+
+    let backend = Arc::new(MockBackend::new([
+        MockTurn::reply("The rain never stops in Redmond."),
+        MockTurn::reply("The lock clicks open."),
+        MockTurn::reply("The hallway is dark."),
+    ]));
+    let (ai_sender, mut ai_receiver) = mpsc::unbounded_channel();
+    let (mut app, mut commands) = App::new(ai_sender).await;
+    app.ai_client = Some(GameAI::with_backend(backend.clone(), |_| {}));
+    app.start_new_game("offline run".to_string(), None)
+        .await
+        .unwrap();
+    wait_for_response(&mut app, &mut commands, &mut ai_receiver).await;
+    let game = app.current_game.clone().unwrap();
+    let thread_id = game.lock().await.thread_id.clone();
+    let thread_length = backend.thread(&thread_id).len();
+
+    // The connection drops as the action is sent: it is kept, and so is the next one typed.
+    backend.set_offline(true);
+    app.process_message("I pick the lock".to_string());
+    wait_for_response(&mut app, &mut commands, &mut ai_receiver).await;
+    assert!(app.offline);
+    app.process_message("I slip inside".to_string());
+    assert_eq!(app.offline_queue.len(), 2);
+    assert_eq!(backend.thread(&thread_id).len(), thread_length);
+
+    // Once it is back, they are sent in order, each after the answer to the one before.
+    backend.set_offline(false);
+    app.handle_reconnected();
+    assert!(!app.offline);
+    wait_for_response(&mut app, &mut commands, &mut ai_receiver).await;
+    let next = loop {
+        match commands.recv().await.unwrap() {
+            AppCommand::ProcessMessage(message) => break message,
+            _ => continue,
+        }
+    };
+    assert_eq!(next, "I slip inside");
+    app.process_message(next);
+    wait_for_response(&mut app, &mut commands, &mut ai_receiver).await;
+    assert!(app.offline_queue.is_empty());
+
+    let thread = backend.thread(&thread_id);
+    assert_eq!(thread.len(), thread_length + 4);
+    assert!(thread[thread_length].content.contains("I pick the lock"));
+    assert!(thread[thread_length + 1]
+        .content
+        .contains("The lock clicks open."));
+    assert!(thread[thread_length + 2].content.contains("I slip inside"));
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_runs_cut_short_by_closing_the_game_are_picked_up() {
+    use sharad_ratatui::ai::{GameAI, GameConversationState};
+    use sharad_ratatui::backend::{AssistantBackend, MockBackend, MockTurn};
+    use sharad_ratatui::events::GameEvent;
+    use std::sync::{Arc, Mutex};
+
+    let backend = Arc::new(MockBackend::new([
+        MockTurn::reply("The first answer."),
+        MockTurn::reply("The lock gives way while you were away."),
+        MockTurn::reply("Never told.").tool("roll_dice", r#"{"expression": "2d6"}"#),
+    ]));
+    let mut ai = GameAI::with_backend(backend.clone(), |_| {});
+    let saved = Arc::new(Mutex::new(Vec::new()));
+    let checkpoints = saved.clone();
+    ai.checkpoint = Some(Arc::new(move |game_state: &GameState| {
+        checkpoints
+            .lock()
+            .unwrap()
+            .push(game_state.pending_run.clone())
+    }));
+    ai.start_new_conversation(
+        "asst_mock",
+        GameConversationState {
+            assistant_id: "asst_mock".to_string(),
+            thread_id: String::new(),
+            character_sheet: None,
+        },
+    )
+    .await
+    .unwrap();
+    let (thread_id, _) = ai.get_conversation_ids().await.unwrap();
+    let mut game_state = GameState::new("resume");
+    game_state.thread_id = thread_id.clone();
+
+    // The game is saved with the run as it starts, and the run is cleared once it ends.
+    ai.send_message("I look around", &mut game_state)
+        .await
+        .unwrap();
+    assert!(matches!(&saved.lock().unwrap()[..], [Some(_)]));
+    assert_eq!(game_state.pending_run, None);
+    assert!(ai.resume_run(&mut game_state).await.unwrap().is_none());
+
+    // A run that finished while the game was closed gives its answer on the next start.
+    backend
+        .add_message(&thread_id, "I pick the lock")
+        .await
+        .unwrap();
+    let run_id = backend
+        .create_run(&thread_id, "asst_mock", &Default::default())
+        .await
+        .unwrap();
+    game_state
+        .record(GameEvent::Run {
+            run_id: Some(run_id),
+        })
+        .unwrap();
+    let answer = ai.resume_run(&mut game_state).await.unwrap().unwrap();
+    assert!(answer
+        .fluff
+        .dialogue
+        .iter()
+        .any(|line| line.text.contains("The lock gives way")));
+    assert_eq!(game_state.pending_run, None);
+
+    // One waiting for tool outputs is cancelled, as its calls may have run already.
+    let run_id = backend
+        .create_run(&thread_id, "asst_mock", &Default::default())
+        .await
+        .unwrap();
+    game_state
+        .record(GameEvent::Run {
+            run_id: Some(run_id.clone()),
+        })
+        .unwrap();
+    assert!(ai.resume_run(&mut game_state).await.unwrap().is_none());
+    assert_eq!(game_state.pending_run, None);
+    assert!(backend.run_state(&thread_id, &run_id).await.is_err());
+
+    // An answer left broken is sent back for repair like a fresh one.
+    backend.queue(MockTurn::verbatim(r#"{"crunch": "cut off"#));
+    backend.queue(MockTurn::reply("The alarm stays silent."));
+    let run_id = backend
+        .create_run(&thread_id, "asst_mock", &Default::default())
+        .await
+        .unwrap();
+    game_state
+        .record(GameEvent::Run {
+            run_id: Some(run_id),
+        })
+        .unwrap();
+    let answer = ai.resume_run(&mut game_state).await.unwrap().unwrap();
+    assert!(answer
+        .fluff
+        .dialogue
+        .iter()
+        .any(|line| line.text.contains("The alarm stays silent.")));
+}
+
+#[tokio::test]
+async fn test_saves_whose_thread_or_assistant_is_gone_get_new_ones() {
+    use sharad_ratatui::ai::{GameAI, GameConversationState, Repaired};
+    use sharad_ratatui::backend::{AssistantBackend, MockBackend, MockTurn};
+    use std::sync::Arc;
+
+    let backend = Arc::new(MockBackend::new([
+        MockTurn::reply("You reach the docks."),
+        MockTurn::reply("The fixer is waiting."),
+    ]));
+    let mut ai = GameAI::with_backend(backend.clone(), |_| {});
+    ai.start_new_conversation(
+        "asst_mock",
+        GameConversationState {
+            assistant_id: "asst_mock".to_string(),
+            thread_id: String::new(),
+            character_sheet: None,
+        },
+    )
+    .await
+    .unwrap();
+    let (thread_id, _) = ai.get_conversation_ids().await.unwrap();
+    let mut game_state = GameState::new("repair");
+    game_state.assistant_id = "asst_mock".to_string();
+    game_state.thread_id = thread_id.clone();
+    ai.send_message("I head to the docks", &mut game_state)
+        .await
+        .unwrap();
+
+    // Nothing is made again while both exist.
+    assert_eq!(
+        ai.repair_conversation(&mut game_state).await.unwrap(),
+        Repaired::default()
+    );
+
+    backend.delete_assistant("asst_mock").await;
+    backend.delete_thread(&thread_id);
+    let repaired = ai.repair_conversation(&mut game_state).await.unwrap();
+    assert_eq!(
+        repaired,
+        Repaired {
+            assistant: true,
+            thread: true
+        }
+    );
+    assert_ne!(game_state.assistant_id, "asst_mock");
+    assert_ne!(game_state.thread_id, thread_id);
+    // The new thread opens with a summary of the messages the save kept.
+    let seed = backend.thread(&game_state.thread_id);
+    assert_eq!(seed.len(), 1);
+    assert!(seed[0].content.contains("[mock summary 1]"));
+    assert_eq!(game_state.archive.uncovered, Some(1));
+
+    // And the game goes on in it.
+    ai.load_conversation(GameConversationState {
+        assistant_id: game_state.assistant_id.clone(),
+        thread_id: game_state.thread_id.clone(),
+        character_sheet: None,
+    })
+    .await;
+    ai.send_message("I talk to the fixer", &mut game_state)
+        .await
+        .unwrap();
+    assert_eq!(backend.thread(&game_state.thread_id).len(), 3);
+}
+
+#[tokio::test]
+async fn test_assistants_of_old_campaigns_can_get_the_new_instructions() {
+    use sharad_ratatui::ai::{GameAI, GameConversationState};
+    use sharad_ratatui::assistant::instructions_version;
+    use sharad_ratatui::backend::{AssistantBackend, MockBackend};
+    use sharad_ratatui::ruleset::RulesetKind;
+    use std::sync::Arc;
+
+    assert_eq!(
+        instructions_version(RulesetKind::default()),
+        instructions_version(RulesetKind::default())
+    );
+    let backend = Arc::new(MockBackend::new([]));
+    let mut ai = GameAI::with_backend(backend.clone(), |_| {});
+    ai.load_conversation(GameConversationState {
+        assistant_id: "asst_mock".to_string(),
+        thread_id: "thread_mock".to_string(),
+        character_sheet: None,
+    })
+    .await;
+    // A save from before versions has none, so it is behind.
+    let mut game_state = GameState::new("upgrade");
+    game_state.assistant_id = "asst_mock".to_string();
+    game_state.thread_id = "thread_mock".to_string();
+    assert!(GameAI::instructions_outdated(&game_state));
+
+    ai.upgrade_assistant(&mut game_state, false).await.unwrap();
+    assert_eq!(backend.updated_assistants(), vec!["asst_mock".to_string()]);
+    assert_eq!(game_state.assistant_id, "asst_mock");
+    assert!(!GameAI::instructions_outdated(&game_state));
+
+    // A new assistant takes the old one's place, on the same thread.
+    ai.upgrade_assistant(&mut game_state, true).await.unwrap();
+    assert_ne!(game_state.assistant_id, "asst_mock");
+    assert_eq!(game_state.thread_id, "thread_mock");
+    assert!(!backend.assistant_exists("asst_mock").await.unwrap());
+    assert_eq!(
+        ai.get_conversation_ids().await.unwrap(),
+        (
+            game_state.thread_id.clone(),
+            game_state.assistant_id.clone()
+        )
+    );
+}
+
+#[test]
+fn test_load_menu_previews_come_from_the_save() {
+    use sharad_ratatui::archetype::ARCHETYPES;
+    use sharad_ratatui::backend::narration;
+    use sharad_ratatui::events::GameEvent;
+    use sharad_ratatui::message::MessageType;
+    use sharad_ratatui::save::SaveMetadata;
+    use std::path::PathBuf;
+
+    let sheet = ARCHETYPES[0].builder().build();
+    let mut game_state = GameState::new("preview");
+    assert_eq!(SaveMetadata::of(&game_state).character, None);
+    assert!(SaveMetadata::of(&game_state).summary.is_empty());
+
+    game_state
+        .record(GameEvent::MainCharacter {
+            sheet: Some(sheet.clone()),
+        })
+        .unwrap();
+    game_state
+        .record(GameEvent::Message {
+            sender: MessageType::User,
+            text: "I knock".to_string(),
+        })
+        .unwrap();
+    game_state
+        .record(GameEvent::Message {
+            sender: MessageType::Game,
+            text: serde_json::to_string(&narration("The door opens.".to_string())).unwrap(),
+        })
+        .unwrap();
+    game_state
+        .record(GameEvent::Portrait {
+            name: sheet.name.clone(),
+            path: PathBuf::from("portraits/runner.png"),
+        })
+        .unwrap();
+
+    let metadata = SaveMetadata::of(&game_state);
+    assert_eq!(metadata.character, Some(sheet.name.clone()));
+    assert_eq!(metadata.race, Some(sheet.race.to_string()));
+    assert_eq!(metadata.stats.turns, 1);
+    assert_eq!(
+        metadata.thumbnail,
+        Some(PathBuf::from("portraits/runner.png"))
+    );
+    assert_eq!(metadata.summary, "The door opens.");
+    assert_eq!(metadata.last_played.map(|at| at.len()), Some(16));
+
+    // The archivist's summary says more than the latest narration.
+    let mut archive = game_state.archive.clone();
+    archive.summary = "The runner got in.".to_string();
+    game_state.record(GameEvent::Archive { archive }).unwrap();
+    assert_eq!(SaveMetadata::of(&game_state).summary, "The runner got in.");
+}
+
+#[test]
+fn test_save_previews_show_the_portrait_and_backstory() {
+    use sharad_ratatui::save::{excerpt, EXCERPT_CHARS};
+    use sharad_ratatui::thumbnail::Thumbnails;
+
+    assert_eq!(
+        excerpt("  Grew up in the Barrens.\n\nThen the war came."),
+        "Grew up in the Barrens."
+    );
+    let long = "word ".repeat(100);
+    let cut = excerpt(&long);
+    assert!(cut.ends_with("word…"));
+    assert!(cut.chars().count() <= EXCERPT_CHARS + 1);
+
+    let dir = std::env::temp_dir().join(format!("sharad_thumbnail_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let portrait = dir.join("portrait.png");
+    ::image::RgbImage::from_pixel(8, 8, ::image::Rgb([200, 30, 30]))
+        .save(&portrait)
+        .unwrap();
+    let mut thumbnails = Thumbnails::default();
+    assert!(thumbnails.get(&portrait).is_some());
+    // A missing picture is left out of the preview.
+    assert!(thumbnails.get(&dir.join("missing.png")).is_none());
+    assert!(thumbnails.get(&portrait).is_some());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_saves_can_be_renamed_with_their_backups_and_assistant() {
+    use sharad_ratatui::backend::{AssistantBackend, MockBackend};
+    use sharad_ratatui::journal::{self, Journal};
+    use sharad_ratatui::ruleset::RulesetKind;
+    use sharad_ratatui::save::check_save_name;
+
+    let existing = vec!["Seattle".to_string(), "Tokyo".to_string()];
+    assert_eq!(
+        check_save_name("  Hong Kong ", &existing),
+        Ok("Hong Kong".to_string())
+    );
+    // "Run.emergency" would be saved as Run.emergency.json and hidden as an emergency save.
+    for bad in [
+        "",
+        "   ",
+        "Tokyo",
+        "../Tokyo",
+        "a\\b",
+        ".hidden",
+        "Run.emergency",
+    ] {
+        assert!(check_save_name(bad, &existing).is_err(), "{:?}", bad);
+    }
+
+    let dir = std::env::temp_dir().join(format!("sharad_rename_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let mut state = GameState::new("Seattle");
+    let mut journal = Journal::new(&dir, "Seattle");
+    journal.record(&state).unwrap();
+    state.assistant_id = "asst_seattle".to_string();
+    journal.record(&state).unwrap();
+    fs::create_dir_all(journal::backup_dir(&dir, "Seattle")).unwrap();
+    fs::write(journal::backup_dir(&dir, "Seattle").join("1.json"), "{}").unwrap();
+
+    let renamed = journal::rename(&dir, "Seattle", "Hong Kong").unwrap();
+    assert_eq!(renamed.save_name, "Hong Kong");
+    assert_eq!(renamed.assistant_id, "asst_seattle");
+    for path in journal::files_for(&dir, "Seattle") {
+        assert!(!path.exists(), "{}", path.display());
+    }
+    assert!(journal::backup_dir(&dir, "Hong Kong")
+        .join("1.json")
+        .exists());
+    let (_, loaded) = Journal::load(&dir, "Hong Kong").unwrap();
+    assert_eq!(loaded.save_name, "Hong Kong");
+    assert_eq!(loaded.assistant_id, "asst_seattle");
+
+    // A save already called that is never overwritten.
+    Journal::new(&dir, "Tokyo").record(&state).unwrap();
+    assert!(journal::rename(&dir, "Hong Kong", "Tokyo").is_err());
+    assert!(journal::snapshot_path(&dir, "Hong Kong").exists());
+    fs::remove_dir_all(&dir).unwrap();
+
+    let backend = MockBackend::new([]);
+    let assistant_id = backend
+        .create_assistant("Seattle", RulesetKind::default())
+        .await
+        .unwrap();
+    backend
+        .rename_assistant(&assistant_id, "Hong Kong")
+        .await
+        .unwrap();
+    assert_eq!(
+        backend.assistant_name(&assistant_id).as_deref(),
+        Some("Hong Kong")
+    );
+}
+
+#[tokio::test]
+async fn test_saves_can_be_duplicated_from_the_load_menu() {
+    use sharad_ratatui::ai::GameAI;
+    use sharad_ratatui::app::App;
+    use sharad_ratatui::backend::{MockBackend, MockTurn};
+    use sharad_ratatui::headless::wait_for_response;
+    use sharad_ratatui::journal::Journal;
+    use sharad_ratatui::{paths, settings::Settings};
+    use std::sync::Arc;
+    use tokio::sync::mpsc;
+
+    let _data_dir = DATA_DIR.lock().await;
+    let dir = std::env::temp_dir().join(format!("sharad_duplicate_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("config")).unwrap();
+    Settings {
+        data_dir: Some(dir.join("data")),
+        audio_output_enabled: false,
+        ..Settings::default()
+    }
+    .save_to_file(dir.join("config").join("settings.json").to_str().unwrap())
+    .unwrap();
+    paths::init(Some(dir.join("config")), false);
+
+    let backend = Arc::new(MockBackend::new([MockTurn::reply(
+        "The job is on the table.",
+    )]));
+    let (ai_sender, mut ai_receiver) = mpsc::unbounded_channel();
+    let (mut app, mut commands) = App::new(ai_sender).await;
+    app.ai_client = Some(GameAI::with_backend(backend.clone(), |_| {}));
+    app.start_new_game("heist".to_string(), None).await.unwrap();
+    wait_for_response(&mut app, &mut commands, &mut ai_receiver).await;
+    let original = app.current_game.clone().unwrap().lock().await.clone();
+
+    app.duplicate_save("heist", "heist fork").await.unwrap();
+    let (_, copy) = Journal::load(&paths::save_dir(), "heist fork").unwrap();
+    assert_eq!(copy.save_name, "heist fork");
+    assert_ne!(copy.thread_id, original.thread_id);
+    assert_ne!(copy.assistant_id, original.assistant_id);
+    assert!(backend.thread(&copy.thread_id)[0]
+        .content
+        .contains("[mock summary"));
+    // The original stays as it was, and the one loaded.
+    let (_, kept) = Journal::load(&paths::save_dir(), "heist").unwrap();
+    assert_eq!(kept.thread_id, original.thread_id);
+    assert_eq!(
+        app.current_game.clone().unwrap().lock().await.save_name,
+        "heist"
+    );
+    assert_eq!(app.selected_save().as_deref(), Some("heist fork"));
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_load_menu_filters_and_sorts_saves() {
+    use sharad_ratatui::save::{SaveManager, SaveMetadata, SaveSort};
+
+    let metadata = |character: &str, last_played: &str, seconds: u64| {
+        let mut metadata = SaveMetadata::of(&GameState::new("unused"));
+        metadata.character = Some(character.to_string());
+        metadata.last_played = Some(last_played.to_string());
+        metadata.stats.seconds = seconds;
+        metadata
+    };
+    let save_manager = SaveManager {
+        available_saves: ["tokyo", "Barrens", "seattle", "broken"]
+            .map(String::from)
+            .to_vec(),
+        current_save: None,
+        metadata: [
+            ("tokyo", metadata("Kaito", "2024-09-03 20:00", 600)),
+            ("Barrens", metadata("Raven", "2024-09-01 21:14", 7200)),
+            ("seattle", metadata("Ghost", "2024-09-05 09:30", 60)),
+        ]
+        .into_iter()
+        .map(|(name, metadata)| (name.to_string(), metadata))
+        .collect(),
+    };
+
+    let listed = |filter: &str, sort: SaveSort| save_manager.listed(filter, sort);
+    assert_eq!(
+        listed("", SaveSort::Name),
+        ["Barrens", "broken", "seattle", "tokyo"]
+    );
+    // Saves that couldn't be read come last.
+    assert_eq!(
+        listed("", SaveSort::LastPlayed),
+        ["seattle", "tokyo", "Barrens", "broken"]
+    );
+    assert_eq!(
+        listed("", SaveSort::PlayTime),
+        ["Barrens", "tokyo", "seattle", "broken"]
+    );
+    // The filter matches the save's name or its character's, whatever the case.
+    assert_eq!(listed("B", SaveSort::Name), ["Barrens", "broken"]);
+    assert_eq!(listed("raven", SaveSort::Name), ["Barrens"]);
+    assert!(listed("nowhere", SaveSort::Name).is_empty());
+    assert_eq!(SaveSort::PlayTime.next(), SaveSort::Name);
+}
+
+#[tokio::test]
+async fn test_load_menu_lists_saves_without_loading_them() {
+    use sharad_ratatui::journal::{self, Journal};
+    use sharad_ratatui::save::{SaveManager, SaveMetadata};
+    use sharad_ratatui::{paths, settings::Settings};
+
+    let _data_dir = DATA_DIR.lock().await;
+    let dir = std::env::temp_dir().join(format!("sharad_save_list_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("config")).unwrap();
+    Settings {
+        data_dir: Some(dir.join("data")),
+        audio_output_enabled: false,
+        ..Settings::default()
+    }
+    .save_to_file(dir.join("config").join("settings.json").to_str().unwrap())
+    .unwrap();
+    paths::init(Some(dir.join("config")), false);
+    let save_dir = paths::save_dir();
+    fs::create_dir_all(&save_dir).unwrap();
+
+    // A save written now keeps its metadata next to it.
+    let mut state = GameState::new("listed run");
+    state.archive.summary = "The runners reached the docks.".to_string();
+    SaveManager {
+        available_saves: Vec::new(),
+        current_save: Some(state.clone()),
+        metadata: HashMap::new(),
+    }
+    .save()
+    .unwrap();
+    // One an older version wrote has none.
+    Journal::new(&save_dir, "older run")
+        .record(&GameState::new("older run"))
+        .unwrap();
+
+    let mut save_manager = SaveManager::new();
+    save_manager.refresh();
+    assert_eq!(
+        save_manager.metadata.get("listed run"),
+        Some(&SaveMetadata::of(&state))
+    );
+    assert!(!save_manager.metadata.contains_key("older run"));
+
+    // The list comes from the metadata, not from the save.
+    let mut written = SaveMetadata::of(&state);
+    written.summary = "As written.".to_string();
+    fs::write(
+        journal::metadata_path(&save_dir, "listed run"),
+        serde_json::to_vec(&written).unwrap(),
+    )
+    .unwrap();
+    save_manager.refresh();
+    assert_eq!(save_manager.metadata["listed run"].summary, "As written.");
+
+    // Selecting the older save loads it once, and it has metadata from then on.
+    save_manager.describe("older run");
+    assert!(save_manager.metadata.contains_key("older run"));
+    assert!(journal::metadata_path(&save_dir, "older run").exists());
+    save_manager.refresh();
+    assert!(save_manager.metadata.contains_key("older run"));
+
+    for save_name in ["listed run", "older run"] {
+        for path in journal::files_for(&save_dir, save_name) {
+            let _ = fs::remove_file(path);
+        }
+    }
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_main_menu_continues_the_last_game() {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use sharad_ratatui::ai::GameAI;
+    use sharad_ratatui::app::{App, AppCommand, MainMenuItem};
+    use sharad_ratatui::app_state::AppState;
+    use sharad_ratatui::backend::{MockBackend, MockTurn};
+    use sharad_ratatui::headless::wait_for_response;
+    use sharad_ratatui::{paths, settings::Settings};
+    use std::sync::Arc;
+    use tokio::sync::mpsc;
+
+    let _data_dir = DATA_DIR.lock().await;
+    let dir = std::env::temp_dir().join(format!("sharad_continue_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("config")).unwrap();
+    let settings = Settings {
+        data_dir: Some(dir.join("data")),
+        audio_output_enabled: false,
+        ..Settings::default()
+    };
+    settings
+        .save_to_file(dir.join("config").join("settings.json").to_str().unwrap())
+        .unwrap();
+    paths::init(Some(dir.join("config")), false);
+    // The paths belong to whichever test ran first, so write this test's settings where they
+    // point rather than relying on what other tests left there, and put them back at the end.
+    let settings_file = sharad_ratatui::settings::settings_path();
+    settings.save_to_file(&settings_file).unwrap();
+
+    let (ai_sender, mut ai_receiver) = mpsc::unbounded_channel();
+    let (mut app, mut commands) = App::new(ai_sender.clone()).await;
+    // Nothing to continue before a game is played.
+    assert_eq!(app.main_menu_items()[0], MainMenuItem::NewGame);
+    let backend = Arc::new(MockBackend::new([MockTurn::reply("Welcome back.")]));
+    app.ai_client = Some(GameAI::with_backend(backend, |_| {}));
+    app.start_new_game("night run".to_string(), None)
+        .await
+        .unwrap();
+    wait_for_response(&mut app, &mut commands, &mut ai_receiver).await;
+
+    // The next run offers it first, and Enter loads it without going through the load menu.
+    let (mut app, mut commands) = App::new(ai_sender).await;
+    assert_eq!(
+        app.main_menu_items()[0],
+        MainMenuItem::Continue("night run".to_string())
+    );
+    app.state = AppState::MainMenu;
+    app.handle_input(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    let loaded = loop {
+        match commands.try_recv() {
+            Ok(AppCommand::LoadGame(save_name)) => break Some(save_name),
+            Ok(_) => continue,
+            Err(_) => break None,
+        }
+    };
+    assert_eq!(loaded.as_deref(), Some("night run"));
+    assert!(app.state == AppState::MainMenu);
+
+    // A save that is gone isn't offered.
+    fs::remove_file(paths::save_dir().join("night run.json")).unwrap();
+    app.save_manager.refresh();
+    assert_eq!(app.main_menu_items()[0], MainMenuItem::NewGame);
+    settings.save_to_file(&settings_file).unwrap();
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_main_menu_fits_small_terminals() {
+    use ratatui::layout::Rect;
+    use sharad_ratatui::ui::main_menu::menu_layout;
+
+    let heights = |width, height, show_art| {
+        menu_layout(Rect::new(0, 0, width, height), show_art)
+            .map(|rows| rows.iter().map(|row| row.height).collect::<Vec<u16>>())
+    };
+    // Art and ASCII title on a large terminal, unless the art is turned off.
+    let large = heights(120, 50, true).unwrap();
+    assert_eq!((large[1], large[2]), (20, 7));
+    assert_eq!(heights(120, 50, false).unwrap()[1], 0);
+    // The art goes first, then the title shrinks to a line, and the menu always keeps its rows.
+    let medium = heights(80, 30, true).unwrap();
+    assert_eq!((medium[1], medium[2]), (0, 7));
+    let small = heights(50, 18, true).unwrap();
+    assert_eq!((small[1], small[2]), (0, 1));
+    assert!(small[4] >= 6);
+    assert_eq!(heights(30, 10, true), None);
+}
+
+#[tokio::test]
+async fn test_campaigns_can_bring_their_own_menu_art() {
+    use sharad_ratatui::branding::{self, Branding, BrandingCache};
+    use sharad_ratatui::{paths, settings::Settings};
+
+    let _data_dir = DATA_DIR.lock().await;
+    let dir = std::env::temp_dir().join(format!("sharad_branding_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("config")).unwrap();
+    Settings {
+        data_dir: Some(dir.join("data")),
+        ..Settings::default()
+    }
+    .save_to_file(dir.join("config").join("settings.json").to_str().unwrap())
+    .unwrap();
+    paths::init(Some(dir.join("config")), false);
+
+    fs::create_dir_all(paths::branding_dir()).unwrap();
+    fs::write(
+        branding::path_for("neon"),
+        r#"{"title": "NEON TIDE", "art": "  ~~~\n ~~~~~"}"#,
+    )
+    .unwrap();
+    fs::write(branding::path_for("torn"), r#"{"title": "#).unwrap();
+
+    let mut cache = BrandingCache::default();
+    assert_eq!(cache.get("neon").title.as_deref(), Some("NEON TIDE"));
+    assert_eq!(cache.get("neon").art.as_deref(), Some("  ~~~\n ~~~~~"));
+    // Campaigns without a file, or with one that doesn't parse, keep the built-in art.
+    assert_eq!(*cache.get("plain"), Branding::default());
+    assert_eq!(Branding::load("torn"), Branding::default());
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_screens_ask_to_be_resized_on_small_terminals() {
+    use ratatui::{backend::TestBackend, Terminal};
+    use sharad_ratatui::app::App;
+    use sharad_ratatui::app_state::AppState;
+    use sharad_ratatui::{paths, settings::Settings, ui};
+    use tokio::sync::mpsc;
+
+    let _data_dir = DATA_DIR.lock().await;
+    let dir = std::env::temp_dir().join(format!("sharad_resize_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("config")).unwrap();
+    Settings {
+        data_dir: Some(dir.join("data")),
+        audio_output_enabled: false,
+        ..Settings::default()
+    }
+    .save_to_file(dir.join("config").join("settings.json").to_str().unwrap())
+    .unwrap();
+    paths::init(Some(dir.join("config")), false);
+    let (ai_sender, _ai_receiver) = mpsc::unbounded_channel();
+    let (mut app, _commands) = App::new(ai_sender).await;
+
+    let screen = |terminal: &Terminal<TestBackend>| {
+        let buffer = terminal.backend().buffer();
+        buffer
+            .content()
+            .chunks(buffer.area.width as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+    for state in [
+        AppState::MainMenu,
+        AppState::InGame,
+        AppState::LoadMenu,
+        AppState::CreateImage,
+        AppState::SettingsMenu,
+        AppState::InputApiKey,
+        AppState::InputWebhookUrl,
+        AppState::InputDataDir,
+        AppState::InputSaveName,
+        AppState::ChooseArchetype,
+        AppState::CharacterWizard,
+        AppState::Logs,
+        AppState::Macros,
+    ] {
+        app.state = state;
+        let mut terminal = Terminal::new(TestBackend::new(30, 12)).unwrap();
+        terminal.draw(|f| ui::draw(f, &mut app)).unwrap();
+        let text = screen(&terminal);
+        assert!(text.contains("Please resize"), "{}", text);
+        assert!(text.contains("30×12"), "{}", text);
+
+        // Resized, the screen is drawn again.
+        terminal.backend_mut().resize(120, 60);
+        terminal.draw(|f| ui::draw(f, &mut app)).unwrap();
+        assert!(!screen(&terminal).contains("Please resize"));
+    }
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_modified_enter_sends_or_starts_a_new_line() {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use sharad_ratatui::ai::GameAI;
+    use sharad_ratatui::app::{App, AppCommand, InputMode};
+    use sharad_ratatui::backend::{MockBackend, MockTurn};
+    use sharad_ratatui::headless::wait_for_response;
+    use sharad_ratatui::message::MessageType;
+    use sharad_ratatui::{paths, settings::Settings};
+    use std::sync::Arc;
+    use tokio::sync::mpsc;
+
+    let _data_dir = DATA_DIR.lock().await;
+    let dir = std::env::temp_dir().join(format!("sharad_enter_keys_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("config")).unwrap();
+    Settings {
+        data_dir: Some(dir.join("data")),
+        audio_output_enabled: false,
+        ..Settings::default()
+    }
+    .save_to_file(dir.join("config").join("settings.json").to_str().unwrap())
+    .unwrap();
+    paths::init(Some(dir.join("config")), false);
+
+    let (ai_sender, mut ai_receiver) = mpsc::unbounded_channel();
+    let (mut app, mut commands) = App::new(ai_sender).await;
+    let backend = Arc::new(MockBackend::new([MockTurn::reply("The street is quiet.")]));
+    app.ai_client = Some(GameAI::with_backend(backend, |_| {}));
+    app.start_new_game("keys".to_string(), None).await.unwrap();
+    wait_for_response(&mut app, &mut commands, &mut ai_receiver).await;
+
+    let press = |app: &mut App, code, modifiers| app.handle_input(KeyEvent::new(code, modifiers));
+    app.input_mode = InputMode::Editing;
+    for c in "I knock".chars() {
+        press(&mut app, KeyCode::Char(c), KeyModifiers::NONE);
+    }
+    press(&mut app, KeyCode::Enter, KeyModifiers::SHIFT);
+    for c in "twice".chars() {
+        press(&mut app, KeyCode::Char(c), KeyModifiers::NONE);
+    }
+    assert_eq!(app.user_input.value(), "I knock\ntwice");
+
+    // Ctrl+Enter sends and keeps the input open for the next message.
+    press(&mut app, KeyCode::Enter, KeyModifiers::CONTROL);
+    let sent = loop {
+        match commands.try_recv() {
+            Ok(AppCommand::ProcessMessage(message)) => break Some(message),
+            Ok(_) => continue,
+            Err(_) => break None,
+        }
+    };
+    assert_eq!(sent.as_deref(), Some("I knock\ntwice"));
+    assert!(app.input_mode == InputMode::Editing);
+    assert!(app.user_input.value().is_empty());
+    assert!(app
+        .game_content
+        .borrow()
+        .iter()
+        .any(|message| message.message_type == MessageType::User
+            && message.content == "I knock\ntwice"));
+
+    // A plain Enter still only leaves the input.
+    press(&mut app, KeyCode::Char('x'), KeyModifiers::NONE);
+    press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+    assert!(app.input_mode == InputMode::Normal);
+    assert_eq!(app.user_input.value(), "x");
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_the_submit_key_and_enter_while_typing_can_be_changed() {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use sharad_ratatui::ai::GameAI;
+    use sharad_ratatui::app::{App, AppCommand, InputMode};
+    use sharad_ratatui::backend::{MockBackend, MockTurn};
+    use sharad_ratatui::headless::wait_for_response;
+    use sharad_ratatui::paths;
+    use sharad_ratatui::settings::{settings_path, InputStyle, Settings, SubmitKey};
+    use std::sync::Arc;
+    use tokio::sync::mpsc;
+
+    let _data_dir = DATA_DIR.lock().await;
+    let dir = std::env::temp_dir().join(format!("sharad_submit_key_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("config")).unwrap();
+    Settings {
+        data_dir: Some(dir.join("data")),
+        audio_output_enabled: false,
+        ..Settings::default()
+    }
+    .save_to_file(dir.join("config").join("settings.json").to_str().unwrap())
+    .unwrap();
+    paths::init(Some(dir.join("config")), false);
+
+    let (ai_sender, mut ai_receiver) = mpsc::unbounded_channel();
+    let (mut app, mut commands) = App::new(ai_sender).await;
+    let backend = Arc::new(MockBackend::new([MockTurn::reply("The street is quiet.")]));
+    app.ai_client = Some(GameAI::with_backend(backend, |_| {}));
+    app.start_new_game("submit".to_string(), None)
+        .await
+        .unwrap();
+    wait_for_response(&mut app, &mut commands, &mut ai_receiver).await;
+    app.settings.input_style = InputStyle::Vim;
+    app.settings.submit_key = SubmitKey::CtrlS;
+    app.settings.enter_new_line = true;
+
+    let press = |app: &mut App, code, modifiers| app.handle_input(KeyEvent::new(code, modifiers));
+    let sent = |commands: &mut mpsc::UnboundedReceiver<AppCommand>| loop {
+        match commands.try_recv() {
+            Ok(AppCommand::ProcessMessage(message)) => break Some(message),
+            Ok(_) => continue,
+            Err(_) => break None,
+        }
+    };
+    let type_text = |app: &mut App, text: &str| {
+        for c in text.chars() {
+            app.handle_input(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+    };
+
+    // Enter starts a new line in the input, Esc leaves it, and Enter no longer sends.
+    app.input_mode = InputMode::Editing;
+    type_text(&mut app, "I wait");
+    press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+    type_text(&mut app, "and listen");
+    press(&mut app, KeyCode::Esc, KeyModifiers::NONE);
+    press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+    assert_eq!(sent(&mut commands), None);
+    assert_eq!(app.user_input.value(), "I wait\nand listen");
+
+    // Ctrl+S sends, from Normal mode as from the input.
+    press(&mut app, KeyCode::Char('s'), KeyModifiers::CONTROL);
+    assert_eq!(sent(&mut commands).as_deref(), Some("I wait\nand listen"));
+    app.input_mode = InputMode::Editing;
+    type_text(&mut app, "I leave");
+    press(&mut app, KeyCode::Char('s'), KeyModifiers::CONTROL);
+    assert_eq!(sent(&mut commands).as_deref(), Some("I leave"));
+    assert!(app.input_mode == InputMode::Editing);
+
+    // In the Simple input mode too, Enter is a new line and Ctrl+S sends.
+    app.settings.input_style = InputStyle::Simple;
+    type_text(&mut app, "I run");
+    press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+    type_text(&mut app, "fast");
+    assert_eq!(sent(&mut commands), None);
+    press(&mut app, KeyCode::Char('s'), KeyModifiers::CONTROL);
+    assert_eq!(sent(&mut commands).as_deref(), Some("I run\nfast"));
+    // The settings are saved with the game, for the tests that follow.
+    app.settings = Settings {
+        data_dir: app.settings.data_dir.clone(),
+        audio_output_enabled: false,
+        ..Settings::default()
+    };
+    app.settings.save_to_file(&settings_path()).unwrap();
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_the_simple_input_mode_is_always_typing() {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use sharad_ratatui::ai::GameAI;
+    use sharad_ratatui::app::{App, AppCommand, InputMode};
+    use sharad_ratatui::app_state::AppState;
+    use sharad_ratatui::backend::{MockBackend, MockTurn};
+    use sharad_ratatui::headless::wait_for_response;
+    use sharad_ratatui::paths;
+    use sharad_ratatui::settings::{settings_path, InputStyle, Settings};
+    use std::sync::Arc;
+    use tokio::sync::mpsc;
+
+    let _data_dir = DATA_DIR.lock().await;
+    let dir = std::env::temp_dir().join(format!("sharad_simple_input_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("config")).unwrap();
+    Settings {
+        data_dir: Some(dir.join("data")),
+        audio_output_enabled: false,
+        ..Settings::default()
+    }
+    .save_to_file(dir.join("config").join("settings.json").to_str().unwrap())
+    .unwrap();
+    paths::init(Some(dir.join("config")), false);
+
+    let (ai_sender, mut ai_receiver) = mpsc::unbounded_channel();
+    let (mut app, mut commands) = App::new(ai_sender).await;
+    app.settings.input_style = InputStyle::Simple;
+    let press = |app: &mut App, code, modifiers| app.handle_input(KeyEvent::new(code, modifiers));
+
+    // The settings inputs are typing too, and Esc leaves them.
+    for state in [AppState::InputApiKey, AppState::InputWebhookUrl] {
+        app.state = state.clone();
+        for c in "sk-re".chars() {
+            press(&mut app, KeyCode::Char(c), KeyModifiers::NONE);
+        }
+        assert!(app.input_mode == InputMode::Editing);
+        press(&mut app, KeyCode::Esc, KeyModifiers::NONE);
+        assert!(app.state == AppState::SettingsMenu);
+        assert!(app.input_mode == InputMode::Normal);
+    }
+    assert_eq!(app.api_key_input.value(), "sk-re");
+    assert_eq!(app.webhook_url_input.value(), "sk-re");
+
+    // A new data folder is kept in the settings and used after a restart, which it says.
+    let data_dir = app.settings.data_dir.clone();
+    app.state = AppState::InputDataDir;
+    for c in "/tmp/runs".chars() {
+        press(&mut app, KeyCode::Char(c), KeyModifiers::NONE);
+    }
+    press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+    assert!(app.state == AppState::SettingsMenu);
+    assert_eq!(
+        app.settings.data_dir.as_deref(),
+        Some(std::path::Path::new("/tmp/runs"))
+    );
+    assert!(paths::data_dir_changed(app.settings.data_dir.as_deref()));
+    assert!(app
+        .toasts
+        .iter()
+        .any(|toast| toast.text.contains("Restart")));
+    // Applying the settings took the input mode from the menu, where it is still Vim.
+    app.settings.data_dir = data_dir;
+    app.settings.input_style = InputStyle::Simple;
+
+    // The save name takes 'e' and 'r' as letters, and Enter confirms it.
+    app.state = AppState::InputSaveName;
+    for c in "river".chars() {
+        press(&mut app, KeyCode::Char(c), KeyModifiers::NONE);
+    }
+    assert!(app.input_mode == InputMode::Editing);
+    assert_eq!(app.save_name_input.value(), "river");
+    press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+    assert!(app.state == AppState::ChooseArchetype);
+
+    let backend = Arc::new(MockBackend::new([MockTurn::reply("The street is quiet.")]));
+    app.ai_client = Some(GameAI::with_backend(backend, |_| {}));
+    app.start_new_game("simple".to_string(), None)
+        .await
+        .unwrap();
+    wait_for_response(&mut app, &mut commands, &mut ai_receiver).await;
+    app.on_tick();
+    assert!(app.input_mode == InputMode::Editing);
+
+    // In the game, Alt+Enter starts a new line and Enter sends at once.
+    for c in "I run".chars() {
+        press(&mut app, KeyCode::Char(c), KeyModifiers::NONE);
+    }
+    press(&mut app, KeyCode::Enter, KeyModifiers::ALT);
+    for c in "fast".chars() {
+        press(&mut app, KeyCode::Char(c), KeyModifiers::NONE);
+    }
+    press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+    let sent = loop {
+        match commands.try_recv() {
+            Ok(AppCommand::ProcessMessage(message)) => break Some(message),
+            Ok(_) => continue,
+            Err(_) => break None,
+        }
+    };
+    assert_eq!(sent.as_deref(), Some("I run\nfast"));
+    assert!(app.input_mode == InputMode::Editing);
+    assert!(app.user_input.value().is_empty());
+
+    // Esc leaves the game.
+    press(&mut app, KeyCode::Esc, KeyModifiers::NONE);
+    assert!(app.state == AppState::MainMenu);
+    assert!(app.input_mode == InputMode::Normal);
+    // The settings are saved with the game, for the tests that follow.
+    app.settings.input_style = InputStyle::Vim;
+    app.settings.save_to_file(&settings_path()).unwrap();
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_pasted_text_goes_into_the_input_being_typed_in() {
+    use sharad_ratatui::app::{App, AppCommand, InputMode};
+    use sharad_ratatui::app_state::AppState;
+    use sharad_ratatui::{paths, settings::Settings};
+    use tokio::sync::mpsc;
+
+    let _data_dir = DATA_DIR.lock().await;
+    let dir = std::env::temp_dir().join(format!("sharad_paste_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("config")).unwrap();
+    Settings {
+        data_dir: Some(dir.join("data")),
+        audio_output_enabled: false,
+        ..Settings::default()
+    }
+    .save_to_file(dir.join("config").join("settings.json").to_str().unwrap())
+    .unwrap();
+    paths::init(Some(dir.join("config")), false);
+
+    let (ai_sender, _ai_receiver) = mpsc::unbounded_channel();
+    let (mut app, mut commands) = App::new(ai_sender).await;
+
+    // The player's message keeps its lines, and nothing is sent.
+    app.state = AppState::InGame;
+    app.input_mode = InputMode::Editing;
+    app.paste("I read the note:\r\nMeet at the docks.\n");
+    assert_eq!(
+        app.user_input.value(),
+        "I read the note:\nMeet at the docks."
+    );
+    assert!(!std::iter::from_fn(|| commands.try_recv().ok())
+        .any(|command| matches!(command, AppCommand::ProcessMessage(_))));
+
+    // Outside the input, a paste isn't taken for key commands.
+    app.user_input.reset();
+    app.input_mode = InputMode::Normal;
+    app.paste("xnq");
+    assert!(app.state == AppState::InGame);
+    assert!(app.user_input.value().is_empty());
+
+    // One-line inputs take it on one line.
+    app.state = AppState::InputApiKey;
+    app.paste("  sk-test-key\n");
+    assert_eq!(app.api_key_input.value(), "sk-test-key");
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_selected_text_follows_the_drawn_lines() {
+    use ratatui::text::Line;
+    use sharad_ratatui::selection::{selected_text, Point, Selection};
+
+    // Drawn 20 wide: "  Hello there" loses its leading spaces, "Go now" is centered from column 7.
+    let lines = vec![Line::raw("  Hello there"), Line::raw("Go now").centered()];
+    let selection = Selection {
+        anchor: Point {
+            line: 11,
+            column: 9,
+        },
+        head: Point {
+            line: 10,
+            column: 6,
+        },
+    };
+    assert_eq!(selected_text(&selection, &lines, 10, 20), "there\nGo");
+    // A click selects nothing.
+    assert!(Selection::new(Point {
+        line: 10,
+        column: 3
+    })
+    .is_empty());
+}
+
+#[tokio::test]
+async fn test_dragging_over_the_transcript_copies_the_text() {
+    use crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+    use ratatui::{backend::TestBackend, Terminal};
+    use sharad_ratatui::ai::GameAI;
+    use sharad_ratatui::app::App;
+    use sharad_ratatui::backend::{MockBackend, MockTurn};
+    use sharad_ratatui::clipboard::Clipboard;
+    use sharad_ratatui::headless::wait_for_response;
+    use sharad_ratatui::{paths, settings::Settings, ui};
+    use std::sync::Arc;
+    use tokio::sync::mpsc;
+
+    let _data_dir = DATA_DIR.lock().await;
+    let dir = std::env::temp_dir().join(format!("sharad_mouse_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("config")).unwrap();
+    Settings {
+        data_dir: Some(dir.join("data")),
+        audio_output_enabled: false,
+        ..Settings::default()
+    }
+    .save_to_file(dir.join("config").join("settings.json").to_str().unwrap())
+    .unwrap();
+    paths::init(Some(dir.join("config")), false);
+
+    let (ai_sender, mut ai_receiver) = mpsc::unbounded_channel();
+    let (mut app, mut commands) = App::new(ai_sender).await;
+    app.clipboard = Clipboard::internal();
+    let backend = Arc::new(MockBackend::new([MockTurn::reply(
+        "The alley smells of rain.",
+    )]));
+    app.ai_client = Some(GameAI::with_backend(backend, |_| {}));
+    app.start_new_game("mouse".to_string(), None).await.unwrap();
+    wait_for_response(&mut app, &mut commands, &mut ai_receiver).await;
+
+    // The first frame settles the scroll.
+    let mut terminal = Terminal::new(TestBackend::new(120, 60)).unwrap();
+    terminal.draw(|f| ui::draw(f, &mut app)).unwrap();
+    terminal.draw(|f| ui::draw(f, &mut app)).unwrap();
+    // Where the answer is on screen.
+    let buffer = terminal.backend().buffer().clone();
+    let (column, row) = (0..buffer.area.height)
+        .find_map(|y| {
+            let row: String = (0..buffer.area.width)
+                .map(|x| buffer[(x, y)].symbol().to_string())
+                .collect();
+            let at = row.find("alley smells")?;
+            Some((row[..at].chars().count() as u16, y))
+        })
+        .expect("the answer is drawn");
+
+    let mouse = |kind, column| MouseEvent {
+        kind,
+        column,
+        row,
+        modifiers: KeyModifiers::NONE,
+    };
+    app.handle_mouse(mouse(MouseEventKind::Down(MouseButton::Left), column));
+    app.handle_mouse(mouse(MouseEventKind::Drag(MouseButton::Left), column + 11));
+    app.handle_mouse(mouse(MouseEventKind::Up(MouseButton::Left), column + 11));
+    assert_eq!(app.clipboard.get().as_deref(), Some("alley smells"));
+
+    // The selection is shown in reverse video.
+    terminal.draw(|f| ui::draw(f, &mut app)).unwrap();
+    let cell = &terminal.backend().buffer()[(column, row)];
+    assert!(cell.modifier.contains(ratatui::style::Modifier::REVERSED));
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_every_setting_is_shown_on_the_smallest_settings_screen() {
+    use ratatui::{backend::TestBackend, Terminal};
+    use sharad_ratatui::app::App;
+    use sharad_ratatui::app_state::AppState;
+    use sharad_ratatui::settings_state::SETTINGS_ITEMS;
+    use sharad_ratatui::ui::utils::{MIN_HEIGHT, MIN_WIDTH};
+    use sharad_ratatui::{paths, settings::Settings, ui};
+    use tokio::sync::mpsc;
+
+    let _data_dir = DATA_DIR.lock().await;
+    let dir = std::env::temp_dir().join(format!("sharad_settings_screen_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("config")).unwrap();
+    Settings {
+        data_dir: Some(dir.join("data")),
+        audio_output_enabled: false,
+        ..Settings::default()
+    }
+    .save_to_file(dir.join("config").join("settings.json").to_str().unwrap())
+    .unwrap();
+    paths::init(Some(dir.join("config")), false);
+
+    let (ai_sender, _ai_receiver) = mpsc::unbounded_channel();
+    let (mut app, _commands) = App::new(ai_sender).await;
+    app.state = AppState::SettingsMenu;
+    let mut terminal = Terminal::new(TestBackend::new(MIN_WIDTH + 1, MIN_HEIGHT)).unwrap();
+    for (number, (label, _)) in SETTINGS_ITEMS.iter().enumerate() {
+        app.settings_state.selected_setting = number;
+        terminal.draw(|f| ui::draw(f, &mut app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let screen: String = (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let row = format!("{}. {}", number + 1, label);
+        assert!(screen.contains(&row), "{:?} is not shown:\n{}", row, screen);
+    }
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_copies_work_without_a_system_clipboard() {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use sharad_ratatui::ai::GameAI;
+    use sharad_ratatui::app::{App, InputMode};
+    use sharad_ratatui::backend::{MockBackend, MockTurn};
+    use sharad_ratatui::clipboard::{Clipboard, NO_SYSTEM_CLIPBOARD};
+    use sharad_ratatui::headless::wait_for_response;
+    use sharad_ratatui::{paths, settings::Settings};
+    use std::sync::Arc;
+    use tokio::sync::mpsc;
+    use tui_input::Input;
+
+    let _data_dir = DATA_DIR.lock().await;
+    let dir = std::env::temp_dir().join(format!("sharad_clipboard_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("config")).unwrap();
+    Settings {
+        data_dir: Some(dir.join("data")),
+        audio_output_enabled: false,
+        ..Settings::default()
+    }
+    .save_to_file(dir.join("config").join("settings.json").to_str().unwrap())
+    .unwrap();
+    paths::init(Some(dir.join("config")), false);
+
+    let (ai_sender, mut ai_receiver) = mpsc::unbounded_channel();
+    let (mut app, mut commands) = App::new(ai_sender).await;
+    app.clipboard = Clipboard::internal();
+    let backend = Arc::new(MockBackend::new([MockTurn::reply(
+        "The alley smells of rain.",
+    )]));
+    app.ai_client = Some(GameAI::with_backend(backend, |_| {}));
+    app.start_new_game("clipboard".to_string(), None)
+        .await
+        .unwrap();
+    wait_for_response(&mut app, &mut commands, &mut ai_receiver).await;
+
+    for _ in 0..2 {
+        app.user_input = Input::new("/copy".to_string());
+        app.handle_input(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    }
+    // Warned once, not on every copy.
+    let warnings = app
+        .game_content
+        .borrow()
+        .iter()
+        .filter(|message| message.content == NO_SYSTEM_CLIPBOARD)
+        .count();
+    assert_eq!(warnings, 1);
+
+    // Ctrl+V pastes what was copied.
+    app.input_mode = InputMode::Editing;
+    app.handle_input(KeyEvent::new(KeyCode::Char('v'), KeyModifiers::CONTROL));
+    assert!(app.user_input.value().contains("The alley smells of rain."));
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_copies_can_be_sent_to_the_terminal() {
+    use sharad_ratatui::app::App;
+    use sharad_ratatui::clipboard::osc52;
+    use sharad_ratatui::paths;
+    use sharad_ratatui::settings::{Settings, TerminalCopy};
+    use sharad_ratatui::settings_state::{SettingsState, TERMINAL_COPY};
+    use tokio::sync::mpsc;
+
+    assert_eq!(osc52("run"), "\x1b]52;c;cnVu\x07");
+
+    let _data_dir = DATA_DIR.lock().await;
+    let dir = std::env::temp_dir().join(format!("sharad_osc52_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("config")).unwrap();
+    paths::init(Some(dir.join("config")), false);
+
+    let (ai_sender, _ai_receiver) = mpsc::unbounded_channel();
+    let (mut app, _commands) = App::new(ai_sender).await;
+    app.settings_state.selected_options[TERMINAL_COPY] = 1;
+    app.apply_settings();
+    assert_eq!(app.settings.terminal_copy, TerminalCopy::Always);
+    assert_eq!(app.clipboard.terminal_copy, TerminalCopy::Always);
+    let settings =
+        Settings::load_settings_from_file(&sharad_ratatui::settings::settings_path()).unwrap();
+    assert_eq!(
+        SettingsState::from_settings(&settings).selected_options[TERMINAL_COPY],
+        1
+    );
+
+    app.settings_state.selected_options[TERMINAL_COPY] = 0;
+    app.apply_settings();
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_frames_slow_down_while_nothing_moves() {
+    use sharad_ratatui::app::App;
+    use sharad_ratatui::paths;
+    use sharad_ratatui::settings::IDLE_FRAME_RATE;
+    use sharad_ratatui::settings_state::{FRAME_RATE, TICK_RATE};
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+
+    let _data_dir = DATA_DIR.lock().await;
+    let dir = std::env::temp_dir().join(format!("sharad_frames_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("config")).unwrap();
+    paths::init(Some(dir.join("config")), false);
+
+    let (ai_sender, _ai_receiver) = mpsc::unbounded_channel();
+    let (mut app, _commands) = App::new(ai_sender).await;
+    app.settings_state.selected_options[FRAME_RATE] = 1;
+    app.settings_state.selected_options[TICK_RATE] = 2;
+    app.apply_settings();
+    assert_eq!(app.frame_interval(), Duration::from_secs(1) / 30);
+    assert_eq!(app.tick_interval(), Duration::from_secs(1) / 4);
+
+    // Idle, a few frames a second; with the spinner going, every frame.
+    app.settings.debug_mode = false;
+    assert!(!app.animating());
+    assert_eq!(
+        app.redraw_interval(),
+        Duration::from_secs(1) / IDLE_FRAME_RATE
+    );
+    app.start_spinner();
+    assert!(app.animating());
+    assert_eq!(app.redraw_interval(), app.frame_interval());
+
+    app.settings_state.selected_options[FRAME_RATE] = 0;
+    app.settings_state.selected_options[TICK_RATE] = 1;
+    app.apply_settings();
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_background_tasks_are_listed_and_can_be_cancelled() {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use sharad_ratatui::app::App;
+    use sharad_ratatui::app_state::AppState;
+    use sharad_ratatui::paths;
+    use sharad_ratatui::tasks::{TaskKind, TaskStatus};
+    use tokio::sync::mpsc;
+
+    let _data_dir = DATA_DIR.lock().await;
+    let dir = std::env::temp_dir().join(format!("sharad_tasks_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("config")).unwrap();
+    paths::init(Some(dir.join("config")), false);
+
+    let (ai_sender, _ai_receiver) = mpsc::unbounded_channel();
+    let (mut app, _commands) = App::new(ai_sender).await;
+    app.tasks
+        .spawn(TaskKind::Image, "a neon alley", async { Ok(None) });
+    app.tasks
+        .spawn(TaskKind::Transcription, "Voice input", async {
+            Err("timed out".to_string())
+        });
+    // Never answers, like a request that hangs.
+    app.tasks
+        .spawn(TaskKind::Speech, "3 lines", std::future::pending());
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    let statuses: Vec<TaskStatus> = app
+        .tasks
+        .list()
+        .into_iter()
+        .map(|task| task.status)
+        .collect();
+    assert_eq!(
+        statuses,
+        [
+            TaskStatus::Done,
+            TaskStatus::Failed("timed out".to_string()),
+            TaskStatus::Running
+        ]
+    );
+    assert_eq!(app.tasks.running(), 1);
+
+    // 't' opens the panel on the latest task, and 'c' cancels it.
+    app.state = AppState::InGame;
+    let press = |app: &mut App, code| app.handle_input(KeyEvent::new(code, KeyModifiers::NONE));
+    press(&mut app, KeyCode::Char('t'));
+    assert_eq!(app.tasks_panel, Some(2));
+    press(&mut app, KeyCode::Char('c'));
+    assert_eq!(app.tasks.list()[2].status, TaskStatus::Cancelled);
+    assert_eq!(app.tasks.running(), 0);
+    // Ended tasks can't be cancelled again.
+    press(&mut app, KeyCode::Up);
+    press(&mut app, KeyCode::Char('c'));
+    assert!(matches!(app.tasks.list()[1].status, TaskStatus::Failed(_)));
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.tasks_panel, None);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_tasks_finished_away_from_the_game_leave_a_notice() {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use sharad_ratatui::app::{App, AppCommand};
+    use sharad_ratatui::app_state::AppState;
+    use sharad_ratatui::paths;
+    use sharad_ratatui::tasks::{Jump, TaskKind};
+    use tokio::sync::mpsc;
+
+    let _data_dir = DATA_DIR.lock().await;
+    let dir = std::env::temp_dir().join(format!("sharad_toasts_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("config")).unwrap();
+    paths::init(Some(dir.join("config")), false);
+
+    let (ai_sender, _ai_receiver) = mpsc::unbounded_channel();
+    let (mut app, mut commands) = App::new(ai_sender).await;
+
+    // In the game, the result shows there.
+    app.state = AppState::InGame;
+    app.tasks.spawn(TaskKind::Image, "a neon alley", async {
+        Ok(Some(Jump::Image("alley.png".into())))
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    app.on_tick();
+    assert!(app.toasts.is_empty());
+
+    // Back in the main menu, a narration that ends and a picture that fails are announced.
+    app.state = AppState::MainMenu;
+    app.tasks.spawn(TaskKind::Speech, "3 lines", async {
+        Ok(Some(Jump::Game("Neon Nights".to_string())))
+    });
+    app.tasks.spawn(TaskKind::Image, "a rooftop", async {
+        Err("timed out".to_string())
+    });
+    app.tasks
+        .spawn(TaskKind::Transcription, "Voice input", async { Ok(None) });
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    app.on_tick();
+    let texts: Vec<&str> = app.toasts.iter().map(|toast| toast.text.as_str()).collect();
+    assert_eq!(
+        texts,
+        ["Narration done: 3 lines", "Image failed: timed out"]
+    );
+    assert_eq!(app.followed_toast(), Some(0));
+
+    // F2 goes back to the game that was narrated.
+    while commands.try_recv().is_ok() {}
+    app.handle_input(KeyEvent::new(KeyCode::F(2), KeyModifiers::NONE));
+    assert!(matches!(
+        commands.try_recv(),
+        Ok(AppCommand::LoadGame(save_name)) if save_name == "Neon Nights"
+    ));
+    assert_eq!(app.toasts.len(), 1);
+    assert_eq!(app.followed_toast(), None);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_failed_turns_are_explained_and_can_be_retried() {
+    use sharad_ratatui::ai::{GameAI, MAX_REPAIR_ATTEMPTS};
+    use sharad_ratatui::app::{App, AppCommand};
+    use sharad_ratatui::backend::{MockBackend, MockTurn};
+    use sharad_ratatui::headless::wait_for_response;
+    use sharad_ratatui::message::MessageType;
+    use sharad_ratatui::{paths, settings::Settings};
+    use std::sync::Arc;
+    use tokio::sync::mpsc;
+
+    let _data_dir = DATA_DIR.lock().await;
+    let dir = std::env::temp_dir().join(format!("sharad_failed_turn_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("config")).unwrap();
+    Settings {
+        data_dir: Some(dir.join("data")),
+        audio_output_enabled: false,
+        ..Settings::default()
+    }
+    .save_to_file(dir.join("config").join("settings.json").to_str().unwrap())
+    .unwrap();
+    paths::init(Some(dir.join("config")), false);
+
+    let backend = Arc::new(MockBackend::new([MockTurn::reply("The rain never stops.")]));
+    let (ai_sender, mut ai_receiver) = mpsc::unbounded_channel();
+    let (mut app, mut commands) = App::new(ai_sender).await;
+    app.ai_client = Some(GameAI::with_backend(backend.clone(), |_| {}));
+    app.start_new_game("failed turn".to_string(), None)
+        .await
+        .unwrap();
+    wait_for_response(&mut app, &mut commands, &mut ai_receiver).await;
+
+    // An answer that can't be read ends the turn with a note saying so.
+    for _ in 0..=MAX_REPAIR_ATTEMPTS {
+        backend.queue(MockTurn::verbatim("not json"));
+    }
+    app.process_message("I pick the lock".to_string());
+    wait_for_response(&mut app, &mut commands, &mut ai_receiver).await;
+    assert!(!app.spinner_active);
+    let last = app.game_content.borrow().last().cloned().unwrap();
+    assert_eq!(last.message_type, MessageType::System);
+    assert!(
+        last.content.contains("could not be read"),
+        "{}",
+        last.content
+    );
+    assert!(last.content.contains("/retry"), "{}", last.content);
+
+    // /retry sends the same action again.
+    while commands.try_recv().is_ok() {}
+    backend.queue(MockTurn::reply("The lock clicks open."));
+    app.retry_failed_action();
+    let action = loop {
+        match commands.try_recv() {
+            Ok(AppCommand::ProcessMessage(action)) => break action,
+            Ok(_) => continue,
+            Err(e) => panic!("no retry sent: {}", e),
+        }
+    };
+    assert_eq!(action, "I pick the lock");
+    app.process_message(action);
+    wait_for_response(&mut app, &mut commands, &mut ai_receiver).await;
+    let last = app.game_content.borrow().last().cloned().unwrap();
+    assert_eq!(last.message_type, MessageType::Game);
+    assert!(last.content.contains("The lock clicks open."));
+    app.retry_failed_action();
+    let last = app.game_content.borrow().last().cloned().unwrap();
+    assert_eq!(last.content, "There is no failed action to send again.");
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_elevenlabs_voices_follow_the_speakers() {
+    use async_openai::types::Voice;
+    use sharad_ratatui::audio::{narration_clips, SpeechSettings, PLAYER_SPEAKER};
+    use sharad_ratatui::backend::narration;
+    use sharad_ratatui::elevenlabs::{voice_id, DEFAULT_VOICES};
+    use sharad_ratatui::settings::{Settings, SpeechBackend};
+    use sharad_ratatui::settings_state::{SettingsState, SPEECH_BACKEND};
+    use std::collections::HashMap;
+
+    // Each OpenAI voice stands for an ElevenLabs one, unless the settings give another, by
+    // speaker or by voice.
+    let adam = DEFAULT_VOICES
+        .iter()
+        .find(|(voice, _)| *voice == Voice::Onyx)
+        .unwrap()
+        .1;
+    let mut voices = HashMap::new();
+    assert_eq!(voice_id(&voices, "Fixer", &Voice::Onyx), adam);
+    voices.insert("nova".to_string(), "voice_for_nova".to_string());
+    voices.insert("Narrator".to_string(), "voice_for_narrator".to_string());
+    assert_eq!(voice_id(&voices, "Fixer", &Voice::Nova), "voice_for_nova");
+    assert_eq!(
+        voice_id(&voices, "Narrator", &Voice::Onyx),
+        "voice_for_narrator"
+    );
+
+    // OpenAI reads the narration unless ElevenLabs is chosen.
+    let settings = Settings {
+        elevenlabs_voices: voices,
+        player_voice: Some(Voice::Echo),
+        ..Settings::default()
+    };
+    assert_eq!(
+        SpeechSettings::from_settings(&settings).elevenlabs_voice("Narrator", &Voice::Onyx),
+        None
+    );
+    let settings = Settings {
+        speech_backend: SpeechBackend::ElevenLabs,
+        ..settings
+    };
+    let speech = SpeechSettings::from_settings(&settings);
+    let clips = narration_clips(
+        &narration("The door opens.".to_string()).fluff,
+        Some("I push the door."),
+        &speech,
+    );
+    let speakers: Vec<_> = clips
+        .iter()
+        .map(|clip| speech.elevenlabs_voice(&clip.speaker, &clip.voice))
+        .collect();
+    assert_eq!(clips[0].speaker, PLAYER_SPEAKER);
+    assert_eq!(speakers[1].as_deref(), Some("voice_for_narrator"));
+    assert_eq!(
+        SettingsState::from_settings(&settings).selected_options[SPEECH_BACKEND],
+        1
+    );
+}
+
+#[test]
+fn test_portraits_are_drawn_in_basic_colors_on_the_linux_console() {
+    use ::image::{DynamicImage, Rgb, RgbImage};
+    use ratatui::style::Color;
+    use sharad_ratatui::thumbnail::{basic_colors_only, half_block_art, nearest_basic_color};
+
+    assert!(basic_colors_only(Some("linux"), None));
+    assert!(!basic_colors_only(Some("linux"), Some("truecolor")));
+    assert!(!basic_colors_only(Some("xterm-256color"), None));
+
+    assert_eq!(nearest_basic_color(Rgb([250, 10, 10]), false), Color::Red);
+    assert_eq!(
+        nearest_basic_color(Rgb([255, 255, 255]), false),
+        Color::White
+    );
+    // Bright backgrounds don't exist there.
+    assert_eq!(nearest_basic_color(Rgb([255, 255, 255]), true), Color::Gray);
+
+    // Red above, blue below: each cell is the top pixel over the bottom one, scaled to fit.
+    let picture = RgbImage::from_fn(8, 8, |_, y| {
+        if y < 4 {
+            Rgb([200, 0, 0])
+        } else {
+            Rgb([0, 0, 200])
+        }
+    });
+    let art = half_block_art(&DynamicImage::ImageRgb8(picture), 4, 2);
+    assert_eq!(art.len(), 2);
+    assert_eq!(art[0].spans.len(), 4);
+    let cell = &art[0].spans[0];
+    assert_eq!(cell.content, "▀");
+    assert_eq!(cell.style.fg, Some(Color::Red));
+    assert_eq!(art[1].spans[0].style.bg, Some(Color::Blue));
+}